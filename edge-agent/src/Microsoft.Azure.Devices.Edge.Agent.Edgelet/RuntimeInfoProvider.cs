@@ -46,10 +46,7 @@ namespace Microsoft.Azure.Devices.Edge.Agent.Edgelet
             Option<DateTime> exitTime = exitStatus == null ? Option.None<DateTime>() : Option.Some(exitStatus.ExitTime);
             Option<DateTime> startTime = !moduleDetails.Status.StartTime.HasValue ? Option.None<DateTime>() : Option.Some(moduleDetails.Status.StartTime.Value);
 
-            if (!Enum.TryParse(moduleDetails.Status.RuntimeStatus.Status, true, out ModuleStatus status))
-            {
-                status = ModuleStatus.Unknown;
-            }
+            ModuleStatus status = ToModuleStatus(moduleDetails.Status.RuntimeStatus.Status, exitCode);
 
             if (!(moduleDetails.Config.Settings is JObject jobject))
             {
@@ -61,5 +58,25 @@ namespace Microsoft.Azure.Devices.Edge.Agent.Edgelet
                 moduleDetails.Status.RuntimeStatus.Description, exitCode, startTime, exitTime, config);
             return moduleRuntimeInfo;
         }
+
+        // The edge runtime reports some states that the agent doesn't track separately.
+        // Map those to the status the runtime used to report for them.
+        static ModuleStatus ToModuleStatus(string status, long exitCode)
+        {
+            switch (status?.ToLowerInvariant())
+            {
+                case "created":
+                case "paused":
+                case "restarting":
+                case "backoff":
+                    return ModuleStatus.Stopped;
+
+                case "dead":
+                    return exitCode == 0 ? ModuleStatus.Stopped : ModuleStatus.Failed;
+
+                default:
+                    return Enum.TryParse(status, true, out ModuleStatus moduleStatus) ? moduleStatus : ModuleStatus.Unknown;
+            }
+        }
     }
 }
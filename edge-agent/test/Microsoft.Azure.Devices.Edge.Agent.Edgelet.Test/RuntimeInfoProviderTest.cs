@@ -111,6 +111,48 @@ namespace Microsoft.Azure.Devices.Edge.Agent.Edgelet.Test
             Assert.Equal((runtimeInfo2 as ModuleRuntimeInfo<TestConfig>)?.Config.ImageHash, module2Hash);
         }
 
+        [Theory]
+        [InlineData("running", "0", ModuleStatus.Running)]
+        [InlineData("created", "0", ModuleStatus.Stopped)]
+        [InlineData("paused", "0", ModuleStatus.Stopped)]
+        [InlineData("restarting", "1", ModuleStatus.Stopped)]
+        [InlineData("backoff", "1", ModuleStatus.Stopped)]
+        [InlineData("dead", "0", ModuleStatus.Stopped)]
+        [InlineData("dead", "137", ModuleStatus.Failed)]
+        [InlineData("whatever", "0", ModuleStatus.Unknown)]
+        public async Task GetModulesMapsRuntimeStatusTest(string runtimeStatus, string exitCode, ModuleStatus expectedStatus)
+        {
+            // Arrange
+            var module = new ModuleDetails
+            {
+                Id = Guid.NewGuid().ToString(),
+                Name = "module1",
+                Status = new Status
+                {
+                    StartTime = new DateTime(2011, 02, 03, 04, 05, 06),
+                    RuntimeStatus = new RuntimeStatus { Status = runtimeStatus, Description = runtimeStatus },
+                    ExitStatus = new ExitStatus { ExitTime = new DateTime(2011, 02, 03, 05, 06, 07), StatusCode = exitCode }
+                },
+                Type = "docker",
+                Config = new Config
+                {
+                    Env = new ObservableCollection<EnvVar>(),
+                    Settings = JObject.FromObject(new TestConfig(Guid.NewGuid().ToString()))
+                }
+            };
+
+            var modules = new List<ModuleDetails> { module };
+            var moduleManager = Mock.Of<IModuleManager>(m => m.GetModules(It.IsAny<CancellationToken>()) == Task.FromResult(modules.AsEnumerable()));
+            IRuntimeInfoProvider runtimeInfoProvider = new RuntimeInfoProvider<TestConfig>(moduleManager);
+
+            // Act
+            List<ModuleRuntimeInfo> runtimeInfos = (await runtimeInfoProvider.GetModules(CancellationToken.None)).ToList();
+
+            // Assert
+            Assert.Single(runtimeInfos);
+            Assert.Equal(expectedStatus, runtimeInfos[0].ModuleStatus);
+        }
+
         class TestConfig
         {
             public TestConfig(string imageHash)
@@ -0,0 +1,33 @@
+// Copyright (c) Microsoft. All rights reserved.
+extern crate protoc_rust_grpc;
+
+use std::env;
+use std::fs;
+use std::path::Path;
+
+const PROTO: &str = "proto/api.proto";
+
+fn main() {
+    let out_dir = env::var("OUT_DIR").unwrap();
+
+    println!("cargo:rerun-if-changed={}", PROTO);
+    protoc_rust_grpc::run(protoc_rust_grpc::Args {
+        out_dir: &out_dir,
+        includes: &["proto"],
+        input: &[PROTO],
+        rust_protobuf: true,
+    }).expect("protoc failed to generate the CRI bindings, is protoc installed?");
+
+    // The generated files start with inner attributes, which are not
+    // allowed in files brought in with include!. The crate sets the same
+    // lints itself.
+    for name in &["api.rs", "api_grpc.rs"] {
+        let path = Path::new(&out_dir).join(name);
+        let source = fs::read_to_string(&path).unwrap();
+        let source: Vec<&str> = source
+            .lines()
+            .filter(|line| !line.starts_with("#!["))
+            .collect();
+        fs::write(&path, source.join("\n")).unwrap();
+    }
+}
@@ -0,0 +1,31 @@
+#![allow(
+    unused_imports,
+    non_camel_case_types,
+    non_snake_case,
+    non_upper_case_globals,
+    unused_mut,
+    dead_code,
+    unknown_lints,
+    trivial_casts,
+    unsafe_code,
+    unused_qualifications,
+    unused_results,
+    missing_docs
+)]
+#![allow(clippy, clippy_pedantic)]
+
+//! Bindings for the Container Runtime Interface, generated from
+//! `proto/api.proto` when the crate is built.
+
+extern crate futures;
+extern crate grpc;
+extern crate protobuf;
+extern crate tls_api;
+
+pub mod api {
+    include!(concat!(env!("OUT_DIR"), "/api.rs"));
+}
+
+pub mod api_grpc {
+    include!(concat!(env!("OUT_DIR"), "/api_grpc.rs"));
+}
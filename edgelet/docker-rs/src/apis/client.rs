@@ -8,6 +8,7 @@ use hyper;
 pub struct APIClient<C: hyper::client::Connect> {
     configuration: Rc<Configuration<C>>,
     container_api: Box<::apis::ContainerApi>,
+    exec_api: Box<::apis::ExecApi>,
     image_api: Box<::apis::ImageApi>,
     network_api: Box<::apis::NetworkApi>,
     system_api: Box<::apis::SystemApi>,
@@ -21,6 +22,7 @@ impl<C: hyper::client::Connect> APIClient<C> {
         APIClient {
             configuration: rc.clone(),
             container_api: Box::new(::apis::ContainerApiClient::new(rc.clone())),
+            exec_api: Box::new(::apis::ExecApiClient::new(rc.clone())),
             image_api: Box::new(::apis::ImageApiClient::new(rc.clone())),
             network_api: Box::new(::apis::NetworkApiClient::new(rc.clone())),
             system_api: Box::new(::apis::SystemApiClient::new(rc.clone())),
@@ -32,6 +34,10 @@ impl<C: hyper::client::Connect> APIClient<C> {
         self.container_api.as_ref()
     }
 
+    pub fn exec_api(&self) -> &::apis::ExecApi {
+        self.exec_api.as_ref()
+    }
+
     pub fn image_api(&self) -> &::apis::ImageApi {
         self.image_api.as_ref()
     }
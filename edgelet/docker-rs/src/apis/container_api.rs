@@ -38,7 +38,7 @@ pub trait ContainerApi {
         &self,
         id: &str,
         path: &str,
-    ) -> Box<Future<Item = (), Error = Error<serde_json::Value>>>;
+    ) -> Box<Future<Item = hyper::Body, Error = Error<serde_json::Value>>>;
     fn container_archive_info(
         &self,
         id: &str,
@@ -108,6 +108,7 @@ pub trait ContainerApi {
         stdout: bool,
         stderr: bool,
         since: i32,
+        until: i32,
         timestamps: bool,
         tail: &str,
     ) -> Box<Future<Item = hyper::Body, Error = Error<serde_json::Value>>>;
@@ -142,7 +143,7 @@ pub trait ContainerApi {
         &self,
         id: &str,
         stream: bool,
-    ) -> Box<Future<Item = serde_json::Value, Error = Error<serde_json::Value>>>;
+    ) -> Box<Future<Item = hyper::Body, Error = Error<serde_json::Value>>>;
     fn container_stop(
         &self,
         id: &str,
@@ -171,8 +172,8 @@ pub trait ContainerApi {
         &self,
         id: &str,
         path: &str,
-        input_stream: &str,
-        no_overwrite_dir_non_dir: &str,
+        input_stream: hyper::Body,
+        no_overwrite_dir_non_dir: bool,
     ) -> Box<Future<Item = (), Error = Error<serde_json::Value>>>;
 }
 
@@ -181,7 +182,7 @@ impl<C: hyper::client::Connect> ContainerApi for ContainerApiClient<C> {
         &self,
         id: &str,
         path: &str,
-    ) -> Box<Future<Item = (), Error = Error<serde_json::Value>>> {
+    ) -> Box<Future<Item = hyper::Body, Error = Error<serde_json::Value>>> {
         let configuration: &configuration::Configuration<C> = self.configuration.borrow();
 
         let method = hyper::Method::Get;
@@ -211,17 +212,18 @@ impl<C: hyper::client::Connect> ContainerApi for ContainerApiClient<C> {
                 .map_err(|e| Error::from(e))
                 .and_then(|resp| {
                     let status = resp.status();
-                    resp.body()
-                        .concat2()
-                        .and_then(move |body| Ok((status, body)))
-                        .map_err(|e| Error::from(e))
-                }).and_then(|(status, body)| {
                     if status.is_success() {
-                        Ok(body)
+                        futures::future::Either::A(futures::future::ok(resp.body()))
                     } else {
-                        Err(Error::from((status, &*body)))
+                        // the body of an error holds the message from docker
+                        futures::future::Either::B(
+                            resp.body()
+                                .concat2()
+                                .map_err(|e| Error::from(e))
+                                .and_then(move |body| Err(Error::from((status, &*body)))),
+                        )
                     }
-                }).and_then(|_| futures::future::ok(())),
+                }),
         )
     }
 
@@ -756,6 +758,7 @@ impl<C: hyper::client::Connect> ContainerApi for ContainerApiClient<C> {
         stdout: bool,
         stderr: bool,
         since: i32,
+        until: i32,
         timestamps: bool,
         tail: &str,
     ) -> Box<Future<Item = hyper::Body, Error = Error<serde_json::Value>>> {
@@ -768,6 +771,7 @@ impl<C: hyper::client::Connect> ContainerApi for ContainerApiClient<C> {
             .append_pair("stdout", &stdout.to_string())
             .append_pair("stderr", &stderr.to_string())
             .append_pair("since", &since.to_string())
+            .append_pair("until", &until.to_string())
             .append_pair("timestamps", &timestamps.to_string())
             .append_pair("tail", &tail.to_string())
             .finish();
@@ -794,10 +798,15 @@ impl<C: hyper::client::Connect> ContainerApi for ContainerApiClient<C> {
                 .and_then(|resp| {
                     let status = resp.status();
                     if status.is_success() {
-                        Ok(resp.body())
+                        futures::future::Either::A(futures::future::ok(resp.body()))
                     } else {
-                        let b: &[u8] = &[];
-                        Err(Error::from((status, b)))
+                        // the body of an error holds the message from docker
+                        futures::future::Either::B(
+                            resp.body()
+                                .concat2()
+                                .map_err(|e| Error::from(e))
+                                .and_then(move |body| Err(Error::from((status, &*body)))),
+                        )
                     }
                 }),
         )
@@ -1096,7 +1105,7 @@ impl<C: hyper::client::Connect> ContainerApi for ContainerApiClient<C> {
         &self,
         id: &str,
         stream: bool,
-    ) -> Box<Future<Item = serde_json::Value, Error = Error<serde_json::Value>>> {
+    ) -> Box<Future<Item = hyper::Body, Error = Error<serde_json::Value>>> {
         let configuration: &configuration::Configuration<C> = self.configuration.borrow();
 
         let method = hyper::Method::Get;
@@ -1126,19 +1135,17 @@ impl<C: hyper::client::Connect> ContainerApi for ContainerApiClient<C> {
                 .map_err(|e| Error::from(e))
                 .and_then(|resp| {
                     let status = resp.status();
-                    resp.body()
-                        .concat2()
-                        .and_then(move |body| Ok((status, body)))
-                        .map_err(|e| Error::from(e))
-                }).and_then(|(status, body)| {
                     if status.is_success() {
-                        Ok(body)
+                        futures::future::Either::A(futures::future::ok(resp.body()))
                     } else {
-                        Err(Error::from((status, &*body)))
+                        // the body of an error holds the message from docker
+                        futures::future::Either::B(
+                            resp.body()
+                                .concat2()
+                                .map_err(|e| Error::from(e))
+                                .and_then(move |body| Err(Error::from((status, &*body)))),
+                        )
                     }
-                }).and_then(|body| {
-                    let parsed: Result<serde_json::Value, _> = serde_json::from_slice(&body);
-                    parsed.map_err(|e| Error::from(e))
                 }),
         )
     }
@@ -1398,8 +1405,8 @@ impl<C: hyper::client::Connect> ContainerApi for ContainerApiClient<C> {
         &self,
         id: &str,
         path: &str,
-        input_stream: &str,
-        no_overwrite_dir_non_dir: &str,
+        input_stream: hyper::Body,
+        no_overwrite_dir_non_dir: bool,
     ) -> Box<Future<Item = (), Error = Error<serde_json::Value>>> {
         let configuration: &configuration::Configuration<C> = self.configuration.borrow();
 
@@ -1425,11 +1432,9 @@ impl<C: hyper::client::Connect> ContainerApi for ContainerApiClient<C> {
                 .set(UserAgent::new(Cow::Owned(user_agent.clone())));
         }
 
-        let serialized = serde_json::to_string(&input_stream).unwrap();
-        req.headers_mut().set(hyper::header::ContentType::json());
         req.headers_mut()
-            .set(hyper::header::ContentLength(serialized.len() as u64));
-        req.set_body(serialized);
+            .set(hyper::header::ContentType("application/x-tar".parse().unwrap()));
+        req.set_body(input_stream);
 
         // send request
         Box::new(
@@ -97,7 +97,7 @@ pub trait ImageApi {
     fn image_get(
         &self,
         name: &str,
-    ) -> Box<Future<Item = Vec<u8>, Error = Error<serde_json::Value>>>;
+    ) -> Box<Future<Item = hyper::Body, Error = Error<serde_json::Value>>>;
     fn image_get_all(
         &self,
         names: Vec<String>,
@@ -118,7 +118,7 @@ pub trait ImageApi {
     ) -> Box<Future<Item = Vec<::models::ImageSummary>, Error = Error<serde_json::Value>>>;
     fn image_load(
         &self,
-        images_tarball: Vec<u8>,
+        images_tarball: hyper::Body,
         quiet: bool,
     ) -> Box<Future<Item = (), Error = Error<serde_json::Value>>>;
     fn image_prune(
@@ -493,7 +493,7 @@ impl<C: hyper::client::Connect> ImageApi for ImageApiClient<C> {
     fn image_get(
         &self,
         name: &str,
-    ) -> Box<Future<Item = Vec<u8>, Error = Error<serde_json::Value>>> {
+    ) -> Box<Future<Item = hyper::Body, Error = Error<serde_json::Value>>> {
         let configuration: &configuration::Configuration<C> = self.configuration.borrow();
 
         let method = hyper::Method::Get;
@@ -520,19 +520,17 @@ impl<C: hyper::client::Connect> ImageApi for ImageApiClient<C> {
                 .map_err(|e| Error::from(e))
                 .and_then(|resp| {
                     let status = resp.status();
-                    resp.body()
-                        .concat2()
-                        .and_then(move |body| Ok((status, body)))
-                        .map_err(|e| Error::from(e))
-                }).and_then(|(status, body)| {
                     if status.is_success() {
-                        Ok(body)
+                        futures::future::Either::A(futures::future::ok(resp.body()))
                     } else {
-                        Err(Error::from((status, &*body)))
+                        // the body of an error holds the message from docker
+                        futures::future::Either::B(
+                            resp.body()
+                                .concat2()
+                                .map_err(|e| Error::from(e))
+                                .and_then(move |body| Err(Error::from((status, &*body)))),
+                        )
                     }
-                }).and_then(|body| {
-                    let parsed: Result<Vec<u8>, _> = serde_json::from_slice(&body);
-                    parsed.map_err(|e| Error::from(e))
                 }),
         )
     }
@@ -740,7 +738,7 @@ impl<C: hyper::client::Connect> ImageApi for ImageApiClient<C> {
 
     fn image_load(
         &self,
-        images_tarball: Vec<u8>,
+        images_tarball: hyper::Body,
         quiet: bool,
     ) -> Box<Future<Item = (), Error = Error<serde_json::Value>>> {
         let configuration: &configuration::Configuration<C> = self.configuration.borrow();
@@ -764,11 +762,9 @@ impl<C: hyper::client::Connect> ImageApi for ImageApiClient<C> {
                 .set(UserAgent::new(Cow::Owned(user_agent.clone())));
         }
 
-        let serialized = serde_json::to_string(&images_tarball).unwrap();
-        req.headers_mut().set(hyper::header::ContentType::json());
         req.headers_mut()
-            .set(hyper::header::ContentLength(serialized.len() as u64));
-        req.set_body(serialized);
+            .set(hyper::header::ContentType("application/x-tar".parse().unwrap()));
+        req.set_body(images_tarball);
 
         // send request
         Box::new(
@@ -46,7 +46,7 @@ pub trait SystemApi {
         since: &str,
         until: &str,
         filters: &str,
-    ) -> Box<Future<Item = ::models::InlineResponse20012, Error = Error<serde_json::Value>>>;
+    ) -> Box<Future<Item = hyper::Body, Error = Error<serde_json::Value>>>;
     fn system_info(
         &self,
     ) -> Box<Future<Item = ::models::SystemInfo, Error = Error<serde_json::Value>>>;
@@ -163,7 +163,7 @@ impl<C: hyper::client::Connect> SystemApi for SystemApiClient<C> {
         since: &str,
         until: &str,
         filters: &str,
-    ) -> Box<Future<Item = ::models::InlineResponse20012, Error = Error<serde_json::Value>>> {
+    ) -> Box<Future<Item = hyper::Body, Error = Error<serde_json::Value>>> {
         let configuration: &configuration::Configuration<C> = self.configuration.borrow();
 
         let method = hyper::Method::Get;
@@ -195,20 +195,17 @@ impl<C: hyper::client::Connect> SystemApi for SystemApiClient<C> {
                 .map_err(|e| Error::from(e))
                 .and_then(|resp| {
                     let status = resp.status();
-                    resp.body()
-                        .concat2()
-                        .and_then(move |body| Ok((status, body)))
-                        .map_err(|e| Error::from(e))
-                }).and_then(|(status, body)| {
                     if status.is_success() {
-                        Ok(body)
+                        futures::future::Either::A(futures::future::ok(resp.body()))
                     } else {
-                        Err(Error::from((status, &*body)))
+                        // the body of an error holds the message from docker
+                        futures::future::Either::B(
+                            resp.body()
+                                .concat2()
+                                .map_err(|e| Error::from(e))
+                                .and_then(move |body| Err(Error::from((status, &*body)))),
+                        )
                     }
-                }).and_then(|body| {
-                    let parsed: Result<::models::InlineResponse20012, _> =
-                        serde_json::from_slice(&body);
-                    parsed.map_err(|e| Error::from(e))
                 }),
         )
     }
@@ -58,6 +58,8 @@ pub struct InlineResponse200State {
         skip_serializing_if = "Option::is_none"
     )]
     finished_at: Option<String>,
+    #[serde(rename = "Health", skip_serializing_if = "Option::is_none")]
+    health: Option<::models::Health>,
 }
 
 impl InlineResponse200State {
@@ -75,6 +77,7 @@ impl InlineResponse200State {
             error: None,
             started_at: None,
             finished_at: None,
+            health: None,
         }
     }
 
@@ -264,4 +267,21 @@ impl InlineResponse200State {
     pub fn reset_finished_at(&mut self) {
         self.finished_at = None;
     }
+
+    pub fn set_health(&mut self, health: ::models::Health) {
+        self.health = Some(health);
+    }
+
+    pub fn with_health(mut self, health: ::models::Health) -> InlineResponse200State {
+        self.health = Some(health);
+        self
+    }
+
+    pub fn health(&self) -> Option<&::models::Health> {
+        self.health.as_ref()
+    }
+
+    pub fn reset_health(&mut self) {
+        self.health = None;
+    }
 }
@@ -76,6 +76,8 @@ mod generic_resources_inner_named_resource_spec;
 pub use self::generic_resources_inner_named_resource_spec::GenericResourcesInnerNamedResourceSpec;
 mod graph_driver_data;
 pub use self::graph_driver_data::GraphDriverData;
+mod health;
+pub use self::health::Health;
 mod health_config;
 pub use self::health_config::HealthConfig;
 mod host_config_log_config;
@@ -0,0 +1,336 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+use std::path::{Component, Path, PathBuf};
+
+/// Stands for every capability in `CapAdd` and in capability rules.
+const ALL_CAPABILITIES: &str = "ALL";
+
+/// Values a create option may take. A value is allowed when it is not in
+/// `deny` and, if `allow` is set, when it is in `allow`.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct RuleList {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    allow: Option<Vec<String>>,
+    #[serde(default)]
+    deny: Vec<String>,
+}
+
+impl RuleList {
+    pub fn new(allow: Option<Vec<String>>, deny: Vec<String>) -> Self {
+        RuleList { allow, deny }
+    }
+
+    pub fn allow(&self) -> Option<&[String]> {
+        self.allow.as_ref().map(|allow| allow.as_slice())
+    }
+
+    pub fn deny(&self) -> &[String] {
+        &self.deny
+    }
+
+    /// Compares values for equality.
+    pub fn permits(&self, value: &str) -> bool {
+        self.check(|rule| rule == value)
+    }
+
+    /// Compares capabilities, where "ALL" stands for every capability. A
+    /// rule of "ALL" matches any capability, and "ALL" itself is only
+    /// permitted when no capability is denied and every one is allowed.
+    pub fn permits_capability(&self, capability: &str) -> bool {
+        let all = |value: &str| value.eq_ignore_ascii_case(ALL_CAPABILITIES);
+        if all(capability) {
+            self.deny.is_empty()
+                && self
+                    .allow
+                    .as_ref()
+                    .map_or(true, |allow| allow.iter().any(|rule| all(rule)))
+        } else {
+            self.check(|rule| all(rule) || rule == capability)
+        }
+    }
+
+    /// Treats the rules as path prefixes, so "/var/lib" covers
+    /// "/var/lib/module" but not "/var/library". Paths are compared after
+    /// resolving ".." so that they cannot climb out of an allowed prefix.
+    pub fn permits_path(&self, path: &str) -> bool {
+        let path = normalize(path);
+        self.check(|rule| path.starts_with(normalize(rule)))
+    }
+
+    fn check<F>(&self, matches: F) -> bool
+    where
+        F: Fn(&str) -> bool,
+    {
+        !self.deny.iter().any(|rule| matches(rule))
+            && self
+                .allow
+                .as_ref()
+                .map_or(true, |allow| allow.iter().any(|rule| matches(rule)))
+    }
+}
+
+// Resolves "." and ".." without looking at the file system, the paths are
+// those of the host the container engine runs on.
+fn normalize(path: &str) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in Path::new(path).components() {
+        match component {
+            Component::CurDir => (),
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            component => normalized.push(component.as_os_str()),
+        }
+    }
+    normalized
+}
+
+/// Limits the create options of modules. The defaults allow everything, so
+/// deployments behave as they do without a policy.
+///
+/// Memory and CPU ceilings also reject modules that do not set a limit,
+/// since those can use all of the memory and CPU of the device.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+pub struct AdmissionSettings {
+    allow_privileged: bool,
+    allow_host_pid: bool,
+    allow_host_ipc: bool,
+    allow_host_userns: bool,
+    capabilities: RuleList,
+    bind_paths: RuleList,
+    devices: RuleList,
+    network_modes: RuleList,
+    security_options: RuleList,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_memory_bytes: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_nano_cpus: Option<u64>,
+}
+
+impl Default for AdmissionSettings {
+    fn default() -> Self {
+        AdmissionSettings {
+            allow_privileged: true,
+            allow_host_pid: true,
+            allow_host_ipc: true,
+            allow_host_userns: true,
+            capabilities: RuleList::default(),
+            bind_paths: RuleList::default(),
+            devices: RuleList::default(),
+            network_modes: RuleList::default(),
+            security_options: RuleList::default(),
+            max_memory_bytes: None,
+            max_nano_cpus: None,
+        }
+    }
+}
+
+impl AdmissionSettings {
+    pub fn new() -> Self {
+        AdmissionSettings::default()
+    }
+
+    pub fn allow_privileged(&self) -> bool {
+        self.allow_privileged
+    }
+
+    pub fn with_allow_privileged(mut self, allow_privileged: bool) -> Self {
+        self.allow_privileged = allow_privileged;
+        self
+    }
+
+    pub fn allow_host_pid(&self) -> bool {
+        self.allow_host_pid
+    }
+
+    pub fn with_allow_host_pid(mut self, allow_host_pid: bool) -> Self {
+        self.allow_host_pid = allow_host_pid;
+        self
+    }
+
+    pub fn allow_host_ipc(&self) -> bool {
+        self.allow_host_ipc
+    }
+
+    pub fn with_allow_host_ipc(mut self, allow_host_ipc: bool) -> Self {
+        self.allow_host_ipc = allow_host_ipc;
+        self
+    }
+
+    /// Whether modules may opt out of user namespace remapping with a
+    /// `UsernsMode` of "host".
+    pub fn allow_host_userns(&self) -> bool {
+        self.allow_host_userns
+    }
+
+    pub fn with_allow_host_userns(mut self, allow_host_userns: bool) -> Self {
+        self.allow_host_userns = allow_host_userns;
+        self
+    }
+
+    /// Capabilities in `CapAdd`, without the "CAP_" prefix.
+    pub fn capabilities(&self) -> &RuleList {
+        &self.capabilities
+    }
+
+    pub fn with_capabilities(mut self, capabilities: RuleList) -> Self {
+        self.capabilities = capabilities;
+        self
+    }
+
+    /// Host paths of bind mounts.
+    pub fn bind_paths(&self) -> &RuleList {
+        &self.bind_paths
+    }
+
+    pub fn with_bind_paths(mut self, bind_paths: RuleList) -> Self {
+        self.bind_paths = bind_paths;
+        self
+    }
+
+    /// Host paths of devices.
+    pub fn devices(&self) -> &RuleList {
+        &self.devices
+    }
+
+    pub fn with_devices(mut self, devices: RuleList) -> Self {
+        self.devices = devices;
+        self
+    }
+
+    /// Values of `NetworkMode`, with "default" for modules that do not set
+    /// one.
+    pub fn network_modes(&self) -> &RuleList {
+        &self.network_modes
+    }
+
+    pub fn with_network_modes(mut self, network_modes: RuleList) -> Self {
+        self.network_modes = network_modes;
+        self
+    }
+
+    /// Values of `SecurityOpt`, such as "seccomp=unconfined", written with
+    /// "=" rather than the older ":".
+    pub fn security_options(&self) -> &RuleList {
+        &self.security_options
+    }
+
+    pub fn with_security_options(mut self, security_options: RuleList) -> Self {
+        self.security_options = security_options;
+        self
+    }
+
+    pub fn max_memory_bytes(&self) -> Option<u64> {
+        self.max_memory_bytes
+    }
+
+    pub fn with_max_memory_bytes(mut self, max_memory_bytes: Option<u64>) -> Self {
+        self.max_memory_bytes = max_memory_bytes;
+        self
+    }
+
+    pub fn max_nano_cpus(&self) -> Option<u64> {
+        self.max_nano_cpus
+    }
+
+    pub fn with_max_nano_cpus(mut self, max_nano_cpus: Option<u64>) -> Self {
+        self.max_nano_cpus = max_nano_cpus;
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use serde_json;
+
+    fn rules(allow: Option<&[&str]>, deny: &[&str]) -> RuleList {
+        let strings = |values: &[&str]| values.iter().map(|v| v.to_string()).collect();
+        RuleList::new(allow.map(strings), strings(deny))
+    }
+
+    #[test]
+    fn defaults_allow_everything() {
+        let settings: AdmissionSettings = serde_json::from_str("{}").unwrap();
+        assert_eq!(AdmissionSettings::default(), settings);
+        assert!(settings.allow_privileged());
+        assert!(settings.allow_host_pid());
+        assert!(settings.allow_host_ipc());
+        assert!(settings.allow_host_userns());
+        assert!(settings.capabilities().permits("SYS_ADMIN"));
+        assert!(settings.capabilities().permits_capability("ALL"));
+        assert!(settings.bind_paths().permits_path("/"));
+        assert_eq!(None, settings.max_memory_bytes());
+    }
+
+    #[test]
+    fn deny_wins_over_allow() {
+        let list = rules(Some(&["NET_ADMIN", "SYS_TIME"]), &["SYS_TIME"]);
+        assert!(list.permits("NET_ADMIN"));
+        assert!(!list.permits("SYS_TIME"));
+        assert!(!list.permits("SYS_ADMIN"));
+    }
+
+    #[test]
+    fn paths_match_whole_components() {
+        let list = rules(Some(&["/var/lib/modules"]), &["/var/lib/modules/secrets"]);
+        assert!(list.permits_path("/var/lib/modules"));
+        assert!(list.permits_path("/var/lib/modules/data"));
+        assert!(!list.permits_path("/var/lib/modules-other"));
+        assert!(!list.permits_path("/var/lib/modules/secrets/key"));
+        assert!(!list.permits_path("/etc"));
+    }
+
+    #[test]
+    fn paths_cannot_climb_out_of_rules() {
+        let list = rules(Some(&["/var/lib/modules"]), &["/etc"]);
+        assert!(list.permits_path("/var/lib/modules/./data"));
+        assert!(list.permits_path("/var/lib/modules/data/../cache"));
+        assert!(!list.permits_path("/var/lib/modules/../../../etc"));
+        assert!(!list.permits_path("/var/lib/modules/.."));
+
+        let list = rules(None, &["/etc"]);
+        assert!(!list.permits_path("/tmp/../etc/shadow"));
+        assert!(!list.permits_path("/../etc"));
+    }
+
+    #[test]
+    fn all_matches_every_capability() {
+        let list = rules(Some(&["NET_ADMIN"]), &[]);
+        assert!(list.permits_capability("NET_ADMIN"));
+        assert!(!list.permits_capability("ALL"));
+
+        let list = rules(None, &["SYS_ADMIN"]);
+        assert!(!list.permits_capability("ALL"));
+
+        let list = rules(None, &["ALL"]);
+        assert!(!list.permits_capability("NET_ADMIN"));
+
+        let list = rules(Some(&["ALL"]), &[]);
+        assert!(list.permits_capability("ALL"));
+        assert!(list.permits_capability("SYS_ADMIN"));
+    }
+
+    #[test]
+    fn deserialize_settings() {
+        let settings: AdmissionSettings = serde_json::from_str(
+            r#"{
+                "allow_privileged": false,
+                "capabilities": { "deny": ["SYS_ADMIN"] },
+                "network_modes": { "allow": ["azure-iot-edge"] },
+                "max_memory_bytes": 268435456
+            }"#,
+        ).unwrap();
+        assert!(!settings.allow_privileged());
+        assert!(settings.allow_host_pid());
+        assert_eq!(&rules(None, &["SYS_ADMIN"]), settings.capabilities());
+        assert_eq!(
+            &rules(Some(&["azure-iot-edge"]), &[]),
+            settings.network_modes()
+        );
+        assert_eq!(Some(268_435_456), settings.max_memory_bytes());
+        assert_eq!(None, settings.max_nano_cpus());
+    }
+}
@@ -1,22 +1,161 @@
 // Copyright (c) Microsoft. All rights reserved.
 
-use error::Error;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use error::{Error, ErrorKind};
 use futures::future::Either;
 use futures::{future, Future};
 use module::{Module, ModuleRuntime};
-use pid::Pid;
+use module_index::{ModuleIdentity, ModuleIndex};
+use pid::{self, Pid};
+
+/// Who may call an API route. A caller is authorized when any of the
+/// settings allows it, so the default policy authorizes no one.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct Policy {
+    /// Any caller, including callers that cannot be identified.
+    #[serde(default)]
+    anonymous: bool,
+    /// The module named by the route, e.g. the module whose keys are used.
+    #[serde(default)]
+    caller: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    modules: Vec<String>,
+    /// Callers running on the host rather than inside a module, e.g. an
+    /// operator using the `iotedge` tool. Callers whose pid is unknown, or
+    /// that are not in the pid namespace of the daemon, are rejected.
+    #[serde(default)]
+    host: bool,
+    /// Callers running on the host whose user is in one of these groups,
+    /// given by name or id.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    host_groups: Vec<String>,
+}
+
+impl Policy {
+    pub fn anonymous() -> Self {
+        Policy {
+            anonymous: true,
+            ..Policy::default()
+        }
+    }
+
+    pub fn caller() -> Self {
+        Policy {
+            caller: true,
+            ..Policy::default()
+        }
+    }
+
+    pub fn module(name: &str) -> Self {
+        Policy {
+            modules: vec![name.to_string()],
+            ..Policy::default()
+        }
+    }
+
+    pub fn host() -> Self {
+        Policy {
+            host: true,
+            ..Policy::default()
+        }
+    }
+
+    pub fn is_anonymous(&self) -> bool {
+        self.anonymous
+    }
+
+    pub fn is_caller(&self) -> bool {
+        self.caller
+    }
+
+    pub fn modules(&self) -> &[String] {
+        &self.modules
+    }
+
+    pub fn with_modules(mut self, modules: Vec<String>) -> Self {
+        self.modules = modules;
+        self
+    }
 
-pub enum Policy {
-    Anonymous,
-    Caller,
-    Module(&'static str),
+    pub fn is_host(&self) -> bool {
+        self.host
+    }
+
+    pub fn host_groups(&self) -> &[String] {
+        &self.host_groups
+    }
+
+    pub fn with_host_groups(mut self, host_groups: Vec<String>) -> Self {
+        self.host_groups = host_groups;
+        self
+    }
+}
+
+/// Policies that replace the defaults of the routes of an API, keyed by the
+/// method and path of the route, e.g. "POST /modules/{name}/stop".
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct RoutePolicies(HashMap<String, Policy>);
+
+impl RoutePolicies {
+    pub fn new(policies: HashMap<String, Policy>) -> Self {
+        RoutePolicies(policies)
+    }
+
+    /// The policy of a route, or `default` when none is configured.
+    pub fn policy(&self, route: &str, default: Policy) -> Policy {
+        self.0
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(route))
+            .map_or(default, |(_, policy)| policy.clone())
+    }
+
+    /// Fails for a policy of a route that is not one of `routes`, so that a
+    /// typo in the settings does not fall back to the default policy.
+    pub fn check_routes(&self, routes: &[&str]) -> Result<(), Error> {
+        match self
+            .0
+            .keys()
+            .find(|key| !routes.iter().any(|route| key.eq_ignore_ascii_case(route)))
+        {
+            Some(key) => Err(Error::from(ErrorKind::UnknownRoute(key.clone()))),
+            None => Ok(()),
+        }
+    }
+}
+
+/// The authorization policy document of the management and workload APIs.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct AuthorizationSettings {
+    #[serde(default)]
+    management: RoutePolicies,
+    #[serde(default)]
+    workload: RoutePolicies,
+}
+
+impl AuthorizationSettings {
+    pub fn new(management: RoutePolicies, workload: RoutePolicies) -> Self {
+        AuthorizationSettings {
+            management,
+            workload,
+        }
+    }
+
+    pub fn management(&self) -> &RoutePolicies {
+        &self.management
+    }
+
+    pub fn workload(&self) -> &RoutePolicies {
+        &self.workload
+    }
 }
 
 pub struct Authorization<M>
 where
     M: 'static + ModuleRuntime,
 {
-    runtime: M,
+    index: Arc<ModuleIndex<M>>,
     policy: Policy,
 }
 
@@ -26,21 +165,86 @@ where
     M::Error: Into<Error>,
     <M::Module as Module>::Error: Into<Error>,
 {
-    pub fn new(runtime: M, policy: Policy) -> Self {
-        Authorization { runtime, policy }
+    /// Authorizes callers against the modules in `index`, which is meant to
+    /// be shared by every route.
+    pub fn new(index: Arc<ModuleIndex<M>>, policy: Policy) -> Self {
+        Authorization { index, policy }
     }
 
+    /// Authorizes a caller by the container its process runs in, which
+    /// identifies every process of a module. Callers outside of the
+    /// containers of modules are identified by their pid.
     pub fn authorize(
         &self,
         name: Option<String>,
         pid: Pid,
     ) -> impl Future<Item = bool, Error = Error> {
+        if self.policy.anonymous {
+            return Either::A(self.auth_anonymous());
+        }
+        Either::B(self.authorize_process(name, Caller::new(pid)))
+    }
+
+    fn authorize_process(
+        &self,
+        name: Option<String>,
+        caller: Caller,
+    ) -> impl Future<Item = bool, Error = Error> {
+        if self.policy.anonymous {
+            return Either::A(self.auth_anonymous());
+        }
+
         let name = name.map(|n| n.trim_left_matches('$').to_string());
-        match self.policy {
-            Policy::Anonymous => Either::A(Either::A(self.auth_anonymous())),
-            Policy::Caller => Either::A(Either::B(self.auth_caller(name, pid))),
-            Policy::Module(ref expected_name) => Either::B(self.auth_module(expected_name, pid)),
+        let mut checks: Vec<Box<Future<Item = bool, Error = Error>>> = Vec::new();
+        if self.policy.caller {
+            checks.push(Box::new(self.auth_caller(name, caller.clone())));
         }
+        for module in &self.policy.modules {
+            checks.push(Box::new(self.auth_module(module, caller.clone())));
+        }
+        if self.policy.host || !self.policy.host_groups.is_empty() {
+            checks.push(Box::new(self.auth_host(caller)));
+        }
+        Either::B(future::join_all(checks).map(|checks| checks.into_iter().any(|check| check)))
+    }
+
+    /// Authorizes a caller that was identified as `module` by its client
+    /// certificate rather than by its pid. Certificates that name no module
+    /// in the runtime are rejected, so callers over TLS are never the host.
+    pub fn authorize_identity(
+        &self,
+        name: Option<String>,
+        module: &str,
+    ) -> impl Future<Item = bool, Error = Error> {
+        if self.policy.anonymous {
+            return Either::A(self.auth_anonymous());
+        }
+
+        let name = name.map(|n| n.trim_left_matches('$').to_string());
+        let module = module.trim_left_matches('$').to_string();
+        let allowed = (self.policy.caller && name.map_or(false, |name| name == module))
+            || self.policy.modules.iter().any(|m| *m == module);
+        if !allowed {
+            info!(
+                "Request not authorized - client certificate belongs to module {}",
+                module
+            );
+            return Either::A(future::ok(false));
+        }
+
+        let is_deployed = {
+            let module = module.clone();
+            move |modules: &[ModuleIdentity]| modules.iter().any(|m| m.name() == module)
+        };
+        Either::B(self.index.matches(is_deployed).map(move |is_deployed| {
+            if !is_deployed {
+                info!(
+                    "Request not authorized - client certificate names {}, which is not a module",
+                    module
+                );
+            }
+            is_deployed
+        }))
     }
 
     fn auth_anonymous(&self) -> impl Future<Item = bool, Error = Error> {
@@ -50,33 +254,32 @@ where
     fn auth_caller(
         &self,
         name: Option<String>,
-        pid: Pid,
+        caller: Caller,
     ) -> impl Future<Item = bool, Error = Error> {
         name.map_or_else(
             || Either::A(future::ok(false)),
             |name| {
-                Either::B(
-                    self.runtime
-                        .list()
-                        .map_err(|e| e.into())
-                        .and_then(move |list| {
-                            list.iter()
-                                .filter_map(|m| if m.name() == name { Some(m) } else { None })
-                                .nth(0)
-                                .map(|m| {
-                                    Either::A(m.runtime_state().map_err(|e| e.into()).and_then(
-                                        move |rs| {
-                                            let authorized = rs.pid() == &pid;
-                                            if !authorized {
-                                                info!("Request not authorized - expected caller pid: {}, actual caller pid: {}", rs.pid(), pid);
-                                            }
-                                            Ok(authorized)
-                                        },
-                                    ))
-                                })
-                                .unwrap_or_else(|| Either::B(future::ok(false)))
-                        }),
-                )
+                let pid = caller.pid.clone();
+                let is_caller = {
+                    let name = name.clone();
+                    move |modules: &[ModuleIdentity]| {
+                        is_caller(
+                            modules,
+                            &name,
+                            &caller.pid,
+                            caller.container.as_ref().map(AsRef::as_ref),
+                        )
+                    }
+                };
+                Either::B(self.index.matches(is_caller).map(move |authorized| {
+                    if !authorized {
+                        info!(
+                            "Request not authorized - caller pid {} does not belong to module {}",
+                            pid, name
+                        );
+                    }
+                    authorized
+                }))
             },
         )
     }
@@ -84,32 +287,131 @@ where
     fn auth_module(
         &self,
         expected_name: &str,
-        pid: Pid,
+        caller: Caller,
     ) -> impl Future<Item = bool, Error = Error> {
-        self.auth_caller(Some(expected_name.to_string()), pid)
+        self.auth_caller(Some(expected_name.to_string()), caller)
+    }
+
+    fn auth_host(&self, caller: Caller) -> impl Future<Item = bool, Error = Error> {
+        // any host caller is allowed unless only some host groups are
+        let groups = if self.policy.host {
+            Vec::new()
+        } else {
+            self.policy.host_groups.clone()
+        };
+        let pid = caller.pid.clone();
+        if let Pid::Value(_) = pid {
+            // processes in other pid namespaces, e.g. those a module forked,
+            // are never the host
+            if !caller.host_namespace {
+                info!(
+                    "Request not authorized - caller pid {} is not in the pid namespace of the host",
+                    pid
+                );
+                return Either::B(future::ok(false));
+            }
+
+            let is_module = move |modules: &[ModuleIdentity]| {
+                is_module(
+                    modules,
+                    &caller.pid,
+                    caller.container.as_ref().map(AsRef::as_ref),
+                )
+            };
+            Either::A(self.index.matches(is_module).map(move |is_module| {
+                if is_module {
+                    info!(
+                        "Request not authorized - caller pid {} belongs to a module",
+                        pid
+                    );
+                    false
+                } else if groups.is_empty() {
+                    true
+                } else {
+                    let member = pid::in_groups(&pid, &groups);
+                    if !member {
+                        info!(
+                            "Request not authorized - caller pid {} is not in the host groups",
+                            pid
+                        );
+                    }
+                    member
+                }
+            }))
+        } else {
+            info!("Request not authorized - caller pid {} is not known", pid);
+            Either::B(future::ok(false))
+        }
+    }
+}
+
+/// What is known about the process of a caller.
+#[derive(Clone, Debug)]
+struct Caller {
+    pid: Pid,
+    container: Option<String>,
+    host_namespace: bool,
+}
+
+impl Caller {
+    fn new(pid: Pid) -> Self {
+        Caller {
+            container: pid::container_id(&pid),
+            host_namespace: pid::in_host_namespace(&pid),
+            pid,
+        }
+    }
+}
+
+fn module_in_container<'a>(
+    modules: &'a [ModuleIdentity],
+    container: Option<&str>,
+) -> Option<&'a ModuleIdentity> {
+    container.and_then(|container| modules.iter().find(|m| m.container_id() == Some(container)))
+}
+
+// Callers in the container of a module are that module. Others are only a
+// module when they are its main process.
+fn is_caller(modules: &[ModuleIdentity], name: &str, pid: &Pid, container: Option<&str>) -> bool {
+    match module_in_container(modules, container) {
+        Some(module) => module.name() == name,
+        None => modules.iter().any(|m| m.name() == name && m.pid() == pid),
     }
 }
 
+fn is_module(modules: &[ModuleIdentity], pid: &Pid, container: Option<&str>) -> bool {
+    module_in_container(modules, container).is_some() || modules.iter().any(|m| m.pid() == pid)
+}
+
 #[cfg(test)]
 mod tests {
 
+    use std::cell::Cell;
+    use std::rc::Rc;
     use std::time::Duration;
+    use std::vec::IntoIter;
 
     use super::*;
     use error::{Error, ErrorKind};
     use failure::Context;
     use futures::future;
     use futures::future::FutureResult;
-    use futures::stream::Empty;
+    use futures::stream::{self, IterOk};
     use module::{
-        LogOptions, Module, ModuleRegistry, ModuleRuntimeState, ModuleSpec,
-        SystemInfo as CoreSystemInfo,
+        ArchiveStream, EventOptions, ExecOptions, Image, LogOptions, Module, ModuleAction,
+        ModuleEvent, ModuleRegistry, ModuleRuntimeState, ModuleSpec, ModuleStats, PruneOptions,
+        PruneResult, StatsOptions, SystemInfo as CoreSystemInfo, Volume,
     };
+    use serde_json;
+
+    fn index(runtime: TestModuleList) -> Arc<ModuleIndex<TestModuleList>> {
+        Arc::new(ModuleIndex::new(runtime))
+    }
 
     #[test]
     fn should_authorize_anonymous() {
         let runtime = TestModuleList::new(&vec![]);
-        let auth = Authorization::new(runtime, Policy::Anonymous);
+        let auth = Authorization::new(index(runtime), Policy::anonymous());
         assert_eq!(true, auth.authorize(None, Pid::None).wait().unwrap());
     }
 
@@ -119,7 +421,7 @@ mod tests {
             TestModule::new("xyz", 987),
             TestModule::new("abc", 123),
         ]);
-        let auth = Authorization::new(runtime, Policy::Caller);
+        let auth = Authorization::new(index(runtime), Policy::caller());
         assert_eq!(
             true,
             auth.authorize(Some("abc".to_string()), Pid::Value(123))
@@ -134,7 +436,7 @@ mod tests {
             TestModule::new("xyz", 987),
             TestModule::new("edgeAgent", 123),
         ]);
-        let auth = Authorization::new(runtime, Policy::Caller);
+        let auth = Authorization::new(index(runtime), Policy::caller());
         assert_eq!(
             true,
             auth.authorize(Some("$edgeAgent".to_string()), Pid::Value(123))
@@ -146,14 +448,14 @@ mod tests {
     #[test]
     fn should_reject_caller_without_name() {
         let runtime = TestModuleList::new(&vec![TestModule::new("abc", 123)]);
-        let auth = Authorization::new(runtime, Policy::Caller);
+        let auth = Authorization::new(index(runtime), Policy::caller());
         assert_eq!(false, auth.authorize(None, Pid::Value(123)).wait().unwrap());
     }
 
     #[test]
     fn should_reject_caller_with_different_name() {
         let runtime = TestModuleList::new(&vec![TestModule::new("abc", 123)]);
-        let auth = Authorization::new(runtime, Policy::Caller);
+        let auth = Authorization::new(index(runtime), Policy::caller());
         assert_eq!(
             false,
             auth.authorize(Some("xyz".to_string()), Pid::Value(123))
@@ -165,7 +467,7 @@ mod tests {
     #[test]
     fn should_reject_caller_with_different_pid() {
         let runtime = TestModuleList::new(&vec![TestModule::new("abc", 123)]);
-        let auth = Authorization::new(runtime, Policy::Caller);
+        let auth = Authorization::new(index(runtime), Policy::caller());
         assert_eq!(
             false,
             auth.authorize(Some("abc".to_string()), Pid::Value(456))
@@ -180,21 +482,21 @@ mod tests {
             TestModule::new("xyz", 987),
             TestModule::new("abc", 123),
         ]);
-        let auth = Authorization::new(runtime, Policy::Module("abc"));
+        let auth = Authorization::new(index(runtime), Policy::module("abc"));
         assert_eq!(true, auth.authorize(None, Pid::Value(123)).wait().unwrap());
     }
 
     #[test]
     fn should_reject_module_whose_name_does_not_match_policy() {
         let runtime = TestModuleList::new(&vec![TestModule::new("xyz", 123)]);
-        let auth = Authorization::new(runtime, Policy::Module("abc"));
+        let auth = Authorization::new(index(runtime), Policy::module("abc"));
         assert_eq!(false, auth.authorize(None, Pid::Value(123)).wait().unwrap());
     }
 
     #[test]
     fn should_reject_module_with_different_pid() {
         let runtime = TestModuleList::new(&vec![TestModule::new("abc", 123)]);
-        let auth = Authorization::new(runtime, Policy::Module("abc"));
+        let auth = Authorization::new(index(runtime), Policy::module("abc"));
         assert_eq!(false, auth.authorize(None, Pid::Value(456)).wait().unwrap());
     }
 
@@ -205,7 +507,7 @@ mod tests {
             123,
             TestModuleBehavior::NoPid,
         )]);
-        let auth = Authorization::new(runtime, Policy::Caller);
+        let auth = Authorization::new(index(runtime), Policy::caller());
         assert_eq!(
             false,
             auth.authorize(Some("abc".to_string()), Pid::Value(123))
@@ -222,7 +524,7 @@ mod tests {
             123,
             TestModuleBehavior::FailRuntimeState,
         )]);
-        let auth = Authorization::new(runtime, Policy::Caller);
+        let auth = Authorization::new(index(runtime), Policy::caller());
         auth.authorize(Some("abc".to_string()), Pid::Value(123))
             .wait()
             .unwrap();
@@ -235,12 +537,362 @@ mod tests {
             &vec![TestModule::new("abc", 123)],
             TestModuleListBehavior::FailList,
         );
-        let auth = Authorization::new(runtime, Policy::Caller);
+        let auth = Authorization::new(index(runtime), Policy::caller());
         auth.authorize(Some("abc".to_string()), Pid::Value(123))
             .wait()
             .unwrap();
     }
 
+    #[test]
+    fn should_authorize_host() {
+        let runtime = TestModuleList::new(&vec![
+            TestModule::new("xyz", 987),
+            TestModule::new("abc", 123),
+        ]);
+        let auth = Authorization::new(index(runtime), Policy::host());
+        assert_eq!(
+            true,
+            auth.authorize_process(None, host_caller(456))
+                .wait()
+                .unwrap()
+        );
+    }
+
+    // e.g. an operator running 'iotedge exec' or 'iotedge cp', on any platform
+    #[test]
+    fn should_authorize_own_process_as_host() {
+        let runtime = TestModuleList::new(&vec![TestModule::new("abc", 123)]);
+        let auth = Authorization::new(index(runtime), Policy::host());
+        let own = Pid::Value(::std::process::id() as i32);
+        assert_eq!(true, auth.authorize(None, own).wait().unwrap());
+    }
+
+    #[test]
+    fn should_reject_host_outside_of_host_pid_namespace() {
+        let runtime = TestModuleList::new(&vec![TestModule::new("abc", 123)]);
+        let auth = Authorization::new(index(runtime), Policy::host());
+        assert_eq!(
+            false,
+            auth.authorize_process(None, module_caller(456, None))
+                .wait()
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn should_reject_host_when_caller_is_module() {
+        let runtime = TestModuleList::new(&vec![
+            TestModule::new("xyz", 987),
+            TestModule::new("abc", 123),
+        ]);
+        let auth = Authorization::new(index(runtime), Policy::host());
+        assert_eq!(
+            false,
+            auth.authorize_process(Some("xyz".to_string()), host_caller(123))
+                .wait()
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn should_reject_host_without_pid() {
+        let runtime = TestModuleList::new(&vec![TestModule::new("abc", 123)]);
+        let auth = Authorization::new(index(runtime), Policy::host());
+        assert_eq!(false, auth.authorize(None, Pid::None).wait().unwrap());
+        assert_eq!(false, auth.authorize(None, Pid::Any).wait().unwrap());
+    }
+
+    #[test]
+    fn should_reject_everyone_by_default() {
+        let runtime = TestModuleList::new(&vec![TestModule::new("abc", 123)]);
+        let auth = Authorization::new(index(runtime), Policy::default());
+        assert_eq!(
+            false,
+            auth.authorize(Some("abc".to_string()), Pid::Value(123))
+                .wait()
+                .unwrap()
+        );
+        assert_eq!(false, auth.authorize(None, Pid::Value(456)).wait().unwrap());
+    }
+
+    #[test]
+    fn should_authorize_any_allowed_caller() {
+        let runtime = TestModuleList::new(&vec![
+            TestModule::new("xyz", 987),
+            TestModule::new("abc", 123),
+        ]);
+        let policy = Policy::caller().with_modules(vec!["xyz".to_string()]);
+        let auth = Authorization::new(index(runtime), policy);
+        assert_eq!(
+            true,
+            auth.authorize(Some("abc".to_string()), Pid::Value(123))
+                .wait()
+                .unwrap()
+        );
+        assert_eq!(
+            true,
+            auth.authorize(Some("abc".to_string()), Pid::Value(987))
+                .wait()
+                .unwrap()
+        );
+        assert_eq!(
+            false,
+            auth.authorize(Some("abc".to_string()), Pid::Value(456))
+                .wait()
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn should_reject_host_outside_of_host_groups() {
+        let runtime = TestModuleList::new(&vec![TestModule::new("abc", 123)]);
+        let policy = Policy::default().with_host_groups(vec!["no-such-group".to_string()]);
+        let auth = Authorization::new(index(runtime), policy);
+        assert_eq!(
+            false,
+            auth.authorize_process(None, host_caller(456))
+                .wait()
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn route_policies_override_defaults() {
+        let policies: RoutePolicies = serde_json::from_str(
+            r#"{
+                "POST /modules/{name}/stop": { "modules": ["edgeAgent"], "host_groups": ["iotedge"] }
+            }"#,
+        ).unwrap();
+        assert_eq!(
+            Policy::module("edgeAgent").with_host_groups(vec!["iotedge".to_string()]),
+            policies.policy("post /modules/{name}/stop", Policy::anonymous())
+        );
+        assert_eq!(
+            Policy::anonymous(),
+            policies.policy("POST /modules/{name}/start", Policy::anonymous())
+        );
+    }
+
+    #[test]
+    fn unknown_route_policies_are_rejected() {
+        let policies: RoutePolicies =
+            serde_json::from_str(r#"{ "POST /modules/{name}/stpo": { "host": true } }"#).unwrap();
+        assert!(policies
+            .check_routes(&["POST /modules/{name}/stop", "POST /modules/{name}/start"])
+            .is_err());
+        assert!(policies
+            .check_routes(&["post /modules/{name}/stpo"])
+            .is_ok());
+    }
+
+    #[test]
+    fn should_authorize_caller_identity() {
+        let runtime = TestModuleList::new(&vec![TestModule::new("abc", 123)]);
+        let auth = Authorization::new(index(runtime), Policy::caller());
+        assert_eq!(
+            true,
+            auth.authorize_identity(Some("$abc".to_string()), "abc")
+                .wait()
+                .unwrap()
+        );
+        assert_eq!(
+            false,
+            auth.authorize_identity(Some("abc".to_string()), "xyz")
+                .wait()
+                .unwrap()
+        );
+        assert_eq!(false, auth.authorize_identity(None, "abc").wait().unwrap());
+    }
+
+    #[test]
+    fn should_authorize_module_identity() {
+        let runtime = TestModuleList::new(&vec![TestModule::new("abc", 123)]);
+        let auth = Authorization::new(index(runtime), Policy::module("abc"));
+        assert_eq!(true, auth.authorize_identity(None, "abc").wait().unwrap());
+        assert_eq!(false, auth.authorize_identity(None, "xyz").wait().unwrap());
+    }
+
+    #[test]
+    fn should_reject_identity_that_is_not_a_module() {
+        let runtime = TestModuleList::new(&vec![TestModule::new("abc", 123)]);
+        let auth = Authorization::new(
+            runtime,
+            Policy::host().with_modules(vec!["operator".to_string()]),
+        );
+        assert_eq!(
+            false,
+            auth.authorize_identity(None, "operator").wait().unwrap()
+        );
+        assert_eq!(false, auth.authorize_identity(None, "abc").wait().unwrap());
+    }
+
+    fn module_caller(pid: i32, container: Option<&str>) -> Caller {
+        Caller {
+            pid: Pid::Value(pid),
+            container: container.map(ToString::to_string),
+            host_namespace: false,
+        }
+    }
+
+    fn host_caller(pid: i32) -> Caller {
+        Caller {
+            pid: Pid::Value(pid),
+            container: None,
+            host_namespace: true,
+        }
+    }
+
+    impl Caller {
+        fn in_container(mut self, container: &str) -> Self {
+            self.container = Some(container.to_string());
+            self
+        }
+    }
+
+    const CONTAINER: &str = "8e3209d08ed5e73d1c9c8e7580ddad232b6dceb5bf0c6d74cadbed75422eef0e";
+
+    #[test]
+    fn should_authorize_caller_by_container() {
+        let runtime = TestModuleList::new(&vec![
+            TestModule::new("xyz", 987),
+            TestModule::new("abc", 123).with_container_id(CONTAINER),
+        ]);
+        let auth = Authorization::new(index(runtime), Policy::caller());
+        assert_eq!(
+            true,
+            auth.authorize_process(Some("abc".to_string()), module_caller(456, Some(CONTAINER)))
+                .wait()
+                .unwrap()
+        );
+        assert_eq!(
+            false,
+            auth.authorize_process(Some("xyz".to_string()), module_caller(987, Some(CONTAINER)))
+                .wait()
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn should_authorize_caller_by_pid_outside_of_module_containers() {
+        let runtime = TestModuleList::new(&vec![
+            TestModule::new("abc", 123).with_container_id(CONTAINER)
+        ]);
+        let auth = Authorization::new(index(runtime), Policy::module("abc"));
+        assert_eq!(
+            true,
+            auth.authorize_process(None, module_caller(123, Some("other")))
+                .wait()
+                .unwrap()
+        );
+        assert_eq!(
+            false,
+            auth.authorize_process(None, module_caller(456, None))
+                .wait()
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn should_reject_host_in_module_container() {
+        let runtime = TestModuleList::new(&vec![
+            TestModule::new("abc", 123).with_container_id(CONTAINER)
+        ]);
+        let auth = Authorization::new(index(runtime), Policy::host());
+        assert_eq!(
+            false,
+            auth.authorize_process(None, host_caller(456).in_container(CONTAINER))
+                .wait()
+                .unwrap()
+        );
+        assert_eq!(
+            true,
+            auth.authorize_process(None, host_caller(456))
+                .wait()
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn should_reuse_module_listing_of_authorized_callers() {
+        let runtime = TestModuleList::new(&vec![TestModule::new("abc", 123)]);
+        let lists = runtime.lists.clone();
+        let auth = Authorization::new(index(runtime), Policy::caller());
+        for _ in 0..3 {
+            assert_eq!(
+                true,
+                auth.authorize(Some("abc".to_string()), Pid::Value(123))
+                    .wait()
+                    .unwrap()
+            );
+        }
+        assert_eq!(1, lists.get());
+    }
+
+    #[test]
+    fn should_share_module_listing_across_routes() {
+        let runtime = TestModuleList::new(&vec![TestModule::new("abc", 123)]);
+        let lists = runtime.lists.clone();
+        let index = index(runtime);
+        let sign = Authorization::new(index.clone(), Policy::caller());
+        let list = Authorization::new(index, Policy::module("abc"));
+        assert_eq!(
+            true,
+            sign.authorize(Some("abc".to_string()), Pid::Value(123))
+                .wait()
+                .unwrap()
+        );
+        assert_eq!(true, list.authorize(None, Pid::Value(123)).wait().unwrap());
+        assert_eq!(1, lists.get());
+    }
+
+    #[test]
+    fn should_list_modules_again_after_module_event() {
+        let mut runtime = TestModuleList::new(&vec![TestModule::new("abc", 123)]);
+        runtime.events = vec![
+            ModuleEvent::new(
+                "abc".to_string(),
+                ModuleAction::Health("healthy".to_string()),
+            ),
+            ModuleEvent::new("abc".to_string(), ModuleAction::Die),
+        ];
+        let lists = runtime.lists.clone();
+        let index = index(runtime);
+        let auth = Authorization::new(index.clone(), Policy::caller());
+        let authorize = || {
+            auth.authorize(Some("abc".to_string()), Pid::Value(123))
+                .wait()
+                .unwrap()
+        };
+
+        assert_eq!(true, authorize());
+        assert_eq!(true, authorize());
+        assert_eq!(1, lists.get());
+
+        index.watch().wait().unwrap();
+        assert_eq!(true, authorize());
+        assert_eq!(2, lists.get());
+    }
+
+    #[test]
+    fn should_list_modules_again_before_rejecting_caller() {
+        let runtime = TestModuleList::new(&vec![TestModule::new("abc", 123)]);
+        let lists = runtime.lists.clone();
+        let auth = Authorization::new(index(runtime), Policy::caller());
+        assert_eq!(
+            true,
+            auth.authorize(Some("abc".to_string()), Pid::Value(123))
+                .wait()
+                .unwrap()
+        );
+        assert_eq!(
+            false,
+            auth.authorize(Some("abc".to_string()), Pid::Value(456))
+                .wait()
+                .unwrap()
+        );
+        assert_eq!(2, lists.get());
+    }
+
     struct TestConfig {}
 
     #[derive(Clone)]
@@ -254,6 +906,7 @@ mod tests {
     struct TestModule {
         name: String,
         pid: i32,
+        container_id: Option<String>,
         behavior: TestModuleBehavior,
     }
 
@@ -263,6 +916,7 @@ mod tests {
             TestModule {
                 name,
                 pid,
+                container_id: None,
                 behavior: TestModuleBehavior::Default,
             }
         }
@@ -272,9 +926,15 @@ mod tests {
             TestModule {
                 name,
                 pid,
+                container_id: None,
                 behavior,
             }
         }
+
+        pub fn with_container_id(mut self, container_id: &str) -> Self {
+            self.container_id = Some(container_id.to_string());
+            self
+        }
     }
 
     macro_rules! notimpl_error {
@@ -297,6 +957,9 @@ mod tests {
         fn config(&self) -> &Self::Config {
             &TestConfig {}
         }
+        fn container_id(&self) -> Option<&str> {
+            self.container_id.as_ref().map(AsRef::as_ref)
+        }
         fn runtime_state(&self) -> Self::RuntimeStateFuture {
             match self.behavior {
                 TestModuleBehavior::Default => {
@@ -318,6 +981,8 @@ mod tests {
     struct TestModuleList {
         modules: Vec<TestModule>,
         behavior: TestModuleListBehavior,
+        lists: Rc<Cell<usize>>,
+        events: Vec<ModuleEvent>,
     }
 
     impl TestModuleList {
@@ -325,6 +990,8 @@ mod tests {
             TestModuleList {
                 modules: modules.clone(),
                 behavior: TestModuleListBehavior::Default,
+                lists: Rc::new(Cell::new(0)),
+                events: Vec::new(),
             }
         }
 
@@ -335,6 +1002,8 @@ mod tests {
             TestModuleList {
                 modules: modules.clone(),
                 behavior,
+                lists: Rc::new(Cell::new(0)),
+                events: Vec::new(),
             }
         }
     }
@@ -344,6 +1013,12 @@ mod tests {
         type Error = Error;
         type PullFuture = FutureResult<(), Self::Error>;
         type RemoveFuture = FutureResult<(), Self::Error>;
+        type ListImagesFuture = FutureResult<Vec<Image>, Self::Error>;
+        type PruneImagesFuture = FutureResult<PruneResult, Self::Error>;
+        type LoadImagesFuture = FutureResult<(), Self::Error>;
+        type SaveImageFuture = FutureResult<Self::ImageArchive, Self::Error>;
+        type ImageChunk = String;
+        type ImageArchive = Empty<Self::ImageChunk, Self::Error>;
 
         fn pull(&self, _config: &Self::Config) -> Self::PullFuture {
             notimpl_error!()
@@ -351,6 +1026,18 @@ mod tests {
         fn remove(&self, _name: &str) -> Self::RemoveFuture {
             notimpl_error!()
         }
+        fn list_images(&self) -> Self::ListImagesFuture {
+            notimpl_error!()
+        }
+        fn prune_images(&self, _options: &PruneOptions) -> Self::PruneImagesFuture {
+            notimpl_error!()
+        }
+        fn load_images(&self, _archive: ArchiveStream) -> Self::LoadImagesFuture {
+            notimpl_error!()
+        }
+        fn save_image(&self, _name: &str) -> Self::SaveImageFuture {
+            notimpl_error!()
+        }
     }
 
     impl ModuleRuntime for TestModuleList {
@@ -360,17 +1047,31 @@ mod tests {
         type ModuleRegistry = Self;
         type Chunk = String;
         type Logs = Empty<Self::Chunk, Self::Error>;
+        type Stats = Empty<ModuleStats, Self::Error>;
+        type Events = IterOk<IntoIter<ModuleEvent>, Self::Error>;
 
         type CreateFuture = FutureResult<(), Self::Error>;
+        type EventsFuture = FutureResult<Self::Events, Self::Error>;
+        type ExecFuture = FutureResult<Self::Logs, Self::Error>;
+        type GetArchiveFuture = FutureResult<Self::Logs, Self::Error>;
         type InitFuture = FutureResult<(), Self::Error>;
         type ListFuture = FutureResult<Vec<Self::Module>, Self::Error>;
         type LogsFuture = FutureResult<Self::Logs, Self::Error>;
         type RemoveFuture = FutureResult<(), Self::Error>;
+        type PauseFuture = FutureResult<(), Self::Error>;
+        type PutArchiveFuture = FutureResult<(), Self::Error>;
         type RestartFuture = FutureResult<(), Self::Error>;
+        type StatsFuture = FutureResult<Self::Stats, Self::Error>;
         type StartFuture = FutureResult<(), Self::Error>;
         type StopFuture = FutureResult<(), Self::Error>;
+        type UnpauseFuture = FutureResult<(), Self::Error>;
         type SystemInfoFuture = FutureResult<CoreSystemInfo, Self::Error>;
         type RemoveAllFuture = FutureResult<(), Self::Error>;
+        type ListVolumesFuture = FutureResult<Vec<Volume>, Self::Error>;
+        type InspectVolumeFuture = FutureResult<Volume, Self::Error>;
+        type CreateVolumeFuture = FutureResult<Volume, Self::Error>;
+        type RemoveVolumeFuture = FutureResult<(), Self::Error>;
+        type BackupVolumeFuture = FutureResult<Self::Logs, Self::Error>;
 
         fn init(&self) -> Self::InitFuture {
             notimpl_error!()
@@ -396,11 +1097,24 @@ mod tests {
             notimpl_error!()
         }
 
+        fn pause(&self, _id: &str) -> Self::PauseFuture {
+            notimpl_error!()
+        }
+
+        fn unpause(&self, _id: &str) -> Self::UnpauseFuture {
+            notimpl_error!()
+        }
+
         fn remove(&self, _id: &str) -> Self::RemoveFuture {
             notimpl_error!()
         }
 
+        fn remove_with_volumes(&self, _id: &str) -> Self::RemoveFuture {
+            notimpl_error!()
+        }
+
         fn list(&self) -> Self::ListFuture {
+            self.lists.set(self.lists.get() + 1);
             match self.behavior {
                 TestModuleListBehavior::Default => future::ok(self.modules.clone()),
                 TestModuleListBehavior::FailList => notimpl_error!(),
@@ -411,6 +1125,31 @@ mod tests {
             notimpl_error!()
         }
 
+        fn stats(&self, _id: &str, _options: &StatsOptions) -> Self::StatsFuture {
+            notimpl_error!()
+        }
+
+        fn events(&self, _options: &EventOptions) -> Self::EventsFuture {
+            future::ok(stream::iter_ok(self.events.clone()))
+        }
+
+        fn exec(&self, _id: &str, _options: &ExecOptions) -> Self::ExecFuture {
+            notimpl_error!()
+        }
+
+        fn get_archive(&self, _id: &str, _path: &str) -> Self::GetArchiveFuture {
+            notimpl_error!()
+        }
+
+        fn put_archive(
+            &self,
+            _id: &str,
+            _path: &str,
+            _archive: ArchiveStream,
+        ) -> Self::PutArchiveFuture {
+            notimpl_error!()
+        }
+
         fn registry(&self) -> &Self::ModuleRegistry {
             self
         }
@@ -418,5 +1157,25 @@ mod tests {
         fn remove_all(&self) -> Self::RemoveAllFuture {
             notimpl_error!()
         }
+
+        fn list_volumes(&self) -> Self::ListVolumesFuture {
+            notimpl_error!()
+        }
+
+        fn inspect_volume(&self, _name: &str) -> Self::InspectVolumeFuture {
+            notimpl_error!()
+        }
+
+        fn create_volume(&self, _name: &str, _module: Option<&str>) -> Self::CreateVolumeFuture {
+            notimpl_error!()
+        }
+
+        fn remove_volume(&self, _name: &str) -> Self::RemoveVolumeFuture {
+            notimpl_error!()
+        }
+
+        fn backup_volume(&self, _name: &str) -> Self::BackupVolumeFuture {
+            notimpl_error!()
+        }
     }
 }
@@ -0,0 +1,205 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+use std::collections::{HashMap, HashSet};
+
+use error::{Error, ErrorKind};
+
+/// Groups modules into stages, so that modules only depend on modules in
+/// earlier stages. The modules of a stage can be started together once the
+/// earlier stages are up, and stopped in the reverse order of the stages.
+///
+/// `dependencies` maps each module to the modules it depends on.
+/// Dependencies on modules that are not in the map are ignored.
+pub fn start_stages(
+    dependencies: &HashMap<String, Vec<String>>,
+) -> Result<Vec<Vec<String>>, Error> {
+    let mut remaining: HashMap<&str, HashSet<&str>> = dependencies
+        .iter()
+        .map(|(name, deps)| {
+            let deps = deps
+                .iter()
+                .map(String::as_str)
+                .filter(|dep| *dep != name.as_str() && dependencies.contains_key(*dep))
+                .collect();
+            (name.as_str(), deps)
+        }).collect();
+
+    let mut stages = Vec::new();
+    while !remaining.is_empty() {
+        let mut stage: Vec<String> = remaining
+            .iter()
+            .filter(|&(_, deps)| deps.is_empty())
+            .map(|(name, _)| name.to_string())
+            .collect();
+        if stage.is_empty() {
+            let mut cycle: Vec<&str> = remaining.keys().cloned().collect();
+            cycle.sort();
+            return Err(Error::from(ErrorKind::DependencyCycle(cycle.join(", "))));
+        }
+        stage.sort();
+
+        for name in &stage {
+            remaining.remove(name.as_str());
+        }
+        for deps in remaining.values_mut() {
+            for name in &stage {
+                deps.remove(name.as_str());
+            }
+        }
+        stages.push(stage);
+    }
+    Ok(stages)
+}
+
+/// The stages of the modules `name` depends on, directly or through other
+/// modules, without `name` itself.
+pub fn dependency_stages(
+    dependencies: &HashMap<String, Vec<String>>,
+    name: &str,
+) -> Result<Vec<Vec<String>>, Error> {
+    let mut required = HashMap::new();
+    let mut pending = vec![name];
+    while let Some(module) = pending.pop() {
+        if let Some(deps) = dependencies.get(module) {
+            if required.insert(module.to_string(), deps.clone()).is_none() {
+                pending.extend(deps.iter().map(String::as_str));
+            }
+        }
+    }
+
+    let mut stages = start_stages(&required)?;
+    // everything else is a dependency of `name`, so it is alone in the last stage
+    stages.pop();
+    Ok(stages)
+}
+
+/// The stages of the modules that depend on `name`, directly or through other
+/// modules, without `name` itself. The stages are in the order the modules
+/// should be stopped in, so a module comes before the modules it depends on.
+pub fn dependent_stages(
+    dependencies: &HashMap<String, Vec<String>>,
+    name: &str,
+) -> Result<Vec<Vec<String>>, Error> {
+    let mut dependents = HashSet::new();
+    let mut pending = vec![name];
+    while let Some(module) = pending.pop() {
+        for (other, deps) in dependencies {
+            if other != module
+                && other != name
+                && deps.iter().any(|dep| dep == module)
+                && dependents.insert(other.as_str())
+            {
+                pending.push(other.as_str());
+            }
+        }
+    }
+
+    let required = dependencies
+        .iter()
+        .filter(|&(module, _)| module == name || dependents.contains(module.as_str()))
+        .map(|(module, deps)| (module.clone(), deps.clone()))
+        .collect();
+    let mut stages: Vec<Vec<String>> = start_stages(&required)?
+        .into_iter()
+        .map(|stage| stage.into_iter().filter(|module| module != name).collect())
+        .filter(|stage: &Vec<String>| !stage.is_empty())
+        .collect();
+    stages.reverse();
+    Ok(stages)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn graph(edges: &[(&str, &[&str])]) -> HashMap<String, Vec<String>> {
+        edges
+            .iter()
+            .map(|&(name, deps)| {
+                (
+                    name.to_string(),
+                    deps.iter().map(|d| d.to_string()).collect(),
+                )
+            }).collect()
+    }
+
+    fn stages(stages: &[&[&str]]) -> Vec<Vec<String>> {
+        stages
+            .iter()
+            .map(|stage| stage.iter().map(|s| s.to_string()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn dependencies_come_first() {
+        let deps = graph(&[
+            ("edgeAgent", &[]),
+            ("edgeHub", &[]),
+            ("filter", &["edgeHub", "store"]),
+            ("sensor", &["edgeHub"]),
+            ("store", &["edgeHub", "missing"]),
+        ]);
+        assert_eq!(
+            stages(&[&["edgeAgent", "edgeHub"], &["sensor", "store"], &["filter"]]),
+            start_stages(&deps).unwrap()
+        );
+    }
+
+    #[test]
+    fn cycles_are_reported() {
+        let deps = graph(&[("a", &["b"]), ("b", &["c"]), ("c", &["a"]), ("d", &[])]);
+        let err = start_stages(&deps).unwrap_err();
+        match *err.kind() {
+            ErrorKind::DependencyCycle(ref modules) => assert_eq!("a, b, c", modules),
+            _ => panic!("Expected a dependency cycle, got {:?}", err),
+        }
+    }
+
+    #[test]
+    fn module_depending_on_itself_is_not_a_cycle() {
+        let deps = graph(&[("a", &["a"])]);
+        assert_eq!(stages(&[&["a"]]), start_stages(&deps).unwrap());
+    }
+
+    #[test]
+    fn dependency_stages_of_module() {
+        let deps = graph(&[
+            ("edgeHub", &[]),
+            ("filter", &["store"]),
+            ("sensor", &["edgeHub"]),
+            ("store", &["edgeHub"]),
+        ]);
+        assert_eq!(
+            stages(&[&["edgeHub"], &["store"]]),
+            dependency_stages(&deps, "filter").unwrap()
+        );
+        assert!(dependency_stages(&deps, "edgeHub").unwrap().is_empty());
+        assert!(dependency_stages(&deps, "unknown").unwrap().is_empty());
+    }
+
+    #[test]
+    fn dependent_stages_of_module() {
+        let deps = graph(&[
+            ("edgeHub", &[]),
+            ("filter", &["store"]),
+            ("sensor", &["edgeHub"]),
+            ("store", &["edgeHub"]),
+        ]);
+        assert_eq!(
+            stages(&[&["filter"], &["sensor", "store"]]),
+            dependent_stages(&deps, "edgeHub").unwrap()
+        );
+        assert_eq!(
+            stages(&[&["filter"]]),
+            dependent_stages(&deps, "store").unwrap()
+        );
+        assert!(dependent_stages(&deps, "filter").unwrap().is_empty());
+        assert!(dependent_stages(&deps, "unknown").unwrap().is_empty());
+    }
+
+    #[test]
+    fn dependency_stages_reports_cycles() {
+        let deps = graph(&[("a", &["b"]), ("b", &["a"]), ("c", &["a"])]);
+        assert!(dependency_stages(&deps, "c").is_err());
+    }
+}
@@ -6,6 +6,7 @@ use std::num::ParseIntError;
 
 use edgelet_utils::Error as UtilsError;
 use failure::{Backtrace, Context, Fail};
+use serde_json::Error as SerdeError;
 use tokio_timer;
 
 pub type Result<T> = ::std::result::Result<T, Error>;
@@ -50,6 +51,12 @@ pub enum ErrorKind {
     Parse,
     #[fail(display = "Http error")]
     Http,
+    #[fail(display = "Serde error")]
+    Serde,
+    #[fail(display = "Modules {} depend on each other", _0)]
+    DependencyCycle(String),
+    #[fail(display = "An authorization policy is set for unknown route {}", _0)]
+    UnknownRoute(String),
 }
 
 impl Fail for Error {
@@ -108,6 +115,14 @@ impl From<tokio_timer::Error> for Error {
     }
 }
 
+impl From<SerdeError> for Error {
+    fn from(error: SerdeError) -> Error {
+        Error {
+            inner: error.context(ErrorKind::Serde),
+        }
+    }
+}
+
 impl From<ParseIntError> for Error {
     fn from(error: ParseIntError) -> Error {
         Error {
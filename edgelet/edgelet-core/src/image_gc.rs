@@ -0,0 +1,220 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+use std::cmp;
+use std::time::{Duration, Instant};
+
+use edgelet_utils::log_failure;
+use futures::future::{self, Either};
+use futures::Future;
+use log::Level;
+use tokio::prelude::*;
+use tokio::timer::Interval;
+
+use error::Error;
+use module::{ModuleRegistry, ModuleRuntime, PruneOptions};
+
+/// Default frequency with which unused images are collected.
+const DEFAULT_FREQUENCY_SECS: u64 = 3600;
+
+/// Default number of unused images kept for each repository.
+const DEFAULT_KEEP_PREVIOUS: u32 = 1;
+
+/// Default age below which unused images are kept.
+const DEFAULT_MIN_AGE_SECS: u64 = 86400;
+
+fn default_frequency_secs() -> u64 {
+    DEFAULT_FREQUENCY_SECS
+}
+
+fn default_keep_previous() -> u32 {
+    DEFAULT_KEEP_PREVIOUS
+}
+
+fn default_min_age_secs() -> u64 {
+    DEFAULT_MIN_AGE_SECS
+}
+
+/// Controls the removal of images that no module uses. Collection is off
+/// unless enabled. With a high water mark, images are only removed once the
+/// space used by images exceeds it, until usage drops to the low water mark.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct ImageGcSettings {
+    #[serde(default)]
+    enabled: bool,
+    #[serde(default = "default_frequency_secs")]
+    frequency_secs: u64,
+    #[serde(default = "default_keep_previous")]
+    keep_previous: u32,
+    #[serde(default = "default_min_age_secs")]
+    min_age_secs: u64,
+    #[serde(default)]
+    high_water_mark_bytes: Option<u64>,
+    #[serde(default)]
+    low_water_mark_bytes: Option<u64>,
+}
+
+impl Default for ImageGcSettings {
+    fn default() -> Self {
+        ImageGcSettings {
+            enabled: false,
+            frequency_secs: DEFAULT_FREQUENCY_SECS,
+            keep_previous: DEFAULT_KEEP_PREVIOUS,
+            min_age_secs: DEFAULT_MIN_AGE_SECS,
+            high_water_mark_bytes: None,
+            low_water_mark_bytes: None,
+        }
+    }
+}
+
+impl ImageGcSettings {
+    pub fn new(enabled: bool, frequency_secs: u64, keep_previous: u32, min_age_secs: u64) -> Self {
+        ImageGcSettings {
+            enabled,
+            frequency_secs,
+            keep_previous,
+            min_age_secs,
+            high_water_mark_bytes: None,
+            low_water_mark_bytes: None,
+        }
+    }
+
+    pub fn with_water_marks(mut self, high: Option<u64>, low: Option<u64>) -> Self {
+        self.high_water_mark_bytes = high;
+        self.low_water_mark_bytes = low;
+        self
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn frequency(&self) -> Duration {
+        Duration::from_secs(self.frequency_secs)
+    }
+
+    /// The options for each collection. A missing low water mark, or one
+    /// above the high water mark, is the same as the high water mark.
+    pub fn prune_options(&self) -> PruneOptions {
+        let water_marks = self.high_water_mark_bytes.map(|high| {
+            (
+                high,
+                cmp::min(self.low_water_mark_bytes.unwrap_or(high), high),
+            )
+        });
+        PruneOptions::new()
+            .with_keep_previous(self.keep_previous)
+            .with_min_age(Duration::from_secs(self.min_age_secs))
+            .with_water_marks(water_marks)
+    }
+}
+
+pub struct ImageGarbageCollector<M> {
+    runtime: M,
+    settings: ImageGcSettings,
+}
+
+impl<M> ImageGarbageCollector<M>
+where
+    M: 'static + ModuleRuntime + Clone,
+    M::Error: Into<Error>,
+{
+    pub fn new(runtime: M, settings: ImageGcSettings) -> Self {
+        ImageGarbageCollector { runtime, settings }
+    }
+
+    /// Collects unused images with the configured frequency until the
+    /// shutdown signal completes. The first collection happens one period
+    /// after start, once the edge runtime had a chance to start its modules.
+    pub fn run_until<F>(self, shutdown_signal: F) -> impl Future<Item = (), Error = Error>
+    where
+        F: Future<Item = (), Error = ()> + 'static,
+    {
+        // Swallow any errors from shutdown_signal
+        let shutdown_signal = shutdown_signal.then(|_| Ok(()));
+        if !self.settings.enabled() {
+            return Either::A(shutdown_signal);
+        }
+
+        info!(
+            "Starting image garbage collection with {} second frequency...",
+            self.settings.frequency().as_secs()
+        );
+        let runtime = self.runtime;
+        let options = self.settings.prune_options();
+        let frequency = self.settings.frequency();
+        let collector = Interval::new(Instant::now() + frequency, frequency)
+            .map_err(Error::from)
+            .for_each(move |_| {
+                runtime.registry().prune_images(&options).then(|result| {
+                    match result {
+                        Ok(result) => info!(
+                            "Removed {} unused images, reclaimed {} bytes",
+                            result.images_deleted().len(),
+                            result.space_reclaimed()
+                        ),
+                        Err(e) => {
+                            let e: Error = e.into();
+                            warn!("Error in image garbage collection:");
+                            log_failure(Level::Warn, &e);
+                        }
+                    }
+                    future::ok(())
+                })
+            });
+
+        // The collector never completes, so this waits for the shutdown signal.
+        Either::B(
+            shutdown_signal
+                .select(collector)
+                .map(|_| ())
+                .map_err(|(e, _)| e),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use serde_json;
+
+    #[test]
+    fn defaults_disable_collection() {
+        let settings: ImageGcSettings = serde_json::from_str("{}").unwrap();
+        assert_eq!(ImageGcSettings::default(), settings);
+        assert!(!settings.enabled());
+
+        let options = settings.prune_options();
+        assert_eq!(DEFAULT_KEEP_PREVIOUS, options.keep_previous());
+        assert_eq!(Duration::from_secs(DEFAULT_MIN_AGE_SECS), options.min_age());
+        assert_eq!(None, options.water_marks());
+    }
+
+    #[test]
+    fn low_water_mark_defaults_to_high_water_mark() {
+        let settings = ImageGcSettings::new(true, 60, 0, 0);
+        assert_eq!(
+            Some((100, 100)),
+            settings
+                .clone()
+                .with_water_marks(Some(100), None)
+                .prune_options()
+                .water_marks()
+        );
+        assert_eq!(
+            Some((100, 100)),
+            settings
+                .clone()
+                .with_water_marks(Some(100), Some(200))
+                .prune_options()
+                .water_marks()
+        );
+        assert_eq!(
+            Some((100, 50)),
+            settings
+                .with_water_marks(Some(100), Some(50))
+                .prune_options()
+                .water_marks()
+        );
+    }
+}
@@ -27,16 +27,22 @@ extern crate tokio_timer;
 #[macro_use]
 extern crate edgelet_utils;
 
+pub mod admission;
 mod authorization;
 mod certificate_properties;
 pub mod crypto;
+pub mod dependency;
 mod error;
 mod identity;
+pub mod image_gc;
 mod module;
+mod module_index;
+pub mod network;
 pub mod pid;
+pub mod signature;
 pub mod watchdog;
 
-pub use authorization::{Authorization, Policy};
+pub use authorization::{Authorization, AuthorizationSettings, Policy, RoutePolicies};
 pub use certificate_properties::{CertificateIssuer, CertificateProperties, CertificateType};
 pub use crypto::{
     Certificate, CreateCertificate, Decrypt, Encrypt, GetTrustBundle, KeyBytes, KeyIdentity,
@@ -45,9 +51,12 @@ pub use crypto::{
 pub use error::{Error, ErrorKind};
 pub use identity::{AuthType, Identity, IdentityManager, IdentitySpec};
 pub use module::{
-    LogOptions, LogTail, Module, ModuleRegistry, ModuleRuntime, ModuleRuntimeState, ModuleSpec,
-    ModuleStatus, SystemInfo,
+    ArchiveStream, EventOptions, ExecOptions, Image, ImagePullPolicy, LogOptions, LogTail, Module,
+    ModuleAction, ModuleEvent, ModuleHealth, ModuleRegistry, ModuleRuntime, ModuleRuntimeState,
+    ModuleSpec, ModuleStats, ModuleStatus, PruneOptions, PruneResult, StatsOptions, SystemInfo,
+    Volume,
 };
+pub use module_index::{ModuleIdentity, ModuleIndex};
 
 lazy_static! {
     static ref VERSION: String = option_env!("VERSION")
@@ -12,7 +12,9 @@ use chrono::prelude::*;
 use failure::Fail;
 use futures::{Future, Stream};
 use pid::Pid;
+use serde::Serialize;
 use serde_json;
+use sha2::{Digest, Sha256};
 
 use error::{Error, Result};
 
@@ -20,9 +22,16 @@ use error::{Error, Result};
 #[serde(rename_all = "lowercase")]
 pub enum ModuleStatus {
     Unknown,
+    Created,
     Running,
+    Paused,
+    Restarting,
+    /// The module exited with an error and the runtime is waiting before
+    /// restarting it.
+    Backoff,
     Stopped,
     Failed,
+    Dead,
 }
 
 impl FromStr for ModuleStatus {
@@ -45,6 +54,35 @@ impl fmt::Display for ModuleStatus {
     }
 }
 
+/// Result of the health check configured for a module, if any.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[serde(rename_all = "lowercase")]
+pub enum ModuleHealth {
+    Starting,
+    Healthy,
+    Unhealthy,
+}
+
+impl FromStr for ModuleHealth {
+    type Err = serde_json::Error;
+
+    fn from_str(s: &str) -> StdResult<Self, Self::Err> {
+        serde_json::from_str(&format!("\"{}\"", s))
+    }
+}
+
+impl fmt::Display for ModuleHealth {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            formatter,
+            "{}",
+            serde_json::to_string(self)
+                .map(|s| s.trim_matches('"').to_string())
+                .map_err(|_| fmt::Error)?
+        )
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
 pub struct ModuleRuntimeState {
     status: ModuleStatus,
@@ -54,6 +92,9 @@ pub struct ModuleRuntimeState {
     finished_at: Option<DateTime<Utc>>,
     image_id: Option<String>,
     pid: Pid,
+    health: Option<ModuleHealth>,
+    restart_count: i32,
+    oom_killed: bool,
 }
 
 impl Default for ModuleRuntimeState {
@@ -66,6 +107,9 @@ impl Default for ModuleRuntimeState {
             finished_at: None,
             image_id: None,
             pid: Pid::None,
+            health: None,
+            restart_count: 0,
+            oom_killed: false,
         }
     }
 }
@@ -136,6 +180,35 @@ impl ModuleRuntimeState {
         self.pid = pid.clone();
         self
     }
+
+    pub fn health(&self) -> Option<&ModuleHealth> {
+        self.health.as_ref()
+    }
+
+    pub fn with_health(mut self, health: Option<ModuleHealth>) -> ModuleRuntimeState {
+        self.health = health;
+        self
+    }
+
+    /// Number of times the runtime restarted the module on its own.
+    pub fn restart_count(&self) -> i32 {
+        self.restart_count
+    }
+
+    pub fn with_restart_count(mut self, restart_count: i32) -> ModuleRuntimeState {
+        self.restart_count = restart_count;
+        self
+    }
+
+    /// Whether the module was last killed because it ran out of memory.
+    pub fn oom_killed(&self) -> bool {
+        self.oom_killed
+    }
+
+    pub fn with_oom_killed(mut self, oom_killed: bool) -> ModuleRuntimeState {
+        self.oom_killed = oom_killed;
+        self
+    }
 }
 
 #[derive(Deserialize, Debug, Serialize)]
@@ -146,6 +219,12 @@ pub struct ModuleSpec<T> {
     config: T,
     #[serde(default = "HashMap::new")]
     env: HashMap<String, String>,
+    #[serde(default, skip_serializing_if = "is_false")]
+    record_spec_hash: bool,
+}
+
+fn is_false(value: &bool) -> bool {
+    !*value
 }
 
 impl<T> Clone for ModuleSpec<T>
@@ -158,6 +237,7 @@ where
             type_: self.type_.clone(),
             config: self.config.clone(),
             env: self.env.clone(),
+            record_spec_hash: self.record_spec_hash,
         }
     }
 }
@@ -174,6 +254,7 @@ impl<T> ModuleSpec<T> {
             type_: ensure_not_empty!(type_).to_string(),
             config,
             env,
+            record_spec_hash: false,
         })
     }
 
@@ -220,9 +301,46 @@ impl<T> ModuleSpec<T> {
         self.env = env;
         self
     }
+
+    /// Whether the runtime records the hash of the spec on the module. Only
+    /// the watchdog asks for it, so a module created by anyone else (like
+    /// edgeAgent updating itself) is never taken for a stale copy of the
+    /// watchdog's spec.
+    pub fn record_spec_hash(&self) -> bool {
+        self.record_spec_hash
+    }
+
+    pub fn with_record_spec_hash(mut self, record_spec_hash: bool) -> Self {
+        self.record_spec_hash = record_spec_hash;
+        self
+    }
+}
+
+impl<T> ModuleSpec<T>
+where
+    T: Serialize,
+{
+    /// Returns a hex encoded SHA-256 digest of the spec. The spec is first
+    /// converted to a `serde_json::Value` so that object keys are sorted and
+    /// the digest doesn't depend on `HashMap` iteration order.
+    pub fn spec_hash(&self) -> Result<String> {
+        let value = serde_json::to_value(self)?;
+        let digest = Sha256::digest_str(&value.to_string());
+        Ok(digest.iter().map(|b| format!("{:02x}", b)).collect())
+    }
+
+    /// The hash the runtime should record on the module, if the spec asks
+    /// for one.
+    pub fn recorded_spec_hash(&self) -> Result<Option<String>> {
+        if self.record_spec_hash {
+            self.spec_hash().map(Some)
+        } else {
+            Ok(None)
+        }
+    }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum LogTail {
     All,
     Num(u64),
@@ -261,6 +379,9 @@ impl ToString for LogTail {
 pub struct LogOptions {
     follow: bool,
     tail: LogTail,
+    since: i32,
+    until: i32,
+    timestamps: bool,
 }
 
 impl LogOptions {
@@ -268,6 +389,9 @@ impl LogOptions {
         LogOptions {
             follow: false,
             tail: LogTail::All,
+            since: 0,
+            until: 0,
+            timestamps: false,
         }
     }
 
@@ -281,6 +405,25 @@ impl LogOptions {
         self
     }
 
+    /// Only return logs written at or after this UNIX timestamp. Zero means
+    /// no lower bound.
+    pub fn with_since(mut self, since: i32) -> Self {
+        self.since = since;
+        self
+    }
+
+    /// Only return logs written before this UNIX timestamp. Zero means no
+    /// upper bound.
+    pub fn with_until(mut self, until: i32) -> Self {
+        self.until = until;
+        self
+    }
+
+    pub fn with_timestamps(mut self, timestamps: bool) -> Self {
+        self.timestamps = timestamps;
+        self
+    }
+
     pub fn follow(&self) -> bool {
         self.follow
     }
@@ -288,6 +431,300 @@ impl LogOptions {
     pub fn tail(&self) -> &LogTail {
         &self.tail
     }
+
+    pub fn since(&self) -> i32 {
+        self.since
+    }
+
+    pub fn until(&self) -> i32 {
+        self.until
+    }
+
+    pub fn timestamps(&self) -> bool {
+        self.timestamps
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct StatsOptions {
+    stream: bool,
+}
+
+impl StatsOptions {
+    pub fn new() -> Self {
+        StatsOptions { stream: false }
+    }
+
+    pub fn with_stream(mut self, stream: bool) -> Self {
+        self.stream = stream;
+        self
+    }
+
+    pub fn stream(&self) -> bool {
+        self.stream
+    }
+}
+
+/// The command to run inside a module with `ModuleRuntime::exec`.
+#[derive(Debug, Default)]
+pub struct ExecOptions {
+    cmd: Vec<String>,
+    user: Option<String>,
+}
+
+impl ExecOptions {
+    pub fn new(cmd: Vec<String>) -> Self {
+        ExecOptions { cmd, user: None }
+    }
+
+    /// The user, and optionally group, to run the command as. Defaults to
+    /// the user the module runs as.
+    pub fn with_user(mut self, user: Option<String>) -> Self {
+        self.user = user;
+        self
+    }
+
+    pub fn cmd(&self) -> &[String] {
+        &self.cmd
+    }
+
+    pub fn user(&self) -> Option<&str> {
+        self.user.as_ref().map(AsRef::as_ref)
+    }
+}
+
+/// A single resource usage sample for a module.
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq, Clone)]
+pub struct ModuleStats {
+    read: Option<DateTime<Utc>>,
+    cpu_percent: f64,
+    cpu_total_usage: u64,
+    memory_usage: u64,
+    memory_limit: u64,
+    network_rx_bytes: u64,
+    network_tx_bytes: u64,
+    block_read_bytes: u64,
+    block_write_bytes: u64,
+}
+
+impl ModuleStats {
+    pub fn read(&self) -> Option<&DateTime<Utc>> {
+        self.read.as_ref()
+    }
+
+    pub fn with_read(mut self, read: Option<DateTime<Utc>>) -> Self {
+        self.read = read;
+        self
+    }
+
+    pub fn cpu_percent(&self) -> f64 {
+        self.cpu_percent
+    }
+
+    pub fn with_cpu_percent(mut self, cpu_percent: f64) -> Self {
+        self.cpu_percent = cpu_percent;
+        self
+    }
+
+    pub fn cpu_total_usage(&self) -> u64 {
+        self.cpu_total_usage
+    }
+
+    pub fn with_cpu_total_usage(mut self, cpu_total_usage: u64) -> Self {
+        self.cpu_total_usage = cpu_total_usage;
+        self
+    }
+
+    pub fn memory_usage(&self) -> u64 {
+        self.memory_usage
+    }
+
+    pub fn with_memory_usage(mut self, memory_usage: u64) -> Self {
+        self.memory_usage = memory_usage;
+        self
+    }
+
+    pub fn memory_limit(&self) -> u64 {
+        self.memory_limit
+    }
+
+    pub fn with_memory_limit(mut self, memory_limit: u64) -> Self {
+        self.memory_limit = memory_limit;
+        self
+    }
+
+    pub fn network_rx_bytes(&self) -> u64 {
+        self.network_rx_bytes
+    }
+
+    pub fn with_network_rx_bytes(mut self, network_rx_bytes: u64) -> Self {
+        self.network_rx_bytes = network_rx_bytes;
+        self
+    }
+
+    pub fn network_tx_bytes(&self) -> u64 {
+        self.network_tx_bytes
+    }
+
+    pub fn with_network_tx_bytes(mut self, network_tx_bytes: u64) -> Self {
+        self.network_tx_bytes = network_tx_bytes;
+        self
+    }
+
+    pub fn block_read_bytes(&self) -> u64 {
+        self.block_read_bytes
+    }
+
+    pub fn with_block_read_bytes(mut self, block_read_bytes: u64) -> Self {
+        self.block_read_bytes = block_read_bytes;
+        self
+    }
+
+    pub fn block_write_bytes(&self) -> u64 {
+        self.block_write_bytes
+    }
+
+    pub fn with_block_write_bytes(mut self, block_write_bytes: u64) -> Self {
+        self.block_write_bytes = block_write_bytes;
+        self
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct EventOptions {
+    since: i32,
+    until: i32,
+}
+
+impl EventOptions {
+    pub fn new() -> Self {
+        EventOptions { since: 0, until: 0 }
+    }
+
+    /// Only return events emitted at or after this UNIX timestamp. Zero means
+    /// that only events emitted from now on are returned.
+    pub fn with_since(mut self, since: i32) -> Self {
+        self.since = since;
+        self
+    }
+
+    /// Stop the stream once this UNIX timestamp is reached. Zero means the
+    /// stream stays open.
+    pub fn with_until(mut self, until: i32) -> Self {
+        self.until = until;
+        self
+    }
+
+    pub fn since(&self) -> i32 {
+        self.since
+    }
+
+    pub fn until(&self) -> i32 {
+        self.until
+    }
+}
+
+/// A change in the lifecycle of a module. Actions reported by the runtime that
+/// don't map to a known variant are kept as `Other`.
+#[derive(Debug, PartialEq, Clone)]
+pub enum ModuleAction {
+    Create,
+    Start,
+    Stop,
+    Restart,
+    Kill,
+    Die,
+    Oom,
+    Pause,
+    Unpause,
+    Destroy,
+    Health(String),
+    Other(String),
+}
+
+impl<'a> From<&'a str> for ModuleAction {
+    fn from(action: &'a str) -> Self {
+        match action {
+            "create" => ModuleAction::Create,
+            "start" => ModuleAction::Start,
+            "stop" => ModuleAction::Stop,
+            "restart" => ModuleAction::Restart,
+            "kill" => ModuleAction::Kill,
+            "die" => ModuleAction::Die,
+            "oom" => ModuleAction::Oom,
+            "pause" => ModuleAction::Pause,
+            "unpause" => ModuleAction::Unpause,
+            "destroy" => ModuleAction::Destroy,
+            _ if action.starts_with("health_status:") => {
+                ModuleAction::Health(action["health_status:".len()..].trim().to_string())
+            }
+            _ => ModuleAction::Other(action.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for ModuleAction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ModuleAction::Create => write!(f, "create"),
+            ModuleAction::Start => write!(f, "start"),
+            ModuleAction::Stop => write!(f, "stop"),
+            ModuleAction::Restart => write!(f, "restart"),
+            ModuleAction::Kill => write!(f, "kill"),
+            ModuleAction::Die => write!(f, "die"),
+            ModuleAction::Oom => write!(f, "oom"),
+            ModuleAction::Pause => write!(f, "pause"),
+            ModuleAction::Unpause => write!(f, "unpause"),
+            ModuleAction::Destroy => write!(f, "destroy"),
+            ModuleAction::Health(status) => write!(f, "health_status: {}", status),
+            ModuleAction::Other(action) => write!(f, "{}", action),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct ModuleEvent {
+    name: String,
+    action: ModuleAction,
+    time: Option<DateTime<Utc>>,
+    exit_code: Option<i64>,
+}
+
+impl ModuleEvent {
+    pub fn new(name: String, action: ModuleAction) -> Self {
+        ModuleEvent {
+            name,
+            action,
+            time: None,
+            exit_code: None,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn action(&self) -> &ModuleAction {
+        &self.action
+    }
+
+    pub fn time(&self) -> Option<&DateTime<Utc>> {
+        self.time.as_ref()
+    }
+
+    pub fn with_time(mut self, time: Option<DateTime<Utc>>) -> Self {
+        self.time = time;
+        self
+    }
+
+    pub fn exit_code(&self) -> Option<&i64> {
+        self.exit_code.as_ref()
+    }
+
+    pub fn with_exit_code(mut self, exit_code: Option<i64>) -> Self {
+        self.exit_code = exit_code;
+        self
+    }
 }
 
 pub trait Module {
@@ -299,16 +736,256 @@ pub trait Module {
     fn type_(&self) -> &str;
     fn config(&self) -> &Self::Config;
     fn runtime_state(&self) -> Self::RuntimeStateFuture;
+
+    /// Hash of the `ModuleSpec` the module was created from, if the runtime
+    /// recorded one.
+    fn spec_hash(&self) -> Option<&str> {
+        None
+    }
+
+    /// Id of the container the module runs in, for runtimes that run modules
+    /// in containers.
+    fn container_id(&self) -> Option<&str> {
+        None
+    }
 }
 
+/// An image known to a module registry.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Image {
+    id: String,
+    names: Vec<String>,
+    created: Option<DateTime<Utc>>,
+    size: u64,
+}
+
+impl Image {
+    pub fn new(id: String) -> Self {
+        Image {
+            id,
+            names: Vec::new(),
+            created: None,
+            size: 0,
+        }
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// The references the image is known by, e.g. `repository:tag`.
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    pub fn with_names(mut self, names: Vec<String>) -> Self {
+        self.names = names;
+        self
+    }
+
+    pub fn created(&self) -> Option<&DateTime<Utc>> {
+        self.created.as_ref()
+    }
+
+    pub fn with_created(mut self, created: Option<DateTime<Utc>>) -> Self {
+        self.created = created;
+        self
+    }
+
+    /// Size of the image in bytes.
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    pub fn with_size(mut self, size: u64) -> Self {
+        self.size = size;
+        self
+    }
+}
+
+/// Limits which images `ModuleRegistry::prune_images` removes. Images used
+/// by a module are never removed.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PruneOptions {
+    keep_previous: u32,
+    min_age: Duration,
+    water_marks: Option<(u64, u64)>,
+}
+
+impl PruneOptions {
+    pub fn new() -> Self {
+        PruneOptions::default()
+    }
+
+    /// Number of unused images to keep for each repository, newest first, so
+    /// that a deployment can be rolled back without pulling again.
+    pub fn keep_previous(&self) -> u32 {
+        self.keep_previous
+    }
+
+    pub fn with_keep_previous(mut self, keep_previous: u32) -> Self {
+        self.keep_previous = keep_previous;
+        self
+    }
+
+    /// Images created, pulled or used more recently than this are kept.
+    pub fn min_age(&self) -> Duration {
+        self.min_age
+    }
+
+    pub fn with_min_age(mut self, min_age: Duration) -> Self {
+        self.min_age = min_age;
+        self
+    }
+
+    /// Disk usage in bytes above which images are removed, and the usage to
+    /// bring it back down to. Without water marks every image that may be
+    /// removed is removed.
+    pub fn water_marks(&self) -> Option<(u64, u64)> {
+        self.water_marks
+    }
+
+    pub fn with_water_marks(mut self, water_marks: Option<(u64, u64)>) -> Self {
+        self.water_marks = water_marks;
+        self
+    }
+}
+
+/// The outcome of `ModuleRegistry::prune_images`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PruneResult {
+    images_deleted: Vec<String>,
+    space_reclaimed: u64,
+}
+
+impl PruneResult {
+    pub fn new(images_deleted: Vec<String>, space_reclaimed: u64) -> Self {
+        PruneResult {
+            images_deleted,
+            space_reclaimed,
+        }
+    }
+
+    pub fn images_deleted(&self) -> &[String] {
+        &self.images_deleted
+    }
+
+    /// Bytes freed by removing the images.
+    pub fn space_reclaimed(&self) -> u64 {
+        self.space_reclaimed
+    }
+}
+
+/// A volume that keeps the data of a module across updates of the module.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Volume {
+    name: String,
+    module: Option<String>,
+    driver: String,
+    mountpoint: String,
+    created: Option<DateTime<Utc>>,
+}
+
+impl Volume {
+    pub fn new(name: String) -> Self {
+        Volume {
+            name,
+            module: None,
+            driver: String::new(),
+            mountpoint: String::new(),
+            created: None,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The module the volume belongs to. Volumes of a module can be removed
+    /// together with the module.
+    pub fn module(&self) -> Option<&str> {
+        self.module.as_ref().map(AsRef::as_ref)
+    }
+
+    pub fn with_module(mut self, module: Option<String>) -> Self {
+        self.module = module;
+        self
+    }
+
+    pub fn driver(&self) -> &str {
+        &self.driver
+    }
+
+    pub fn with_driver(mut self, driver: String) -> Self {
+        self.driver = driver;
+        self
+    }
+
+    /// Where the data of the volume is on the host.
+    pub fn mountpoint(&self) -> &str {
+        &self.mountpoint
+    }
+
+    pub fn with_mountpoint(mut self, mountpoint: String) -> Self {
+        self.mountpoint = mountpoint;
+        self
+    }
+
+    pub fn created(&self) -> Option<&DateTime<Utc>> {
+        self.created.as_ref()
+    }
+
+    pub fn with_created(mut self, created: Option<DateTime<Utc>>) -> Self {
+        self.created = created;
+        self
+    }
+}
+
+/// Whether `ModuleRegistry::pull` fetches the image of a module from its
+/// registry.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImagePullPolicy {
+    Always,
+    /// The image is only looked up locally, for devices that cannot reach
+    /// the registry and have the image loaded with
+    /// `ModuleRegistry::load_images` instead.
+    Never,
+}
+
+impl Default for ImagePullPolicy {
+    fn default() -> Self {
+        ImagePullPolicy::Always
+    }
+}
+
+/// A tar archive read in chunks, so that it is never held in memory as a
+/// whole.
+pub type ArchiveStream = Box<Stream<Item = Vec<u8>, Error = Error>>;
+
 pub trait ModuleRegistry {
     type Error: Fail;
     type PullFuture: Future<Item = (), Error = Self::Error>;
     type RemoveFuture: Future<Item = (), Error = Self::Error>;
+    type ListImagesFuture: Future<Item = Vec<Image>, Error = Self::Error>;
+    type PruneImagesFuture: Future<Item = PruneResult, Error = Self::Error>;
+    type LoadImagesFuture: Future<Item = (), Error = Self::Error>;
+    type SaveImageFuture: Future<Item = Self::ImageArchive, Error = Self::Error>;
+    type ImageChunk: AsRef<[u8]>;
+    type ImageArchive: Stream<Item = Self::ImageChunk, Error = Self::Error>;
     type Config;
 
     fn pull(&self, config: &Self::Config) -> Self::PullFuture;
     fn remove(&self, name: &str) -> Self::RemoveFuture;
+    fn list_images(&self) -> Self::ListImagesFuture;
+
+    /// Removes the images no module uses, within the limits of `options`.
+    fn prune_images(&self, options: &PruneOptions) -> Self::PruneImagesFuture;
+    /// Loads the images in a tar archive written by `save_image`, as the
+    /// archive is read.
+    fn load_images(&self, archive: ArchiveStream) -> Self::LoadImagesFuture;
+    /// Returns a tar archive of an image and its tags.
+    fn save_image(&self, name: &str) -> Self::SaveImageFuture;
 }
 
 #[derive(Debug)]
@@ -351,29 +1028,65 @@ pub trait ModuleRuntime {
     type ModuleRegistry: ModuleRegistry<Config = Self::Config, Error = Self::Error>;
     type Chunk: AsRef<[u8]>;
     type Logs: Stream<Item = Self::Chunk, Error = Self::Error>;
+    type Stats: Stream<Item = ModuleStats, Error = Self::Error>;
+    type Events: Stream<Item = ModuleEvent, Error = Self::Error>;
 
     type CreateFuture: Future<Item = (), Error = Self::Error>;
+    type EventsFuture: Future<Item = Self::Events, Error = Self::Error>;
+    type ExecFuture: Future<Item = Self::Logs, Error = Self::Error>;
+    type GetArchiveFuture: Future<Item = Self::Logs, Error = Self::Error>;
     type InitFuture: Future<Item = (), Error = Self::Error>;
     type ListFuture: Future<Item = Vec<Self::Module>, Error = Self::Error>;
     type LogsFuture: Future<Item = Self::Logs, Error = Self::Error>;
     type RemoveFuture: Future<Item = (), Error = Self::Error>;
+    type PauseFuture: Future<Item = (), Error = Self::Error>;
+    type PutArchiveFuture: Future<Item = (), Error = Self::Error>;
     type RestartFuture: Future<Item = (), Error = Self::Error>;
+    type StatsFuture: Future<Item = Self::Stats, Error = Self::Error>;
     type StartFuture: Future<Item = (), Error = Self::Error>;
     type StopFuture: Future<Item = (), Error = Self::Error>;
+    type UnpauseFuture: Future<Item = (), Error = Self::Error>;
     type SystemInfoFuture: Future<Item = SystemInfo, Error = Self::Error>;
     type RemoveAllFuture: Future<Item = (), Error = Self::Error>;
+    type ListVolumesFuture: Future<Item = Vec<Volume>, Error = Self::Error>;
+    type InspectVolumeFuture: Future<Item = Volume, Error = Self::Error>;
+    type CreateVolumeFuture: Future<Item = Volume, Error = Self::Error>;
+    type RemoveVolumeFuture: Future<Item = (), Error = Self::Error>;
+    type BackupVolumeFuture: Future<Item = Self::Logs, Error = Self::Error>;
 
     fn init(&self) -> Self::InitFuture;
     fn create(&self, module: ModuleSpec<Self::Config>) -> Self::CreateFuture;
     fn start(&self, id: &str) -> Self::StartFuture;
     fn stop(&self, id: &str, wait_before_kill: Option<Duration>) -> Self::StopFuture;
     fn restart(&self, id: &str) -> Self::RestartFuture;
+    fn pause(&self, id: &str) -> Self::PauseFuture;
+    fn unpause(&self, id: &str) -> Self::UnpauseFuture;
     fn remove(&self, id: &str) -> Self::RemoveFuture;
+    /// Removes a module together with the volumes that belong to it.
+    fn remove_with_volumes(&self, id: &str) -> Self::RemoveFuture;
     fn system_info(&self) -> Self::SystemInfoFuture;
     fn list(&self) -> Self::ListFuture;
     fn logs(&self, id: &str, options: &LogOptions) -> Self::LogsFuture;
+    fn stats(&self, id: &str, options: &StatsOptions) -> Self::StatsFuture;
+    fn events(&self, options: &EventOptions) -> Self::EventsFuture;
+    /// Runs a command inside a running module. The output has the same
+    /// stdout/stderr framing as `logs`.
+    fn exec(&self, id: &str, options: &ExecOptions) -> Self::ExecFuture;
+    /// Returns a tar archive of the file or directory at `path` inside a module.
+    fn get_archive(&self, id: &str, path: &str) -> Self::GetArchiveFuture;
+    /// Extracts a tar archive into the directory at `path` inside a module,
+    /// as the archive is read.
+    fn put_archive(&self, id: &str, path: &str, archive: ArchiveStream) -> Self::PutArchiveFuture;
     fn registry(&self) -> &Self::ModuleRegistry;
     fn remove_all(&self) -> Self::RemoveAllFuture;
+    fn list_volumes(&self) -> Self::ListVolumesFuture;
+    fn inspect_volume(&self, name: &str) -> Self::InspectVolumeFuture;
+    /// Creates a volume, owned by `module` if it is set. Creating a volume
+    /// that exists returns the existing volume.
+    fn create_volume(&self, name: &str, module: Option<&str>) -> Self::CreateVolumeFuture;
+    fn remove_volume(&self, name: &str) -> Self::RemoveVolumeFuture;
+    /// Returns a tar archive of the data in a volume.
+    fn backup_volume(&self, name: &str) -> Self::BackupVolumeFuture;
 }
 
 #[cfg(test)]
@@ -389,9 +1102,14 @@ mod tests {
     fn get_inputs() -> Vec<(&'static str, ModuleStatus)> {
         vec![
             ("unknown", ModuleStatus::Unknown),
+            ("created", ModuleStatus::Created),
             ("running", ModuleStatus::Running),
+            ("paused", ModuleStatus::Paused),
+            ("restarting", ModuleStatus::Restarting),
+            ("backoff", ModuleStatus::Backoff),
             ("stopped", ModuleStatus::Stopped),
             ("failed", ModuleStatus::Failed),
+            ("dead", ModuleStatus::Dead),
         ]
     }
 
@@ -411,6 +1129,90 @@ mod tests {
         }
     }
 
+    #[test]
+    fn module_health_round_trips() {
+        let inputs = vec![
+            ("starting", ModuleHealth::Starting),
+            ("healthy", ModuleHealth::Healthy),
+            ("unhealthy", ModuleHealth::Unhealthy),
+        ];
+        for (text, health) in inputs {
+            assert_eq!(health, ModuleHealth::from_str(text).unwrap());
+            assert_eq!(text, health.to_string());
+        }
+        assert!(ModuleHealth::from_str("none").is_err());
+    }
+
+    #[test]
+    fn spec_hash_ignores_env_order() {
+        let mut env1 = HashMap::new();
+        let mut env2 = HashMap::new();
+        for i in 0..32 {
+            env1.insert(format!("key{}", i), format!("value{}", i));
+        }
+        for i in (0..32).rev() {
+            env2.insert(format!("key{}", i), format!("value{}", i));
+        }
+        let spec1 = ModuleSpec::new("m1", "docker", "image:1.0".to_string(), env1).unwrap();
+        let spec2 = ModuleSpec::new("m1", "docker", "image:1.0".to_string(), env2).unwrap();
+
+        let hash = spec1.spec_hash().unwrap();
+        assert_eq!(64, hash.len());
+        assert_eq!(hash, spec2.spec_hash().unwrap());
+    }
+
+    #[test]
+    fn spec_hash_changes_with_config() {
+        let spec =
+            ModuleSpec::new("m1", "docker", "image:1.0".to_string(), HashMap::new()).unwrap();
+        let updated = spec.clone().with_config("image:1.1".to_string());
+        assert_ne!(spec.spec_hash().unwrap(), updated.spec_hash().unwrap());
+    }
+
+    #[test]
+    fn recorded_spec_hash_only_when_asked() {
+        let spec =
+            ModuleSpec::new("m1", "docker", "image:1.0".to_string(), HashMap::new()).unwrap();
+        assert_eq!(None, spec.recorded_spec_hash().unwrap());
+
+        let recorded = spec.with_record_spec_hash(true);
+        assert_eq!(
+            Some(recorded.spec_hash().unwrap()),
+            recorded.recorded_spec_hash().unwrap()
+        );
+    }
+
+    #[test]
+    fn image_pull_policy_deser() {
+        assert_eq!(ImagePullPolicy::Always, ImagePullPolicy::default());
+        assert_eq!(
+            ImagePullPolicy::Never,
+            serde_json::from_str::<ImagePullPolicy>(r#""never""#).unwrap()
+        );
+        assert!(serde_json::from_str::<ImagePullPolicy>(r#""sometimes""#).is_err());
+    }
+
+    #[test]
+    fn module_action_round_trips() {
+        let inputs = vec![
+            ("create", ModuleAction::Create),
+            ("die", ModuleAction::Die),
+            ("oom", ModuleAction::Oom),
+            (
+                "health_status: unhealthy",
+                ModuleAction::Health("unhealthy".to_string()),
+            ),
+            (
+                "exec_start: sh",
+                ModuleAction::Other("exec_start: sh".to_string()),
+            ),
+        ];
+        for (action, expected) in inputs {
+            assert_eq!(expected, ModuleAction::from(action));
+            assert_eq!(action, &expected.to_string());
+        }
+    }
+
     #[test]
     fn module_config_empty_name_fails() {
         match ModuleSpec::new("", "docker", 10i32, HashMap::new()) {
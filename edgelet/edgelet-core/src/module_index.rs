@@ -0,0 +1,240 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use edgelet_utils::log_failure;
+use futures::future::{self, Either, Loop};
+use futures::{Future, Stream};
+use log::Level;
+use tokio::timer::Delay;
+
+use error::Error;
+use module::{EventOptions, Module, ModuleAction, ModuleRuntime};
+use pid::Pid;
+
+const DEFAULT_TTL_SECS: u64 = 5;
+const WATCH_MIN_BACKOFF_SECS: u64 = 1;
+const WATCH_MAX_BACKOFF_SECS: u64 = 60;
+
+/// What identifies the processes of a module when it was last listed.
+#[derive(Clone, Debug)]
+pub struct ModuleIdentity {
+    name: String,
+    pid: Pid,
+    container_id: Option<String>,
+}
+
+impl ModuleIdentity {
+    pub fn new(name: String, pid: Pid, container_id: Option<String>) -> Self {
+        ModuleIdentity {
+            name,
+            pid,
+            container_id,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn pid(&self) -> &Pid {
+        &self.pid
+    }
+
+    pub fn container_id(&self) -> Option<&str> {
+        self.container_id.as_ref().map(AsRef::as_ref)
+    }
+}
+
+struct Listing {
+    modules: Arc<Vec<ModuleIdentity>>,
+    listed: Instant,
+}
+
+// The listing along with a count of the invalidations, so that a listing
+// taken before the last invalidation is not kept.
+#[derive(Default)]
+struct Cache {
+    listing: Option<Listing>,
+    generation: u64,
+}
+
+/// Keeps the identities of the modules of a runtime for a while, so that
+/// authorizing a request does not list and inspect every module. One index
+/// is shared by every route of the management and workload APIs.
+///
+/// A module may have started, restarted or been replaced since the listing
+/// was taken, so only a listing that matches is trusted. Listings that do
+/// not match are confirmed with the runtime before giving up. The listing is
+/// also dropped as soon as the runtime reports a module event, see `watch`.
+pub struct ModuleIndex<M> {
+    runtime: M,
+    ttl: Duration,
+    cache: Arc<Mutex<Cache>>,
+}
+
+impl<M> ModuleIndex<M>
+where
+    M: 'static + ModuleRuntime,
+    M::Error: Into<Error>,
+    <M::Module as Module>::Error: Into<Error>,
+{
+    pub fn new(runtime: M) -> Self {
+        ModuleIndex {
+            runtime,
+            ttl: Duration::from_secs(DEFAULT_TTL_SECS),
+            cache: Arc::new(Mutex::new(Cache::default())),
+        }
+    }
+
+    /// How long a listing is used for when the runtime reports no module
+    /// events.
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Whether the predicate holds for the modules of the runtime.
+    pub fn matches<F>(&self, predicate: F) -> impl Future<Item = bool, Error = Error>
+    where
+        F: 'static + Fn(&[ModuleIdentity]) -> bool,
+    {
+        if self.cached().map_or(false, |modules| predicate(&modules)) {
+            Either::A(future::ok(true))
+        } else {
+            Either::B(self.refresh().map(move |modules| predicate(&modules)))
+        }
+    }
+
+    /// Drops the listing, so that the next request lists the modules again.
+    pub fn invalidate(&self) {
+        invalidate(&self.cache);
+    }
+
+    /// Drops the listing whenever the runtime reports a module event, since a
+    /// module that was started, removed or recreated would otherwise keep its
+    /// old identity until the listing expires. Resolves when the runtime ends
+    /// the stream of events.
+    pub fn watch(&self) -> impl Future<Item = (), Error = Error> {
+        watch_events(&self.runtime, self.cache.clone())
+    }
+
+    /// Like `watch`, but subscribes to the events again whenever the stream
+    /// fails or ends, waiting twice as long after each subscription that did
+    /// not last, up to a minute. The listing is dropped on every subscription
+    /// since events may have been missed in between. Never resolves.
+    pub fn watch_forever(&self) -> impl Future<Item = (), Error = ()>
+    where
+        M: Clone,
+    {
+        let runtime = self.runtime.clone();
+        let cache = self.cache.clone();
+        let min_backoff = Duration::from_secs(WATCH_MIN_BACKOFF_SECS);
+        let max_backoff = Duration::from_secs(WATCH_MAX_BACKOFF_SECS);
+        future::loop_fn(min_backoff, move |backoff| {
+            let subscribed = Instant::now();
+            invalidate(&cache);
+            watch_events(&runtime, cache.clone()).then(move |result| {
+                // a subscription that lasted was not failing right away
+                let backoff = if subscribed.elapsed() >= max_backoff {
+                    min_backoff
+                } else {
+                    backoff
+                };
+                match result {
+                    Ok(()) => warn!(
+                        "The module runtime ended its stream of events, watching it again in {} seconds",
+                        backoff.as_secs()
+                    ),
+                    Err(err) => {
+                        warn!(
+                            "Could not watch the module runtime for events, retrying in {} seconds:",
+                            backoff.as_secs()
+                        );
+                        log_failure(Level::Warn, &err);
+                    }
+                }
+                let next = ::std::cmp::min(backoff * 2, max_backoff);
+                Delay::new(Instant::now() + backoff).then(move |_| Ok(Loop::Continue(next)))
+            })
+        })
+    }
+
+    fn cached(&self) -> Option<Arc<Vec<ModuleIdentity>>> {
+        let ttl = self.ttl;
+        self.cache.lock().ok().and_then(|cache| {
+            cache.listing.as_ref().and_then(|listing| {
+                if listing.listed.elapsed() < ttl {
+                    Some(listing.modules.clone())
+                } else {
+                    None
+                }
+            })
+        })
+    }
+
+    fn refresh(&self) -> impl Future<Item = Arc<Vec<ModuleIdentity>>, Error = Error> {
+        let cache = self.cache.clone();
+        let generation = self.cache.lock().map(|cache| cache.generation).ok();
+        self.runtime
+            .list()
+            .map_err(|e| e.into())
+            .and_then(|list| {
+                let modules = list
+                    .iter()
+                    .map(|m| {
+                        let name = m.name().to_string();
+                        let container_id = m.container_id().map(ToString::to_string);
+                        m.runtime_state().map_err(|e| e.into()).map(move |rs| {
+                            ModuleIdentity::new(name, rs.pid().clone(), container_id)
+                        })
+                    }).collect::<Vec<_>>();
+                future::join_all(modules)
+            }).map(move |modules| {
+                let modules = Arc::new(modules);
+                if let Ok(mut cache) = cache.lock() {
+                    // a module changed while it was being listed
+                    if Some(cache.generation) == generation {
+                        cache.listing = Some(Listing {
+                            modules: modules.clone(),
+                            listed: Instant::now(),
+                        });
+                    }
+                }
+                modules
+            })
+    }
+}
+
+fn watch_events<M>(runtime: &M, cache: Arc<Mutex<Cache>>) -> impl Future<Item = (), Error = Error>
+where
+    M: 'static + ModuleRuntime,
+    M::Error: Into<Error>,
+{
+    runtime
+        .events(&EventOptions::new())
+        .map_err(|e| e.into())
+        .and_then(move |events| {
+            events.map_err(|e| e.into()).for_each(move |event| {
+                // health checks do not change which processes belong to a module
+                if let ModuleAction::Health(_) = *event.action() {
+                    return Ok(());
+                }
+                debug!(
+                    "Module {} {}, dropping the module identities",
+                    event.name(),
+                    event.action()
+                );
+                invalidate(&cache);
+                Ok(())
+            })
+        })
+}
+
+fn invalidate(cache: &Mutex<Cache>) {
+    if let Ok(mut cache) = cache.lock() {
+        cache.listing = None;
+        cache.generation += 1;
+    }
+}
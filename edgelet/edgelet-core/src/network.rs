@@ -0,0 +1,165 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+/// The network modules are attached to. Giving only a name keeps the
+/// defaults of the runtime, which picks a free subnet for the network.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum MobyNetwork {
+    Network(Network),
+    Name(String),
+}
+
+/// Describes a network the daemon creates on startup. Settings that are not
+/// given are left to the runtime.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct Network {
+    name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    driver: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    ipv6: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    internal: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    mtu: Option<u32>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    ipam: Vec<IpamConfig>,
+}
+
+impl Network {
+    pub fn new(name: String) -> Self {
+        Network {
+            name,
+            ..Network::default()
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn with_name(mut self, name: String) -> Self {
+        self.name = name;
+        self
+    }
+
+    pub fn driver(&self) -> Option<&str> {
+        self.driver.as_ref().map(AsRef::as_ref)
+    }
+
+    pub fn with_driver(mut self, driver: Option<String>) -> Self {
+        self.driver = driver;
+        self
+    }
+
+    pub fn ipv6(&self) -> Option<bool> {
+        self.ipv6
+    }
+
+    pub fn with_ipv6(mut self, ipv6: Option<bool>) -> Self {
+        self.ipv6 = ipv6;
+        self
+    }
+
+    /// Internal networks have no route to the outside of the device.
+    pub fn internal(&self) -> Option<bool> {
+        self.internal
+    }
+
+    pub fn with_internal(mut self, internal: Option<bool>) -> Self {
+        self.internal = internal;
+        self
+    }
+
+    pub fn mtu(&self) -> Option<u32> {
+        self.mtu
+    }
+
+    pub fn with_mtu(mut self, mtu: Option<u32>) -> Self {
+        self.mtu = mtu;
+        self
+    }
+
+    /// Fixed address ranges of the network, one per subnet.
+    pub fn ipam(&self) -> &[IpamConfig] {
+        &self.ipam
+    }
+
+    pub fn with_ipam(mut self, ipam: Vec<IpamConfig>) -> Self {
+        self.ipam = ipam;
+        self
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct IpamConfig {
+    subnet: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    gateway: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    ip_range: Option<String>,
+}
+
+impl IpamConfig {
+    pub fn new(subnet: String) -> Self {
+        IpamConfig {
+            subnet,
+            gateway: None,
+            ip_range: None,
+        }
+    }
+
+    pub fn subnet(&self) -> &str {
+        &self.subnet
+    }
+
+    pub fn gateway(&self) -> Option<&str> {
+        self.gateway.as_ref().map(AsRef::as_ref)
+    }
+
+    pub fn with_gateway(mut self, gateway: Option<String>) -> Self {
+        self.gateway = gateway;
+        self
+    }
+
+    /// The part of the subnet addresses of modules are allocated from.
+    pub fn ip_range(&self) -> Option<&str> {
+        self.ip_range.as_ref().map(AsRef::as_ref)
+    }
+
+    pub fn with_ip_range(mut self, ip_range: Option<String>) -> Self {
+        self.ip_range = ip_range;
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use serde_json;
+
+    #[test]
+    fn network_by_name() {
+        let network: MobyNetwork = serde_json::from_str(r#""azure-iot-edge""#).unwrap();
+        assert_eq!(MobyNetwork::Name("azure-iot-edge".to_string()), network);
+    }
+
+    #[test]
+    fn network_description() {
+        let network: MobyNetwork = serde_json::from_str(
+            r#"{
+                "name": "azure-iot-edge",
+                "ipv6": true,
+                "mtu": 1400,
+                "ipam": [{ "subnet": "172.30.0.0/16", "gateway": "172.30.0.1" }]
+            }"#,
+        ).unwrap();
+        let expected = Network::new("azure-iot-edge".to_string())
+            .with_ipv6(Some(true))
+            .with_mtu(Some(1400))
+            .with_ipam(vec![IpamConfig::new("172.30.0.0/16".to_string())
+                .with_gateway(Some("172.30.0.1".to_string()))]);
+        assert_eq!(MobyNetwork::Network(expected), network);
+    }
+}
@@ -2,6 +2,10 @@
 
 use std::cmp;
 use std::fmt;
+use std::fs;
+use std::io;
+#[cfg(target_os = "linux")]
+use std::path::PathBuf;
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum Pid {
@@ -40,10 +44,191 @@ impl cmp::PartialEq for Pid {
     }
 }
 
+/// Whether the user of a process is a member of one of the groups, given by
+/// name or id. The groups of a process are only known on Linux.
+pub fn in_groups(pid: &Pid, groups: &[String]) -> bool {
+    match *pid {
+        Pid::Value(pid) => process_groups(pid)
+            .map(|gids| {
+                groups
+                    .iter()
+                    .filter_map(|group| group_id(group))
+                    .any(|gid| gids.contains(&gid))
+            }).unwrap_or(false),
+        _ => false,
+    }
+}
+
+/// The id of the container a process runs in, read from its cgroups, which
+/// also names the container of processes forked by the container's main
+/// process or running in nested pid namespaces. The cgroups of a process are
+/// only known on Linux.
+pub fn container_id(pid: &Pid) -> Option<String> {
+    match *pid {
+        Pid::Value(pid) => process_cgroups(pid)
+            .ok()
+            .and_then(|cgroups| parse_container_id(&cgroups)),
+        _ => None,
+    }
+}
+
+/// Whether a process shares the pid namespace of this process. Processes in
+/// containers have their own, unless they run in the pid namespace of the
+/// host. Processes whose namespace cannot be read, e.g. because they are
+/// gone, are taken as not sharing it.
+#[cfg(target_os = "linux")]
+pub fn in_host_namespace(pid: &Pid) -> bool {
+    match *pid {
+        Pid::Value(pid) => match (pid_namespace("self"), pid_namespace(&pid.to_string())) {
+            (Ok(own), Ok(other)) => own == other,
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+/// Other platforms have no pid namespaces, so every process that has a pid
+/// is taken as running on the host. Callers that are modules are still told
+/// apart by their pid.
+#[cfg(not(target_os = "linux"))]
+pub fn in_host_namespace(pid: &Pid) -> bool {
+    match *pid {
+        Pid::Value(_) => true,
+        _ => false,
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn pid_namespace(process: &str) -> io::Result<PathBuf> {
+    fs::read_link(format!("/proc/{}/ns/pid", process))
+}
+
+#[cfg(target_os = "linux")]
+fn process_cgroups(pid: i32) -> io::Result<String> {
+    fs::read_to_string(format!("/proc/{}/cgroup", pid))
+}
+
+#[cfg(not(target_os = "linux"))]
+fn process_cgroups(_pid: i32) -> io::Result<String> {
+    Err(io::Error::new(
+        io::ErrorKind::Other,
+        "The cgroups of a process are only known on Linux",
+    ))
+}
+
+// Container runtimes name the cgroup of a container after its id, either as
+// is, e.g. "/docker/<id>", or in a systemd scope, e.g.
+// "/system.slice/docker-<id>.scope".
+fn parse_container_id(cgroups: &str) -> Option<String> {
+    cgroups
+        .lines()
+        .filter_map(|line| line.splitn(3, ':').nth(2))
+        .flat_map(|path| path.split('/'))
+        .filter_map(|segment| segment.trim_right_matches(".scope").rsplit('-').next())
+        .find(|segment| segment.len() == 64 && segment.chars().all(|c| c.is_digit(16)))
+        .map(ToString::to_string)
+}
+
+#[cfg(target_os = "linux")]
+fn process_groups(pid: i32) -> io::Result<Vec<u32>> {
+    let status = fs::read_to_string(format!("/proc/{}/status", pid))?;
+    Ok(parse_groups(&status))
+}
+
+#[cfg(not(target_os = "linux"))]
+fn process_groups(_pid: i32) -> io::Result<Vec<u32>> {
+    Err(io::Error::new(
+        io::ErrorKind::Other,
+        "The groups of a process are only known on Linux",
+    ))
+}
+
+// The ids on the "Gid:" and "Groups:" lines of /proc/<pid>/status
+fn parse_groups(status: &str) -> Vec<u32> {
+    status
+        .lines()
+        .filter(|line| line.starts_with("Gid:") || line.starts_with("Groups:"))
+        .flat_map(|line| line.split_whitespace().skip(1))
+        .filter_map(|gid| gid.parse().ok())
+        .collect()
+}
+
+fn group_id(group: &str) -> Option<u32> {
+    group.parse().ok().or_else(|| {
+        fs::read_to_string("/etc/group")
+            .ok()
+            .and_then(|groups| find_group(&groups, group))
+    })
+}
+
+fn find_group(groups: &str, name: &str) -> Option<u32> {
+    groups
+        .lines()
+        .map(|line| line.split(':').collect::<Vec<_>>())
+        .find(|fields| fields.len() > 2 && fields[0] == name)
+        .and_then(|fields| fields[2].parse().ok())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn groups_of_process_status() {
+        let status = "Name:\tiotedge\nUid:\t1000\t1000\t1000\t1000\nGid:\t1000\t1000\t1000\t1000\nGroups:\t4 27 998 \n";
+        let groups = parse_groups(status);
+        assert!(groups.contains(&1000));
+        assert!(groups.contains(&998));
+        assert!(!groups.contains(&0));
+    }
+
+    #[test]
+    fn group_by_name() {
+        let groups = "root:x:0:\niotedge:x:998:alice,bob\ndocker:x:999:\n";
+        assert_eq!(Some(998), find_group(groups, "iotedge"));
+        assert_eq!(None, find_group(groups, "plant"));
+    }
+
+    #[test]
+    fn container_id_of_docker_cgroups() {
+        let id = "8e3209d08ed5e73d1c9c8e7580ddad232b6dceb5bf0c6d74cadbed75422eef0e";
+        let cgroupfs = format!("12:pids:/docker/{}\n1:name=systemd:/docker/{}\n", id, id);
+        assert_eq!(Some(id.to_string()), parse_container_id(&cgroupfs));
+
+        let systemd = format!("0::/system.slice/docker-{}.scope\n", id);
+        assert_eq!(Some(id.to_string()), parse_container_id(&systemd));
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn own_process_is_in_host_namespace() {
+        let own = Pid::Value(::std::process::id() as i32);
+        assert!(in_host_namespace(&own));
+        assert!(!in_host_namespace(&Pid::Any));
+    }
+
+    #[test]
+    #[cfg(not(target_os = "linux"))]
+    fn processes_are_in_host_namespace_without_pid_namespaces() {
+        assert!(in_host_namespace(&Pid::Value(4242)));
+        assert!(!in_host_namespace(&Pid::None));
+        assert!(!in_host_namespace(&Pid::Any));
+    }
+
+    #[test]
+    fn no_container_id_on_host() {
+        let cgroups = "12:pids:/user.slice/user-1000.slice/session-2.scope\n0::/init.scope\n";
+        assert_eq!(None, parse_container_id(cgroups));
+        assert_eq!(None, container_id(&Pid::None));
+    }
+
+    #[test]
+    fn no_groups_without_pid() {
+        let groups = vec!["0".to_string()];
+        assert!(!in_groups(&Pid::None, &groups));
+        assert!(!in_groups(&Pid::Any, &groups));
+    }
+
     #[test]
     fn test_eq() {
         assert_ne!(Pid::None, Pid::None);
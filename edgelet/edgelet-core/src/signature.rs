@@ -0,0 +1,84 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+use std::path::PathBuf;
+
+/// What to do with a module image that is not signed by a trusted key.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VerificationMode {
+    /// Signatures are not checked.
+    Off,
+    /// Unsigned images are logged and run anyway.
+    Warn,
+    /// Unsigned images are rejected.
+    Enforce,
+}
+
+impl Default for VerificationMode {
+    fn default() -> Self {
+        VerificationMode::Off
+    }
+}
+
+/// Controls the verification of module images before their modules are
+/// created. An image is trusted when its signature verifies with any of the
+/// public keys in `trusted_keys`.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct ImageSignatureSettings {
+    #[serde(default)]
+    mode: VerificationMode,
+    #[serde(default)]
+    trusted_keys: Vec<PathBuf>,
+}
+
+impl ImageSignatureSettings {
+    pub fn new(mode: VerificationMode, trusted_keys: Vec<PathBuf>) -> Self {
+        ImageSignatureSettings { mode, trusted_keys }
+    }
+
+    pub fn mode(&self) -> VerificationMode {
+        self.mode
+    }
+
+    pub fn trusted_keys(&self) -> &[PathBuf] {
+        &self.trusted_keys
+    }
+}
+
+/// Checks a detached signature over an image digest, such as
+/// "sha256:4e2f...".
+pub trait SignatureVerifier {
+    fn verify(&self, digest: &str, signature: &[u8]) -> bool;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use serde_json;
+
+    #[test]
+    fn defaults_disable_verification() {
+        let settings: ImageSignatureSettings = serde_json::from_str("{}").unwrap();
+        assert_eq!(ImageSignatureSettings::default(), settings);
+        assert_eq!(VerificationMode::Off, settings.mode());
+        assert!(settings.trusted_keys().is_empty());
+    }
+
+    #[test]
+    fn deserialize_modes() {
+        let settings: ImageSignatureSettings = serde_json::from_str(
+            r#"{"mode": "enforce", "trusted_keys": ["/etc/iotedge/keys/publisher.pem"]}"#,
+        ).unwrap();
+        assert_eq!(VerificationMode::Enforce, settings.mode());
+        assert_eq!(
+            vec![PathBuf::from("/etc/iotedge/keys/publisher.pem")],
+            settings.trusted_keys()
+        );
+
+        let settings: ImageSignatureSettings = serde_json::from_str(r#"{"mode": "warn"}"#).unwrap();
+        assert_eq!(VerificationMode::Warn, settings.mode());
+
+        assert!(serde_json::from_str::<ImageSignatureSettings>(r#"{"mode": "on"}"#).is_err());
+    }
+}
@@ -1,13 +1,16 @@
 // Copyright (c) Microsoft. All rights reserved.
 
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
 use edgelet_utils::log_failure;
-use futures::future::{self, Either, FutureResult};
+use futures::future::{self, Either, FutureResult, Loop};
 use futures::Future;
 use log::Level;
-use tokio::prelude::*;
-use tokio::timer::Interval;
+use serde::de::{self, Deserialize, Deserializer};
+use serde::Serialize;
+use tokio::timer::Delay;
 
 use error::{Error, ErrorKind};
 use identity::{Identity, IdentityManager, IdentitySpec};
@@ -19,25 +22,297 @@ const EDGE_RUNTIME_STOP_TIME: Duration = Duration::from_secs(60);
 /// This variable holds the generation ID associated with the Edge Agent module.
 const MODULE_GENERATIONID: &str = "IOTEDGE_MODULEGENERATIONID";
 
-/// This is the frequency with which the watchdog checks for the status of the edge runtime module.
-const WATCHDOG_FREQUENCY_SECS: u64 = 60;
+/// Default frequency with which the watchdog checks for the status of the edge runtime module.
+const DEFAULT_FREQUENCY_SECS: u64 = 60;
+
+/// Default delay before the second restart attempt of a failing edge runtime module.
+const DEFAULT_INITIAL_BACKOFF_SECS: u64 = 10;
+
+/// Default upper bound for the delay between two restart attempts.
+const DEFAULT_MAX_BACKOFF_SECS: u64 = 300;
+
+/// Default number of consecutive failures after which the edge runtime module is crash looping.
+const DEFAULT_CRASH_LOOP_THRESHOLD: u32 = 5;
+
+/// Default time the edge runtime module has to keep running before its failures are forgotten.
+const DEFAULT_MIN_UPTIME_SECS: u64 = 60;
+
+fn default_frequency_secs() -> u64 {
+    DEFAULT_FREQUENCY_SECS
+}
+
+fn default_initial_backoff_secs() -> u64 {
+    DEFAULT_INITIAL_BACKOFF_SECS
+}
+
+fn default_max_backoff_secs() -> u64 {
+    DEFAULT_MAX_BACKOFF_SECS
+}
+
+fn default_crash_loop_threshold() -> u32 {
+    DEFAULT_CRASH_LOOP_THRESHOLD
+}
+
+fn default_min_uptime_secs() -> u64 {
+    DEFAULT_MIN_UPTIME_SECS
+}
+
+// The watchdog would check the edge runtime module in a busy loop.
+fn deserialize_frequency_secs<'de, D>(deserializer: D) -> Result<u64, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let frequency_secs = u64::deserialize(deserializer)?;
+    if frequency_secs == 0 {
+        Err(de::Error::custom("frequency_secs must be greater than 0"))
+    } else {
+        Ok(frequency_secs)
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct WatchdogSettings {
+    #[serde(
+        default = "default_frequency_secs",
+        deserialize_with = "deserialize_frequency_secs"
+    )]
+    frequency_secs: u64,
+    #[serde(default)]
+    restart_policy: RestartPolicy,
+}
+
+impl Default for WatchdogSettings {
+    fn default() -> Self {
+        WatchdogSettings {
+            frequency_secs: DEFAULT_FREQUENCY_SECS,
+            restart_policy: RestartPolicy::default(),
+        }
+    }
+}
+
+impl WatchdogSettings {
+    pub fn new(frequency_secs: u64, restart_policy: RestartPolicy) -> Self {
+        WatchdogSettings {
+            frequency_secs,
+            restart_policy,
+        }
+    }
+
+    pub fn frequency(&self) -> Duration {
+        Duration::from_secs(self.frequency_secs)
+    }
+
+    pub fn restart_policy(&self) -> &RestartPolicy {
+        &self.restart_policy
+    }
+}
+
+/// Controls how quickly the watchdog retries starting an edge runtime module
+/// that keeps failing. The first restart is attempted right away; every
+/// consecutive failure after that doubles the delay until `max_backoff_secs`
+/// is reached. Failures are only forgotten once the module has kept running
+/// for `min_uptime_secs`.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct RestartPolicy {
+    #[serde(default = "default_initial_backoff_secs")]
+    initial_backoff_secs: u64,
+    #[serde(default = "default_max_backoff_secs")]
+    max_backoff_secs: u64,
+    #[serde(default = "default_crash_loop_threshold")]
+    crash_loop_threshold: u32,
+    #[serde(default = "default_min_uptime_secs")]
+    min_uptime_secs: u64,
+}
+
+impl Default for RestartPolicy {
+    fn default() -> Self {
+        RestartPolicy {
+            initial_backoff_secs: DEFAULT_INITIAL_BACKOFF_SECS,
+            max_backoff_secs: DEFAULT_MAX_BACKOFF_SECS,
+            crash_loop_threshold: DEFAULT_CRASH_LOOP_THRESHOLD,
+            min_uptime_secs: DEFAULT_MIN_UPTIME_SECS,
+        }
+    }
+}
+
+impl RestartPolicy {
+    pub fn new(
+        initial_backoff_secs: u64,
+        max_backoff_secs: u64,
+        crash_loop_threshold: u32,
+        min_uptime_secs: u64,
+    ) -> Self {
+        RestartPolicy {
+            initial_backoff_secs,
+            max_backoff_secs,
+            crash_loop_threshold,
+            min_uptime_secs,
+        }
+    }
+
+    pub fn initial_backoff_secs(&self) -> u64 {
+        self.initial_backoff_secs
+    }
+
+    pub fn max_backoff_secs(&self) -> u64 {
+        self.max_backoff_secs
+    }
+
+    pub fn crash_loop_threshold(&self) -> u32 {
+        self.crash_loop_threshold
+    }
+
+    pub fn min_uptime(&self) -> Duration {
+        Duration::from_secs(self.min_uptime_secs)
+    }
+
+    /// Delay to wait after the `failures`-th consecutive failure before the
+    /// next restart attempt.
+    pub fn backoff(&self, failures: u32) -> Duration {
+        let exponent = failures.saturating_sub(1).min(31);
+        let secs = self
+            .initial_backoff_secs
+            .saturating_mul(1u64 << exponent)
+            .min(self.max_backoff_secs);
+        Duration::from_secs(secs)
+    }
+}
+
+/// Snapshot of what the watchdog knows about the edge runtime module.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct WatchdogState {
+    restart_count: u64,
+    consecutive_failures: u32,
+    crash_loop: bool,
+    last_restart: Option<DateTime<Utc>>,
+    next_attempt: Option<DateTime<Utc>>,
+}
+
+impl WatchdogState {
+    pub fn restart_count(&self) -> u64 {
+        self.restart_count
+    }
+
+    pub fn consecutive_failures(&self) -> u32 {
+        self.consecutive_failures
+    }
+
+    pub fn crash_loop(&self) -> bool {
+        self.crash_loop
+    }
+
+    pub fn last_restart(&self) -> Option<&DateTime<Utc>> {
+        self.last_restart.as_ref()
+    }
+
+    pub fn next_attempt(&self) -> Option<&DateTime<Utc>> {
+        self.next_attempt.as_ref()
+    }
+
+    fn can_attempt(&self, now: DateTime<Utc>) -> bool {
+        self.next_attempt.map(|next| now >= next).unwrap_or(true)
+    }
+
+    // A module that crashes soon after starting can be seen running, so the
+    // failures are only reset once it has been up for the minimum uptime. It
+    // counts from when the runtime started the module, or else from the last
+    // restart by the watchdog.
+    fn record_running(
+        &mut self,
+        now: DateTime<Utc>,
+        started_at: Option<DateTime<Utc>>,
+        policy: &RestartPolicy,
+    ) {
+        let up_long_enough = started_at.or(self.last_restart).map_or(true, |started| {
+            ChronoDuration::from_std(policy.min_uptime())
+                .map(|min_uptime| now.signed_duration_since(started) >= min_uptime)
+                .unwrap_or(false)
+        });
+        if up_long_enough {
+            self.consecutive_failures = 0;
+            self.crash_loop = false;
+            self.next_attempt = None;
+        }
+    }
+
+    // Time until the next check, which is brought forward to the next
+    // restart attempt when that comes first.
+    fn next_check(&self, now: DateTime<Utc>, frequency: Duration) -> Duration {
+        self.next_attempt
+            .and_then(|next| next.signed_duration_since(now).to_std().ok())
+            .map_or(frequency, |backoff| backoff.min(frequency))
+    }
+
+    fn record_failure(&mut self, now: DateTime<Utc>, policy: &RestartPolicy) {
+        self.consecutive_failures = self.consecutive_failures.saturating_add(1);
+        self.crash_loop = self.consecutive_failures >= policy.crash_loop_threshold();
+        let backoff = policy.backoff(self.consecutive_failures);
+        self.next_attempt = ChronoDuration::from_std(backoff)
+            .ok()
+            .map(|backoff| now + backoff);
+    }
+
+    fn record_restart(&mut self, now: DateTime<Utc>) {
+        self.restart_count += 1;
+        self.last_restart = Some(now);
+    }
+}
+
+/// Shared handle to the watchdog state. Clones observe the same state, so a
+/// handle can be given to the management API before the watchdog is started.
+#[derive(Clone, Debug, Default)]
+pub struct WatchdogStatus {
+    state: Arc<Mutex<WatchdogState>>,
+}
+
+impl WatchdogStatus {
+    pub fn new() -> Self {
+        WatchdogStatus::default()
+    }
+
+    pub fn state(&self) -> WatchdogState {
+        self.state
+            .lock()
+            .expect("watchdog state lock poisoned")
+            .clone()
+    }
+
+    fn update<F>(&self, f: F)
+    where
+        F: FnOnce(&mut WatchdogState),
+    {
+        f(&mut self.state.lock().expect("watchdog state lock poisoned"))
+    }
+}
 
 pub struct Watchdog<M, I> {
     runtime: M,
     id_mgr: I,
+    settings: WatchdogSettings,
+    status: WatchdogStatus,
 }
 
 impl<M, I> Watchdog<M, I>
 where
     M: 'static + ModuleRuntime + Clone,
-    <M::Module as Module>::Config: Clone,
+    <M::Module as Module>::Config: Clone + Serialize,
     M::Error: Into<Error>,
     <M::Module as Module>::Error: Into<Error>,
     I: 'static + IdentityManager + Clone,
     I::Error: Into<Error>,
 {
-    pub fn new(runtime: M, id_mgr: I) -> Self {
-        Watchdog { runtime, id_mgr }
+    pub fn new(
+        runtime: M,
+        id_mgr: I,
+        settings: WatchdogSettings,
+        status: WatchdogStatus,
+    ) -> Self {
+        Watchdog {
+            runtime,
+            id_mgr,
+            settings,
+            status,
+        }
     }
 
     // Start the edge runtime module (EdgeAgent). This also updates the identity of the module (module_id)
@@ -58,7 +333,14 @@ where
         let id_mgr = self.id_mgr.clone();
         let module_id = module_id.to_string();
 
-        let watchdog = start_watchdog(runtime, id_mgr, spec, module_id);
+        let watchdog = start_watchdog(
+            runtime,
+            id_mgr,
+            spec,
+            module_id,
+            self.settings,
+            self.status,
+        );
 
         // Swallow any errors from shutdown_signal
         let shutdown_signal = shutdown_signal.then(|_| Ok(()));
@@ -89,16 +371,19 @@ where
         .map_err(|e| e.into())
 }
 
-// Start watchdog on a timer for 1 minute
+// Start watchdog on a timer with the configured frequency, checking earlier
+// when a restart attempt is due before the next check
 pub fn start_watchdog<M, I>(
     runtime: M,
     id_mgr: I,
     spec: ModuleSpec<<M::Module as Module>::Config>,
     module_id: String,
+    settings: WatchdogSettings,
+    status: WatchdogStatus,
 ) -> impl Future<Item = (), Error = Error>
 where
     M: 'static + ModuleRuntime + Clone,
-    <M::Module as Module>::Config: Clone,
+    <M::Module as Module>::Config: Clone + Serialize,
     M::Error: Into<Error>,
     <M::Module as Module>::Error: Into<Error>,
     I: 'static + IdentityManager + Clone,
@@ -106,63 +391,264 @@ where
 {
     info!(
         "Starting watchdog with {} second frequency...",
-        WATCHDOG_FREQUENCY_SECS
+        settings.frequency().as_secs()
     );
-    Interval::new(Instant::now(), Duration::from_secs(WATCHDOG_FREQUENCY_SECS))
-        .map_err(Error::from)
-        .for_each(move |_| {
-            info!("Checking edge runtime status");
-            check_runtime(
-                runtime.clone(),
-                id_mgr.clone(),
-                spec.clone(),
-                module_id.clone(),
-            ).or_else(|e| {
-                warn!("Error in watchdog when checking for edge runtime status:");
-                log_failure(Level::Warn, &e);
-                future::ok(())
-            })
+    let frequency = settings.frequency();
+    let policy = settings.restart_policy().clone();
+    let desired_hash = Arc::new(Mutex::new(None));
+    future::loop_fn((), move |()| {
+        info!("Checking edge runtime status");
+        let status_copy = status.clone();
+        check_runtime(
+            runtime.clone(),
+            id_mgr.clone(),
+            spec.clone(),
+            module_id.clone(),
+            status.clone(),
+            &policy,
+            desired_hash.clone(),
+        ).or_else(|e| {
+            warn!("Error in watchdog when checking for edge runtime status:");
+            log_failure(Level::Warn, &e);
+            future::ok(())
+        }).and_then(move |()| {
+            let next_check = status_copy.state().next_check(Utc::now(), frequency);
+            Delay::new(Instant::now() + next_check)
+                .map_err(Error::from)
+                .map(|()| Loop::Continue(()))
         })
+    })
 }
 
-// Check if the edge runtime module is running, and if not, start it.
+// Check if the edge runtime module was created from the current spec, and
+// recreate it if not. Otherwise make sure it is running.
 fn check_runtime<M, I>(
     runtime: M,
     id_mgr: I,
     spec: ModuleSpec<<M::Module as Module>::Config>,
     module_id: String,
+    status: WatchdogStatus,
+    policy: &RestartPolicy,
+    desired_hash: Arc<Mutex<Option<String>>>,
 ) -> impl Future<Item = (), Error = Error>
 where
     M: 'static + ModuleRuntime + Clone,
-    <M::Module as Module>::Config: Clone,
+    <M::Module as Module>::Config: Clone + Serialize,
     M::Error: Into<Error>,
     <M::Module as Module>::Error: Into<Error>,
     I: 'static + IdentityManager + Clone,
     I::Error: Into<Error>,
 {
     let module = spec.name().to_string();
+    let policy = policy.clone();
+    let id_mgr_copy = id_mgr.clone();
+    let spec_copy = spec.clone();
+    let module_id_copy = module_id.clone();
+    let desired_hash_copy = desired_hash.clone();
     get_edge_runtime_mod(&runtime, module.clone())
+        .and_then(move |m| {
+            let current = m
+                .as_ref()
+                .and_then(|m| m.spec_hash())
+                .map(|hash| hash.to_string());
+            match current {
+                // Modules the watchdog did not create are left alone.
+                None => Either::A(future::ok((m, false))),
+                Some(current) => Either::B(
+                    desired_spec_hash(&id_mgr_copy, spec_copy, &module_id_copy, &desired_hash_copy)
+                        .then(move |desired| match desired {
+                            Ok(desired) => Ok((m, current != desired)),
+                            Err(e) => {
+                                warn!("Could not compute the edge runtime spec hash:");
+                                log_failure(Level::Warn, &e);
+                                Ok((m, false))
+                            }
+                        }),
+                ),
+            }
+        }).and_then(move |(m, drifted)| {
+            if drifted {
+                let now = Utc::now();
+                if !begin_restart(&status, &policy, now) {
+                    return Either::A(Either::A(future::ok(())));
+                }
+                info!(
+                    "Edge runtime module {} does not match its spec, recreating it...",
+                    module
+                );
+                let runtime_copy = runtime.clone();
+                let recreate = runtime
+                    .remove(&module)
+                    .map_err(|e| e.into())
+                    .and_then(move |_| {
+                        create_and_start(runtime_copy, &id_mgr, spec, &module_id, &desired_hash)
+                    }).map(move |_| status.update(|s| s.record_restart(now)));
+                Either::A(Either::B(recreate))
+            } else {
+                Either::B(check_status(
+                    runtime,
+                    id_mgr,
+                    spec,
+                    module_id,
+                    status,
+                    policy,
+                    desired_hash,
+                    m,
+                ))
+            }
+        })
+}
+
+// Check if the edge runtime module is running, and if not, start it unless
+// the restart policy asks to back off for a while. Paused and restarting
+// modules are left alone.
+#[cfg_attr(feature = "cargo-clippy", allow(too_many_arguments))]
+fn check_status<M, I>(
+    runtime: M,
+    id_mgr: I,
+    spec: ModuleSpec<<M::Module as Module>::Config>,
+    module_id: String,
+    status: WatchdogStatus,
+    policy: RestartPolicy,
+    desired_hash: Arc<Mutex<Option<String>>>,
+    module: Option<M::Module>,
+) -> impl Future<Item = (), Error = Error>
+where
+    M: 'static + ModuleRuntime + Clone,
+    <M::Module as Module>::Config: Clone + Serialize,
+    M::Error: Into<Error>,
+    <M::Module as Module>::Error: Into<Error>,
+    I: 'static + IdentityManager + Clone,
+    I::Error: Into<Error>,
+{
+    let name = spec.name().to_string();
+    future::ok(module)
         .and_then(|m| m.map(|m| m.runtime_state().map_err(|e| e.into())))
         .and_then(move |state| {
-            state
-                .map(|state| {
-                    let res = match *state.status() {
-                        ModuleStatus::Running => {
-                            info!("Edge runtime is running.");
-                            future::Either::A(future::ok(()))
-                        }
-                        _ => {
-                            info!(
-                                "Edge runtime status is {}, starting module now...",
-                                *state.status()
-                            );
-                            future::Either::B(runtime.start(&module).map_err(|e| e.into()))
-                        }
-                    };
-                    Either::A(res)
-                })
-                .unwrap_or_else(|| Either::B(create_and_start(runtime, &id_mgr, spec, &module_id)))
-        }).map(|_| ())
+            let running = state.as_ref().and_then(|state| match *state.status() {
+                ModuleStatus::Running => Some(state.started_at().cloned()),
+                _ => None,
+            });
+            if let Some(started_at) = running {
+                info!("Edge runtime is running.");
+                status.update(|s| s.record_running(Utc::now(), started_at, &policy));
+                return Either::A(future::ok(()));
+            }
+
+            // A paused module was paused on purpose, e.g. by an operator, and
+            // one that is restarting is already being taken care of by the
+            // module runtime. Neither counts as a failure.
+            match state.as_ref().map(|state| state.status()) {
+                Some(&ModuleStatus::Paused) => {
+                    info!("Edge runtime is paused, leaving it paused.");
+                    return Either::A(future::ok(()));
+                }
+                Some(&ModuleStatus::Restarting) | Some(&ModuleStatus::Backoff) => {
+                    info!("Edge runtime is being restarted by the module runtime.");
+                    return Either::A(future::ok(()));
+                }
+                _ => (),
+            }
+
+            let now = Utc::now();
+            if !begin_restart(&status, &policy, now) {
+                return Either::A(future::ok(()));
+            }
+
+            let start = match state {
+                Some(state) => {
+                    info!(
+                        "Edge runtime status is {}, starting module now...",
+                        *state.status()
+                    );
+                    Either::A(runtime.start(&name).map_err(|e| e.into()))
+                }
+                None => Either::B(create_and_start(
+                    runtime,
+                    &id_mgr,
+                    spec,
+                    &module_id,
+                    &desired_hash,
+                )),
+            };
+            Either::B(start.map(move |_| status.update(|s| s.record_restart(now))))
+        })
+}
+
+// Whether the restart policy lets the watchdog start or recreate the edge
+// runtime module now. Every attempt counts as a failure until the module has
+// kept running for the minimum uptime.
+fn begin_restart(status: &WatchdogStatus, policy: &RestartPolicy, now: DateTime<Utc>) -> bool {
+    let current = status.state();
+    if !current.can_attempt(now) {
+        info!(
+            "Edge runtime needs to be restarted, next attempt at {} ({} failures)",
+            current
+                .next_attempt()
+                .map(|t| t.to_rfc3339())
+                .unwrap_or_default(),
+            current.consecutive_failures()
+        );
+        return false;
+    }
+
+    status.update(|s| s.record_failure(now, policy));
+    let current = status.state();
+    if current.crash_loop() {
+        warn!(
+            "Edge runtime is crash looping, {} consecutive failures",
+            current.consecutive_failures()
+        );
+    }
+    true
+}
+
+// The spec the watchdog creates the edge runtime module from: the generation
+// ID of the module identity is added to the environment, and the runtime is
+// asked to record the hash of the spec. A module without that hash was
+// created by someone else, like edgeAgent updating itself through the
+// management API, and is left alone.
+fn desired_spec<T>(spec: ModuleSpec<T>, generation_id: &str) -> ModuleSpec<T> {
+    let mut env = spec.env().clone();
+    env.insert(MODULE_GENERATIONID.to_string(), generation_id.to_string());
+    spec.with_env(env).with_record_spec_hash(true)
+}
+
+// Hash of the spec the edge runtime module should have been created from.
+// Looking it up needs the generation ID of the module identity, so the result
+// is cached until the watchdog creates the module again.
+fn desired_spec_hash<I, T>(
+    id_mgr: &I,
+    spec: ModuleSpec<T>,
+    module_id: &str,
+    desired_hash: &Arc<Mutex<Option<String>>>,
+) -> impl Future<Item = String, Error = Error>
+where
+    I: 'static + IdentityManager + Clone,
+    I::Error: Into<Error>,
+    T: Serialize,
+{
+    let cached = desired_hash
+        .lock()
+        .expect("spec hash lock poisoned")
+        .clone();
+    match cached {
+        Some(hash) => Either::A(future::ok(hash)),
+        None => {
+            let desired_hash = desired_hash.clone();
+            let lookup = id_mgr
+                .get(IdentitySpec::new(module_id))
+                .map_err(|e| e.into())
+                .and_then(move |identity| {
+                    let identity = identity
+                        .ok_or_else(|| Error::from(ErrorKind::EdgeRuntimeIdentityNotFound))?;
+                    let hash = desired_spec(spec, identity.generation_id()).spec_hash()?;
+                    *desired_hash.lock().expect("spec hash lock poisoned") = Some(hash.clone());
+                    Ok(hash)
+                });
+            Either::B(lookup)
+        }
+    }
 }
 
 // Gets the edge runtime module, if it exists.
@@ -223,10 +709,11 @@ fn create_and_start<M, I>(
     id_mgr: &I,
     spec: ModuleSpec<<M::Module as Module>::Config>,
     module_id: &str,
+    desired_hash: &Arc<Mutex<Option<String>>>,
 ) -> impl Future<Item = (), Error = Error>
 where
     M: 'static + ModuleRuntime + Clone,
-    <M::Module as Module>::Config: Clone,
+    <M::Module as Module>::Config: Clone + Serialize,
     M::Error: Into<Error>,
     <M::Module as Module>::Error: Into<Error>,
     I: 'static + IdentityManager + Clone,
@@ -235,16 +722,13 @@ where
     let module_name = spec.name().to_string();
     info!("Creating and starting edge runtime module {}", module_name);
     let runtime_copy = runtime.clone();
+    let desired_hash = desired_hash.clone();
 
     let mut id_mgr = id_mgr.clone();
-    update_identity(&mut id_mgr, module_id).and_then(|id| {
+    update_identity(&mut id_mgr, module_id).and_then(move |id| {
         // add the generation ID for edge agent as an environment variable
-        let mut env = spec.env().clone();
-        env.insert(
-            MODULE_GENERATIONID.to_string(),
-            id.generation_id().to_string(),
-        );
-        let spec = spec.with_env(env);
+        let spec = desired_spec(spec, id.generation_id());
+        *desired_hash.lock().expect("spec hash lock poisoned") = spec.spec_hash().ok();
         runtime
             .registry()
             .pull(spec.clone().config())
@@ -258,12 +742,19 @@ where
 mod tests {
     use super::*;
     use std::cell::RefCell;
+    use std::collections::HashMap;
     use std::rc::Rc;
 
     use futures::future::{self, FutureResult};
+    use futures::stream::Empty;
 
     use error::{Error as CoreError, ErrorKind as CoreErrorKind};
     use identity::{AuthType, Identity, IdentityManager, IdentitySpec};
+    use module::{
+        ArchiveStream, EventOptions, ExecOptions, Image, LogOptions, ModuleEvent,
+        ModuleRuntimeState, ModuleStats, PruneOptions, PruneResult, StatsOptions, SystemInfo,
+        Volume,
+    };
 
     #[derive(Clone, Debug, Fail)]
     pub enum Error {
@@ -449,6 +940,614 @@ mod tests {
         }
     }
 
+    #[derive(Clone)]
+    struct TestModule {
+        name: String,
+        image: String,
+        spec_hash: Option<String>,
+        status: ModuleStatus,
+    }
+
+    impl Module for TestModule {
+        type Config = String;
+        type Error = Error;
+        type RuntimeStateFuture = FutureResult<ModuleRuntimeState, Self::Error>;
+
+        fn name(&self) -> &str {
+            &self.name
+        }
+
+        fn type_(&self) -> &str {
+            "test"
+        }
+
+        fn config(&self) -> &Self::Config {
+            &self.image
+        }
+
+        fn runtime_state(&self) -> Self::RuntimeStateFuture {
+            future::ok(ModuleRuntimeState::default().with_status(self.status.clone()))
+        }
+
+        fn spec_hash(&self) -> Option<&str> {
+            self.spec_hash.as_ref().map(AsRef::as_ref)
+        }
+    }
+
+    #[derive(Default)]
+    struct RuntimeState {
+        modules: Vec<TestModule>,
+        calls: Vec<String>,
+    }
+
+    // Keeps the modules in memory and records the calls that change them.
+    #[derive(Clone, Default)]
+    struct TestRuntime {
+        state: Rc<RefCell<RuntimeState>>,
+    }
+
+    impl TestRuntime {
+        fn with_module(self, name: &str, spec_hash: Option<String>, status: ModuleStatus) -> Self {
+            self.state.borrow_mut().modules.push(TestModule {
+                name: name.to_string(),
+                image: String::new(),
+                spec_hash,
+                status,
+            });
+            self
+        }
+
+        fn module(&self, name: &str) -> Option<TestModule> {
+            self.state
+                .borrow()
+                .modules
+                .iter()
+                .find(|m| m.name == name)
+                .cloned()
+        }
+
+        fn calls(&self) -> Vec<String> {
+            self.state.borrow().calls.clone()
+        }
+
+        fn set_status(&self, name: &str, status: ModuleStatus, call: &str) {
+            let mut state = self.state.borrow_mut();
+            state.calls.push(format!("{} {}", call, name));
+            for module in state.modules.iter_mut().filter(|m| m.name == name) {
+                module.status = status.clone();
+            }
+        }
+    }
+
+    macro_rules! notimpl_error {
+        () => {
+            future::err(Error::General)
+        };
+    }
+
+    impl ModuleRegistry for TestRuntime {
+        type Config = String;
+        type Error = Error;
+        type PullFuture = FutureResult<(), Self::Error>;
+        type RemoveFuture = FutureResult<(), Self::Error>;
+        type ListImagesFuture = FutureResult<Vec<Image>, Self::Error>;
+        type PruneImagesFuture = FutureResult<PruneResult, Self::Error>;
+        type LoadImagesFuture = FutureResult<(), Self::Error>;
+        type SaveImageFuture = FutureResult<Self::ImageArchive, Self::Error>;
+        type ImageChunk = String;
+        type ImageArchive = Empty<Self::ImageChunk, Self::Error>;
+
+        fn pull(&self, _config: &Self::Config) -> Self::PullFuture {
+            future::ok(())
+        }
+        fn remove(&self, _name: &str) -> Self::RemoveFuture {
+            notimpl_error!()
+        }
+        fn list_images(&self) -> Self::ListImagesFuture {
+            notimpl_error!()
+        }
+        fn prune_images(&self, _options: &PruneOptions) -> Self::PruneImagesFuture {
+            notimpl_error!()
+        }
+        fn load_images(&self, _archive: ArchiveStream) -> Self::LoadImagesFuture {
+            notimpl_error!()
+        }
+        fn save_image(&self, _name: &str) -> Self::SaveImageFuture {
+            notimpl_error!()
+        }
+    }
+
+    impl ModuleRuntime for TestRuntime {
+        type Error = Error;
+        type Config = String;
+        type Module = TestModule;
+        type ModuleRegistry = Self;
+        type Chunk = String;
+        type Logs = Empty<Self::Chunk, Self::Error>;
+        type Stats = Empty<ModuleStats, Self::Error>;
+        type Events = Empty<ModuleEvent, Self::Error>;
+
+        type CreateFuture = FutureResult<(), Self::Error>;
+        type EventsFuture = FutureResult<Self::Events, Self::Error>;
+        type ExecFuture = FutureResult<Self::Logs, Self::Error>;
+        type GetArchiveFuture = FutureResult<Self::Logs, Self::Error>;
+        type InitFuture = FutureResult<(), Self::Error>;
+        type ListFuture = FutureResult<Vec<Self::Module>, Self::Error>;
+        type LogsFuture = FutureResult<Self::Logs, Self::Error>;
+        type RemoveFuture = FutureResult<(), Self::Error>;
+        type PauseFuture = FutureResult<(), Self::Error>;
+        type PutArchiveFuture = FutureResult<(), Self::Error>;
+        type RestartFuture = FutureResult<(), Self::Error>;
+        type StatsFuture = FutureResult<Self::Stats, Self::Error>;
+        type StartFuture = FutureResult<(), Self::Error>;
+        type StopFuture = FutureResult<(), Self::Error>;
+        type UnpauseFuture = FutureResult<(), Self::Error>;
+        type SystemInfoFuture = FutureResult<SystemInfo, Self::Error>;
+        type RemoveAllFuture = FutureResult<(), Self::Error>;
+        type ListVolumesFuture = FutureResult<Vec<Volume>, Self::Error>;
+        type InspectVolumeFuture = FutureResult<Volume, Self::Error>;
+        type CreateVolumeFuture = FutureResult<Volume, Self::Error>;
+        type RemoveVolumeFuture = FutureResult<(), Self::Error>;
+        type BackupVolumeFuture = FutureResult<Self::Logs, Self::Error>;
+
+        fn init(&self) -> Self::InitFuture {
+            notimpl_error!()
+        }
+
+        fn create(&self, module: ModuleSpec<Self::Config>) -> Self::CreateFuture {
+            let mut state = self.state.borrow_mut();
+            state.calls.push(format!("create {}", module.name()));
+            state.modules.push(TestModule {
+                name: module.name().to_string(),
+                image: module.config().clone(),
+                spec_hash: module.recorded_spec_hash().unwrap(),
+                status: ModuleStatus::Created,
+            });
+            future::ok(())
+        }
+
+        fn start(&self, id: &str) -> Self::StartFuture {
+            self.set_status(id, ModuleStatus::Running, "start");
+            future::ok(())
+        }
+
+        fn stop(&self, _id: &str, _wait_before_kill: Option<Duration>) -> Self::StopFuture {
+            notimpl_error!()
+        }
+
+        fn system_info(&self) -> Self::SystemInfoFuture {
+            notimpl_error!()
+        }
+
+        fn restart(&self, _id: &str) -> Self::RestartFuture {
+            notimpl_error!()
+        }
+
+        fn pause(&self, _id: &str) -> Self::PauseFuture {
+            notimpl_error!()
+        }
+
+        fn unpause(&self, _id: &str) -> Self::UnpauseFuture {
+            notimpl_error!()
+        }
+
+        fn remove(&self, id: &str) -> Self::RemoveFuture {
+            let mut state = self.state.borrow_mut();
+            state.calls.push(format!("remove {}", id));
+            state.modules.retain(|m| m.name != id);
+            future::ok(())
+        }
+
+        fn remove_with_volumes(&self, _id: &str) -> Self::RemoveFuture {
+            notimpl_error!()
+        }
+
+        fn list(&self) -> Self::ListFuture {
+            future::ok(self.state.borrow().modules.clone())
+        }
+
+        fn logs(&self, _id: &str, _options: &LogOptions) -> Self::LogsFuture {
+            notimpl_error!()
+        }
+
+        fn stats(&self, _id: &str, _options: &StatsOptions) -> Self::StatsFuture {
+            notimpl_error!()
+        }
+
+        fn events(&self, _options: &EventOptions) -> Self::EventsFuture {
+            notimpl_error!()
+        }
+
+        fn exec(&self, _id: &str, _options: &ExecOptions) -> Self::ExecFuture {
+            notimpl_error!()
+        }
+
+        fn get_archive(&self, _id: &str, _path: &str) -> Self::GetArchiveFuture {
+            notimpl_error!()
+        }
+
+        fn put_archive(
+            &self,
+            _id: &str,
+            _path: &str,
+            _archive: ArchiveStream,
+        ) -> Self::PutArchiveFuture {
+            notimpl_error!()
+        }
+
+        fn registry(&self) -> &Self::ModuleRegistry {
+            self
+        }
+
+        fn remove_all(&self) -> Self::RemoveAllFuture {
+            notimpl_error!()
+        }
+
+        fn list_volumes(&self) -> Self::ListVolumesFuture {
+            notimpl_error!()
+        }
+
+        fn inspect_volume(&self, _name: &str) -> Self::InspectVolumeFuture {
+            notimpl_error!()
+        }
+
+        fn create_volume(&self, _name: &str, _module: Option<&str>) -> Self::CreateVolumeFuture {
+            notimpl_error!()
+        }
+
+        fn remove_volume(&self, _name: &str) -> Self::RemoveVolumeFuture {
+            notimpl_error!()
+        }
+
+        fn backup_volume(&self, _name: &str) -> Self::BackupVolumeFuture {
+            notimpl_error!()
+        }
+    }
+
+    fn agent_spec(image: &str) -> ModuleSpec<String> {
+        ModuleSpec::new("edgeAgent", "test", image.to_string(), HashMap::new()).unwrap()
+    }
+
+    // Hash of the spec as recorded by the watchdog when it created the agent.
+    fn created_hash(image: &str) -> Option<String> {
+        desired_spec(agent_spec(image), "1").spec_hash().ok()
+    }
+
+    fn check(runtime: &TestRuntime, image: &str, status: &WatchdogStatus) {
+        let manager = TestIdentityManager::new(vec![TestIdentity::new(
+            "$edgeAgent",
+            "iotedge",
+            "1",
+            AuthType::Sas,
+        )]);
+        check_runtime(
+            runtime.clone(),
+            manager,
+            agent_spec(image),
+            "$edgeAgent".to_string(),
+            status.clone(),
+            &RestartPolicy::default(),
+            Arc::new(Mutex::new(None)),
+        ).wait()
+        .unwrap();
+    }
+
+    #[test]
+    fn check_runtime_creates_missing_agent() {
+        let runtime = TestRuntime::default();
+        let status = WatchdogStatus::new();
+
+        check(&runtime, "agent:1.0", &status);
+
+        assert_eq!(vec!["create edgeAgent", "start edgeAgent"], runtime.calls());
+        let agent = runtime.module("edgeAgent").unwrap();
+        assert_eq!(created_hash("agent:1.0"), agent.spec_hash);
+        assert_eq!(1, status.state().restart_count());
+    }
+
+    #[test]
+    fn check_runtime_recreates_agent_created_from_other_spec() {
+        let runtime = TestRuntime::default().with_module(
+            "edgeAgent",
+            created_hash("agent:1.0"),
+            ModuleStatus::Running,
+        );
+        let status = WatchdogStatus::new();
+
+        check(&runtime, "agent:1.1", &status);
+
+        assert_eq!(
+            vec!["remove edgeAgent", "create edgeAgent", "start edgeAgent"],
+            runtime.calls()
+        );
+        let agent = runtime.module("edgeAgent").unwrap();
+        assert_eq!("agent:1.1", agent.image);
+        assert_eq!(created_hash("agent:1.1"), agent.spec_hash);
+        assert_eq!(ModuleStatus::Running, agent.status);
+        assert_eq!(1, status.state().restart_count());
+    }
+
+    #[test]
+    fn check_runtime_leaves_agent_matching_spec_alone() {
+        let runtime = TestRuntime::default().with_module(
+            "edgeAgent",
+            created_hash("agent:1.0"),
+            ModuleStatus::Running,
+        );
+        let status = WatchdogStatus::new();
+
+        check(&runtime, "agent:1.0", &status);
+
+        assert!(runtime.calls().is_empty());
+        assert_eq!(0, status.state().restart_count());
+    }
+
+    #[test]
+    fn check_runtime_leaves_agent_updated_via_mgmt_alone() {
+        // edgeAgent updates itself through the management API, which creates
+        // the module from the deployment's spec
+        let runtime = TestRuntime::default();
+        runtime.create(agent_spec("agent:2.0")).wait().unwrap();
+        runtime.start("edgeAgent").wait().unwrap();
+        let status = WatchdogStatus::new();
+
+        check(&runtime, "agent:1.0", &status);
+
+        assert_eq!(vec!["create edgeAgent", "start edgeAgent"], runtime.calls());
+        let agent = runtime.module("edgeAgent").unwrap();
+        assert_eq!("agent:2.0", agent.image);
+        assert_eq!(None, agent.spec_hash);
+        assert_eq!(0, status.state().restart_count());
+    }
+
+    #[test]
+    fn check_runtime_leaves_paused_agent_alone() {
+        let runtime = TestRuntime::default().with_module(
+            "edgeAgent",
+            created_hash("agent:1.0"),
+            ModuleStatus::Paused,
+        );
+        let status = WatchdogStatus::new();
+
+        check(&runtime, "agent:1.0", &status);
+
+        assert!(runtime.calls().is_empty());
+        let agent = runtime.module("edgeAgent").unwrap();
+        assert_eq!(ModuleStatus::Paused, agent.status);
+        assert_eq!(0, status.state().restart_count());
+    }
+
+    #[test]
+    fn check_runtime_leaves_restarting_agent_alone() {
+        let runtime = TestRuntime::default().with_module(
+            "edgeAgent",
+            created_hash("agent:1.0"),
+            ModuleStatus::Restarting,
+        );
+        let status = WatchdogStatus::new();
+
+        check(&runtime, "agent:1.0", &status);
+
+        assert!(runtime.calls().is_empty());
+        assert_eq!(0, status.state().restart_count());
+    }
+
+    #[test]
+    fn check_runtime_starts_stopped_agent() {
+        let runtime = TestRuntime::default().with_module(
+            "edgeAgent",
+            created_hash("agent:1.0"),
+            ModuleStatus::Stopped,
+        );
+        let status = WatchdogStatus::new();
+
+        check(&runtime, "agent:1.0", &status);
+
+        assert_eq!(vec!["start edgeAgent"], runtime.calls());
+        assert_eq!(1, status.state().restart_count());
+    }
+
+    #[test]
+    fn backoff_doubles_up_to_max() {
+        let policy = RestartPolicy::new(10, 300, 5, 60);
+        assert_eq!(Duration::from_secs(10), policy.backoff(1));
+        assert_eq!(Duration::from_secs(20), policy.backoff(2));
+        assert_eq!(Duration::from_secs(160), policy.backoff(5));
+        assert_eq!(Duration::from_secs(300), policy.backoff(6));
+        assert_eq!(Duration::from_secs(300), policy.backoff(u32::max_value()));
+    }
+
+    #[test]
+    fn settings_defaults() {
+        let settings: WatchdogSettings = ::serde_json::from_str("{}").unwrap();
+        assert_eq!(WatchdogSettings::default(), settings);
+        assert_eq!(Duration::from_secs(60), settings.frequency());
+        assert_eq!(5, settings.restart_policy().crash_loop_threshold());
+        assert_eq!(
+            Duration::from_secs(60),
+            settings.restart_policy().min_uptime()
+        );
+    }
+
+    #[test]
+    fn settings_reject_zero_frequency() {
+        let settings: Result<WatchdogSettings, _> =
+            ::serde_json::from_str(r#"{ "frequency_secs": 0 }"#);
+        assert!(settings.is_err());
+    }
+
+    #[test]
+    fn state_backs_off_after_failure() {
+        let policy = RestartPolicy::new(10, 300, 2, 60);
+        let now = Utc::now();
+        let mut state = WatchdogState::default();
+        assert!(state.can_attempt(now));
+
+        state.record_failure(now, &policy);
+        state.record_restart(now);
+        assert_eq!(1, state.consecutive_failures());
+        assert_eq!(1, state.restart_count());
+        assert_eq!(Some(&now), state.last_restart());
+        assert!(!state.crash_loop());
+        assert!(!state.can_attempt(now + ChronoDuration::seconds(5)));
+        assert!(state.can_attempt(now + ChronoDuration::seconds(10)));
+
+        let later = now + ChronoDuration::seconds(10);
+        state.record_failure(later, &policy);
+        assert_eq!(2, state.consecutive_failures());
+        assert!(state.crash_loop());
+        assert_eq!(
+            Some(&(later + ChronoDuration::seconds(20))),
+            state.next_attempt()
+        );
+    }
+
+    #[test]
+    fn state_next_check_at_backoff_deadline() {
+        let policy = RestartPolicy::new(10, 300, 5, 60);
+        let frequency = Duration::from_secs(60);
+        let now = Utc::now();
+        let mut state = WatchdogState::default();
+        assert_eq!(frequency, state.next_check(now, frequency));
+
+        state.record_failure(now, &policy);
+        assert_eq!(Duration::from_secs(10), state.next_check(now, frequency));
+        assert_eq!(
+            Duration::from_secs(4),
+            state.next_check(now + ChronoDuration::seconds(6), frequency)
+        );
+        assert_eq!(
+            frequency,
+            state.next_check(now + ChronoDuration::seconds(11), frequency)
+        );
+
+        for _ in 0..4 {
+            state.record_failure(now, &policy);
+        }
+        assert_eq!(frequency, state.next_check(now, frequency));
+    }
+
+    #[test]
+    fn state_keeps_failures_until_min_uptime() {
+        let policy = RestartPolicy::default();
+        let now = Utc::now();
+        let mut state = WatchdogState::default();
+        state.record_failure(now, &policy);
+        state.record_restart(now);
+
+        state.record_running(now + ChronoDuration::seconds(30), None, &policy);
+        assert_eq!(1, state.consecutive_failures());
+
+        let started_at = now + ChronoDuration::seconds(50);
+        state.record_running(now + ChronoDuration::seconds(70), Some(started_at), &policy);
+        assert_eq!(1, state.consecutive_failures());
+
+        state.record_running(
+            now + ChronoDuration::seconds(110),
+            Some(started_at),
+            &policy,
+        );
+        assert_eq!(0, state.consecutive_failures());
+    }
+
+    #[test]
+    fn state_resets_when_running() {
+        let policy = RestartPolicy::default();
+        let now = Utc::now();
+        let mut state = WatchdogState::default();
+        for _ in 0..6 {
+            state.record_failure(now, &policy);
+            state.record_restart(now);
+        }
+        assert!(state.crash_loop());
+
+        state.record_running(now + ChronoDuration::seconds(60), None, &policy);
+        assert_eq!(0, state.consecutive_failures());
+        assert_eq!(6, state.restart_count());
+        assert!(!state.crash_loop());
+        assert_eq!(None, state.next_attempt());
+        assert!(state.can_attempt(now));
+    }
+
+    #[test]
+    fn begin_restart_follows_backoff() {
+        let policy = RestartPolicy::new(10, 300, 5, 60);
+        let status = WatchdogStatus::new();
+        let now = Utc::now();
+        assert!(begin_restart(&status, &policy, now));
+        assert!(!begin_restart(
+            &status,
+            &policy,
+            now + ChronoDuration::seconds(5)
+        ));
+        assert_eq!(1, status.state().consecutive_failures());
+
+        assert!(begin_restart(
+            &status,
+            &policy,
+            now + ChronoDuration::seconds(10)
+        ));
+        assert_eq!(2, status.state().consecutive_failures());
+    }
+
+    #[test]
+    fn status_clones_share_state() {
+        let status = WatchdogStatus::new();
+        let copy = status.clone();
+        status.update(|s| s.record_restart(Utc::now()));
+        assert_eq!(1, copy.state().restart_count());
+    }
+
+    #[test]
+    fn desired_spec_hash_includes_generation_id() {
+        let manager = TestIdentityManager::new(vec![TestIdentity::new(
+            "$edgeAgent",
+            "iotedge",
+            "1",
+            AuthType::Sas,
+        )]);
+        let spec = ModuleSpec::new("edgeAgent", "docker", "agent:1.0".to_string(), HashMap::new())
+            .unwrap();
+        let cache = Arc::new(Mutex::new(None));
+
+        let hash = desired_spec_hash(&manager, spec.clone(), "$edgeAgent", &cache)
+            .wait()
+            .unwrap();
+
+        let expected = desired_spec(spec.clone(), "1").spec_hash().unwrap();
+        assert_eq!(expected, hash);
+        assert_ne!(spec.spec_hash().unwrap(), hash);
+        assert_eq!(Some(expected), *cache.lock().unwrap());
+    }
+
+    #[test]
+    fn desired_spec_hash_uses_cache() {
+        let manager = TestIdentityManager::new(vec![]).with_fail_get(true);
+        let spec = ModuleSpec::new("edgeAgent", "docker", "agent:1.0".to_string(), HashMap::new())
+            .unwrap();
+        let cache = Arc::new(Mutex::new(Some("cached".to_string())));
+
+        let hash = desired_spec_hash(&manager, spec, "$edgeAgent", &cache)
+            .wait()
+            .unwrap();
+        assert_eq!("cached", hash);
+    }
+
+    #[test]
+    fn desired_spec_hash_identity_not_found() {
+        let manager = TestIdentityManager::new(vec![]);
+        let spec = ModuleSpec::new("edgeAgent", "docker", "agent:1.0".to_string(), HashMap::new())
+            .unwrap();
+        let cache = Arc::new(Mutex::new(None));
+
+        assert!(
+            desired_spec_hash(&manager, spec, "$edgeAgent", &cache)
+                .wait()
+                .is_err()
+        );
+        assert_eq!(None, *cache.lock().unwrap());
+    }
+
     #[test]
     fn update_identity_get_fails() {
         let mut manager = TestIdentityManager::new(vec![]).with_fail_get(true);
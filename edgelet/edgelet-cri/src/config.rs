@@ -0,0 +1,243 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+use std::collections::HashMap;
+
+use error::Result;
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CriConfig {
+    image: String,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    command: Vec<String>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    args: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    working_dir: Option<String>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    mounts: Vec<MountConfig>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    labels: HashMap<String, String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    auth: Option<AuthConfig>,
+}
+
+impl CriConfig {
+    pub fn new(image: &str) -> Result<CriConfig> {
+        let config = CriConfig {
+            image: ensure_not_empty!(image.to_string()),
+            command: Vec::new(),
+            args: Vec::new(),
+            working_dir: None,
+            mounts: Vec::new(),
+            labels: HashMap::new(),
+            auth: None,
+        };
+        Ok(config)
+    }
+
+    pub fn image(&self) -> &str {
+        &self.image
+    }
+
+    /// Overrides the entrypoint of the image.
+    pub fn command(&self) -> &[String] {
+        &self.command
+    }
+
+    pub fn with_command(mut self, command: Vec<String>) -> Self {
+        self.command = command;
+        self
+    }
+
+    pub fn args(&self) -> &[String] {
+        &self.args
+    }
+
+    pub fn with_args(mut self, args: Vec<String>) -> Self {
+        self.args = args;
+        self
+    }
+
+    pub fn working_dir(&self) -> Option<&str> {
+        self.working_dir.as_ref().map(AsRef::as_ref)
+    }
+
+    pub fn with_working_dir(mut self, working_dir: String) -> Self {
+        self.working_dir = Some(working_dir);
+        self
+    }
+
+    pub fn mounts(&self) -> &[MountConfig] {
+        &self.mounts
+    }
+
+    pub fn with_mounts(mut self, mounts: Vec<MountConfig>) -> Self {
+        self.mounts = mounts;
+        self
+    }
+
+    pub fn add_mount(&mut self, mount: MountConfig) {
+        self.mounts.push(mount);
+    }
+
+    pub fn labels(&self) -> &HashMap<String, String> {
+        &self.labels
+    }
+
+    pub fn with_labels(mut self, labels: HashMap<String, String>) -> Self {
+        self.labels = labels;
+        self
+    }
+
+    /// Credentials used to pull the image.
+    pub fn auth(&self) -> Option<&AuthConfig> {
+        self.auth.as_ref()
+    }
+
+    pub fn with_auth(mut self, auth: AuthConfig) -> Self {
+        self.auth = Some(auth);
+        self
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct MountConfig {
+    host_path: String,
+    container_path: String,
+    #[serde(default)]
+    read_only: bool,
+}
+
+impl MountConfig {
+    pub fn new(host_path: String, container_path: String) -> Self {
+        MountConfig {
+            host_path,
+            container_path,
+            read_only: false,
+        }
+    }
+
+    pub fn host_path(&self) -> &str {
+        &self.host_path
+    }
+
+    pub fn container_path(&self) -> &str {
+        &self.container_path
+    }
+
+    pub fn read_only(&self) -> bool {
+        self.read_only
+    }
+
+    pub fn with_read_only(mut self, read_only: bool) -> Self {
+        self.read_only = read_only;
+        self
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct AuthConfig {
+    username: String,
+    password: String,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "String::is_empty")]
+    server_address: String,
+}
+
+impl AuthConfig {
+    pub fn new(username: String, password: String) -> Self {
+        AuthConfig {
+            username,
+            password,
+            server_address: String::new(),
+        }
+    }
+
+    pub fn username(&self) -> &str {
+        &self.username
+    }
+
+    pub fn password(&self) -> &str {
+        &self.password
+    }
+
+    pub fn server_address(&self) -> &str {
+        &self.server_address
+    }
+
+    pub fn with_server_address(mut self, server_address: String) -> Self {
+        self.server_address = server_address;
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json;
+
+    #[test]
+    #[should_panic]
+    fn empty_image_fails() {
+        CriConfig::new("").unwrap();
+    }
+
+    #[test]
+    #[should_panic]
+    fn white_space_image_fails() {
+        CriConfig::new("    ").unwrap();
+    }
+
+    #[test]
+    fn cri_config_ser() {
+        let config = CriConfig::new("microsoft/azureiotedge-agent:1.0")
+            .unwrap()
+            .with_args(vec!["--verbose".to_string()])
+            .with_mounts(vec![MountConfig::new(
+                "/var/run/iotedge/mgmt.sock".to_string(),
+                "/var/run/iotedge/mgmt.sock".to_string(),
+            ).with_read_only(true)])
+            .with_auth(
+                AuthConfig::new("user".to_string(), "pass".to_string())
+                    .with_server_address("registry.example.com".to_string()),
+            );
+        let actual_json = serde_json::to_string(&config).unwrap();
+        let expected_json = json!({
+            "image": "microsoft/azureiotedge-agent:1.0",
+            "args": ["--verbose"],
+            "mounts": [{
+                "hostPath": "/var/run/iotedge/mgmt.sock",
+                "containerPath": "/var/run/iotedge/mgmt.sock",
+                "readOnly": true
+            }],
+            "auth": {
+                "username": "user",
+                "password": "pass",
+                "serverAddress": "registry.example.com"
+            }
+        });
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&actual_json).unwrap(),
+            expected_json
+        );
+    }
+
+    #[test]
+    fn cri_config_deser_image_only() {
+        let input_json = json!({
+            "image": "microsoft/azureiotedge-agent:1.0"
+        });
+        let config = serde_json::from_str::<CriConfig>(&input_json.to_string()).unwrap();
+        assert_eq!("microsoft/azureiotedge-agent:1.0", config.image());
+        assert!(config.command().is_empty());
+        assert!(config.mounts().is_empty());
+        assert_eq!(None, config.auth());
+    }
+}
@@ -0,0 +1,123 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+use std::fmt;
+use std::fmt::Display;
+use std::io;
+
+use failure::{Backtrace, Context, Fail};
+use grpc::{Error as GrpcError, GrpcStatus};
+use serde_json::Error as SerdeError;
+
+use edgelet_core::{Error as CoreError, ErrorKind as CoreErrorKind};
+use edgelet_utils::Error as UtilsError;
+
+pub type Result<T> = ::std::result::Result<T, Error>;
+
+#[derive(Debug)]
+pub struct Error {
+    inner: Context<ErrorKind>,
+}
+
+#[derive(Debug, Fail)]
+pub enum ErrorKind {
+    #[fail(display = "Utils error")]
+    Utils,
+    #[fail(display = "IO error")]
+    Io,
+    #[fail(display = "Serde error")]
+    Serde,
+    #[fail(display = "CRI request failed")]
+    Grpc,
+    #[fail(display = "Invalid CRI endpoint {}", _0)]
+    InvalidUri(String),
+    #[fail(display = "Not found")]
+    NotFound,
+    #[fail(display = "Conflict with current operation")]
+    Conflict,
+    #[fail(display = "Container already in this state")]
+    NotModified,
+    #[fail(display = "The CRI runtime does not support {}", _0)]
+    NotSupported(&'static str),
+}
+
+impl Fail for Error {
+    fn cause(&self) -> Option<&Fail> {
+        self.inner.cause()
+    }
+
+    fn backtrace(&self) -> Option<&Backtrace> {
+        self.inner.backtrace()
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        Display::fmt(&self.inner, f)
+    }
+}
+
+impl Error {
+    pub fn kind(&self) -> &ErrorKind {
+        self.inner.get_context()
+    }
+}
+
+impl From<ErrorKind> for Error {
+    fn from(kind: ErrorKind) -> Error {
+        Error {
+            inner: Context::new(kind),
+        }
+    }
+}
+
+impl From<Context<ErrorKind>> for Error {
+    fn from(inner: Context<ErrorKind>) -> Error {
+        Error { inner }
+    }
+}
+
+impl From<UtilsError> for Error {
+    fn from(error: UtilsError) -> Error {
+        Error {
+            inner: error.context(ErrorKind::Utils),
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(error: io::Error) -> Error {
+        Error {
+            inner: error.context(ErrorKind::Io),
+        }
+    }
+}
+
+impl From<SerdeError> for Error {
+    fn from(error: SerdeError) -> Error {
+        Error {
+            inner: error.context(ErrorKind::Serde),
+        }
+    }
+}
+
+impl From<GrpcError> for Error {
+    fn from(error: GrpcError) -> Error {
+        let kind = match error {
+            GrpcError::GrpcMessage(ref message)
+                if message.grpc_status == GrpcStatus::NotFound as i32 =>
+            {
+                ErrorKind::NotFound
+            }
+            _ => ErrorKind::Grpc,
+        };
+        Error {
+            inner: error.context(kind),
+        }
+    }
+}
+
+impl From<Error> for CoreError {
+    fn from(err: Error) -> CoreError {
+        CoreError::from(err.context(CoreErrorKind::ModuleRuntime))
+    }
+}
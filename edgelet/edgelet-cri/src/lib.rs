@@ -0,0 +1,45 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+#![deny(warnings)]
+
+extern crate chrono;
+#[macro_use]
+extern crate failure;
+#[macro_use]
+extern crate futures;
+extern crate grpc;
+extern crate hyper;
+#[macro_use]
+extern crate log;
+extern crate protobuf;
+extern crate serde;
+#[macro_use]
+extern crate serde_derive;
+// Need stuff other than macros from serde_json for non-test code.
+#[cfg(not(test))]
+extern crate serde_json;
+extern crate url;
+
+// Need macros from serde_json for unit tests.
+#[cfg(test)]
+#[macro_use]
+extern crate serde_json;
+#[cfg(test)]
+extern crate tempfile;
+
+extern crate cri;
+extern crate edgelet_core;
+#[macro_use]
+extern crate edgelet_utils;
+
+mod config;
+mod error;
+mod logs;
+mod module;
+mod runtime;
+
+pub use config::{AuthConfig, CriConfig, MountConfig};
+pub use error::{Error, ErrorKind};
+pub use logs::{Chunk, Logs};
+pub use module::{CriModule, MODULE_TYPE};
+pub use runtime::{CriModuleRuntime, CriRegistry};
@@ -0,0 +1,204 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+
+use futures::prelude::*;
+use hyper::{Body, Chunk as HyperChunk};
+
+use edgelet_core::LogTail;
+use error::{Error, Result};
+
+const STDOUT_STREAM: u8 = 1;
+const STDERR_STREAM: u8 = 2;
+
+/// Reads a container log file written by the CRI runtime and converts it to
+/// the stdout/stderr framing of the Docker logs API, so that clients decode
+/// logs the same way for every runtime.
+///
+/// Each line of the file looks like `<RFC 3339 time> <stream> <tag> <log>`,
+/// where the tag is `P` for the partial pieces of a long line and `F` for
+/// the piece that ends it.
+pub fn read_logs(path: &Path, tail: &LogTail) -> Result<Vec<u8>> {
+    let data = read_file(path)?;
+    let mut lines = parse_lines(&data);
+    if let LogTail::Num(num) = *tail {
+        let skip = lines.len().saturating_sub(num as usize);
+        lines.drain(..skip);
+    }
+
+    let mut output = Vec::new();
+    for (stream, mut line) in lines {
+        line.push(b'\n');
+        frame(stream, &line, &mut output);
+    }
+    Ok(output)
+}
+
+/// Frames the stdout and stderr of a finished command.
+pub fn frame_output(stdout: &[u8], stderr: &[u8]) -> Vec<u8> {
+    let mut output = Vec::new();
+    if !stdout.is_empty() {
+        frame(STDOUT_STREAM, stdout, &mut output);
+    }
+    if !stderr.is_empty() {
+        frame(STDERR_STREAM, stderr, &mut output);
+    }
+    output
+}
+
+fn read_file(path: &Path) -> Result<Vec<u8>> {
+    let mut data = Vec::new();
+    match File::open(path) {
+        Ok(mut file) => {
+            file.read_to_end(&mut data)?;
+        }
+        Err(ref err) if err.kind() == io::ErrorKind::NotFound => (),
+        Err(err) => return Err(Error::from(err)),
+    }
+    Ok(data)
+}
+
+fn parse_lines(data: &[u8]) -> Vec<(u8, Vec<u8>)> {
+    let mut lines = Vec::new();
+    let mut partial_stdout = Vec::new();
+    let mut partial_stderr = Vec::new();
+
+    for entry in data.split(|b| *b == b'\n').filter(|line| !line.is_empty()) {
+        let mut fields = entry.splitn(4, |b| *b == b' ').skip(1);
+        let (stream, partial) = match fields.next() {
+            Some(b"stdout") => (STDOUT_STREAM, &mut partial_stdout),
+            Some(b"stderr") => (STDERR_STREAM, &mut partial_stderr),
+            _ => continue,
+        };
+        match (fields.next(), fields.next()) {
+            (Some(b"P"), Some(log)) => partial.extend_from_slice(log),
+            (Some(b"F"), log) => {
+                partial.extend_from_slice(log.unwrap_or_default());
+                lines.push((stream, partial.split_off(0)));
+            }
+            _ => continue,
+        }
+    }
+    lines
+}
+
+// Each frame is an 8 byte header holding the stream type and the big endian
+// length of the payload.
+fn frame(stream: u8, data: &[u8], output: &mut Vec<u8>) {
+    let len = data.len() as u32;
+    output.extend_from_slice(&[
+        stream,
+        0,
+        0,
+        0,
+        (len >> 24) as u8,
+        (len >> 16) as u8,
+        (len >> 8) as u8,
+        len as u8,
+    ]);
+    output.extend_from_slice(data);
+}
+
+pub struct Logs(Body);
+
+impl From<Vec<u8>> for Logs {
+    fn from(data: Vec<u8>) -> Logs {
+        Logs(Body::from(data))
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Chunk(HyperChunk);
+
+impl IntoIterator for Chunk {
+    type Item = u8;
+    type IntoIter = <HyperChunk as IntoIterator>::IntoIter;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl Extend<u8> for Chunk {
+    fn extend<T>(&mut self, iter: T)
+    where
+        T: IntoIterator<Item = u8>,
+    {
+        self.0.extend(iter)
+    }
+}
+
+impl Stream for Logs {
+    type Item = Chunk;
+    type Error = Error;
+
+    fn poll(&mut self) -> Poll<Option<Self::Item>, Self::Error> {
+        if let Some(c) = try_ready!(self.0.poll()) {
+            Ok(Async::Ready(Some(Chunk(c))))
+        } else {
+            Ok(Async::Ready(None))
+        }
+    }
+}
+
+impl Into<Body> for Logs {
+    fn into(self) -> Body {
+        self.0
+    }
+}
+
+impl AsRef<[u8]> for Chunk {
+    fn as_ref(&self) -> &[u8] {
+        self.0.as_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::fs;
+
+    use tempfile::tempdir;
+
+    static LOG: &str = "\
+2018-06-01T10:00:00.000000001Z stdout F one
+2018-06-01T10:00:01.000000001Z stderr P tw
+2018-06-01T10:00:01.000000002Z stderr F o
+2018-06-01T10:00:02.000000001Z stdout F three
+";
+
+    #[test]
+    fn read_logs_joins_partial_lines() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("0.log");
+        fs::write(&path, LOG).unwrap();
+
+        let output = read_logs(&path, &LogTail::All).unwrap();
+        let mut expected = frame_output(b"one\n", b"two\n");
+        frame(STDOUT_STREAM, b"three\n", &mut expected);
+        assert_eq!(expected, output);
+    }
+
+    #[test]
+    fn read_logs_applies_tail() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("0.log");
+        fs::write(&path, LOG).unwrap();
+
+        let output = read_logs(&path, &LogTail::Num(1)).unwrap();
+        assert_eq!(
+            vec![1, 0, 0, 0, 0, 0, 0, 6, b't', b'h', b'r', b'e', b'e', b'\n'],
+            output
+        );
+    }
+
+    #[test]
+    fn read_logs_without_file_is_empty() {
+        let dir = tempdir().unwrap();
+        let output = read_logs(&dir.path().join("0.log"), &LogTail::All).unwrap();
+        assert!(output.is_empty());
+    }
+}
@@ -0,0 +1,172 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+use chrono::prelude::*;
+use cri::api::{ContainerState, ContainerStatus};
+use futures::future::{self, FutureResult};
+
+use config::CriConfig;
+use edgelet_core::{Module, ModuleRuntimeState, ModuleStatus};
+use error::{Error, Result};
+
+pub const MODULE_TYPE: &str = "cri";
+
+/// Label holding the name of the module a sandbox or container belongs to.
+pub const MODULE_LABEL_KEY: &str = "net.azure-devices.edge.module";
+
+/// Label marking the sandboxes and containers created by iotedged, so that
+/// anything else the CRI runtime runs is left alone.
+pub const OWNER_LABEL_KEY: &str = "net.azure-devices.edge.owner";
+pub const OWNER_LABEL_VALUE: &str = "iotedged";
+
+/// Annotation on the sandbox holding the module spec, which is needed to
+/// create a new container when a module is started again.
+pub const SPEC_ANNOTATION_KEY: &str = "net.azure-devices.edge.spec";
+
+const NANOS_PER_SECOND: i64 = 1_000_000_000;
+
+#[derive(Clone, Debug)]
+pub struct CriModule {
+    name: String,
+    config: CriConfig,
+    spec_hash: Option<String>,
+    state: ModuleRuntimeState,
+}
+
+impl CriModule {
+    pub fn new(name: &str, config: CriConfig, state: ModuleRuntimeState) -> Result<Self> {
+        Ok(CriModule {
+            name: ensure_not_empty!(name.to_string()),
+            config,
+            spec_hash: None,
+            state,
+        })
+    }
+
+    pub fn with_spec_hash(mut self, spec_hash: Option<String>) -> Self {
+        self.spec_hash = spec_hash;
+        self
+    }
+}
+
+impl Module for CriModule {
+    type Config = CriConfig;
+    type Error = Error;
+    type RuntimeStateFuture = FutureResult<ModuleRuntimeState, Self::Error>;
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn type_(&self) -> &str {
+        MODULE_TYPE
+    }
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn spec_hash(&self) -> Option<&str> {
+        self.spec_hash.as_ref().map(AsRef::as_ref)
+    }
+
+    fn runtime_state(&self) -> Self::RuntimeStateFuture {
+        future::ok(self.state.clone())
+    }
+}
+
+/// Converts the status the CRI runtime reports for a container. Every start
+/// after the first one creates a new container, so the attempt number of
+/// the container is the restart count of the module.
+pub fn runtime_state(status: &ContainerStatus) -> ModuleRuntimeState {
+    let exited = status.get_state() == ContainerState::CONTAINER_EXITED;
+    let exit_code = i64::from(status.get_exit_code());
+    let module_status = match status.get_state() {
+        ContainerState::CONTAINER_CREATED => ModuleStatus::Created,
+        ContainerState::CONTAINER_RUNNING => ModuleStatus::Running,
+        ContainerState::CONTAINER_EXITED if exit_code == 0 => ModuleStatus::Stopped,
+        ContainerState::CONTAINER_EXITED => ModuleStatus::Failed,
+        ContainerState::CONTAINER_UNKNOWN => ModuleStatus::Unknown,
+    };
+    let description = [status.get_reason(), status.get_message()]
+        .iter()
+        .filter(|s| !s.is_empty())
+        .cloned()
+        .collect::<Vec<&str>>()
+        .join(": ");
+
+    ModuleRuntimeState::default()
+        .with_status(module_status)
+        .with_exit_code(if exited { Some(exit_code) } else { None })
+        .with_status_description(non_empty(&description))
+        .with_started_at(timestamp(status.get_started_at()))
+        .with_finished_at(timestamp(status.get_finished_at()))
+        .with_image_id(non_empty(status.get_image_ref()))
+        .with_restart_count(status.get_metadata().get_attempt() as i32)
+}
+
+fn non_empty(value: &str) -> Option<String> {
+    if value.is_empty() {
+        None
+    } else {
+        Some(value.to_string())
+    }
+}
+
+// CRI timestamps are nanoseconds since the epoch, zero when not set.
+pub fn timestamp(nanos: i64) -> Option<DateTime<Utc>> {
+    if nanos > 0 {
+        Some(Utc.timestamp(nanos / NANOS_PER_SECOND, (nanos % NANOS_PER_SECOND) as u32))
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use cri::api::ContainerMetadata;
+
+    fn status(state: ContainerState, exit_code: i32) -> ContainerStatus {
+        let mut metadata = ContainerMetadata::new();
+        metadata.set_name("mod1".to_string());
+        metadata.set_attempt(2);
+
+        let mut status = ContainerStatus::new();
+        status.set_metadata(metadata);
+        status.set_state(state);
+        status.set_exit_code(exit_code);
+        status.set_started_at(1_527_847_200_000_000_001);
+        status.set_image_ref("sha256:abc".to_string());
+        status
+    }
+
+    #[test]
+    fn running_container_state() {
+        let state = runtime_state(&status(ContainerState::CONTAINER_RUNNING, 0));
+        assert_eq!(ModuleStatus::Running, *state.status());
+        assert_eq!(None, state.exit_code());
+        assert_eq!(None, state.finished_at());
+        assert_eq!(2, state.restart_count());
+        assert_eq!(Some(&"sha256:abc".to_string()), state.image_id());
+        assert_eq!(Some(&Utc.timestamp(1_527_847_200, 1)), state.started_at());
+    }
+
+    #[test]
+    fn exited_container_state() {
+        let mut failed = status(ContainerState::CONTAINER_EXITED, 1);
+        failed.set_reason("Error".to_string());
+        failed.set_message("out of disk".to_string());
+        let state = runtime_state(&failed);
+        assert_eq!(ModuleStatus::Failed, *state.status());
+        assert_eq!(Some(&1), state.exit_code());
+        assert_eq!(
+            Some(&"Error: out of disk".to_string()),
+            state.status_description()
+        );
+
+        let state = runtime_state(&status(ContainerState::CONTAINER_EXITED, 0));
+        assert_eq!(ModuleStatus::Stopped, *state.status());
+        assert_eq!(None, state.status_description());
+    }
+}
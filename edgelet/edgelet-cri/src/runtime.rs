@@ -0,0 +1,941 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+use std::vec::IntoIter;
+
+use cri::api::*;
+use cri::api_grpc::{ImageService, ImageServiceClient, RuntimeService, RuntimeServiceClient};
+use futures::future::{self, Either};
+use futures::prelude::*;
+use futures::stream::{self, Empty, IterOk};
+use futures::sync::oneshot;
+use grpc::{Client, ClientConf, RequestOptions};
+use log::Level;
+use protobuf::RepeatedField;
+use serde_json;
+use url::Url;
+
+use config::CriConfig;
+use edgelet_core::{
+    ArchiveStream, EventOptions, ExecOptions, Image as CoreImage, LogOptions, ModuleEvent,
+    ModuleRegistry, ModuleRuntime, ModuleSpec, ModuleStats, PruneOptions, PruneResult,
+    StatsOptions, SystemInfo as CoreSystemInfo, Volume,
+};
+use edgelet_utils::log_failure;
+use error::{Error, ErrorKind, Result};
+use logs::{self, Chunk, Logs};
+use module::{
+    self, CriModule, MODULE_LABEL_KEY, MODULE_TYPE as CRI_MODULE_TYPE, OWNER_LABEL_KEY,
+    OWNER_LABEL_VALUE, SPEC_ANNOTATION_KEY,
+};
+
+const UNIX_SCHEME: &str = "unix";
+const HTTP_SCHEME: &str = "http";
+const CRI_API_VERSION: &str = "v1alpha2";
+const DEFAULT_NAMESPACE: &str = "iotedge";
+const WAIT_BEFORE_KILL_SECONDS: u64 = 10;
+
+type CriFuture<T> = Box<Future<Item = T, Error = Error>>;
+
+// Every module runs in a sandbox of its own. The sandbox holds the spec the
+// module was created from, the container is recreated from it each time the
+// module is started after it exited, like the kubelet does.
+struct ModuleContainer {
+    sandbox: PodSandbox,
+    container: Container,
+}
+
+/// Runs modules through a runtime implementing the Kubernetes Container
+/// Runtime Interface, such as containerd with its CRI plugin.
+#[derive(Clone)]
+pub struct CriModuleRuntime {
+    client: Arc<RuntimeServiceClient>,
+    registry: CriRegistry,
+    log_dir: PathBuf,
+    namespace: String,
+}
+
+impl CriModuleRuntime {
+    /// Connects to the CRI endpoint, either a `unix://` socket or an
+    /// `http://` address.
+    pub fn new(uri: &Url, log_dir: &Path) -> Result<CriModuleRuntime> {
+        Ok(CriModuleRuntime {
+            client: Arc::new(RuntimeServiceClient::with_client(connect(uri)?)),
+            registry: CriRegistry {
+                client: Arc::new(ImageServiceClient::with_client(connect(uri)?)),
+            },
+            log_dir: log_dir.to_path_buf(),
+            namespace: DEFAULT_NAMESPACE.to_string(),
+        })
+    }
+
+    /// Namespace of the sandboxes created for modules.
+    pub fn with_namespace(mut self, namespace: String) -> Self {
+        self.namespace = namespace;
+        self
+    }
+
+    pub fn log_dir(&self) -> &Path {
+        &self.log_dir
+    }
+
+    fn sandbox_log_dir(&self, name: &str) -> PathBuf {
+        self.log_dir.join(name)
+    }
+
+    fn sandbox_config(&self, spec: &ModuleSpec<CriConfig>) -> Result<PodSandboxConfig> {
+        let mut metadata = PodSandboxMetadata::new();
+        metadata.set_name(spec.name().to_string());
+        metadata.set_uid(spec.name().to_string());
+        metadata.set_namespace(self.namespace.clone());
+
+        let mut annotations = HashMap::new();
+        annotations.insert(
+            SPEC_ANNOTATION_KEY.to_string(),
+            serde_json::to_string(spec)?,
+        );
+
+        let mut config = PodSandboxConfig::new();
+        config.set_metadata(metadata);
+        config.set_hostname(spec.name().to_string());
+        config.set_log_directory(
+            self.sandbox_log_dir(spec.name())
+                .to_string_lossy()
+                .into_owned(),
+        );
+        config.set_labels(labels(Some(spec.name())));
+        config.set_annotations(annotations);
+        Ok(config)
+    }
+
+    // Creates the container for the given attempt at running the module.
+    fn create_container(
+        &self,
+        sandbox_id: String,
+        spec: &ModuleSpec<CriConfig>,
+        attempt: u32,
+    ) -> CriFuture<String> {
+        let sandbox_config = match self.sandbox_config(spec) {
+            Ok(sandbox_config) => sandbox_config,
+            Err(err) => return Box::new(future::err(err)),
+        };
+
+        let mut request = CreateContainerRequest::new();
+        request.set_pod_sandbox_id(sandbox_id);
+        request.set_config(container_config(spec, attempt));
+        request.set_sandbox_config(sandbox_config);
+        Box::new(
+            self.client
+                .create_container(RequestOptions::new(), request)
+                .drop_metadata()
+                .map(|mut response| response.take_container_id())
+                .map_err(Error::from),
+        )
+    }
+
+    fn sandboxes(&self, name: Option<&str>) -> CriFuture<Vec<PodSandbox>> {
+        let mut filter = PodSandboxFilter::new();
+        filter.set_label_selector(labels(name));
+        let mut request = ListPodSandboxRequest::new();
+        request.set_filter(filter);
+        Box::new(
+            self.client
+                .list_pod_sandbox(RequestOptions::new(), request)
+                .drop_metadata()
+                .map(|mut response| response.take_items().into_vec())
+                .map_err(Error::from),
+        )
+    }
+
+    fn containers(&self, name: Option<&str>) -> CriFuture<Vec<Container>> {
+        let mut filter = ContainerFilter::new();
+        filter.set_label_selector(labels(name));
+        let mut request = ListContainersRequest::new();
+        request.set_filter(filter);
+        Box::new(
+            self.client
+                .list_containers(RequestOptions::new(), request)
+                .drop_metadata()
+                .map(|mut response| response.take_containers().into_vec())
+                .map_err(Error::from),
+        )
+    }
+
+    // Finds the sandbox of a module and its latest container.
+    fn module_container(&self, name: &str) -> CriFuture<ModuleContainer> {
+        Box::new(
+            self.sandboxes(Some(name))
+                .join(self.containers(Some(name)))
+                .and_then(|(sandboxes, containers)| {
+                    let sandbox = sandboxes
+                        .into_iter()
+                        .next()
+                        .ok_or_else(|| Error::from(ErrorKind::NotFound))?;
+                    let container = containers
+                        .into_iter()
+                        .filter(|c| c.get_pod_sandbox_id() == sandbox.get_id())
+                        .max_by_key(|c| c.get_metadata().get_attempt())
+                        .ok_or_else(|| Error::from(ErrorKind::NotFound))?;
+                    Ok(ModuleContainer { sandbox, container })
+                }),
+        )
+    }
+
+    fn container_status(&self, id: &str) -> CriFuture<ContainerStatus> {
+        let mut request = ContainerStatusRequest::new();
+        request.set_container_id(id.to_string());
+        Box::new(
+            self.client
+                .container_status(RequestOptions::new(), request)
+                .drop_metadata()
+                .map(|mut response| response.take_status())
+                .map_err(Error::from),
+        )
+    }
+
+    fn start_container(&self, id: String) -> CriFuture<()> {
+        let mut request = StartContainerRequest::new();
+        request.set_container_id(id);
+        Box::new(
+            self.client
+                .start_container(RequestOptions::new(), request)
+                .drop_metadata()
+                .map(|_| ())
+                .map_err(Error::from),
+        )
+    }
+
+    fn remove_container(&self, id: String) -> CriFuture<()> {
+        let mut request = RemoveContainerRequest::new();
+        request.set_container_id(id);
+        Box::new(
+            self.client
+                .remove_container(RequestOptions::new(), request)
+                .drop_metadata()
+                .map(|_| ())
+                .map_err(Error::from),
+        )
+    }
+
+    // Removing the sandbox removes its containers as well.
+    fn remove_sandbox(&self, id: String) -> CriFuture<()> {
+        let mut stop = StopPodSandboxRequest::new();
+        stop.set_pod_sandbox_id(id.clone());
+        let mut remove = RemovePodSandboxRequest::new();
+        remove.set_pod_sandbox_id(id);
+        let client = self.client.clone();
+        Box::new(
+            self.client
+                .stop_pod_sandbox(RequestOptions::new(), stop)
+                .drop_metadata()
+                .and_then(move |_| {
+                    client
+                        .remove_pod_sandbox(RequestOptions::new(), remove)
+                        .drop_metadata()
+                }).map(|_| ())
+                .map_err(Error::from),
+        )
+    }
+
+    // Containers that exited cannot be started again, so a new container is
+    // created from the spec kept on the sandbox.
+    fn recreate_and_start(&self, module: ModuleContainer) -> CriFuture<()> {
+        let spec = match module_spec(&module.sandbox) {
+            Ok(spec) => spec,
+            Err(err) => return Box::new(future::err(err)),
+        };
+        let attempt = module.container.get_metadata().get_attempt() + 1;
+        let sandbox_id = module.sandbox.get_id().to_string();
+        let runtime = self.clone();
+        let runtime_copy = self.clone();
+        Box::new(
+            self.remove_container(module.container.get_id().to_string())
+                .and_then(move |_| runtime.create_container(sandbox_id, &spec, attempt))
+                .and_then(move |id| runtime_copy.start_container(id)),
+        )
+    }
+}
+
+fn connect(uri: &Url) -> Result<Client> {
+    let client = match uri.scheme() {
+        UNIX_SCHEME => Client::new_plain_unix(uri.path(), ClientConf::new())?,
+        HTTP_SCHEME => {
+            let host = uri
+                .host_str()
+                .ok_or_else(|| ErrorKind::InvalidUri(uri.to_string()))?;
+            let port = uri
+                .port()
+                .ok_or_else(|| ErrorKind::InvalidUri(uri.to_string()))?;
+            Client::new_plain(host, port, ClientConf::new())?
+        }
+        _ => return Err(Error::from(ErrorKind::InvalidUri(uri.to_string()))),
+    };
+    Ok(client)
+}
+
+fn labels(name: Option<&str>) -> HashMap<String, String> {
+    let mut labels = HashMap::new();
+    labels.insert(OWNER_LABEL_KEY.to_string(), OWNER_LABEL_VALUE.to_string());
+    if let Some(name) = name {
+        labels.insert(MODULE_LABEL_KEY.to_string(), name.to_string());
+    }
+    labels
+}
+
+fn module_spec(sandbox: &PodSandbox) -> Result<ModuleSpec<CriConfig>> {
+    let spec = sandbox
+        .get_annotations()
+        .get(SPEC_ANNOTATION_KEY)
+        .ok_or_else(|| Error::from(ErrorKind::NotFound))?;
+    Ok(serde_json::from_str(spec)?)
+}
+
+fn container_config(spec: &ModuleSpec<CriConfig>, attempt: u32) -> ContainerConfig {
+    let config = spec.config();
+
+    let mut metadata = ContainerMetadata::new();
+    metadata.set_name(spec.name().to_string());
+    metadata.set_attempt(attempt);
+
+    let mut image = ImageSpec::new();
+    image.set_image(config.image().to_string());
+
+    let envs = spec
+        .env()
+        .iter()
+        .map(|(key, value)| {
+            let mut env = KeyValue::new();
+            env.set_key(key.to_string());
+            env.set_value(value.to_string());
+            env
+        }).collect();
+
+    let mounts = config
+        .mounts()
+        .iter()
+        .map(|m| {
+            let mut mount = Mount::new();
+            mount.set_host_path(m.host_path().to_string());
+            mount.set_container_path(m.container_path().to_string());
+            mount.set_readonly(m.read_only());
+            mount
+        }).collect();
+
+    let mut container_labels = config.labels().clone();
+    container_labels.extend(labels(Some(spec.name())));
+
+    let mut container = ContainerConfig::new();
+    container.set_metadata(metadata);
+    container.set_image(image);
+    container.set_command(RepeatedField::from_vec(config.command().to_vec()));
+    container.set_args(RepeatedField::from_vec(config.args().to_vec()));
+    if let Some(working_dir) = config.working_dir() {
+        container.set_working_dir(working_dir.to_string());
+    }
+    container.set_envs(RepeatedField::from_vec(envs));
+    container.set_mounts(RepeatedField::from_vec(mounts));
+    container.set_labels(container_labels);
+    // relative to the log directory of the sandbox
+    container.set_log_path(format!("{}.log", attempt));
+    container
+}
+
+fn log_result<F>(future: F, message: &'static str) -> Box<Future<Item = F::Item, Error = Error>>
+where
+    F: 'static + Future<Error = Error>,
+{
+    Box::new(future.map_err(move |e| {
+        warn!("{}", message);
+        log_failure(Level::Warn, &e);
+        e
+    }))
+}
+
+// Runs blocking work on its own thread so that the reactor is not held up
+// while a log file is read.
+fn blocking<F, T>(f: F) -> CriFuture<T>
+where
+    F: 'static + Send + FnOnce() -> Result<T>,
+    T: 'static + Send,
+{
+    let (tx, rx) = oneshot::channel();
+    thread::spawn(move || {
+        tx.send(f()).unwrap_or(());
+    });
+    Box::new(rx.then(|result| result.unwrap_or_else(|_| Err(Error::from(ErrorKind::Io)))))
+}
+
+/// Pulls and removes images through the CRI image service.
+#[derive(Clone)]
+pub struct CriRegistry {
+    client: Arc<ImageServiceClient>,
+}
+
+impl ModuleRegistry for CriRegistry {
+    type Error = Error;
+    type PullFuture = Box<Future<Item = (), Error = Self::Error>>;
+    type RemoveFuture = Box<Future<Item = (), Error = Self::Error>>;
+    type ListImagesFuture = Box<Future<Item = Vec<CoreImage>, Error = Self::Error>>;
+    type PruneImagesFuture = Box<Future<Item = PruneResult, Error = Self::Error>>;
+    type LoadImagesFuture = Box<Future<Item = (), Error = Self::Error>>;
+    type SaveImageFuture = Box<Future<Item = Self::ImageArchive, Error = Self::Error>>;
+    type ImageChunk = Chunk;
+    type ImageArchive = Logs;
+    type Config = CriConfig;
+
+    fn pull(&self, config: &Self::Config) -> Self::PullFuture {
+        debug!("Pulling {}", config.image());
+        let mut image = ImageSpec::new();
+        image.set_image(config.image().to_string());
+        let mut request = PullImageRequest::new();
+        request.set_image(image);
+        if let Some(creds) = config.auth() {
+            let mut auth = AuthConfig::new();
+            auth.set_username(creds.username().to_string());
+            auth.set_password(creds.password().to_string());
+            auth.set_server_address(creds.server_address().to_string());
+            request.set_auth(auth);
+        }
+
+        let image = config.image().to_string();
+        log_result(
+            self.client
+                .pull_image(RequestOptions::new(), request)
+                .drop_metadata()
+                .map(move |response| {
+                    debug!("Pulled {} as {}", image, response.get_image_ref());
+                }).map_err(Error::from),
+            "Attempt to pull image failed.",
+        )
+    }
+
+    fn remove(&self, name: &str) -> Self::RemoveFuture {
+        debug!("Removing image {}", name);
+        let mut image = ImageSpec::new();
+        image.set_image(fensure_not_empty!(name).to_string());
+        let mut request = RemoveImageRequest::new();
+        request.set_image(image);
+        log_result(
+            self.client
+                .remove_image(RequestOptions::new(), request)
+                .drop_metadata()
+                .map(|_| ())
+                .map_err(Error::from),
+            "Attempt to remove image failed.",
+        )
+    }
+
+    // The kubelet collects images for CRI runtimes, iotedged leaves them to
+    // whatever manages the runtime.
+    fn list_images(&self) -> Self::ListImagesFuture {
+        Box::new(future::err(Error::from(ErrorKind::NotSupported(
+            "listing images",
+        ))))
+    }
+
+    fn prune_images(&self, _options: &PruneOptions) -> Self::PruneImagesFuture {
+        Box::new(future::err(Error::from(ErrorKind::NotSupported(
+            "pruning images",
+        ))))
+    }
+
+    // CRI has no way to import or export images.
+    fn load_images(&self, _archive: ArchiveStream) -> Self::LoadImagesFuture {
+        Box::new(future::err(Error::from(ErrorKind::NotSupported(
+            "loading images",
+        ))))
+    }
+
+    fn save_image(&self, _name: &str) -> Self::SaveImageFuture {
+        Box::new(future::err(Error::from(ErrorKind::NotSupported(
+            "saving images",
+        ))))
+    }
+}
+
+impl ModuleRuntime for CriModuleRuntime {
+    type Error = Error;
+    type Config = CriConfig;
+    type Module = CriModule;
+    type ModuleRegistry = CriRegistry;
+    type Chunk = Chunk;
+    type Logs = Logs;
+    type Stats = IterOk<IntoIter<ModuleStats>, Self::Error>;
+    type Events = Empty<ModuleEvent, Self::Error>;
+
+    type CreateFuture = Box<Future<Item = (), Error = Self::Error>>;
+    type EventsFuture = Box<Future<Item = Self::Events, Error = Self::Error>>;
+    type ExecFuture = Box<Future<Item = Self::Logs, Error = Self::Error>>;
+    type GetArchiveFuture = Box<Future<Item = Self::Logs, Error = Self::Error>>;
+    type InitFuture = Box<Future<Item = (), Error = Self::Error>>;
+    type ListFuture = Box<Future<Item = Vec<Self::Module>, Error = Self::Error>>;
+    type LogsFuture = Box<Future<Item = Self::Logs, Error = Self::Error>>;
+    type RemoveFuture = Box<Future<Item = (), Error = Self::Error>>;
+    type PauseFuture = Box<Future<Item = (), Error = Self::Error>>;
+    type PutArchiveFuture = Box<Future<Item = (), Error = Self::Error>>;
+    type RestartFuture = Box<Future<Item = (), Error = Self::Error>>;
+    type StatsFuture = Box<Future<Item = Self::Stats, Error = Self::Error>>;
+    type StartFuture = Box<Future<Item = (), Error = Self::Error>>;
+    type StopFuture = Box<Future<Item = (), Error = Self::Error>>;
+    type UnpauseFuture = Box<Future<Item = (), Error = Self::Error>>;
+    type SystemInfoFuture = Box<Future<Item = CoreSystemInfo, Error = Self::Error>>;
+    type RemoveAllFuture = Box<Future<Item = (), Error = Self::Error>>;
+    type ListVolumesFuture = Box<Future<Item = Vec<Volume>, Error = Self::Error>>;
+    type InspectVolumeFuture = Box<Future<Item = Volume, Error = Self::Error>>;
+    type CreateVolumeFuture = Box<Future<Item = Volume, Error = Self::Error>>;
+    type RemoveVolumeFuture = Box<Future<Item = (), Error = Self::Error>>;
+    type BackupVolumeFuture = Box<Future<Item = Self::Logs, Error = Self::Error>>;
+
+    fn init(&self) -> Self::InitFuture {
+        let created = fs::create_dir_all(&self.log_dir).map_err(Error::from);
+        let mut request = VersionRequest::new();
+        request.set_version(CRI_API_VERSION.to_string());
+        let version = self
+            .client
+            .version(RequestOptions::new(), request)
+            .drop_metadata()
+            .map(|response| {
+                info!(
+                    "Using CRI runtime {} {} (API {})",
+                    response.get_runtime_name(),
+                    response.get_runtime_version(),
+                    response.get_runtime_api_version()
+                );
+            }).map_err(Error::from);
+        log_result(
+            future::result(created).and_then(|_| version),
+            "Module runtime init failed.",
+        )
+    }
+
+    fn create(&self, module: ModuleSpec<Self::Config>) -> Self::CreateFuture {
+        // we only want "cri" modules
+        fensure!(module.type_(), module.type_() == CRI_MODULE_TYPE);
+
+        debug!(
+            "Creating CRI module {} from {}",
+            module.name(),
+            module.config().image()
+        );
+        let sandbox_config = match self.sandbox_config(&module) {
+            Ok(sandbox_config) => sandbox_config,
+            Err(err) => return Box::new(future::err(err)),
+        };
+        let runtime = self.clone();
+        let runtime_copy = self.clone();
+        let created = self
+            .sandboxes(Some(module.name()))
+            .and_then(move |sandboxes| {
+                if sandboxes.is_empty() {
+                    Ok(())
+                } else {
+                    Err(Error::from(ErrorKind::Conflict))
+                }
+            }).and_then(move |_| {
+                let mut request = RunPodSandboxRequest::new();
+                request.set_config(sandbox_config);
+                runtime
+                    .client
+                    .run_pod_sandbox(RequestOptions::new(), request)
+                    .drop_metadata()
+                    .map(|mut response| response.take_pod_sandbox_id())
+                    .map_err(Error::from)
+            }).and_then(move |sandbox_id| runtime_copy.create_container(sandbox_id, &module, 0))
+            .map(|_| ());
+        log_result(created, "Attempt to create a CRI module failed.")
+    }
+
+    fn start(&self, id: &str) -> Self::StartFuture {
+        debug!("Starting CRI module {}", id);
+        let runtime = self.clone();
+        let started = self
+            .module_container(fensure_not_empty!(id))
+            .and_then(move |module| match module.container.get_state() {
+                ContainerState::CONTAINER_RUNNING => {
+                    Either::A(future::err(Error::from(ErrorKind::NotModified)))
+                }
+                ContainerState::CONTAINER_CREATED => {
+                    Either::B(runtime.start_container(module.container.get_id().to_string()))
+                }
+                _ => Either::B(runtime.recreate_and_start(module)),
+            });
+        log_result(started, "Attempt to start a CRI module failed.")
+    }
+
+    fn stop(&self, id: &str, wait_before_kill: Option<Duration>) -> Self::StopFuture {
+        debug!("Stopping CRI module {}", id);
+        let timeout = wait_before_kill
+            .unwrap_or_else(|| Duration::from_secs(WAIT_BEFORE_KILL_SECONDS))
+            .as_secs() as i64;
+        let client = self.client.clone();
+        let stopped = self
+            .module_container(fensure_not_empty!(id))
+            .and_then(move |module| {
+                if module.container.get_state() != ContainerState::CONTAINER_RUNNING {
+                    return Either::A(future::err(Error::from(ErrorKind::NotModified)));
+                }
+                let mut request = StopContainerRequest::new();
+                request.set_container_id(module.container.get_id().to_string());
+                request.set_timeout(timeout);
+                Either::B(
+                    client
+                        .stop_container(RequestOptions::new(), request)
+                        .drop_metadata()
+                        .map(|_| ())
+                        .map_err(Error::from),
+                )
+            });
+        log_result(stopped, "Attempt to stop a CRI module failed.")
+    }
+
+    fn restart(&self, id: &str) -> Self::RestartFuture {
+        let runtime = self.clone();
+        let name = id.to_string();
+        Box::new(
+            self.stop(fensure_not_empty!(id), None)
+                .or_else(|err| match *err.kind() {
+                    ErrorKind::NotModified => Ok(()),
+                    _ => Err(err),
+                }).and_then(move |_| runtime.start(&name)),
+        )
+    }
+
+    fn pause(&self, _id: &str) -> Self::PauseFuture {
+        Box::new(future::err(Error::from(ErrorKind::NotSupported("pause"))))
+    }
+
+    fn unpause(&self, _id: &str) -> Self::UnpauseFuture {
+        Box::new(future::err(Error::from(ErrorKind::NotSupported("unpause"))))
+    }
+
+    fn remove(&self, id: &str) -> Self::RemoveFuture {
+        debug!("Removing CRI module {}", id);
+        let runtime = self.clone();
+        let removed = self
+            .sandboxes(Some(fensure_not_empty!(id)))
+            .and_then(move |sandboxes| {
+                if sandboxes.is_empty() {
+                    return Either::A(future::err(Error::from(ErrorKind::NotFound)));
+                }
+                let removals = sandboxes
+                    .iter()
+                    .map(|sandbox| runtime.remove_sandbox(sandbox.get_id().to_string()))
+                    .collect::<Vec<_>>();
+                Either::B(future::join_all(removals).map(|_| ()))
+            });
+        log_result(removed, "Attempt to remove a CRI module failed.")
+    }
+
+    // CRI modules only mount host paths, which are not the runtime's to
+    // remove.
+    fn remove_with_volumes(&self, id: &str) -> Self::RemoveFuture {
+        self.remove(id)
+    }
+
+    fn system_info(&self) -> Self::SystemInfoFuture {
+        Box::new(future::ok(CoreSystemInfo::new(
+            env::consts::OS.to_string(),
+            env::consts::ARCH.to_string(),
+        )))
+    }
+
+    fn list(&self) -> Self::ListFuture {
+        let runtime = self.clone();
+        let modules = self.sandboxes(None).join(self.containers(None)).and_then(
+            move |(sandboxes, containers)| {
+                let sandboxes: HashMap<String, PodSandbox> = sandboxes
+                    .into_iter()
+                    .map(|sandbox| (sandbox.get_id().to_string(), sandbox))
+                    .collect();
+
+                // only the latest container of each sandbox is the module
+                let mut latest: HashMap<String, Container> = HashMap::new();
+                for container in containers {
+                    let newer = latest
+                        .get(container.get_pod_sandbox_id())
+                        .map(|c| c.get_metadata().get_attempt())
+                        .map_or(true, |attempt| {
+                            attempt < container.get_metadata().get_attempt()
+                        });
+                    if newer {
+                        latest.insert(container.get_pod_sandbox_id().to_string(), container);
+                    }
+                }
+
+                let modules = latest
+                    .into_iter()
+                    .filter_map(|(sandbox_id, container)| {
+                        let spec = sandboxes.get(&sandbox_id).map(module_spec);
+                        match spec {
+                            Some(Ok(spec)) => Some((spec, container)),
+                            _ => {
+                                warn!(
+                                    "Ignoring container {} without a module spec",
+                                    container.get_id()
+                                );
+                                None
+                            }
+                        }
+                    }).map(|(spec, container)| {
+                        runtime
+                            .container_status(container.get_id())
+                            .and_then(move |status| {
+                                let hash = spec.recorded_spec_hash().ok().and_then(|hash| hash);
+                                CriModule::new(
+                                    spec.name(),
+                                    spec.config().clone(),
+                                    module::runtime_state(&status),
+                                )
+                                .map(|m| m.with_spec_hash(hash))
+                            })
+                    }).collect::<Vec<_>>();
+                future::join_all(modules)
+            },
+        );
+        log_result(modules, "Attempt to list CRI modules failed.")
+    }
+
+    // The CRI runtime writes container output to files in the log directory,
+    // they are read as they are at the time of the call. Following the
+    // output, time ranges and timestamps are not supported.
+    fn logs(&self, id: &str, options: &LogOptions) -> Self::LogsFuture {
+        if options.follow() {
+            return Box::new(future::err(Error::from(ErrorKind::NotSupported(
+                "following logs",
+            ))));
+        }
+        if options.since() != 0 || options.until() != 0 {
+            return Box::new(future::err(Error::from(ErrorKind::NotSupported(
+                "filtering logs by time",
+            ))));
+        }
+        if options.timestamps() {
+            return Box::new(future::err(Error::from(ErrorKind::NotSupported(
+                "log timestamps",
+            ))));
+        }
+
+        let runtime = self.clone();
+        let tail = options.tail().clone();
+        let logs = self
+            .module_container(fensure_not_empty!(id))
+            .and_then(move |module| {
+                // the log path of a container is relative to the log
+                // directory of its sandbox
+                let log_dir = runtime.sandbox_log_dir(module.sandbox.get_metadata().get_name());
+                runtime
+                    .container_status(module.container.get_id())
+                    .map(move |status| log_dir.join(status.get_log_path()))
+            }).and_then(move |path| blocking(move || logs::read_logs(&path, &tail)))
+            .map(Logs::from);
+        log_result(logs, "Attempt to get CRI module logs failed.")
+    }
+
+    fn stats(&self, id: &str, _options: &StatsOptions) -> Self::StatsFuture {
+        let client = self.client.clone();
+        let stats = self
+            .module_container(fensure_not_empty!(id))
+            .and_then(move |module| {
+                let mut request = ContainerStatsRequest::new();
+                request.set_container_id(module.container.get_id().to_string());
+                client
+                    .container_stats(RequestOptions::new(), request)
+                    .drop_metadata()
+                    .map_err(Error::from)
+            }).map(|response| {
+                let stats = response.get_stats();
+                let cpu = stats.get_cpu();
+                let memory = stats.get_memory();
+                let stats = ModuleStats::default()
+                    .with_read(module::timestamp(cpu.get_timestamp()))
+                    .with_cpu_total_usage(cpu.get_usage_core_nano_seconds().get_value())
+                    .with_memory_usage(memory.get_working_set_bytes().get_value());
+                stream::iter_ok(vec![stats])
+            });
+        log_result(stats, "Attempt to get CRI module stats failed.")
+    }
+
+    // The CRI has no event stream.
+    fn events(&self, _options: &EventOptions) -> Self::EventsFuture {
+        Box::new(future::ok(stream::empty()))
+    }
+
+    fn exec(&self, id: &str, options: &ExecOptions) -> Self::ExecFuture {
+        if options.user().is_some() {
+            return Box::new(future::err(Error::from(ErrorKind::NotSupported(
+                "running commands as another user",
+            ))));
+        }
+
+        debug!("Running {:?} for CRI module {}", options.cmd(), id);
+        let cmd = options.cmd().to_vec();
+        let client = self.client.clone();
+        let output = self
+            .module_container(fensure_not_empty!(id))
+            .and_then(move |module| {
+                if module.container.get_state() != ContainerState::CONTAINER_RUNNING {
+                    return Either::A(future::err(Error::from(ErrorKind::Conflict)));
+                }
+                let mut request = ExecSyncRequest::new();
+                request.set_container_id(module.container.get_id().to_string());
+                request.set_cmd(RepeatedField::from_vec(cmd));
+                Either::B(
+                    client
+                        .exec_sync(RequestOptions::new(), request)
+                        .drop_metadata()
+                        .map(|response| {
+                            Logs::from(logs::frame_output(
+                                response.get_stdout(),
+                                response.get_stderr(),
+                            ))
+                        }).map_err(Error::from),
+                )
+            });
+        log_result(output, "Attempt to run a command in a CRI module failed.")
+    }
+
+    fn get_archive(&self, _id: &str, _path: &str) -> Self::GetArchiveFuture {
+        Box::new(future::err(Error::from(ErrorKind::NotSupported(
+            "copying files",
+        ))))
+    }
+
+    fn put_archive(
+        &self,
+        _id: &str,
+        _path: &str,
+        _archive: ArchiveStream,
+    ) -> Self::PutArchiveFuture {
+        Box::new(future::err(Error::from(ErrorKind::NotSupported(
+            "copying files",
+        ))))
+    }
+
+    fn registry(&self) -> &Self::ModuleRegistry {
+        &self.registry
+    }
+
+    fn remove_all(&self) -> Self::RemoveAllFuture {
+        let runtime = self.clone();
+        let removed = self.sandboxes(None).and_then(move |sandboxes| {
+            let removals = sandboxes
+                .iter()
+                .map(|sandbox| runtime.remove_sandbox(sandbox.get_id().to_string()))
+                .collect::<Vec<_>>();
+            future::join_all(removals).map(|_| ())
+        });
+        log_result(removed, "Attempt to remove all CRI modules failed.")
+    }
+
+    // The CRI has no volumes, modules mount host paths instead.
+    fn list_volumes(&self) -> Self::ListVolumesFuture {
+        Box::new(future::ok(Vec::new()))
+    }
+
+    fn inspect_volume(&self, _name: &str) -> Self::InspectVolumeFuture {
+        Box::new(future::err(Error::from(ErrorKind::NotSupported("volumes"))))
+    }
+
+    fn create_volume(&self, _name: &str, _module: Option<&str>) -> Self::CreateVolumeFuture {
+        Box::new(future::err(Error::from(ErrorKind::NotSupported("volumes"))))
+    }
+
+    fn remove_volume(&self, _name: &str) -> Self::RemoveVolumeFuture {
+        Box::new(future::err(Error::from(ErrorKind::NotSupported("volumes"))))
+    }
+
+    fn backup_volume(&self, _name: &str) -> Self::BackupVolumeFuture {
+        Box::new(future::err(Error::from(ErrorKind::NotSupported("volumes"))))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use config::MountConfig;
+
+    fn spec() -> ModuleSpec<CriConfig> {
+        let config = CriConfig::new("microsoft/azureiotedge-agent:1.0")
+            .unwrap()
+            .with_args(vec!["--verbose".to_string()])
+            .with_mounts(vec![MountConfig::new(
+                "/var/run/iotedge/mgmt.sock".to_string(),
+                "/var/run/iotedge/mgmt.sock".to_string(),
+            )]);
+        let mut env = HashMap::new();
+        env.insert("K1".to_string(), "V1".to_string());
+        ModuleSpec::new("edgeAgent", CRI_MODULE_TYPE, config, env).unwrap()
+    }
+
+    #[test]
+    fn container_config_from_spec() {
+        let config = container_config(&spec(), 3);
+        assert_eq!("edgeAgent", config.get_metadata().get_name());
+        assert_eq!(3, config.get_metadata().get_attempt());
+        assert_eq!(
+            "microsoft/azureiotedge-agent:1.0",
+            config.get_image().get_image()
+        );
+        assert_eq!(&["--verbose".to_string()], config.get_args());
+        assert_eq!("K1", config.get_envs()[0].get_key());
+        assert_eq!("V1", config.get_envs()[0].get_value());
+        assert_eq!(
+            "/var/run/iotedge/mgmt.sock",
+            config.get_mounts()[0].get_host_path()
+        );
+        assert_eq!("3.log", config.get_log_path());
+        assert_eq!(
+            Some(&"edgeAgent".to_string()),
+            config.get_labels().get(MODULE_LABEL_KEY)
+        );
+    }
+
+    #[test]
+    fn connect_rejects_unknown_scheme() {
+        let uri = Url::parse("ftp://localhost:1234").unwrap();
+        let err = connect(&uri).err().unwrap();
+        assert!(match *err.kind() {
+            ErrorKind::InvalidUri(_) => true,
+            _ => false,
+        });
+    }
+
+    #[test]
+    fn logs_fails_when_following() {
+        let dir = ::tempfile::tempdir().unwrap();
+        let uri = Url::parse("http://localhost:1234").unwrap();
+        let runtime = CriModuleRuntime::new(&uri, dir.path()).unwrap();
+
+        let err = runtime
+            .logs("edgeAgent", &LogOptions::new().with_follow(true))
+            .wait()
+            .unwrap_err();
+        assert!(match *err.kind() {
+            ErrorKind::NotSupported(_) => true,
+            _ => false,
+        });
+    }
+
+    #[test]
+    fn module_spec_requires_annotation() {
+        assert!(module_spec(&PodSandbox::new()).is_err());
+
+        let mut annotations = HashMap::new();
+        annotations.insert(
+            SPEC_ANNOTATION_KEY.to_string(),
+            serde_json::to_string(&spec()).unwrap(),
+        );
+        let mut sandbox = PodSandbox::new();
+        sandbox.set_annotations(annotations);
+        let spec = module_spec(&sandbox).unwrap();
+        assert_eq!("edgeAgent", spec.name());
+        assert_eq!("microsoft/azureiotedge-agent:1.0", spec.config().image());
+    }
+}
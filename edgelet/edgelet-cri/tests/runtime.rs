@@ -0,0 +1,612 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+#![deny(warnings)]
+
+extern crate chrono;
+extern crate futures;
+extern crate grpc;
+extern crate tempfile;
+extern crate url;
+
+extern crate cri;
+extern crate edgelet_core;
+extern crate edgelet_cri;
+
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use chrono::prelude::*;
+use cri::api::*;
+use cri::api_grpc::{ImageService, ImageServiceServer, RuntimeService, RuntimeServiceServer};
+use futures::prelude::*;
+use grpc::{
+    Error as GrpcError, GrpcMessageError, GrpcStatus, RequestOptions, ServerBuilder, SingleResponse,
+};
+use tempfile::tempdir;
+use url::Url;
+
+use edgelet_core::{
+    ExecOptions, LogOptions, LogTail, Module, ModuleRegistry, ModuleRuntime, ModuleRuntimeState,
+    ModuleSpec, ModuleStatus,
+};
+use edgelet_cri::{CriConfig, CriModuleRuntime, ErrorKind, MODULE_TYPE};
+
+#[derive(Default)]
+struct StubState {
+    next_id: u32,
+    sandboxes: Vec<(PodSandbox, PodSandboxConfig)>,
+    containers: Vec<(Container, ContainerStatus)>,
+    images: Vec<String>,
+}
+
+/// An in-memory CRI server, just enough of one to drive the runtime.
+#[derive(Clone, Default)]
+struct StubCri {
+    state: Arc<Mutex<StubState>>,
+}
+
+fn not_found<T: Send + 'static>(what: &str) -> SingleResponse<T> {
+    SingleResponse::err(GrpcError::GrpcMessage(GrpcMessageError {
+        grpc_status: GrpcStatus::NotFound as i32,
+        grpc_message: format!("{} not found", what),
+    }))
+}
+
+fn failed<T: Send + 'static>(message: &str) -> SingleResponse<T> {
+    SingleResponse::err(GrpcError::GrpcMessage(GrpcMessageError {
+        grpc_status: GrpcStatus::FailedPrecondition as i32,
+        grpc_message: message.to_string(),
+    }))
+}
+
+fn matches(labels: &HashMap<String, String>, selector: &HashMap<String, String>) -> bool {
+    selector
+        .iter()
+        .all(|(key, value)| labels.get(key) == Some(value))
+}
+
+fn now() -> i64 {
+    Utc::now().timestamp_nanos()
+}
+
+fn append_log(path: &Path, line: &str) {
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .unwrap();
+    writeln!(file, "{} stdout F {}", Utc::now().to_rfc3339(), line).unwrap();
+}
+
+impl StubCri {
+    // The log file of a container, in the log directory of its sandbox.
+    fn log_file(&self, id: &str) -> Option<PathBuf> {
+        let state = self.state.lock().unwrap();
+        let &(ref container, ref status) = state
+            .containers
+            .iter()
+            .find(|&&(ref c, _)| c.get_id() == id)?;
+        let &(_, ref config) = state
+            .sandboxes
+            .iter()
+            .find(|&&(ref s, _)| s.get_id() == container.get_pod_sandbox_id())?;
+        Some(Path::new(config.get_log_directory()).join(status.get_log_path()))
+    }
+
+    fn with_container<T, F>(&self, id: &str, f: F) -> SingleResponse<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut ContainerStatus) -> Result<T, String>,
+    {
+        let mut state = self.state.lock().unwrap();
+        match state
+            .containers
+            .iter_mut()
+            .find(|&&mut (ref c, _)| c.get_id() == id)
+        {
+            Some(&mut (ref mut container, ref mut status)) => match f(status) {
+                Ok(response) => {
+                    container.set_state(status.get_state());
+                    SingleResponse::completed(response)
+                }
+                Err(message) => failed(&message),
+            },
+            None => not_found(id),
+        }
+    }
+
+    // Simulates the main process of a module exiting.
+    fn exit(&self, name: &str, exit_code: i32) {
+        let mut state = self.state.lock().unwrap();
+        for &mut (ref mut container, ref mut status) in &mut state.containers {
+            if container.get_metadata().get_name() == name
+                && container.get_state() == ContainerState::CONTAINER_RUNNING
+            {
+                container.set_state(ContainerState::CONTAINER_EXITED);
+                status.set_state(ContainerState::CONTAINER_EXITED);
+                status.set_exit_code(exit_code);
+                status.set_finished_at(now());
+            }
+        }
+    }
+}
+
+impl RuntimeService for StubCri {
+    fn version(&self, _o: RequestOptions, _p: VersionRequest) -> SingleResponse<VersionResponse> {
+        let mut response = VersionResponse::new();
+        response.set_runtime_name("stub".to_string());
+        response.set_runtime_version("0.1.0".to_string());
+        response.set_runtime_api_version("v1alpha2".to_string());
+        SingleResponse::completed(response)
+    }
+
+    fn run_pod_sandbox(
+        &self,
+        _o: RequestOptions,
+        mut p: RunPodSandboxRequest,
+    ) -> SingleResponse<RunPodSandboxResponse> {
+        let mut state = self.state.lock().unwrap();
+        state.next_id += 1;
+        let config = p.take_config();
+
+        let mut sandbox = PodSandbox::new();
+        sandbox.set_id(format!("sandbox-{}", state.next_id));
+        sandbox.set_metadata(config.get_metadata().clone());
+        sandbox.set_state(PodSandboxState::SANDBOX_READY);
+        sandbox.set_created_at(now());
+        sandbox.set_labels(config.get_labels().clone());
+        sandbox.set_annotations(config.get_annotations().clone());
+
+        let mut response = RunPodSandboxResponse::new();
+        response.set_pod_sandbox_id(sandbox.get_id().to_string());
+        state.sandboxes.push((sandbox, config));
+        SingleResponse::completed(response)
+    }
+
+    fn stop_pod_sandbox(
+        &self,
+        _o: RequestOptions,
+        p: StopPodSandboxRequest,
+    ) -> SingleResponse<StopPodSandboxResponse> {
+        let mut state = self.state.lock().unwrap();
+        for &mut (ref mut container, ref mut status) in &mut state.containers {
+            if container.get_pod_sandbox_id() == p.get_pod_sandbox_id()
+                && container.get_state() == ContainerState::CONTAINER_RUNNING
+            {
+                container.set_state(ContainerState::CONTAINER_EXITED);
+                status.set_state(ContainerState::CONTAINER_EXITED);
+                status.set_exit_code(137);
+                status.set_finished_at(now());
+            }
+        }
+        SingleResponse::completed(StopPodSandboxResponse::new())
+    }
+
+    fn remove_pod_sandbox(
+        &self,
+        _o: RequestOptions,
+        p: RemovePodSandboxRequest,
+    ) -> SingleResponse<RemovePodSandboxResponse> {
+        let mut state = self.state.lock().unwrap();
+        let count = state.sandboxes.len();
+        state
+            .sandboxes
+            .retain(|&(ref s, _)| s.get_id() != p.get_pod_sandbox_id());
+        if count == state.sandboxes.len() {
+            return not_found(p.get_pod_sandbox_id());
+        }
+        state
+            .containers
+            .retain(|&(ref c, _)| c.get_pod_sandbox_id() != p.get_pod_sandbox_id());
+        SingleResponse::completed(RemovePodSandboxResponse::new())
+    }
+
+    fn list_pod_sandbox(
+        &self,
+        _o: RequestOptions,
+        p: ListPodSandboxRequest,
+    ) -> SingleResponse<ListPodSandboxResponse> {
+        let state = self.state.lock().unwrap();
+        let items = state
+            .sandboxes
+            .iter()
+            .map(|&(ref s, _)| s)
+            .filter(|s| matches(s.get_labels(), p.get_filter().get_label_selector()))
+            .cloned()
+            .collect();
+        let mut response = ListPodSandboxResponse::new();
+        response.set_items(items);
+        SingleResponse::completed(response)
+    }
+
+    fn create_container(
+        &self,
+        _o: RequestOptions,
+        mut p: CreateContainerRequest,
+    ) -> SingleResponse<CreateContainerResponse> {
+        let mut state = self.state.lock().unwrap();
+        if !state
+            .sandboxes
+            .iter()
+            .any(|&(ref s, _)| s.get_id() == p.get_pod_sandbox_id())
+        {
+            return not_found(p.get_pod_sandbox_id());
+        }
+        state.next_id += 1;
+        let config = p.take_config();
+
+        let mut container = Container::new();
+        container.set_id(format!("container-{}", state.next_id));
+        container.set_pod_sandbox_id(p.get_pod_sandbox_id().to_string());
+        container.set_metadata(config.get_metadata().clone());
+        container.set_image(config.get_image().clone());
+        container.set_state(ContainerState::CONTAINER_CREATED);
+        container.set_created_at(now());
+        container.set_labels(config.get_labels().clone());
+
+        let mut status = ContainerStatus::new();
+        status.set_id(container.get_id().to_string());
+        status.set_metadata(config.get_metadata().clone());
+        status.set_state(ContainerState::CONTAINER_CREATED);
+        status.set_created_at(container.get_created_at());
+        status.set_image(config.get_image().clone());
+        status.set_image_ref(format!("sha256:{}", config.get_image().get_image()));
+        status.set_labels(config.get_labels().clone());
+        // like in the config, relative to the log directory of the sandbox
+        status.set_log_path(config.get_log_path().to_string());
+
+        let mut response = CreateContainerResponse::new();
+        response.set_container_id(container.get_id().to_string());
+        state.containers.push((container, status));
+        SingleResponse::completed(response)
+    }
+
+    fn start_container(
+        &self,
+        _o: RequestOptions,
+        p: StartContainerRequest,
+    ) -> SingleResponse<StartContainerResponse> {
+        let log_file = self.log_file(p.get_container_id());
+        self.with_container(p.get_container_id(), move |status| {
+            if status.get_state() != ContainerState::CONTAINER_CREATED {
+                return Err("container is not in created state".to_string());
+            }
+            status.set_state(ContainerState::CONTAINER_RUNNING);
+            status.set_started_at(now());
+            if let Some(log_file) = log_file {
+                append_log(
+                    &log_file,
+                    &format!("started {}", status.get_metadata().get_name()),
+                );
+            }
+            Ok(StartContainerResponse::new())
+        })
+    }
+
+    fn stop_container(
+        &self,
+        _o: RequestOptions,
+        p: StopContainerRequest,
+    ) -> SingleResponse<StopContainerResponse> {
+        self.with_container(p.get_container_id(), |status| {
+            if status.get_state() == ContainerState::CONTAINER_RUNNING {
+                status.set_state(ContainerState::CONTAINER_EXITED);
+                status.set_exit_code(0);
+                status.set_finished_at(now());
+            }
+            Ok(StopContainerResponse::new())
+        })
+    }
+
+    fn remove_container(
+        &self,
+        _o: RequestOptions,
+        p: RemoveContainerRequest,
+    ) -> SingleResponse<RemoveContainerResponse> {
+        let mut state = self.state.lock().unwrap();
+        state
+            .containers
+            .retain(|&(ref c, _)| c.get_id() != p.get_container_id());
+        SingleResponse::completed(RemoveContainerResponse::new())
+    }
+
+    fn list_containers(
+        &self,
+        _o: RequestOptions,
+        p: ListContainersRequest,
+    ) -> SingleResponse<ListContainersResponse> {
+        let state = self.state.lock().unwrap();
+        let containers = state
+            .containers
+            .iter()
+            .map(|&(ref c, _)| c)
+            .filter(|c| matches(c.get_labels(), p.get_filter().get_label_selector()))
+            .cloned()
+            .collect();
+        let mut response = ListContainersResponse::new();
+        response.set_containers(containers);
+        SingleResponse::completed(response)
+    }
+
+    fn container_status(
+        &self,
+        _o: RequestOptions,
+        p: ContainerStatusRequest,
+    ) -> SingleResponse<ContainerStatusResponse> {
+        self.with_container(p.get_container_id(), |status| {
+            let mut response = ContainerStatusResponse::new();
+            response.set_status(status.clone());
+            Ok(response)
+        })
+    }
+
+    fn exec_sync(
+        &self,
+        _o: RequestOptions,
+        p: ExecSyncRequest,
+    ) -> SingleResponse<ExecSyncResponse> {
+        let output = format!("{}\n", p.get_cmd().join(" "));
+        self.with_container(p.get_container_id(), move |_| {
+            let mut response = ExecSyncResponse::new();
+            response.set_stdout(output.into_bytes());
+            Ok(response)
+        })
+    }
+
+    fn container_stats(
+        &self,
+        _o: RequestOptions,
+        p: ContainerStatsRequest,
+    ) -> SingleResponse<ContainerStatsResponse> {
+        self.with_container(p.get_container_id(), |_| {
+            let mut usage = UInt64Value::new();
+            usage.set_value(100);
+            let mut cpu = CpuUsage::new();
+            cpu.set_timestamp(now());
+            cpu.set_usage_core_nano_seconds(usage);
+
+            let mut working_set = UInt64Value::new();
+            working_set.set_value(200);
+            let mut memory = MemoryUsage::new();
+            memory.set_working_set_bytes(working_set);
+
+            let mut stats = ContainerStats::new();
+            stats.set_cpu(cpu);
+            stats.set_memory(memory);
+            let mut response = ContainerStatsResponse::new();
+            response.set_stats(stats);
+            Ok(response)
+        })
+    }
+}
+
+impl ImageService for StubCri {
+    fn pull_image(
+        &self,
+        _o: RequestOptions,
+        p: PullImageRequest,
+    ) -> SingleResponse<PullImageResponse> {
+        let image = p.get_image().get_image().to_string();
+        let mut response = PullImageResponse::new();
+        response.set_image_ref(format!("sha256:{}", image));
+        self.state.lock().unwrap().images.push(image);
+        SingleResponse::completed(response)
+    }
+
+    fn remove_image(
+        &self,
+        _o: RequestOptions,
+        p: RemoveImageRequest,
+    ) -> SingleResponse<RemoveImageResponse> {
+        let mut state = self.state.lock().unwrap();
+        let count = state.images.len();
+        state.images.retain(|i| i != p.get_image().get_image());
+        if count == state.images.len() {
+            not_found(p.get_image().get_image())
+        } else {
+            SingleResponse::completed(RemoveImageResponse::new())
+        }
+    }
+}
+
+struct Fixture {
+    stub: StubCri,
+    runtime: CriModuleRuntime,
+    // keep the server and the log directory alive for the whole test
+    _server: grpc::Server,
+    _log_dir: tempfile::TempDir,
+}
+
+fn fixture() -> Fixture {
+    let stub = StubCri::default();
+    let mut builder = ServerBuilder::new_plain();
+    builder.http.set_port(0);
+    builder.add_service(RuntimeServiceServer::new_service_def(stub.clone()));
+    builder.add_service(ImageServiceServer::new_service_def(stub.clone()));
+    let server = builder.build().unwrap();
+
+    let log_dir = tempdir().unwrap();
+    let uri = Url::parse(&format!("http://127.0.0.1:{}", server.local_addr().port())).unwrap();
+    let runtime = CriModuleRuntime::new(&uri, log_dir.path()).unwrap();
+    runtime.init().wait().unwrap();
+
+    Fixture {
+        stub,
+        runtime,
+        _server: server,
+        _log_dir: log_dir,
+    }
+}
+
+fn spec(name: &str) -> ModuleSpec<CriConfig> {
+    let config = CriConfig::new("microsoft/azureiotedge-agent:1.0").unwrap();
+    let mut env = HashMap::new();
+    env.insert("K1".to_string(), "V1".to_string());
+    ModuleSpec::new(name, MODULE_TYPE, config, env).unwrap()
+}
+
+fn state(runtime: &CriModuleRuntime, name: &str) -> ModuleRuntimeState {
+    runtime
+        .list()
+        .wait()
+        .unwrap()
+        .into_iter()
+        .find(|m| m.name() == name)
+        .unwrap()
+        .runtime_state()
+        .wait()
+        .unwrap()
+}
+
+#[test]
+fn module_lifecycle() {
+    let fixture = fixture();
+    let runtime = &fixture.runtime;
+
+    let spec = spec("mod1").with_record_spec_hash(true);
+    runtime.create(spec.clone()).wait().unwrap();
+    let modules = runtime.list().wait().unwrap();
+    assert_eq!(1, modules.len());
+    let hash = spec.spec_hash().unwrap();
+    assert_eq!(Some(hash.as_str()), modules[0].spec_hash());
+    assert_eq!(ModuleStatus::Created, *state(runtime, "mod1").status());
+
+    runtime.start("mod1").wait().unwrap();
+    assert_eq!(ModuleStatus::Running, *state(runtime, "mod1").status());
+
+    let logs = runtime
+        .logs("mod1", &LogOptions::new().with_tail(LogTail::All))
+        .and_then(|logs| logs.concat2())
+        .wait()
+        .unwrap();
+    assert_eq!(b"started mod1\n", &logs.as_ref()[8..]);
+
+    runtime.stop("mod1", None).wait().unwrap();
+    let stopped = state(runtime, "mod1");
+    assert_eq!(ModuleStatus::Stopped, *stopped.status());
+    assert_eq!(Some(&0), stopped.exit_code());
+
+    // an exited container is replaced by a new one
+    runtime.start("mod1").wait().unwrap();
+    fixture.stub.exit("mod1", 1);
+    assert_eq!(ModuleStatus::Failed, *state(runtime, "mod1").status());
+    runtime.restart("mod1").wait().unwrap();
+    let restarted = state(runtime, "mod1");
+    assert_eq!(ModuleStatus::Running, *restarted.status());
+    assert_eq!(2, restarted.restart_count());
+
+    runtime.remove("mod1").wait().unwrap();
+    assert!(runtime.list().wait().unwrap().is_empty());
+}
+
+#[test]
+fn create_existing_module_fails() {
+    let fixture = fixture();
+    fixture.runtime.create(spec("mod1")).wait().unwrap();
+
+    let err = fixture.runtime.create(spec("mod1")).wait().unwrap_err();
+    assert!(match *err.kind() {
+        ErrorKind::Conflict => true,
+        _ => false,
+    });
+}
+
+#[test]
+fn missing_module_is_not_found() {
+    let fixture = fixture();
+
+    let err = fixture.runtime.start("mod1").wait().unwrap_err();
+    assert!(match *err.kind() {
+        ErrorKind::NotFound => true,
+        _ => false,
+    });
+    let err = fixture.runtime.remove("mod1").wait().unwrap_err();
+    assert!(match *err.kind() {
+        ErrorKind::NotFound => true,
+        _ => false,
+    });
+}
+
+#[test]
+fn stop_created_module_is_not_modified() {
+    let fixture = fixture();
+    fixture.runtime.create(spec("mod1")).wait().unwrap();
+
+    let err = fixture.runtime.stop("mod1", None).wait().unwrap_err();
+    assert!(match *err.kind() {
+        ErrorKind::NotModified => true,
+        _ => false,
+    });
+    let err = fixture.runtime.pause("mod1").wait().unwrap_err();
+    assert!(match *err.kind() {
+        ErrorKind::NotSupported(_) => true,
+        _ => false,
+    });
+}
+
+#[test]
+fn exec_and_stats() {
+    let fixture = fixture();
+    fixture.runtime.create(spec("mod1")).wait().unwrap();
+
+    let options = ExecOptions::new(vec!["echo".to_string(), "hello".to_string()]);
+    let err = fixture.runtime.exec("mod1", &options).wait().unwrap_err();
+    assert!(match *err.kind() {
+        ErrorKind::Conflict => true,
+        _ => false,
+    });
+
+    fixture.runtime.start("mod1").wait().unwrap();
+    let output = fixture
+        .runtime
+        .exec("mod1", &options)
+        .and_then(|logs| logs.concat2())
+        .wait()
+        .unwrap();
+    assert_eq!(b"echo hello\n", &output.as_ref()[8..]);
+
+    let stats = fixture
+        .runtime
+        .stats("mod1", &Default::default())
+        .and_then(|stats| stats.collect())
+        .wait()
+        .unwrap();
+    assert_eq!(100, stats[0].cpu_total_usage());
+    assert_eq!(200, stats[0].memory_usage());
+}
+
+#[test]
+fn registry_pulls_and_removes_images() {
+    let fixture = fixture();
+    let registry = fixture.runtime.registry();
+    let image = "microsoft/azureiotedge-agent:1.0";
+    registry
+        .pull(&CriConfig::new(image).unwrap())
+        .wait()
+        .unwrap();
+    assert_eq!(
+        vec![image.to_string()],
+        fixture.stub.state.lock().unwrap().images
+    );
+
+    ModuleRegistry::remove(registry, image).wait().unwrap();
+    let err = ModuleRegistry::remove(registry, image).wait().unwrap_err();
+    assert!(match *err.kind() {
+        ErrorKind::NotFound => true,
+        _ => false,
+    });
+}
+
+#[test]
+fn remove_all_removes_every_module() {
+    let fixture = fixture();
+    fixture.runtime.create(spec("mod1")).wait().unwrap();
+    fixture.runtime.create(spec("mod2")).wait().unwrap();
+
+    fixture.runtime.remove_all().wait().unwrap();
+    assert!(fixture.runtime.list().wait().unwrap().is_empty());
+}
@@ -0,0 +1,446 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+use std::path::Path;
+
+use docker::models::{ContainerCreateBody, HostConfig};
+use edgelet_core::admission::AdmissionSettings;
+
+use error::{Error, ErrorKind, Result};
+
+/// Network mode of modules that do not set one.
+const DEFAULT_NETWORK_MODE: &str = "default";
+
+/// Decides whether a module may be created with its create options.
+#[derive(Clone, Debug, Default)]
+pub struct AdmissionPolicy {
+    settings: AdmissionSettings,
+    daemon_sockets: Vec<String>,
+}
+
+impl AdmissionPolicy {
+    pub fn new(settings: AdmissionSettings) -> Self {
+        AdmissionPolicy {
+            settings,
+            daemon_sockets: Vec::new(),
+        }
+    }
+
+    /// Host paths of the sockets of the management and workload APIs. The
+    /// edge agent bind mounts them into modules, so they are allowed whatever
+    /// the bind path rules say.
+    pub fn with_daemon_sockets(mut self, daemon_sockets: Vec<String>) -> Self {
+        self.daemon_sockets = daemon_sockets;
+        self
+    }
+
+    /// Checks the host config of a module. The error names the first rule
+    /// the options violate.
+    pub fn check(&self, name: &str, create_options: &ContainerCreateBody) -> Result<()> {
+        let default_host_config = HostConfig::new();
+        let host_config = create_options.host_config().unwrap_or(&default_host_config);
+        self.violation(host_config).map_or(Ok(()), |rule| {
+            Err(Error::from(ErrorKind::AdmissionDenied(
+                name.to_string(),
+                rule,
+            )))
+        })
+    }
+
+    fn violation(&self, host_config: &HostConfig) -> Option<String> {
+        let settings = &self.settings;
+
+        if !settings.allow_privileged() && host_config.privileged() == Some(&true) {
+            return Some("allow_privileged".to_string());
+        }
+        if !settings.allow_host_pid() && host_config.pid_mode().map(String::as_str) == Some("host")
+        {
+            return Some("allow_host_pid".to_string());
+        }
+        if !settings.allow_host_ipc() && host_config.ipc_mode().map(String::as_str) == Some("host")
+        {
+            return Some("allow_host_ipc".to_string());
+        }
+        if !settings.allow_host_userns()
+            && host_config.userns_mode().map(String::as_str) == Some("host")
+        {
+            return Some("allow_host_userns".to_string());
+        }
+
+        let capabilities = host_config.cap_add().map_or(&[][..], |c| c.as_slice());
+        for capability in capabilities {
+            let capability = capability.to_uppercase();
+            let capability = capability.trim_left_matches("CAP_");
+            if !settings.capabilities().permits_capability(capability) {
+                return Some(format!("capabilities: {}", capability));
+            }
+        }
+
+        let security_options = host_config.security_opt().map_or(&[][..], |s| s.as_slice());
+        for option in security_options {
+            let option = security_option(option);
+            if !settings.security_options().permits(&option) {
+                return Some(format!("security_options: {}", option));
+            }
+        }
+
+        let binds = host_config.binds().map_or(&[][..], |b| b.as_slice());
+        let mounts = host_config.mounts().map_or(&[][..], |m| m.as_slice());
+        let bind_paths = binds.iter().filter_map(|bind| bind_source(bind)).chain(
+            mounts
+                .iter()
+                .filter(|mount| mount._type().map(String::as_str) == Some("bind"))
+                .filter_map(|mount| mount.source().map(String::as_str)),
+        );
+        for path in bind_paths {
+            let daemon_socket = self
+                .daemon_sockets
+                .iter()
+                .any(|socket| Path::new(socket) == Path::new(path));
+            if !daemon_socket && !settings.bind_paths().permits_path(path) {
+                return Some(format!("bind_paths: {}", path));
+            }
+        }
+
+        let devices = host_config.devices().map_or(&[][..], |d| d.as_slice());
+        for path in devices.iter().filter_map(|d| d.path_on_host()) {
+            if !settings.devices().permits_path(path) {
+                return Some(format!("devices: {}", path));
+            }
+        }
+
+        let network_mode = host_config
+            .network_mode()
+            .map_or(DEFAULT_NETWORK_MODE, String::as_str);
+        if !settings.network_modes().permits(network_mode) {
+            return Some(format!("network_modes: {}", network_mode));
+        }
+
+        if let Some(max) = settings.max_memory_bytes() {
+            if !within(host_config.memory().cloned(), max) {
+                return Some("max_memory_bytes".to_string());
+            }
+        }
+        if let Some(max) = settings.max_nano_cpus() {
+            if !within(nano_cpus(host_config), max) {
+                return Some("max_nano_cpus".to_string());
+            }
+        }
+
+        None
+    }
+}
+
+// "/host/path:/container/path:ro" -> "/host/path". Binds of named volumes
+// have no host path.
+pub fn bind_source(bind: &str) -> Option<&str> {
+    // skip the colon of a Windows drive letter
+    let start = if bind.get(1..2) == Some(":") { 2 } else { 0 };
+    let source = match bind[start..].find(':') {
+        Some(index) => &bind[..start + index],
+        None => bind,
+    };
+    if start > 0 || source.starts_with('/') || source.starts_with('\\') {
+        Some(source)
+    } else {
+        None
+    }
+}
+
+// "seccomp:unconfined" -> "seccomp=unconfined". Docker still accepts the
+// older ":" separator.
+fn security_option(option: &str) -> String {
+    if option.contains('=') {
+        option.to_string()
+    } else {
+        option.replacen(':', "=", 1)
+    }
+}
+
+// NanoCpus, or the equivalent of CpuQuota and CpuPeriod.
+fn nano_cpus(host_config: &HostConfig) -> Option<i64> {
+    match host_config.nano_cp_us() {
+        Some(&nano_cpus) if nano_cpus > 0 => Some(nano_cpus),
+        _ => match (host_config.cpu_quota(), host_config.cpu_period()) {
+            (Some(&quota), Some(&period)) if quota > 0 && period > 0 => {
+                Some(quota * 1_000_000_000 / period)
+            }
+            _ => None,
+        },
+    }
+}
+
+// Docker treats a limit of 0 as no limit.
+fn within(limit: Option<i64>, max: u64) -> bool {
+    match limit {
+        Some(limit) if limit > 0 => limit as u64 <= max,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use docker::models::{DeviceMapping, Mount};
+    use edgelet_core::admission::RuleList;
+    use serde_json;
+
+    fn rules(allow: Option<&[&str]>, deny: &[&str]) -> RuleList {
+        let strings = |values: &[&str]| values.iter().map(|v| v.to_string()).collect();
+        RuleList::new(allow.map(strings), strings(deny))
+    }
+
+    fn violation(settings: AdmissionSettings, host_config: HostConfig) -> Option<String> {
+        let create_options = ContainerCreateBody::new().with_host_config(host_config);
+        match AdmissionPolicy::new(settings).check("mod1", &create_options) {
+            Ok(()) => None,
+            Err(err) => match *err.kind() {
+                ErrorKind::AdmissionDenied(ref name, ref rule) => {
+                    assert_eq!("mod1", name);
+                    Some(rule.clone())
+                }
+                _ => panic!("Expected admission denied, got {:?}", err),
+            },
+        }
+    }
+
+    fn privileged() -> HostConfig {
+        HostConfig::new()
+            .with_privileged(true)
+            .with_pid_mode("host".to_string())
+            .with_ipc_mode("host".to_string())
+            .with_userns_mode("host".to_string())
+            .with_security_opt(vec!["seccomp:unconfined".to_string()])
+            .with_cap_add(vec!["CAP_SYS_ADMIN".to_string()])
+            .with_binds(vec!["/:/host".to_string()])
+            .with_devices(vec![
+                DeviceMapping::new().with_path_on_host("/dev/mem".to_string())
+            ])
+            .with_network_mode("host".to_string())
+    }
+
+    #[test]
+    fn default_policy_allows_everything() {
+        assert_eq!(None, violation(AdmissionSettings::default(), privileged()));
+
+        let create_options = ContainerCreateBody::new();
+        assert!(AdmissionPolicy::default()
+            .check("mod1", &create_options)
+            .is_ok());
+    }
+
+    #[test]
+    fn privileged_and_host_pid_are_denied() {
+        let settings = AdmissionSettings::new().with_allow_privileged(false);
+        assert_eq!(
+            Some("allow_privileged".to_string()),
+            violation(settings, privileged())
+        );
+
+        let settings = AdmissionSettings::new().with_allow_host_pid(false);
+        assert_eq!(
+            Some("allow_host_pid".to_string()),
+            violation(settings, privileged())
+        );
+
+        let settings = AdmissionSettings::new().with_allow_host_ipc(false);
+        assert_eq!(
+            Some("allow_host_ipc".to_string()),
+            violation(settings, privileged())
+        );
+
+        let settings = AdmissionSettings::new().with_allow_host_userns(false);
+        assert_eq!(
+            Some("allow_host_userns".to_string()),
+            violation(settings, privileged())
+        );
+    }
+
+    #[test]
+    fn security_options_are_checked() {
+        let settings =
+            AdmissionSettings::new().with_security_options(rules(None, &["seccomp=unconfined"]));
+        assert_eq!(
+            Some("security_options: seccomp=unconfined".to_string()),
+            violation(settings.clone(), privileged())
+        );
+        let host_config =
+            HostConfig::new().with_security_opt(vec!["no-new-privileges".to_string()]);
+        assert_eq!(None, violation(settings, host_config));
+    }
+
+    #[test]
+    fn capabilities_are_checked_without_prefix() {
+        let settings = AdmissionSettings::new().with_capabilities(rules(Some(&["NET_ADMIN"]), &[]));
+        assert_eq!(
+            Some("capabilities: SYS_ADMIN".to_string()),
+            violation(settings.clone(), privileged())
+        );
+        let host_config = HostConfig::new().with_cap_add(vec!["net_admin".to_string()]);
+        assert_eq!(None, violation(settings.clone(), host_config));
+        let host_config = HostConfig::new().with_cap_add(vec!["ALL".to_string()]);
+        assert_eq!(
+            Some("capabilities: ALL".to_string()),
+            violation(settings, host_config)
+        );
+    }
+
+    #[test]
+    fn binds_and_mounts_are_checked() {
+        let settings =
+            AdmissionSettings::new().with_bind_paths(rules(Some(&["/var/lib/modules"]), &[]));
+        assert_eq!(
+            Some("bind_paths: /".to_string()),
+            violation(settings.clone(), privileged())
+        );
+
+        let host_config = HostConfig::new()
+            .with_binds(vec![
+                "/var/lib/modules/mod1:/data:ro".to_string(),
+                "mod1-volume:/cache".to_string(),
+            ])
+            .with_mounts(vec![Mount::new()
+                .with__type("bind".to_string())
+                .with_source("/etc".to_string())]);
+        assert_eq!(
+            Some("bind_paths: /etc".to_string()),
+            violation(settings.clone(), host_config)
+        );
+
+        let host_config =
+            HostConfig::new().with_binds(vec!["/var/lib/modules/../../../etc:/etc".to_string()]);
+        assert_eq!(
+            Some("bind_paths: /var/lib/modules/../../../etc".to_string()),
+            violation(settings, host_config)
+        );
+    }
+
+    #[test]
+    fn daemon_sockets_are_always_bindable() {
+        let settings =
+            AdmissionSettings::new().with_bind_paths(rules(Some(&["/var/lib/modules"]), &[]));
+        let policy = AdmissionPolicy::new(settings)
+            .with_daemon_sockets(vec!["/var/run/iotedge/workload.sock".to_string()]);
+
+        let create_options =
+            ContainerCreateBody::new().with_host_config(HostConfig::new().with_binds(vec![
+                "/var/run/iotedge/workload.sock:/var/run/iotedge/workload.sock".to_string(),
+            ]));
+        assert!(policy.check("mod1", &create_options).is_ok());
+
+        let create_options = ContainerCreateBody::new().with_host_config(
+            HostConfig::new().with_binds(vec!["/var/run/iotedge:/var/run/iotedge".to_string()]),
+        );
+        assert!(policy.check("mod1", &create_options).is_err());
+    }
+
+    // the example policy of config.yaml
+    #[test]
+    fn example_policy_admits_edge_agent_modules() {
+        let settings = AdmissionSettings::new()
+            .with_allow_privileged(false)
+            .with_allow_host_pid(false)
+            .with_allow_host_ipc(false)
+            .with_capabilities(rules(Some(&["NET_ADMIN"]), &[]))
+            .with_bind_paths(rules(Some(&["/var/lib/iotedge-modules"]), &[]))
+            .with_devices(rules(None, &["/dev/mem"]))
+            .with_network_modes(rules(None, &["host"]))
+            .with_security_options(rules(
+                None,
+                &["seccomp=unconfined", "apparmor=unconfined", "label=disable"],
+            ));
+        let policy = AdmissionPolicy::new(settings).with_daemon_sockets(vec![
+            "/var/run/iotedge/mgmt.sock".to_string(),
+            "/var/run/iotedge/workload.sock".to_string(),
+        ]);
+
+        // the create options the edge agent gives the edge hub
+        let create_options: ContainerCreateBody = serde_json::from_value(json!({
+            "Image": "mcr.microsoft.com/azureiotedge-hub:1.0",
+            "Env": ["IOTEDGE_WORKLOADURI=unix:///var/run/iotedge/workload.sock"],
+            "HostConfig": {
+                "Binds": ["/var/run/iotedge/workload.sock:/var/run/iotedge/workload.sock"],
+                "PortBindings": {
+                    "8883/tcp": [{ "HostPort": "8883" }],
+                    "443/tcp": [{ "HostPort": "443" }]
+                }
+            },
+            "NetworkingConfig": {
+                "EndpointsConfig": {
+                    "azure-iot-edge": { "Aliases": ["edgeHub"] }
+                }
+            }
+        })).unwrap();
+        assert!(policy.check("edgeHub", &create_options).is_ok());
+
+        let create_options = ContainerCreateBody::new()
+            .with_host_config(HostConfig::new().with_network_mode("host".to_string()));
+        assert!(policy.check("mod1", &create_options).is_err());
+    }
+
+    #[test]
+    fn devices_and_network_modes_are_checked() {
+        let settings = AdmissionSettings::new().with_devices(rules(None, &["/dev/mem"]));
+        assert_eq!(
+            Some("devices: /dev/mem".to_string()),
+            violation(settings, privileged())
+        );
+
+        let settings =
+            AdmissionSettings::new().with_network_modes(rules(Some(&["azure-iot-edge"]), &[]));
+        assert_eq!(
+            Some("network_modes: host".to_string()),
+            violation(settings.clone(), privileged())
+        );
+        assert_eq!(
+            Some("network_modes: default".to_string()),
+            violation(settings.clone(), HostConfig::new())
+        );
+        let host_config = HostConfig::new().with_network_mode("azure-iot-edge".to_string());
+        assert_eq!(None, violation(settings, host_config));
+    }
+
+    #[test]
+    fn resource_ceilings_require_limits() {
+        let settings = AdmissionSettings::new()
+            .with_max_memory_bytes(Some(256 * 1024 * 1024))
+            .with_max_nano_cpus(Some(500_000_000));
+        assert_eq!(
+            Some("max_memory_bytes".to_string()),
+            violation(settings.clone(), HostConfig::new())
+        );
+        assert_eq!(
+            Some("max_memory_bytes".to_string()),
+            violation(
+                settings.clone(),
+                HostConfig::new().with_memory(512 * 1024 * 1024)
+            )
+        );
+        assert_eq!(
+            Some("max_nano_cpus".to_string()),
+            violation(
+                settings.clone(),
+                HostConfig::new()
+                    .with_memory(128 * 1024 * 1024)
+                    .with_cpu_quota(100_000)
+                    .with_cpu_period(100_000)
+            )
+        );
+        assert_eq!(
+            None,
+            violation(
+                settings,
+                HostConfig::new()
+                    .with_memory(128 * 1024 * 1024)
+                    .with_nano_cp_us(250_000_000)
+            )
+        );
+    }
+
+    #[test]
+    fn bind_sources() {
+        assert_eq!(Some("/var/data"), bind_source("/var/data:/data:ro"));
+        assert_eq!(Some("C:\\data"), bind_source("C:\\data:C:\\data"));
+        assert_eq!(None, bind_source("volume:/data"));
+    }
+}
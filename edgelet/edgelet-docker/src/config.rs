@@ -1,10 +1,24 @@
 // Copyright (c) Microsoft. All rights reserved.
 
 use docker::models::{AuthConfig, ContainerCreateBody};
+use edgelet_core::ImagePullPolicy;
 use edgelet_utils::serde_clone;
 
 use error::Result;
 
+/// Image hashes with this prefix are manifest digests a registry can pull by.
+const MANIFEST_DIGEST_PREFIX: &str = "sha256:";
+
+/// Splits an image reference into its repository and tag, dropping any
+/// digest. "registry:5000/module:1.0" -> ("registry:5000/module", Some("1.0"))
+pub fn split_reference(reference: &str) -> (&str, Option<&str>) {
+    let name = reference.split('@').next().unwrap_or(reference);
+    match name.rfind(':') {
+        Some(pos) if !name[pos..].contains('/') => (&name[..pos], Some(&name[pos + 1..])),
+        _ => (name, None),
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct DockerConfig {
@@ -16,6 +30,10 @@ pub struct DockerConfig {
     create_options: ContainerCreateBody,
     #[serde(skip_serializing_if = "Option::is_none")]
     auth: Option<AuthConfig>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    signature: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pull_policy: Option<ImagePullPolicy>,
 }
 
 impl DockerConfig {
@@ -29,6 +47,8 @@ impl DockerConfig {
             image_id: None,
             create_options,
             auth,
+            signature: None,
+            pull_policy: None,
         };
         Ok(config)
     }
@@ -55,6 +75,19 @@ impl DockerConfig {
         self
     }
 
+    /// The reference the image is pulled with. With a manifest digest as the
+    /// image hash the image is pulled by digest, so a tag repointed in the
+    /// registry is not used. Other hashes are image IDs, which registries do
+    /// not know, so the image is pulled by tag and checked against the hash.
+    pub fn pull_reference(&self) -> String {
+        match self.image_id {
+            Some(ref digest) if digest.starts_with(MANIFEST_DIGEST_PREFIX) => {
+                format!("{}@{}", split_reference(&self.image).0, digest)
+            }
+            _ => self.image.clone(),
+        }
+    }
+
     pub fn create_options(&self) -> &ContainerCreateBody {
         &self.create_options
     }
@@ -76,6 +109,25 @@ impl DockerConfig {
         self.auth = Some(auth);
         self
     }
+
+    /// The base64 encoded detached signature of the image digest.
+    pub fn signature(&self) -> Option<&str> {
+        self.signature.as_ref().map(AsRef::as_ref)
+    }
+
+    pub fn with_signature(mut self, signature: String) -> Self {
+        self.signature = Some(signature);
+        self
+    }
+
+    pub fn pull_policy(&self) -> ImagePullPolicy {
+        self.pull_policy.unwrap_or_default()
+    }
+
+    pub fn with_pull_policy(mut self, pull_policy: ImagePullPolicy) -> Self {
+        self.pull_policy = Some(pull_policy);
+        self
+    }
 }
 
 #[cfg(test)]
@@ -98,6 +150,32 @@ mod tests {
         DockerConfig::new("    ", ContainerCreateBody::new(), None).unwrap();
     }
 
+    #[test]
+    fn split_reference_separates_tag() {
+        assert_eq!(("ubuntu", None), split_reference("ubuntu"));
+        assert_eq!(("ubuntu", Some("18.04")), split_reference("ubuntu:18.04"));
+        assert_eq!(
+            ("localhost:5000/edge/agent", Some("1.0")),
+            split_reference("localhost:5000/edge/agent:1.0@sha256:abc")
+        );
+        assert_eq!(
+            ("localhost:5000/edge/agent", None),
+            split_reference("localhost:5000/edge/agent")
+        );
+    }
+
+    #[test]
+    fn pull_reference_pins_digest() {
+        let config = DockerConfig::new("ubuntu:18.04", ContainerCreateBody::new(), None).unwrap();
+        assert_eq!("ubuntu:18.04", config.pull_reference());
+
+        let pinned = config.clone().with_image_id("sha256:abc".to_string());
+        assert_eq!("ubuntu@sha256:abc", pinned.pull_reference());
+
+        let config = config.with_image_id("abc".to_string());
+        assert_eq!("ubuntu:18.04", config.pull_reference());
+    }
+
     #[test]
     fn docker_config_ser() {
         let mut labels = HashMap::new();
@@ -202,6 +280,30 @@ mod tests {
         });
         let config = serde_json::from_str::<DockerConfig>(&input_json.to_string()).unwrap();
         assert_eq!(config.image, "ubuntu");
+        assert_eq!(None, config.signature());
+        assert_eq!(ImagePullPolicy::Always, config.pull_policy());
+    }
+
+    #[test]
+    fn docker_config_deser_signature() {
+        let input_json = json!({
+            "image": "ubuntu",
+            "imageHash": "sha256:abc",
+            "signature": "MEUCIQ=="
+        });
+        let config = serde_json::from_str::<DockerConfig>(&input_json.to_string()).unwrap();
+        assert_eq!(Some(&"sha256:abc".to_string()), config.image_id());
+        assert_eq!(Some("MEUCIQ=="), config.signature());
+    }
+
+    #[test]
+    fn docker_config_deser_pull_policy() {
+        let input_json = json!({
+            "image": "ubuntu",
+            "pullPolicy": "never"
+        });
+        let config = serde_json::from_str::<DockerConfig>(&input_json.to_string()).unwrap();
+        assert_eq!(ImagePullPolicy::Never, config.pull_policy());
     }
 
     #[test]
@@ -44,6 +44,23 @@ pub enum ErrorKind {
     Docker,
     #[fail(display = "Container runtime error - {:?}", _0)]
     DockerRuntime(DockerError<serde_json::Value>),
+    #[fail(display = "Image {} does not match digest {}, found {}", _0, _1, _2)]
+    ImageDigestMismatch(String, String, String),
+    #[fail(display = "Image {} is not signed by a trusted key", _0)]
+    UntrustedImage(String),
+    #[fail(display = "Invalid trusted key {}", _0)]
+    InvalidTrustedKey(String),
+    #[fail(display = "Image {} was not found and its pull policy is never", _0)]
+    LocalImageNotFound(String),
+    #[fail(display = "Module {} violates admission rule {}", _0, _1)]
+    AdmissionDenied(String, String),
+    #[fail(
+        display = "Module {} depends on {}, which did not become ready within {} seconds",
+        _0, _1, _2
+    )]
+    DependencyNotReady(String, String, u64),
+    #[fail(display = "Volume {} is not mounted by any module", _0)]
+    VolumeNotMounted(String),
     #[fail(display = "Core error")]
     Core,
     #[fail(display = "Http error")]
@@ -0,0 +1,128 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+use chrono::prelude::*;
+use futures::{Async, Poll, Stream};
+
+use docker::models::InlineResponse20012 as DockerEvent;
+use edgelet_core::{ModuleAction, ModuleEvent};
+use edgelet_http::JsonLines;
+
+use error::Error;
+use runtime::Logs;
+
+/// Container actions that are forwarded to subscribers. Everything else the
+/// docker engine reports (attach, exec, resize, ...) is filtered out by the
+/// engine itself.
+pub const EVENT_ACTIONS: &[&str] = &[
+    "create",
+    "start",
+    "stop",
+    "restart",
+    "kill",
+    "die",
+    "oom",
+    "pause",
+    "unpause",
+    "destroy",
+    "health_status",
+];
+
+fn to_module_event(event: &DockerEvent) -> ModuleEvent {
+    let attributes = event.actor().and_then(|actor| actor.attributes());
+    let name = attributes
+        .and_then(|attributes| attributes.get("name"))
+        .or_else(|| event.actor().and_then(|actor| actor.ID()))
+        .cloned()
+        .unwrap_or_default();
+    let action = event
+        .action()
+        .map(|action| ModuleAction::from(action.as_str()))
+        .unwrap_or_else(|| ModuleAction::Other(String::new()));
+    let time = event
+        .time_nano()
+        .map(|nanos| Utc.timestamp(nanos / 1_000_000_000, (nanos % 1_000_000_000) as u32))
+        .or_else(|| event.time().map(|secs| Utc.timestamp(i64::from(*secs), 0)));
+    let exit_code = attributes
+        .and_then(|attributes| attributes.get("exitCode"))
+        .and_then(|code| code.parse().ok());
+
+    ModuleEvent::new(name, action)
+        .with_time(time)
+        .with_exit_code(exit_code)
+}
+
+/// Stream of lifecycle events for the containers owned by edgelet.
+pub struct Events(JsonLines<Logs, DockerEvent>);
+
+impl Events {
+    pub fn new(body: Logs) -> Self {
+        Events(JsonLines::new(body))
+    }
+}
+
+impl Stream for Events {
+    type Item = ModuleEvent;
+    type Error = Error;
+
+    fn poll(&mut self) -> Poll<Option<Self::Item>, Self::Error> {
+        let event = try_ready!(self.0.poll());
+        Ok(Async::Ready(event.as_ref().map(to_module_event)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use serde_json;
+
+    #[test]
+    fn docker_event_to_module_event() {
+        let event: DockerEvent = serde_json::from_value(json!({
+            "Type": "container",
+            "Action": "die",
+            "Actor": {
+                "ID": "ede54ee1afda366ab42f824e8a5ffd195155d853ceaec74a927f249ea270c743",
+                "Attributes": {
+                    "exitCode": "137",
+                    "image": "microsoft/azureiotedge-hub:1.0",
+                    "name": "edgeHub",
+                    "net.azure-devices.edge.owner": "Microsoft.Azure.Devices.Edge.Agent"
+                }
+            },
+            "time": 1533168000,
+            "timeNano": 1533168000123456789i64
+        })).unwrap();
+
+        let event = to_module_event(&event);
+        assert_eq!("edgeHub", event.name());
+        assert_eq!(ModuleAction::Die, *event.action());
+        assert_eq!(Some(&137), event.exit_code());
+        assert_eq!(
+            "2018-08-02T00:00:00.123456789+00:00",
+            event.time().unwrap().to_rfc3339()
+        );
+    }
+
+    #[test]
+    fn docker_health_event_to_module_event() {
+        let event: DockerEvent = serde_json::from_value(json!({
+            "Type": "container",
+            "Action": "health_status: unhealthy",
+            "Actor": {
+                "ID": "ede54ee1afda",
+                "Attributes": {}
+            },
+            "time": 1533168000
+        })).unwrap();
+
+        let event = to_module_event(&event);
+        assert_eq!("ede54ee1afda", event.name());
+        assert_eq!(
+            ModuleAction::Health("unhealthy".to_string()),
+            *event.action()
+        );
+        assert_eq!(None, event.exit_code());
+        assert_eq!(1_533_168_000, event.time().unwrap().timestamp());
+    }
+}
@@ -0,0 +1,298 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+use std::cmp;
+use std::collections::{HashMap, HashSet};
+
+use chrono::{DateTime, Duration as ChronoDuration, TimeZone, Utc};
+use docker::models::{Image, ImageSummary};
+use edgelet_core::PruneOptions;
+
+use config::split_reference;
+
+/// Reference docker reports for images without a tag.
+const UNTAGGED: &str = "<none>:<none>";
+
+/// The `repository:tag` references of an image, without the placeholder
+/// docker reports for untagged images.
+pub fn references(image: &ImageSummary) -> Vec<String> {
+    image
+        .repo_tags()
+        .iter()
+        .filter(|tag| tag.as_str() != UNTAGGED)
+        .cloned()
+        .collect()
+}
+
+pub fn created(image: &ImageSummary) -> DateTime<Utc> {
+    Utc.timestamp(i64::from(*image.created()), 0)
+}
+
+/// When the image was last pulled or tagged. Docker reports the zero time
+/// for images that were never tagged since they were built or loaded.
+pub fn last_tagged(image: &Image) -> Option<DateTime<Utc>> {
+    image
+        .metadata()
+        .and_then(|metadata| metadata.last_tag_time())
+        .and_then(|time| DateTime::parse_from_rfc3339(time).ok())
+        .map(|time| time.with_timezone(&Utc))
+}
+
+// The latest of when the image was created and when it was last known to be
+// pulled or used.
+fn last_used_time(
+    image: &ImageSummary,
+    last_used: &HashMap<String, DateTime<Utc>>,
+) -> DateTime<Utc> {
+    let created = created(image);
+    last_used
+        .get(image.id())
+        .map_or(created, |time| cmp::max(*time, created))
+}
+
+pub fn size(image: &ImageSummary) -> u64 {
+    if *image.size() > 0 {
+        *image.size() as u64
+    } else {
+        0
+    }
+}
+
+// "registry:5000/module:1.0" -> "registry:5000/module"
+fn repository(reference: &str) -> &str {
+    split_reference(reference).0
+}
+
+/// Selects the images to remove, in the order to remove them.
+///
+/// Images in `in_use` are never selected, and neither are the newest
+/// `keep_previous` other images of each repository or images created, pulled
+/// or used less than `min_age` ago, going by the times in `last_used` where
+/// known. The remaining images are selected least recently used first. With
+/// water marks nothing is selected while `usage` is at or below the high
+/// water mark, and selection stops once removing the selected images brings
+/// `usage` down to the low water mark.
+pub fn select_images<'a>(
+    images: &'a [ImageSummary],
+    in_use: &HashSet<String>,
+    last_used: &HashMap<String, DateTime<Utc>>,
+    usage: u64,
+    options: &PruneOptions,
+    now: DateTime<Utc>,
+) -> Vec<&'a ImageSummary> {
+    if let Some((high, _)) = options.water_marks() {
+        if usage <= high {
+            return Vec::new();
+        }
+    }
+
+    let mut unused: Vec<&ImageSummary> = images
+        .iter()
+        .filter(|image| !in_use.contains(image.id()))
+        .collect();
+    // newest first, so the images kept for each repository come first
+    unused.sort_by(|a, b| b.created().cmp(a.created()));
+
+    let mut kept = HashSet::new();
+    let mut per_repository: HashMap<&str, u32> = HashMap::new();
+    for image in &unused {
+        for reference in image.repo_tags() {
+            if reference == UNTAGGED {
+                continue;
+            }
+            let count = per_repository.entry(repository(reference)).or_insert(0);
+            if *count < options.keep_previous() {
+                *count += 1;
+                kept.insert(image.id());
+            }
+        }
+    }
+
+    let min_age =
+        ChronoDuration::from_std(options.min_age()).unwrap_or_else(|_| ChronoDuration::max_value());
+    let mut candidates: Vec<&ImageSummary> = unused
+        .iter()
+        .filter(|image| !kept.contains(image.id()))
+        .filter(|image| now.signed_duration_since(last_used_time(image, last_used)) >= min_age)
+        .cloned()
+        .collect();
+    candidates.reverse();
+    candidates.sort_by_key(|image| last_used_time(image, last_used));
+
+    if let Some((_, low)) = options.water_marks() {
+        let mut remaining = usage;
+        candidates = candidates
+            .into_iter()
+            .take_while(|image| {
+                let needed = remaining > low;
+                remaining = remaining.saturating_sub(size(image));
+                needed
+            }).collect();
+    }
+    candidates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::time::Duration;
+
+    fn image(id: &str, tags: &[&str], created: i32, size: i32) -> ImageSummary {
+        ImageSummary::new(
+            id.to_string(),
+            String::new(),
+            tags.iter().map(|t| t.to_string()).collect(),
+            Vec::new(),
+            created,
+            size,
+            0,
+            size,
+            HashMap::new(),
+            0,
+        )
+    }
+
+    fn ids(images: Vec<&ImageSummary>) -> Vec<&str> {
+        images.iter().map(|i| i.id().as_str()).collect()
+    }
+
+    fn now() -> DateTime<Utc> {
+        Utc.timestamp(10_000, 0)
+    }
+
+    fn images() -> Vec<ImageSummary> {
+        vec![
+            image("a1", &["edge/a:1"], 1000, 100),
+            image("a2", &["edge/a:2"], 2000, 100),
+            image("a3", &["edge/a:3"], 3000, 100),
+            image("b1", &["localhost:5000/b:1"], 1500, 100),
+            image("b2", &["localhost:5000/b:2"], 2500, 100),
+            image("c", &["<none>:<none>"], 500, 100),
+        ]
+    }
+
+    #[test]
+    fn repository_strips_tag_and_digest() {
+        assert_eq!("edge/a", repository("edge/a:1"));
+        assert_eq!("edge/a", repository("edge/a@sha256:abc"));
+        assert_eq!("localhost:5000/b", repository("localhost:5000/b:2"));
+        assert_eq!("localhost:5000/b", repository("localhost:5000/b"));
+    }
+
+    #[test]
+    fn images_in_use_are_never_selected() {
+        let images = images();
+        let in_use = ["a3", "b2"].iter().map(|s| s.to_string()).collect();
+        let selected = select_images(
+            &images,
+            &in_use,
+            &HashMap::new(),
+            0,
+            &PruneOptions::new(),
+            now(),
+        );
+        assert_eq!(vec!["c", "a1", "b1", "a2"], ids(selected));
+    }
+
+    #[test]
+    fn previous_versions_are_kept_per_repository() {
+        let images = images();
+        let in_use = ["a3"].iter().map(|s| s.to_string()).collect();
+        let options = PruneOptions::new().with_keep_previous(1);
+        let selected = select_images(&images, &in_use, &HashMap::new(), 0, &options, now());
+        assert_eq!(vec!["c", "a1", "b1"], ids(selected));
+    }
+
+    #[test]
+    fn young_images_are_kept() {
+        let images = images();
+        let options = PruneOptions::new().with_min_age(Duration::from_secs(8001));
+        let selected = select_images(
+            &images,
+            &HashSet::new(),
+            &HashMap::new(),
+            0,
+            &options,
+            now(),
+        );
+        assert_eq!(vec!["c", "a1", "b1"], ids(selected));
+    }
+
+    #[test]
+    fn recently_used_images_are_kept() {
+        let images = images();
+        let last_used = vec![("a1".to_string(), Utc.timestamp(9_000, 0))]
+            .into_iter()
+            .collect();
+        let options = PruneOptions::new().with_min_age(Duration::from_secs(7001));
+        let selected = select_images(&images, &HashSet::new(), &last_used, 0, &options, now());
+        assert_eq!(vec!["c", "b1", "a2", "b2"], ids(selected));
+    }
+
+    #[test]
+    fn least_recently_used_images_are_selected_first() {
+        let images = images();
+        let last_used = vec![("a1".to_string(), Utc.timestamp(2_200, 0))]
+            .into_iter()
+            .collect();
+        let options = PruneOptions::new().with_keep_previous(1);
+        let selected = select_images(&images, &HashSet::new(), &last_used, 0, &options, now());
+        assert_eq!(vec!["c", "b1", "a2", "a1"], ids(selected));
+    }
+
+    fn inspected(metadata: serde_json::Value) -> Image {
+        serde_json::from_value(json!({
+            "Id": "sha256:a1",
+            "Parent": "",
+            "Comment": "",
+            "Created": "2018-06-01T10:00:00Z",
+            "Container": "",
+            "DockerVersion": "18.02.0",
+            "Author": "",
+            "Architecture": "amd64",
+            "Os": "linux",
+            "Size": 100,
+            "VirtualSize": 100,
+            "GraphDriver": { "Name": "overlay2" },
+            "RootFS": { "Type": "layers" },
+            "Metadata": metadata
+        })).unwrap()
+    }
+
+    #[test]
+    fn last_tagged_reads_image_metadata() {
+        let image = inspected(json!({ "LastTagTime": "2018-06-01T10:00:00.5Z" }));
+        assert_eq!(
+            Some(Utc.ymd(2018, 6, 1).and_hms_milli(10, 0, 0, 500)),
+            last_tagged(&image)
+        );
+
+        let image = inspected(json!({}));
+        assert_eq!(None, last_tagged(&image));
+    }
+
+    #[test]
+    fn water_marks_limit_selection() {
+        let images = images();
+        let options = PruneOptions::new().with_water_marks(Some((600, 400)));
+        let selected = select_images(
+            &images,
+            &HashSet::new(),
+            &HashMap::new(),
+            600,
+            &options,
+            now(),
+        );
+        assert!(selected.is_empty());
+
+        let selected = select_images(
+            &images,
+            &HashSet::new(),
+            &HashMap::new(),
+            650,
+            &options,
+            now(),
+        );
+        assert_eq!(vec!["c", "a1", "b1"], ids(selected));
+    }
+}
@@ -13,6 +13,7 @@ extern crate hyper;
 extern crate lazy_static;
 #[macro_use]
 extern crate log;
+extern crate openssl;
 extern crate serde;
 #[macro_use]
 extern crate serde_derive;
@@ -20,6 +21,7 @@ extern crate serde_derive;
 #[cfg(not(test))]
 extern crate serde_json;
 extern crate tokio_core;
+extern crate tokio_timer;
 extern crate url;
 
 // Need macros from serde_json for unit tests.
@@ -40,14 +42,23 @@ extern crate edgelet_utils;
 #[cfg(test)]
 extern crate edgelet_test_utils;
 
+mod admission;
 mod client;
 mod config;
 mod error;
+mod events;
+mod image_gc;
 mod module;
+mod network;
 mod runtime;
+mod signature;
+mod stats;
+mod volume;
 
+pub use admission::AdmissionPolicy;
 pub use config::DockerConfig;
 pub use error::{Error, ErrorKind};
 pub use module::{DockerModule, MODULE_TYPE};
 
 pub use runtime::DockerModuleRuntime;
+pub use signature::{PublicKeyVerifier, SignaturePolicy};
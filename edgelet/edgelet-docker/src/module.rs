@@ -8,16 +8,30 @@ use hyper::client::Connect;
 
 use client::DockerClient;
 use config::DockerConfig;
-use edgelet_core::{pid::Pid, Module, ModuleRuntimeState, ModuleStatus};
+use edgelet_core::{pid::Pid, Module, ModuleHealth, ModuleRuntimeState, ModuleStatus};
 use error::{Error, Result};
 
 pub const MODULE_TYPE: &str = "docker";
+
+/// Label holding the hash of the `ModuleSpec` a container was created from,
+/// set only when the spec asks for it to be recorded.
+pub const SPEC_HASH_LABEL_KEY: &str = "net.azure-devices.edge.spec-hash";
+/// Label holding the image a module was configured with, for containers
+/// created from the id of the image that was checked before creating them.
+pub const IMAGE_LABEL_KEY: &str = "net.azure-devices.edge.image";
+/// Label listing the modules a container depends on, separated by commas.
+/// Its dependencies are started first and removed last.
+pub const DEPENDS_ON_LABEL_KEY: &str = "net.azure-devices.edge.depends-on";
+/// Label holding the status a module's deployment wants it in. A dependency
+/// that is meant to be stopped is not started for the modules depending on it.
+pub const DESIRED_STATUS_LABEL_KEY: &str = "net.azure-devices.edge.desired-status";
 pub const MIN_DATE: &str = "0001-01-01T00:00:00Z";
 
 pub struct DockerModule<C: Connect> {
     client: DockerClient<C>,
     name: String,
     config: DockerConfig,
+    container_id: Option<String>,
 }
 
 impl<C: Connect> DockerModule<C> {
@@ -30,8 +44,39 @@ impl<C: Connect> DockerModule<C> {
             client,
             name: ensure_not_empty!(name.to_string()),
             config,
+            container_id: None,
         })
     }
+
+    pub fn with_container_id(mut self, container_id: Option<String>) -> Self {
+        self.container_id = container_id;
+        self
+    }
+
+    pub fn depends_on(&self) -> Vec<String> {
+        self.config
+            .create_options()
+            .labels()
+            .and_then(|labels| labels.get(DEPENDS_ON_LABEL_KEY))
+            .map(|modules| {
+                modules
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|module| !module.is_empty())
+                    .map(ToString::to_string)
+                    .collect()
+            }).unwrap_or_else(Vec::new)
+    }
+
+    pub fn desired_stopped(&self) -> bool {
+        self.config
+            .create_options()
+            .labels()
+            .and_then(|labels| labels.get(DESIRED_STATUS_LABEL_KEY))
+            .map_or(false, |status| {
+                status.trim().eq_ignore_ascii_case("stopped")
+            })
+    }
 }
 
 fn status_from_exit_code(exit_code: Option<i64>) -> Option<ModuleStatus> {
@@ -44,6 +89,15 @@ fn status_from_exit_code(exit_code: Option<i64>) -> Option<ModuleStatus> {
     })
 }
 
+// Docker reports "restarting" both while a restart is in flight and while the
+// restart policy is waiting to bring a crashed container back up.
+fn restarting_status(exit_code: Option<i64>) -> ModuleStatus {
+    match exit_code {
+        Some(code) if code != 0 => ModuleStatus::Backoff,
+        _ => ModuleStatus::Restarting,
+    }
+}
+
 impl<C: Connect> Module for DockerModule<C> {
     type Config = DockerConfig;
     type Error = Error;
@@ -61,6 +115,18 @@ impl<C: Connect> Module for DockerModule<C> {
         &self.config
     }
 
+    fn spec_hash(&self) -> Option<&str> {
+        self.config
+            .create_options()
+            .labels()
+            .and_then(|labels| labels.get(SPEC_HASH_LABEL_KEY))
+            .map(String::as_str)
+    }
+
+    fn container_id(&self) -> Option<&str> {
+        self.container_id.as_ref().map(AsRef::as_ref)
+    }
+
     fn runtime_state(&self) -> Self::RuntimeStateFuture {
         Box::new(
             self.client
@@ -72,11 +138,13 @@ impl<C: Connect> Module for DockerModule<C> {
                             let status = state
                                 .status()
                                 .and_then(|status| match status.as_ref() {
-                                    "created" => Some(ModuleStatus::Stopped),
-                                    "paused" => Some(ModuleStatus::Stopped),
-                                    "restarting" => Some(ModuleStatus::Stopped),
+                                    "created" => Some(ModuleStatus::Created),
+                                    "paused" => Some(ModuleStatus::Paused),
+                                    "restarting" => {
+                                        Some(restarting_status(state.exit_code().cloned()))
+                                    }
                                     "removing" => status_from_exit_code(state.exit_code().cloned()),
-                                    "dead" => status_from_exit_code(state.exit_code().cloned()),
+                                    "dead" => Some(ModuleStatus::Dead),
                                     "exited" => status_from_exit_code(state.exit_code().cloned()),
                                     "running" => Some(ModuleStatus::Running),
                                     _ => Some(ModuleStatus::Unknown),
@@ -100,7 +168,13 @@ impl<C: Connect> Module for DockerModule<C> {
                                 ).with_image_id(resp.id().cloned())
                                 .with_pid(
                                     &state.pid().map(|val| Pid::Value(*val)).unwrap_or(Pid::None),
-                                )
+                                ).with_health(
+                                    state
+                                        .health()
+                                        .and_then(|health| health.status())
+                                        .and_then(|health| ModuleHealth::from_str(health).ok()),
+                                ).with_restart_count(resp.restart_count().cloned().unwrap_or(0))
+                                .with_oom_killed(state.oom_killed().cloned().unwrap_or(false))
                         }).unwrap_or_else(ModuleRuntimeState::default)
                 }).map_err(Error::from),
         )
@@ -120,8 +194,10 @@ mod tests {
 
     use docker::apis::client::APIClient;
     use docker::apis::configuration::Configuration;
-    use docker::models::{ContainerCreateBody, InlineResponse200, InlineResponse200State};
-    use edgelet_core::{pid::Pid, Module, ModuleStatus};
+    use docker::models::{
+        ContainerCreateBody, Health, InlineResponse200, InlineResponse200State,
+    };
+    use edgelet_core::{pid::Pid, Module, ModuleHealth, ModuleStatus};
     use edgelet_test_utils::JsonConnector;
 
     use client::DockerClient;
@@ -155,6 +231,69 @@ mod tests {
         assert_eq!("mod1", docker_module.name());
         assert_eq!("docker", docker_module.type_());
         assert_eq!("ubuntu", docker_module.config().image());
+        assert_eq!(None, docker_module.spec_hash());
+        assert!(docker_module.depends_on().is_empty());
+        assert_eq!(None, docker_module.container_id());
+    }
+
+    #[test]
+    fn container_id_from_listing() {
+        let core = Core::new().unwrap();
+        let docker_module = DockerModule::new(
+            create_api_client(&core, "boo"),
+            "mod1",
+            DockerConfig::new("ubuntu", ContainerCreateBody::new(), None).unwrap(),
+        ).unwrap()
+        .with_container_id(Some("8e3209d08ed5".to_string()));
+        assert_eq!(Some("8e3209d08ed5"), docker_module.container_id());
+    }
+
+    #[test]
+    fn spec_hash_from_label() {
+        let core = Core::new().unwrap();
+        let mut labels = ::std::collections::HashMap::new();
+        labels.insert(SPEC_HASH_LABEL_KEY.to_string(), "abc123".to_string());
+        let docker_module = DockerModule::new(
+            create_api_client(&core, "boo"),
+            "mod1",
+            DockerConfig::new("ubuntu", ContainerCreateBody::new().with_labels(labels), None)
+                .unwrap(),
+        ).unwrap();
+        assert_eq!(Some("abc123"), docker_module.spec_hash());
+    }
+
+    #[test]
+    fn depends_on_from_label() {
+        let core = Core::new().unwrap();
+        let mut labels = ::std::collections::HashMap::new();
+        labels.insert(
+            DEPENDS_ON_LABEL_KEY.to_string(),
+            "edgeHub, store,,".to_string(),
+        );
+        let docker_module = DockerModule::new(
+            create_api_client(&core, "boo"),
+            "mod1",
+            DockerConfig::new("ubuntu", ContainerCreateBody::new().with_labels(labels), None)
+                .unwrap(),
+        ).unwrap();
+        assert_eq!(
+            vec!["edgeHub".to_string(), "store".to_string()],
+            docker_module.depends_on()
+        );
+    }
+
+    #[test]
+    fn desired_stopped_from_label() {
+        let core = Core::new().unwrap();
+        let mut labels = ::std::collections::HashMap::new();
+        labels.insert(DESIRED_STATUS_LABEL_KEY.to_string(), "Stopped".to_string());
+        let docker_module = DockerModule::new(
+            create_api_client(&core, "boo"),
+            "mod1",
+            DockerConfig::new("ubuntu", ContainerCreateBody::new().with_labels(labels), None)
+                .unwrap(),
+        ).unwrap();
+        assert!(docker_module.desired_stopped());
     }
 
     #[test]
@@ -181,14 +320,15 @@ mod tests {
 
     fn get_inputs() -> Vec<(&'static str, i64, ModuleStatus)> {
         vec![
-            ("created", 0, ModuleStatus::Stopped),
-            ("paused", 0, ModuleStatus::Stopped),
-            ("restarting", 0, ModuleStatus::Stopped),
+            ("created", 0, ModuleStatus::Created),
+            ("paused", 0, ModuleStatus::Paused),
+            ("restarting", 0, ModuleStatus::Restarting),
+            ("restarting", 1, ModuleStatus::Backoff),
             ("removing", 0, ModuleStatus::Stopped),
-            ("dead", 0, ModuleStatus::Stopped),
+            ("dead", 0, ModuleStatus::Dead),
             ("exited", 0, ModuleStatus::Stopped),
             ("removing", -1, ModuleStatus::Failed),
-            ("dead", -2, ModuleStatus::Failed),
+            ("dead", -2, ModuleStatus::Dead),
             ("exited", -42, ModuleStatus::Failed),
             ("running", 0, ModuleStatus::Running),
         ]
@@ -254,7 +394,7 @@ mod tests {
     }
 
     #[test]
-    fn module_runtime_state_failed_from_dead() {
+    fn module_runtime_state_dead() {
         let started_at = Utc::now().to_rfc3339();
         let finished_at = (Utc::now() + Duration::hours(1)).to_rfc3339();
         let mut core = Core::new().unwrap();
@@ -275,7 +415,7 @@ mod tests {
         ).unwrap();
 
         let runtime_state = core.run(docker_module.runtime_state()).unwrap();
-        assert_eq!(ModuleStatus::Failed, *runtime_state.status());
+        assert_eq!(ModuleStatus::Dead, *runtime_state.status());
         assert_eq!(10, *runtime_state.exit_code().unwrap());
         assert_eq!(&"dead", &runtime_state.status_description().unwrap());
         assert_eq!(started_at, runtime_state.started_at().unwrap().to_rfc3339());
@@ -285,6 +425,54 @@ mod tests {
         );
     }
 
+    #[test]
+    fn module_runtime_state_health_and_restarts() {
+        let mut core = Core::new().unwrap();
+        let docker_module = DockerModule::new(
+            create_api_client(
+                &core,
+                InlineResponse200::new()
+                    .with_state(
+                        InlineResponse200State::new()
+                            .with_status("running".to_string())
+                            .with_oom_killed(true)
+                            .with_health(Health::new().with_status("unhealthy".to_string())),
+                    ).with_restart_count(3)
+                    .with_id("mod1".to_string()),
+            ),
+            "mod1",
+            DockerConfig::new("ubuntu", ContainerCreateBody::new(), None).unwrap(),
+        ).unwrap();
+
+        let runtime_state = core.run(docker_module.runtime_state()).unwrap();
+        assert_eq!(Some(&ModuleHealth::Unhealthy), runtime_state.health());
+        assert_eq!(3, runtime_state.restart_count());
+        assert!(runtime_state.oom_killed());
+    }
+
+    #[test]
+    fn module_runtime_state_without_health_check() {
+        let mut core = Core::new().unwrap();
+        let docker_module = DockerModule::new(
+            create_api_client(
+                &core,
+                InlineResponse200::new()
+                    .with_state(
+                        InlineResponse200State::new()
+                            .with_status("running".to_string())
+                            .with_health(Health::new().with_status("none".to_string())),
+                    ).with_id("mod1".to_string()),
+            ),
+            "mod1",
+            DockerConfig::new("ubuntu", ContainerCreateBody::new(), None).unwrap(),
+        ).unwrap();
+
+        let runtime_state = core.run(docker_module.runtime_state()).unwrap();
+        assert_eq!(None, runtime_state.health());
+        assert_eq!(0, runtime_state.restart_count());
+        assert!(!runtime_state.oom_killed());
+    }
+
     #[test]
     fn module_runtime_state_with_bad_started_at() {
         let started_at = "not really a date".to_string();
@@ -0,0 +1,173 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+use std::collections::HashMap;
+
+use docker::models::{Ipam, Network as DockerNetwork, NetworkConfig};
+use edgelet_core::network::{IpamConfig, Network};
+
+const MTU_OPTION: &str = "com.docker.network.driver.mtu";
+
+pub fn network_config(network: &Network) -> NetworkConfig {
+    let mut config = NetworkConfig::new(network.name().to_string());
+    if let Some(driver) = network.driver() {
+        config.set_driver(driver.to_string());
+    }
+    if let Some(ipv6) = network.ipv6() {
+        config.set_enable_i_pv6(ipv6);
+    }
+    if let Some(internal) = network.internal() {
+        config.set_internal(internal);
+    }
+    if let Some(mtu) = network.mtu() {
+        let mut options = HashMap::new();
+        options.insert(MTU_OPTION.to_string(), mtu.to_string());
+        config.set_options(options);
+    }
+    if !network.ipam().is_empty() {
+        let ipam = network.ipam().iter().map(ipam_config).collect();
+        config.set_IPAM(Ipam::new().with_config(ipam));
+    }
+    config
+}
+
+fn ipam_config(config: &IpamConfig) -> HashMap<String, String> {
+    let mut result = HashMap::new();
+    result.insert("Subnet".to_string(), config.subnet().to_string());
+    if let Some(gateway) = config.gateway() {
+        result.insert("Gateway".to_string(), gateway.to_string());
+    }
+    if let Some(ip_range) = config.ip_range() {
+        result.insert("IPRange".to_string(), ip_range.to_string());
+    }
+    result
+}
+
+/// The ways an existing network differs from its description. Settings the
+/// description leaves out are not compared.
+pub fn drift(network: &Network, existing: &DockerNetwork) -> Vec<String> {
+    let mut drift = Vec::new();
+
+    if let Some(driver) = network.driver() {
+        let existing_driver = existing.driver().map_or("", String::as_str);
+        if driver != existing_driver {
+            drift.push(format!(
+                "driver is {}, expected {}",
+                existing_driver, driver
+            ));
+        }
+    }
+
+    if let Some(ipv6) = network.ipv6() {
+        let existing_ipv6 = existing.enable_i_pv6().cloned().unwrap_or(false);
+        if ipv6 != existing_ipv6 {
+            drift.push(format!("ipv6 is {}, expected {}", existing_ipv6, ipv6));
+        }
+    }
+
+    if let Some(internal) = network.internal() {
+        let existing_internal = existing.internal().cloned().unwrap_or(false);
+        if internal != existing_internal {
+            drift.push(format!(
+                "internal is {}, expected {}",
+                existing_internal, internal
+            ));
+        }
+    }
+
+    if let Some(mtu) = network.mtu() {
+        let existing_mtu = existing
+            .options()
+            .and_then(|options| options.get(MTU_OPTION))
+            .map_or("default", String::as_str);
+        if mtu.to_string() != existing_mtu {
+            drift.push(format!("mtu is {}, expected {}", existing_mtu, mtu));
+        }
+    }
+
+    let existing_ipam = existing
+        .IPAM()
+        .and_then(Ipam::config)
+        .map_or(&[][..], |config| config.as_slice());
+    for config in network.ipam() {
+        let expected = ipam_config(config);
+        let found = existing_ipam.iter().any(|existing| {
+            expected
+                .iter()
+                .all(|(key, value)| existing.get(key) == Some(value))
+        });
+        if !found {
+            drift.push(format!(
+                "subnet {} is not configured as expected",
+                config.subnet()
+            ));
+        }
+    }
+
+    drift
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn existing(subnet: &str, gateway: &str) -> DockerNetwork {
+        let mut ipam = HashMap::new();
+        ipam.insert("Subnet".to_string(), subnet.to_string());
+        ipam.insert("Gateway".to_string(), gateway.to_string());
+        DockerNetwork::new()
+            .with_name("azure-iot-edge".to_string())
+            .with_driver("bridge".to_string())
+            .with_IPAM(Ipam::new().with_config(vec![ipam]))
+    }
+
+    fn fixed_subnet() -> Network {
+        Network::new("azure-iot-edge".to_string())
+            .with_ipam(vec![IpamConfig::new("172.30.0.0/16".to_string())
+                .with_gateway(Some("172.30.0.1".to_string()))])
+    }
+
+    #[test]
+    fn config_describes_network() {
+        let network = fixed_subnet()
+            .with_ipv6(Some(true))
+            .with_internal(Some(true))
+            .with_mtu(Some(1400));
+        let config = network_config(&network);
+        assert_eq!("azure-iot-edge", config.name());
+        assert_eq!(None, config.driver());
+        assert_eq!(Some(&true), config.enable_i_pv6());
+        assert_eq!(Some(&true), config.internal());
+        assert_eq!(
+            Some(&"1400".to_string()),
+            config.options().and_then(|options| options.get(MTU_OPTION))
+        );
+        let ipam = &config.IPAM().and_then(Ipam::config).unwrap()[0];
+        assert_eq!("172.30.0.0/16", ipam["Subnet"]);
+        assert_eq!("172.30.0.1", ipam["Gateway"]);
+    }
+
+    #[test]
+    fn no_drift_when_network_matches() {
+        let existing = existing("172.30.0.0/16", "172.30.0.1");
+        assert!(drift(&fixed_subnet(), &existing).is_empty());
+        assert!(drift(&Network::new("azure-iot-edge".to_string()), &existing).is_empty());
+    }
+
+    #[test]
+    fn drift_is_reported() {
+        let existing = existing("172.18.0.0/16", "172.18.0.1");
+        let network = fixed_subnet()
+            .with_driver(Some("macvlan".to_string()))
+            .with_ipv6(Some(true))
+            .with_mtu(Some(1400));
+        assert_eq!(
+            vec![
+                "driver is bridge, expected macvlan".to_string(),
+                "ipv6 is false, expected true".to_string(),
+                "mtu is default, expected 1400".to_string(),
+                "subnet 172.30.0.0/16 is not configured as expected".to_string(),
+            ],
+            drift(&network, &existing)
+        );
+    }
+}
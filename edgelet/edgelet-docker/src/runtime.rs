@@ -1,34 +1,60 @@
 // Copyright (c) Microsoft. All rights reserved.
 
-use std::collections::HashMap;
+use std::cmp;
+use std::collections::{HashMap, HashSet};
 use std::convert::From;
 use std::ops::Deref;
-use std::time::Duration;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 use base64;
+use chrono::{DateTime, Utc};
+use failure::ResultExt;
 use futures::future;
 use futures::prelude::*;
+use futures::stream;
 use hyper::{Body, Chunk as HyperChunk, Client};
 use log::Level;
 use serde_json;
 use tokio_core::reactor::Handle;
+use tokio_timer::Delay;
 use url::Url;
 
+use admission::AdmissionPolicy;
 use client::DockerClient;
-use config::DockerConfig;
+use config::{split_reference, DockerConfig};
 use docker::apis::client::APIClient;
 use docker::apis::configuration::Configuration;
-use docker::models::{ContainerCreateBody, NetworkConfig};
+use docker::models::{
+    ContainerCreateBody, ExecConfig, ExecStartConfig, Image, ImageSummary, InlineResponse200,
+    Network as DockerNetwork, VolumeConfig,
+};
+use edgelet_core::dependency;
+use edgelet_core::network::Network;
 use edgelet_core::{
-    LogOptions, Module, ModuleRegistry, ModuleRuntime, ModuleSpec, SystemInfo as CoreSystemInfo,
+    ArchiveStream, EventOptions, ExecOptions, Image as CoreImage, ImagePullPolicy, LogOptions,
+    Module, ModuleRegistry, ModuleRuntime, ModuleSpec, PruneOptions, PruneResult, StatsOptions,
+    SystemInfo as CoreSystemInfo, Volume as CoreVolume,
 };
-use edgelet_http::UrlConnector;
+use edgelet_http::{stream_body, UrlConnector};
 use edgelet_utils::log_failure;
 
-use error::{Error, Result};
-use module::{DockerModule, MODULE_TYPE as DOCKER_MODULE_TYPE};
+use error::{Error, ErrorKind, Result};
+use events::{Events, EVENT_ACTIONS};
+use image_gc;
+use module::{
+    DockerModule, IMAGE_LABEL_KEY, MODULE_TYPE as DOCKER_MODULE_TYPE, SPEC_HASH_LABEL_KEY,
+};
+use network;
+use signature::SignaturePolicy;
+use stats::Stats;
+use volume::{self, MODULE_LABEL_KEY};
 
 const WAIT_BEFORE_KILL_SECONDS: i32 = 10;
+// edgeAgent gives up on a request to the management API after 100 seconds,
+// so starting a module has to be done, dependencies included, before that.
+const DEPENDENCY_TIMEOUT_SECS: u64 = 90;
+const DEPENDENCY_POLL_INTERVAL_SECS: u64 = 1;
 
 static LABEL_KEY: &str = "net.azure-devices.edge.owner";
 static LABEL_VALUE: &str = "Microsoft.Azure.Devices.Edge.Agent";
@@ -44,7 +70,13 @@ lazy_static! {
 #[derive(Clone)]
 pub struct DockerModuleRuntime {
     client: DockerClient<UrlConnector>,
-    network_id: Option<String>,
+    network: Option<Network>,
+    additional_networks: Vec<Network>,
+    signature_policy: Option<SignaturePolicy>,
+    admission_policy: AdmissionPolicy,
+    dependency_timeout: Duration,
+    // when images were last seen in use by a module, as of the last prune
+    images_last_used: Arc<Mutex<HashMap<String, DateTime<Utc>>>>,
 }
 
 impl DockerModuleRuntime {
@@ -66,15 +98,382 @@ impl DockerModuleRuntime {
 
         Ok(DockerModuleRuntime {
             client: DockerClient::new(APIClient::new(configuration)),
-            network_id: None,
+            network: None,
+            additional_networks: Vec::new(),
+            signature_policy: None,
+            admission_policy: AdmissionPolicy::default(),
+            dependency_timeout: Duration::from_secs(DEPENDENCY_TIMEOUT_SECS),
+            images_last_used: Arc::new(Mutex::new(HashMap::new())),
         })
     }
 
     pub fn with_network_id(mut self, network_id: String) -> DockerModuleRuntime {
-        self.network_id = Some(network_id);
+        self.network = Some(Network::new(network_id));
+        self
+    }
+
+    /// The network modules are attached to, created on init with the
+    /// settings it describes.
+    pub fn with_network(mut self, network: Network) -> DockerModuleRuntime {
+        self.network = Some(network);
+        self
+    }
+
+    /// Other networks to create on init, which modules can join through
+    /// their create options.
+    pub fn with_additional_networks(mut self, networks: Vec<Network>) -> DockerModuleRuntime {
+        self.additional_networks = networks;
         self
     }
 
+    pub fn with_signature_policy(mut self, policy: SignaturePolicy) -> DockerModuleRuntime {
+        self.signature_policy = Some(policy);
+        self
+    }
+
+    pub fn with_admission_policy(mut self, policy: AdmissionPolicy) -> DockerModuleRuntime {
+        self.admission_policy = policy;
+        self
+    }
+
+    /// How long to wait, in total, for the dependencies of a module to become
+    /// ready before giving up on starting it.
+    pub fn with_dependency_timeout(mut self, timeout: Duration) -> DockerModuleRuntime {
+        self.dependency_timeout = timeout;
+        self
+    }
+
+    // Maps each module to the modules it depends on.
+    fn dependencies(&self) -> impl Future<Item = HashMap<String, Vec<String>>, Error = Error> {
+        self.list().map(|modules| {
+            modules
+                .iter()
+                .map(|module| (module.name().to_string(), module.depends_on()))
+                .collect()
+        })
+    }
+
+    // Networks that do not exist are created. A network that differs from its
+    // settings is recreated when no container, running or not, uses it,
+    // otherwise the drift is only reported, since removing the network would
+    // break the modules on it.
+    fn ensure_network(&self, network: Network) -> Box<Future<Item = (), Error = Error>> {
+        let filter = format!(r#"{{"name":{{"{}":true}}}}"#, network.name());
+        let runtime = self.clone();
+        let result = self
+            .client
+            .network_api()
+            .network_list(&filter)
+            .map_err(Error::from)
+            .and_then(move |existing_networks| {
+                // the name filter also matches networks that contain the name
+                let existing = existing_networks
+                    .into_iter()
+                    .find(|existing| existing.name().map(String::as_str) == Some(network.name()));
+                match existing {
+                    Some(existing) => runtime.reconcile_network(network, &existing),
+                    None => {
+                        info!("Creating network {}", network.name());
+                        runtime.create_network(&network)
+                    }
+                }
+            }).map_err(|e| {
+                warn!("Module runtime init failed.");
+                log_failure(Level::Warn, &e);
+                e
+            });
+        Box::new(result)
+    }
+
+    fn create_network(&self, network: &Network) -> Box<Future<Item = (), Error = Error>> {
+        let result = self
+            .client
+            .network_api()
+            .network_create(network::network_config(network))
+            .map(|_| ())
+            .map_err(Error::from);
+        Box::new(result)
+    }
+
+    fn reconcile_network(
+        &self,
+        network: Network,
+        existing: &DockerNetwork,
+    ) -> Box<Future<Item = (), Error = Error>> {
+        let drift = network::drift(&network, existing);
+        if drift.is_empty() {
+            return Box::new(future::ok(()));
+        }
+        warn!(
+            "Network {} differs from its settings: {}",
+            network.name(),
+            drift.join("; ")
+        );
+
+        let id = existing
+            .id()
+            .cloned()
+            .unwrap_or_else(|| network.name().to_string());
+        // Stopped containers are not attached to the network, but still
+        // reference it and could not be started once it is removed.
+        let filter = format!(r#"{{"network":{{"{}":true}}}}"#, id);
+        let runtime = self.clone();
+        let result = self
+            .client
+            .container_api()
+            .container_list(true, 0, false, &filter)
+            .map_err(Error::from)
+            .and_then(move |containers| {
+                if containers.is_empty() {
+                    info!("Recreating network {}", network.name());
+                    let deleted = runtime.client.network_api().network_delete(&id);
+                    let recreated = deleted
+                        .map_err(Error::from)
+                        .and_then(move |_| runtime.create_network(&network));
+                    future::Either::A(recreated)
+                } else {
+                    warn!(
+                        "Keeping network {} since {} containers use it, remove them to apply its settings",
+                        network.name(),
+                        containers.len()
+                    );
+                    future::Either::B(future::ok(()))
+                }
+            });
+        Box::new(result)
+    }
+
+    fn start_container(&self, id: &str) -> impl Future<Item = (), Error = Error> {
+        debug!("Starting container {}", id);
+        self.client
+            .container_api()
+            .container_start(id, "")
+            .map_err(|err| {
+                let e = Error::from(err);
+                warn!("Attempt to start a container failed.");
+                log_failure(Level::Warn, &e);
+                e
+            }).map(|_| ())
+    }
+
+    // Starts the dependencies of a module a stage at a time, waiting for each
+    // stage to be ready before starting the next one. The wait for all of
+    // them is bounded by the dependency timeout. Dependencies that are meant
+    // to be stopped are not started, so they have to be ready already.
+    fn start_dependencies(&self, name: String) -> impl Future<Item = (), Error = Error> {
+        let runtime = self.clone();
+        let deadline = Instant::now() + self.dependency_timeout;
+        self.list()
+            .and_then(move |modules| {
+                let dependencies: HashMap<String, Vec<String>> = modules
+                    .iter()
+                    .map(|module| (module.name().to_string(), module.depends_on()))
+                    .collect();
+                let stopped: HashSet<String> = modules
+                    .iter()
+                    .filter(|module| module.desired_stopped())
+                    .map(|module| module.name().to_string())
+                    .collect();
+                let stages = dependency::dependency_stages(&dependencies, &name)?;
+                Ok((name, stages, stopped))
+            }).and_then(move |(name, stages, stopped)| {
+                if !stages.is_empty() {
+                    info!("Starting the dependencies of module {}", name);
+                }
+                stream::iter_ok(stages).for_each(move |stage| {
+                    let started = stage.into_iter().map(|dependency| {
+                        let runtime_copy = runtime.clone();
+                        let name = name.clone();
+                        if stopped.contains(&dependency) {
+                            info!(
+                                "Not starting {}, a dependency of module {}, since it is meant to be stopped",
+                                dependency, name
+                            );
+                            return future::Either::A(runtime.wait_until_ready(
+                                name,
+                                dependency,
+                                Instant::now(),
+                            ));
+                        }
+                        let started = runtime
+                            .start_container(&dependency)
+                            .or_else(|err| {
+                                // the dependency is already running
+                                if let ErrorKind::NotModified = *err.kind() {
+                                    return Ok(());
+                                }
+                                Err(err)
+                            }).and_then(move |_| {
+                                runtime_copy.wait_until_ready(name, dependency, deadline)
+                            });
+                        future::Either::B(started)
+                    });
+                    future::join_all(started).map(|_| ())
+                })
+            })
+    }
+
+    fn stop_container(
+        &self,
+        id: &str,
+        wait_before_kill: Option<Duration>,
+    ) -> impl Future<Item = (), Error = Error> {
+        debug!("Stopping container {}", id);
+        self.client
+            .container_api()
+            .container_stop(
+                id,
+                wait_before_kill
+                    .map(|s| s.as_secs() as i32)
+                    .unwrap_or(WAIT_BEFORE_KILL_SECONDS),
+            ).map_err(|err| {
+                let e = Error::from(err);
+                warn!("Attempt to stop a container failed.");
+                log_failure(Level::Warn, &e);
+                e
+            }).map(|_| ())
+    }
+
+    // Stops the dependents of a module a stage at a time, dependents of
+    // dependents first.
+    fn stop_dependents(
+        &self,
+        stages: Vec<Vec<String>>,
+        wait_before_kill: Option<Duration>,
+    ) -> impl Future<Item = (), Error = Error> {
+        let runtime = self.clone();
+        stream::iter_ok(stages).for_each(move |stage| {
+            let stopped = stage.into_iter().map(|dependent| {
+                runtime
+                    .stop_container(&dependent, wait_before_kill)
+                    .or_else(|err| {
+                        // the dependent is not running
+                        if let ErrorKind::NotModified = *err.kind() {
+                            return Ok(());
+                        }
+                        Err(err)
+                    })
+            });
+            future::join_all(stopped).map(|_| ())
+        })
+    }
+
+    // A dependency is ready once it is running and, if it has a health check,
+    // healthy. It is checked at least once, even if the deadline has passed.
+    fn wait_until_ready(
+        &self,
+        name: String,
+        dependency: String,
+        deadline: Instant,
+    ) -> impl Future<Item = (), Error = Error> {
+        let client = self.client.clone();
+        let interval = Duration::from_secs(DEPENDENCY_POLL_INTERVAL_SECS);
+        let timeout = self.dependency_timeout.as_secs();
+        future::loop_fn((), move |_| {
+            let name = name.clone();
+            let dependency_copy = dependency.clone();
+            client
+                .container_api()
+                .container_inspect(&dependency, false)
+                .map_err(Error::from)
+                .and_then(move |container| {
+                    if is_ready(&container) {
+                        debug!("Module {} is ready", dependency_copy);
+                        future::Either::A(future::ok(future::Loop::Break(())))
+                    } else if Instant::now() >= deadline {
+                        future::Either::A(future::err(Error::from(ErrorKind::DependencyNotReady(
+                            name,
+                            dependency_copy,
+                            timeout,
+                        ))))
+                    } else {
+                        future::Either::B(
+                            Delay::new(Instant::now() + interval)
+                                .then(|_| Ok(future::Loop::Continue(()))),
+                        )
+                    }
+                })
+        })
+    }
+
+    fn remove_container(
+        &self,
+        id: &str,
+        remove_volumes: bool,
+    ) -> impl Future<Item = (), Error = Error> {
+        self.client
+            .container_api()
+            .container_delete(
+                id,
+                remove_volumes,
+                /* force */ true,
+                /* remove link */ false,
+            ).map_err(|err| {
+                let e = Error::from(err);
+                warn!("Attempt to remove a container failed.");
+                log_failure(Level::Warn, &e);
+                e
+            }).map(|_| ())
+    }
+
+    // Lists the volumes with all of the labels.
+    fn volumes(&self, labels: Vec<String>) -> impl Future<Item = Vec<CoreVolume>, Error = Error> {
+        let mut filters = HashMap::new();
+        filters.insert("label", labels);
+
+        let client = self.client.clone();
+        future::result(serde_json::to_string(&filters))
+            .map_err(Error::from)
+            .and_then(move |filters| {
+                client
+                    .volume_api()
+                    .volume_list(&filters)
+                    .map_err(Error::from)
+            }).map(|response| {
+                response
+                    .volumes()
+                    .iter()
+                    .map(volume::to_core_volume)
+                    .collect()
+            })
+    }
+
+    // A module pinned to a digest is only created from that image, and with
+    // signature verification on only from an image signed by a trusted key.
+    // The id of the image that was checked is returned, so that the container
+    // is created from it even if the tag is moved to another image meanwhile.
+    fn check_image(
+        &self,
+        config: &DockerConfig,
+    ) -> impl Future<Item = Option<String>, Error = Error> {
+        let policy = match self.signature_policy {
+            Some(ref policy) if policy.enabled() => Some(policy.clone()),
+            _ => None,
+        };
+        if config.image_id().is_none() && policy.is_none() {
+            return future::Either::B(future::ok(None));
+        }
+
+        let image = config.image().to_string();
+        let digest = config.image_id().cloned();
+        let signature = config.signature().map(ToString::to_string);
+        future::Either::A(
+            self.client
+                .image_api()
+                .image_inspect(&image)
+                .map_err(Error::from)
+                .and_then(move |inspected| {
+                    if let Some(digest) = digest {
+                        verify_image(&inspected, &image, &digest)?;
+                    }
+                    if let Some(policy) = policy {
+                        policy.check(&image, &inspected, signature.as_ref().map(AsRef::as_ref))?;
+                    }
+                    Ok(Some(inspected.id().to_string()))
+                }),
+        )
+    }
+
     fn merge_env(cur_env: Option<&Vec<String>>, new_env: &HashMap<String, String>) -> Vec<String> {
         // build a new merged hashmap containing string slices for keys and values
         // pointing into String instances in new_env
@@ -109,21 +508,73 @@ impl ModuleRegistry for DockerModuleRuntime {
     type Error = Error;
     type PullFuture = Box<Future<Item = (), Error = Self::Error>>;
     type RemoveFuture = Box<Future<Item = (), Error = Self::Error>>;
+    type ListImagesFuture = Box<Future<Item = Vec<CoreImage>, Error = Self::Error>>;
+    type PruneImagesFuture = Box<Future<Item = PruneResult, Error = Self::Error>>;
+    type LoadImagesFuture = Box<Future<Item = (), Error = Self::Error>>;
+    type SaveImageFuture = Box<Future<Item = Self::ImageArchive, Error = Self::Error>>;
+    type ImageChunk = Chunk;
+    type ImageArchive = Logs;
     type Config = DockerConfig;
 
+    // An image with an image hash is pulled by digest when the hash is a
+    // manifest digest, or else by tag, and checked against the hash. An
+    // image pulled by digest is then tagged, so that containers created from
+    // the configured image name use the pinned image. With the never pull policy the image
+    // has to be on the device already, e.g. loaded with `load_images`.
     fn pull(&self, config: &Self::Config) -> Self::PullFuture {
+        if config.pull_policy() == ImagePullPolicy::Never {
+            let image = config.image().to_string();
+            let digest = config.image_id().cloned();
+            debug!("Using local image {}", image);
+            let local = self
+                .client
+                .image_api()
+                .image_inspect(&image)
+                .map_err(Error::from)
+                .then(move |result| match result {
+                    Ok(inspected) => match digest {
+                        Some(digest) => verify_image(&inspected, &image, &digest),
+                        None => Ok(()),
+                    },
+                    Err(err) => {
+                        if let ErrorKind::NotFound = *err.kind() {
+                            return Err(Error::from(ErrorKind::LocalImageNotFound(image)));
+                        }
+                        Err(err)
+                    }
+                }).map_err(|e| {
+                    warn!("Attempt to pull image failed.");
+                    log_failure(Level::Warn, &e);
+                    e
+                });
+            return Box::new(local);
+        }
+
+        let reference = config.pull_reference();
+        let pinned = config.image_id().map(|digest| {
+            (
+                self.client.clone(),
+                config.image().to_string(),
+                digest.clone(),
+            )
+        });
         let response = config
             .auth()
             .map(|a| serde_json::to_string(a).map(|json| base64::encode(&json)))
             .unwrap_or_else(|| Ok("".to_string()))
             .map(|creds: String| {
-                debug!("Pulling {}", config.image());
+                debug!("Pulling {}", reference);
                 let ok = self
                     .client
                     .image_api()
-                    .image_create(config.image(), "", "", "", "", &creds, "")
-                    .map_err(|err| {
-                        let e = Error::from(err);
+                    .image_create(&reference, "", "", "", "", &creds, "")
+                    .map_err(Error::from)
+                    .and_then(move |_| match pinned {
+                        Some((client, image, digest)) => {
+                            future::Either::A(pin_image(&client, reference, image, digest))
+                        }
+                        None => future::Either::B(future::ok(())),
+                    }).map_err(|e| {
                         warn!("Attempt to pull image failed.");
                         log_failure(Level::Warn, &e);
                         e
@@ -148,6 +599,300 @@ impl ModuleRegistry for DockerModuleRuntime {
                 }),
         )
     }
+
+    fn list_images(&self) -> Self::ListImagesFuture {
+        Box::new(
+            self.client
+                .image_api()
+                .image_list(false, "", false)
+                .map(|images| {
+                    images
+                        .iter()
+                        .map(|image| {
+                            CoreImage::new(image.id().to_string())
+                                .with_names(image_gc::references(image))
+                                .with_created(Some(image_gc::created(image)))
+                                .with_size(image_gc::size(image))
+                        }).collect()
+                }).map_err(|err| {
+                    let e = Error::from(err);
+                    warn!("Attempt to list images failed.");
+                    log_failure(Level::Warn, &e);
+                    e
+                }),
+        )
+    }
+
+    // Images are compared with the images of the module containers, running
+    // or not. An image used by a container the daemon did not create cannot
+    // be removed without force, it is skipped. An image counts as used when
+    // it was last pulled, or when a prune last found a module using it.
+    fn prune_images(&self, options: &PruneOptions) -> Self::PruneImagesFuture {
+        let mut filters = HashMap::new();
+        filters.insert("label", LABELS.deref());
+        let filters = match serde_json::to_string(&filters) {
+            Ok(filters) => filters,
+            Err(err) => return Box::new(future::err(Error::from(err))),
+        };
+
+        let usage = if options.water_marks().is_some() {
+            future::Either::A(
+                self.client
+                    .system_api()
+                    .system_data_usage()
+                    .map(|usage| {
+                        usage
+                            .layers_size()
+                            .map_or(0, |size| cmp::max(*size, 0) as u64)
+                    })
+                    .map_err(Error::from),
+            )
+        } else {
+            future::Either::B(future::ok(0))
+        };
+
+        let client = self.client.clone();
+        let inspect_client = self.client.clone();
+        let images_last_used = self.images_last_used.clone();
+        let removed_images = self.images_last_used.clone();
+        let options = options.clone();
+        let min_age = options.min_age();
+        let result = self
+            .client
+            .image_api()
+            .image_list(false, "", false)
+            .map_err(Error::from)
+            .join3(
+                self.client
+                    .container_api()
+                    .container_list(true, 0, false, &filters)
+                    .map_err(Error::from),
+                usage,
+            ).and_then(move |(images, containers, usage)| {
+                let in_use: HashSet<String> = containers
+                    .iter()
+                    .map(|container| container.image_id().to_string())
+                    .collect();
+                let now = Utc::now();
+                let recorded = images_last_used
+                    .lock()
+                    .map(|mut last_used| {
+                        for id in &in_use {
+                            last_used.insert(id.clone(), now);
+                        }
+                        last_used.clone()
+                    }).unwrap_or_default();
+                last_used_times(&inspect_client, &images, &in_use, recorded, min_age)
+                    .map(move |last_used| (images, in_use, last_used, usage, now))
+            }).and_then(move |(images, in_use, last_used, usage, now)| {
+                let selected: Vec<(String, Vec<String>, u64)> =
+                    image_gc::select_images(&images, &in_use, &last_used, usage, &options, now)
+                        .into_iter()
+                        .map(|image| {
+                            (
+                                image.id().to_string(),
+                                image_gc::references(image),
+                                image_gc::size(image),
+                            )
+                        }).collect();
+                debug!("Pruning {} unused images", selected.len());
+
+                // one at a time, so a failure only skips that image
+                stream::iter_ok(selected)
+                    .and_then(move |(id, references, size)| {
+                        let removed_images = removed_images.clone();
+                        remove_image(&client, &id, references).then(move |result| match result {
+                            Ok(()) => {
+                                if let Ok(mut last_used) = removed_images.lock() {
+                                    last_used.remove(&id);
+                                }
+                                Ok(Some((id, size)))
+                            }
+                            Err(e) => {
+                                info!("Skipping image {} that could not be removed.", id);
+                                log_failure(Level::Info, &e);
+                                Ok(None)
+                            }
+                        })
+                    }).fold(PruneResult::default(), |result, removed| {
+                        let result = match removed {
+                            Some((id, size)) => {
+                                let mut deleted = result.images_deleted().to_vec();
+                                deleted.push(id);
+                                PruneResult::new(deleted, result.space_reclaimed() + size)
+                            }
+                            None => result,
+                        };
+                        Ok(result) as Result<PruneResult>
+                    })
+            }).map_err(|err| {
+                warn!("Attempt to prune images failed.");
+                log_failure(Level::Warn, &err);
+                err
+            });
+        Box::new(result)
+    }
+
+    // The archive is sent to docker as it is read.
+    fn load_images(&self, archive: ArchiveStream) -> Self::LoadImagesFuture {
+        debug!("Loading images");
+        let (body, feed) = stream_body(archive);
+        Box::new(
+            self.client
+                .image_api()
+                .image_load(body, /* quiet */ true)
+                .map_err(|err| {
+                    let e = Error::from(err);
+                    warn!("Attempt to load images failed.");
+                    log_failure(Level::Warn, &e);
+                    e
+                }).join(feed.then(|_| Ok(())))
+                .map(|_| ()),
+        )
+    }
+
+    fn save_image(&self, name: &str) -> Self::SaveImageFuture {
+        debug!("Saving image {}", name);
+        Box::new(
+            self.client
+                .image_api()
+                .image_get(fensure_not_empty!(name))
+                .map(Logs)
+                .map_err(|err| {
+                    let e = Error::from(err);
+                    warn!("Attempt to save image failed.");
+                    log_failure(Level::Warn, &e);
+                    e
+                }),
+        )
+    }
+}
+
+// The times unused images were last pulled, or last seen in use. Images
+// are only inspected when they could be too young to remove, and an image
+// that cannot be inspected goes by the recorded time or its creation.
+fn last_used_times(
+    client: &DockerClient<UrlConnector>,
+    images: &[ImageSummary],
+    in_use: &HashSet<String>,
+    recorded: HashMap<String, DateTime<Utc>>,
+    min_age: Duration,
+) -> impl Future<Item = HashMap<String, DateTime<Utc>>, Error = Error> {
+    if min_age == Duration::from_secs(0) {
+        return future::Either::A(future::ok(recorded));
+    }
+
+    let inspects = images
+        .iter()
+        .filter(|image| !in_use.contains(image.id()))
+        .map(|image| {
+            let id = image.id().to_string();
+            client
+                .image_api()
+                .image_inspect(&id)
+                .then(move |result| -> Result<_> {
+                    let tagged = result.ok().and_then(|image| image_gc::last_tagged(&image));
+                    Ok((id, tagged))
+                })
+        }).collect::<Vec<_>>();
+    let times = future::join_all(inspects).map(move |tagged| {
+        let mut times = recorded;
+        for (id, tagged) in tagged {
+            if let Some(tagged) = tagged {
+                let time = times.entry(id).or_insert(tagged);
+                *time = cmp::max(*time, tagged);
+            }
+        }
+        times
+    });
+    future::Either::B(times)
+}
+
+// Verifies the pulled image and, when it was pulled by digest, tags it with
+// the configured image name. An image configured by digest only has no tag
+// to apply.
+fn pin_image(
+    client: &DockerClient<UrlConnector>,
+    reference: String,
+    image: String,
+    digest: String,
+) -> impl Future<Item = (), Error = Error> {
+    let client = client.clone();
+    let inspect = client.image_api().image_inspect(&reference);
+    inspect
+        .map_err(Error::from)
+        .and_then(move |inspected| {
+            verify_image(&inspected, &image, &digest)?;
+            Ok((inspected.id().to_string(), image))
+        }).and_then(move |(id, image)| match split_reference(&image) {
+            (repository, Some(tag)) if reference != image && !image.contains('@') => {
+                debug!("Tagging {} as {}", id, image);
+                future::Either::A(
+                    client
+                        .image_api()
+                        .image_tag(&id, repository, tag)
+                        .map_err(Error::from),
+                )
+            }
+            _ => future::Either::B(future::ok(())),
+        })
+}
+
+// The digest is either the ID of the image, with or without the "sha256:"
+// prefix, or the digest of its manifest in the registry.
+fn verify_image(image: &Image, name: &str, digest: &str) -> Result<()> {
+    let suffix = format!("@{}", digest);
+    let matches = image.id() == digest
+        || image.id().splitn(2, ':').nth(1) == Some(digest)
+        || image.repo_digests().map_or(false, |digests| {
+            digests.iter().any(|d| d.ends_with(&suffix))
+        });
+    if matches {
+        Ok(())
+    } else {
+        Err(Error::from(ErrorKind::ImageDigestMismatch(
+            name.to_string(),
+            digest.to_string(),
+            image.id().to_string(),
+        )))
+    }
+}
+
+// Creates the container from the id of the image that was checked. The image
+// it was configured with is kept in a label, which is what the module reports.
+fn pin_image(
+    create_options: ContainerCreateBody,
+    image: String,
+    image_id: String,
+) -> ContainerCreateBody {
+    let mut labels = create_options
+        .labels()
+        .cloned()
+        .unwrap_or_else(HashMap::new);
+    labels.insert(IMAGE_LABEL_KEY.to_string(), image);
+    create_options.with_image(image_id).with_labels(labels)
+}
+
+// Removes every tag of an image, which removes the image with the last one.
+// Untagged images are removed by id.
+fn remove_image(
+    client: &DockerClient<UrlConnector>,
+    id: &str,
+    references: Vec<String>,
+) -> impl Future<Item = (), Error = Error> {
+    let references = if references.is_empty() {
+        vec![id.to_string()]
+    } else {
+        references
+    };
+    let client = client.clone();
+    stream::iter_ok(references).for_each(move |reference| {
+        client
+            .image_api()
+            .image_delete(&reference, false, false)
+            .map(|_| ())
+            .map_err(Error::from)
+    })
 }
 
 impl ModuleRuntime for DockerModuleRuntime {
@@ -157,50 +902,40 @@ impl ModuleRuntime for DockerModuleRuntime {
     type ModuleRegistry = Self;
     type Chunk = Chunk;
     type Logs = Logs;
+    type Stats = Stats;
+    type Events = Events;
 
     type CreateFuture = Box<Future<Item = (), Error = Self::Error>>;
+    type EventsFuture = Box<Future<Item = Self::Events, Error = Self::Error>>;
+    type ExecFuture = Box<Future<Item = Self::Logs, Error = Self::Error>>;
+    type GetArchiveFuture = Box<Future<Item = Self::Logs, Error = Self::Error>>;
     type InitFuture = Box<Future<Item = (), Error = Self::Error>>;
     type ListFuture = Box<Future<Item = Vec<Self::Module>, Error = Self::Error>>;
     type LogsFuture = Box<Future<Item = Self::Logs, Error = Self::Error>>;
     type RemoveFuture = Box<Future<Item = (), Error = Self::Error>>;
+    type PauseFuture = Box<Future<Item = (), Error = Self::Error>>;
+    type PutArchiveFuture = Box<Future<Item = (), Error = Self::Error>>;
     type RestartFuture = Box<Future<Item = (), Error = Self::Error>>;
+    type StatsFuture = Box<Future<Item = Self::Stats, Error = Self::Error>>;
     type StartFuture = Box<Future<Item = (), Error = Self::Error>>;
     type StopFuture = Box<Future<Item = (), Error = Self::Error>>;
+    type UnpauseFuture = Box<Future<Item = (), Error = Self::Error>>;
     type SystemInfoFuture = Box<Future<Item = CoreSystemInfo, Error = Self::Error>>;
     type RemoveAllFuture = Box<Future<Item = (), Error = Self::Error>>;
+    type ListVolumesFuture = Box<Future<Item = Vec<CoreVolume>, Error = Self::Error>>;
+    type InspectVolumeFuture = Box<Future<Item = CoreVolume, Error = Self::Error>>;
+    type CreateVolumeFuture = Box<Future<Item = CoreVolume, Error = Self::Error>>;
+    type RemoveVolumeFuture = Box<Future<Item = (), Error = Self::Error>>;
+    type BackupVolumeFuture = Box<Future<Item = Self::Logs, Error = Self::Error>>;
 
     fn init(&self) -> Self::InitFuture {
-        let created = self
-            .network_id
-            .as_ref()
-            .map(|id| {
-                let id = id.clone();
-                let filter = format!(r#"{{"name":{{"{}":true}}}}"#, id);
-                let client_copy = self.client.clone();
-                let fut = self
-                    .client
-                    .network_api()
-                    .network_list(&filter)
-                    .and_then(move |existing_networks| {
-                        if existing_networks.is_empty() {
-                            let fut = client_copy
-                                .network_api()
-                                .network_create(NetworkConfig::new(id))
-                                .map(|_| ());
-                            future::Either::A(fut)
-                        } else {
-                            future::Either::B(future::ok(()))
-                        }
-                    }).map_err(|err| {
-                        let e = Error::from(err);
-                        warn!("Module runtime init failed.");
-                        log_failure(Level::Warn, &e);
-                        e
-                    });
-                future::Either::A(fut)
-            }).unwrap_or_else(|| future::Either::B(future::ok(())));
-
-        Box::new(created)
+        let networks: Vec<_> = self
+            .network
+            .iter()
+            .chain(&self.additional_networks)
+            .map(|network| self.ensure_network(network.clone()))
+            .collect();
+        Box::new(future::join_all(networks).map(|_| ()))
     }
 
     fn create(&self, module: ModuleSpec<Self::Config>) -> Self::CreateFuture {
@@ -219,6 +954,11 @@ impl ModuleRuntime for DockerModuleRuntime {
                     .cloned()
                     .unwrap_or_else(HashMap::new);
                 labels.insert(LABEL_KEY.to_string(), LABEL_VALUE.to_string());
+                match module.recorded_spec_hash().context(ErrorKind::Serde)? {
+                    Some(hash) => labels.insert(SPEC_HASH_LABEL_KEY.to_string(), hash),
+                    None => labels.remove(SPEC_HASH_LABEL_KEY),
+                };
+                labels.remove(IMAGE_LABEL_KEY);
 
                 debug!(
                     "Creating container {} with image {}",
@@ -230,20 +970,42 @@ impl ModuleRuntime for DockerModuleRuntime {
                     .with_image(module.config().image().to_string())
                     .with_env(merged_env)
                     .with_labels(labels);
+                self.admission_policy
+                    .check(module.name(), &create_options)?;
+
+                let volumes = volume::named_volumes(&create_options)
+                    .iter()
+                    .map(|volume| self.create_volume(volume, Some(module.name())))
+                    .collect::<Vec<_>>();
 
                 // Here we don't add the container to the iot edge docker network as the edge-agent is expected to do that.
                 // It contains the logic to add a container to the iot edge network only if a network is not already specified.
 
-                Ok(self
-                    .client
-                    .container_api()
-                    .container_create(create_options, module.name())
-                    .map_err(Error::from)
-                    .map(|_| ()))
+                let client = self.client.clone();
+                let name = module.name().to_string();
+                let image = module.config().image().to_string();
+                let verify = self.check_image(module.config());
+                Ok(verify
+                    .and_then(|image_id| future::join_all(volumes).map(|_| image_id))
+                    .and_then(move |image_id| {
+                        let create_options = match image_id {
+                            Some(image_id) => pin_image(create_options, image, image_id),
+                            None => create_options,
+                        };
+                        client
+                            .container_api()
+                            .container_create(create_options, &name)
+                            .map_err(Error::from)
+                            .map(|_| ())
+                    }))
             });
 
         match result {
-            Ok(f) => Box::new(f),
+            Ok(f) => Box::new(f.map_err(|err| {
+                warn!("Attempt to create a container failed.");
+                log_failure(Level::Warn, &err);
+                err
+            })),
             Err(err) => {
                 warn!("Attempt to create a container failed.");
                 log_failure(Level::Warn, &err);
@@ -253,37 +1015,41 @@ impl ModuleRuntime for DockerModuleRuntime {
     }
 
     fn start(&self, id: &str) -> Self::StartFuture {
-        debug!("Starting container {}", id);
-        Box::new(
-            self.client
-                .container_api()
-                .container_start(fensure_not_empty!(id), "")
-                .map_err(|err| {
-                    let e = Error::from(err);
-                    warn!("Attempt to start a container failed.");
-                    log_failure(Level::Warn, &e);
-                    e
-                }).map(|_| ()),
-        )
+        let name = fensure_not_empty!(id).to_string();
+        let runtime = self.clone();
+        let started = self
+            .start_dependencies(name.clone())
+            .and_then(move |_| runtime.start_container(&name));
+        Box::new(started)
     }
 
+    // The modules that depend on the module are stopped first, so that no
+    // module is left running without its dependencies.
     fn stop(&self, id: &str, wait_before_kill: Option<Duration>) -> Self::StopFuture {
-        debug!("Stopping container {}", id);
-        Box::new(
-            self.client
-                .container_api()
-                .container_stop(
-                    fensure_not_empty!(id),
-                    wait_before_kill
-                        .map(|s| s.as_secs() as i32)
-                        .unwrap_or(WAIT_BEFORE_KILL_SECONDS),
-                ).map_err(|err| {
-                    let e = Error::from(err);
-                    warn!("Attempt to stop a container failed.");
-                    log_failure(Level::Warn, &e);
-                    e
-                }).map(|_| ()),
-        )
+        let name = fensure_not_empty!(id).to_string();
+        let runtime = self.clone();
+        let stopped = self
+            .dependencies()
+            .map(move |dependencies| {
+                let stages = dependency::dependent_stages(&dependencies, &name);
+                match stages {
+                    Ok(stages) => (name, stages),
+                    Err(err) => {
+                        let e = Error::from(err);
+                        warn!("Stopping module {} before its dependents.", name);
+                        log_failure(Level::Warn, &e);
+                        (name, Vec::new())
+                    }
+                }
+            }).and_then(move |(name, stages)| {
+                if !stages.is_empty() {
+                    info!("Stopping the dependents of module {}", name);
+                }
+                runtime
+                    .stop_dependents(stages, wait_before_kill)
+                    .and_then(move |_| runtime.stop_container(&name, wait_before_kill))
+            });
+        Box::new(stopped)
     }
 
     fn system_info(&self) -> Self::SystemInfoFuture {
@@ -311,40 +1077,84 @@ impl ModuleRuntime for DockerModuleRuntime {
         )
     }
 
+    // Like start, the dependencies of the module are started first. Its
+    // dependents are left running, as they would be if it crashed.
     fn restart(&self, id: &str) -> Self::RestartFuture {
-        debug!("Restarting container {}", id);
+        let name = fensure_not_empty!(id).to_string();
+        let client = self.client.clone();
+        let restarted = self.start_dependencies(name.clone()).and_then(move |_| {
+            debug!("Restarting container {}", name);
+            client
+                .container_api()
+                .container_restart(&name, WAIT_BEFORE_KILL_SECONDS)
+                .map_err(|err| {
+                    let e = Error::from(err);
+                    warn!("Attempt to restart a container failed.");
+                    log_failure(Level::Warn, &e);
+                    e
+                }).map(|_| ())
+        });
+        Box::new(restarted)
+    }
+
+    fn pause(&self, id: &str) -> Self::PauseFuture {
+        debug!("Pausing container {}", id);
         Box::new(
             self.client
                 .container_api()
-                .container_restart(fensure_not_empty!(id), WAIT_BEFORE_KILL_SECONDS)
+                .container_pause(fensure_not_empty!(id))
                 .map_err(|err| {
                     let e = Error::from(err);
-                    warn!("Attempt to restart a container failed.");
+                    warn!("Attempt to pause a container failed.");
                     log_failure(Level::Warn, &e);
                     e
-                }).map(|_| ()),
+                }),
         )
     }
 
-    fn remove(&self, id: &str) -> Self::RemoveFuture {
-        debug!("Removing container {}", id);
+    fn unpause(&self, id: &str) -> Self::UnpauseFuture {
+        debug!("Unpausing container {}", id);
         Box::new(
             self.client
                 .container_api()
-                .container_delete(
-                    fensure_not_empty!(id),
-                    /* remove volumes */ false,
-                    /* force */ true,
-                    /* remove link */ false,
-                ).map_err(|err| {
+                .container_unpause(fensure_not_empty!(id))
+                .map_err(|err| {
                     let e = Error::from(err);
-                    warn!("Attempt to remove a container failed.");
+                    warn!("Attempt to unpause a container failed.");
                     log_failure(Level::Warn, &e);
                     e
-                }).map(|_| ()),
+                }),
         )
     }
 
+    fn remove(&self, id: &str) -> Self::RemoveFuture {
+        debug!("Removing container {}", id);
+        Box::new(self.remove_container(fensure_not_empty!(id), false))
+    }
+
+    // Docker only removes the anonymous volumes of a container, the named
+    // volumes of the module are found by their labels.
+    fn remove_with_volumes(&self, id: &str) -> Self::RemoveFuture {
+        debug!("Removing container {} and its volumes", id);
+        let name = fensure_not_empty!(id).to_string();
+        let runtime = self.clone();
+        let removed = self.remove_container(&name, true).and_then(move |_| {
+            let labels = vec![
+                format!("{}={}", LABEL_KEY, LABEL_VALUE),
+                format!("{}={}", MODULE_LABEL_KEY, name),
+            ];
+            let volumes = runtime.volumes(labels);
+            volumes.and_then(move |volumes| {
+                let removals = volumes
+                    .iter()
+                    .map(|volume| runtime.remove_volume(volume.name()))
+                    .collect::<Vec<_>>();
+                future::join_all(removals).map(|_| ())
+            })
+        });
+        Box::new(removed)
+    }
+
     fn list(&self) -> Self::ListFuture {
         let mut filters = HashMap::new();
         filters.insert("label", LABELS.deref());
@@ -361,8 +1171,14 @@ impl ModuleRuntime for DockerModuleRuntime {
                         containers
                             .iter()
                             .flat_map(|container| {
+                                // containers created from a checked image
+                                // have its id as their image
+                                let image = container
+                                    .labels()
+                                    .get(IMAGE_LABEL_KEY)
+                                    .unwrap_or_else(|| container.image());
                                 DockerConfig::new(
-                                    container.image(),
+                                    image,
                                     ContainerCreateBody::new()
                                         .with_labels(container.labels().clone()),
                                     None,
@@ -382,7 +1198,7 @@ impl ModuleRuntime for DockerModuleRuntime {
                                         .map(|s| &s[1..])
                                         .unwrap_or("Unknown"),
                                     config,
-                                )
+                                ).map(|module| module.with_container_id(container.id().cloned()))
                             }).collect()
                     }).map_err(Error::from))
             }).map_err(Error::from);
@@ -402,7 +1218,16 @@ impl ModuleRuntime for DockerModuleRuntime {
         let result = self
             .client
             .container_api()
-            .container_logs(id, options.follow(), true, true, 0, false, tail)
+            .container_logs(
+                id,
+                options.follow(),
+                true,
+                true,
+                options.since(),
+                options.until(),
+                options.timestamps(),
+                tail,
+            )
             .map(Logs)
             .map_err(|err| {
                 let e = Error::from(err);
@@ -413,19 +1238,282 @@ impl ModuleRuntime for DockerModuleRuntime {
         Box::new(result)
     }
 
+    fn stats(&self, id: &str, options: &StatsOptions) -> Self::StatsFuture {
+        debug!("Getting stats for container {}", id);
+        let result = self
+            .client
+            .container_api()
+            .container_stats(fensure_not_empty!(id), options.stream())
+            .map(|body| Stats::new(Logs(body)))
+            .map_err(|err| {
+                let e = Error::from(err);
+                warn!("Attempt to get container stats failed.");
+                log_failure(Level::Warn, &e);
+                e
+            });
+        Box::new(result)
+    }
+
+    fn exec(&self, id: &str, options: &ExecOptions) -> Self::ExecFuture {
+        debug!("Running {:?} in container {}", options.cmd(), id);
+        let mut config = ExecConfig::new()
+            .with_attach_stdout(true)
+            .with_attach_stderr(true)
+            .with_cmd(options.cmd().to_vec());
+        if let Some(user) = options.user() {
+            config.set_user(user.to_string());
+        }
+
+        let client_copy = self.client.clone();
+        let result = self
+            .client
+            .exec_api()
+            .container_exec(config, fensure_not_empty!(id))
+            .and_then(move |exec| {
+                client_copy.exec_api().exec_start(
+                    exec.id(),
+                    ExecStartConfig::new().with_detach(false).with_tty(false),
+                )
+            }).map(Logs)
+            .map_err(|err| {
+                let e = Error::from(err);
+                warn!("Attempt to run a command in a container failed.");
+                log_failure(Level::Warn, &e);
+                e
+            });
+        Box::new(result)
+    }
+
+    fn get_archive(&self, id: &str, path: &str) -> Self::GetArchiveFuture {
+        debug!("Copying {} out of container {}", path, id);
+        let result = self
+            .client
+            .container_api()
+            .container_archive(fensure_not_empty!(id), path)
+            .map(Logs)
+            .map_err(|err| {
+                let e = Error::from(err);
+                warn!("Attempt to copy files out of a container failed.");
+                log_failure(Level::Warn, &e);
+                e
+            });
+        Box::new(result)
+    }
+
+    // The archive is sent to docker as it is read.
+    fn put_archive(&self, id: &str, path: &str, archive: ArchiveStream) -> Self::PutArchiveFuture {
+        debug!("Copying files into {} in container {}", path, id);
+        let id = fensure_not_empty!(id);
+        let (body, feed) = stream_body(archive);
+        let result = self
+            .client
+            .container_api()
+            .put_container_archive(id, path, body, /* no overwrite dir non dir */ true)
+            .map_err(|err| {
+                let e = Error::from(err);
+                warn!("Attempt to copy files into a container failed.");
+                log_failure(Level::Warn, &e);
+                e
+            }).join(feed.then(|_| Ok(())))
+            .map(|_| ());
+        Box::new(result)
+    }
+
+    fn events(&self, options: &EventOptions) -> Self::EventsFuture {
+        let types = vec!["container"];
+        let actions = EVENT_ACTIONS.to_vec();
+        let mut filters = HashMap::new();
+        filters.insert("type", &types);
+        filters.insert("label", LABELS.deref());
+        filters.insert("event", &actions);
+
+        // the docker engine treats an empty value as "not set"
+        let since = if options.since() > 0 {
+            options.since().to_string()
+        } else {
+            String::new()
+        };
+        let until = if options.until() > 0 {
+            options.until().to_string()
+        } else {
+            String::new()
+        };
+
+        let result = serde_json::to_string(&filters).map(|filters| {
+            self.client
+                .system_api()
+                .system_events(&since, &until, &filters)
+                .map(|body| Events::new(Logs(body)))
+                .map_err(|err| {
+                    let e = Error::from(err);
+                    warn!("Attempt to get container events failed.");
+                    log_failure(Level::Warn, &e);
+                    e
+                })
+        });
+
+        match result {
+            Ok(f) => Box::new(f),
+            Err(err) => {
+                let e = Error::from(err);
+                warn!("Attempt to get container events failed.");
+                log_failure(Level::Warn, &e);
+                Box::new(future::err(e))
+            }
+        }
+    }
+
     fn registry(&self) -> &Self::ModuleRegistry {
         self
     }
 
     fn remove_all(&self) -> Self::RemoveAllFuture {
         let self_for_remove = self.clone();
-        Box::new(self.list().and_then(move |list| {
-            let n = list.into_iter().map(move |c| {
-                <DockerModuleRuntime as ModuleRuntime>::remove(&self_for_remove, c.name())
+        Box::new(self.dependencies().and_then(move |dependencies| {
+            let stages = dependency::start_stages(&dependencies).unwrap_or_else(|err| {
+                let e = Error::from(err);
+                warn!("Removing all modules at once.");
+                log_failure(Level::Warn, &e);
+                vec![dependencies.keys().cloned().collect()]
             });
-            future::join_all(n).map(|_| ())
+            // dependents are removed before the modules they depend on
+            stream::iter_ok(stages.into_iter().rev()).for_each(move |stage| {
+                let n = stage.iter().map(|name| {
+                    <DockerModuleRuntime as ModuleRuntime>::remove(&self_for_remove, name)
+                });
+                future::join_all(n).map(|_| ())
+            })
         }))
     }
+
+    fn list_volumes(&self) -> Self::ListVolumesFuture {
+        let labels = LABELS.iter().map(ToString::to_string).collect();
+        Box::new(self.volumes(labels).map_err(|err| {
+            warn!("Attempt to list volumes failed.");
+            log_failure(Level::Warn, &err);
+            err
+        }))
+    }
+
+    fn inspect_volume(&self, name: &str) -> Self::InspectVolumeFuture {
+        debug!("Inspecting volume {}", name);
+        Box::new(
+            self.client
+                .volume_api()
+                .volume_inspect(fensure_not_empty!(name))
+                .map(|volume| volume::to_core_volume(&volume))
+                .map_err(|err| {
+                    let e = Error::from(err);
+                    warn!("Attempt to inspect a volume failed.");
+                    log_failure(Level::Warn, &e);
+                    e
+                }),
+        )
+    }
+
+    fn create_volume(&self, name: &str, module: Option<&str>) -> Self::CreateVolumeFuture {
+        debug!("Creating volume {}", name);
+        let mut labels = HashMap::new();
+        labels.insert(LABEL_KEY.to_string(), LABEL_VALUE.to_string());
+        if let Some(module) = module {
+            labels.insert(MODULE_LABEL_KEY.to_string(), module.to_string());
+        }
+        let config = VolumeConfig::new()
+            .with_name(fensure_not_empty!(name).to_string())
+            .with_labels(labels);
+
+        Box::new(
+            self.client
+                .volume_api()
+                .volume_create(config)
+                .map(|volume| volume::to_core_volume(&volume))
+                .map_err(|err| {
+                    let e = Error::from(err);
+                    warn!("Attempt to create a volume failed.");
+                    log_failure(Level::Warn, &e);
+                    e
+                }),
+        )
+    }
+
+    fn remove_volume(&self, name: &str) -> Self::RemoveVolumeFuture {
+        debug!("Removing volume {}", name);
+        Box::new(
+            self.client
+                .volume_api()
+                .volume_delete(fensure_not_empty!(name), /* force */ false)
+                .map_err(|err| {
+                    let e = Error::from(err);
+                    warn!("Attempt to remove a volume failed.");
+                    log_failure(Level::Warn, &e);
+                    e
+                }),
+        )
+    }
+
+    // Docker has no way to export a volume, so the archive is copied out of
+    // a module that mounts the volume.
+    fn backup_volume(&self, name: &str) -> Self::BackupVolumeFuture {
+        debug!("Backing up volume {}", name);
+        let name = fensure_not_empty!(name).to_string();
+        let filters = {
+            let mut filters = HashMap::new();
+            filters.insert("label", LABELS.to_vec());
+            filters.insert("volume", vec![name.as_str()]);
+            serde_json::to_string(&filters)
+        };
+
+        let client = self.client.clone();
+        let backup = future::result(filters)
+            .map_err(Error::from)
+            .and_then(move |filters| {
+                client
+                    .container_api()
+                    .container_list(true, 0, false, &filters)
+                    .map_err(Error::from)
+                    .and_then(move |containers| match containers.first() {
+                        Some(container) => Ok((client, container.id().to_string(), name)),
+                        None => Err(Error::from(ErrorKind::VolumeNotMounted(name))),
+                    })
+            }).and_then(|(client, id, name)| {
+                client
+                    .container_api()
+                    .container_inspect(&id, false)
+                    .map_err(Error::from)
+                    .and_then(move |container| {
+                        let destination = container
+                            .mounts()
+                            .and_then(|mounts| {
+                                mounts
+                                    .iter()
+                                    .find(|mount| mount.name() == Some(&name))
+                                    .and_then(|mount| mount.destination())
+                            }).cloned();
+                        match destination {
+                            Some(destination) => Ok((client, id, destination)),
+                            None => Err(Error::from(ErrorKind::VolumeNotMounted(name))),
+                        }
+                    })
+            }).and_then(|(client, id, destination)| {
+                client
+                    .container_api()
+                    .container_archive(&id, &destination)
+                    .map(Logs)
+                    .map_err(Error::from)
+            }).map_err(|err| {
+                warn!("Attempt to back up a volume failed.");
+                log_failure(Level::Warn, &err);
+                err
+            });
+        Box::new(backup)
+    }
+}
+
+fn is_ready(container: &InlineResponse200) -> bool {
+    container.state().map_or(false, |state| {
+        let health = state.health().and_then(|health| health.status());
+        state.running() == Some(&true) && health.map_or(true, |health| health == "healthy")
+    })
 }
 
 #[derive(Debug)]
@@ -487,7 +1575,8 @@ mod tests {
     use tokio_core::reactor::Core;
     use url::Url;
 
-    use docker::models::ContainerCreateBody;
+    use docker::models::{ContainerCreateBody, HostConfig};
+    use edgelet_core::admission::AdmissionSettings;
     use edgelet_core::ModuleRegistry;
 
     use error::{Error, ErrorKind};
@@ -562,6 +1651,39 @@ mod tests {
         empty_test(|ref mut mri| <DockerModuleRuntime as ModuleRegistry>::remove(mri, "     "));
     }
 
+    fn image(id: &str, repo_digest: &str) -> Image {
+        serde_json::from_value(json!({
+            "Id": id,
+            "RepoDigests": [repo_digest],
+            "Parent": "",
+            "Comment": "",
+            "Created": "2018-06-01T10:00:00Z",
+            "Container": "",
+            "DockerVersion": "18.02.0",
+            "Author": "",
+            "Architecture": "amd64",
+            "Os": "linux",
+            "Size": 100,
+            "VirtualSize": 100,
+            "GraphDriver": { "Name": "overlay2" },
+            "RootFS": { "Type": "layers" }
+        })).unwrap()
+    }
+
+    #[test]
+    fn verify_image_accepts_id_or_manifest_digest() {
+        let image = image("sha256:1234", "edge/module@sha256:abcd");
+        assert!(verify_image(&image, "edge/module:1.0", "sha256:1234").is_ok());
+        assert!(verify_image(&image, "edge/module:1.0", "1234").is_ok());
+        assert!(verify_image(&image, "edge/module:1.0", "sha256:abcd").is_ok());
+
+        let err = verify_image(&image, "edge/module:1.0", "sha256:5678").unwrap_err();
+        assert!(match *err.kind() {
+            ErrorKind::ImageDigestMismatch(..) => true,
+            _ => false,
+        });
+    }
+
     #[test]
     fn merge_env_empty() {
         let cur_env = Some(vec![]);
@@ -627,6 +1749,40 @@ mod tests {
         core.run(task).unwrap();
     }
 
+    #[test]
+    fn create_fails_for_denied_create_options() {
+        let mut core = Core::new().unwrap();
+        let mri =
+            DockerModuleRuntime::new(&Url::parse("http://localhost/").unwrap(), &core.handle())
+                .unwrap()
+                .with_admission_policy(AdmissionPolicy::new(
+                    AdmissionSettings::new().with_allow_privileged(false),
+                ));
+
+        let create_options =
+            ContainerCreateBody::new().with_host_config(HostConfig::new().with_privileged(true));
+        let module_config = ModuleSpec::new(
+            "m1",
+            "docker",
+            DockerConfig::new("nginx:latest", create_options, None).unwrap(),
+            HashMap::new(),
+        ).unwrap();
+
+        let task = mri.create(module_config).then(|result| match result {
+            Ok(_) => panic!("Expected test to fail but it didn't!"),
+            Err(err) => match *err.kind() {
+                ErrorKind::AdmissionDenied(ref name, ref rule) => {
+                    assert_eq!("m1", name);
+                    assert_eq!("allow_privileged", rule);
+                    Ok(()) as Result<()>
+                }
+                _ => panic!("Expected admission denied error. Got some other error."),
+            },
+        });
+
+        core.run(task).unwrap();
+    }
+
     #[test]
     fn start_fails_for_empty_id() {
         let mut core = Core::new().unwrap();
@@ -735,6 +1891,134 @@ mod tests {
         core.run(task).unwrap();
     }
 
+    #[test]
+    fn pause_fails_for_empty_id() {
+        let mut core = Core::new().unwrap();
+        let mri =
+            DockerModuleRuntime::new(&Url::parse("http://localhost/").unwrap(), &core.handle())
+                .unwrap();
+
+        let task = mri.pause("").then(|result| match result {
+            Ok(_) => panic!("Expected test to fail but it didn't!"),
+            Err(err) => match err.kind() {
+                &ErrorKind::Utils => Ok(()) as Result<()>,
+                _ => panic!("Expected utils error. Got some other error."),
+            },
+        });
+
+        core.run(task).unwrap();
+    }
+
+    #[test]
+    fn pause_fails_for_white_space_id() {
+        let mut core = Core::new().unwrap();
+        let mri =
+            DockerModuleRuntime::new(&Url::parse("http://localhost/").unwrap(), &core.handle())
+                .unwrap();
+
+        let task = mri.pause("     ").then(|result| match result {
+            Ok(_) => panic!("Expected test to fail but it didn't!"),
+            Err(err) => match err.kind() {
+                &ErrorKind::Utils => Ok(()) as Result<()>,
+                _ => panic!("Expected utils error. Got some other error."),
+            },
+        });
+
+        core.run(task).unwrap();
+    }
+
+    #[test]
+    fn unpause_fails_for_empty_id() {
+        let mut core = Core::new().unwrap();
+        let mri =
+            DockerModuleRuntime::new(&Url::parse("http://localhost/").unwrap(), &core.handle())
+                .unwrap();
+
+        let task = mri.unpause("").then(|result| match result {
+            Ok(_) => panic!("Expected test to fail but it didn't!"),
+            Err(err) => match err.kind() {
+                &ErrorKind::Utils => Ok(()) as Result<()>,
+                _ => panic!("Expected utils error. Got some other error."),
+            },
+        });
+
+        core.run(task).unwrap();
+    }
+
+    #[test]
+    fn unpause_fails_for_white_space_id() {
+        let mut core = Core::new().unwrap();
+        let mri =
+            DockerModuleRuntime::new(&Url::parse("http://localhost/").unwrap(), &core.handle())
+                .unwrap();
+
+        let task = mri.unpause("     ").then(|result| match result {
+            Ok(_) => panic!("Expected test to fail but it didn't!"),
+            Err(err) => match err.kind() {
+                &ErrorKind::Utils => Ok(()) as Result<()>,
+                _ => panic!("Expected utils error. Got some other error."),
+            },
+        });
+
+        core.run(task).unwrap();
+    }
+
+    #[test]
+    fn exec_fails_for_empty_id() {
+        let mut core = Core::new().unwrap();
+        let mri =
+            DockerModuleRuntime::new(&Url::parse("http://localhost/").unwrap(), &core.handle())
+                .unwrap();
+
+        let options = ExecOptions::new(vec!["ls".to_string()]);
+        let task = mri.exec("", &options).then(|result| match result {
+            Ok(_) => panic!("Expected test to fail but it didn't!"),
+            Err(err) => match err.kind() {
+                &ErrorKind::Utils => Ok(()) as Result<()>,
+                _ => panic!("Expected utils error. Got some other error."),
+            },
+        });
+
+        core.run(task).unwrap();
+    }
+
+    #[test]
+    fn get_archive_fails_for_empty_id() {
+        let mut core = Core::new().unwrap();
+        let mri =
+            DockerModuleRuntime::new(&Url::parse("http://localhost/").unwrap(), &core.handle())
+                .unwrap();
+
+        let task = mri.get_archive("", "/tmp").then(|result| match result {
+            Ok(_) => panic!("Expected test to fail but it didn't!"),
+            Err(err) => match err.kind() {
+                &ErrorKind::Utils => Ok(()) as Result<()>,
+                _ => panic!("Expected utils error. Got some other error."),
+            },
+        });
+
+        core.run(task).unwrap();
+    }
+
+    #[test]
+    fn put_archive_fails_for_empty_id() {
+        let mut core = Core::new().unwrap();
+        let mri =
+            DockerModuleRuntime::new(&Url::parse("http://localhost/").unwrap(), &core.handle())
+                .unwrap();
+
+        let task =
+            mri.put_archive("", "/tmp", Box::new(stream::empty())).then(|result| match result {
+                    Ok(_) => panic!("Expected test to fail but it didn't!"),
+                    Err(err) => match err.kind() {
+                        &ErrorKind::Utils => Ok(()) as Result<()>,
+                        _ => panic!("Expected utils error. Got some other error."),
+                    },
+                });
+
+        core.run(task).unwrap();
+    }
+
     #[test]
     fn remove_fails_for_empty_id() {
         let mut core = Core::new().unwrap();
@@ -753,6 +2037,26 @@ mod tests {
         core.run(task).unwrap();
     }
 
+    #[test]
+    fn stats_fails_for_empty_id() {
+        let mut core = Core::new().unwrap();
+        let mri =
+            DockerModuleRuntime::new(&Url::parse("http://localhost/").unwrap(), &core.handle())
+                .unwrap();
+
+        let task = mri
+            .stats("", &StatsOptions::new())
+            .then(|result| match result {
+                Ok(_) => panic!("Expected test to fail but it didn't!"),
+                Err(err) => match err.kind() {
+                    &ErrorKind::Utils => Ok(()) as Result<()>,
+                    _ => panic!("Expected utils error. Got some other error."),
+                },
+            });
+
+        core.run(task).unwrap();
+    }
+
     #[test]
     fn remove_fails_for_white_space_id() {
         let mut core = Core::new().unwrap();
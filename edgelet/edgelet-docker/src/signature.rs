@@ -0,0 +1,229 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+
+use base64;
+use docker::models::Image;
+use edgelet_core::signature::{ImageSignatureSettings, SignatureVerifier, VerificationMode};
+use failure::ResultExt;
+use openssl::hash::MessageDigest;
+use openssl::pkey::PKey;
+use openssl::sign::Verifier;
+
+use error::{Error, ErrorKind, Result};
+
+/// Verifies signatures made with the private key of any of a set of PEM
+/// encoded public keys. The signature is over the SHA-256 hash of the digest.
+pub struct PublicKeyVerifier {
+    keys: Vec<PKey>,
+}
+
+impl PublicKeyVerifier {
+    pub fn new(keys: Vec<PKey>) -> Self {
+        PublicKeyVerifier { keys }
+    }
+
+    pub fn from_files<P: AsRef<Path>>(paths: &[P]) -> Result<Self> {
+        let keys = paths
+            .iter()
+            .map(|path| {
+                let path = path.as_ref();
+                let context = || ErrorKind::InvalidTrustedKey(path.display().to_string());
+                let pem = fs::read(path).with_context(|_| context())?;
+                PKey::public_key_from_pem(&pem)
+                    .with_context(|_| context())
+                    .map_err(Error::from)
+            }).collect::<Result<Vec<PKey>>>()?;
+        Ok(PublicKeyVerifier::new(keys))
+    }
+}
+
+impl SignatureVerifier for PublicKeyVerifier {
+    fn verify(&self, digest: &str, signature: &[u8]) -> bool {
+        self.keys.iter().any(|key| {
+            Verifier::new(MessageDigest::sha256(), key)
+                .and_then(|mut verifier| {
+                    verifier.update(digest.as_bytes())?;
+                    verifier.verify(signature)
+                }).unwrap_or(false)
+        })
+    }
+}
+
+/// Decides whether an image may be used to create a module.
+#[derive(Clone)]
+pub struct SignaturePolicy {
+    mode: VerificationMode,
+    verifier: Arc<SignatureVerifier + Send + Sync>,
+}
+
+impl SignaturePolicy {
+    pub fn new<V>(mode: VerificationMode, verifier: V) -> Self
+    where
+        V: 'static + SignatureVerifier + Send + Sync,
+    {
+        SignaturePolicy {
+            mode,
+            verifier: Arc::new(verifier),
+        }
+    }
+
+    /// Builds the policy with the trusted keys in the settings. The keys
+    /// are only loaded when verification is on.
+    pub fn from_settings(settings: &ImageSignatureSettings) -> Result<Self> {
+        let verifier = match settings.mode() {
+            VerificationMode::Off => PublicKeyVerifier::new(Vec::new()),
+            _ => PublicKeyVerifier::from_files(settings.trusted_keys())?,
+        };
+        Ok(SignaturePolicy::new(settings.mode(), verifier))
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.mode != VerificationMode::Off
+    }
+
+    /// Checks the base64 encoded signature of an image. The signature may be
+    /// over the image ID or over the digest of its manifest in a registry.
+    pub fn check(&self, name: &str, image: &Image, signature: Option<&str>) -> Result<()> {
+        if !self.enabled() || self.is_trusted(image, signature) {
+            return Ok(());
+        }
+
+        match self.mode {
+            VerificationMode::Enforce => {
+                Err(Error::from(ErrorKind::UntrustedImage(name.to_string())))
+            }
+            _ => {
+                warn!("Image {} is not signed by a trusted key", name);
+                Ok(())
+            }
+        }
+    }
+
+    fn is_trusted(&self, image: &Image, signature: Option<&str>) -> bool {
+        let signature = match signature.map(base64::decode) {
+            Some(Ok(signature)) => signature,
+            _ => return false,
+        };
+        let repo_digests = image.repo_digests().map_or(&[][..], |d| d.as_slice());
+        let mut digests = repo_digests
+            .iter()
+            .filter_map(|d| d.splitn(2, '@').nth(1))
+            .chain(Some(image.id().as_str()));
+        digests.any(|digest| self.verifier.verify(digest, &signature))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use openssl::ec::{EcGroup, EcKey};
+    use openssl::nid;
+    use openssl::sign::Signer;
+    use serde_json;
+
+    const IMAGE_ID: &str = "sha256:0a1b2c";
+    const IMAGE_DIGEST: &str = "sha256:3d4e5f";
+
+    fn key_pair() -> (PKey, PKey) {
+        let group = EcGroup::from_curve_name(nid::X9_62_PRIME256V1).unwrap();
+        let private_key = PKey::from_ec_key(EcKey::generate(&group).unwrap()).unwrap();
+        let pem = private_key.public_key_to_pem().unwrap();
+        let public_key = PKey::public_key_from_pem(&pem).unwrap();
+        (private_key, public_key)
+    }
+
+    fn sign(key: &PKey, digest: &str) -> String {
+        let mut signer = Signer::new(MessageDigest::sha256(), key).unwrap();
+        signer.update(digest.as_bytes()).unwrap();
+        base64::encode(&signer.sign_to_vec().unwrap())
+    }
+
+    fn image() -> Image {
+        serde_json::from_value(json!({
+            "Id": IMAGE_ID,
+            "RepoDigests": [format!("edge/module@{}", IMAGE_DIGEST)],
+            "Parent": "",
+            "Comment": "",
+            "Created": "2018-06-01T10:00:00Z",
+            "Container": "",
+            "DockerVersion": "18.02.0",
+            "Author": "",
+            "Architecture": "amd64",
+            "Os": "linux",
+            "Size": 100,
+            "VirtualSize": 100,
+            "GraphDriver": { "Name": "overlay2" },
+            "RootFS": { "Type": "layers" }
+        })).unwrap()
+    }
+
+    fn policy(mode: VerificationMode, key: PKey) -> SignaturePolicy {
+        SignaturePolicy::new(mode, PublicKeyVerifier::new(vec![key]))
+    }
+
+    #[test]
+    fn signed_image_is_trusted() {
+        let (private_key, public_key) = key_pair();
+        let policy = policy(VerificationMode::Enforce, public_key);
+
+        let signature = sign(&private_key, IMAGE_DIGEST);
+        assert!(policy
+            .check("edge/module", &image(), Some(&signature))
+            .is_ok());
+
+        let signature = sign(&private_key, IMAGE_ID);
+        assert!(policy
+            .check("edge/module", &image(), Some(&signature))
+            .is_ok());
+    }
+
+    #[test]
+    fn enforce_rejects_untrusted_image() {
+        let (private_key, _) = key_pair();
+        let (_, public_key) = key_pair();
+        let policy = policy(VerificationMode::Enforce, public_key);
+
+        let signature = sign(&private_key, IMAGE_DIGEST);
+        for signature in &[None, Some("not base64"), Some(signature.as_str())] {
+            let err = policy
+                .check("edge/module", &image(), *signature)
+                .unwrap_err();
+            match *err.kind() {
+                ErrorKind::UntrustedImage(ref name) => assert_eq!("edge/module", name),
+                _ => panic!("Expected untrusted image, got {:?}", err),
+            }
+        }
+    }
+
+    #[test]
+    fn warn_and_off_allow_untrusted_image() {
+        let warn = policy(VerificationMode::Warn, key_pair().1);
+        assert!(warn.enabled());
+        assert!(warn.check("edge/module", &image(), None).is_ok());
+
+        let off = policy(VerificationMode::Off, key_pair().1);
+        assert!(!off.enabled());
+        assert!(off.check("edge/module", &image(), None).is_ok());
+    }
+
+    #[test]
+    fn missing_trusted_key_fails() {
+        let settings = ImageSignatureSettings::new(
+            VerificationMode::Enforce,
+            vec!["/does/not/exist.pem".into()],
+        );
+        let err = SignaturePolicy::from_settings(&settings).err().unwrap();
+        match *err.kind() {
+            ErrorKind::InvalidTrustedKey(ref path) => assert_eq!("/does/not/exist.pem", path),
+            _ => panic!("Expected invalid trusted key, got {:?}", err),
+        }
+
+        let settings =
+            ImageSignatureSettings::new(VerificationMode::Off, vec!["/does/not/exist.pem".into()]);
+        assert!(SignaturePolicy::from_settings(&settings).is_ok());
+    }
+}
@@ -0,0 +1,265 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use chrono::prelude::*;
+use futures::{Async, Poll, Stream};
+
+use edgelet_core::ModuleStats;
+use edgelet_http::JsonLines;
+
+use error::Error;
+use module::MIN_DATE;
+use runtime::Logs;
+
+/// Subset of the document returned by the docker engine's container stats
+/// endpoint that we surface through `ModuleStats`.
+#[derive(Debug, Default, Deserialize)]
+pub struct DockerStats {
+    #[serde(default)]
+    read: Option<String>,
+    #[serde(default)]
+    cpu_stats: Option<CpuStats>,
+    #[serde(default)]
+    precpu_stats: Option<CpuStats>,
+    #[serde(default)]
+    memory_stats: Option<MemoryStats>,
+    #[serde(default)]
+    networks: Option<HashMap<String, NetworkStats>>,
+    #[serde(default)]
+    blkio_stats: Option<BlkioStats>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct CpuStats {
+    #[serde(default)]
+    cpu_usage: Option<CpuUsage>,
+    #[serde(default)]
+    system_cpu_usage: Option<u64>,
+    #[serde(default)]
+    online_cpus: Option<u64>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct CpuUsage {
+    #[serde(default)]
+    total_usage: u64,
+    #[serde(default)]
+    percpu_usage: Option<Vec<u64>>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct MemoryStats {
+    #[serde(default)]
+    usage: u64,
+    #[serde(default)]
+    limit: u64,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct NetworkStats {
+    #[serde(default)]
+    rx_bytes: u64,
+    #[serde(default)]
+    tx_bytes: u64,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct BlkioStats {
+    #[serde(default)]
+    io_service_bytes_recursive: Option<Vec<BlkioStatEntry>>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct BlkioStatEntry {
+    #[serde(default)]
+    op: String,
+    #[serde(default)]
+    value: u64,
+}
+
+impl CpuStats {
+    fn total_usage(&self) -> u64 {
+        self.cpu_usage.as_ref().map(|u| u.total_usage).unwrap_or(0)
+    }
+
+    fn online_cpus(&self) -> u64 {
+        self.online_cpus
+            .or_else(|| {
+                self.cpu_usage
+                    .as_ref()
+                    .and_then(|u| u.percpu_usage.as_ref())
+                    .map(|p| p.len() as u64)
+            }).unwrap_or(0)
+    }
+}
+
+impl BlkioStats {
+    fn total(&self, op: &str) -> u64 {
+        self.io_service_bytes_recursive
+            .as_ref()
+            .map(|entries| {
+                entries
+                    .iter()
+                    .filter(|e| e.op.eq_ignore_ascii_case(op))
+                    .map(|e| e.value)
+                    .sum()
+            }).unwrap_or(0)
+    }
+}
+
+// This is the same computation the docker CLI performs for `docker stats`.
+fn cpu_percent(cpu: &CpuStats, precpu: Option<&CpuStats>) -> f64 {
+    let (pre_total, pre_system) = precpu
+        .map(|p| (p.total_usage(), p.system_cpu_usage.unwrap_or(0)))
+        .unwrap_or((0, 0));
+    let cpu_delta = cpu.total_usage().saturating_sub(pre_total);
+    let system_delta = cpu.system_cpu_usage.unwrap_or(0).saturating_sub(pre_system);
+
+    if cpu_delta > 0 && system_delta > 0 {
+        (cpu_delta as f64 / system_delta as f64) * cpu.online_cpus() as f64 * 100.0
+    } else {
+        0.0
+    }
+}
+
+impl From<DockerStats> for ModuleStats {
+    fn from(stats: DockerStats) -> ModuleStats {
+        let read = stats
+            .read
+            .as_ref()
+            .and_then(|d| if d != MIN_DATE { Some(d) } else { None })
+            .and_then(|read| DateTime::from_str(read).ok());
+        let (cpu_percent, cpu_total_usage) = stats
+            .cpu_stats
+            .as_ref()
+            .map(|cpu| {
+                (
+                    cpu_percent(cpu, stats.precpu_stats.as_ref()),
+                    cpu.total_usage(),
+                )
+            }).unwrap_or((0.0, 0));
+        let (memory_usage, memory_limit) = stats
+            .memory_stats
+            .as_ref()
+            .map(|m| (m.usage, m.limit))
+            .unwrap_or((0, 0));
+        let (network_rx_bytes, network_tx_bytes) = stats
+            .networks
+            .as_ref()
+            .map(|networks| {
+                networks.values().fold((0, 0), |(rx, tx), n| {
+                    (rx + n.rx_bytes, tx + n.tx_bytes)
+                })
+            }).unwrap_or((0, 0));
+        let (block_read_bytes, block_write_bytes) = stats
+            .blkio_stats
+            .as_ref()
+            .map(|b| (b.total("read"), b.total("write")))
+            .unwrap_or((0, 0));
+
+        ModuleStats::default()
+            .with_read(read)
+            .with_cpu_percent(cpu_percent)
+            .with_cpu_total_usage(cpu_total_usage)
+            .with_memory_usage(memory_usage)
+            .with_memory_limit(memory_limit)
+            .with_network_rx_bytes(network_rx_bytes)
+            .with_network_tx_bytes(network_tx_bytes)
+            .with_block_read_bytes(block_read_bytes)
+            .with_block_write_bytes(block_write_bytes)
+    }
+}
+
+/// Stream of stats samples for a container. Yields a single sample unless
+/// streaming was requested.
+pub struct Stats(JsonLines<Logs, DockerStats>);
+
+impl Stats {
+    pub fn new(body: Logs) -> Self {
+        Stats(JsonLines::new(body))
+    }
+}
+
+impl Stream for Stats {
+    type Item = ModuleStats;
+    type Error = Error;
+
+    fn poll(&mut self) -> Poll<Option<Self::Item>, Self::Error> {
+        let stats = try_ready!(self.0.poll());
+        Ok(Async::Ready(stats.map(ModuleStats::from)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use serde_json;
+
+    #[test]
+    fn docker_stats_to_module_stats() {
+        let stats: DockerStats = serde_json::from_value(json!({
+            "read": "2018-08-01T22:57:31.547920715Z",
+            "cpu_stats": {
+                "cpu_usage": {
+                    "total_usage": 300,
+                    "percpu_usage": [150, 150]
+                },
+                "system_cpu_usage": 2000,
+                "online_cpus": 2
+            },
+            "precpu_stats": {
+                "cpu_usage": {
+                    "total_usage": 100
+                },
+                "system_cpu_usage": 1000
+            },
+            "memory_stats": {
+                "usage": 6537216,
+                "limit": 67108864
+            },
+            "networks": {
+                "eth0": { "rx_bytes": 5338, "tx_bytes": 648 },
+                "eth5": { "rx_bytes": 4641, "tx_bytes": 690 }
+            },
+            "blkio_stats": {
+                "io_service_bytes_recursive": [
+                    { "major": 8, "minor": 0, "op": "Read", "value": 1024 },
+                    { "major": 8, "minor": 0, "op": "Write", "value": 2048 },
+                    { "major": 8, "minor": 16, "op": "Read", "value": 512 },
+                    { "major": 8, "minor": 0, "op": "Total", "value": 3584 }
+                ]
+            }
+        })).unwrap();
+
+        let stats = ModuleStats::from(stats);
+        assert_eq!(
+            "2018-08-01T22:57:31.547920715+00:00",
+            stats.read().unwrap().to_rfc3339()
+        );
+        assert_eq!(40.0, stats.cpu_percent());
+        assert_eq!(300, stats.cpu_total_usage());
+        assert_eq!(6537216, stats.memory_usage());
+        assert_eq!(67108864, stats.memory_limit());
+        assert_eq!(9979, stats.network_rx_bytes());
+        assert_eq!(1338, stats.network_tx_bytes());
+        assert_eq!(1536, stats.block_read_bytes());
+        assert_eq!(2048, stats.block_write_bytes());
+    }
+
+    #[test]
+    fn docker_stats_with_missing_sections() {
+        let stats: DockerStats = serde_json::from_value(json!({
+            "read": MIN_DATE,
+            "blkio_stats": { "io_service_bytes_recursive": null }
+        })).unwrap();
+
+        let stats = ModuleStats::from(stats);
+        assert_eq!(None, stats.read());
+        assert_eq!(0.0, stats.cpu_percent());
+        assert_eq!(0, stats.memory_usage());
+        assert_eq!(0, stats.block_read_bytes());
+    }
+}
@@ -0,0 +1,111 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+use std::str::FromStr;
+
+use chrono::DateTime;
+use docker::models::{ContainerCreateBody, Volume};
+use edgelet_core::Volume as CoreVolume;
+
+use admission::bind_source;
+
+/// Label of a volume with the name of the module the volume belongs to.
+pub const MODULE_LABEL_KEY: &str = "net.azure-devices.edge.module";
+
+pub fn to_core_volume(volume: &Volume) -> CoreVolume {
+    CoreVolume::new(volume.name().to_string())
+        .with_module(volume.labels().get(MODULE_LABEL_KEY).cloned())
+        .with_driver(volume.driver().to_string())
+        .with_mountpoint(volume.mountpoint().to_string())
+        .with_created(
+            volume
+                .created_at()
+                .and_then(|created_at| DateTime::from_str(created_at).ok()),
+        )
+}
+
+/// The named volumes in the binds and mounts of a module. Docker would
+/// create missing ones without labels, so they are created with the module
+/// as their owner first.
+pub fn named_volumes(create_options: &ContainerCreateBody) -> Vec<String> {
+    let host_config = match create_options.host_config() {
+        Some(host_config) => host_config,
+        None => return Vec::new(),
+    };
+
+    let binds = host_config.binds().map_or(&[][..], |b| b.as_slice());
+    let mounts = host_config.mounts().map_or(&[][..], |m| m.as_slice());
+    let mut volumes: Vec<String> = binds
+        .iter()
+        .filter(|bind| bind_source(bind).is_none())
+        .filter_map(|bind| bind.split(':').next())
+        .chain(
+            mounts
+                .iter()
+                .filter(|mount| mount._type().map(String::as_str) == Some("volume"))
+                .filter_map(|mount| mount.source().map(String::as_str)),
+        ).filter(|name| !name.is_empty())
+        .map(ToString::to_string)
+        .collect();
+    volumes.sort();
+    volumes.dedup();
+    volumes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::collections::HashMap;
+
+    use docker::models::{HostConfig, Mount};
+
+    #[test]
+    fn volume_belongs_to_module_from_label() {
+        let mut labels = HashMap::new();
+        labels.insert(MODULE_LABEL_KEY.to_string(), "mod1".to_string());
+        let volume = Volume::new(
+            "mod1-data".to_string(),
+            "local".to_string(),
+            "/var/lib/docker/volumes/mod1-data/_data".to_string(),
+            labels,
+            "local".to_string(),
+            HashMap::new(),
+        ).with_created_at("2018-08-01T12:00:00Z".to_string());
+
+        let volume = to_core_volume(&volume);
+        assert_eq!("mod1-data", volume.name());
+        assert_eq!(Some("mod1"), volume.module());
+        assert_eq!("local", volume.driver());
+        assert_eq!(
+            "2018-08-01T12:00:00+00:00",
+            volume.created().unwrap().to_rfc3339()
+        );
+    }
+
+    #[test]
+    fn named_volumes_skip_host_paths() {
+        let host_config = HostConfig::new()
+            .with_binds(vec![
+                "/var/data:/data".to_string(),
+                "C:\\data:C:\\data".to_string(),
+                "mod1-data:/data:ro".to_string(),
+            ]).with_mounts(vec![
+                Mount::new()
+                    .with__type("volume".to_string())
+                    .with_source("mod1-cache".to_string()),
+                Mount::new()
+                    .with__type("bind".to_string())
+                    .with_source("/etc".to_string()),
+                Mount::new()
+                    .with__type("volume".to_string())
+                    .with_source("mod1-data".to_string()),
+            ]);
+        let create_options = ContainerCreateBody::new().with_host_config(host_config);
+
+        assert_eq!(
+            vec!["mod1-cache".to_string(), "mod1-data".to_string()],
+            named_volumes(&create_options)
+        );
+        assert!(named_volumes(&ContainerCreateBody::new()).is_empty());
+    }
+}
@@ -23,7 +23,7 @@ use std::thread;
 use std::time::Duration;
 
 use futures::prelude::*;
-use futures::{future, Stream};
+use futures::{future, stream, Stream};
 use hyper::header::{ContentLength, ContentType};
 use hyper::server::{Request, Response};
 use hyper::{Error as HyperError, Method, StatusCode};
@@ -35,10 +35,16 @@ use url::Url;
 use docker::models::AuthConfig;
 use docker::models::{
     ContainerCreateBody, ContainerHostConfig, ContainerNetworkSettings, ContainerSummary,
-    HostConfig, HostConfigPortBindings, ImageDeleteResponseItem,
+    ExecConfig, HostConfig, HostConfigPortBindings, ImageDeleteResponseItem, NetworkConfig,
+    VolumeConfig,
 };
-use edgelet_core::{LogOptions, LogTail, Module, ModuleRegistry, ModuleRuntime, ModuleSpec};
-use edgelet_docker::{DockerConfig, DockerModuleRuntime};
+use edgelet_core::{
+    EventOptions, ExecOptions, ImagePullPolicy, LogOptions, LogTail, Module, ModuleAction,
+    ModuleRegistry, ModuleRuntime, ModuleSpec, StatsOptions,
+};
+use edgelet_core::network::{IpamConfig, Network};
+use edgelet_core::signature::{SignatureVerifier, VerificationMode};
+use edgelet_docker::{DockerConfig, DockerModuleRuntime, ErrorKind, SignaturePolicy};
 use edgelet_test_utils::{get_unused_tcp_port, run_tcp_server};
 
 const IMAGE_NAME: &str = "nginx:latest";
@@ -221,6 +227,232 @@ fn image_remove_succeeds() {
     core.run(task).unwrap();
 }
 
+const IMAGE_DIGEST: &str = "sha256:4bd4ea3ba8b9";
+const IMAGE_ID: &str = "sha256:a1d9fa1c2cbc";
+
+fn image_inspect_response(id: &str, repo_digests: &[&str]) -> Response {
+    let response = json!({
+        "Id": id,
+        "RepoDigests": repo_digests,
+        "Parent": "",
+        "Comment": "",
+        "Created": "2018-08-01T00:00:00Z",
+        "Container": "",
+        "DockerVersion": "18.03.1",
+        "Author": "",
+        "Architecture": "amd64",
+        "Os": "linux",
+        "Size": 1024,
+        "VirtualSize": 1024,
+        "GraphDriver": { "Name": "overlay2" },
+        "RootFS": { "Type": "layers" }
+    }).to_string();
+    Response::new()
+        .with_header(ContentLength(response.len() as u64))
+        .with_header(ContentType::json())
+        .with_body(response)
+        .with_status(StatusCode::Ok)
+}
+
+#[cfg_attr(feature = "cargo-clippy", allow(needless_pass_by_value))]
+fn image_pull_by_digest_handler(req: Request) -> Box<Future<Item = Response, Error = HyperError>> {
+    let pinned = format!("nginx@{}", IMAGE_DIGEST);
+    let query_map: HashMap<String, String> = req
+        .query()
+        .map(|query| parse_query(query.as_bytes()).into_owned().collect())
+        .unwrap_or_else(HashMap::new);
+
+    let response = if req.path() == "/images/create" {
+        assert_eq!(query_map.get("fromImage"), Some(&pinned));
+        Response::new().with_status(StatusCode::Ok)
+    } else if req.path() == format!("/images/{}/json", pinned) {
+        image_inspect_response(IMAGE_ID, &[&pinned])
+    } else {
+        // the pulled image gets the configured name
+        assert_eq!(req.path(), format!("/images/{}/tag", IMAGE_ID));
+        assert_eq!(query_map.get("repo"), Some(&"nginx".to_string()));
+        assert_eq!(query_map.get("tag"), Some(&"latest".to_string()));
+        Response::new().with_status(StatusCode::Created)
+    };
+    Box::new(future::ok(response))
+}
+
+#[test]
+fn image_pull_by_digest_succeeds() {
+    let (sender, receiver) = channel();
+
+    let port = get_unused_tcp_port();
+    thread::spawn(move || {
+        run_tcp_server("127.0.0.1", port, image_pull_by_digest_handler, &sender);
+    });
+
+    // wait for server to get ready
+    receiver.recv().unwrap();
+
+    let mut core = Core::new().unwrap();
+    let mri = DockerModuleRuntime::new(
+        &Url::parse(&format!("http://localhost:{}/", port)).unwrap(),
+        &core.handle(),
+    ).unwrap();
+
+    let config = DockerConfig::new(IMAGE_NAME, ContainerCreateBody::new(), None)
+        .unwrap()
+        .with_image_id(IMAGE_DIGEST.to_string());
+
+    let task = mri.pull(&config);
+    core.run(task).unwrap();
+}
+
+#[cfg_attr(feature = "cargo-clippy", allow(needless_pass_by_value))]
+fn image_mismatch_handler(req: Request) -> Box<Future<Item = Response, Error = HyperError>> {
+    // the tag was repointed to another image
+    assert_eq!(req.path(), format!("/images/{}/json", IMAGE_NAME));
+    Box::new(future::ok(image_inspect_response(
+        "sha256:5c2e1fb7a1d0",
+        &["nginx@sha256:c6e4a53f4a8e"],
+    )))
+}
+
+#[test]
+fn container_create_with_mismatched_image_fails() {
+    let (sender, receiver) = channel();
+
+    let port = get_unused_tcp_port();
+    thread::spawn(move || {
+        run_tcp_server("127.0.0.1", port, image_mismatch_handler, &sender);
+    });
+
+    // wait for server to get ready
+    receiver.recv().unwrap();
+
+    let config = DockerConfig::new(IMAGE_NAME, ContainerCreateBody::new(), None)
+        .unwrap()
+        .with_image_id(IMAGE_DIGEST.to_string());
+    let module_config = ModuleSpec::new("m1", "docker", config, HashMap::new()).unwrap();
+
+    let mut core = Core::new().unwrap();
+    let mri = DockerModuleRuntime::new(
+        &Url::parse(&format!("http://localhost:{}/", port)).unwrap(),
+        &core.handle(),
+    ).unwrap();
+
+    let task = mri.create(module_config);
+    let err = core.run(task).unwrap_err();
+    match *err.kind() {
+        ErrorKind::ImageDigestMismatch(ref image, ref digest, ref found) => {
+            assert_eq!(IMAGE_NAME, image);
+            assert_eq!(IMAGE_DIGEST, digest);
+            assert_eq!("sha256:5c2e1fb7a1d0", found);
+        }
+        _ => panic!("expected an image digest mismatch"),
+    }
+}
+
+fn pinned_image_handler(req: Request) -> Box<Future<Item = Response, Error = HyperError>> {
+    if req.path() == format!("/images/{}/json", IMAGE_NAME) {
+        let pinned = format!("nginx@{}", IMAGE_DIGEST);
+        return Box::new(future::ok(image_inspect_response(IMAGE_ID, &[&pinned])));
+    }
+
+    // the container is created from the image that was checked, not from
+    // whatever the tag points to by now
+    assert_eq!(req.path(), "/containers/create");
+    Box::new(req.body().concat2().map(|body| {
+        let create_options: ContainerCreateBody = serde_json::from_slice(body.as_ref()).unwrap();
+        assert_eq!(IMAGE_ID, create_options.image().unwrap());
+        let labels = create_options.labels().unwrap();
+        assert_eq!(IMAGE_NAME, labels["net.azure-devices.edge.image"]);
+
+        let response = json!({ "Id": "12345", "Warnings": [] }).to_string();
+        Response::new()
+            .with_header(ContentLength(response.len() as u64))
+            .with_header(ContentType::json())
+            .with_body(response)
+            .with_status(StatusCode::Ok)
+    }))
+}
+
+#[test]
+fn container_create_uses_checked_image() {
+    let (sender, receiver) = channel();
+
+    let port = get_unused_tcp_port();
+    thread::spawn(move || {
+        run_tcp_server("127.0.0.1", port, pinned_image_handler, &sender);
+    });
+
+    // wait for server to get ready
+    receiver.recv().unwrap();
+
+    let config = DockerConfig::new(IMAGE_NAME, ContainerCreateBody::new(), None)
+        .unwrap()
+        .with_image_id(IMAGE_DIGEST.to_string());
+    let module_config = ModuleSpec::new("m1", "docker", config, HashMap::new()).unwrap();
+
+    let mut core = Core::new().unwrap();
+    let mri = DockerModuleRuntime::new(
+        &Url::parse(&format!("http://localhost:{}/", port)).unwrap(),
+        &core.handle(),
+    ).unwrap();
+
+    let task = mri.create(module_config);
+    core.run(task).unwrap();
+}
+
+// Trusts images whose signature is the digest of their manifest.
+struct DigestVerifier;
+
+impl SignatureVerifier for DigestVerifier {
+    fn verify(&self, digest: &str, signature: &[u8]) -> bool {
+        digest.as_bytes() == signature
+    }
+}
+
+#[cfg_attr(feature = "cargo-clippy", allow(needless_pass_by_value))]
+fn unsigned_image_handler(req: Request) -> Box<Future<Item = Response, Error = HyperError>> {
+    // the container must not be created
+    assert_eq!(req.path(), format!("/images/{}/json", IMAGE_NAME));
+    Box::new(future::ok(image_inspect_response(
+        "sha256:5c2e1fb7a1d0",
+        &["nginx@sha256:c6e4a53f4a8e"],
+    )))
+}
+
+#[test]
+fn container_create_with_untrusted_image_fails() {
+    let (sender, receiver) = channel();
+
+    let port = get_unused_tcp_port();
+    thread::spawn(move || {
+        run_tcp_server("127.0.0.1", port, unsigned_image_handler, &sender);
+    });
+
+    // wait for server to get ready
+    receiver.recv().unwrap();
+
+    let config = DockerConfig::new(IMAGE_NAME, ContainerCreateBody::new(), None)
+        .unwrap()
+        .with_signature(base64::encode(IMAGE_DIGEST));
+    let module_config = ModuleSpec::new("m1", "docker", config, HashMap::new()).unwrap();
+
+    let mut core = Core::new().unwrap();
+    let mri = DockerModuleRuntime::new(
+        &Url::parse(&format!("http://localhost:{}/", port)).unwrap(),
+        &core.handle(),
+    ).unwrap()
+    .with_signature_policy(SignaturePolicy::new(
+        VerificationMode::Enforce,
+        DigestVerifier,
+    ));
+
+    let task = mri.create(module_config);
+    let err = core.run(task).unwrap_err();
+    match *err.kind() {
+        ErrorKind::UntrustedImage(ref image) => assert_eq!(IMAGE_NAME, image),
+        _ => panic!("expected an untrusted image"),
+    }
+}
+
 fn container_create_handler(req: Request) -> Box<Future<Item = Response, Error = HyperError>> {
     assert_eq!(req.method(), &Method::Post);
     assert_eq!(req.path(), "/containers/create");
@@ -293,6 +525,9 @@ fn container_create_handler(req: Request) -> Box<Future<Item = Response, Error =
                 expected.insert("test1".to_string(), json!({}));
                 assert_eq!(*volumes, expected);
 
+                let labels = create_options.labels().unwrap();
+                assert_eq!(64, labels["net.azure-devices.edge.spec-hash"].len());
+
                 Ok(())
             }).map(|_| {
                 Response::new()
@@ -353,7 +588,8 @@ fn container_create_succeeds() {
         "docker",
         DockerConfig::new("nginx:latest", create_options, None).unwrap(),
         env,
-    ).unwrap();
+    ).unwrap()
+    .with_record_spec_hash(true);
 
     let mut core = Core::new().unwrap();
     let mri = DockerModuleRuntime::new(
@@ -366,7 +602,77 @@ fn container_create_succeeds() {
     core.run(task).unwrap();
 }
 
+fn container_create_without_spec_hash_handler(
+    req: Request,
+) -> Box<Future<Item = Response, Error = HyperError>> {
+    assert_eq!(req.path(), "/containers/create");
+
+    Box::new(req.body().concat2().and_then(|body| {
+        let create_options: ContainerCreateBody = serde_json::from_slice(body.as_ref()).unwrap();
+        let labels = create_options.labels().unwrap();
+        assert!(!labels.contains_key("net.azure-devices.edge.spec-hash"));
+        assert_eq!("bar", labels["foo"]);
+
+        json_response(&json!({ "Id": "12345", "Warnings": [] }).to_string())
+    }))
+}
+
+#[test]
+fn container_create_without_spec_hash_drops_label() {
+    let (sender, receiver) = channel();
+
+    let port = get_unused_tcp_port();
+    thread::spawn(move || {
+        run_tcp_server(
+            "127.0.0.1",
+            port,
+            container_create_without_spec_hash_handler,
+            &sender,
+        );
+    });
+
+    // wait for server to get ready
+    receiver.recv().unwrap();
+
+    // edgeAgent creating a module must not be able to pass for the watchdog
+    let mut labels = HashMap::new();
+    labels.insert("foo".to_string(), "bar".to_string());
+    labels.insert(
+        "net.azure-devices.edge.spec-hash".to_string(),
+        "abc123".to_string(),
+    );
+    let create_options = ContainerCreateBody::new().with_labels(labels);
+    let module_config = ModuleSpec::new(
+        "m1",
+        "docker",
+        DockerConfig::new("nginx:latest", create_options, None).unwrap(),
+        HashMap::new(),
+    ).unwrap();
+
+    let mut core = Core::new().unwrap();
+    let mri = DockerModuleRuntime::new(
+        &Url::parse(&format!("http://localhost:{}/", port)).unwrap(),
+        &core.handle(),
+    ).unwrap();
+
+    core.run(mri.create(module_config)).unwrap();
+}
+
+fn json_response(response: &str) -> Box<Future<Item = Response, Error = HyperError>> {
+    Box::new(future::ok(
+        Response::new()
+            .with_header(ContentLength(response.len() as u64))
+            .with_header(ContentType::json())
+            .with_body(response.to_string())
+            .with_status(StatusCode::Ok),
+    ))
+}
+
 fn container_start_handler(req: Request) -> Box<Future<Item = Response, Error = HyperError>> {
+    if req.path() == "/containers/json" {
+        return json_response("[]");
+    }
+
     assert_eq!(req.method(), &Method::Post);
     assert_eq!(req.path(), "/containers/m1/start");
 
@@ -395,20 +701,83 @@ fn container_start_succeeds() {
     core.run(task).unwrap();
 }
 
-fn container_stop_handler(req: Request) -> Box<Future<Item = Response, Error = HyperError>> {
-    assert_eq!(req.method(), &Method::Post);
-    assert_eq!(req.path(), "/containers/m1/stop");
+fn container_summary(name: &str, depends_on: Option<&str>) -> ContainerSummary {
+    let mut labels = HashMap::new();
+    if let Some(depends_on) = depends_on {
+        labels.insert(
+            "net.azure-devices.edge.depends-on".to_string(),
+            depends_on.to_string(),
+        );
+    }
+    container_summary_with_labels(name, labels)
+}
 
-    Box::new(future::ok(Response::new().with_status(StatusCode::Ok)))
+fn container_summary_with_labels(name: &str, labels: HashMap<String, String>) -> ContainerSummary {
+    ContainerSummary::new(
+        name.to_string(),
+        vec![format!("/{}", name)],
+        "nginx:latest".to_string(),
+        "img1".to_string(),
+        "".to_string(),
+        10,
+        vec![],
+        10,
+        10,
+        labels,
+        "".to_string(),
+        "".to_string(),
+        ContainerHostConfig::new(""),
+        ContainerNetworkSettings::new(HashMap::new()),
+        vec![],
+    )
+}
+
+// m1 depends on m2, which is running once it has been started
+fn dependency_handler(
+    requests: Arc<RwLock<Vec<String>>>,
+    m2_running: bool,
+) -> impl Fn(Request) -> Box<Future<Item = Response, Error = HyperError>> {
+    dependency_handler_with_labels(requests, m2_running, HashMap::new())
+}
+
+fn dependency_handler_with_labels(
+    requests: Arc<RwLock<Vec<String>>>,
+    m2_running: bool,
+    m2_labels: HashMap<String, String>,
+) -> impl Fn(Request) -> Box<Future<Item = Response, Error = HyperError>> {
+    move |req: Request| {
+        requests
+            .write()
+            .unwrap()
+            .push(format!("{} {}", req.method(), req.path()));
+        match req.path() {
+            "/containers/json" => {
+                let modules = vec![
+                    container_summary("m1", Some("m2")),
+                    container_summary_with_labels("m2", m2_labels.clone()),
+                ];
+                json_response(&serde_json::to_string(&modules).unwrap())
+            }
+            "/containers/m2/json" => json_response(
+                &json!({
+                    "Id": "m2",
+                    "State": { "Status": "running", "Running": m2_running }
+                }).to_string(),
+            ),
+            _ => Box::new(future::ok(Response::new().with_status(StatusCode::NoContent))),
+        }
+    }
 }
 
 #[test]
-fn container_stop_succeeds() {
+fn container_start_starts_dependencies_first() {
     let (sender, receiver) = channel();
+    let requests = Arc::new(RwLock::new(Vec::new()));
+    let handler = dependency_handler(requests.clone(), true);
 
     let port = get_unused_tcp_port();
     thread::spawn(move || {
-        run_tcp_server("127.0.0.1", port, container_stop_handler, &sender);
+        run_tcp_server("127.0.0.1", port, handler, &sender);
     });
 
     // wait for server to get ready
@@ -420,32 +789,29 @@ fn container_stop_succeeds() {
         &core.handle(),
     ).unwrap();
 
-    let task = mri.stop("m1", None);
+    let task = mri.start("m1");
     core.run(task).unwrap();
-}
-
-fn container_stop_with_timeout_handler(
-    req: Request,
-) -> Box<Future<Item = Response, Error = HyperError>> {
-    assert_eq!(req.method(), &Method::Post);
-    assert_eq!(req.path(), "/containers/m1/stop");
-    assert_eq!(req.query().unwrap(), "t=600");
 
-    Box::new(future::ok(Response::new().with_status(StatusCode::Ok)))
+    assert_eq!(
+        vec![
+            "GET /containers/json",
+            "POST /containers/m2/start",
+            "GET /containers/m2/json",
+            "POST /containers/m1/start",
+        ],
+        *requests.read().unwrap()
+    );
 }
 
 #[test]
-fn container_stop_with_timeout_succeeds() {
+fn container_start_fails_when_dependency_is_not_ready() {
     let (sender, receiver) = channel();
+    let requests = Arc::new(RwLock::new(Vec::new()));
+    let handler = dependency_handler(requests.clone(), false);
 
     let port = get_unused_tcp_port();
     thread::spawn(move || {
-        run_tcp_server(
-            "127.0.0.1",
-            port,
-            container_stop_with_timeout_handler,
-            &sender,
-        );
+        run_tcp_server("127.0.0.1", port, handler, &sender);
     });
 
     // wait for server to get ready
@@ -455,48 +821,509 @@ fn container_stop_with_timeout_succeeds() {
     let mri = DockerModuleRuntime::new(
         &Url::parse(&format!("http://localhost:{}/", port)).unwrap(),
         &core.handle(),
-    ).unwrap();
+    ).unwrap()
+    .with_dependency_timeout(Duration::from_secs(0));
 
-    let task = mri.stop("m1", Some(Duration::from_secs(600)));
-    core.run(task).unwrap();
+    let task = mri.start("m1");
+    let err = core.run(task).unwrap_err();
+    match *err.kind() {
+        ErrorKind::DependencyNotReady(ref name, ref dependency, timeout) => {
+            assert_eq!("m1", name);
+            assert_eq!("m2", dependency);
+            assert_eq!(0, timeout);
+        }
+        _ => panic!("expected a dependency that is not ready"),
+    }
+    assert!(!requests
+        .read()
+        .unwrap()
+        .contains(&"POST /containers/m1/start".to_string()));
 }
 
-fn container_remove_handler(req: Request) -> Box<Future<Item = Response, Error = HyperError>> {
-    assert_eq!(req.method(), &Method::Delete);
-    assert_eq!(req.path(), "/containers/m1");
+#[test]
+fn container_start_does_not_start_dependency_meant_to_be_stopped() {
+    let (sender, receiver) = channel();
+    let requests = Arc::new(RwLock::new(Vec::new()));
+    let mut labels = HashMap::new();
+    labels.insert(
+        "net.azure-devices.edge.desired-status".to_string(),
+        "stopped".to_string(),
+    );
+    let handler = dependency_handler_with_labels(requests.clone(), false, labels);
 
-    Box::new(future::ok(Response::new().with_status(StatusCode::Ok)))
+    let port = get_unused_tcp_port();
+    thread::spawn(move || {
+        run_tcp_server("127.0.0.1", port, handler, &sender);
+    });
+
+    // wait for server to get ready
+    receiver.recv().unwrap();
+
+    let mut core = Core::new().unwrap();
+    let mri = DockerModuleRuntime::new(
+        &Url::parse(&format!("http://localhost:{}/", port)).unwrap(),
+        &core.handle(),
+    ).unwrap();
+
+    let task = mri.start("m1");
+    let err = core.run(task).unwrap_err();
+    match *err.kind() {
+        ErrorKind::DependencyNotReady(ref name, ref dependency, _) => {
+            assert_eq!("m1", name);
+            assert_eq!("m2", dependency);
+        }
+        _ => panic!("expected a dependency that is not ready"),
+    }
+    assert_eq!(
+        vec!["GET /containers/json", "GET /containers/m2/json"],
+        *requests.read().unwrap()
+    );
 }
 
 #[test]
-fn container_remove_succeeds() {
+fn container_restart_starts_dependencies_first() {
     let (sender, receiver) = channel();
+    let requests = Arc::new(RwLock::new(Vec::new()));
+    let handler = dependency_handler(requests.clone(), true);
 
     let port = get_unused_tcp_port();
     thread::spawn(move || {
-        run_tcp_server("127.0.0.1", port, container_remove_handler, &sender);
+        run_tcp_server("127.0.0.1", port, handler, &sender);
     });
 
     // wait for server to get ready
     receiver.recv().unwrap();
 
     let mut core = Core::new().unwrap();
-    let mut mri = DockerModuleRuntime::new(
+    let mri = DockerModuleRuntime::new(
         &Url::parse(&format!("http://localhost:{}/", port)).unwrap(),
         &core.handle(),
     ).unwrap();
 
-    let task = ModuleRuntime::remove(&mut mri, "m1");
+    let task = mri.restart("m1");
     core.run(task).unwrap();
+
+    assert_eq!(
+        vec![
+            "GET /containers/json",
+            "POST /containers/m2/start",
+            "GET /containers/m2/json",
+            "POST /containers/m1/restart",
+        ],
+        *requests.read().unwrap()
+    );
 }
 
-fn container_list_handler(req: Request) -> Box<Future<Item = Response, Error = HyperError>> {
-    assert_eq!(req.method(), &Method::Get);
-    assert_eq!(req.path(), "/containers/json");
+#[test]
+fn remove_all_removes_dependents_first() {
+    let (sender, receiver) = channel();
+    let requests = Arc::new(RwLock::new(Vec::new()));
+    let handler = dependency_handler(requests.clone(), true);
 
-    let query_map: HashMap<String, String> = parse_query(req.query().unwrap().as_bytes())
-        .into_owned()
-        .collect();
+    let port = get_unused_tcp_port();
+    thread::spawn(move || {
+        run_tcp_server("127.0.0.1", port, handler, &sender);
+    });
+
+    // wait for server to get ready
+    receiver.recv().unwrap();
+
+    let mut core = Core::new().unwrap();
+    let mri = DockerModuleRuntime::new(
+        &Url::parse(&format!("http://localhost:{}/", port)).unwrap(),
+        &core.handle(),
+    ).unwrap();
+
+    let task = mri.remove_all();
+    core.run(task).unwrap();
+
+    assert_eq!(
+        vec![
+            "GET /containers/json",
+            "DELETE /containers/m1",
+            "DELETE /containers/m2",
+        ],
+        *requests.read().unwrap()
+    );
+}
+
+#[test]
+fn container_stop_stops_dependents_first() {
+    let (sender, receiver) = channel();
+    let requests = Arc::new(RwLock::new(Vec::new()));
+    let handler = dependency_handler(requests.clone(), true);
+
+    let port = get_unused_tcp_port();
+    thread::spawn(move || {
+        run_tcp_server("127.0.0.1", port, handler, &sender);
+    });
+
+    // wait for server to get ready
+    receiver.recv().unwrap();
+
+    let mut core = Core::new().unwrap();
+    let mri = DockerModuleRuntime::new(
+        &Url::parse(&format!("http://localhost:{}/", port)).unwrap(),
+        &core.handle(),
+    ).unwrap();
+
+    let task = mri.stop("m2", None);
+    core.run(task).unwrap();
+
+    assert_eq!(
+        vec![
+            "GET /containers/json",
+            "POST /containers/m1/stop",
+            "POST /containers/m2/stop",
+        ],
+        *requests.read().unwrap()
+    );
+}
+
+fn container_pause_handler(req: Request) -> Box<Future<Item = Response, Error = HyperError>> {
+    assert_eq!(req.method(), &Method::Post);
+    assert_eq!(req.path(), "/containers/m1/pause");
+
+    Box::new(future::ok(Response::new().with_status(StatusCode::NoContent)))
+}
+
+#[test]
+fn container_pause_succeeds() {
+    let (sender, receiver) = channel();
+
+    let port = get_unused_tcp_port();
+    thread::spawn(move || {
+        run_tcp_server("127.0.0.1", port, container_pause_handler, &sender);
+    });
+
+    // wait for server to get ready
+    receiver.recv().unwrap();
+
+    let mut core = Core::new().unwrap();
+    let mri = DockerModuleRuntime::new(
+        &Url::parse(&format!("http://localhost:{}/", port)).unwrap(),
+        &core.handle(),
+    ).unwrap();
+
+    let task = mri.pause("m1");
+    core.run(task).unwrap();
+}
+
+fn container_unpause_handler(req: Request) -> Box<Future<Item = Response, Error = HyperError>> {
+    assert_eq!(req.method(), &Method::Post);
+    assert_eq!(req.path(), "/containers/m1/unpause");
+
+    Box::new(future::ok(Response::new().with_status(StatusCode::NoContent)))
+}
+
+#[test]
+fn container_unpause_succeeds() {
+    let (sender, receiver) = channel();
+
+    let port = get_unused_tcp_port();
+    thread::spawn(move || {
+        run_tcp_server("127.0.0.1", port, container_unpause_handler, &sender);
+    });
+
+    // wait for server to get ready
+    receiver.recv().unwrap();
+
+    let mut core = Core::new().unwrap();
+    let mri = DockerModuleRuntime::new(
+        &Url::parse(&format!("http://localhost:{}/", port)).unwrap(),
+        &core.handle(),
+    ).unwrap();
+
+    let task = mri.unpause("m1");
+    core.run(task).unwrap();
+}
+
+fn container_stop_handler(req: Request) -> Box<Future<Item = Response, Error = HyperError>> {
+    if req.path() == "/containers/json" {
+        return json_response("[]");
+    }
+
+    assert_eq!(req.method(), &Method::Post);
+    assert_eq!(req.path(), "/containers/m1/stop");
+
+    Box::new(future::ok(Response::new().with_status(StatusCode::Ok)))
+}
+
+#[test]
+fn container_stop_succeeds() {
+    let (sender, receiver) = channel();
+
+    let port = get_unused_tcp_port();
+    thread::spawn(move || {
+        run_tcp_server("127.0.0.1", port, container_stop_handler, &sender);
+    });
+
+    // wait for server to get ready
+    receiver.recv().unwrap();
+
+    let mut core = Core::new().unwrap();
+    let mri = DockerModuleRuntime::new(
+        &Url::parse(&format!("http://localhost:{}/", port)).unwrap(),
+        &core.handle(),
+    ).unwrap();
+
+    let task = mri.stop("m1", None);
+    core.run(task).unwrap();
+}
+
+fn container_stop_with_timeout_handler(
+    req: Request,
+) -> Box<Future<Item = Response, Error = HyperError>> {
+    if req.path() == "/containers/json" {
+        return json_response("[]");
+    }
+
+    assert_eq!(req.method(), &Method::Post);
+    assert_eq!(req.path(), "/containers/m1/stop");
+    assert_eq!(req.query().unwrap(), "t=600");
+
+    Box::new(future::ok(Response::new().with_status(StatusCode::Ok)))
+}
+
+#[test]
+fn container_stop_with_timeout_succeeds() {
+    let (sender, receiver) = channel();
+
+    let port = get_unused_tcp_port();
+    thread::spawn(move || {
+        run_tcp_server(
+            "127.0.0.1",
+            port,
+            container_stop_with_timeout_handler,
+            &sender,
+        );
+    });
+
+    // wait for server to get ready
+    receiver.recv().unwrap();
+
+    let mut core = Core::new().unwrap();
+    let mri = DockerModuleRuntime::new(
+        &Url::parse(&format!("http://localhost:{}/", port)).unwrap(),
+        &core.handle(),
+    ).unwrap();
+
+    let task = mri.stop("m1", Some(Duration::from_secs(600)));
+    core.run(task).unwrap();
+}
+
+fn container_remove_handler(req: Request) -> Box<Future<Item = Response, Error = HyperError>> {
+    assert_eq!(req.method(), &Method::Delete);
+    assert_eq!(req.path(), "/containers/m1");
+
+    Box::new(future::ok(Response::new().with_status(StatusCode::Ok)))
+}
+
+#[test]
+fn container_remove_succeeds() {
+    let (sender, receiver) = channel();
+
+    let port = get_unused_tcp_port();
+    thread::spawn(move || {
+        run_tcp_server("127.0.0.1", port, container_remove_handler, &sender);
+    });
+
+    // wait for server to get ready
+    receiver.recv().unwrap();
+
+    let mut core = Core::new().unwrap();
+    let mut mri = DockerModuleRuntime::new(
+        &Url::parse(&format!("http://localhost:{}/", port)).unwrap(),
+        &core.handle(),
+    ).unwrap();
+
+    let task = ModuleRuntime::remove(&mut mri, "m1");
+    core.run(task).unwrap();
+}
+
+fn volume_json() -> serde_json::Value {
+    json!({
+        "Name": "m1-data",
+        "Driver": "local",
+        "Mountpoint": "/var/lib/docker/volumes/m1-data/_data",
+        "Labels": {
+            "net.azure-devices.edge.owner": "Microsoft.Azure.Devices.Edge.Agent",
+            "net.azure-devices.edge.module": "m1"
+        },
+        "Scope": "local",
+        "Options": {}
+    })
+}
+
+// m1 mounts the volume m1-data at /data
+fn volume_handler(
+    requests: Arc<RwLock<Vec<String>>>,
+) -> impl Fn(Request) -> Box<Future<Item = Response, Error = HyperError>> {
+    move |req: Request| {
+        let path = req.path().to_string();
+        requests
+            .write()
+            .unwrap()
+            .push(format!("{} {}", req.method(), path));
+        let query: HashMap<String, String> = req
+            .query()
+            .map(|query| parse_query(query.as_bytes()).into_owned().collect())
+            .unwrap_or_else(HashMap::new);
+
+        match path.as_str() {
+            "/containers/create" => json_response(r#"{"Id": "m1", "Warnings": []}"#),
+            "/volumes/create" => Box::new(req.body().concat2().map(|body| {
+                let config: VolumeConfig = serde_json::from_slice(&body).unwrap();
+                assert_eq!(Some(&"m1-data".to_string()), config.name());
+                let labels = config.labels().unwrap();
+                assert_eq!("m1", labels["net.azure-devices.edge.module"]);
+                assert_eq!(
+                    "Microsoft.Azure.Devices.Edge.Agent",
+                    labels["net.azure-devices.edge.owner"]
+                );
+
+                let response = volume_json().to_string();
+                Response::new()
+                    .with_header(ContentLength(response.len() as u64))
+                    .with_header(ContentType::json())
+                    .with_body(response)
+                    .with_status(StatusCode::Created)
+            })),
+            "/containers/m1" => {
+                assert_eq!("true", query["v"]);
+                Box::new(future::ok(
+                    Response::new().with_status(StatusCode::NoContent),
+                ))
+            }
+            "/volumes" => {
+                assert!(query["filters"].contains("net.azure-devices.edge.owner"));
+                let volumes = json!({ "Volumes": [volume_json()], "Warnings": [] });
+                json_response(&volumes.to_string())
+            }
+            "/volumes/m1-data" => Box::new(future::ok(
+                Response::new().with_status(StatusCode::NoContent),
+            )),
+            "/containers/json" => {
+                assert!(query["filters"].contains("m1-data"));
+                let containers = vec![container_summary("m1", None)];
+                json_response(&serde_json::to_string(&containers).unwrap())
+            }
+            "/containers/m1/json" => json_response(
+                &json!({
+                    "Id": "m1",
+                    "Mounts": [{ "Type": "volume", "Name": "m1-data", "Destination": "/data" }]
+                }).to_string(),
+            ),
+            "/containers/m1/archive" => {
+                assert_eq!("/data", query["path"]);
+                Box::new(future::ok(
+                    Response::new()
+                        .with_body(b"archive".to_vec())
+                        .with_status(StatusCode::Ok),
+                ))
+            }
+            _ => panic!("unexpected request {}", path),
+        }
+    }
+}
+
+fn run_volume_test<F, R>(test: F) -> Vec<String>
+where
+    F: FnOnce(DockerModuleRuntime) -> R,
+    R: Future<Error = edgelet_docker::Error>,
+{
+    let (sender, receiver) = channel();
+    let requests = Arc::new(RwLock::new(Vec::new()));
+    let handler = volume_handler(requests.clone());
+
+    let port = get_unused_tcp_port();
+    thread::spawn(move || {
+        run_tcp_server("127.0.0.1", port, handler, &sender);
+    });
+
+    // wait for server to get ready
+    receiver.recv().unwrap();
+
+    let mut core = Core::new().unwrap();
+    let mri = DockerModuleRuntime::new(
+        &Url::parse(&format!("http://localhost:{}/", port)).unwrap(),
+        &core.handle(),
+    ).unwrap();
+
+    core.run(test(mri)).unwrap();
+    let requests = requests.read().unwrap();
+    requests.clone()
+}
+
+#[test]
+fn container_create_creates_named_volumes() {
+    let create_options =
+        ContainerCreateBody::new().with_host_config(HostConfig::new().with_binds(vec![
+            "m1-data:/data".to_string(),
+            "/var/log:/logs".to_string(),
+        ]));
+    let module_config = ModuleSpec::new(
+        "m1",
+        "docker",
+        DockerConfig::new("nginx:latest", create_options, None).unwrap(),
+        HashMap::new(),
+    ).unwrap();
+
+    let requests = run_volume_test(|mri| mri.create(module_config));
+    assert_eq!(
+        vec!["POST /volumes/create", "POST /containers/create"],
+        requests
+    );
+}
+
+#[test]
+fn container_remove_with_volumes_succeeds() {
+    let requests = run_volume_test(|mri| mri.remove_with_volumes("m1"));
+    assert_eq!(
+        vec![
+            "DELETE /containers/m1",
+            "GET /volumes",
+            "DELETE /volumes/m1-data",
+        ],
+        requests
+    );
+}
+
+#[test]
+fn volume_list_succeeds() {
+    run_volume_test(|mri| {
+        mri.list_volumes().map(|volumes| {
+            assert_eq!(1, volumes.len());
+            assert_eq!("m1-data", volumes[0].name());
+            assert_eq!(Some("m1"), volumes[0].module());
+        })
+    });
+}
+
+#[test]
+fn volume_backup_succeeds() {
+    let requests = run_volume_test(|mri| {
+        mri.backup_volume("m1-data")
+            .and_then(|archive| archive.concat2())
+            .map(|archive| assert_eq!(&b"archive"[..], archive.as_ref()))
+    });
+    assert_eq!(
+        vec![
+            "GET /containers/json",
+            "GET /containers/m1/json",
+            "GET /containers/m1/archive",
+        ],
+        requests
+    );
+}
+
+fn container_list_handler(req: Request) -> Box<Future<Item = Response, Error = HyperError>> {
+    assert_eq!(req.method(), &Method::Get);
+    assert_eq!(req.path(), "/containers/json");
+
+    let query_map: HashMap<String, String> = parse_query(req.query().unwrap().as_bytes())
+        .into_owned()
+        .collect();
     assert!(query_map.contains_key("filters"));
     assert_eq!(
         query_map.get("filters"),
@@ -507,82 +1334,408 @@ fn container_list_handler(req: Request) -> Box<Future<Item = Response, Error = H
         )
     );
 
-    let mut labels = HashMap::new();
-    labels.insert("l1".to_string(), "v1".to_string());
-    labels.insert("l2".to_string(), "v2".to_string());
-    labels.insert("l3".to_string(), "v3".to_string());
+    let mut labels = HashMap::new();
+    labels.insert("l1".to_string(), "v1".to_string());
+    labels.insert("l2".to_string(), "v2".to_string());
+    labels.insert("l3".to_string(), "v3".to_string());
+
+    let modules = vec![
+        ContainerSummary::new(
+            "m1".to_string(),
+            vec!["/m1".to_string()],
+            "nginx:latest".to_string(),
+            "img1".to_string(),
+            "".to_string(),
+            10,
+            vec![],
+            10,
+            10,
+            labels.clone(),
+            "".to_string(),
+            "".to_string(),
+            ContainerHostConfig::new(""),
+            ContainerNetworkSettings::new(HashMap::new()),
+            vec![],
+        ),
+        ContainerSummary::new(
+            "m2".to_string(),
+            vec!["/m2".to_string()],
+            "ubuntu:latest".to_string(),
+            "img2".to_string(),
+            "".to_string(),
+            10,
+            vec![],
+            10,
+            10,
+            labels.clone(),
+            "".to_string(),
+            "".to_string(),
+            ContainerHostConfig::new(""),
+            ContainerNetworkSettings::new(HashMap::new()),
+            vec![],
+        ),
+        ContainerSummary::new(
+            "m3".to_string(),
+            vec!["/m3".to_string()],
+            "mongo:latest".to_string(),
+            "img3".to_string(),
+            "".to_string(),
+            10,
+            vec![],
+            10,
+            10,
+            labels.clone(),
+            "".to_string(),
+            "".to_string(),
+            ContainerHostConfig::new(""),
+            ContainerNetworkSettings::new(HashMap::new()),
+            vec![],
+        ),
+    ];
+
+    let response = serde_json::to_string(&modules).unwrap();
+    Box::new(future::ok(
+        Response::new()
+            .with_header(ContentLength(response.len() as u64))
+            .with_header(ContentType::json())
+            .with_body(response)
+            .with_status(StatusCode::Ok),
+    ))
+}
+
+#[test]
+fn container_list_succeeds() {
+    let (sender, receiver) = channel();
+
+    let port = get_unused_tcp_port();
+    thread::spawn(move || {
+        run_tcp_server("127.0.0.1", port, container_list_handler, &sender);
+    });
+
+    // wait for server to get ready
+    receiver.recv().unwrap();
+
+    let mut core = Core::new().unwrap();
+    let mri = DockerModuleRuntime::new(
+        &Url::parse(&format!("http://localhost:{}/", port)).unwrap(),
+        &core.handle(),
+    ).unwrap();
+
+    let task = mri.list();
+    let modules = core.run(task).unwrap();
+
+    assert_eq!(3, modules.len());
+
+    assert_eq!("m1", modules[0].name());
+    assert_eq!("m2", modules[1].name());
+    assert_eq!("m3", modules[2].name());
+
+    assert_eq!("img1", modules[0].config().image_id().unwrap().as_str());
+    assert_eq!("img2", modules[1].config().image_id().unwrap().as_str());
+    assert_eq!("img3", modules[2].config().image_id().unwrap().as_str());
+
+    assert_eq!("nginx:latest", modules[0].config().image());
+    assert_eq!("ubuntu:latest", modules[1].config().image());
+    assert_eq!("mongo:latest", modules[2].config().image());
+
+    for i in 0..3 {
+        for j in 0..3 {
+            assert_eq!(
+                modules[i]
+                    .config()
+                    .create_options()
+                    .labels()
+                    .unwrap()
+                    .get(&format!("l{}", j + 1)),
+                Some(&format!("v{}", j + 1))
+            );
+        }
+    }
+}
+
+fn container_logs_handler(req: Request) -> Box<Future<Item = Response, Error = HyperError>> {
+    assert_eq!(req.method(), &Method::Get);
+    assert_eq!(req.path(), "/containers/mod1/logs");
+
+    let query_map: HashMap<String, String> = parse_query(req.query().unwrap().as_bytes())
+        .into_owned()
+        .collect();
+    assert!(query_map.contains_key("stdout"));
+    assert!(query_map.contains_key("stderr"));
+    assert!(query_map.contains_key("follow"));
+    assert!(query_map.contains_key("tail"));
+    assert_eq!("true", query_map["follow"]);
+    assert_eq!("all", query_map["tail"]);
+    assert_eq!("100000", query_map["since"]);
+    assert_eq!("200000", query_map["until"]);
+    assert_eq!("true", query_map["timestamps"]);
+
+    let body = vec![
+        0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x0d, 0x52, 0x6f, 0x73, 0x65, 0x73, 0x20, 0x61,
+        0x72, 0x65, 0x20, 0x72, 0x65, 0x64, 0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x10, 0x76,
+        0x69, 0x6f, 0x6c, 0x65, 0x74, 0x73, 0x20, 0x61, 0x72, 0x65, 0x20, 0x62, 0x6c, 0x75, 0x65,
+    ];
+
+    Box::new(future::ok(
+        Response::new().with_body(body).with_status(StatusCode::Ok),
+    ))
+}
+
+#[test]
+fn container_logs_succeeds() {
+    let (sender, receiver) = channel();
+
+    let port = get_unused_tcp_port();
+    thread::spawn(move || {
+        run_tcp_server("127.0.0.1", port, container_logs_handler, &sender);
+    });
+
+    // wait for server to get ready
+    receiver.recv().unwrap();
+
+    let mut core = Core::new().unwrap();
+    let mri = DockerModuleRuntime::new(
+        &Url::parse(&format!("http://localhost:{}/", port)).unwrap(),
+        &core.handle(),
+    ).unwrap();
+
+    let options = LogOptions::new()
+        .with_follow(true)
+        .with_tail(LogTail::All)
+        .with_since(100_000)
+        .with_until(200_000)
+        .with_timestamps(true);
+    let task = mri.logs("mod1", &options);
+    let logs = core.run(task).unwrap();
+
+    let expected_body = [
+        0x01u8, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x0d, 0x52, 0x6f, 0x73, 0x65, 0x73, 0x20, 0x61,
+        0x72, 0x65, 0x20, 0x72, 0x65, 0x64, 0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x10, 0x76,
+        0x69, 0x6f, 0x6c, 0x65, 0x74, 0x73, 0x20, 0x61, 0x72, 0x65, 0x20, 0x62, 0x6c, 0x75, 0x65,
+    ];
+
+    let assert = logs.concat2().and_then(|b| {
+        assert_eq!(&expected_body[..], b.as_ref());
+        Ok(())
+    });
+    core.run(assert).unwrap();
+}
+
+fn container_exec_handler(req: Request) -> Box<Future<Item = Response, Error = HyperError>> {
+    assert_eq!(req.method(), &Method::Post);
+
+    if req.path() == "/containers/mod1/exec" {
+        let response = json!({ "Id": "exec1" }).to_string();
+        Box::new(req.body().concat2().and_then(|body| {
+            let config: ExecConfig = serde_json::from_slice(body.as_ref()).unwrap();
+            assert_eq!(
+                &vec!["ls".to_string(), "-l".to_string()],
+                config.cmd().unwrap()
+            );
+            assert_eq!("root", config.user().unwrap());
+            assert_eq!(Some(&true), config.attach_stdout());
+            assert_eq!(Some(&true), config.attach_stderr());
+
+            Ok(Response::new()
+                .with_header(ContentLength(response.len() as u64))
+                .with_header(ContentType::json())
+                .with_body(response)
+                .with_status(StatusCode::Created))
+        }))
+    } else {
+        assert_eq!(req.path(), "/exec/exec1/start");
+        let body = vec![
+            0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x05, 0x66, 0x69, 0x6c, 0x65, 0x0a,
+        ];
+        Box::new(future::ok(
+            Response::new().with_body(body).with_status(StatusCode::Ok),
+        ))
+    }
+}
+
+#[test]
+fn container_exec_succeeds() {
+    let (sender, receiver) = channel();
+
+    let port = get_unused_tcp_port();
+    thread::spawn(move || {
+        run_tcp_server("127.0.0.1", port, container_exec_handler, &sender);
+    });
+
+    // wait for server to get ready
+    receiver.recv().unwrap();
+
+    let mut core = Core::new().unwrap();
+    let mri = DockerModuleRuntime::new(
+        &Url::parse(&format!("http://localhost:{}/", port)).unwrap(),
+        &core.handle(),
+    ).unwrap();
+
+    let options = ExecOptions::new(vec!["ls".to_string(), "-l".to_string()])
+        .with_user(Some("root".to_string()));
+    let task = mri.exec("mod1", &options);
+    let output = core.run(task).unwrap();
+
+    let expected_body = [
+        0x01u8, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x05, 0x66, 0x69, 0x6c, 0x65, 0x0a,
+    ];
+
+    let assert = output.concat2().and_then(|b| {
+        assert_eq!(&expected_body[..], b.as_ref());
+        Ok(())
+    });
+    core.run(assert).unwrap();
+}
+
+fn container_archive_handler(req: Request) -> Box<Future<Item = Response, Error = HyperError>> {
+    assert_eq!(req.method(), &Method::Get);
+    assert_eq!(req.path(), "/containers/mod1/archive");
+
+    let query_map: HashMap<String, String> = parse_query(req.query().unwrap().as_bytes())
+        .into_owned()
+        .collect();
+    assert_eq!("/var/log/dump", query_map["path"]);
+
+    Box::new(future::ok(
+        Response::new()
+            .with_body(b"archive".to_vec())
+            .with_status(StatusCode::Ok),
+    ))
+}
+
+#[test]
+fn container_get_archive_succeeds() {
+    let (sender, receiver) = channel();
+
+    let port = get_unused_tcp_port();
+    thread::spawn(move || {
+        run_tcp_server("127.0.0.1", port, container_archive_handler, &sender);
+    });
+
+    // wait for server to get ready
+    receiver.recv().unwrap();
+
+    let mut core = Core::new().unwrap();
+    let mri = DockerModuleRuntime::new(
+        &Url::parse(&format!("http://localhost:{}/", port)).unwrap(),
+        &core.handle(),
+    ).unwrap();
+
+    let task = mri.get_archive("mod1", "/var/log/dump");
+    let archive = core.run(task).unwrap();
+
+    let assert = archive.concat2().and_then(|b| {
+        assert_eq!(&b"archive"[..], b.as_ref());
+        Ok(())
+    });
+    core.run(assert).unwrap();
+}
+
+fn container_put_archive_handler(
+    req: Request,
+) -> Box<Future<Item = Response, Error = HyperError>> {
+    assert_eq!(req.method(), &Method::Put);
+    assert_eq!(req.path(), "/containers/mod1/archive");
+    assert_eq!(
+        "application/x-tar",
+        req.headers().get::<ContentType>().unwrap().to_string()
+    );
+
+    let query_map: HashMap<String, String> = parse_query(req.query().unwrap().as_bytes())
+        .into_owned()
+        .collect();
+    assert_eq!("/etc/calibration", query_map["path"]);
+    assert_eq!("true", query_map["noOverwriteDirNonDir"]);
+    // the archive is streamed, its length is not known upfront
+    assert!(req.headers().get::<ContentLength>().is_none());
+
+    Box::new(req.body().concat2().and_then(|body| {
+        assert_eq!(&b"archive"[..], body.as_ref());
+        Ok(Response::new().with_status(StatusCode::Ok))
+    }))
+}
+
+#[test]
+fn container_put_archive_succeeds() {
+    let (sender, receiver) = channel();
+
+    let port = get_unused_tcp_port();
+    thread::spawn(move || {
+        run_tcp_server("127.0.0.1", port, container_put_archive_handler, &sender);
+    });
+
+    // wait for server to get ready
+    receiver.recv().unwrap();
+
+    let mut core = Core::new().unwrap();
+    let mri = DockerModuleRuntime::new(
+        &Url::parse(&format!("http://localhost:{}/", port)).unwrap(),
+        &core.handle(),
+    ).unwrap();
+
+    let archive = stream::iter_ok(vec![b"arch".to_vec(), b"ive".to_vec()]);
+    let task = mri.put_archive("mod1", "/etc/calibration", Box::new(archive));
+    core.run(task).unwrap();
+}
+
+fn image_load_handler(req: Request) -> Box<Future<Item = Response, Error = HyperError>> {
+    assert_eq!(req.method(), &Method::Post);
+    assert_eq!(req.path(), "/images/load");
+    assert_eq!(
+        "application/x-tar",
+        req.headers().get::<ContentType>().unwrap().to_string()
+    );
+    // the archive is streamed, its length is not known upfront
+    assert!(req.headers().get::<ContentLength>().is_none());
+
+    Box::new(req.body().concat2().and_then(|body| {
+        assert_eq!(&b"images"[..], body.as_ref());
+        Ok(Response::new().with_status(StatusCode::Ok))
+    }))
+}
+
+#[test]
+fn image_load_succeeds() {
+    let (sender, receiver) = channel();
+
+    let port = get_unused_tcp_port();
+    thread::spawn(move || {
+        run_tcp_server("127.0.0.1", port, image_load_handler, &sender);
+    });
+
+    // wait for server to get ready
+    receiver.recv().unwrap();
 
-    let modules = vec![
-        ContainerSummary::new(
-            "m1".to_string(),
-            vec!["/m1".to_string()],
-            "nginx:latest".to_string(),
-            "img1".to_string(),
-            "".to_string(),
-            10,
-            vec![],
-            10,
-            10,
-            labels.clone(),
-            "".to_string(),
-            "".to_string(),
-            ContainerHostConfig::new(""),
-            ContainerNetworkSettings::new(HashMap::new()),
-            vec![],
-        ),
-        ContainerSummary::new(
-            "m2".to_string(),
-            vec!["/m2".to_string()],
-            "ubuntu:latest".to_string(),
-            "img2".to_string(),
-            "".to_string(),
-            10,
-            vec![],
-            10,
-            10,
-            labels.clone(),
-            "".to_string(),
-            "".to_string(),
-            ContainerHostConfig::new(""),
-            ContainerNetworkSettings::new(HashMap::new()),
-            vec![],
-        ),
-        ContainerSummary::new(
-            "m3".to_string(),
-            vec!["/m3".to_string()],
-            "mongo:latest".to_string(),
-            "img3".to_string(),
-            "".to_string(),
-            10,
-            vec![],
-            10,
-            10,
-            labels.clone(),
-            "".to_string(),
-            "".to_string(),
-            ContainerHostConfig::new(""),
-            ContainerNetworkSettings::new(HashMap::new()),
-            vec![],
-        ),
-    ];
+    let mut core = Core::new().unwrap();
+    let mri = DockerModuleRuntime::new(
+        &Url::parse(&format!("http://localhost:{}/", port)).unwrap(),
+        &core.handle(),
+    ).unwrap();
+
+    let archive = stream::iter_ok(vec![b"ima".to_vec(), b"ges".to_vec()]);
+    let task = mri.load_images(Box::new(archive));
+    core.run(task).unwrap();
+}
+
+#[cfg_attr(feature = "cargo-clippy", allow(needless_pass_by_value))]
+fn image_save_handler(req: Request) -> Box<Future<Item = Response, Error = HyperError>> {
+    assert_eq!(req.method(), &Method::Get);
+    assert_eq!(req.path(), format!("/images/{}/get", IMAGE_NAME));
 
-    let response = serde_json::to_string(&modules).unwrap();
     Box::new(future::ok(
         Response::new()
-            .with_header(ContentLength(response.len() as u64))
-            .with_header(ContentType::json())
-            .with_body(response)
+            .with_body(b"image".to_vec())
             .with_status(StatusCode::Ok),
     ))
 }
 
 #[test]
-fn container_list_succeeds() {
+fn image_save_succeeds() {
     let (sender, receiver) = channel();
 
     let port = get_unused_tcp_port();
     thread::spawn(move || {
-        run_tcp_server("127.0.0.1", port, container_list_handler, &sender);
+        run_tcp_server("127.0.0.1", port, image_save_handler, &sender);
     });
 
     // wait for server to get ready
@@ -594,70 +1747,121 @@ fn container_list_succeeds() {
         &core.handle(),
     ).unwrap();
 
-    let task = mri.list();
-    let modules = core.run(task).unwrap();
+    let task = mri.save_image(IMAGE_NAME);
+    let archive = core.run(task).unwrap();
 
-    assert_eq!(3, modules.len());
+    let assert = archive.concat2().and_then(|b| {
+        assert_eq!(&b"image"[..], b.as_ref());
+        Ok(())
+    });
+    core.run(assert).unwrap();
+}
 
-    assert_eq!("m1", modules[0].name());
-    assert_eq!("m2", modules[1].name());
-    assert_eq!("m3", modules[2].name());
+#[cfg_attr(feature = "cargo-clippy", allow(needless_pass_by_value))]
+fn local_image_handler(req: Request) -> Box<Future<Item = Response, Error = HyperError>> {
+    // the image is never pulled
+    assert_eq!(req.path(), format!("/images/{}/json", IMAGE_NAME));
+    Box::new(future::ok(image_inspect_response(IMAGE_ID, &[])))
+}
 
-    assert_eq!("img1", modules[0].config().image_id().unwrap().as_str());
-    assert_eq!("img2", modules[1].config().image_id().unwrap().as_str());
-    assert_eq!("img3", modules[2].config().image_id().unwrap().as_str());
+#[test]
+fn image_pull_never_uses_local_image() {
+    let (sender, receiver) = channel();
 
-    assert_eq!("nginx:latest", modules[0].config().image());
-    assert_eq!("ubuntu:latest", modules[1].config().image());
-    assert_eq!("mongo:latest", modules[2].config().image());
+    let port = get_unused_tcp_port();
+    thread::spawn(move || {
+        run_tcp_server("127.0.0.1", port, local_image_handler, &sender);
+    });
 
-    for i in 0..3 {
-        for j in 0..3 {
-            assert_eq!(
-                modules[i]
-                    .config()
-                    .create_options()
-                    .labels()
-                    .unwrap()
-                    .get(&format!("l{}", j + 1)),
-                Some(&format!("v{}", j + 1))
-            );
-        }
+    // wait for server to get ready
+    receiver.recv().unwrap();
+
+    let mut core = Core::new().unwrap();
+    let mri = DockerModuleRuntime::new(
+        &Url::parse(&format!("http://localhost:{}/", port)).unwrap(),
+        &core.handle(),
+    ).unwrap();
+
+    let config = DockerConfig::new(IMAGE_NAME, ContainerCreateBody::new(), None)
+        .unwrap()
+        .with_image_id(IMAGE_ID.to_string())
+        .with_pull_policy(ImagePullPolicy::Never);
+
+    let task = mri.pull(&config);
+    core.run(task).unwrap();
+}
+
+#[cfg_attr(feature = "cargo-clippy", allow(needless_pass_by_value))]
+fn missing_image_handler(req: Request) -> Box<Future<Item = Response, Error = HyperError>> {
+    assert_eq!(req.path(), format!("/images/{}/json", IMAGE_NAME));
+    let response = json!({ "message": "No such image" }).to_string();
+    Box::new(future::ok(
+        Response::new()
+            .with_header(ContentLength(response.len() as u64))
+            .with_header(ContentType::json())
+            .with_body(response)
+            .with_status(StatusCode::NotFound),
+    ))
+}
+
+#[test]
+fn image_pull_never_fails_without_local_image() {
+    let (sender, receiver) = channel();
+
+    let port = get_unused_tcp_port();
+    thread::spawn(move || {
+        run_tcp_server("127.0.0.1", port, missing_image_handler, &sender);
+    });
+
+    // wait for server to get ready
+    receiver.recv().unwrap();
+
+    let mut core = Core::new().unwrap();
+    let mri = DockerModuleRuntime::new(
+        &Url::parse(&format!("http://localhost:{}/", port)).unwrap(),
+        &core.handle(),
+    ).unwrap();
+
+    let config = DockerConfig::new(IMAGE_NAME, ContainerCreateBody::new(), None)
+        .unwrap()
+        .with_pull_policy(ImagePullPolicy::Never);
+
+    let task = mri.pull(&config);
+    let err = core.run(task).unwrap_err();
+    match *err.kind() {
+        ErrorKind::LocalImageNotFound(ref image) => assert_eq!(IMAGE_NAME, image),
+        _ => panic!("expected a missing local image"),
     }
 }
 
-fn container_logs_handler(req: Request) -> Box<Future<Item = Response, Error = HyperError>> {
+fn container_stats_handler(req: Request) -> Box<Future<Item = Response, Error = HyperError>> {
     assert_eq!(req.method(), &Method::Get);
-    assert_eq!(req.path(), "/containers/mod1/logs");
+    assert_eq!(req.path(), "/containers/mod1/stats");
 
     let query_map: HashMap<String, String> = parse_query(req.query().unwrap().as_bytes())
         .into_owned()
         .collect();
-    assert!(query_map.contains_key("stdout"));
-    assert!(query_map.contains_key("stderr"));
-    assert!(query_map.contains_key("follow"));
-    assert!(query_map.contains_key("tail"));
-    assert_eq!("true", query_map["follow"]);
-    assert_eq!("all", query_map["tail"]);
+    assert_eq!("true", query_map["stream"]);
 
-    let body = vec![
-        0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x0d, 0x52, 0x6f, 0x73, 0x65, 0x73, 0x20, 0x61,
-        0x72, 0x65, 0x20, 0x72, 0x65, 0x64, 0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x10, 0x76,
-        0x69, 0x6f, 0x6c, 0x65, 0x74, 0x73, 0x20, 0x61, 0x72, 0x65, 0x20, 0x62, 0x6c, 0x75, 0x65,
-    ];
+    let body = r#"{"read":"2018-08-01T22:57:31Z","memory_stats":{"usage":1024,"limit":4096},"networks":{"eth0":{"rx_bytes":10,"tx_bytes":20}}}
+{"read":"2018-08-01T22:57:32Z","memory_stats":{"usage":2048,"limit":4096},"networks":{"eth0":{"rx_bytes":30,"tx_bytes":40}}}
+"#;
 
     Box::new(future::ok(
-        Response::new().with_body(body).with_status(StatusCode::Ok),
+        Response::new()
+            .with_header(ContentType::json())
+            .with_body(body)
+            .with_status(StatusCode::Ok),
     ))
 }
 
 #[test]
-fn container_logs_succeeds() {
+fn container_stats_succeeds() {
     let (sender, receiver) = channel();
 
     let port = get_unused_tcp_port();
     thread::spawn(move || {
-        run_tcp_server("127.0.0.1", port, container_logs_handler, &sender);
+        run_tcp_server("127.0.0.1", port, container_stats_handler, &sender);
     });
 
     // wait for server to get ready
@@ -669,21 +1873,77 @@ fn container_logs_succeeds() {
         &core.handle(),
     ).unwrap();
 
-    let options = LogOptions::new().with_follow(true).with_tail(LogTail::All);
-    let task = mri.logs("mod1", &options);
-    let logs = core.run(task).unwrap();
+    let options = StatsOptions::new().with_stream(true);
+    let task = mri.stats("mod1", &options).and_then(|stats| stats.collect());
+    let samples = core.run(task).unwrap();
 
-    let expected_body = [
-        0x01u8, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x0d, 0x52, 0x6f, 0x73, 0x65, 0x73, 0x20, 0x61,
-        0x72, 0x65, 0x20, 0x72, 0x65, 0x64, 0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x10, 0x76,
-        0x69, 0x6f, 0x6c, 0x65, 0x74, 0x73, 0x20, 0x61, 0x72, 0x65, 0x20, 0x62, 0x6c, 0x75, 0x65,
-    ];
+    assert_eq!(2, samples.len());
+    assert_eq!(1024, samples[0].memory_usage());
+    assert_eq!(4096, samples[0].memory_limit());
+    assert_eq!(10, samples[0].network_rx_bytes());
+    assert_eq!(2048, samples[1].memory_usage());
+    assert_eq!(40, samples[1].network_tx_bytes());
+}
 
-    let assert = logs.concat2().and_then(|b| {
-        assert_eq!(&expected_body[..], b.as_ref());
-        Ok(())
+fn system_events_handler(req: Request) -> Box<Future<Item = Response, Error = HyperError>> {
+    assert_eq!(req.method(), &Method::Get);
+    assert_eq!(req.path(), "/events");
+
+    let query_map: HashMap<String, String> = parse_query(req.query().unwrap().as_bytes())
+        .into_owned()
+        .collect();
+    assert_eq!("1533168000", query_map["since"]);
+    assert_eq!("", query_map["until"]);
+
+    let filters: HashMap<String, Vec<String>> =
+        serde_json::from_str(&query_map["filters"]).unwrap();
+    assert_eq!(vec!["container".to_string()], filters["type"]);
+    assert_eq!(
+        vec!["net.azure-devices.edge.owner=Microsoft.Azure.Devices.Edge.Agent".to_string()],
+        filters["label"]
+    );
+    assert!(filters["event"].contains(&"die".to_string()));
+    assert!(filters["event"].contains(&"health_status".to_string()));
+
+    let body = r#"{"Type":"container","Action":"start","Actor":{"ID":"abc","Attributes":{"name":"edgeHub"}},"time":1533168001}
+{"Type":"container","Action":"die","Actor":{"ID":"abc","Attributes":{"name":"edgeHub","exitCode":"1"}},"time":1533168002}
+"#;
+
+    Box::new(future::ok(
+        Response::new()
+            .with_header(ContentType::json())
+            .with_body(body)
+            .with_status(StatusCode::Ok),
+    ))
+}
+
+#[test]
+fn system_events_succeeds() {
+    let (sender, receiver) = channel();
+
+    let port = get_unused_tcp_port();
+    thread::spawn(move || {
+        run_tcp_server("127.0.0.1", port, system_events_handler, &sender);
     });
-    core.run(assert).unwrap();
+
+    // wait for server to get ready
+    receiver.recv().unwrap();
+
+    let mut core = Core::new().unwrap();
+    let mri = DockerModuleRuntime::new(
+        &Url::parse(&format!("http://localhost:{}/", port)).unwrap(),
+        &core.handle(),
+    ).unwrap();
+
+    let options = EventOptions::new().with_since(1_533_168_000);
+    let task = mri.events(&options).and_then(|events| events.collect());
+    let events = core.run(task).unwrap();
+
+    assert_eq!(2, events.len());
+    assert_eq!("edgeHub", events[0].name());
+    assert_eq!(ModuleAction::Start, *events[0].action());
+    assert_eq!(ModuleAction::Die, *events[1].action());
+    assert_eq!(Some(&1), events[1].exit_code());
 }
 
 #[test]
@@ -874,6 +2134,122 @@ fn runtime_init_network_exist_do_not_create() {
     assert_eq!(false, *create_got_called_lock_cloned.read().unwrap());
 }
 
+// Serves a network on 172.18.0.0/16 that `containers` use.
+fn network_handler(
+    requests: Arc<RwLock<Vec<String>>>,
+    containers: serde_json::Value,
+) -> impl Fn(Request) -> Box<Future<Item = Response, Error = HyperError>> {
+    move |req: Request| {
+        let path = req.path().to_string();
+        requests
+            .write()
+            .unwrap()
+            .push(format!("{} {}", req.method(), path));
+        let network = json!({
+            "Name": "azure-iot-edge",
+            "Id": "8e3209d08ed5",
+            "Driver": "bridge",
+            "IPAM": {
+                "Driver": "default",
+                "Config": [{ "Subnet": "172.18.0.0/16", "Gateway": "172.18.0.1" }]
+            },
+            "Options": {}
+        });
+
+        match path.as_str() {
+            "/networks" => json_response(&json!([network]).to_string()),
+            "/containers/json" => {
+                let query = req.query().unwrap_or("");
+                assert!(query.contains("all=true"));
+                assert!(
+                    query.contains("filters=%7B%22network%22%3A%7B%228e3209d08ed5%22%3Atrue%7D%7D")
+                );
+                json_response(&containers.to_string())
+            }
+            "/networks/8e3209d08ed5" => Box::new(future::ok(
+                Response::new().with_status(StatusCode::NoContent),
+            )),
+            "/networks/create" => Box::new(req.body().concat2().map(|body| {
+                let config: NetworkConfig = serde_json::from_slice(&body).unwrap();
+                let ipam = &config.IPAM().and_then(|ipam| ipam.config()).unwrap()[0];
+                assert_eq!("172.30.0.0/16", ipam["Subnet"]);
+
+                let response = r#"{"Id": "12345", "Warnings": ""}"#;
+                Response::new()
+                    .with_header(ContentLength(response.len() as u64))
+                    .with_header(ContentType::json())
+                    .with_body(response)
+                    .with_status(StatusCode::Created)
+            })),
+            _ => panic!("Unexpected request {} {}", req.method(), path),
+        }
+    }
+}
+
+fn run_network_test(containers: serde_json::Value) -> Vec<String> {
+    let (sender, receiver) = channel();
+    let requests = Arc::new(RwLock::new(Vec::new()));
+    let handler = network_handler(requests.clone(), containers);
+
+    let port = get_unused_tcp_port();
+    thread::spawn(move || {
+        run_tcp_server("127.0.0.1", port, handler, &sender);
+    });
+
+    // wait for server to get ready
+    receiver.recv().unwrap();
+
+    let mut core = Core::new().unwrap();
+    let network = Network::new("azure-iot-edge".to_string())
+        .with_ipam(vec![IpamConfig::new("172.30.0.0/16".to_string())]);
+    let mri = DockerModuleRuntime::new(
+        &Url::parse(&format!("http://localhost:{}/", port)).unwrap(),
+        &core.handle(),
+    ).unwrap()
+    .with_network(network);
+
+    core.run(mri.init()).unwrap();
+    let requests = requests.read().unwrap();
+    requests.clone()
+}
+
+#[test]
+fn runtime_init_recreates_drifted_network() {
+    assert_eq!(
+        vec![
+            "GET /networks",
+            "GET /containers/json",
+            "DELETE /networks/8e3209d08ed5",
+            "POST /networks/create",
+        ],
+        run_network_test(json!([]))
+    );
+}
+
+#[test]
+fn runtime_init_keeps_drifted_network_in_use() {
+    // a stopped container is not attached to the network
+    let containers = json!([{
+        "Id": "3f2d9c8b",
+        "Names": ["/edgeHub"],
+        "Image": "mcr.microsoft.com/azureiotedge-hub:1.0",
+        "ImageID": "sha256:a1d9fa1c2cbc",
+        "Command": "",
+        "Created": 1_540_000_000,
+        "Ports": [],
+        "Labels": {},
+        "State": "exited",
+        "Status": "Exited (0) 5 minutes ago",
+        "HostConfig": { "NetworkMode": "default" },
+        "NetworkSettings": { "Networks": {} },
+        "Mounts": []
+    }]);
+    assert_eq!(
+        vec!["GET /networks", "GET /containers/json"],
+        run_network_test(containers)
+    );
+}
+
 #[test]
 fn runtime_system_info_succeed() {
     //arrange
@@ -8,14 +8,22 @@ use std::time::Duration;
 use edgelet_core::SystemInfo as CoreSystemInfo;
 use edgelet_core::*;
 use edgelet_docker::{self, DockerConfig};
-use edgelet_http::{UrlConnector, API_VERSION};
+use edgelet_http::{
+    stream_body, JsonLines, UrlConnector, API_VERSION, API_VERSION_2018_06_28,
+    SUPPORTED_API_VERSIONS,
+};
+use edgelet_process::{self, ProcessConfig};
 use futures::future::{self, FutureResult};
 use futures::prelude::*;
 use hyper::client::Client;
-use hyper::{Body, Chunk as HyperChunk};
+use hyper::{Body, Chunk as HyperChunk, StatusCode};
 use management::apis::client::APIClient;
 use management::apis::configuration::Configuration;
-use management::models::{Config, ModuleDetails as HttpModuleDetails};
+use management::apis::Error as MgmtError;
+use management::models::{
+    Config, ExecSpec, ModuleDetails as HttpModuleDetails, ModuleEvent as HttpModuleEvent,
+    ModuleStats as HttpModuleStats, Volume as HttpVolume, VolumeSpec,
+};
 use serde_json;
 use tokio_core::reactor::Handle;
 use url::Url;
@@ -24,6 +32,7 @@ use error::{Error, ErrorKind};
 
 pub struct ModuleClient {
     client: Rc<APIClient<UrlConnector>>,
+    api_version: &'static str,
 }
 
 impl ModuleClient {
@@ -43,9 +52,40 @@ impl ModuleClient {
 
         let module_client = ModuleClient {
             client: Rc::new(APIClient::new(configuration)),
+            api_version: API_VERSION,
         };
         Ok(module_client)
     }
+
+    /// Picks the newest version of the API that both the client and the
+    /// daemon serve. Daemons that do not serve `/versions` predate it and only
+    /// serve 2018-06-28. If the daemon cannot be reached the newest version is
+    /// kept, and the error is reported by the call that follows.
+    pub fn negotiate(self) -> Box<Future<Item = Self, Error = Error>> {
+        let versions = self.client.system_information_api().list_api_versions();
+        let result = versions.then(move |result| -> Result<_, Error> {
+            let api_version = match result {
+                Ok(versions) => SUPPORTED_API_VERSIONS
+                    .iter()
+                    .rev()
+                    .find(|&&v| versions.versions().iter().any(|served| served == v))
+                    .cloned()
+                    .unwrap_or(API_VERSION),
+                Err(MgmtError::ApiError(ref e))
+                    if e.code == StatusCode::NotFound || e.code == StatusCode::BadRequest =>
+                {
+                    API_VERSION_2018_06_28
+                }
+                Err(_) => API_VERSION,
+            };
+            debug!("Using version {} of the management API", api_version);
+            Ok(ModuleClient {
+                api_version,
+                ..self
+            })
+        });
+        Box::new(result)
+    }
 }
 
 fn get_base_path(url: &Url) -> &str {
@@ -59,6 +99,7 @@ impl Clone for ModuleClient {
     fn clone(&self) -> Self {
         ModuleClient {
             client: self.client.clone(),
+            api_version: self.api_version,
         }
     }
 }
@@ -71,10 +112,19 @@ pub struct ModuleConfig(String, Config);
 
 impl fmt::Display for ModuleConfig {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        if let edgelet_docker::MODULE_TYPE = self.0.as_ref() {
-            if let Ok(c) = serde_json::from_value::<DockerConfig>(self.1.settings().clone()) {
-                write!(f, "{}", c.image())?;
+        match self.0.as_ref() {
+            edgelet_docker::MODULE_TYPE => {
+                if let Ok(c) = serde_json::from_value::<DockerConfig>(self.1.settings().clone()) {
+                    write!(f, "{}", c.image())?;
+                }
             }
+            edgelet_process::MODULE_TYPE => {
+                if let Ok(c) = serde_json::from_value::<ProcessConfig>(self.1.settings().clone())
+                {
+                    write!(f, "{}", c.executable())?;
+                }
+            }
+            _ => (),
         }
         Ok(())
     }
@@ -114,13 +164,33 @@ fn runtime_status(details: &HttpModuleDetails) -> Result<ModuleRuntimeState, Err
         .exit_status()
         .and_then(|e| e.exit_time().parse().ok());
     let start_time = details.status().start_time().and_then(|s| s.parse().ok());
+    let health = details
+        .status()
+        .runtime_status()
+        .health()
+        .and_then(|h| ModuleHealth::from_str(h).ok());
+    let restart_count = details
+        .status()
+        .runtime_status()
+        .restart_count()
+        .cloned()
+        .unwrap_or(0);
+    let oom_killed = details
+        .status()
+        .runtime_status()
+        .oom_killed()
+        .cloned()
+        .unwrap_or(false);
 
     let state = ModuleRuntimeState::default()
         .with_status(status)
         .with_status_description(description)
         .with_exit_code(exit_code)
         .with_started_at(start_time)
-        .with_finished_at(exit_time);
+        .with_finished_at(exit_time)
+        .with_health(health)
+        .with_restart_count(restart_count)
+        .with_oom_killed(oom_killed);
     Ok(state)
 }
 
@@ -128,6 +198,12 @@ impl ModuleRegistry for ModuleClient {
     type Error = Error;
     type PullFuture = FutureResult<(), Self::Error>;
     type RemoveFuture = FutureResult<(), Self::Error>;
+    type ListImagesFuture = FutureResult<Vec<Image>, Self::Error>;
+    type PruneImagesFuture = Box<Future<Item = PruneResult, Error = Self::Error>>;
+    type LoadImagesFuture = Box<Future<Item = (), Error = Self::Error>>;
+    type SaveImageFuture = Box<Future<Item = Self::ImageArchive, Error = Self::Error>>;
+    type ImageChunk = Chunk;
+    type ImageArchive = Logs;
     type Config = ModuleConfig;
 
     fn pull(&self, _config: &Self::Config) -> Self::PullFuture {
@@ -137,6 +213,49 @@ impl ModuleRegistry for ModuleClient {
     fn remove(&self, _name: &str) -> Self::RemoveFuture {
         future::ok(())
     }
+
+    // The management API has no endpoint to list images.
+    fn list_images(&self) -> Self::ListImagesFuture {
+        future::err(Error::from(ErrorKind::NotSupported))
+    }
+
+    // The daemon prunes with its own garbage collection settings, the
+    // options are not sent.
+    fn prune_images(&self, _options: &PruneOptions) -> Self::PruneImagesFuture {
+        let result = self
+            .client
+            .image_api()
+            .prune_images(self.api_version)
+            .map(|result| {
+                PruneResult::new(
+                    result.images_deleted().clone(),
+                    *result.space_reclaimed() as u64,
+                )
+            }).map_err(Error::from);
+        Box::new(result)
+    }
+
+    fn load_images(&self, archive: ArchiveStream) -> Self::LoadImagesFuture {
+        let (body, feed) = stream_body(archive);
+        let result = self
+            .client
+            .image_api()
+            .load_images(self.api_version, body)
+            .map_err(Error::from)
+            .join(feed.then(|_| Ok(())))
+            .map(|_| ());
+        Box::new(result)
+    }
+
+    fn save_image(&self, name: &str) -> Self::SaveImageFuture {
+        let result = self
+            .client
+            .image_api()
+            .save_image(self.api_version, name)
+            .map(Logs)
+            .map_err(Error::from);
+        Box::new(result)
+    }
 }
 
 impl ModuleRuntime for ModuleClient {
@@ -146,17 +265,31 @@ impl ModuleRuntime for ModuleClient {
     type ModuleRegistry = Self;
     type Chunk = Chunk;
     type Logs = Logs;
+    type Stats = Stats;
+    type Events = Events;
 
     type CreateFuture = Box<Future<Item = (), Error = Self::Error>>;
+    type EventsFuture = Box<Future<Item = Self::Events, Error = Self::Error>>;
+    type ExecFuture = Box<Future<Item = Self::Logs, Error = Self::Error>>;
+    type GetArchiveFuture = Box<Future<Item = Self::Logs, Error = Self::Error>>;
     type InitFuture = FutureResult<(), Self::Error>;
     type ListFuture = Box<Future<Item = Vec<Self::Module>, Error = Self::Error>>;
     type LogsFuture = Box<Future<Item = Self::Logs, Error = Self::Error>>;
     type RemoveFuture = Box<Future<Item = (), Error = Self::Error>>;
+    type PauseFuture = Box<Future<Item = (), Error = Self::Error>>;
+    type PutArchiveFuture = Box<Future<Item = (), Error = Self::Error>>;
     type RestartFuture = Box<Future<Item = (), Error = Self::Error>>;
     type StartFuture = Box<Future<Item = (), Error = Self::Error>>;
     type StopFuture = Box<Future<Item = (), Error = Self::Error>>;
+    type UnpauseFuture = Box<Future<Item = (), Error = Self::Error>>;
+    type StatsFuture = Box<Future<Item = Self::Stats, Error = Self::Error>>;
     type SystemInfoFuture = Box<Future<Item = CoreSystemInfo, Error = Self::Error>>;
     type RemoveAllFuture = Box<Future<Item = (), Error = Self::Error>>;
+    type ListVolumesFuture = Box<Future<Item = Vec<Volume>, Error = Self::Error>>;
+    type InspectVolumeFuture = Box<Future<Item = Volume, Error = Self::Error>>;
+    type CreateVolumeFuture = Box<Future<Item = Volume, Error = Self::Error>>;
+    type RemoveVolumeFuture = Box<Future<Item = (), Error = Self::Error>>;
+    type BackupVolumeFuture = Box<Future<Item = Self::Logs, Error = Self::Error>>;
 
     fn system_info(&self) -> Self::SystemInfoFuture {
         unimplemented!()
@@ -174,7 +307,7 @@ impl ModuleRuntime for ModuleClient {
         let start = self
             .client
             .module_api()
-            .start_module(API_VERSION, id)
+            .start_module(self.api_version, id)
             .map_err(Error::from)
             .then(|result| match result {
                 Err(e) => match *e.kind() {
@@ -190,7 +323,7 @@ impl ModuleRuntime for ModuleClient {
         let stop = self
             .client
             .module_api()
-            .stop_module(API_VERSION, id)
+            .stop_module(self.api_version, id)
             .map_err(Error::from)
             .then(|result| match result {
                 Err(e) => match *e.kind() {
@@ -206,7 +339,7 @@ impl ModuleRuntime for ModuleClient {
         let restart = self
             .client
             .module_api()
-            .restart_module(API_VERSION, id)
+            .restart_module(self.api_version, id)
             .map_err(Error::from)
             .then(|result| match result {
                 Err(e) => match *e.kind() {
@@ -218,15 +351,61 @@ impl ModuleRuntime for ModuleClient {
         Box::new(restart)
     }
 
-    fn remove(&self, _id: &str) -> Self::RemoveFuture {
-        unimplemented!()
+    fn pause(&self, id: &str) -> Self::PauseFuture {
+        let pause = self
+            .client
+            .module_api()
+            .pause_module(self.api_version, id)
+            .map_err(Error::from)
+            .then(|result| match result {
+                Err(e) => match *e.kind() {
+                    ErrorKind::NotModified => Ok(()),
+                    _ => Err(e),
+                },
+                other => other,
+            });
+        Box::new(pause)
+    }
+
+    fn unpause(&self, id: &str) -> Self::UnpauseFuture {
+        let unpause = self
+            .client
+            .module_api()
+            .unpause_module(self.api_version, id)
+            .map_err(Error::from)
+            .then(|result| match result {
+                Err(e) => match *e.kind() {
+                    ErrorKind::NotModified => Ok(()),
+                    _ => Err(e),
+                },
+                other => other,
+            });
+        Box::new(unpause)
+    }
+
+    fn remove(&self, id: &str) -> Self::RemoveFuture {
+        let remove = self
+            .client
+            .module_api()
+            .delete_module(self.api_version, id, false)
+            .map_err(Error::from);
+        Box::new(remove)
+    }
+
+    fn remove_with_volumes(&self, id: &str) -> Self::RemoveFuture {
+        let remove = self
+            .client
+            .module_api()
+            .delete_module(self.api_version, id, true)
+            .map_err(Error::from);
+        Box::new(remove)
     }
 
     fn list(&self) -> Self::ListFuture {
         let modules = self
             .client
             .module_api()
-            .list_modules(API_VERSION)
+            .list_modules(self.api_version)
             .map(|list| {
                 list.modules()
                     .into_iter()
@@ -245,12 +424,76 @@ impl ModuleRuntime for ModuleClient {
         let result = self
             .client
             .module_api()
-            .module_logs(API_VERSION, id, options.follow(), tail)
+            .module_logs(
+                self.api_version,
+                id,
+                options.follow(),
+                tail,
+                options.since(),
+                options.until(),
+                options.timestamps(),
+            )
+            .map(Logs)
+            .map_err(Error::from);
+        Box::new(result)
+    }
+
+    fn stats(&self, id: &str, options: &StatsOptions) -> Self::StatsFuture {
+        let result = self
+            .client
+            .module_api()
+            .module_stats(self.api_version, id, options.stream())
+            .map(|body| Stats(JsonLines::new(Logs(body))))
+            .map_err(Error::from);
+        Box::new(result)
+    }
+
+    fn events(&self, options: &EventOptions) -> Self::EventsFuture {
+        let result = self
+            .client
+            .module_api()
+            .module_events(self.api_version, options.since(), options.until())
+            .map(|body| Events(JsonLines::new(Logs(body))))
+            .map_err(Error::from);
+        Box::new(result)
+    }
+
+    fn exec(&self, id: &str, options: &ExecOptions) -> Self::ExecFuture {
+        let mut spec = ExecSpec::new(options.cmd().to_vec());
+        if let Some(user) = options.user() {
+            spec.set_user(user.to_string());
+        }
+        let result = self
+            .client
+            .module_api()
+            .exec_module(self.api_version, id, spec)
             .map(Logs)
             .map_err(Error::from);
         Box::new(result)
     }
 
+    fn get_archive(&self, id: &str, path: &str) -> Self::GetArchiveFuture {
+        let result = self
+            .client
+            .module_api()
+            .get_module_archive(self.api_version, id, path)
+            .map(Logs)
+            .map_err(Error::from);
+        Box::new(result)
+    }
+
+    fn put_archive(&self, id: &str, path: &str, archive: ArchiveStream) -> Self::PutArchiveFuture {
+        let (body, feed) = stream_body(archive);
+        let result = self
+            .client
+            .module_api()
+            .put_module_archive(self.api_version, id, path, body)
+            .map_err(Error::from)
+            .join(feed.then(|_| Ok(())))
+            .map(|_| ());
+        Box::new(result)
+    }
+
     fn registry(&self) -> &Self::ModuleRegistry {
         self
     }
@@ -264,6 +507,67 @@ impl ModuleRuntime for ModuleClient {
             future::join_all(n).map(|_| ())
         }))
     }
+
+    fn list_volumes(&self) -> Self::ListVolumesFuture {
+        let result = self
+            .client
+            .volume_api()
+            .list_volumes(self.api_version)
+            .map(|list| list.volumes().iter().map(core_volume).collect())
+            .map_err(Error::from);
+        Box::new(result)
+    }
+
+    fn inspect_volume(&self, name: &str) -> Self::InspectVolumeFuture {
+        let result = self
+            .client
+            .volume_api()
+            .get_volume(self.api_version, name)
+            .map(|volume| core_volume(&volume))
+            .map_err(Error::from);
+        Box::new(result)
+    }
+
+    fn create_volume(&self, name: &str, module: Option<&str>) -> Self::CreateVolumeFuture {
+        let mut spec = VolumeSpec::new(name.to_string());
+        if let Some(module) = module {
+            spec.set_module(module.to_string());
+        }
+        let result = self
+            .client
+            .volume_api()
+            .create_volume(self.api_version, spec)
+            .map(|volume| core_volume(&volume))
+            .map_err(Error::from);
+        Box::new(result)
+    }
+
+    fn remove_volume(&self, name: &str) -> Self::RemoveVolumeFuture {
+        let result = self
+            .client
+            .volume_api()
+            .delete_volume(self.api_version, name)
+            .map_err(Error::from);
+        Box::new(result)
+    }
+
+    fn backup_volume(&self, name: &str) -> Self::BackupVolumeFuture {
+        let result = self
+            .client
+            .volume_api()
+            .backup_volume(self.api_version, name)
+            .map(Logs)
+            .map_err(Error::from);
+        Box::new(result)
+    }
+}
+
+fn core_volume(volume: &HttpVolume) -> Volume {
+    Volume::new(volume.name().clone())
+        .with_module(volume.module().cloned())
+        .with_driver(volume.driver().clone())
+        .with_mountpoint(volume.mountpoint().clone())
+        .with_created(volume.created().and_then(|t| t.parse().ok()))
 }
 
 pub struct Logs(Body);
@@ -288,3 +592,46 @@ impl AsRef<[u8]> for Chunk {
         self.0.as_ref()
     }
 }
+
+pub struct Stats(JsonLines<Logs, HttpModuleStats>);
+
+impl Stream for Stats {
+    type Item = ModuleStats;
+    type Error = Error;
+
+    fn poll(&mut self) -> Poll<Option<Self::Item>, Self::Error> {
+        let stats = try_ready!(self.0.poll());
+        Ok(Async::Ready(stats.as_ref().map(core_stats)))
+    }
+}
+
+fn core_stats(stats: &HttpModuleStats) -> ModuleStats {
+    ModuleStats::default()
+        .with_read(stats.read_time().and_then(|r| r.parse().ok()))
+        .with_cpu_percent(*stats.cpu_percent())
+        .with_cpu_total_usage(*stats.cpu_total_usage() as u64)
+        .with_memory_usage(*stats.memory_usage() as u64)
+        .with_memory_limit(*stats.memory_limit() as u64)
+        .with_network_rx_bytes(*stats.network_rx_bytes() as u64)
+        .with_network_tx_bytes(*stats.network_tx_bytes() as u64)
+        .with_block_read_bytes(*stats.block_read_bytes() as u64)
+        .with_block_write_bytes(*stats.block_write_bytes() as u64)
+}
+
+pub struct Events(JsonLines<Logs, HttpModuleEvent>);
+
+impl Stream for Events {
+    type Item = ModuleEvent;
+    type Error = Error;
+
+    fn poll(&mut self) -> Poll<Option<Self::Item>, Self::Error> {
+        let event = try_ready!(self.0.poll());
+        Ok(Async::Ready(event.as_ref().map(core_event)))
+    }
+}
+
+fn core_event(event: &HttpModuleEvent) -> ModuleEvent {
+    ModuleEvent::new(event.name().clone(), ModuleAction::from(event.action().as_str()))
+        .with_time(event.time().and_then(|t| t.parse().ok()))
+        .with_exit_code(event.exit_code().cloned())
+}
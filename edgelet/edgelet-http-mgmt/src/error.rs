@@ -1,6 +1,7 @@
 // Copyright (c) Microsoft. All rights reserved.
 
 use std::fmt::{self, Display};
+use std::num::ParseIntError;
 use std::str::ParseBoolError;
 
 use edgelet_core::Error as CoreError;
@@ -40,6 +41,10 @@ pub enum ErrorKind {
     BadParam,
     #[fail(display = "Bad body")]
     BadBody,
+    #[fail(display = "Module {} not found", _0)]
+    ModuleNotFound(String),
+    #[fail(display = "No stats available for module {}", _0)]
+    NoStats(String),
     #[fail(display = "IoT Hub error")]
     IoTHub,
     #[fail(display = "Invalid or missing API version")]
@@ -50,6 +55,8 @@ pub enum ErrorKind {
     NotModified,
     #[fail(display = "Parse error")]
     Parse,
+    #[fail(display = "Operation is not supported by the management API")]
+    NotSupported,
 }
 
 impl Fail for Error {
@@ -163,6 +170,14 @@ impl From<ParseBoolError> for Error {
     }
 }
 
+impl From<ParseIntError> for Error {
+    fn from(error: ParseIntError) -> Error {
+        Error {
+            inner: error.context(ErrorKind::Parse),
+        }
+    }
+}
+
 impl IntoResponse for Error {
     fn into_response(self) -> Response<Body> {
         let mut fail: &Fail = &self;
@@ -176,6 +191,8 @@ impl IntoResponse for Error {
             ErrorKind::BadParam => StatusCode::BAD_REQUEST,
             ErrorKind::BadBody => StatusCode::BAD_REQUEST,
             ErrorKind::InvalidApiVersion => StatusCode::BAD_REQUEST,
+            ErrorKind::ModuleNotFound(_) => StatusCode::NOT_FOUND,
+            ErrorKind::NoStats(_) => StatusCode::NOT_FOUND,
             _ => {
                 error!("Internal server error: {}", message);
                 StatusCode::INTERNAL_SERVER_ERROR
@@ -5,10 +5,14 @@
 #[cfg(test)]
 extern crate chrono;
 extern crate edgelet_core;
+#[cfg(feature = "cri")]
+extern crate edgelet_cri;
 extern crate edgelet_docker;
 #[macro_use]
 extern crate edgelet_http;
 extern crate edgelet_iothub;
+extern crate edgelet_memory;
+extern crate edgelet_process;
 #[cfg(test)]
 extern crate edgelet_test_utils;
 extern crate failure;
@@ -41,7 +45,7 @@ mod server;
 
 pub use client::ModuleClient;
 pub use error::{Error, ErrorKind};
-pub use server::ManagementService;
+pub use server::{ManagementService, ROUTES};
 
 pub trait IntoResponse {
     fn into_response(self) -> Response<Body>;
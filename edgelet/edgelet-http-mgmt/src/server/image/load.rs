@@ -0,0 +1,144 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+use edgelet_core::{Error as CoreError, ErrorKind as CoreErrorKind, ModuleRegistry, ModuleRuntime};
+use edgelet_http::route::{BoxFuture, Handler, Parameters};
+use failure::Fail;
+use futures::{future, stream, Future, Stream};
+use http::{Request, Response, StatusCode};
+use hyper::{Body, Error as HyperError};
+
+use error::{Error, ErrorKind};
+use IntoResponse;
+
+/// Loads the images in the tar archive in the request body, for devices
+/// that cannot pull them from a registry. The body is passed on to the
+/// runtime as it arrives.
+pub struct LoadImages<M>
+where
+    M: 'static + ModuleRuntime + Clone,
+    <M::ModuleRegistry as ModuleRegistry>::Error: IntoResponse,
+{
+    runtime: M,
+}
+
+impl<M> LoadImages<M>
+where
+    M: 'static + ModuleRuntime + Clone,
+    <M::ModuleRegistry as ModuleRegistry>::Error: IntoResponse,
+{
+    pub fn new(runtime: M) -> Self {
+        LoadImages { runtime }
+    }
+}
+
+impl<M> Handler<Parameters> for LoadImages<M>
+where
+    M: 'static + ModuleRuntime + Clone,
+    <M::ModuleRegistry as ModuleRegistry>::Error: IntoResponse,
+{
+    fn handle(
+        &self,
+        req: Request<Body>,
+        _params: Parameters,
+    ) -> BoxFuture<Response<Body>, HyperError> {
+        let runtime = self.runtime.clone();
+        // the first chunk tells an empty body apart
+        let response = req
+            .into_body()
+            .into_future()
+            .map_err(|(e, _)| e)
+            .and_then(move |(first, rest)| match first {
+                Some(first) => {
+                    debug!("Load images");
+                    let archive = stream::once(Ok(first))
+                        .chain(rest)
+                        .map(|chunk| chunk.to_vec())
+                        .map_err(|e| CoreError::from(e.context(CoreErrorKind::Http)));
+                    let load = runtime
+                        .registry()
+                        .load_images(Box::new(archive))
+                        .map(|_| {
+                            Response::builder()
+                                .status(StatusCode::NO_CONTENT)
+                                .body(Body::default())
+                                .unwrap_or_else(|e| e.into_response())
+                        }).or_else(|e| future::ok(e.into_response()));
+                    future::Either::A(load)
+                }
+                None => {
+                    future::Either::B(future::ok(Error::from(ErrorKind::BadBody).into_response()))
+                }
+            }).or_else(|e| future::ok(e.into_response()));
+
+        Box::new(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use edgelet_memory::{MemoryRuntime, Operation};
+    use management::models::ErrorResponse;
+    use serde_json;
+
+    use super::*;
+
+    #[test]
+    fn load_adds_images() {
+        // arrange
+        let runtime = MemoryRuntime::new();
+        let handler = LoadImages::new(runtime.clone());
+        let request = Request::post("http://localhost/images/load?api-version=2018-06-28")
+            .body(b"microsoft/test-image:1".to_vec().into())
+            .unwrap();
+
+        // act
+        let response = handler.handle(request, Parameters::new()).wait().unwrap();
+
+        // assert
+        assert_eq!(StatusCode::NO_CONTENT, response.status());
+        assert_eq!(
+            vec!["microsoft/test-image:1".to_string()],
+            runtime.registry().images()
+        );
+    }
+
+    #[test]
+    fn empty_archive_fails() {
+        // arrange
+        let handler = LoadImages::new(MemoryRuntime::new());
+        let request = Request::post("http://localhost/images/load?api-version=2018-06-28")
+            .body(Body::default())
+            .unwrap();
+
+        // act
+        let response = handler.handle(request, Parameters::new()).wait().unwrap();
+
+        // assert
+        assert_eq!(StatusCode::BAD_REQUEST, response.status());
+        response
+            .into_body()
+            .concat2()
+            .and_then(|b| {
+                let error: ErrorResponse = serde_json::from_slice(&b).unwrap();
+                assert_eq!("Bad body", error.message());
+                Ok(())
+            }).wait()
+            .unwrap();
+    }
+
+    #[test]
+    fn load_failed() {
+        // arrange
+        let runtime = MemoryRuntime::new().with_failure(Operation::LoadImages);
+        let handler = LoadImages::new(runtime);
+        let request = Request::post("http://localhost/images/load?api-version=2018-06-28")
+            .body(b"microsoft/test-image:1".to_vec().into())
+            .unwrap();
+
+        // act
+        let response = handler.handle(request, Parameters::new()).wait().unwrap();
+
+        // assert
+        assert_eq!(StatusCode::INTERNAL_SERVER_ERROR, response.status());
+    }
+}
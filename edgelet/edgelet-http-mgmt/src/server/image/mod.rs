@@ -0,0 +1,8 @@
+// Copyright (c) Microsoft. All rights reserved.
+mod load;
+mod prune;
+mod save;
+
+pub use self::load::LoadImages;
+pub use self::prune::PruneImages;
+pub use self::save::SaveImage;
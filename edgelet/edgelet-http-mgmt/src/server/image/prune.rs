@@ -0,0 +1,159 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+use edgelet_core::{ModuleRegistry, ModuleRuntime, PruneOptions};
+use edgelet_http::route::{BoxFuture, Handler, Parameters};
+use failure::ResultExt;
+use futures::{future, Future};
+use http::header::{CONTENT_LENGTH, CONTENT_TYPE};
+use http::{Request, Response, StatusCode};
+use hyper::{Body, Error as HyperError};
+use management::models::PruneResult;
+use serde_json;
+
+use error::ErrorKind;
+use IntoResponse;
+
+/// Removes unused images with the options the daemon was configured with,
+/// callers cannot ask for more images to be removed than the automatic
+/// collection would.
+pub struct PruneImages<M>
+where
+    M: 'static + ModuleRuntime,
+    <M::ModuleRegistry as ModuleRegistry>::Error: IntoResponse,
+{
+    runtime: M,
+    options: PruneOptions,
+}
+
+impl<M> PruneImages<M>
+where
+    M: 'static + ModuleRuntime,
+    <M::ModuleRegistry as ModuleRegistry>::Error: IntoResponse,
+{
+    pub fn new(runtime: M, options: PruneOptions) -> Self {
+        PruneImages { runtime, options }
+    }
+}
+
+impl<M> Handler<Parameters> for PruneImages<M>
+where
+    M: 'static + ModuleRuntime,
+    <M::ModuleRegistry as ModuleRegistry>::Error: IntoResponse,
+{
+    fn handle(
+        &self,
+        _req: Request<Body>,
+        _params: Parameters,
+    ) -> BoxFuture<Response<Body>, HyperError> {
+        debug!("Prune images");
+        let response = self
+            .runtime
+            .registry()
+            .prune_images(&self.options)
+            .and_then(|result| {
+                let body = PruneResult::new(
+                    result.images_deleted().to_vec(),
+                    result.space_reclaimed() as i64,
+                );
+                let response = serde_json::to_string(&body)
+                    .context(ErrorKind::Serde)
+                    .map(|b| {
+                        Response::builder()
+                            .status(StatusCode::OK)
+                            .header(CONTENT_TYPE, "application/json")
+                            .header(CONTENT_LENGTH, b.len().to_string().as_str())
+                            .body(b.into())
+                            .unwrap_or_else(|e| e.into_response())
+                    }).unwrap_or_else(|e| e.into_response());
+                future::ok(response)
+            }).or_else(|e| future::ok(e.into_response()));
+
+        Box::new(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use edgelet_core::ModuleSpec;
+    use edgelet_memory::{MemoryConfig, MemoryRuntime, Operation};
+    use futures::Stream;
+    use management::models::ErrorResponse;
+
+    use super::*;
+
+    fn pull(runtime: &MemoryRuntime, image: &str) {
+        runtime
+            .registry()
+            .pull(&MemoryConfig::new(image.to_string()))
+            .wait()
+            .unwrap();
+    }
+
+    #[test]
+    fn prune_removes_unused_images() {
+        // arrange
+        let runtime = MemoryRuntime::new();
+        let spec = ModuleSpec::new(
+            "mod1",
+            "docker",
+            MemoryConfig::new("microsoft/test-image:2".to_string()),
+            HashMap::new(),
+        ).unwrap();
+        runtime.create(spec).wait().unwrap();
+        pull(&runtime, "microsoft/test-image:1");
+        pull(&runtime, "microsoft/test-image:2");
+        let handler = PruneImages::new(runtime.clone(), PruneOptions::new());
+        let request = Request::post("http://localhost/images/prune")
+            .body(Body::default())
+            .unwrap();
+
+        // act
+        let response = handler.handle(request, Parameters::new()).wait().unwrap();
+
+        // assert
+        assert_eq!(StatusCode::OK, response.status());
+        response
+            .into_body()
+            .concat2()
+            .and_then(|b| {
+                let result: PruneResult = serde_json::from_slice(&b).unwrap();
+                assert_eq!(
+                    &vec!["microsoft/test-image:1".to_string()],
+                    result.images_deleted()
+                );
+                Ok(())
+            }).wait()
+            .unwrap();
+        assert_eq!(
+            vec!["microsoft/test-image:2".to_string()],
+            runtime.registry().images()
+        );
+    }
+
+    #[test]
+    fn prune_failed() {
+        // arrange
+        let runtime = MemoryRuntime::new().with_failure(Operation::PruneImages);
+        let handler = PruneImages::new(runtime, PruneOptions::new());
+        let request = Request::post("http://localhost/images/prune")
+            .body(Body::default())
+            .unwrap();
+
+        // act
+        let response = handler.handle(request, Parameters::new()).wait().unwrap();
+
+        // assert
+        assert_eq!(StatusCode::INTERNAL_SERVER_ERROR, response.status());
+        response
+            .into_body()
+            .concat2()
+            .and_then(|b| {
+                let error: ErrorResponse = serde_json::from_slice(&b).unwrap();
+                assert_eq!("Injected failure for PruneImages", error.message());
+                Ok(())
+            }).wait()
+            .unwrap();
+    }
+}
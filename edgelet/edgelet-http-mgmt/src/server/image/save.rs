@@ -0,0 +1,150 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+use edgelet_core::{ModuleRegistry, ModuleRuntime};
+use edgelet_http::route::{BoxFuture, Handler, Parameters};
+use futures::{future, Future};
+use http::header::CONTENT_TYPE;
+use http::{Request, Response, StatusCode};
+use hyper::{Body, Error as HyperError};
+use url::form_urlencoded;
+
+use error::{Error, ErrorKind};
+use IntoResponse;
+
+/// Returns a tar archive of the image named in the query, which can be
+/// loaded on another device with `/images/load`.
+pub struct SaveImage<M>
+where
+    M: 'static + ModuleRuntime,
+    <M::ModuleRegistry as ModuleRegistry>::Error: IntoResponse,
+    <M::ModuleRegistry as ModuleRegistry>::ImageArchive: Into<Body>,
+{
+    runtime: M,
+}
+
+impl<M> SaveImage<M>
+where
+    M: 'static + ModuleRuntime,
+    <M::ModuleRegistry as ModuleRegistry>::Error: IntoResponse,
+    <M::ModuleRegistry as ModuleRegistry>::ImageArchive: Into<Body>,
+{
+    pub fn new(runtime: M) -> Self {
+        SaveImage { runtime }
+    }
+}
+
+impl<M> Handler<Parameters> for SaveImage<M>
+where
+    M: 'static + ModuleRuntime,
+    <M::ModuleRegistry as ModuleRegistry>::Error: IntoResponse,
+    <M::ModuleRegistry as ModuleRegistry>::ImageArchive: Into<Body>,
+{
+    fn handle(
+        &self,
+        req: Request<Body>,
+        _params: Parameters,
+    ) -> BoxFuture<Response<Body>, HyperError> {
+        let response = image_name(&req)
+            .map(|name| {
+                debug!("Save image {}", name);
+                let result = self
+                    .runtime
+                    .registry()
+                    .save_image(&name)
+                    .map(|archive| {
+                        Response::builder()
+                            .status(StatusCode::OK)
+                            .header(CONTENT_TYPE, "application/x-tar")
+                            .body(archive.into())
+                            .unwrap_or_else(|e| e.into_response())
+                    }).or_else(|e| future::ok(e.into_response()));
+                future::Either::A(result)
+            }).unwrap_or_else(|e| future::Either::B(future::ok(e.into_response())));
+        Box::new(response)
+    }
+}
+
+fn image_name(req: &Request<Body>) -> Result<String, Error> {
+    let name = req.uri().query().and_then(|query| {
+        form_urlencoded::parse(query.as_bytes())
+            .find(|&(ref key, _)| key == "name")
+            .map(|(_, val)| val.into_owned())
+    });
+    match name {
+        Some(ref name) if !name.trim().is_empty() => Ok(name.to_string()),
+        _ => Err(Error::from(ErrorKind::BadParam)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use edgelet_memory::{MemoryConfig, MemoryRuntime};
+    use futures::Stream;
+
+    use super::*;
+
+    #[test]
+    fn save_returns_archive() {
+        // arrange
+        let runtime = MemoryRuntime::new();
+        runtime
+            .registry()
+            .pull(&MemoryConfig::new("microsoft/test-image:1".to_string()))
+            .wait()
+            .unwrap();
+        let handler = SaveImage::new(runtime);
+        let request = Request::get(
+            "http://localhost/images/save?api-version=2018-06-28&name=microsoft%2Ftest-image%3A1",
+        ).body(Body::default())
+        .unwrap();
+
+        // act
+        let response = handler.handle(request, Parameters::new()).wait().unwrap();
+
+        // assert
+        assert_eq!(StatusCode::OK, response.status());
+        assert_eq!(
+            "application/x-tar",
+            response.headers().get(CONTENT_TYPE).unwrap()
+        );
+        response
+            .into_body()
+            .concat2()
+            .and_then(|b| {
+                assert_eq!(&b"microsoft/test-image:1"[..], b.as_ref());
+                Ok(())
+            }).wait()
+            .unwrap();
+    }
+
+    #[test]
+    fn missing_name_fails() {
+        // arrange
+        let handler = SaveImage::new(MemoryRuntime::new());
+        let request = Request::get("http://localhost/images/save?api-version=2018-06-28")
+            .body(Body::default())
+            .unwrap();
+
+        // act
+        let response = handler.handle(request, Parameters::new()).wait().unwrap();
+
+        // assert
+        assert_eq!(StatusCode::BAD_REQUEST, response.status());
+    }
+
+    #[test]
+    fn unknown_image_fails() {
+        // arrange
+        let handler = SaveImage::new(MemoryRuntime::new());
+        let request = Request::get(
+            "http://localhost/images/save?api-version=2018-06-28&name=microsoft%2Fother-image",
+        ).body(Body::default())
+        .unwrap();
+
+        // act
+        let response = handler.handle(request, Parameters::new()).wait().unwrap();
+
+        // assert
+        assert_eq!(StatusCode::NOT_FOUND, response.status());
+    }
+}
@@ -1,25 +1,34 @@
 // Copyright (c) Microsoft. All rights reserved.
 
 mod identity;
+mod image;
 mod module;
 mod system_info;
+mod volume;
 
 use std::io;
+use std::sync::Arc;
 
+use edgelet_core::watchdog::WatchdogStatus;
 use edgelet_core::{
-    Error as CoreError, IdentityManager, Module, ModuleRegistry, ModuleRuntime, Policy,
+    Error as CoreError, IdentityManager, Module, ModuleIndex, ModuleRegistry, ModuleRuntime,
+    Policy, PruneOptions, RoutePolicies,
 };
 use edgelet_http::authorization::Authorization;
 use edgelet_http::route::*;
+use edgelet_http::{Since, API_VERSION_2019_01_30};
 use http::{Request, Response};
 use hyper::server::{NewService, Service};
 use hyper::{Body, Error as HyperError};
 use serde::de::DeserializeOwned;
 use serde::Serialize;
+use tokio_core::reactor::Handle;
 
 use self::identity::*;
+use self::image::*;
 use self::module::*;
 use self::system_info::*;
+use self::volume::*;
 
 use IntoResponse;
 
@@ -27,13 +36,56 @@ lazy_static! {
     static ref AGENT_NAME: String = "edgeAgent".to_string();
 }
 
+/// The routes of the management API, as named by the authorization policies in
+/// the settings.
+pub const ROUTES: &[&str] = &[
+    "GET /modules",
+    "POST /modules",
+    "GET /modules/{name}",
+    "PUT /modules/{name}",
+    "DELETE /modules/{name}",
+    "POST /modules/{name}/start",
+    "POST /modules/{name}/stop",
+    "POST /modules/{name}/restart",
+    "POST /modules/{name}/pause",
+    "POST /modules/{name}/unpause",
+    "POST /modules/{name}/exec",
+    "GET /modules/{name}/archive",
+    "PUT /modules/{name}/archive",
+    "GET /modules/{name}/logs",
+    "GET /modules/{name}/stats",
+    "GET /events",
+    "GET /identities",
+    "POST /identities",
+    "PUT /identities/{name}",
+    "DELETE /identities/{name}",
+    "GET /systeminfo",
+    "GET /watchdog",
+    "POST /images/prune",
+    "POST /images/load",
+    "GET /images/save",
+    "GET /volumes",
+    "POST /volumes",
+    "GET /volumes/{name}",
+    "DELETE /volumes/{name}",
+    "GET /volumes/{name}/backup",
+];
+
 #[derive(Clone)]
 pub struct ManagementService {
     inner: RouterService<RegexRecognizer>,
 }
 
 impl ManagementService {
-    pub fn new<M, I>(runtime: &M, identity: &I) -> Result<Self, HyperError>
+    pub fn new<M, I>(
+        runtime: &M,
+        index: &Arc<ModuleIndex<M>>,
+        identity: &I,
+        watchdog: &WatchdogStatus,
+        prune_options: &PruneOptions,
+        policies: &RoutePolicies,
+        handle: &Handle,
+    ) -> Result<Self, HyperError>
     where
         M: 'static + ModuleRuntime + Clone,
         <M::Module as Module>::Config: DeserializeOwned + Serialize,
@@ -42,27 +94,50 @@ impl ManagementService {
         <M::Module as Module>::Error: Into<CoreError>,
         M::Logs: Into<Body>,
         <M::ModuleRegistry as ModuleRegistry>::Error: IntoResponse,
+        <M::ModuleRegistry as ModuleRegistry>::ImageArchive: Into<Body>,
         I: 'static + IdentityManager + Clone,
         I::Identity: Serialize,
         I::Error: IntoResponse,
     {
+        // the default policies can be replaced per route through the settings,
+        // and routes added after the first version of the API are only served
+        // to the versions that have them
         let router = router!(
-            get    "/modules"                         => Authorization::new(ListModules::new(runtime.clone()), Policy::Anonymous, runtime.clone()),
-            post   "/modules"                         => Authorization::new(CreateModule::new(runtime.clone()), Policy::Module(&*AGENT_NAME), runtime.clone()),
-            get    "/modules/(?P<name>[^/]+)"         => Authorization::new(GetModule, Policy::Anonymous, runtime.clone()),
-            put    "/modules/(?P<name>[^/]+)"         => Authorization::new(UpdateModule::new(runtime.clone()), Policy::Module(&*AGENT_NAME), runtime.clone()),
-            delete "/modules/(?P<name>[^/]+)"         => Authorization::new(DeleteModule::new(runtime.clone()), Policy::Module(&*AGENT_NAME), runtime.clone()),
-            post   "/modules/(?P<name>[^/]+)/start"   => Authorization::new(StartModule::new(runtime.clone()), Policy::Anonymous, runtime.clone()),
-            post   "/modules/(?P<name>[^/]+)/stop"    => Authorization::new(StopModule::new(runtime.clone()), Policy::Anonymous, runtime.clone()),
-            post   "/modules/(?P<name>[^/]+)/restart" => Authorization::new(RestartModule::new(runtime.clone()), Policy::Anonymous, runtime.clone()),
-            get    "/modules/(?P<name>[^/]+)/logs"    => Authorization::new(ModuleLogs::new(runtime.clone()), Policy::Anonymous, runtime.clone()),
-
-            get    "/identities"                      => Authorization::new(ListIdentities::new(identity.clone()), Policy::Module(&*AGENT_NAME), runtime.clone()),
-            post   "/identities"                      => Authorization::new(CreateIdentity::new(identity.clone()), Policy::Module(&*AGENT_NAME), runtime.clone()),
-            put    "/identities/(?P<name>[^/]+)"      => Authorization::new(UpdateIdentity::new(identity.clone()), Policy::Module(&*AGENT_NAME), runtime.clone()),
-            delete "/identities/(?P<name>[^/]+)"      => Authorization::new(DeleteIdentity::new(identity.clone()), Policy::Module(&*AGENT_NAME), runtime.clone()),
-
-            get    "/systeminfo"                      => Authorization::new(GetSystemInfo::new(runtime.clone()), Policy::Anonymous, runtime.clone()),
+            get    "/modules"                         => Authorization::new(ListModules::new(runtime.clone()), policy(policies, "GET /modules", Policy::anonymous()), index.clone()),
+            post   "/modules"                         => Authorization::new(CreateModule::new(runtime.clone()), policy(policies, "POST /modules", Policy::module(&AGENT_NAME)), index.clone()),
+            get    "/modules/(?P<name>[^/]+)"         => Authorization::new(GetModule::new(runtime.clone()), policy(policies, "GET /modules/{name}", Policy::anonymous()), index.clone()),
+            put    "/modules/(?P<name>[^/]+)"         => Authorization::new(UpdateModule::new(runtime.clone()), policy(policies, "PUT /modules/{name}", Policy::module(&AGENT_NAME)), index.clone()),
+            delete "/modules/(?P<name>[^/]+)"         => Authorization::new(DeleteModule::new(runtime.clone()), policy(policies, "DELETE /modules/{name}", Policy::module(&AGENT_NAME)), index.clone()),
+            post   "/modules/(?P<name>[^/]+)/start"   => Authorization::new(StartModule::new(runtime.clone()), policy(policies, "POST /modules/{name}/start", Policy::anonymous()), index.clone()),
+            post   "/modules/(?P<name>[^/]+)/stop"    => Authorization::new(StopModule::new(runtime.clone()), policy(policies, "POST /modules/{name}/stop", Policy::anonymous()), index.clone()),
+            post   "/modules/(?P<name>[^/]+)/restart" => Authorization::new(RestartModule::new(runtime.clone()), policy(policies, "POST /modules/{name}/restart", Policy::anonymous()), index.clone()),
+            post   "/modules/(?P<name>[^/]+)/pause"   => Since::new(API_VERSION_2019_01_30, Authorization::new(PauseModule::new(runtime.clone()), policy(policies, "POST /modules/{name}/pause", Policy::anonymous()), index.clone())),
+            post   "/modules/(?P<name>[^/]+)/unpause" => Since::new(API_VERSION_2019_01_30, Authorization::new(UnpauseModule::new(runtime.clone()), policy(policies, "POST /modules/{name}/unpause", Policy::anonymous()), index.clone())),
+            post   "/modules/(?P<name>[^/]+)/exec"    => Since::new(API_VERSION_2019_01_30, Authorization::new(ExecModule::new(runtime.clone()), policy(policies, "POST /modules/{name}/exec", Policy::host()), index.clone())),
+            get    "/modules/(?P<name>[^/]+)/archive" => Since::new(API_VERSION_2019_01_30, Authorization::new(GetModuleArchive::new(runtime.clone()), policy(policies, "GET /modules/{name}/archive", Policy::host()), index.clone())),
+            put    "/modules/(?P<name>[^/]+)/archive" => Since::new(API_VERSION_2019_01_30, Authorization::new(PutModuleArchive::new(runtime.clone()), policy(policies, "PUT /modules/{name}/archive", Policy::host()), index.clone())),
+            get    "/modules/(?P<name>[^/]+)/logs"    => Authorization::new(ModuleLogs::new(runtime.clone()), policy(policies, "GET /modules/{name}/logs", Policy::anonymous()), index.clone()),
+            get    "/modules/(?P<name>[^/]+)/stats"   => Since::new(API_VERSION_2019_01_30, Authorization::new(ModuleStats::new(runtime.clone(), handle.clone()), policy(policies, "GET /modules/{name}/stats", Policy::anonymous()), index.clone())),
+
+            get    "/events"                          => Since::new(API_VERSION_2019_01_30, Authorization::new(ModuleEvents::new(runtime.clone(), handle.clone()), policy(policies, "GET /events", Policy::anonymous()), index.clone())),
+
+            get    "/identities"                      => Authorization::new(ListIdentities::new(identity.clone()), policy(policies, "GET /identities", Policy::module(&AGENT_NAME)), index.clone()),
+            post   "/identities"                      => Authorization::new(CreateIdentity::new(identity.clone()), policy(policies, "POST /identities", Policy::module(&AGENT_NAME)), index.clone()),
+            put    "/identities/(?P<name>[^/]+)"      => Authorization::new(UpdateIdentity::new(identity.clone()), policy(policies, "PUT /identities/{name}", Policy::module(&AGENT_NAME)), index.clone()),
+            delete "/identities/(?P<name>[^/]+)"      => Authorization::new(DeleteIdentity::new(identity.clone()), policy(policies, "DELETE /identities/{name}", Policy::module(&AGENT_NAME)), index.clone()),
+
+            get    "/systeminfo"                      => Authorization::new(GetSystemInfo::new(runtime.clone()), policy(policies, "GET /systeminfo", Policy::anonymous()), index.clone()),
+            get    "/watchdog"                        => Since::new(API_VERSION_2019_01_30, Authorization::new(GetWatchdogStatus::new(watchdog.clone()), policy(policies, "GET /watchdog", Policy::anonymous()), index.clone())),
+
+            post   "/images/prune"                    => Since::new(API_VERSION_2019_01_30, Authorization::new(PruneImages::new(runtime.clone(), prune_options.clone()), policy(policies, "POST /images/prune", Policy::host()), index.clone())),
+            post   "/images/load"                     => Since::new(API_VERSION_2019_01_30, Authorization::new(LoadImages::new(runtime.clone()), policy(policies, "POST /images/load", Policy::host()), index.clone())),
+            get    "/images/save"                     => Since::new(API_VERSION_2019_01_30, Authorization::new(SaveImage::new(runtime.clone()), policy(policies, "GET /images/save", Policy::host()), index.clone())),
+
+            get    "/volumes"                         => Since::new(API_VERSION_2019_01_30, Authorization::new(ListVolumes::new(runtime.clone()), policy(policies, "GET /volumes", Policy::anonymous()), index.clone())),
+            post   "/volumes"                         => Since::new(API_VERSION_2019_01_30, Authorization::new(CreateVolume::new(runtime.clone()), policy(policies, "POST /volumes", Policy::module(&AGENT_NAME)), index.clone())),
+            get    "/volumes/(?P<name>[^/]+)"         => Since::new(API_VERSION_2019_01_30, Authorization::new(GetVolume::new(runtime.clone()), policy(policies, "GET /volumes/{name}", Policy::anonymous()), index.clone())),
+            delete "/volumes/(?P<name>[^/]+)"         => Since::new(API_VERSION_2019_01_30, Authorization::new(DeleteVolume::new(runtime.clone()), policy(policies, "DELETE /volumes/{name}", Policy::host()), index.clone())),
+            get    "/volumes/(?P<name>[^/]+)/backup"  => Since::new(API_VERSION_2019_01_30, Authorization::new(BackupVolume::new(runtime.clone()), policy(policies, "GET /volumes/{name}/backup", Policy::host()), index.clone())),
         );
         let inner = router.new_service()?;
         let service = ManagementService { inner };
@@ -91,3 +166,8 @@ impl NewService for ManagementService {
         Ok(self.clone())
     }
 }
+
+fn policy(policies: &RoutePolicies, route: &str, default: Policy) -> Policy {
+    debug_assert!(ROUTES.contains(&route), "{} is missing from ROUTES", route);
+    policies.policy(route, default)
+}
@@ -5,6 +5,7 @@ use edgelet_http::route::{BoxFuture, Handler, Parameters};
 use futures::{future, Future};
 use http::{Request, Response, StatusCode};
 use hyper::{Body, Error as HyperError};
+use url::form_urlencoded;
 
 use error::{Error, ErrorKind};
 use IntoResponse;
@@ -34,16 +35,20 @@ where
 {
     fn handle(
         &self,
-        _req: Request<Body>,
+        req: Request<Body>,
         params: Parameters,
     ) -> BoxFuture<Response<Body>, HyperError> {
+        let remove_volumes = remove_volumes(&req);
         let response = params
             .name("name")
             .ok_or_else(|| Error::from(ErrorKind::BadParam))
             .map(|name| {
-                let result = self
-                    .runtime
-                    .remove(name)
+                let removed = if remove_volumes {
+                    self.runtime.remove_with_volumes(name)
+                } else {
+                    self.runtime.remove(name)
+                };
+                let result = removed
                     .map(|_| {
                         Response::builder()
                             .status(StatusCode::NO_CONTENT)
@@ -56,11 +61,24 @@ where
     }
 }
 
+fn remove_volumes(req: &Request<Body>) -> bool {
+    req.uri()
+        .query()
+        .and_then(|query| {
+            form_urlencoded::parse(query.as_bytes())
+                .find(|&(ref key, _)| key == "removeVolumes")
+                .map(|(_, val)| val == "true")
+        }).unwrap_or(false)
+}
+
 #[cfg(test)]
 mod tests {
+    use std::collections::HashMap;
+
     use chrono::prelude::*;
-    use edgelet_core::{ModuleRuntimeState, ModuleStatus};
+    use edgelet_core::{ModuleRuntimeState, ModuleSpec, ModuleStatus};
     use edgelet_http::route::Parameters;
+    use edgelet_memory::{MemoryConfig, MemoryRuntime};
     use edgelet_test_utils::module::*;
     use server::module::tests::Error;
 
@@ -119,4 +137,60 @@ mod tests {
         // assert
         assert_eq!(StatusCode::BAD_REQUEST, response.status());
     }
+
+    fn runtime_with_volume() -> MemoryRuntime {
+        let runtime = MemoryRuntime::new();
+        let spec = ModuleSpec::new(
+            "mod1",
+            "docker",
+            MemoryConfig::new("microsoft/test-image".to_string()),
+            HashMap::new(),
+        ).unwrap();
+        runtime.create(spec).wait().unwrap();
+        runtime
+            .create_volume("mod1-data", Some("mod1"))
+            .wait()
+            .unwrap();
+        runtime
+    }
+
+    fn delete(runtime: &MemoryRuntime, uri: &str) -> StatusCode {
+        let handler = DeleteModule::new(runtime.clone());
+        let parameters =
+            Parameters::with_captures(vec![(Some("name".to_string()), "mod1".to_string())]);
+        let request = Request::delete(uri).body(Body::default()).unwrap();
+        handler.handle(request, parameters).wait().unwrap().status()
+    }
+
+    #[test]
+    fn delete_keeps_volumes() {
+        // arrange
+        let runtime = runtime_with_volume();
+
+        // act
+        let status = delete(
+            &runtime,
+            "http://localhost/modules/mod1?api-version=2018-06-28",
+        );
+
+        // assert
+        assert_eq!(StatusCode::NO_CONTENT, status);
+        assert_eq!(1, runtime.list_volumes().wait().unwrap().len());
+    }
+
+    #[test]
+    fn delete_removes_volumes() {
+        // arrange
+        let runtime = runtime_with_volume();
+
+        // act
+        let status = delete(
+            &runtime,
+            "http://localhost/modules/mod1?api-version=2018-06-28&removeVolumes=true",
+        );
+
+        // assert
+        assert_eq!(StatusCode::NO_CONTENT, status);
+        assert!(runtime.list_volumes().wait().unwrap().is_empty());
+    }
 }
@@ -0,0 +1,190 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+use edgelet_core::{EventOptions, ModuleEvent as CoreModuleEvent, ModuleRuntime};
+use edgelet_http::route::{BoxFuture, Handler, Parameters};
+use failure::ResultExt;
+use futures::{future, Future, Stream};
+use http::{Request, Response};
+use hyper::{Body, Error as HyperError};
+use management::models::ModuleEvent as HttpModuleEvent;
+use tokio_core::reactor::Handle;
+use url::form_urlencoded;
+
+use super::json_lines_response;
+use error::{Error, ErrorKind};
+use IntoResponse;
+
+pub struct ModuleEvents<M>
+where
+    M: 'static + ModuleRuntime + Clone,
+{
+    runtime: M,
+    handle: Handle,
+}
+
+impl<M> ModuleEvents<M>
+where
+    M: 'static + ModuleRuntime + Clone,
+{
+    pub fn new(runtime: M, handle: Handle) -> Self {
+        ModuleEvents { runtime, handle }
+    }
+}
+
+impl<M> Handler<Parameters> for ModuleEvents<M>
+where
+    M: 'static + ModuleRuntime + Clone,
+    M::Error: IntoResponse,
+{
+    fn handle(
+        &self,
+        req: Request<Body>,
+        _params: Parameters,
+    ) -> BoxFuture<Response<Body>, HyperError> {
+        let runtime = self.runtime.clone();
+        let handle = self.handle.clone();
+        let response = req
+            .uri()
+            .query()
+            .map(parse_options)
+            .unwrap_or_else(|| Ok(EventOptions::default()))
+            .context(ErrorKind::BadParam)
+            .map(|options| {
+                let result = runtime
+                    .events(&options)
+                    .map(move |events| {
+                        json_lines_response(events.map(|event| http_event(&event)), &handle)
+                    }).or_else(|e| future::ok(e.into_response()));
+                future::Either::A(result)
+            }).unwrap_or_else(|e| future::Either::B(future::ok(e.into_response())));
+        Box::new(response)
+    }
+}
+
+fn http_event(event: &CoreModuleEvent) -> HttpModuleEvent {
+    let mut http_event =
+        HttpModuleEvent::new(event.name().to_string(), event.action().to_string());
+    if let Some(time) = event.time() {
+        http_event.set_time(time.to_rfc3339());
+    }
+    if let Some(exit_code) = event.exit_code() {
+        http_event.set_exit_code(*exit_code);
+    }
+    http_event
+}
+
+fn parse_options(query: &str) -> Result<EventOptions, Error> {
+    let parse = form_urlencoded::parse(query.as_bytes()).collect::<Vec<_>>();
+    let since = parse
+        .iter()
+        .find(|&(ref key, _)| key == "since")
+        .map(|(_, val)| val.parse::<i32>())
+        .unwrap_or_else(|| Ok(0))?;
+    let until = parse
+        .iter()
+        .find(|&(ref key, _)| key == "until")
+        .map(|(_, val)| val.parse::<i32>())
+        .unwrap_or_else(|| Ok(0))?;
+    Ok(EventOptions::new().with_since(since).with_until(until))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use edgelet_core::{ModuleRuntimeState, ModuleStatus};
+    use edgelet_test_utils::module::*;
+    use http::StatusCode;
+    use management::models::ErrorResponse;
+    use serde_json;
+    use server::module::tests::Error;
+    use tokio_core::reactor::Core;
+
+    #[test]
+    fn correct_eventoptions() {
+        let options = parse_options("since=1533168000&until=1533171600").unwrap();
+        assert_eq!(1_533_168_000, options.since());
+        assert_eq!(1_533_171_600, options.until());
+    }
+
+    #[test]
+    fn eventoptions_defaults() {
+        let options = parse_options("").unwrap();
+        assert_eq!(0, options.since());
+        assert_eq!(0, options.until());
+    }
+
+    #[test]
+    fn eventoptions_since_error() {
+        let options = parse_options("since=yesterday");
+        assert!(options.is_err());
+        assert_eq!("Parse error", options.err().unwrap().to_string());
+    }
+
+    #[test]
+    fn test_success() {
+        let mut core = Core::new().unwrap();
+        let config = TestConfig::new("microsoft/test-image".to_string());
+        let state = ModuleRuntimeState::default().with_status(ModuleStatus::Running);
+        let module: TestModule<Error> =
+            TestModule::new("test-module".to_string(), config, Ok(state));
+        let runtime = TestRuntime::new(Ok(module));
+        let handler = ModuleEvents::new(runtime, core.handle());
+        let request = Request::get("http://localhost/events?api-version=2018-06-28")
+            .body(Body::default())
+            .unwrap();
+
+        // act
+        let response = core
+            .run(handler.handle(request, Parameters::new()))
+            .unwrap();
+
+        // assert
+        assert_eq!(StatusCode::OK, response.status());
+        let body = core.run(response.into_body().concat2()).unwrap();
+        let event: HttpModuleEvent = serde_json::from_slice(&body).unwrap();
+        assert_eq!("test-module", event.name());
+        assert_eq!("start", event.action());
+    }
+
+    #[test]
+    fn bad_params_fails() {
+        let core = Core::new().unwrap();
+        let runtime = TestRuntime::new(Err(Error::General));
+        let handler = ModuleEvents::new(runtime, core.handle());
+        let request = Request::get("http://localhost/events?api-version=2018-06-28&until=never")
+            .body(Body::default())
+            .unwrap();
+
+        // act
+        let response = handler.handle(request, Parameters::new()).wait().unwrap();
+
+        // assert
+        assert_eq!(StatusCode::BAD_REQUEST, response.status());
+    }
+
+    #[test]
+    fn runtime_error() {
+        let core = Core::new().unwrap();
+        let runtime = TestRuntime::new(Err(Error::General));
+        let handler = ModuleEvents::new(runtime, core.handle());
+        let request = Request::get("http://localhost/events?api-version=2018-06-28")
+            .body(Body::default())
+            .unwrap();
+
+        // act
+        let response = handler.handle(request, Parameters::new()).wait().unwrap();
+
+        // assert
+        assert_eq!(StatusCode::INTERNAL_SERVER_ERROR, response.status());
+        response
+            .into_body()
+            .concat2()
+            .and_then(|b| {
+                let error: ErrorResponse = serde_json::from_slice(&b).unwrap();
+                assert_eq!("General error", error.message());
+                Ok(())
+            }).wait()
+            .unwrap();
+    }
+}
@@ -0,0 +1,170 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+use edgelet_core::{ExecOptions, ModuleRuntime};
+use edgelet_http::route::{BoxFuture, Handler, Parameters};
+use failure::ResultExt;
+use futures::{future, Future, Stream};
+use http::{Request, Response, StatusCode};
+use hyper::{Body, Error as HyperError};
+use management::models::ExecSpec;
+use serde_json;
+
+use error::{Error, ErrorKind};
+use IntoResponse;
+
+pub struct ExecModule<M>
+where
+    M: 'static + ModuleRuntime + Clone,
+{
+    runtime: M,
+}
+
+impl<M> ExecModule<M>
+where
+    M: 'static + ModuleRuntime + Clone,
+{
+    pub fn new(runtime: M) -> Self {
+        ExecModule { runtime }
+    }
+}
+
+impl<M> Handler<Parameters> for ExecModule<M>
+where
+    M: 'static + ModuleRuntime + Clone,
+    M::Error: IntoResponse,
+    M::Logs: Into<Body>,
+{
+    fn handle(
+        &self,
+        req: Request<Body>,
+        params: Parameters,
+    ) -> BoxFuture<Response<Body>, HyperError> {
+        let runtime = self.runtime.clone();
+        let response = params
+            .name("name")
+            .ok_or_else(|| Error::from(ErrorKind::BadParam))
+            .map(|name| {
+                let name = name.to_string();
+                let result = req
+                    .into_body()
+                    .concat2()
+                    .and_then(move |b| {
+                        serde_json::from_slice::<ExecSpec>(&b)
+                            .context(ErrorKind::BadBody)
+                            .map_err(Error::from)
+                            .and_then(|spec| spec_to_options(&spec))
+                            .map(|options| {
+                                let output = runtime
+                                    .exec(&name, &options)
+                                    .map(|s| {
+                                        Response::builder()
+                                            .status(StatusCode::OK)
+                                            .body(s.into())
+                                            .unwrap_or_else(|e| e.into_response())
+                                    }).or_else(|e| future::ok(e.into_response()));
+                                future::Either::A(output)
+                            }).unwrap_or_else(|e| future::Either::B(future::ok(e.into_response())))
+                    }).or_else(|e| future::ok(e.into_response()));
+                future::Either::A(result)
+            }).unwrap_or_else(|e| future::Either::B(future::ok(e.into_response())));
+        Box::new(response)
+    }
+}
+
+fn spec_to_options(spec: &ExecSpec) -> Result<ExecOptions, Error> {
+    if spec.cmd().is_empty() {
+        Err(Error::from(ErrorKind::BadBody))
+    } else {
+        Ok(ExecOptions::new(spec.cmd().clone()).with_user(spec.user().cloned()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use edgelet_core::{ModuleRuntimeState, ModuleStatus};
+    use edgelet_test_utils::module::*;
+    use management::models::ErrorResponse;
+    use server::module::tests::Error;
+
+    fn runtime() -> TestRuntime<Error> {
+        let state = ModuleRuntimeState::default().with_status(ModuleStatus::Running);
+        let config = TestConfig::new("microsoft/test-image".to_string());
+        let module: TestModule<Error> =
+            TestModule::new("test-module".to_string(), config, Ok(state));
+        TestRuntime::new(Ok(module))
+    }
+
+    fn parameters() -> Parameters {
+        Parameters::with_captures(vec![(Some("name".to_string()), "mod1".to_string())])
+    }
+
+    #[test]
+    fn success() {
+        let handler = ExecModule::new(runtime());
+        let body = serde_json::to_string(&ExecSpec::new(vec!["ls".to_string()])).unwrap();
+        let request = Request::post("http://localhost/modules/mod1/exec?api-version=2018-06-28")
+            .body(body.into())
+            .unwrap();
+
+        // act
+        let response = handler.handle(request, parameters()).wait().unwrap();
+
+        // assert
+        assert_eq!(StatusCode::OK, response.status());
+    }
+
+    #[test]
+    fn empty_cmd_fails() {
+        let handler = ExecModule::new(runtime());
+        let body = serde_json::to_string(&ExecSpec::new(vec![])).unwrap();
+        let request = Request::post("http://localhost/modules/mod1/exec?api-version=2018-06-28")
+            .body(body.into())
+            .unwrap();
+
+        // act
+        let response = handler.handle(request, parameters()).wait().unwrap();
+
+        // assert
+        assert_eq!(StatusCode::BAD_REQUEST, response.status());
+        response
+            .into_body()
+            .concat2()
+            .and_then(|b| {
+                let error: ErrorResponse = serde_json::from_slice(&b).unwrap();
+                assert_eq!("Bad body", error.message());
+                Ok(())
+            }).wait()
+            .unwrap();
+    }
+
+    #[test]
+    fn runtime_error() {
+        let handler = ExecModule::new(TestRuntime::new(Err(Error::General)));
+        let body = serde_json::to_string(&ExecSpec::new(vec!["ls".to_string()])).unwrap();
+        let request = Request::post("http://localhost/modules/mod1/exec?api-version=2018-06-28")
+            .body(body.into())
+            .unwrap();
+
+        // act
+        let response = handler.handle(request, parameters()).wait().unwrap();
+
+        // assert
+        assert_eq!(StatusCode::INTERNAL_SERVER_ERROR, response.status());
+    }
+
+    #[test]
+    fn bad_params_fails() {
+        let handler = ExecModule::new(runtime());
+        let request = Request::post("http://localhost/modules/mod1/exec?api-version=2018-06-28")
+            .body(Body::default())
+            .unwrap();
+
+        // act
+        let response = handler.handle(request, Parameters::new()).wait().unwrap();
+
+        // assert
+        assert_eq!(StatusCode::BAD_REQUEST, response.status());
+    }
+}
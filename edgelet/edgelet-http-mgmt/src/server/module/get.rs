@@ -1,19 +1,189 @@
 // Copyright (c) Microsoft. All rights reserved.
 
+use edgelet_core::{Module, ModuleRuntime};
 use edgelet_http::route::{BoxFuture, Handler, Parameters};
-use futures::future;
-use http::{Request, Response};
+use edgelet_http::ApiVersion;
+use failure::ResultExt;
+use futures::{future, Future};
+use http::header::{CONTENT_LENGTH, CONTENT_TYPE};
+use http::{Request, Response, StatusCode};
 use hyper::{Body, Error as HyperError};
+use serde::Serialize;
+use serde_json;
 
-pub struct GetModule;
+use super::core_to_details;
+use error::{Error, ErrorKind};
+use IntoResponse;
 
-impl Handler<Parameters> for GetModule {
+pub struct GetModule<M>
+where
+    M: 'static + ModuleRuntime,
+    <M::Module as Module>::Config: Serialize,
+{
+    runtime: M,
+}
+
+impl<M> GetModule<M>
+where
+    M: 'static + ModuleRuntime,
+    <M::Module as Module>::Config: Serialize,
+{
+    pub fn new(runtime: M) -> Self {
+        GetModule { runtime }
+    }
+}
+
+impl<M> Handler<Parameters> for GetModule<M>
+where
+    M: 'static + ModuleRuntime,
+    <M::Module as Module>::Config: Serialize,
+{
     fn handle(
         &self,
-        _req: Request<Body>,
-        _params: Parameters,
+        req: Request<Body>,
+        params: Parameters,
     ) -> BoxFuture<Response<Body>, HyperError> {
-        let response = Response::new(Body::default());
-        Box::new(future::ok(response))
+        let version = req.extensions().get::<ApiVersion>().cloned();
+        let response = params
+            .name("name")
+            .ok_or_else(|| Error::from(ErrorKind::BadParam))
+            .map(|name| {
+                debug!("Get module {}", name);
+                let name = name.to_string();
+                let result = self
+                    .runtime
+                    .list()
+                    .then(|result| {
+                        result
+                            .context(ErrorKind::ModuleRuntime)
+                            .map_err(Error::from)
+                    })
+                    .and_then(move |mods| {
+                        mods.into_iter()
+                            .find(|module| module.name() == name)
+                            .ok_or_else(|| Error::from(ErrorKind::ModuleNotFound(name)))
+                    }).and_then(move |module| core_to_details(module, version))
+                    .and_then(|details| -> Result<_, Error> {
+                        let b = serde_json::to_string(&details).context(ErrorKind::Serde)?;
+                        let response = Response::builder()
+                            .status(StatusCode::OK)
+                            .header(CONTENT_TYPE, "application/json")
+                            .header(CONTENT_LENGTH, b.len().to_string().as_str())
+                            .body(b.into())
+                            .context(ErrorKind::Http)?;
+                        Ok(response)
+                    }).or_else(|e| future::ok(e.into_response()));
+                future::Either::A(result)
+            }).unwrap_or_else(|e| future::Either::B(future::ok(e.into_response())));
+        Box::new(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use edgelet_core::{ModuleRuntimeState, ModuleStatus};
+    use edgelet_http::route::Parameters;
+    use edgelet_http::API_VERSION_2018_06_28;
+    use edgelet_test_utils::module::*;
+    use futures::Stream;
+    use management::models::{ErrorResponse, ModuleDetails};
+    use server::module::tests::Error;
+
+    use super::*;
+
+    fn runtime(status: ModuleStatus) -> TestRuntime<Error> {
+        let state = ModuleRuntimeState::default()
+            .with_status(status)
+            .with_restart_count(3);
+        let config = TestConfig::new("microsoft/test-image".to_string());
+        let module = TestModule::new("test-module".to_string(), config, Ok(state));
+        TestRuntime::new(Ok(module))
+    }
+
+    #[test]
+    fn success() {
+        // arrange
+        let handler = GetModule::new(runtime(ModuleStatus::Paused));
+        let request = Request::get("http://localhost/modules/test-module")
+            .body(Body::default())
+            .unwrap();
+        let parameters =
+            Parameters::with_captures(vec![(Some("name".to_string()), "test-module".to_string())]);
+
+        // act
+        let response = handler.handle(request, parameters).wait().unwrap();
+
+        // assert
+        assert_eq!(StatusCode::OK, response.status());
+        response
+            .into_body()
+            .concat2()
+            .and_then(|b| {
+                let details: ModuleDetails = serde_json::from_slice(&b).unwrap();
+                assert_eq!("test-module", details.name());
+                assert_eq!("paused", details.status().runtime_status().status());
+                assert_eq!(
+                    3,
+                    *details.status().runtime_status().restart_count().unwrap()
+                );
+                Ok(())
+            }).wait()
+            .unwrap();
+    }
+
+    #[test]
+    fn legacy_shape_for_2018_06_28() {
+        // arrange
+        let handler = GetModule::new(runtime(ModuleStatus::Restarting));
+        let mut request = Request::get("http://localhost/modules/test-module")
+            .body(Body::default())
+            .unwrap();
+        request
+            .extensions_mut()
+            .insert(ApiVersion::new(API_VERSION_2018_06_28).unwrap());
+        let parameters =
+            Parameters::with_captures(vec![(Some("name".to_string()), "test-module".to_string())]);
+
+        // act
+        let response = handler.handle(request, parameters).wait().unwrap();
+
+        // assert
+        response
+            .into_body()
+            .concat2()
+            .and_then(|b| {
+                let details: ModuleDetails = serde_json::from_slice(&b).unwrap();
+                assert_eq!("stopped", details.status().runtime_status().status());
+                assert_eq!(None, details.status().runtime_status().restart_count());
+                assert_eq!(None, details.status().runtime_status().oom_killed());
+                Ok(())
+            }).wait()
+            .unwrap();
+    }
+
+    #[test]
+    fn not_found() {
+        // arrange
+        let handler = GetModule::new(runtime(ModuleStatus::Running));
+        let request = Request::get("http://localhost/modules/other-module")
+            .body(Body::default())
+            .unwrap();
+        let parameters =
+            Parameters::with_captures(vec![(Some("name".to_string()), "other-module".to_string())]);
+
+        // act
+        let response = handler.handle(request, parameters).wait().unwrap();
+
+        // assert
+        assert_eq!(StatusCode::NOT_FOUND, response.status());
+        response
+            .into_body()
+            .concat2()
+            .and_then(|b| {
+                let error: ErrorResponse = serde_json::from_slice(&b).unwrap();
+                assert_eq!("Module other-module not found", error.message());
+                Ok(())
+            }).wait()
+            .unwrap();
     }
 }
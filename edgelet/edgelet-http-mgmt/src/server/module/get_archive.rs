@@ -0,0 +1,156 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+use edgelet_core::ModuleRuntime;
+use edgelet_http::route::{BoxFuture, Handler, Parameters};
+use futures::{future, Future};
+use http::header::CONTENT_TYPE;
+use http::{Request, Response, StatusCode};
+use hyper::{Body, Error as HyperError};
+
+use super::archive_path;
+use error::{Error, ErrorKind};
+use IntoResponse;
+
+pub struct GetModuleArchive<M>
+where
+    M: 'static + ModuleRuntime + Clone,
+{
+    runtime: M,
+}
+
+impl<M> GetModuleArchive<M>
+where
+    M: 'static + ModuleRuntime + Clone,
+{
+    pub fn new(runtime: M) -> Self {
+        GetModuleArchive { runtime }
+    }
+}
+
+impl<M> Handler<Parameters> for GetModuleArchive<M>
+where
+    M: 'static + ModuleRuntime + Clone,
+    M::Error: IntoResponse,
+    M::Logs: Into<Body>,
+{
+    fn handle(
+        &self,
+        req: Request<Body>,
+        params: Parameters,
+    ) -> BoxFuture<Response<Body>, HyperError> {
+        let runtime = self.runtime.clone();
+        let response = params
+            .name("name")
+            .ok_or_else(|| Error::from(ErrorKind::BadParam))
+            .and_then(|name| Ok((name, archive_path(&req)?)))
+            .map(|(name, path)| {
+                let result = runtime
+                    .get_archive(name, &path)
+                    .map(|s| {
+                        Response::builder()
+                            .status(StatusCode::OK)
+                            .header(CONTENT_TYPE, "application/x-tar")
+                            .body(s.into())
+                            .unwrap_or_else(|e| e.into_response())
+                    }).or_else(|e| future::ok(e.into_response()));
+                future::Either::A(result)
+            }).unwrap_or_else(|e| future::Either::B(future::ok(e.into_response())));
+        Box::new(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use edgelet_core::{ModuleRuntimeState, ModuleStatus};
+    use edgelet_test_utils::module::*;
+    use futures::Stream;
+    use management::models::ErrorResponse;
+    use serde_json;
+    use server::module::tests::Error;
+
+    fn runtime() -> TestRuntime<Error> {
+        let state = ModuleRuntimeState::default().with_status(ModuleStatus::Running);
+        let config = TestConfig::new("microsoft/test-image".to_string());
+        let module: TestModule<Error> =
+            TestModule::new("test-module".to_string(), config, Ok(state));
+        TestRuntime::new(Ok(module))
+    }
+
+    fn parameters() -> Parameters {
+        Parameters::with_captures(vec![(Some("name".to_string()), "mod1".to_string())])
+    }
+
+    #[test]
+    fn success() {
+        let handler = GetModuleArchive::new(runtime());
+        let request = Request::get(
+            "http://localhost/modules/mod1/archive?api-version=2018-06-28&path=%2Fvar%2Flog",
+        ).body(Body::default())
+        .unwrap();
+
+        // act
+        let response = handler.handle(request, parameters()).wait().unwrap();
+
+        // assert
+        assert_eq!(StatusCode::OK, response.status());
+        assert_eq!(
+            "application/x-tar",
+            response.headers().get(CONTENT_TYPE).unwrap().to_str().unwrap()
+        );
+    }
+
+    #[test]
+    fn missing_path_fails() {
+        let handler = GetModuleArchive::new(runtime());
+        let request = Request::get("http://localhost/modules/mod1/archive?api-version=2018-06-28")
+            .body(Body::default())
+            .unwrap();
+
+        // act
+        let response = handler.handle(request, parameters()).wait().unwrap();
+
+        // assert
+        assert_eq!(StatusCode::BAD_REQUEST, response.status());
+        response
+            .into_body()
+            .concat2()
+            .and_then(|b| {
+                let error: ErrorResponse = serde_json::from_slice(&b).unwrap();
+                assert_eq!("Bad parameter", error.message());
+                Ok(())
+            }).wait()
+            .unwrap();
+    }
+
+    #[test]
+    fn runtime_error() {
+        let handler = GetModuleArchive::new(TestRuntime::new(Err(Error::General)));
+        let request = Request::get(
+            "http://localhost/modules/mod1/archive?api-version=2018-06-28&path=%2Fvar%2Flog",
+        ).body(Body::default())
+        .unwrap();
+
+        // act
+        let response = handler.handle(request, parameters()).wait().unwrap();
+
+        // assert
+        assert_eq!(StatusCode::INTERNAL_SERVER_ERROR, response.status());
+    }
+
+    #[test]
+    fn bad_params_fails() {
+        let handler = GetModuleArchive::new(runtime());
+        let request = Request::get(
+            "http://localhost/modules/mod1/archive?api-version=2018-06-28&path=%2Fvar%2Flog",
+        ).body(Body::default())
+        .unwrap();
+
+        // act
+        let response = handler.handle(request, Parameters::new()).wait().unwrap();
+
+        // assert
+        assert_eq!(StatusCode::BAD_REQUEST, response.status());
+    }
+}
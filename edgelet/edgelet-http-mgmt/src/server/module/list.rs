@@ -2,6 +2,7 @@
 
 use edgelet_core::{Module, ModuleRuntime};
 use edgelet_http::route::{BoxFuture, Handler, Parameters};
+use edgelet_http::ApiVersion;
 use failure::ResultExt;
 use futures::{future, Future};
 use http::header::{CONTENT_LENGTH, CONTENT_TYPE};
@@ -40,16 +41,19 @@ where
 {
     fn handle(
         &self,
-        _req: Request<Body>,
+        req: Request<Body>,
         _params: Parameters,
     ) -> BoxFuture<Response<Body>, HyperError> {
         debug!("List modules");
+        let version = req.extensions().get::<ApiVersion>().cloned();
         let response =
             self.runtime
                 .list()
                 .then(|result| match result.context(ErrorKind::ModuleRuntime) {
                     Ok(mods) => {
-                        let futures = mods.into_iter().map(core_to_details);
+                        let futures = mods
+                            .into_iter()
+                            .map(move |module| core_to_details(module, version));
                         let response = future::join_all(futures)
                             .map(|details| {
                                 let body = ModuleList::new(details);
@@ -75,8 +79,9 @@ where
 #[cfg(test)]
 mod tests {
     use chrono::prelude::*;
-    use edgelet_core::{ModuleRuntimeState, ModuleStatus};
+    use edgelet_core::{ModuleHealth, ModuleRuntimeState, ModuleStatus};
     use edgelet_http::route::Parameters;
+    use edgelet_http::API_VERSION_2018_06_28;
     use edgelet_test_utils::module::*;
     use futures::Stream;
     use management::models::ModuleList;
@@ -93,7 +98,9 @@ mod tests {
             .with_status_description(Some("description".to_string()))
             .with_started_at(Some(Utc.ymd(2018, 4, 13).and_hms_milli(14, 20, 0, 1)))
             .with_finished_at(Some(Utc.ymd(2018, 4, 13).and_hms_milli(15, 20, 0, 1)))
-            .with_image_id(Some("image-id".to_string()));
+            .with_image_id(Some("image-id".to_string()))
+            .with_health(Some(ModuleHealth::Healthy))
+            .with_restart_count(2);
         let config = TestConfig::new("microsoft/test-image".to_string());
         let module: TestModule<Error> =
             TestModule::new("test-module".to_string(), config, Ok(state));
@@ -136,6 +143,89 @@ mod tests {
                     "description",
                     module.status().runtime_status().description().unwrap()
                 );
+                assert_eq!(
+                    "healthy",
+                    module.status().runtime_status().health().unwrap()
+                );
+                assert_eq!(
+                    2,
+                    *module.status().runtime_status().restart_count().unwrap()
+                );
+                assert_eq!(
+                    false,
+                    *module.status().runtime_status().oom_killed().unwrap()
+                );
+                Ok(())
+            }).wait()
+            .unwrap();
+    }
+
+    #[test]
+    fn legacy_shape_for_2018_06_28() {
+        // arrange
+        let state = ModuleRuntimeState::default()
+            .with_status(ModuleStatus::Paused)
+            .with_exit_code(Some(0))
+            .with_health(Some(ModuleHealth::Healthy))
+            .with_restart_count(2)
+            .with_oom_killed(true);
+        let config = TestConfig::new("microsoft/test-image".to_string());
+        let module: TestModule<Error> =
+            TestModule::new("test-module".to_string(), config, Ok(state));
+        let runtime = TestRuntime::new(Ok(module));
+        let handler = ListModules::new(runtime);
+        let mut request = Request::get("http://localhost/modules?api-version=2018-06-28")
+            .body(Body::default())
+            .unwrap();
+        request
+            .extensions_mut()
+            .insert(ApiVersion::new(API_VERSION_2018_06_28).unwrap());
+
+        // act
+        let response = handler.handle(request, Parameters::new()).wait().unwrap();
+
+        // assert
+        response
+            .into_body()
+            .concat2()
+            .and_then(|b| {
+                let list: serde_json::Value = serde_json::from_slice(&b).unwrap();
+                let runtime_status = &list["modules"][0]["status"]["runtimeStatus"];
+                assert_eq!(json!({ "status": "stopped" }), *runtime_status);
+                Ok(())
+            }).wait()
+            .unwrap();
+    }
+
+    #[test]
+    fn backoff_is_failed_for_2018_06_28() {
+        // arrange
+        let state = ModuleRuntimeState::default()
+            .with_status(ModuleStatus::Backoff)
+            .with_exit_code(Some(1));
+        let config = TestConfig::new("microsoft/test-image".to_string());
+        let module: TestModule<Error> =
+            TestModule::new("test-module".to_string(), config, Ok(state));
+        let runtime = TestRuntime::new(Ok(module));
+        let handler = ListModules::new(runtime);
+        let mut request = Request::get("http://localhost/modules?api-version=2018-06-28")
+            .body(Body::default())
+            .unwrap();
+        request
+            .extensions_mut()
+            .insert(ApiVersion::new(API_VERSION_2018_06_28).unwrap());
+
+        // act
+        let response = handler.handle(request, Parameters::new()).wait().unwrap();
+
+        // assert
+        response
+            .into_body()
+            .concat2()
+            .and_then(|b| {
+                let list: ModuleList = serde_json::from_slice(&b).unwrap();
+                let module = list.modules().iter().next().unwrap();
+                assert_eq!("failed", module.status().runtime_status().status());
                 Ok(())
             }).wait()
             .unwrap();
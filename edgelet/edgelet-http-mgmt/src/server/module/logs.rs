@@ -77,7 +77,27 @@ fn parse_options(query: &str) -> Result<LogOptions, Error> {
         .find(|&(ref key, _)| key == "follow")
         .map(|(_, val)| val.parse::<bool>())
         .unwrap_or_else(|| Ok(false))?;
-    let options = LogOptions::new().with_follow(follow).with_tail(tail);
+    let since = parse
+        .iter()
+        .find(|&(ref key, _)| key == "since")
+        .map(|(_, val)| val.parse::<i32>())
+        .unwrap_or_else(|| Ok(0))?;
+    let until = parse
+        .iter()
+        .find(|&(ref key, _)| key == "until")
+        .map(|(_, val)| val.parse::<i32>())
+        .unwrap_or_else(|| Ok(0))?;
+    let timestamps = parse
+        .iter()
+        .find(|&(ref key, _)| key == "timestamps")
+        .map(|(_, val)| val.parse::<bool>())
+        .unwrap_or_else(|| Ok(false))?;
+    let options = LogOptions::new()
+        .with_follow(follow)
+        .with_tail(tail)
+        .with_since(since)
+        .with_until(until)
+        .with_timestamps(timestamps);
     Ok(options)
 }
 
@@ -95,10 +115,13 @@ mod tests {
 
     #[test]
     fn correct_logoptions() {
-        let query = "follow=true&tail=6";
+        let query = "follow=true&tail=6&since=1533168000&until=1533171600&timestamps=true";
         let options = parse_options(&query).unwrap();
         assert_eq!(LogTail::Num(6), *options.tail());
         assert_eq!(true, options.follow());
+        assert_eq!(1_533_168_000, options.since());
+        assert_eq!(1_533_171_600, options.until());
+        assert_eq!(true, options.timestamps());
     }
 
     #[test]
@@ -107,6 +130,9 @@ mod tests {
         let options = parse_options(&query).unwrap();
         assert_eq!(LogTail::default(), *options.tail());
         assert_eq!(false, options.follow());
+        assert_eq!(0, options.since());
+        assert_eq!(0, options.until());
+        assert_eq!(false, options.timestamps());
     }
 
     #[test]
@@ -117,6 +143,14 @@ mod tests {
         assert_eq!("Parse error", options.err().unwrap().to_string());
     }
 
+    #[test]
+    fn logoption_since_error() {
+        let query = "since=yesterday";
+        let options = parse_options(&query);
+        assert!(options.is_err());
+        assert_eq!("Parse error", options.err().unwrap().to_string());
+    }
+
     #[test]
     fn logoption_tail_error() {
         let query = "follow=false&tail=adsaf";
@@ -1,88 +1,175 @@
 // Copyright (c) Microsoft. All rights reserved.
 
 use std::collections::HashMap;
+use std::io;
 
-use edgelet_core::{Module, ModuleRuntime, ModuleSpec as CoreModuleSpec, ModuleStatus};
+use edgelet_core::{
+    Module, ModuleRuntime, ModuleRuntimeState, ModuleSpec as CoreModuleSpec, ModuleStatus,
+};
+#[cfg(feature = "cri")]
+use edgelet_cri::{Error as CriError, ErrorKind as CriErrorKind};
 use edgelet_docker::{Error as DockerError, ErrorKind as DockerErrorKind};
+use edgelet_http::{ApiVersion, API_VERSION_2019_01_30};
+use edgelet_memory::Error as MemoryError;
+use edgelet_process::{Error as ProcessError, ErrorKind as ProcessErrorKind};
 use failure::{Fail, ResultExt};
-use futures::Future;
+use futures::{Future, Sink, Stream};
 use http::header::{CONTENT_LENGTH, CONTENT_TYPE};
-use http::{Response, StatusCode};
-use hyper::Body;
+use http::{Request, Response, StatusCode};
+use hyper::{Body, Chunk, Error as HyperError};
 use management::models::*;
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 use serde_json;
+use tokio_core::reactor::Handle;
+use url::form_urlencoded;
 
 use error::{Error, ErrorKind};
 use IntoResponse;
 
 mod create;
 mod delete;
+mod events;
+mod exec;
 mod get;
+mod get_archive;
 mod list;
 mod logs;
+mod pause;
+mod put_archive;
 mod restart;
 mod start;
+mod stats;
 mod stop;
+mod unpause;
 mod update;
 
 pub use self::create::CreateModule;
 pub use self::delete::DeleteModule;
+pub use self::events::ModuleEvents;
+pub use self::exec::ExecModule;
 pub use self::get::GetModule;
+pub use self::get_archive::GetModuleArchive;
 pub use self::list::ListModules;
 pub use self::logs::ModuleLogs;
+pub use self::pause::PauseModule;
+pub use self::put_archive::PutModuleArchive;
 pub use self::restart::RestartModule;
 pub use self::start::StartModule;
+pub use self::stats::ModuleStats;
 pub use self::stop::StopModule;
+pub use self::unpause::UnpauseModule;
 pub use self::update::UpdateModule;
 
 impl IntoResponse for DockerError {
     fn into_response(self) -> Response<Body> {
-        let mut fail: &Fail = &self;
-        let mut message = self.to_string();
-        while let Some(cause) = fail.cause() {
-            message.push_str(&format!("\n\tcaused by: {}", cause.to_string()));
-            fail = cause;
-        }
-
         let status_code = match *self.kind() {
-            DockerErrorKind::NotFound => StatusCode::NOT_FOUND,
-            DockerErrorKind::Conflict => StatusCode::CONFLICT,
+            DockerErrorKind::NotFound | DockerErrorKind::LocalImageNotFound(_) => {
+                StatusCode::NOT_FOUND
+            }
+            DockerErrorKind::Conflict | DockerErrorKind::VolumeNotMounted(_) => {
+                StatusCode::CONFLICT
+            }
             DockerErrorKind::NotModified => StatusCode::NOT_MODIFIED,
+            DockerErrorKind::ImageDigestMismatch(..) => StatusCode::UNPROCESSABLE_ENTITY,
+            DockerErrorKind::UntrustedImage(_) | DockerErrorKind::AdmissionDenied(..) => {
+                StatusCode::FORBIDDEN
+            }
             _ => StatusCode::INTERNAL_SERVER_ERROR,
         };
+        runtime_error_response(&self, status_code)
+    }
+}
 
-        // Per the RFC, status code NotModified should not have a body
-        let body = if status_code != StatusCode::NOT_MODIFIED {
-            let b = serde_json::to_string(&ErrorResponse::new(message))
-                .expect("serialization of ErrorResponse failed.");
-            Some(b)
-        } else {
-            None
+impl IntoResponse for ProcessError {
+    fn into_response(self) -> Response<Body> {
+        let status_code = match *self.kind() {
+            ProcessErrorKind::NotFound => StatusCode::NOT_FOUND,
+            ProcessErrorKind::Conflict => StatusCode::CONFLICT,
+            ProcessErrorKind::NotModified => StatusCode::NOT_MODIFIED,
+            ProcessErrorKind::NotSupported(_) => StatusCode::NOT_IMPLEMENTED,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
         };
+        runtime_error_response(&self, status_code)
+    }
+}
 
-        body.map(|b| {
-            Response::builder()
-                .status(status_code)
-                .header(CONTENT_TYPE, "application/json")
-                .header(CONTENT_LENGTH, b.len().to_string().as_str())
-                .body(b.into())
-                .expect("response builder failure")
-        }).unwrap_or_else(|| {
-            Response::builder()
-                .status(status_code)
-                .body(Body::default())
-                .expect("response builder failure")
-        })
+#[cfg(feature = "cri")]
+impl IntoResponse for CriError {
+    fn into_response(self) -> Response<Body> {
+        let status_code = match *self.kind() {
+            CriErrorKind::NotFound => StatusCode::NOT_FOUND,
+            CriErrorKind::Conflict => StatusCode::CONFLICT,
+            CriErrorKind::NotModified => StatusCode::NOT_MODIFIED,
+            CriErrorKind::NotSupported(_) => StatusCode::NOT_IMPLEMENTED,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        runtime_error_response(&self, status_code)
     }
 }
 
-fn core_to_details<M>(module: M) -> Box<Future<Item = ModuleDetails, Error = Error>>
+impl IntoResponse for MemoryError {
+    fn into_response(self) -> Response<Body> {
+        let status_code = match self {
+            MemoryError::NotFound(_)
+            | MemoryError::ImageNotFound(_)
+            | MemoryError::VolumeNotFound(_) => StatusCode::NOT_FOUND,
+            MemoryError::Conflict(_) | MemoryError::VolumeInUse(..) => StatusCode::CONFLICT,
+            MemoryError::NotModified(_) => StatusCode::NOT_MODIFIED,
+            MemoryError::InvalidArchive => StatusCode::BAD_REQUEST,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        runtime_error_response(&self, status_code)
+    }
+}
+
+fn runtime_error_response(error: &Fail, status_code: StatusCode) -> Response<Body> {
+    let mut fail = error;
+    let mut message = error.to_string();
+    while let Some(cause) = fail.cause() {
+        message.push_str(&format!("\n\tcaused by: {}", cause.to_string()));
+        fail = cause;
+    }
+
+    // Per the RFC, status code NotModified should not have a body
+    let body = if status_code != StatusCode::NOT_MODIFIED {
+        let b = serde_json::to_string(&ErrorResponse::new(message))
+            .expect("serialization of ErrorResponse failed.");
+        Some(b)
+    } else {
+        None
+    };
+
+    body.map(|b| {
+        Response::builder()
+            .status(status_code)
+            .header(CONTENT_TYPE, "application/json")
+            .header(CONTENT_LENGTH, b.len().to_string().as_str())
+            .body(b.into())
+            .expect("response builder failure")
+    }).unwrap_or_else(|| {
+        Response::builder()
+            .status(status_code)
+            .body(Body::default())
+            .expect("response builder failure")
+    })
+}
+
+// Clients of 2018-06-28 only know of the unknown, running, stopped and failed
+// statuses, and not of the health, restart count or out of memory fields of
+// the runtime status. Requests that did not go through the version
+// negotiation, as in tests of a single route, get the newest models.
+fn core_to_details<M>(
+    module: M,
+    version: Option<ApiVersion>,
+) -> Box<Future<Item = ModuleDetails, Error = Error>>
 where
     M: 'static + Module,
     M::Config: Serialize,
 {
+    let legacy = version.map_or(false, |version| {
+        !version.is_at_least(API_VERSION_2019_01_30)
+    });
     let details = module
         .runtime_state()
         .then(move |result| {
@@ -91,10 +178,21 @@ where
                     .context(ErrorKind::Serde)
                     .map(|settings| {
                         let config = Config::new(settings).with_env(Vec::new());
-                        let mut runtime_status = RuntimeStatus::new(state.status().to_string());
+                        let mut runtime_status = if legacy {
+                            RuntimeStatus::new(legacy_status(&state).to_string())
+                        } else {
+                            RuntimeStatus::new(state.status().to_string())
+                        };
                         if let Some(description) = state.status_description() {
                             runtime_status.set_description(description.to_string());
                         }
+                        if !legacy {
+                            if let Some(health) = state.health() {
+                                runtime_status.set_health(health.to_string());
+                            }
+                            runtime_status.set_restart_count(state.restart_count());
+                            runtime_status.set_oom_killed(state.oom_killed());
+                        }
                         let mut status = Status::new(runtime_status);
                         if let Some(started_at) = state.started_at() {
                             status.set_start_time(started_at.to_rfc3339());
@@ -121,6 +219,63 @@ where
     Box::new(details)
 }
 
+fn legacy_status(state: &ModuleRuntimeState) -> ModuleStatus {
+    match *state.status() {
+        ModuleStatus::Created | ModuleStatus::Paused | ModuleStatus::Restarting => {
+            ModuleStatus::Stopped
+        }
+        ModuleStatus::Backoff => ModuleStatus::Failed,
+        ModuleStatus::Dead => match state.exit_code() {
+            Some(&0) | None => ModuleStatus::Stopped,
+            Some(_) => ModuleStatus::Failed,
+        },
+        ref status => status.clone(),
+    }
+}
+
+// Items are written to the response body as newline delimited JSON as they
+// arrive. The forwarding task stops when the stream ends or the client goes
+// away. An error from the stream fails the body instead of ending it, so that
+// the caller can tell a broken stream from one that finished.
+fn json_lines_response<S>(stream: S, handle: &Handle) -> Response<Body>
+where
+    S: 'static + Stream,
+    S::Item: Serialize,
+    S::Error: Fail,
+{
+    let (sender, body) = Body::pair();
+    let chunks = stream.then(|item| -> Result<_, ()> {
+        let chunk = item
+            .map_err(|e| e.to_string())
+            .and_then(|item| serde_json::to_vec(&item).map_err(|e| e.to_string()))
+            .map(|mut line| {
+                line.push(b'\n');
+                Chunk::from(line)
+            }).map_err(|e| HyperError::from(io::Error::new(io::ErrorKind::Other, e)));
+        Ok(chunk)
+    });
+    handle.spawn(sender.sink_map_err(|_| ()).send_all(chunks).map(|_| ()));
+
+    Response::builder()
+        .status(StatusCode::OK)
+        .header(CONTENT_TYPE, "application/json")
+        .body(body)
+        .unwrap_or_else(|e| e.into_response())
+}
+
+// The archive routes take the path inside the module as a required `path`
+// query parameter.
+fn archive_path(req: &Request<Body>) -> Result<String, Error> {
+    req.uri()
+        .query()
+        .and_then(|query| {
+            form_urlencoded::parse(query.as_bytes())
+                .find(|&(ref key, _)| key == "path")
+                .map(|(_, val)| val.into_owned())
+        }).filter(|path| !path.trim().is_empty())
+        .ok_or_else(|| Error::from(ErrorKind::BadParam))
+}
+
 fn spec_to_core<M>(
     spec: &ModuleSpec,
 ) -> Result<CoreModuleSpec<<M::Module as Module>::Config>, Error>
@@ -165,13 +320,19 @@ fn spec_to_details(spec: &ModuleSpec, module_status: &ModuleStatus) -> ModuleDet
 
 #[cfg(test)]
 pub mod tests {
+    #[cfg(feature = "cri")]
+    use edgelet_cri::{Error as CriError, ErrorKind as CriErrorKind};
     use edgelet_docker::{Error as DockerError, ErrorKind as DockerErrorKind};
-    use futures::{Future, Stream};
+    use edgelet_memory::{Error as MemoryError, Operation};
+    use edgelet_process::{Error as ProcessError, ErrorKind as ProcessErrorKind};
+    use futures::{stream, Future, Stream};
     use http::{Response, StatusCode};
     use hyper::Body;
     use management::models::ErrorResponse;
     use serde_json;
+    use tokio_core::reactor::Core;
 
+    use super::json_lines_response;
     use IntoResponse;
 
     #[derive(Clone, Debug, Fail)]
@@ -191,6 +352,17 @@ pub mod tests {
         }
     }
 
+    #[test]
+    fn json_lines_response_fails_body_on_stream_error() {
+        let mut core = Core::new().unwrap();
+        let items = stream::iter_result(vec![Ok(1), Err(Error::General)]);
+
+        let response = json_lines_response(items, &core.handle());
+
+        assert_eq!(StatusCode::OK, response.status());
+        assert!(core.run(response.into_body().concat2()).is_err());
+    }
+
     #[test]
     fn not_found() {
         // arrange
@@ -233,6 +405,111 @@ pub mod tests {
             .unwrap();
     }
 
+    #[test]
+    fn image_digest_mismatch() {
+        // arrange
+        let error = DockerError::from(DockerErrorKind::ImageDigestMismatch(
+            "nginx:latest".to_string(),
+            "sha256:4bd4ea3ba8b9".to_string(),
+            "sha256:5c2e1fb7a1d0".to_string(),
+        ));
+
+        // act
+        let response = error.into_response();
+
+        // assert
+        assert_eq!(StatusCode::UNPROCESSABLE_ENTITY, response.status());
+        response
+            .into_body()
+            .concat2()
+            .and_then(|b| {
+                let error: ErrorResponse = serde_json::from_slice(&b).unwrap();
+                assert_eq!(
+                    "Image nginx:latest does not match digest sha256:4bd4ea3ba8b9, found sha256:5c2e1fb7a1d0",
+                    error.message()
+                );
+                Ok(())
+            }).wait()
+            .unwrap();
+    }
+
+    #[test]
+    fn untrusted_image() {
+        // arrange
+        let error = DockerError::from(DockerErrorKind::UntrustedImage("nginx:latest".to_string()));
+
+        // act
+        let response = error.into_response();
+
+        // assert
+        assert_eq!(StatusCode::FORBIDDEN, response.status());
+        response
+            .into_body()
+            .concat2()
+            .and_then(|b| {
+                let error: ErrorResponse = serde_json::from_slice(&b).unwrap();
+                assert_eq!(
+                    "Image nginx:latest is not signed by a trusted key",
+                    error.message()
+                );
+                Ok(())
+            }).wait()
+            .unwrap();
+    }
+
+    #[test]
+    fn admission_denied() {
+        // arrange
+        let error = DockerError::from(DockerErrorKind::AdmissionDenied(
+            "mod1".to_string(),
+            "allow_privileged".to_string(),
+        ));
+
+        // act
+        let response = error.into_response();
+
+        // assert
+        assert_eq!(StatusCode::FORBIDDEN, response.status());
+        response
+            .into_body()
+            .concat2()
+            .and_then(|b| {
+                let error: ErrorResponse = serde_json::from_slice(&b).unwrap();
+                assert_eq!(
+                    "Module mod1 violates admission rule allow_privileged",
+                    error.message()
+                );
+                Ok(())
+            }).wait()
+            .unwrap();
+    }
+
+    #[test]
+    fn local_image_not_found() {
+        // arrange
+        let error = DockerError::from(DockerErrorKind::LocalImageNotFound(
+            "nginx:latest".to_string(),
+        ));
+
+        // act
+        let response = error.into_response();
+
+        // assert
+        assert_eq!(StatusCode::NOT_FOUND, response.status());
+        response
+            .into_body()
+            .concat2()
+            .and_then(|b| {
+                let error: ErrorResponse = serde_json::from_slice(&b).unwrap();
+                assert_eq!(
+                    "Image nginx:latest was not found and its pull policy is never",
+                    error.message()
+                );
+                Ok(())
+            }).wait()
+            .unwrap();
+    }
+
     #[test]
     fn internal_server() {
         // arrange
@@ -253,4 +530,143 @@ pub mod tests {
             }).wait()
             .unwrap();
     }
+
+    #[test]
+    fn process_not_modified_has_no_body() {
+        // arrange
+        let error = ProcessError::from(ProcessErrorKind::NotModified);
+
+        // act
+        let response = error.into_response();
+
+        // assert
+        assert_eq!(StatusCode::NOT_MODIFIED, response.status());
+        response
+            .into_body()
+            .concat2()
+            .and_then(|b| {
+                assert!(b.is_empty());
+                Ok(())
+            }).wait()
+            .unwrap();
+    }
+
+    #[test]
+    fn process_not_supported() {
+        // arrange
+        let error = ProcessError::from(ProcessErrorKind::NotSupported("stats"));
+
+        // act
+        let response = error.into_response();
+
+        // assert
+        assert_eq!(StatusCode::NOT_IMPLEMENTED, response.status());
+        response
+            .into_body()
+            .concat2()
+            .and_then(|b| {
+                let error: ErrorResponse = serde_json::from_slice(&b).unwrap();
+                assert_eq!("The process runtime does not support stats", error.message());
+                Ok(())
+            }).wait()
+            .unwrap();
+    }
+
+    #[cfg(feature = "cri")]
+    #[test]
+    fn cri_not_supported() {
+        // arrange
+        let error = CriError::from(CriErrorKind::NotSupported("pause"));
+
+        // act
+        let response = error.into_response();
+
+        // assert
+        assert_eq!(StatusCode::NOT_IMPLEMENTED, response.status());
+        response
+            .into_body()
+            .concat2()
+            .and_then(|b| {
+                let error: ErrorResponse = serde_json::from_slice(&b).unwrap();
+                assert_eq!("The CRI runtime does not support pause", error.message());
+                Ok(())
+            }).wait()
+            .unwrap();
+    }
+
+    #[test]
+    fn memory_conflict() {
+        // arrange
+        let error = MemoryError::Conflict("mod1".to_string());
+
+        // act
+        let response = error.into_response();
+
+        // assert
+        assert_eq!(StatusCode::CONFLICT, response.status());
+        response
+            .into_body()
+            .concat2()
+            .and_then(|b| {
+                let error: ErrorResponse = serde_json::from_slice(&b).unwrap();
+                assert_eq!(
+                    "Conflict with current operation on module mod1",
+                    error.message()
+                );
+                Ok(())
+            }).wait()
+            .unwrap();
+    }
+
+    #[test]
+    fn volume_not_mounted() {
+        // arrange
+        let error = DockerError::from(DockerErrorKind::VolumeNotMounted("mod1-data".to_string()));
+
+        // act
+        let response = error.into_response();
+
+        // assert
+        assert_eq!(StatusCode::CONFLICT, response.status());
+        response
+            .into_body()
+            .concat2()
+            .and_then(|b| {
+                let error: ErrorResponse = serde_json::from_slice(&b).unwrap();
+                assert_eq!(
+                    "Volume mod1-data is not mounted by any module",
+                    error.message()
+                );
+                Ok(())
+            }).wait()
+            .unwrap();
+    }
+
+    #[test]
+    fn memory_volume_errors() {
+        assert_eq!(
+            StatusCode::NOT_FOUND,
+            MemoryError::VolumeNotFound("mod1-data".to_string())
+                .into_response()
+                .status()
+        );
+        assert_eq!(
+            StatusCode::CONFLICT,
+            MemoryError::VolumeInUse("mod1-data".to_string(), "mod1".to_string())
+                .into_response()
+                .status()
+        );
+    }
+
+    #[test]
+    fn memory_injected_failure() {
+        // arrange
+        let error = MemoryError::Injected(Operation::Start);
+
+        // act
+        let response = error.into_response();
+
+        // assert
+        assert_eq!(StatusCode::INTERNAL_SERVER_ERROR, response.status());
+    }
 }
@@ -0,0 +1,168 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+use edgelet_core::{Error as CoreError, ErrorKind as CoreErrorKind, ModuleRuntime};
+use edgelet_http::route::{BoxFuture, Handler, Parameters};
+use failure::Fail;
+use futures::{future, stream, Future, Stream};
+use http::{Request, Response, StatusCode};
+use hyper::{Body, Error as HyperError};
+
+use super::archive_path;
+use error::{Error, ErrorKind};
+use IntoResponse;
+
+/// Extracts the tar archive in the request body into a module. The body is
+/// passed on to the runtime as it arrives.
+pub struct PutModuleArchive<M>
+where
+    M: 'static + ModuleRuntime + Clone,
+{
+    runtime: M,
+}
+
+impl<M> PutModuleArchive<M>
+where
+    M: 'static + ModuleRuntime + Clone,
+{
+    pub fn new(runtime: M) -> Self {
+        PutModuleArchive { runtime }
+    }
+}
+
+impl<M> Handler<Parameters> for PutModuleArchive<M>
+where
+    M: 'static + ModuleRuntime + Clone,
+    M::Error: IntoResponse,
+{
+    fn handle(
+        &self,
+        req: Request<Body>,
+        params: Parameters,
+    ) -> BoxFuture<Response<Body>, HyperError> {
+        let runtime = self.runtime.clone();
+        let response = params
+            .name("name")
+            .ok_or_else(|| Error::from(ErrorKind::BadParam))
+            .and_then(|name| Ok((name.to_string(), archive_path(&req)?)))
+            .map(|(name, path)| {
+                // the first chunk tells an empty body apart
+                let result = req
+                    .into_body()
+                    .into_future()
+                    .map_err(|(e, _)| e)
+                    .and_then(move |(first, rest)| match first {
+                        Some(first) => {
+                            let archive = stream::once(Ok(first))
+                                .chain(rest)
+                                .map(|chunk| chunk.to_vec())
+                                .map_err(|e| CoreError::from(e.context(CoreErrorKind::Http)));
+                            let put = runtime
+                                .put_archive(&name, &path, Box::new(archive))
+                                .map(|_| {
+                                    Response::builder()
+                                        .status(StatusCode::NO_CONTENT)
+                                        .body(Body::default())
+                                        .unwrap_or_else(|e| e.into_response())
+                                }).or_else(|e| future::ok(e.into_response()));
+                            future::Either::A(put)
+                        }
+                        None => future::Either::B(future::ok(
+                            Error::from(ErrorKind::BadBody).into_response(),
+                        )),
+                    }).or_else(|e| future::ok(e.into_response()));
+                future::Either::A(result)
+            }).unwrap_or_else(|e| future::Either::B(future::ok(e.into_response())));
+        Box::new(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use edgelet_core::{ModuleRuntimeState, ModuleStatus};
+    use edgelet_test_utils::module::*;
+    use management::models::ErrorResponse;
+    use serde_json;
+    use server::module::tests::Error;
+
+    fn runtime() -> TestRuntime<Error> {
+        let state = ModuleRuntimeState::default().with_status(ModuleStatus::Running);
+        let config = TestConfig::new("microsoft/test-image".to_string());
+        let module: TestModule<Error> =
+            TestModule::new("test-module".to_string(), config, Ok(state));
+        TestRuntime::new(Ok(module))
+    }
+
+    fn parameters() -> Parameters {
+        Parameters::with_captures(vec![(Some("name".to_string()), "mod1".to_string())])
+    }
+
+    #[test]
+    fn success() {
+        let handler = PutModuleArchive::new(runtime());
+        let request = Request::put(
+            "http://localhost/modules/mod1/archive?api-version=2018-06-28&path=%2Fetc",
+        ).body(b"archive".to_vec().into())
+        .unwrap();
+
+        // act
+        let response = handler.handle(request, parameters()).wait().unwrap();
+
+        // assert
+        assert_eq!(StatusCode::NO_CONTENT, response.status());
+    }
+
+    #[test]
+    fn empty_archive_fails() {
+        let handler = PutModuleArchive::new(runtime());
+        let request = Request::put(
+            "http://localhost/modules/mod1/archive?api-version=2018-06-28&path=%2Fetc",
+        ).body(Body::default())
+        .unwrap();
+
+        // act
+        let response = handler.handle(request, parameters()).wait().unwrap();
+
+        // assert
+        assert_eq!(StatusCode::BAD_REQUEST, response.status());
+        response
+            .into_body()
+            .concat2()
+            .and_then(|b| {
+                let error: ErrorResponse = serde_json::from_slice(&b).unwrap();
+                assert_eq!("Bad body", error.message());
+                Ok(())
+            }).wait()
+            .unwrap();
+    }
+
+    #[test]
+    fn missing_path_fails() {
+        let handler = PutModuleArchive::new(runtime());
+        let request = Request::put("http://localhost/modules/mod1/archive?api-version=2018-06-28")
+            .body(b"archive".to_vec().into())
+            .unwrap();
+
+        // act
+        let response = handler.handle(request, parameters()).wait().unwrap();
+
+        // assert
+        assert_eq!(StatusCode::BAD_REQUEST, response.status());
+    }
+
+    #[test]
+    fn runtime_error() {
+        let handler = PutModuleArchive::new(TestRuntime::new(Err(Error::General)));
+        let request = Request::put(
+            "http://localhost/modules/mod1/archive?api-version=2018-06-28&path=%2Fetc",
+        ).body(b"archive".to_vec().into())
+        .unwrap();
+
+        // act
+        let response = handler.handle(request, parameters()).wait().unwrap();
+
+        // assert
+        assert_eq!(StatusCode::INTERNAL_SERVER_ERROR, response.status());
+    }
+}
@@ -0,0 +1,273 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+use edgelet_core::{ModuleRuntime, ModuleStats as CoreModuleStats, StatsOptions};
+use edgelet_http::route::{BoxFuture, Handler, Parameters};
+use failure::ResultExt;
+use futures::{future, Future, Stream};
+use http::header::{CONTENT_LENGTH, CONTENT_TYPE};
+use http::{Request, Response, StatusCode};
+use hyper::{Body, Error as HyperError};
+use management::models::ModuleStats as HttpModuleStats;
+use serde_json;
+use tokio_core::reactor::Handle;
+use url::form_urlencoded;
+
+use super::json_lines_response;
+use error::{Error, ErrorKind};
+use IntoResponse;
+
+pub struct ModuleStats<M>
+where
+    M: 'static + ModuleRuntime + Clone,
+{
+    runtime: M,
+    handle: Handle,
+}
+
+impl<M> ModuleStats<M>
+where
+    M: 'static + ModuleRuntime + Clone,
+{
+    pub fn new(runtime: M, handle: Handle) -> Self {
+        ModuleStats { runtime, handle }
+    }
+}
+
+impl<M> Handler<Parameters> for ModuleStats<M>
+where
+    M: 'static + ModuleRuntime + Clone,
+    M::Error: IntoResponse,
+{
+    fn handle(
+        &self,
+        req: Request<Body>,
+        params: Parameters,
+    ) -> BoxFuture<Response<Body>, HyperError> {
+        let runtime = self.runtime.clone();
+        let handle = self.handle.clone();
+        let response = params
+            .name("name")
+            .ok_or_else(|| Error::from(ErrorKind::BadParam))
+            .and_then(|name| {
+                let options = req
+                    .uri()
+                    .query()
+                    .map(parse_options)
+                    .unwrap_or_else(|| Ok(StatsOptions::default()))
+                    .context(ErrorKind::BadParam);
+                Ok((name, options?))
+            }).map(|(name, options)| {
+                let name_copy = name.to_string();
+                let result = runtime
+                    .stats(name, &options)
+                    .and_then(move |stats| {
+                        if options.stream() {
+                            future::Either::A(future::ok(json_lines_response(
+                                stats.map(|sample| http_stats(&sample)),
+                                &handle,
+                            )))
+                        } else {
+                            future::Either::B(
+                                stats
+                                    .into_future()
+                                    .map(move |(sample, _)| single_response(&name_copy, sample))
+                                    .map_err(|(e, _)| e),
+                            )
+                        }
+                    }).or_else(|e| future::ok(e.into_response()));
+                future::Either::A(result)
+            }).unwrap_or_else(|e| future::Either::B(future::ok(e.into_response())));
+        Box::new(response)
+    }
+}
+
+// A module that is not running has no sample to report.
+fn single_response(name: &str, sample: Option<CoreModuleStats>) -> Response<Body> {
+    sample
+        .ok_or_else(|| Error::from(ErrorKind::NoStats(name.to_string())))
+        .and_then(|sample| {
+            serde_json::to_string(&http_stats(&sample))
+                .context(ErrorKind::Serde)
+                .map_err(Error::from)
+        }).map(|b| {
+            Response::builder()
+                .status(StatusCode::OK)
+                .header(CONTENT_TYPE, "application/json")
+                .header(CONTENT_LENGTH, b.len().to_string().as_str())
+                .body(b.into())
+                .unwrap_or_else(|e| e.into_response())
+        }).unwrap_or_else(|e| e.into_response())
+}
+
+fn http_stats(stats: &CoreModuleStats) -> HttpModuleStats {
+    let mut http_stats = HttpModuleStats::new(
+        stats.cpu_percent(),
+        stats.cpu_total_usage() as i64,
+        stats.memory_usage() as i64,
+        stats.memory_limit() as i64,
+        stats.network_rx_bytes() as i64,
+        stats.network_tx_bytes() as i64,
+        stats.block_read_bytes() as i64,
+        stats.block_write_bytes() as i64,
+    );
+    if let Some(read) = stats.read() {
+        http_stats.set_read_time(read.to_rfc3339());
+    }
+    http_stats
+}
+
+fn parse_options(query: &str) -> Result<StatsOptions, Error> {
+    let stream = form_urlencoded::parse(query.as_bytes())
+        .find(|&(ref key, _)| key == "stream")
+        .map(|(_, val)| val.parse::<bool>())
+        .unwrap_or_else(|| Ok(false))?;
+    Ok(StatsOptions::new().with_stream(stream))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use edgelet_core::{ModuleRuntimeState, ModuleStatus};
+    use edgelet_test_utils::module::*;
+    use management::models::ErrorResponse;
+    use server::module::tests::Error;
+    use tokio_core::reactor::Core;
+
+    #[test]
+    fn correct_statsoptions() {
+        let options = parse_options("stream=true").unwrap();
+        assert_eq!(true, options.stream());
+    }
+
+    #[test]
+    fn statsoptions_defaults() {
+        let options = parse_options("").unwrap();
+        assert_eq!(false, options.stream());
+    }
+
+    #[test]
+    fn statsoptions_stream_error() {
+        let options = parse_options("stream=34");
+        assert!(options.is_err());
+        assert_eq!("Parse error", options.err().unwrap().to_string());
+    }
+
+    #[test]
+    fn test_success() {
+        let core = Core::new().unwrap();
+        let config = TestConfig::new("microsoft/test-image".to_string());
+        let state = ModuleRuntimeState::default().with_status(ModuleStatus::Running);
+        let module: TestModule<Error> =
+            TestModule::new("test-module".to_string(), config, Ok(state));
+        let runtime = TestRuntime::new(Ok(module));
+        let handler = ModuleStats::new(runtime, core.handle());
+        let request = Request::get("http://localhost/modules/mod1/stats?api-version=2018-06-28")
+            .body(Body::default())
+            .unwrap();
+        let parameters =
+            Parameters::with_captures(vec![(Some("name".to_string()), "mod1".to_string())]);
+
+        // act
+        let response = handler.handle(request, parameters).wait().unwrap();
+
+        // assert
+        assert_eq!(StatusCode::OK, response.status());
+        response
+            .into_body()
+            .concat2()
+            .and_then(|b| {
+                let stats: HttpModuleStats = serde_json::from_slice(&b).unwrap();
+                assert_eq!(None, stats.read_time());
+                assert_eq!(0, *stats.memory_usage());
+                Ok(())
+            }).wait()
+            .unwrap();
+    }
+
+    #[test]
+    fn test_stream_success() {
+        let mut core = Core::new().unwrap();
+        let config = TestConfig::new("microsoft/test-image".to_string());
+        let state = ModuleRuntimeState::default().with_status(ModuleStatus::Running);
+        let module: TestModule<Error> =
+            TestModule::new("test-module".to_string(), config, Ok(state));
+        let runtime = TestRuntime::new(Ok(module));
+        let handler = ModuleStats::new(runtime, core.handle());
+        let request = Request::get(
+            "http://localhost/modules/mod1/stats?api-version=2018-06-28&stream=true",
+        ).body(Body::default())
+        .unwrap();
+        let parameters =
+            Parameters::with_captures(vec![(Some("name".to_string()), "mod1".to_string())]);
+
+        // act
+        let response = core.run(handler.handle(request, parameters)).unwrap();
+
+        // assert
+        assert_eq!(StatusCode::OK, response.status());
+        let body = core.run(response.into_body().concat2()).unwrap();
+        let lines: Vec<&[u8]> = body.split(|b| *b == b'\n').filter(|l| !l.is_empty()).collect();
+        assert_eq!(1, lines.len());
+        let stats: HttpModuleStats = serde_json::from_slice(lines[0]).unwrap();
+        assert_eq!(0, *stats.cpu_total_usage());
+    }
+
+    #[test]
+    fn no_sample_is_not_found() {
+        let core = Core::new().unwrap();
+        let config = TestConfig::new("microsoft/test-image".to_string());
+        let state = ModuleRuntimeState::default().with_status(ModuleStatus::Stopped);
+        let module: TestModule<Error> =
+            TestModule::new("test-module".to_string(), config, Ok(state));
+        let runtime = TestRuntime::new(Ok(module)).with_stats(vec![]);
+        let handler = ModuleStats::new(runtime, core.handle());
+        let request = Request::get("http://localhost/modules/mod1/stats?api-version=2018-06-28")
+            .body(Body::default())
+            .unwrap();
+        let parameters =
+            Parameters::with_captures(vec![(Some("name".to_string()), "mod1".to_string())]);
+
+        // act
+        let response = handler.handle(request, parameters).wait().unwrap();
+
+        // assert
+        assert_eq!(StatusCode::NOT_FOUND, response.status());
+        response
+            .into_body()
+            .concat2()
+            .and_then(|b| {
+                let error: ErrorResponse = serde_json::from_slice(&b).unwrap();
+                assert_eq!("No stats available for module mod1", error.message());
+                Ok(())
+            }).wait()
+            .unwrap();
+    }
+
+    #[test]
+    fn runtime_error() {
+        let core = Core::new().unwrap();
+        let runtime = TestRuntime::new(Err(Error::General));
+        let handler = ModuleStats::new(runtime, core.handle());
+        let request = Request::get("http://localhost/modules/mod1/stats?api-version=2018-06-28")
+            .body(Body::default())
+            .unwrap();
+        let parameters =
+            Parameters::with_captures(vec![(Some("name".to_string()), "mod1".to_string())]);
+
+        // act
+        let response = handler.handle(request, parameters).wait().unwrap();
+
+        // assert
+        assert_eq!(StatusCode::INTERNAL_SERVER_ERROR, response.status());
+        response
+            .into_body()
+            .concat2()
+            .and_then(|b| {
+                let error: ErrorResponse = serde_json::from_slice(&b).unwrap();
+                assert_eq!("General error", error.message());
+                Ok(())
+            }).wait()
+            .unwrap();
+    }
+}
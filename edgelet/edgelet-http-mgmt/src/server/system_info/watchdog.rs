@@ -0,0 +1,97 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+use edgelet_core::watchdog::{WatchdogState, WatchdogStatus as CoreWatchdogStatus};
+use edgelet_http::route::{BoxFuture, Handler, Parameters};
+use failure::ResultExt;
+use futures::future;
+use http::header::{CONTENT_LENGTH, CONTENT_TYPE};
+use http::{Request, Response, StatusCode};
+use hyper::{Body, Error as HyperError};
+use management::models::WatchdogStatus;
+use serde_json;
+
+use error::ErrorKind;
+use IntoResponse;
+
+pub struct GetWatchdogStatus {
+    status: CoreWatchdogStatus,
+}
+
+impl GetWatchdogStatus {
+    pub fn new(status: CoreWatchdogStatus) -> Self {
+        GetWatchdogStatus { status }
+    }
+}
+
+impl Handler<Parameters> for GetWatchdogStatus {
+    fn handle(
+        &self,
+        _req: Request<Body>,
+        _params: Parameters,
+    ) -> BoxFuture<Response<Body>, HyperError> {
+        debug!("Get Watchdog Status");
+        let body = http_status(&self.status.state());
+        let response = serde_json::to_string(&body)
+            .context(ErrorKind::Serde)
+            .map(|b| {
+                Response::builder()
+                    .status(StatusCode::OK)
+                    .header(CONTENT_TYPE, "application/json")
+                    .header(CONTENT_LENGTH, b.len().to_string().as_str())
+                    .body(b.into())
+                    .unwrap_or_else(|e| e.into_response())
+            }).unwrap_or_else(|e| e.into_response());
+
+        Box::new(future::ok(response))
+    }
+}
+
+fn http_status(state: &WatchdogState) -> WatchdogStatus {
+    let mut status = WatchdogStatus::new(
+        state.restart_count() as i64,
+        state.consecutive_failures() as i32,
+        state.crash_loop(),
+    );
+    if let Some(last_restart) = state.last_restart() {
+        status.set_last_restart(last_restart.to_rfc3339());
+    }
+    if let Some(next_attempt) = state.next_attempt() {
+        status.set_next_attempt(next_attempt.to_rfc3339());
+    }
+    status
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::{Future, Stream};
+
+    use super::*;
+
+    #[test]
+    fn watchdog_status_success() {
+        // arrange
+        let handler = GetWatchdogStatus::new(CoreWatchdogStatus::new());
+        let request = Request::get("http://localhost/watchdog")
+            .body(Body::default())
+            .unwrap();
+
+        // act
+        let response = handler.handle(request, Parameters::new()).wait().unwrap();
+
+        // assert
+        assert_eq!(StatusCode::OK, response.status());
+        response
+            .into_body()
+            .concat2()
+            .and_then(|b| {
+                let status: WatchdogStatus = serde_json::from_slice(&b).unwrap();
+                assert_eq!(0, *status.restart_count());
+                assert_eq!(0, *status.consecutive_failures());
+                assert_eq!(false, *status.crash_loop());
+                assert_eq!(None, status.last_restart());
+                assert_eq!(None, status.next_attempt());
+                Ok(())
+            }).wait()
+            .unwrap();
+    }
+}
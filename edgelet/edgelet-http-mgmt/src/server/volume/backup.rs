@@ -0,0 +1,118 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+use edgelet_core::ModuleRuntime;
+use edgelet_http::route::{BoxFuture, Handler, Parameters};
+use futures::{future, Future};
+use http::header::CONTENT_TYPE;
+use http::{Request, Response, StatusCode};
+use hyper::{Body, Error as HyperError};
+
+use error::{Error, ErrorKind};
+use IntoResponse;
+
+/// Returns a tar archive of the data in a volume, so that it can be kept
+/// when the volume is removed.
+pub struct BackupVolume<M>
+where
+    M: 'static + ModuleRuntime,
+    M::Error: IntoResponse,
+    M::Logs: Into<Body>,
+{
+    runtime: M,
+}
+
+impl<M> BackupVolume<M>
+where
+    M: 'static + ModuleRuntime,
+    M::Error: IntoResponse,
+    M::Logs: Into<Body>,
+{
+    pub fn new(runtime: M) -> Self {
+        BackupVolume { runtime }
+    }
+}
+
+impl<M> Handler<Parameters> for BackupVolume<M>
+where
+    M: 'static + ModuleRuntime,
+    M::Error: IntoResponse,
+    M::Logs: Into<Body>,
+{
+    fn handle(
+        &self,
+        _req: Request<Body>,
+        params: Parameters,
+    ) -> BoxFuture<Response<Body>, HyperError> {
+        let response = params
+            .name("name")
+            .ok_or_else(|| Error::from(ErrorKind::BadParam))
+            .map(|name| {
+                debug!("Back up volume {}", name);
+                let result = self
+                    .runtime
+                    .backup_volume(name)
+                    .map(|archive| {
+                        Response::builder()
+                            .status(StatusCode::OK)
+                            .header(CONTENT_TYPE, "application/x-tar")
+                            .body(archive.into())
+                            .unwrap_or_else(|e| e.into_response())
+                    }).or_else(|e| future::ok(e.into_response()));
+                future::Either::A(result)
+            }).unwrap_or_else(|e| future::Either::B(future::ok(e.into_response())));
+        Box::new(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use edgelet_memory::MemoryRuntime;
+    use futures::Stream;
+
+    use super::*;
+
+    fn backup(runtime: MemoryRuntime, name: &str) -> Response<Body> {
+        let handler = BackupVolume::new(runtime);
+        let parameters =
+            Parameters::with_captures(vec![(Some("name".to_string()), name.to_string())]);
+        let request =
+            Request::get("http://localhost/volumes/mod1-data/backup?api-version=2018-06-28")
+                .body(Body::default())
+                .unwrap();
+        handler.handle(request, parameters).wait().unwrap()
+    }
+
+    #[test]
+    fn backup_returns_archive() {
+        // arrange
+        let runtime = MemoryRuntime::new();
+        runtime.create_volume("mod1-data", None).wait().unwrap();
+
+        // act
+        let response = backup(runtime, "mod1-data");
+
+        // assert
+        assert_eq!(StatusCode::OK, response.status());
+        assert_eq!(
+            "application/x-tar",
+            response.headers().get(CONTENT_TYPE).unwrap()
+        );
+        response
+            .into_body()
+            .concat2()
+            .and_then(|b| {
+                assert_eq!(&b"mod1-data"[..], b.as_ref());
+                Ok(())
+            }).wait()
+            .unwrap();
+    }
+
+    #[test]
+    fn backup_unknown_volume() {
+        // act
+        let response = backup(MemoryRuntime::new(), "mod1-data");
+
+        // assert
+        assert_eq!(StatusCode::NOT_FOUND, response.status());
+    }
+}
@@ -0,0 +1,123 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+use edgelet_core::ModuleRuntime;
+use edgelet_http::route::{BoxFuture, Handler, Parameters};
+use failure::ResultExt;
+use futures::{future, Future, Stream};
+use http::header::{CONTENT_LENGTH, CONTENT_TYPE};
+use http::{Request, Response, StatusCode};
+use hyper::{Body, Error as HyperError};
+use management::models::VolumeSpec;
+use serde_json;
+
+use super::core_to_volume;
+use error::{Error, ErrorKind};
+use IntoResponse;
+
+pub struct CreateVolume<M>
+where
+    M: 'static + ModuleRuntime + Clone,
+    M::Error: IntoResponse,
+{
+    runtime: M,
+}
+
+impl<M> CreateVolume<M>
+where
+    M: 'static + ModuleRuntime + Clone,
+    M::Error: IntoResponse,
+{
+    pub fn new(runtime: M) -> Self {
+        CreateVolume { runtime }
+    }
+}
+
+impl<M> Handler<Parameters> for CreateVolume<M>
+where
+    M: 'static + ModuleRuntime + Clone,
+    M::Error: IntoResponse,
+{
+    fn handle(
+        &self,
+        req: Request<Body>,
+        _params: Parameters,
+    ) -> BoxFuture<Response<Body>, HyperError> {
+        let runtime = self.runtime.clone();
+        let response = req
+            .into_body()
+            .concat2()
+            .and_then(move |b| {
+                serde_json::from_slice::<VolumeSpec>(&b)
+                    .context(ErrorKind::BadBody)
+                    .map_err(Error::from)
+                    .map(|spec| {
+                        debug!("Create volume {}", spec.name());
+                        let created = runtime
+                            .create_volume(spec.name(), spec.module().map(String::as_str))
+                            .map(|volume| {
+                                serde_json::to_string(&core_to_volume(&volume))
+                                    .context(ErrorKind::Serde)
+                                    .map(|b| {
+                                        Response::builder()
+                                            .status(StatusCode::CREATED)
+                                            .header(CONTENT_TYPE, "application/json")
+                                            .header(CONTENT_LENGTH, b.len().to_string().as_str())
+                                            .body(b.into())
+                                            .unwrap_or_else(|e| e.into_response())
+                                    }).unwrap_or_else(|e| e.into_response())
+                            }).or_else(|e| future::ok(e.into_response()));
+                        future::Either::A(created)
+                    }).unwrap_or_else(|e| future::Either::B(future::ok(e.into_response())))
+            }).or_else(|e| future::ok(e.into_response()));
+        Box::new(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use edgelet_memory::MemoryRuntime;
+    use management::models::Volume;
+
+    use super::*;
+
+    fn create(runtime: &MemoryRuntime, body: &str) -> Response<Body> {
+        let handler = CreateVolume::new(runtime.clone());
+        let request = Request::post("http://localhost/volumes?api-version=2018-06-28")
+            .body(body.to_string().into())
+            .unwrap();
+        handler.handle(request, Parameters::new()).wait().unwrap()
+    }
+
+    #[test]
+    fn create_returns_volume() {
+        // arrange
+        let runtime = MemoryRuntime::new();
+
+        // act
+        let response = create(&runtime, r#"{"name": "mod1-data", "module": "mod1"}"#);
+
+        // assert
+        assert_eq!(StatusCode::CREATED, response.status());
+        response
+            .into_body()
+            .concat2()
+            .and_then(|b| {
+                let volume: Volume = serde_json::from_slice(&b).unwrap();
+                assert_eq!("mod1-data", volume.name());
+                assert_eq!(Some(&"mod1".to_string()), volume.module());
+                Ok(())
+            }).wait()
+            .unwrap();
+        let volume = runtime.inspect_volume("mod1-data").wait().unwrap();
+        assert_eq!(Some("mod1"), volume.module());
+    }
+
+    #[test]
+    fn create_bad_body() {
+        // act
+        let response = create(&MemoryRuntime::new(), r#"{"module": "mod1"}"#);
+
+        // assert
+        assert_eq!(StatusCode::BAD_REQUEST, response.status());
+    }
+}
@@ -0,0 +1,124 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+use edgelet_core::ModuleRuntime;
+use edgelet_http::route::{BoxFuture, Handler, Parameters};
+use futures::{future, Future};
+use http::{Request, Response, StatusCode};
+use hyper::{Body, Error as HyperError};
+
+use error::{Error, ErrorKind};
+use IntoResponse;
+
+pub struct DeleteVolume<M>
+where
+    M: 'static + ModuleRuntime,
+    M::Error: IntoResponse,
+{
+    runtime: M,
+}
+
+impl<M> DeleteVolume<M>
+where
+    M: 'static + ModuleRuntime,
+    M::Error: IntoResponse,
+{
+    pub fn new(runtime: M) -> Self {
+        DeleteVolume { runtime }
+    }
+}
+
+impl<M> Handler<Parameters> for DeleteVolume<M>
+where
+    M: 'static + ModuleRuntime,
+    M::Error: IntoResponse,
+{
+    fn handle(
+        &self,
+        _req: Request<Body>,
+        params: Parameters,
+    ) -> BoxFuture<Response<Body>, HyperError> {
+        let response = params
+            .name("name")
+            .ok_or_else(|| Error::from(ErrorKind::BadParam))
+            .map(|name| {
+                debug!("Remove volume {}", name);
+                let result = self
+                    .runtime
+                    .remove_volume(name)
+                    .map(|_| {
+                        Response::builder()
+                            .status(StatusCode::NO_CONTENT)
+                            .body(Body::default())
+                            .unwrap_or_else(|e| e.into_response())
+                    }).or_else(|e| future::ok(e.into_response()));
+                future::Either::A(result)
+            }).unwrap_or_else(|e| future::Either::B(future::ok(e.into_response())));
+        Box::new(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use edgelet_core::ModuleSpec;
+    use edgelet_memory::{MemoryConfig, MemoryRuntime};
+
+    use super::*;
+
+    fn delete(runtime: &MemoryRuntime, name: &str) -> StatusCode {
+        let handler = DeleteVolume::new(runtime.clone());
+        let parameters =
+            Parameters::with_captures(vec![(Some("name".to_string()), name.to_string())]);
+        let request = Request::delete("http://localhost/volumes/mod1-data?api-version=2018-06-28")
+            .body(Body::default())
+            .unwrap();
+        handler.handle(request, parameters).wait().unwrap().status()
+    }
+
+    #[test]
+    fn delete_removes_volume() {
+        // arrange
+        let runtime = MemoryRuntime::new();
+        runtime.create_volume("mod1-data", None).wait().unwrap();
+
+        // act
+        let status = delete(&runtime, "mod1-data");
+
+        // assert
+        assert_eq!(StatusCode::NO_CONTENT, status);
+        assert!(runtime.list_volumes().wait().unwrap().is_empty());
+    }
+
+    #[test]
+    fn delete_volume_in_use() {
+        // arrange
+        let runtime = MemoryRuntime::new();
+        let spec = ModuleSpec::new(
+            "mod1",
+            "docker",
+            MemoryConfig::new("microsoft/test-image".to_string()),
+            HashMap::new(),
+        ).unwrap();
+        runtime.create(spec).wait().unwrap();
+        runtime
+            .create_volume("mod1-data", Some("mod1"))
+            .wait()
+            .unwrap();
+
+        // act
+        let status = delete(&runtime, "mod1-data");
+
+        // assert
+        assert_eq!(StatusCode::CONFLICT, status);
+        assert_eq!(1, runtime.list_volumes().wait().unwrap().len());
+    }
+
+    #[test]
+    fn delete_unknown_volume() {
+        assert_eq!(
+            StatusCode::NOT_FOUND,
+            delete(&MemoryRuntime::new(), "mod1-data")
+        );
+    }
+}
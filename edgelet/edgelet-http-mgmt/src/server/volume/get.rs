@@ -0,0 +1,121 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+use edgelet_core::ModuleRuntime;
+use edgelet_http::route::{BoxFuture, Handler, Parameters};
+use failure::ResultExt;
+use futures::{future, Future};
+use http::header::{CONTENT_LENGTH, CONTENT_TYPE};
+use http::{Request, Response, StatusCode};
+use hyper::{Body, Error as HyperError};
+use serde_json;
+
+use super::core_to_volume;
+use error::{Error, ErrorKind};
+use IntoResponse;
+
+pub struct GetVolume<M>
+where
+    M: 'static + ModuleRuntime,
+    M::Error: IntoResponse,
+{
+    runtime: M,
+}
+
+impl<M> GetVolume<M>
+where
+    M: 'static + ModuleRuntime,
+    M::Error: IntoResponse,
+{
+    pub fn new(runtime: M) -> Self {
+        GetVolume { runtime }
+    }
+}
+
+impl<M> Handler<Parameters> for GetVolume<M>
+where
+    M: 'static + ModuleRuntime,
+    M::Error: IntoResponse,
+{
+    fn handle(
+        &self,
+        _req: Request<Body>,
+        params: Parameters,
+    ) -> BoxFuture<Response<Body>, HyperError> {
+        let response = params
+            .name("name")
+            .ok_or_else(|| Error::from(ErrorKind::BadParam))
+            .map(|name| {
+                let result = self
+                    .runtime
+                    .inspect_volume(name)
+                    .map(|volume| {
+                        serde_json::to_string(&core_to_volume(&volume))
+                            .context(ErrorKind::Serde)
+                            .map(|b| {
+                                Response::builder()
+                                    .status(StatusCode::OK)
+                                    .header(CONTENT_TYPE, "application/json")
+                                    .header(CONTENT_LENGTH, b.len().to_string().as_str())
+                                    .body(b.into())
+                                    .unwrap_or_else(|e| e.into_response())
+                            }).unwrap_or_else(|e| e.into_response())
+                    }).or_else(|e| future::ok(e.into_response()));
+                future::Either::A(result)
+            }).unwrap_or_else(|e| future::Either::B(future::ok(e.into_response())));
+        Box::new(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use edgelet_memory::MemoryRuntime;
+    use futures::Stream;
+    use management::models::Volume;
+
+    use super::*;
+
+    fn get(runtime: MemoryRuntime, name: &str) -> Response<Body> {
+        let handler = GetVolume::new(runtime);
+        let parameters =
+            Parameters::with_captures(vec![(Some("name".to_string()), name.to_string())]);
+        let request = Request::get("http://localhost/volumes/mod1-data?api-version=2018-06-28")
+            .body(Body::default())
+            .unwrap();
+        handler.handle(request, parameters).wait().unwrap()
+    }
+
+    #[test]
+    fn get_returns_volume() {
+        // arrange
+        let runtime = MemoryRuntime::new();
+        runtime
+            .create_volume("mod1-data", Some("mod1"))
+            .wait()
+            .unwrap();
+
+        // act
+        let response = get(runtime, "mod1-data");
+
+        // assert
+        assert_eq!(StatusCode::OK, response.status());
+        response
+            .into_body()
+            .concat2()
+            .and_then(|b| {
+                let volume: Volume = serde_json::from_slice(&b).unwrap();
+                assert_eq!("mod1-data", volume.name());
+                assert_eq!(Some(&"mod1".to_string()), volume.module());
+                Ok(())
+            }).wait()
+            .unwrap();
+    }
+
+    #[test]
+    fn get_unknown_volume() {
+        // act
+        let response = get(MemoryRuntime::new(), "mod1-data");
+
+        // assert
+        assert_eq!(StatusCode::NOT_FOUND, response.status());
+    }
+}
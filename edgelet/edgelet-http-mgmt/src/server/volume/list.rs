@@ -0,0 +1,124 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+use edgelet_core::ModuleRuntime;
+use edgelet_http::route::{BoxFuture, Handler, Parameters};
+use failure::ResultExt;
+use futures::{future, Future};
+use http::header::{CONTENT_LENGTH, CONTENT_TYPE};
+use http::{Request, Response, StatusCode};
+use hyper::{Body, Error as HyperError};
+use management::models::VolumeList;
+use serde_json;
+
+use super::core_to_volume;
+use error::ErrorKind;
+use IntoResponse;
+
+pub struct ListVolumes<M>
+where
+    M: 'static + ModuleRuntime,
+    M::Error: IntoResponse,
+{
+    runtime: M,
+}
+
+impl<M> ListVolumes<M>
+where
+    M: 'static + ModuleRuntime,
+    M::Error: IntoResponse,
+{
+    pub fn new(runtime: M) -> Self {
+        ListVolumes { runtime }
+    }
+}
+
+impl<M> Handler<Parameters> for ListVolumes<M>
+where
+    M: 'static + ModuleRuntime,
+    M::Error: IntoResponse,
+{
+    fn handle(
+        &self,
+        _req: Request<Body>,
+        _params: Parameters,
+    ) -> BoxFuture<Response<Body>, HyperError> {
+        let response = self
+            .runtime
+            .list_volumes()
+            .and_then(|volumes| {
+                let body = VolumeList::new(volumes.iter().map(core_to_volume).collect());
+                let response = serde_json::to_string(&body)
+                    .context(ErrorKind::Serde)
+                    .map(|b| {
+                        Response::builder()
+                            .status(StatusCode::OK)
+                            .header(CONTENT_TYPE, "application/json")
+                            .header(CONTENT_LENGTH, b.len().to_string().as_str())
+                            .body(b.into())
+                            .unwrap_or_else(|e| e.into_response())
+                    }).unwrap_or_else(|e| e.into_response());
+                future::ok(response)
+            }).or_else(|e| future::ok(e.into_response()));
+
+        Box::new(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use edgelet_memory::{MemoryRuntime, Operation};
+    use futures::Stream;
+
+    use super::*;
+
+    #[test]
+    fn list_returns_volumes() {
+        // arrange
+        let runtime = MemoryRuntime::new();
+        runtime
+            .create_volume("mod1-data", Some("mod1"))
+            .wait()
+            .unwrap();
+        runtime.create_volume("shared", None).wait().unwrap();
+        let handler = ListVolumes::new(runtime);
+        let request = Request::get("http://localhost/volumes?api-version=2018-06-28")
+            .body(Body::default())
+            .unwrap();
+
+        // act
+        let response = handler.handle(request, Parameters::new()).wait().unwrap();
+
+        // assert
+        assert_eq!(StatusCode::OK, response.status());
+        response
+            .into_body()
+            .concat2()
+            .and_then(|b| {
+                let list: VolumeList = serde_json::from_slice(&b).unwrap();
+                let volumes = list.volumes();
+                assert_eq!(2, volumes.len());
+                assert_eq!("mod1-data", volumes[0].name());
+                assert_eq!(Some(&"mod1".to_string()), volumes[0].module());
+                assert_eq!("shared", volumes[1].name());
+                assert_eq!(None, volumes[1].module());
+                Ok(())
+            }).wait()
+            .unwrap();
+    }
+
+    #[test]
+    fn list_failed() {
+        // arrange
+        let runtime = MemoryRuntime::new().with_failure(Operation::ListVolumes);
+        let handler = ListVolumes::new(runtime);
+        let request = Request::get("http://localhost/volumes?api-version=2018-06-28")
+            .body(Body::default())
+            .unwrap();
+
+        // act
+        let response = handler.handle(request, Parameters::new()).wait().unwrap();
+
+        // assert
+        assert_eq!(StatusCode::INTERNAL_SERVER_ERROR, response.status());
+    }
+}
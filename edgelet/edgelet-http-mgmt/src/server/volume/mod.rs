@@ -0,0 +1,31 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+use edgelet_core::Volume as CoreVolume;
+use management::models::Volume;
+
+mod backup;
+mod create;
+mod delete;
+mod get;
+mod list;
+
+pub use self::backup::BackupVolume;
+pub use self::create::CreateVolume;
+pub use self::delete::DeleteVolume;
+pub use self::get::GetVolume;
+pub use self::list::ListVolumes;
+
+fn core_to_volume(volume: &CoreVolume) -> Volume {
+    let mut result = Volume::new(
+        volume.name().to_string(),
+        volume.driver().to_string(),
+        volume.mountpoint().to_string(),
+    );
+    if let Some(module) = volume.module() {
+        result.set_module(module.to_string());
+    }
+    if let Some(created) = volume.created() {
+        result.set_created(created.to_rfc3339());
+    }
+    result
+}
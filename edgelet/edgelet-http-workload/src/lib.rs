@@ -30,7 +30,7 @@ use hyper::Body;
 mod error;
 mod server;
 
-pub use server::WorkloadService;
+pub use server::{WorkloadService, ROUTES};
 
 pub trait IntoResponse {
     fn into_response(self) -> Response<Body>;
@@ -7,10 +7,11 @@ mod sign;
 mod trust_bundle;
 
 use std::io;
+use std::sync::Arc;
 
 use edgelet_core::{
     CreateCertificate, Decrypt, Encrypt, Error as CoreError, GetTrustBundle, KeyStore, Module,
-    ModuleRuntime, Policy,
+    ModuleIndex, ModuleRuntime, Policy, RoutePolicies,
 };
 use edgelet_http::authorization::Authorization;
 use edgelet_http::route::*;
@@ -24,13 +25,29 @@ use self::encrypt::EncryptHandler;
 use self::sign::SignHandler;
 use self::trust_bundle::TrustBundleHandler;
 
+/// The routes of the workload API, as named by the authorization policies in
+/// the settings.
+pub const ROUTES: &[&str] = &[
+    "POST /modules/{name}/genid/{genid}/sign",
+    "POST /modules/{name}/genid/{genid}/decrypt",
+    "POST /modules/{name}/genid/{genid}/encrypt",
+    "POST /modules/{name}/certificate/identity",
+    "POST /modules/{name}/genid/{genid}/certificate/server",
+    "GET /trust-bundle",
+];
+
 #[derive(Clone)]
 pub struct WorkloadService {
     inner: RouterService<RegexRecognizer>,
 }
 
 impl WorkloadService {
-    pub fn new<K, H, M>(key_store: &K, hsm: H, runtime: &M) -> Result<Self, HyperError>
+    pub fn new<K, H, M>(
+        key_store: &K,
+        hsm: H,
+        index: &Arc<ModuleIndex<M>>,
+        policies: &RoutePolicies,
+    ) -> Result<Self, HyperError>
     where
         K: 'static + KeyStore + Clone,
         H: 'static + CreateCertificate + Decrypt + Encrypt + GetTrustBundle + Clone,
@@ -40,13 +57,13 @@ impl WorkloadService {
         M::Logs: Into<Body>,
     {
         let router = router!(
-            post   "/modules/(?P<name>[^/]+)/genid/(?P<genid>[^/]+)/sign" => Authorization::new(SignHandler::new(key_store.clone()), Policy::Caller, runtime.clone()),
-            post   "/modules/(?P<name>[^/]+)/genid/(?P<genid>[^/]+)/decrypt" => Authorization::new(DecryptHandler::new(hsm.clone()), Policy::Caller, runtime.clone()),
-            post   "/modules/(?P<name>[^/]+)/genid/(?P<genid>[^/]+)/encrypt" => Authorization::new(EncryptHandler::new(hsm.clone()), Policy::Caller, runtime.clone()),
-            post   "/modules/(?P<name>[^/]+)/certificate/identity" => Authorization::new(IdentityCertHandler, Policy::Caller, runtime.clone()),
-            post   "/modules/(?P<name>[^/]+)/genid/(?P<genid>[^/]+)/certificate/server" => Authorization::new(ServerCertHandler::new(hsm.clone()), Policy::Caller, runtime.clone()),
+            post   "/modules/(?P<name>[^/]+)/genid/(?P<genid>[^/]+)/sign" => Authorization::new(SignHandler::new(key_store.clone()), policy(policies, "POST /modules/{name}/genid/{genid}/sign", Policy::caller()), index.clone()),
+            post   "/modules/(?P<name>[^/]+)/genid/(?P<genid>[^/]+)/decrypt" => Authorization::new(DecryptHandler::new(hsm.clone()), policy(policies, "POST /modules/{name}/genid/{genid}/decrypt", Policy::caller()), index.clone()),
+            post   "/modules/(?P<name>[^/]+)/genid/(?P<genid>[^/]+)/encrypt" => Authorization::new(EncryptHandler::new(hsm.clone()), policy(policies, "POST /modules/{name}/genid/{genid}/encrypt", Policy::caller()), index.clone()),
+            post   "/modules/(?P<name>[^/]+)/certificate/identity" => Authorization::new(IdentityCertHandler, policy(policies, "POST /modules/{name}/certificate/identity", Policy::caller()), index.clone()),
+            post   "/modules/(?P<name>[^/]+)/genid/(?P<genid>[^/]+)/certificate/server" => Authorization::new(ServerCertHandler::new(hsm.clone()), policy(policies, "POST /modules/{name}/genid/{genid}/certificate/server", Policy::caller()), index.clone()),
 
-            get    "/trust-bundle" => Authorization::new(TrustBundleHandler::new(hsm), Policy::Anonymous, runtime.clone()),
+            get    "/trust-bundle" => Authorization::new(TrustBundleHandler::new(hsm), policy(policies, "GET /trust-bundle", Policy::anonymous()), index.clone()),
         );
         let inner = router.new_service()?;
         let service = WorkloadService { inner };
@@ -75,3 +92,8 @@ impl NewService for WorkloadService {
         Ok(self.clone())
     }
 }
+
+fn policy(policies: &RoutePolicies, route: &str, default: Policy) -> Policy {
+    debug_assert!(ROUTES.contains(&route), "{} is missing from ROUTES", route);
+    policies.policy(route, default)
+}
@@ -1,7 +1,8 @@
 // Copyright (c) Microsoft. All rights reserved.
 
 use edgelet_core::{
-    pid::Pid, Authorization as CoreAuth, Error as CoreError, Module, ModuleRuntime, Policy,
+    pid::Pid, Authorization as CoreAuth, Error as CoreError, Module, ModuleIndex, ModuleRuntime,
+    Policy,
 };
 use error::{Error, ErrorKind};
 use futures::{future, future::Either, Future};
@@ -9,6 +10,8 @@ use http::{Request, Response};
 use hyper::{Body, Error as HyperError};
 use route::{BoxFuture, Handler, Parameters};
 use std::rc::Rc;
+use std::sync::Arc;
+use tls::ClientIdentity;
 use IntoResponse;
 
 pub struct Authorization<H, M>
@@ -27,9 +30,9 @@ where
     M::Error: Into<CoreError>,
     <M::Module as Module>::Error: Into<CoreError>,
 {
-    pub fn new(inner: H, policy: Policy, runtime: M) -> Self {
+    pub fn new(inner: H, policy: Policy, index: Arc<ModuleIndex<M>>) -> Self {
         Authorization {
-            auth: CoreAuth::new(runtime, policy),
+            auth: CoreAuth::new(index, policy),
             inner: Rc::new(inner),
         }
     }
@@ -47,18 +50,23 @@ where
         req: Request<Body>,
         params: Parameters,
     ) -> BoxFuture<Response<Body>, HyperError> {
-        let (name, pid) = (
+        let (name, pid, identity) = (
             params.name("name").map(|n| n.to_string()),
             req.extensions()
                 .get::<Pid>()
                 .cloned()
                 .unwrap_or_else(|| Pid::None),
+            req.extensions().get::<ClientIdentity>().cloned(),
         );
         let inner = self.inner.clone();
 
-        let response = self
-            .auth
-            .authorize(name, pid)
+        // callers with a client certificate are authorized as the module it
+        // names, others by their pid
+        let authorized = match identity {
+            Some(identity) => Either::A(self.auth.authorize_identity(name, identity.module())),
+            None => Either::B(self.auth.authorize(name, pid)),
+        };
+        let response = authorized
             .map_err(Error::from)
             .and_then(move |authorized| {
                 if authorized {
@@ -78,7 +86,11 @@ mod tests {
     use std::time::Duration;
 
     use super::*;
-    use edgelet_core::{LogOptions, ModuleRegistry, ModuleRuntimeState, ModuleSpec, SystemInfo};
+    use edgelet_core::{
+        ArchiveStream, EventOptions, ExecOptions, Image, LogOptions, ModuleEvent, ModuleRegistry,
+        ModuleRuntimeState, ModuleSpec, ModuleStats, PruneOptions, PruneResult, StatsOptions,
+        SystemInfo, Volume,
+    };
     use futures::{future::FutureResult, stream::Empty, Stream};
     use http::{Request, Response, StatusCode};
     use hyper::{Body, Error as HyperError};
@@ -90,7 +102,7 @@ mod tests {
         request.extensions_mut().insert(Pid::Value(123));
         let params = Parameters::with_captures(vec![(Some("name".to_string()), "abc".to_string())]);
 
-        let auth = Authorization::new(TestHandler::new(), Policy::Caller, runtime);
+        let auth = Authorization::new(TestHandler::new(), Policy::caller(), index(runtime));
         let response = auth.handle(request, params).wait().unwrap();
         let body = response
             .into_body()
@@ -109,7 +121,7 @@ mod tests {
         let mut request = Request::default();
         request.extensions_mut().insert(Pid::Value(456));
 
-        let auth = Authorization::new(TestHandler::new(), Policy::Caller, runtime);
+        let auth = Authorization::new(TestHandler::new(), Policy::caller(), index(runtime));
         let response = auth.handle(request, params).wait().unwrap();
         assert_eq!(404, response.status());
     }
@@ -121,7 +133,7 @@ mod tests {
         let mut request = Request::default();
         request.extensions_mut().insert(Pid::Value(123));
 
-        let auth = Authorization::new(TestHandler::new(), Policy::Caller, runtime);
+        let auth = Authorization::new(TestHandler::new(), Policy::caller(), index(runtime));
         let response = auth.handle(request, params).wait().unwrap();
         assert_eq!(404, response.status());
     }
@@ -133,7 +145,7 @@ mod tests {
         let mut request = Request::default();
         request.extensions_mut().insert(Pid::None);
 
-        let auth = Authorization::new(TestHandler::new(), Policy::Caller, runtime);
+        let auth = Authorization::new(TestHandler::new(), Policy::caller(), index(runtime));
         let response = auth.handle(request, params).wait().unwrap();
         assert_eq!(404, response.status());
     }
@@ -149,11 +161,66 @@ mod tests {
         let mut request = Request::default();
         request.extensions_mut().insert(Pid::None);
 
-        let auth = Authorization::new(TestHandler::new(), Policy::Caller, runtime);
+        let auth = Authorization::new(TestHandler::new(), Policy::caller(), index(runtime));
+        let response = auth.handle(request, params).wait().unwrap();
+        assert_eq!(404, response.status());
+    }
+
+    #[test]
+    fn handler_authorizes_client_identity_instead_of_pid() {
+        let runtime = TestModuleList::new(&vec![TestModule::new("abc", 123)]);
+        let params = Parameters::with_captures(vec![(Some("name".to_string()), "abc".to_string())]);
+        let mut request = Request::default();
+        request.extensions_mut().insert(Pid::None);
+        request
+            .extensions_mut()
+            .insert(ClientIdentity::new("abc".to_string()));
+
+        let auth = Authorization::new(TestHandler::new(), Policy::caller(), index(runtime));
+        let response = auth.handle(request, params).wait().unwrap();
+        assert_eq!(200, response.status());
+    }
+
+    #[test]
+    fn handler_responds_with_not_found_when_client_identity_does_not_match() {
+        let runtime = TestModuleList::new(&vec![TestModule::new("abc", 123)]);
+        let params = Parameters::with_captures(vec![(Some("name".to_string()), "abc".to_string())]);
+        let mut request = Request::default();
+        request.extensions_mut().insert(Pid::Value(123));
+        request
+            .extensions_mut()
+            .insert(ClientIdentity::new("xyz".to_string()));
+
+        let auth = Authorization::new(TestHandler::new(), Policy::caller(), index(runtime));
         let response = auth.handle(request, params).wait().unwrap();
         assert_eq!(404, response.status());
     }
 
+    #[test]
+    fn handler_only_serves_anonymous_routes_to_callers_without_certificate() {
+        // callers over TLS without a client certificate have no pid
+        let request = || {
+            let mut request = Request::default();
+            request.extensions_mut().insert(Pid::None);
+            request
+        };
+        let params = || Parameters::with_captures(vec![]);
+
+        let runtime = TestModuleList::new(&vec![TestModule::new("abc", 123)]);
+        let auth = Authorization::new(TestHandler::new(), Policy::host(), index(runtime));
+        let response = auth.handle(request(), params()).wait().unwrap();
+        assert_eq!(404, response.status());
+
+        let runtime = TestModuleList::new(&vec![TestModule::new("abc", 123)]);
+        let auth = Authorization::new(TestHandler::new(), Policy::anonymous(), index(runtime));
+        let response = auth.handle(request(), params()).wait().unwrap();
+        assert_eq!(200, response.status());
+    }
+
+    fn index(runtime: TestModuleList) -> Arc<ModuleIndex<TestModuleList>> {
+        Arc::new(ModuleIndex::new(runtime))
+    }
+
     #[derive(Clone)]
     struct TestHandler {}
 
@@ -260,6 +327,12 @@ mod tests {
         type Error = Error;
         type PullFuture = FutureResult<(), Self::Error>;
         type RemoveFuture = FutureResult<(), Self::Error>;
+        type ListImagesFuture = FutureResult<Vec<Image>, Self::Error>;
+        type PruneImagesFuture = FutureResult<PruneResult, Self::Error>;
+        type LoadImagesFuture = FutureResult<(), Self::Error>;
+        type SaveImageFuture = FutureResult<Self::ImageArchive, Self::Error>;
+        type ImageChunk = String;
+        type ImageArchive = Empty<Self::ImageChunk, Self::Error>;
 
         fn pull(&self, _config: &Self::Config) -> Self::PullFuture {
             notimpl_error!()
@@ -267,6 +340,18 @@ mod tests {
         fn remove(&self, _name: &str) -> Self::RemoveFuture {
             notimpl_error!()
         }
+        fn list_images(&self) -> Self::ListImagesFuture {
+            notimpl_error!()
+        }
+        fn prune_images(&self, _options: &PruneOptions) -> Self::PruneImagesFuture {
+            notimpl_error!()
+        }
+        fn load_images(&self, _archive: ArchiveStream) -> Self::LoadImagesFuture {
+            notimpl_error!()
+        }
+        fn save_image(&self, _name: &str) -> Self::SaveImageFuture {
+            notimpl_error!()
+        }
     }
 
     impl ModuleRuntime for TestModuleList {
@@ -276,16 +361,30 @@ mod tests {
         type ModuleRegistry = Self;
         type Chunk = String;
         type Logs = Empty<Self::Chunk, Self::Error>;
+        type Stats = Empty<ModuleStats, Self::Error>;
+        type Events = Empty<ModuleEvent, Self::Error>;
         type CreateFuture = FutureResult<(), Self::Error>;
+        type EventsFuture = FutureResult<Self::Events, Self::Error>;
+        type ExecFuture = FutureResult<Self::Logs, Self::Error>;
+        type GetArchiveFuture = FutureResult<Self::Logs, Self::Error>;
         type InitFuture = FutureResult<(), Self::Error>;
         type ListFuture = FutureResult<Vec<Self::Module>, Self::Error>;
         type LogsFuture = FutureResult<Self::Logs, Self::Error>;
         type RemoveFuture = FutureResult<(), Self::Error>;
+        type PauseFuture = FutureResult<(), Self::Error>;
+        type PutArchiveFuture = FutureResult<(), Self::Error>;
         type RestartFuture = FutureResult<(), Self::Error>;
+        type StatsFuture = FutureResult<Self::Stats, Self::Error>;
         type StartFuture = FutureResult<(), Self::Error>;
         type StopFuture = FutureResult<(), Self::Error>;
+        type UnpauseFuture = FutureResult<(), Self::Error>;
         type SystemInfoFuture = FutureResult<SystemInfo, Self::Error>;
         type RemoveAllFuture = FutureResult<(), Self::Error>;
+        type ListVolumesFuture = FutureResult<Vec<Volume>, Self::Error>;
+        type InspectVolumeFuture = FutureResult<Volume, Self::Error>;
+        type CreateVolumeFuture = FutureResult<Volume, Self::Error>;
+        type RemoveVolumeFuture = FutureResult<(), Self::Error>;
+        type BackupVolumeFuture = FutureResult<Self::Logs, Self::Error>;
 
         fn init(&self) -> Self::InitFuture {
             notimpl_error!()
@@ -311,10 +410,22 @@ mod tests {
             notimpl_error!()
         }
 
+        fn pause(&self, _id: &str) -> Self::PauseFuture {
+            notimpl_error!()
+        }
+
+        fn unpause(&self, _id: &str) -> Self::UnpauseFuture {
+            notimpl_error!()
+        }
+
         fn remove(&self, _id: &str) -> Self::RemoveFuture {
             notimpl_error!()
         }
 
+        fn remove_with_volumes(&self, _id: &str) -> Self::RemoveFuture {
+            notimpl_error!()
+        }
+
         fn list(&self) -> Self::ListFuture {
             future::ok(self.modules.clone())
         }
@@ -323,6 +434,31 @@ mod tests {
             notimpl_error!()
         }
 
+        fn stats(&self, _id: &str, _options: &StatsOptions) -> Self::StatsFuture {
+            notimpl_error!()
+        }
+
+        fn events(&self, _options: &EventOptions) -> Self::EventsFuture {
+            notimpl_error!()
+        }
+
+        fn exec(&self, _id: &str, _options: &ExecOptions) -> Self::ExecFuture {
+            notimpl_error!()
+        }
+
+        fn get_archive(&self, _id: &str, _path: &str) -> Self::GetArchiveFuture {
+            notimpl_error!()
+        }
+
+        fn put_archive(
+            &self,
+            _id: &str,
+            _path: &str,
+            _archive: ArchiveStream,
+        ) -> Self::PutArchiveFuture {
+            notimpl_error!()
+        }
+
         fn registry(&self) -> &Self::ModuleRegistry {
             self
         }
@@ -330,5 +466,25 @@ mod tests {
         fn remove_all(&self) -> Self::RemoveAllFuture {
             notimpl_error!()
         }
+
+        fn list_volumes(&self) -> Self::ListVolumesFuture {
+            notimpl_error!()
+        }
+
+        fn inspect_volume(&self, _name: &str) -> Self::InspectVolumeFuture {
+            notimpl_error!()
+        }
+
+        fn create_volume(&self, _name: &str, _module: Option<&str>) -> Self::CreateVolumeFuture {
+            notimpl_error!()
+        }
+
+        fn remove_volume(&self, _name: &str) -> Self::RemoveVolumeFuture {
+            notimpl_error!()
+        }
+
+        fn backup_volume(&self, _name: &str) -> Self::BackupVolumeFuture {
+            notimpl_error!()
+        }
     }
 }
@@ -58,6 +58,10 @@ pub enum ErrorKind {
     HyperPipe,
     #[fail(display = "A TLS error occurred.")]
     HyperTls,
+    #[fail(display = "Invalid TLS settings")]
+    TlsSettings,
+    #[fail(display = "Listening on {} requires TLS settings", _0)]
+    MissingTlsSettings(String),
     #[fail(display = "Systemd error")]
     Systemd,
     #[fail(display = "Module not found")]
@@ -28,6 +28,7 @@ extern crate libc;
 extern crate log;
 #[cfg(unix)]
 extern crate nix;
+extern crate openssl;
 extern crate percent_encoding;
 extern crate regex;
 #[cfg(unix)]
@@ -44,6 +45,7 @@ extern crate tokio_core;
 extern crate tokio_io;
 #[cfg(windows)]
 extern crate tokio_named_pipe;
+extern crate tokio_openssl;
 #[cfg(unix)]
 extern crate tokio_uds;
 extern crate url;
@@ -57,7 +59,10 @@ use std::net;
 use std::net::ToSocketAddrs;
 #[cfg(unix)]
 use std::os::unix::io::FromRawFd;
+use std::rc::Rc;
 
+use edgelet_core::pid::Pid;
+use futures::future::Either;
 use futures::{future, Future, Poll, Stream};
 use http::{Request, Response};
 use hyper::server::{Http, NewService};
@@ -77,19 +82,26 @@ pub mod error;
 pub mod logging;
 mod pid;
 pub mod route;
+pub mod tls;
 mod unix;
 mod util;
 mod version;
 
 pub use self::error::{Error, ErrorKind};
+pub use self::tls::{ClientIdentity, TlsSettings};
 pub use self::util::proxy::MaybeProxyClient;
-pub use self::util::UrlConnector;
-pub use self::version::{ApiVersionService, API_VERSION};
+pub use self::util::{stream_body, JsonLines, UrlConnector};
+pub use self::version::{
+    ApiVersion, ApiVersionService, Since, API_VERSION, API_VERSION_2018_06_28,
+    API_VERSION_2019_01_30, SUPPORTED_API_VERSIONS, SUPPORTED_API_VERSIONS_HEADER,
+};
 
 use self::pid::PidService;
+use self::tls::TlsAcceptor;
 use self::util::incoming::Incoming;
 
 const HTTP_SCHEME: &str = "http";
+const HTTPS_SCHEME: &str = "https";
 const TCP_SCHEME: &str = "tcp";
 #[cfg(unix)]
 const UNIX_SCHEME: &str = "unix";
@@ -126,6 +138,7 @@ where
     new_service: S,
     handle: Handle,
     incoming: Incoming,
+    tls: Option<Rc<TlsAcceptor>>,
 }
 
 impl<S, B> Server<S, B>
@@ -147,17 +160,32 @@ where
             new_service,
             handle,
             incoming,
+            tls,
         } = self;
 
         let srv = incoming.for_each(move |(socket, addr)| {
             debug!("accepted new connection ({})", addr);
-            let pid = socket.pid()?;
+            // callers over TLS are identified only by their client certificate
+            let pid = if tls.is_some() {
+                Pid::None
+            } else {
+                socket.pid()?
+            };
             let srv = new_service.new_service()?;
-            let service = PidService::new(pid, srv);
-            let fut = protocol
-                .serve_connection(socket, self::compat::service(service))
-                .map(|_| ())
-                .map_err(move |err| error!("server connection error: ({}) {}", addr, err));
+            let connection = match tls {
+                Some(ref tls) => Either::A(tls.accept(socket)),
+                None => Either::B(future::ok((socket, None))),
+            };
+            let protocol = protocol.clone();
+            let fut = connection
+                .map_err(Error::from)
+                .and_then(move |(socket, identity)| {
+                    let service = PidService::new(pid, srv).with_identity(identity);
+                    protocol
+                        .serve_connection(socket, self::compat::service(service))
+                        .map(|_| ())
+                        .map_err(Error::from)
+                }).map_err(move |err| error!("server connection error: ({}) {}", addr, err));
             handle.spawn(fut);
             Ok(())
         });
@@ -190,6 +218,20 @@ pub trait HyperExt<B: AsRef<[u8]> + 'static> {
         S: NewService<Request = Request<Body>, Response = Response<Bd>, Error = HyperError>
             + 'static,
         Bd: Stream<Item = B, Error = HyperError>;
+
+    /// Binds a listener that serves TLS with the given settings, for `https`
+    /// urls.
+    fn bind_handle_tls<S, Bd>(
+        &self,
+        url: Url,
+        handle: Handle,
+        new_service: S,
+        tls: &TlsSettings,
+    ) -> Result<Server<S, Bd>, Error>
+    where
+        S: NewService<Request = Request<Body>, Response = Response<Bd>, Error = HyperError>
+            + 'static,
+        Bd: Stream<Item = B, Error = HyperError>;
 }
 
 impl<B: AsRef<[u8]> + 'static> HyperExt<B> for Http<B> {
@@ -204,53 +246,86 @@ impl<B: AsRef<[u8]> + 'static> HyperExt<B> for Http<B> {
             + 'static,
         Bd: Stream<Item = B, Error = HyperError>,
     {
-        let incoming = match url.scheme() {
-            HTTP_SCHEME | TCP_SCHEME => {
-                let addr = url.to_socket_addrs()?.next().ok_or_else(|| {
-                    io::Error::new(io::ErrorKind::Other, format!("Invalid url: {}", url))
-                })?;
-
-                let listener = TcpListener::bind(&addr, &handle)?;
-                Incoming::Tcp(listener)
-            }
-            #[cfg(unix)]
-            UNIX_SCHEME => {
-                let path = url.path();
-                unix::listener(path, &handle)?
-            }
-            #[cfg(unix)]
-            FD_SCHEME => {
-                let host = url
-                    .host_str()
-                    .ok_or_else(|| Error::from(ErrorKind::InvalidUri(url.to_string())))?;
-                let socket = host
-                    .parse::<usize>()
-                    .map_err(Error::from)
-                    .and_then(|num| systemd::listener(num).map_err(Error::from))
-                    .or_else(|_| systemd::listener_name(host))?;
-
-                match socket {
-                    Socket::Inet(fd, addr) => {
-                        let l = unsafe { net::TcpListener::from_raw_fd(fd) };
-                        Incoming::Tcp(TcpListener::from_listener(l, &addr, &handle)?)
-                    }
-                    Socket::Unix(fd) => {
-                        let l = unsafe { ::std::os::unix::net::UnixListener::from_raw_fd(fd) };
-                        Incoming::Unix(UnixListener::from_listener(l, &handle)?)
-                    }
-                    _ => Err(Error::from(ErrorKind::InvalidUri(url.to_string())))?,
-                }
-            }
-            _ => Err(Error::from(ErrorKind::InvalidUri(url.to_string())))?,
-        };
+        if url.scheme() == HTTPS_SCHEME {
+            return Err(Error::from(ErrorKind::MissingTlsSettings(url.to_string())));
+        }
 
         Ok(Server {
             protocol: self.clone(),
             new_service,
+            incoming: incoming(&url, &handle)?,
             handle,
-            incoming,
+            tls: None,
         })
     }
+
+    fn bind_handle_tls<S, Bd>(
+        &self,
+        url: Url,
+        handle: Handle,
+        new_service: S,
+        tls: &TlsSettings,
+    ) -> Result<Server<S, Bd>, Error>
+    where
+        S: NewService<Request = Request<Body>, Response = Response<Bd>, Error = HyperError>
+            + 'static,
+        Bd: Stream<Item = B, Error = HyperError>,
+    {
+        if url.scheme() != HTTPS_SCHEME {
+            return Err(Error::from(ErrorKind::InvalidUri(url.to_string())));
+        }
+
+        Ok(Server {
+            protocol: self.clone(),
+            new_service,
+            incoming: incoming(&url, &handle)?,
+            handle,
+            tls: Some(Rc::new(tls.acceptor()?)),
+        })
+    }
+}
+
+fn incoming(url: &Url, handle: &Handle) -> Result<Incoming, Error> {
+    let incoming = match url.scheme() {
+        HTTP_SCHEME | TCP_SCHEME | HTTPS_SCHEME => {
+            let addr = url.to_socket_addrs()?.next().ok_or_else(|| {
+                io::Error::new(io::ErrorKind::Other, format!("Invalid url: {}", url))
+            })?;
+
+            let listener = TcpListener::bind(&addr, handle)?;
+            Incoming::Tcp(listener)
+        }
+        #[cfg(unix)]
+        UNIX_SCHEME => {
+            let path = url.path();
+            unix::listener(path, handle)?
+        }
+        #[cfg(unix)]
+        FD_SCHEME => {
+            let host = url
+                .host_str()
+                .ok_or_else(|| Error::from(ErrorKind::InvalidUri(url.to_string())))?;
+            let socket = host
+                .parse::<usize>()
+                .map_err(Error::from)
+                .and_then(|num| systemd::listener(num).map_err(Error::from))
+                .or_else(|_| systemd::listener_name(host))?;
+
+            match socket {
+                Socket::Inet(fd, addr) => {
+                    let l = unsafe { net::TcpListener::from_raw_fd(fd) };
+                    Incoming::Tcp(TcpListener::from_listener(l, &addr, handle)?)
+                }
+                Socket::Unix(fd) => {
+                    let l = unsafe { ::std::os::unix::net::UnixListener::from_raw_fd(fd) };
+                    Incoming::Unix(UnixListener::from_listener(l, handle)?)
+                }
+                _ => Err(Error::from(ErrorKind::InvalidUri(url.to_string())))?,
+            }
+        }
+        _ => Err(Error::from(ErrorKind::InvalidUri(url.to_string())))?,
+    };
+    Ok(incoming)
 }
 
 #[cfg(target_os = "linux")]
@@ -8,9 +8,12 @@ use http::{Request, Response};
 use hyper::server::Service;
 use hyper::{Body, Error as HyperError};
 
+use tls::ClientIdentity;
+
 #[derive(Clone)]
 pub struct PidService<T, B> {
     pid: Pid,
+    identity: Option<ClientIdentity>,
     inner: T,
     phantom: PhantomData<B>,
 }
@@ -19,10 +22,16 @@ impl<T, B> PidService<T, B> {
     pub fn new(pid: Pid, inner: T) -> PidService<T, B> {
         PidService {
             pid,
+            identity: None,
             inner,
             phantom: PhantomData,
         }
     }
+
+    pub fn with_identity(mut self, identity: Option<ClientIdentity>) -> PidService<T, B> {
+        self.identity = identity;
+        self
+    }
 }
 
 impl<T, B> Service for PidService<T, B>
@@ -39,6 +48,9 @@ where
     fn call(&self, req: Self::Request) -> Self::Future {
         let mut req = req;
         req.extensions_mut().insert(self.pid.clone());
+        if let Some(ref identity) = self.identity {
+            req.extensions_mut().insert(identity.clone());
+        }
         self.inner.call(req)
     }
 }
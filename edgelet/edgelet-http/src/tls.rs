@@ -0,0 +1,202 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+use std::collections::HashMap;
+use std::io;
+
+use failure::ResultExt;
+use futures::future::{self, Either};
+use futures::Future;
+use openssl::nid;
+use openssl::pkey::PKey;
+use openssl::ssl::{SslAcceptor, SslAcceptorBuilder, SslMethod, SSL_VERIFY_PEER};
+use openssl::x509::X509;
+use tokio_core::net::TcpStream;
+use tokio_openssl::{SslAcceptorExt, SslStream};
+
+use error::{Error, ErrorKind};
+use util::StreamSelector;
+
+/// The module a caller was identified as from the subject of its client
+/// certificate. It is added to the extensions of requests received over
+/// connections that presented a client certificate.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ClientIdentity(String);
+
+impl ClientIdentity {
+    pub fn new(module: String) -> Self {
+        ClientIdentity(module)
+    }
+
+    pub fn module(&self) -> &str {
+        &self.0
+    }
+}
+
+/// The server certificate of an `https` listener, and the CA its clients'
+/// certificates must be issued by. Without a client CA, or when a client
+/// presents no certificate, the caller is not identified and is only
+/// authorized for routes that allow anonymous callers.
+#[derive(Clone, Debug)]
+pub struct TlsSettings {
+    cert: Vec<u8>,
+    key: Vec<u8>,
+    client_ca: Option<Vec<u8>>,
+    client_modules: HashMap<String, String>,
+}
+
+impl TlsSettings {
+    /// Takes the PEM encoded server certificate, followed by the rest of its
+    /// chain, and its private key.
+    pub fn new(cert: Vec<u8>, key: Vec<u8>) -> Self {
+        TlsSettings {
+            cert,
+            key,
+            client_ca: None,
+            client_modules: HashMap::new(),
+        }
+    }
+
+    pub fn with_client_ca(mut self, client_ca: Option<Vec<u8>>) -> Self {
+        self.client_ca = client_ca;
+        self
+    }
+
+    /// Maps the common names of client certificates to module names. Common
+    /// names that are not mapped are taken as the module name.
+    pub fn with_client_modules(mut self, client_modules: HashMap<String, String>) -> Self {
+        self.client_modules = client_modules;
+        self
+    }
+
+    pub(crate) fn acceptor(&self) -> Result<TlsAcceptor, Error> {
+        let mut chain = X509::stack_from_pem(&self.cert).context(ErrorKind::TlsSettings)?;
+        if chain.is_empty() {
+            return Err(Error::from(ErrorKind::TlsSettings));
+        }
+        let cert = chain.remove(0);
+        let key = PKey::private_key_from_pem(&self.key).context(ErrorKind::TlsSettings)?;
+
+        let mut builder =
+            SslAcceptorBuilder::mozilla_intermediate(SslMethod::tls(), &key, &cert, &chain)
+                .context(ErrorKind::TlsSettings)?;
+        if let Some(ref client_ca) = self.client_ca {
+            // clients without a certificate are accepted, and certificates
+            // not issued by the client CA are not
+            let context = builder.builder_mut();
+            context.set_verify(SSL_VERIFY_PEER);
+            for ca in X509::stack_from_pem(client_ca).context(ErrorKind::TlsSettings)? {
+                context
+                    .cert_store_mut()
+                    .add_cert(ca)
+                    .context(ErrorKind::TlsSettings)?;
+            }
+        }
+
+        Ok(TlsAcceptor {
+            acceptor: builder.build(),
+            client_modules: self.client_modules.clone(),
+        })
+    }
+}
+
+pub(crate) struct TlsAcceptor {
+    acceptor: SslAcceptor,
+    client_modules: HashMap<String, String>,
+}
+
+impl TlsAcceptor {
+    pub fn accept(
+        &self,
+        socket: StreamSelector,
+    ) -> impl Future<Item = (StreamSelector, Option<ClientIdentity>), Error = io::Error> {
+        let stream = match socket {
+            StreamSelector::Tcp(stream) => stream,
+            _ => {
+                return Either::B(future::err(io::Error::new(
+                    io::ErrorKind::Other,
+                    "TLS is only served over TCP",
+                )))
+            }
+        };
+
+        let client_modules = self.client_modules.clone();
+        let accepted = self
+            .acceptor
+            .accept_async(stream)
+            .map_err(|err| io::Error::new(io::ErrorKind::Other, err.to_string()))
+            .map(move |stream| {
+                let identity = client_identity(&stream, &client_modules);
+                (StreamSelector::Tls(stream), identity)
+            });
+        Either::A(accepted)
+    }
+}
+
+fn client_identity(
+    stream: &SslStream<TcpStream>,
+    client_modules: &HashMap<String, String>,
+) -> Option<ClientIdentity> {
+    let cert = stream.get_ref().ssl().peer_certificate()?;
+    let common_name = cert
+        .subject_name()
+        .entries_by_nid(nid::COMMONNAME)
+        .next()?
+        .data()
+        .as_utf8()
+        .ok()?
+        .to_string();
+    let module = client_modules.get(&common_name).cloned();
+    Some(ClientIdentity(module.unwrap_or(common_name)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use openssl::ec::{EcGroup, EcKey};
+    use openssl::hash::MessageDigest;
+    use openssl::x509::{X509Builder, X509NameBuilder};
+
+    fn self_signed() -> (Vec<u8>, Vec<u8>) {
+        let group = EcGroup::from_curve_name(nid::X9_62_PRIME256V1).unwrap();
+        let key = PKey::from_ec_key(EcKey::generate(&group).unwrap()).unwrap();
+
+        let mut name = X509NameBuilder::new().unwrap();
+        name.append_entry_by_nid(nid::COMMONNAME, "localhost")
+            .unwrap();
+        let name = name.build();
+
+        let mut builder = X509Builder::new().unwrap();
+        builder.set_subject_name(&name).unwrap();
+        builder.set_issuer_name(&name).unwrap();
+        builder.set_pubkey(&key).unwrap();
+        builder.sign(&key, MessageDigest::sha256()).unwrap();
+        let cert = builder.build();
+
+        (cert.to_pem().unwrap(), key.private_key_to_pem().unwrap())
+    }
+
+    #[test]
+    fn acceptor_is_built_from_settings() {
+        let (cert, key) = self_signed();
+        let settings = TlsSettings::new(cert.clone(), key).with_client_ca(Some(cert));
+        assert!(settings.acceptor().is_ok());
+    }
+
+    #[test]
+    fn acceptor_is_built_without_client_ca() {
+        let (cert, key) = self_signed();
+        let settings = TlsSettings::new(cert, key);
+        assert!(settings.acceptor().is_ok());
+    }
+
+    #[test]
+    fn acceptor_fails_without_certificate() {
+        let (_, key) = self_signed();
+        let settings = TlsSettings::new(Vec::new(), key);
+        assert_eq!(
+            &ErrorKind::TlsSettings,
+            settings.acceptor().err().unwrap().kind()
+        );
+    }
+}
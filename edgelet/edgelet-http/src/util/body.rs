@@ -0,0 +1,52 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+use futures::sync::mpsc::SendError;
+use futures::{Future, Sink, Stream};
+use hyper::{Body, Chunk, Error as HyperError};
+
+type BodyChunk = Result<Chunk, HyperError>;
+
+/// Builds a request body out of a stream, so that a large upload is sent as
+/// it is read instead of being held in memory. The returned future feeds the
+/// body and has to be polled along with the request, it fails if the body is
+/// dropped before the stream ends. An error of the stream aborts the request.
+pub fn stream_body<S>(stream: S) -> (Body, impl Future<Item = (), Error = ()>)
+where
+    S: Stream,
+    S::Item: Into<Chunk>,
+{
+    let (sender, body) = Body::pair();
+    let chunks = stream.then(|chunk| -> Result<BodyChunk, SendError<BodyChunk>> {
+        Ok(chunk.map(Into::into).map_err(|_| HyperError::Incomplete))
+    });
+    let feed = sender.send_all(chunks).map(|_| ()).map_err(|_| ());
+    (body, feed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use futures::future;
+    use futures::stream;
+
+    #[test]
+    fn body_has_the_chunks_of_the_stream() {
+        let chunks = vec![b"hello ".to_vec(), b"world".to_vec()];
+        let (body, feed) = stream_body(stream::iter_ok::<_, ()>(chunks));
+        let (_, body) = feed.join(body.concat2().map_err(|_| ())).wait().unwrap();
+        assert_eq!(&b"hello world"[..], body.as_ref());
+    }
+
+    #[test]
+    fn stream_error_fails_the_body() {
+        let chunks = vec![Ok(b"hello".to_vec()), Err(())];
+        let (body, feed) = stream_body(stream::iter_result(chunks));
+        let (_, failed) = feed
+            .then(|_| future::ok::<_, ()>(()))
+            .join(body.concat2().then(|result| future::ok(result.is_err())))
+            .wait()
+            .unwrap();
+        assert!(failed);
+    }
+}
@@ -0,0 +1,115 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+use std::marker::PhantomData;
+
+use futures::{Async, Poll, Stream};
+use serde::de::DeserializeOwned;
+use serde_json;
+
+/// Converts a stream of byte chunks carrying newline delimited JSON documents
+/// (as emitted by the streaming endpoints of the docker engine and of the
+/// management API) into a stream of deserialized values. Documents may span
+/// chunk boundaries.
+pub struct JsonLines<S, T> {
+    inner: S,
+    buffer: Vec<u8>,
+    done: bool,
+    phantom: PhantomData<T>,
+}
+
+impl<S, T> JsonLines<S, T> {
+    pub fn new(inner: S) -> Self {
+        JsonLines {
+            inner,
+            buffer: Vec::new(),
+            done: false,
+            phantom: PhantomData,
+        }
+    }
+}
+
+fn is_blank(line: &[u8]) -> bool {
+    line.iter().all(|b| b.is_ascii_whitespace())
+}
+
+impl<S, C, T> Stream for JsonLines<S, T>
+where
+    S: Stream<Item = C>,
+    S::Error: From<serde_json::Error>,
+    C: AsRef<[u8]>,
+    T: DeserializeOwned,
+{
+    type Item = T;
+    type Error = S::Error;
+
+    fn poll(&mut self) -> Poll<Option<Self::Item>, Self::Error> {
+        loop {
+            if let Some(pos) = self.buffer.iter().position(|b| *b == b'\n') {
+                let line: Vec<u8> = self.buffer.drain(..=pos).collect();
+                if !is_blank(&line) {
+                    let value = serde_json::from_slice(&line)?;
+                    return Ok(Async::Ready(Some(value)));
+                }
+            } else if self.done {
+                let line: Vec<u8> = self.buffer.drain(..).collect();
+                return if is_blank(&line) {
+                    Ok(Async::Ready(None))
+                } else {
+                    let value = serde_json::from_slice(&line)?;
+                    Ok(Async::Ready(Some(value)))
+                };
+            } else {
+                match try_ready!(self.inner.poll()) {
+                    Some(chunk) => self.buffer.extend_from_slice(chunk.as_ref()),
+                    None => self.done = true,
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use futures::stream::iter_ok;
+    use futures::Future;
+    use serde_json::Value;
+
+    #[test]
+    fn decodes_documents_split_across_chunks() {
+        let chunks = vec![
+            &b"{\"value\":"[..],
+            &b"1}\n{\"val"[..],
+            &b"ue\":2}\n\n{\"value\":3}"[..],
+        ];
+        let values: Vec<Value> = JsonLines::new(iter_ok::<_, serde_json::Error>(chunks))
+            .collect()
+            .wait()
+            .unwrap();
+        assert_eq!(
+            vec![json!({"value": 1}), json!({"value": 2}), json!({"value": 3})],
+            values
+        );
+    }
+
+    #[test]
+    fn blank_stream_yields_nothing() {
+        let chunks = vec![&b"\n"[..], &b"  "[..]];
+        let values: Vec<Value> = JsonLines::new(iter_ok::<_, serde_json::Error>(chunks))
+            .collect()
+            .wait()
+            .unwrap();
+        assert!(values.is_empty());
+    }
+
+    #[test]
+    fn invalid_document_fails() {
+        let chunks = vec![&b"{\"value\":\n"[..]];
+        let result: Result<Vec<Value>, _> =
+            JsonLines::new(iter_ok::<_, serde_json::Error>(chunks))
+                .collect()
+                .wait();
+        assert!(result.is_err());
+    }
+}
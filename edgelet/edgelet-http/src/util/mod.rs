@@ -13,22 +13,28 @@ use tokio_core::net::TcpStream;
 use tokio_io::{AsyncRead, AsyncWrite};
 #[cfg(windows)]
 use tokio_named_pipe::PipeStream;
+use tokio_openssl::SslStream;
 #[cfg(unix)]
 use tokio_uds::UnixStream;
 
 #[cfg(unix)]
 use pid::UnixStreamExt;
 
+mod body;
 pub mod connector;
 mod hyperwrap;
 pub mod incoming;
+mod json_lines;
 pub mod proxy;
 
+pub use self::body::stream_body;
 pub use self::connector::UrlConnector;
 pub use self::incoming::Incoming;
+pub use self::json_lines::JsonLines;
 
 pub enum StreamSelector {
     Tcp(TcpStream),
+    Tls(SslStream<TcpStream>),
     #[cfg(windows)]
     Pipe(PipeStream),
     #[cfg(unix)]
@@ -39,6 +45,8 @@ impl StreamSelector {
     pub fn pid(&self) -> io::Result<Pid> {
         match *self {
             StreamSelector::Tcp(_) => Ok(Pid::Any),
+            // callers over TLS are identified by their client certificate
+            StreamSelector::Tls(_) => Ok(Pid::None),
             #[cfg(windows)]
             StreamSelector::Pipe(_) => Ok(Pid::Any),
             #[cfg(unix)]
@@ -51,6 +59,7 @@ impl Read for StreamSelector {
     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
         match *self {
             StreamSelector::Tcp(ref mut stream) => stream.read(buf),
+            StreamSelector::Tls(ref mut stream) => stream.read(buf),
             #[cfg(windows)]
             StreamSelector::Pipe(ref mut stream) => stream.read(buf),
             #[cfg(unix)]
@@ -63,6 +72,7 @@ impl Write for StreamSelector {
     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
         match *self {
             StreamSelector::Tcp(ref mut stream) => stream.write(buf),
+            StreamSelector::Tls(ref mut stream) => stream.write(buf),
             #[cfg(windows)]
             StreamSelector::Pipe(ref mut stream) => stream.write(buf),
             #[cfg(unix)]
@@ -73,6 +83,7 @@ impl Write for StreamSelector {
     fn flush(&mut self) -> io::Result<()> {
         match *self {
             StreamSelector::Tcp(ref mut stream) => stream.flush(),
+            StreamSelector::Tls(ref mut stream) => stream.flush(),
             #[cfg(windows)]
             StreamSelector::Pipe(ref mut stream) => stream.flush(),
             #[cfg(unix)]
@@ -86,6 +97,7 @@ impl AsyncRead for StreamSelector {
     unsafe fn prepare_uninitialized_buffer(&self, buf: &mut [u8]) -> bool {
         match *self {
             StreamSelector::Tcp(ref stream) => stream.prepare_uninitialized_buffer(buf),
+            StreamSelector::Tls(ref stream) => stream.prepare_uninitialized_buffer(buf),
             #[cfg(windows)]
             StreamSelector::Pipe(ref stream) => stream.prepare_uninitialized_buffer(buf),
             #[cfg(unix)]
@@ -97,6 +109,7 @@ impl AsyncRead for StreamSelector {
     fn read_buf<B: BufMut>(&mut self, buf: &mut B) -> Poll<usize, io::Error> {
         match *self {
             StreamSelector::Tcp(ref mut stream) => stream.read_buf(buf),
+            StreamSelector::Tls(ref mut stream) => stream.read_buf(buf),
             #[cfg(windows)]
             StreamSelector::Pipe(ref mut stream) => stream.read_buf(buf),
             #[cfg(unix)]
@@ -109,6 +122,7 @@ impl AsyncWrite for StreamSelector {
     fn shutdown(&mut self) -> Poll<(), io::Error> {
         match *self {
             StreamSelector::Tcp(ref mut stream) => <&TcpStream>::shutdown(&mut &*stream),
+            StreamSelector::Tls(ref mut stream) => SslStream::shutdown(stream),
             #[cfg(windows)]
             StreamSelector::Pipe(ref mut stream) => PipeStream::shutdown(stream),
             #[cfg(unix)]
@@ -120,6 +134,7 @@ impl AsyncWrite for StreamSelector {
     fn write_buf<B: Buf>(&mut self, buf: &mut B) -> Poll<usize, io::Error> {
         match *self {
             StreamSelector::Tcp(ref mut stream) => stream.write_buf(buf),
+            StreamSelector::Tls(ref mut stream) => stream.write_buf(buf),
             #[cfg(windows)]
             StreamSelector::Pipe(ref mut stream) => stream.write_buf(buf),
             #[cfg(unix)]
@@ -3,27 +3,161 @@
 use std::io;
 
 use futures::{future, Future};
-use http::{Request, Response};
+use http::header::{HeaderValue, CONTENT_LENGTH, CONTENT_TYPE};
+use http::{Method, Request, Response, StatusCode};
 use hyper::server::{NewService, Service};
 use hyper::{Body, Error as HyperError};
 use url::form_urlencoded::parse as parse_query;
 
 use error::{Error, ErrorKind};
+use route::{BoxFuture, Handler};
 use IntoResponse;
 
-pub const API_VERSION: &str = "2018-06-28";
+/// The version of the API the daemon was first released with. It has the
+/// module, identity and system information routes.
+pub const API_VERSION_2018_06_28: &str = "2018-06-28";
+
+/// Adds pausing modules, exec, archives, stats, events, the watchdog status,
+/// images and volumes to the management API.
+pub const API_VERSION_2019_01_30: &str = "2019-01-30";
+
+/// The newest version of the API, which clients use.
+pub const API_VERSION: &str = API_VERSION_2019_01_30;
+
+/// The versions of the API that are served, oldest first, so that clients
+/// built against an older version keep working against a newer daemon.
+pub const SUPPORTED_API_VERSIONS: &[&str] = &[API_VERSION_2018_06_28, API_VERSION_2019_01_30];
+
+/// Header of every response listing the versions of the API that are served.
+pub const SUPPORTED_API_VERSIONS_HEADER: &str = "api-supported-versions";
+
+/// Lists the versions of the API. It is served without an `api-version`,
+/// since clients use it to find one.
+const VERSIONS_PATH: &str = "/versions";
+
+/// The version of the API a request was made with. It is added to the
+/// extensions of the request, so that handlers can shape their models
+/// for it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ApiVersion(&'static str);
+
+impl ApiVersion {
+    /// The served version of the API named `version`, if there is one.
+    pub fn new(version: &str) -> Option<Self> {
+        SUPPORTED_API_VERSIONS
+            .iter()
+            .find(|&&v| v == version)
+            .map(|&v| ApiVersion(v))
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        self.0
+    }
+
+    /// Whether this version is `version` or a later one. Versions are dates,
+    /// so they are ordered as strings.
+    pub fn is_at_least(&self, version: &str) -> bool {
+        self.0 >= version
+    }
+}
+
+/// Serves a route only to requests made with the version of the API the route
+/// was added in, or a later one. Requests made with an older version get a
+/// 404, the same as for a route that does not exist.
+pub struct Since<H> {
+    version: &'static str,
+    inner: H,
+}
+
+impl<H> Since<H> {
+    pub fn new(version: &'static str, inner: H) -> Self {
+        Since { version, inner }
+    }
+}
+
+impl<H, P> Handler<P> for Since<H>
+where
+    H: Handler<P>,
+    P: 'static,
+{
+    fn handle(&self, req: Request<Body>, params: P) -> BoxFuture<Response<Body>, HyperError> {
+        // a request that did not go through the version negotiation, as in
+        // tests of a single route, is served
+        let served = req
+            .extensions()
+            .get::<ApiVersion>()
+            .map_or(true, |version| version.is_at_least(self.version));
+        if served {
+            self.inner.handle(req, params)
+        } else {
+            Box::new(future::result(
+                Response::builder()
+                    .status(StatusCode::NOT_FOUND)
+                    .body(Body::default())
+                    .map_err(|_| HyperError::Status),
+            ))
+        }
+    }
+}
 
 #[derive(Clone)]
 pub struct ApiVersionService<T> {
     upstream: T,
+    routes: Vec<(&'static str, T)>,
+    versions: Vec<&'static str>,
 }
 
 impl<T> ApiVersionService<T> {
+    /// Serves the supported versions of the API with the same routes. The
+    /// negotiated version is passed on to the routes, so that routes
+    /// added in a later version can be wrapped in `Since`.
     pub fn new(upstream: T) -> ApiVersionService<T> {
-        ApiVersionService { upstream }
+        ApiVersionService {
+            upstream,
+            routes: Vec::new(),
+            versions: SUPPORTED_API_VERSIONS.to_vec(),
+        }
+    }
+
+    /// Serves a version of the API with its own routes, e.g. when the routes
+    /// of the version differ from those of the other versions.
+    pub fn with_version(mut self, version: &'static str, upstream: T) -> Self {
+        if !self.versions.contains(&version) {
+            self.versions.push(version);
+        }
+        self.routes.retain(|&(v, _)| v != version);
+        self.routes.push((version, upstream));
+        self
+    }
+
+    pub fn versions(&self) -> &[&'static str] {
+        &self.versions
+    }
+
+    fn route(&self, version: &str) -> Option<(&'static str, &T)> {
+        self.routes
+            .iter()
+            .find(|&&(v, _)| v == version)
+            .map(|&(v, ref upstream)| (v, upstream))
+            .or_else(|| {
+                SUPPORTED_API_VERSIONS
+                    .iter()
+                    .find(|&&v| v == version)
+                    .map(|&v| (v, &self.upstream))
+            })
     }
 }
 
+fn versions_response(versions: &[&'static str]) -> Response<Body> {
+    let body = json!({ "versions": versions }).to_string();
+    Response::builder()
+        .status(StatusCode::OK)
+        .header(CONTENT_TYPE, "application/json")
+        .header(CONTENT_LENGTH, body.len().to_string().as_str())
+        .body(body.into())
+        .unwrap_or_else(|_| Error::from(ErrorKind::Hyper).into_response())
+}
+
 impl IntoResponse for HyperError {
     fn into_response(self) -> Response<Body> {
         Error::from(self).into_response()
@@ -40,28 +174,44 @@ where
     type Error = T::Error;
     type Future = Box<Future<Item = Self::Response, Error = Self::Error>>;
 
-    fn call(&self, req: Self::Request) -> Self::Future {
-        let response = req
-            .uri()
-            .query()
-            .map(|query| query.to_owned())
-            .and_then(|query| {
-                parse_query(query.as_bytes())
-                    .find(|&(ref key, _)| key == "api-version")
-                    .and_then(|(_, v)| if v == API_VERSION { Some(()) } else { None })
-                    .map(|_| {
-                        future::Either::A(
-                            self.upstream
-                                .call(req)
-                                .or_else(|e| future::ok(e.into_response())),
-                        )
-                    })
-            }).unwrap_or_else(|| {
-                let err = Error::from(ErrorKind::InvalidApiVersion);
-                future::Either::B(future::ok(err.into_response()))
-            });
-
-        Box::new(response)
+    fn call(&self, mut req: Self::Request) -> Self::Future {
+        let response = if req.method() == &Method::GET && req.uri().path() == VERSIONS_PATH {
+            future::Either::B(future::ok(versions_response(&self.versions)))
+        } else {
+            let route = req
+                .uri()
+                .query()
+                .map(|query| query.to_owned())
+                .and_then(|query| {
+                    parse_query(query.as_bytes())
+                        .find(|&(ref key, _)| key == "api-version")
+                        .and_then(|(_, v)| self.route(&v))
+                });
+            match route {
+                Some((version, upstream)) => {
+                    req.extensions_mut().insert(ApiVersion(version));
+                    future::Either::A(
+                        upstream
+                            .call(req)
+                            .or_else(|e| future::ok(e.into_response())),
+                    )
+                }
+                None => {
+                    let err = Error::from(ErrorKind::InvalidApiVersion);
+                    future::Either::B(future::ok(err.into_response()))
+                }
+            }
+        };
+
+        let versions = HeaderValue::from_str(&self.versions.join(", ")).ok();
+        Box::new(response.map(move |mut response| {
+            if let Some(versions) = versions {
+                response
+                    .headers_mut()
+                    .insert(SUPPORTED_API_VERSIONS_HEADER, versions);
+            }
+            response
+        }))
     }
 }
 
@@ -83,7 +233,10 @@ where
 #[cfg(test)]
 mod tests {
     use super::*;
-    use http::StatusCode;
+    use futures::Stream;
+    use serde_json;
+
+    use route::{Builder, Parameters, RegexRecognizer, RegexRoutesBuilder, Router, RouterService};
 
     #[derive(Clone)]
     struct TestService {
@@ -159,6 +312,75 @@ mod tests {
         assert_eq!(StatusCode::BAD_REQUEST, response.status());
     }
 
+    #[test]
+    fn api_version_is_routed_to_its_own_service() {
+        let api_service = ApiVersionService::new(TestService {
+            status_code: StatusCode::OK,
+            error: false,
+        }).with_version(
+            API_VERSION_2018_06_28,
+            TestService {
+                status_code: StatusCode::IM_A_TEAPOT,
+                error: false,
+            },
+        );
+
+        let url = &format!("http://localhost?api-version={}", API_VERSION_2018_06_28);
+        let req = Request::get(url).body(Body::default()).unwrap();
+        let response = Service::call(&api_service, req).wait().unwrap();
+        assert_eq!(StatusCode::IM_A_TEAPOT, response.status());
+
+        let url = &format!("http://localhost?api-version={}", API_VERSION);
+        let req = Request::get(url).body(Body::default()).unwrap();
+        let response = Service::call(&api_service, req).wait().unwrap();
+        assert_eq!(StatusCode::OK, response.status());
+    }
+
+    #[test]
+    fn supported_versions_are_advertised() {
+        let api_service = ApiVersionService::new(TestService {
+            status_code: StatusCode::OK,
+            error: false,
+        }).with_version(
+            "2099-01-01",
+            TestService {
+                status_code: StatusCode::OK,
+                error: false,
+            },
+        );
+        let expected = format!("{}, 2099-01-01", SUPPORTED_API_VERSIONS.join(", "));
+
+        let url = &format!("http://localhost?api-version={}", API_VERSION);
+        let req = Request::get(url).body(Body::default()).unwrap();
+        let response = Service::call(&api_service, req).wait().unwrap();
+        assert_eq!(expected, response.headers()[SUPPORTED_API_VERSIONS_HEADER]);
+
+        let req = Request::get("http://localhost?api-version=not-a-valid-version")
+            .body(Body::default())
+            .unwrap();
+        let response = Service::call(&api_service, req).wait().unwrap();
+        assert_eq!(StatusCode::BAD_REQUEST, response.status());
+        assert_eq!(expected, response.headers()[SUPPORTED_API_VERSIONS_HEADER]);
+    }
+
+    #[test]
+    fn versions_are_listed_without_api_version() {
+        let req = Request::get("http://localhost/versions")
+            .body(Body::default())
+            .unwrap();
+        let api_service = ApiVersionService::new(TestService {
+            status_code: StatusCode::IM_A_TEAPOT,
+            error: false,
+        });
+        let response = Service::call(&api_service, req).wait().unwrap();
+        assert_eq!(StatusCode::OK, response.status());
+        let body = response.into_body().concat2().wait().unwrap();
+        assert_eq!(
+            json!({ "versions": SUPPORTED_API_VERSIONS }),
+            serde_json::from_slice::<serde_json::Value>(&body).unwrap()
+        );
+    }
+
     #[test]
     fn api_version_is_unsupported() {
         let url = "http://localhost?api-version=not-a-valid-version";
@@ -170,4 +392,58 @@ mod tests {
         let response = Service::call(&api_service, req).wait().unwrap();
         assert_eq!(StatusCode::BAD_REQUEST, response.status());
     }
+
+    fn ok(_req: Request<Body>, _params: Parameters) -> BoxFuture<Response<Body>, HyperError> {
+        Box::new(future::ok(Response::new(Body::default())))
+    }
+
+    fn versioned_service() -> ApiVersionService<RouterService<RegexRecognizer>> {
+        let router = Router::from(
+            RegexRoutesBuilder::default()
+                .get("/modules", ok)
+                .get("/volumes", Since::new(API_VERSION_2019_01_30, ok))
+                .finish(),
+        );
+        ApiVersionService::new(router.new_service().unwrap())
+    }
+
+    fn get(
+        service: &ApiVersionService<RouterService<RegexRecognizer>>,
+        path: &str,
+        version: &str,
+    ) -> StatusCode {
+        let url = format!("http://localhost{}?api-version={}", path, version);
+        let req = Request::get(url).body(Body::default()).unwrap();
+        Service::call(service, req).wait().unwrap().status()
+    }
+
+    #[test]
+    fn lower_version_is_served_its_own_routes() {
+        let service = versioned_service();
+
+        let status = get(&service, "/modules", API_VERSION_2018_06_28);
+        assert_eq!(StatusCode::OK, status);
+        let status = get(&service, "/volumes", API_VERSION_2018_06_28);
+        assert_eq!(StatusCode::NOT_FOUND, status);
+    }
+
+    #[test]
+    fn newer_version_is_served_every_route() {
+        let service = versioned_service();
+
+        let status = get(&service, "/modules", API_VERSION_2019_01_30);
+        assert_eq!(StatusCode::OK, status);
+        let status = get(&service, "/volumes", API_VERSION_2019_01_30);
+        assert_eq!(StatusCode::OK, status);
+    }
+
+    #[test]
+    fn unsupported_version_is_served_no_routes() {
+        let service = versioned_service();
+
+        let status = get(&service, "/modules", "2017-11-08");
+        assert_eq!(StatusCode::BAD_REQUEST, status);
+        let status = get(&service, "/volumes", "2017-11-08");
+        assert_eq!(StatusCode::BAD_REQUEST, status);
+    }
 }
@@ -0,0 +1,18 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+/// Modules of the memory runtime only have an image, which is never pulled
+/// from anywhere.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct MemoryConfig {
+    image: String,
+}
+
+impl MemoryConfig {
+    pub fn new(image: String) -> Self {
+        MemoryConfig { image }
+    }
+
+    pub fn image(&self) -> &str {
+        &self.image
+    }
+}
@@ -0,0 +1,22 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+#![deny(warnings)]
+
+extern crate chrono;
+#[macro_use]
+extern crate failure;
+extern crate futures;
+extern crate hyper;
+extern crate serde;
+#[macro_use]
+extern crate serde_derive;
+
+extern crate edgelet_core;
+
+mod config;
+mod runtime;
+
+pub use config::MemoryConfig;
+pub use runtime::{
+    Error, MemoryLogs, MemoryModule, MemoryRegistry, MemoryRuntime, Operation, OutputStream,
+};
@@ -0,0 +1,1178 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+//! A module runtime that keeps all of its state in memory. Modules move
+//! through the same states a container would, without anything actually
+//! running. Failures and latency can be injected per operation, which makes
+//! it usable for end to end tests of the daemon as well as for running a
+//! simulated device without a container engine.
+
+use std::collections::{HashMap, HashSet};
+use std::env;
+use std::fmt;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread;
+use std::time::Duration;
+use std::vec::IntoIter;
+
+use chrono::prelude::*;
+use edgelet_core::pid::Pid;
+use edgelet_core::*;
+use failure::Fail;
+use futures::future::{self, FutureResult};
+use futures::prelude::*;
+use futures::stream::{self, IterOk};
+use futures::sync::oneshot;
+use hyper::Body;
+
+use config::MemoryConfig;
+
+const FIRST_PID: i32 = 1000;
+
+#[derive(Clone, Debug, Fail, PartialEq)]
+pub enum Error {
+    #[fail(display = "Module {} not found", _0)]
+    NotFound(String),
+
+    #[fail(display = "Image {} not found", _0)]
+    ImageNotFound(String),
+
+    #[fail(display = "Image archive is not valid")]
+    InvalidArchive,
+
+    #[fail(display = "Volume {} not found", _0)]
+    VolumeNotFound(String),
+
+    #[fail(display = "Volume {} is in use by module {}", _0, _1)]
+    VolumeInUse(String, String),
+
+    #[fail(display = "Conflict with current operation on module {}", _0)]
+    Conflict(String),
+
+    #[fail(display = "Module {} is already in the requested state", _0)]
+    NotModified(String),
+
+    #[fail(display = "Injected failure for {}", _0)]
+    Injected(Operation),
+
+    #[fail(display = "Simulated runtime operation was canceled")]
+    Canceled,
+}
+
+impl From<Error> for CoreError {
+    fn from(err: Error) -> CoreError {
+        CoreError::from(err.context(CoreErrorKind::ModuleRuntime))
+    }
+}
+
+/// The runtime and registry operations that failures can be injected into.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Operation {
+    Init,
+    Create,
+    Start,
+    Stop,
+    Restart,
+    Pause,
+    Unpause,
+    Remove,
+    List,
+    Logs,
+    Stats,
+    Events,
+    Exec,
+    GetArchive,
+    PutArchive,
+    SystemInfo,
+    RemoveAll,
+    Pull,
+    RemoveImage,
+    ListImages,
+    PruneImages,
+    LoadImages,
+    SaveImage,
+    ListVolumes,
+    InspectVolume,
+    CreateVolume,
+    RemoveVolume,
+    BackupVolume,
+}
+
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum OutputStream {
+    Stdout,
+    Stderr,
+}
+
+impl OutputStream {
+    fn frame_type(self) -> u8 {
+        match self {
+            OutputStream::Stdout => 1,
+            OutputStream::Stderr => 2,
+        }
+    }
+}
+
+struct ModuleEntry {
+    type_: String,
+    config: MemoryConfig,
+    spec_hash: Option<String>,
+    status: ModuleStatus,
+    pid: Option<i32>,
+    exit_code: Option<i64>,
+    started_at: Option<DateTime<Utc>>,
+    finished_at: Option<DateTime<Utc>>,
+    restart_count: i32,
+    logs: Vec<(OutputStream, String)>,
+    files: HashMap<String, Vec<u8>>,
+}
+
+impl ModuleEntry {
+    fn is_running(&self) -> bool {
+        self.status == ModuleStatus::Running || self.status == ModuleStatus::Paused
+    }
+
+    fn module(&self, name: &str) -> MemoryModule {
+        let pid = self.pid.map(Pid::Value).unwrap_or(Pid::None);
+        let state = ModuleRuntimeState::default()
+            .with_status(self.status.clone())
+            .with_exit_code(self.exit_code)
+            .with_started_at(self.started_at)
+            .with_finished_at(self.finished_at)
+            .with_image_id(Some(self.config.image().to_string()))
+            .with_pid(&pid)
+            .with_restart_count(self.restart_count);
+        MemoryModule {
+            name: name.to_string(),
+            type_: self.type_.clone(),
+            config: self.config.clone(),
+            spec_hash: self.spec_hash.clone(),
+            state,
+        }
+    }
+}
+
+#[derive(Default)]
+struct State {
+    modules: HashMap<String, ModuleEntry>,
+    images: HashSet<String>,
+    // volume name -> module the volume belongs to
+    volumes: HashMap<String, Option<String>>,
+    events: Vec<ModuleEvent>,
+    next_pid: i32,
+    // `None` fails every call, `Some(n)` fails the next n calls
+    failures: HashMap<Operation, Option<u32>>,
+    latency: Duration,
+}
+
+impl State {
+    fn check_failure(&mut self, op: Operation) -> Result<(), Error> {
+        let remaining = match self.failures.get(&op) {
+            None => return Ok(()),
+            Some(remaining) => *remaining,
+        };
+        match remaining {
+            Some(0) => {
+                self.failures.remove(&op);
+                Ok(())
+            }
+            Some(1) => {
+                self.failures.remove(&op);
+                Err(Error::Injected(op))
+            }
+            Some(n) => {
+                self.failures.insert(op, Some(n - 1));
+                Err(Error::Injected(op))
+            }
+            None => Err(Error::Injected(op)),
+        }
+    }
+
+    fn module(&mut self, name: &str) -> Result<&mut ModuleEntry, Error> {
+        self.modules
+            .get_mut(name)
+            .ok_or_else(|| Error::NotFound(name.to_string()))
+    }
+
+    fn record(&mut self, name: &str, action: ModuleAction, exit_code: Option<i64>) {
+        let event = ModuleEvent::new(name.to_string(), action)
+            .with_time(Some(Utc::now()))
+            .with_exit_code(exit_code);
+        self.events.push(event);
+    }
+
+    fn start(&mut self, name: &str) -> Result<(), Error> {
+        let pid = FIRST_PID + self.next_pid;
+        {
+            let module = self.module(name)?;
+            if module.is_running() {
+                return Err(Error::NotModified(name.to_string()));
+            }
+            module.status = ModuleStatus::Running;
+            module.pid = Some(pid);
+            module.exit_code = None;
+            module.started_at = Some(Utc::now());
+            module
+                .logs
+                .push((OutputStream::Stdout, format!("Started module {}", name)));
+        }
+        self.next_pid += 1;
+        self.record(name, ModuleAction::Start, None);
+        Ok(())
+    }
+
+    fn exit(&mut self, name: &str, exit_code: i64, action: ModuleAction) -> Result<(), Error> {
+        {
+            let module = self.module(name)?;
+            if !module.is_running() {
+                return Err(Error::NotModified(name.to_string()));
+            }
+            module.status = if exit_code == 0 {
+                ModuleStatus::Stopped
+            } else {
+                ModuleStatus::Failed
+            };
+            module.pid = None;
+            module.exit_code = Some(exit_code);
+            module.finished_at = Some(Utc::now());
+            module.logs.push((
+                OutputStream::Stdout,
+                format!("Module {} exited with code {}", name, exit_code),
+            ));
+        }
+        self.record(name, action, Some(exit_code));
+        Ok(())
+    }
+
+    fn remove(&mut self, name: &str) -> Result<(), Error> {
+        self.modules
+            .remove(name)
+            .ok_or_else(|| Error::NotFound(name.to_string()))?;
+        self.record(name, ModuleAction::Destroy, None);
+        Ok(())
+    }
+
+    fn volume(&self, name: &str) -> Result<Volume, Error> {
+        self.volumes
+            .get(name)
+            .map(|module| Volume::new(name.to_string()).with_module(module.clone()))
+            .ok_or_else(|| Error::VolumeNotFound(name.to_string()))
+    }
+
+    fn logs(&mut self, name: &str, tail: Option<u64>) -> Result<Vec<u8>, Error> {
+        let module = self.module(name)?;
+        let skip = tail.map_or(0, |n| module.logs.len().saturating_sub(n as usize));
+        let mut output = Vec::new();
+        for &(stream, ref line) in module.logs.iter().skip(skip) {
+            frame(stream, format!("{}\n", line).as_bytes(), &mut output);
+        }
+        Ok(output)
+    }
+}
+
+// Docker style framing, so that clients can demultiplex the output the same
+// way they do for containers.
+fn frame(stream: OutputStream, data: &[u8], output: &mut Vec<u8>) {
+    let len = data.len() as u32;
+    output.extend_from_slice(&[
+        stream.frame_type(),
+        0,
+        0,
+        0,
+        (len >> 24) as u8,
+        (len >> 16) as u8,
+        (len >> 8) as u8,
+        len as u8,
+    ]);
+    output.extend_from_slice(data);
+}
+
+fn lock(state: &Arc<Mutex<State>>) -> MutexGuard<State> {
+    state.lock().expect("memory runtime lock poisoned")
+}
+
+// Runs an operation against the shared state once the configured latency has
+// passed.
+fn run<T, F>(
+    state: &Arc<Mutex<State>>,
+    op: Operation,
+    f: F,
+) -> Box<Future<Item = T, Error = Error> + Send>
+where
+    T: 'static + Send,
+    F: 'static + Send + FnOnce(&mut State) -> Result<T, Error>,
+{
+    let latency = lock(state).latency;
+    let state = state.clone();
+    let apply = move || {
+        let mut state = lock(&state);
+        state.check_failure(op).and_then(|_| f(&mut *state))
+    };
+
+    if latency == Duration::from_secs(0) {
+        Box::new(future::result(apply()))
+    } else {
+        let (tx, rx) = oneshot::channel();
+        thread::spawn(move || {
+            thread::sleep(latency);
+            tx.send(apply()).unwrap_or(());
+        });
+        Box::new(rx.then(|result| result.unwrap_or(Err(Error::Canceled))))
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct MemoryModule {
+    name: String,
+    type_: String,
+    config: MemoryConfig,
+    spec_hash: Option<String>,
+    state: ModuleRuntimeState,
+}
+
+impl Module for MemoryModule {
+    type Config = MemoryConfig;
+    type Error = Error;
+    type RuntimeStateFuture = FutureResult<ModuleRuntimeState, Self::Error>;
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn type_(&self) -> &str {
+        &self.type_
+    }
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn spec_hash(&self) -> Option<&str> {
+        self.spec_hash.as_ref().map(AsRef::as_ref)
+    }
+
+    fn runtime_state(&self) -> Self::RuntimeStateFuture {
+        future::ok(self.state.clone())
+    }
+}
+
+/// Output of a module or of a command run in it, framed the way Docker
+/// frames container output.
+pub struct MemoryLogs(Option<Vec<u8>>);
+
+impl Stream for MemoryLogs {
+    type Item = Vec<u8>;
+    type Error = Error;
+
+    fn poll(&mut self) -> Poll<Option<Self::Item>, Self::Error> {
+        Ok(Async::Ready(self.0.take()))
+    }
+}
+
+impl From<MemoryLogs> for Body {
+    fn from(logs: MemoryLogs) -> Body {
+        logs.0.map(Body::from).unwrap_or_else(Body::empty)
+    }
+}
+
+/// Keeps track of the images that were pulled or loaded. An image archive
+/// here holds the names of its images, one per line.
+#[derive(Clone)]
+pub struct MemoryRegistry {
+    state: Arc<Mutex<State>>,
+}
+
+impl MemoryRegistry {
+    pub fn images(&self) -> Vec<String> {
+        lock(&self.state).images.iter().cloned().collect()
+    }
+}
+
+impl ModuleRegistry for MemoryRegistry {
+    type Error = Error;
+    type PullFuture = Box<Future<Item = (), Error = Self::Error> + Send>;
+    type RemoveFuture = Box<Future<Item = (), Error = Self::Error> + Send>;
+    type ListImagesFuture = Box<Future<Item = Vec<Image>, Error = Self::Error> + Send>;
+    type PruneImagesFuture = Box<Future<Item = PruneResult, Error = Self::Error> + Send>;
+    type LoadImagesFuture = Box<Future<Item = (), Error = Self::Error>>;
+    type SaveImageFuture = Box<Future<Item = Self::ImageArchive, Error = Self::Error> + Send>;
+    type ImageChunk = Vec<u8>;
+    type ImageArchive = MemoryLogs;
+    type Config = MemoryConfig;
+
+    fn pull(&self, config: &Self::Config) -> Self::PullFuture {
+        let image = config.image().to_string();
+        run(&self.state, Operation::Pull, move |state| {
+            state.images.insert(image);
+            Ok(())
+        })
+    }
+
+    fn remove(&self, name: &str) -> Self::RemoveFuture {
+        let image = name.to_string();
+        run(&self.state, Operation::RemoveImage, move |state| {
+            if state.images.remove(&image) {
+                Ok(())
+            } else {
+                Err(Error::ImageNotFound(image))
+            }
+        })
+    }
+
+    fn list_images(&self) -> Self::ListImagesFuture {
+        run(&self.state, Operation::ListImages, |state| {
+            Ok(state
+                .images
+                .iter()
+                .map(|image| Image::new(image.clone()).with_names(vec![image.clone()]))
+                .collect())
+        })
+    }
+
+    // Images have no age or size here, so only the images used by modules
+    // are kept.
+    fn prune_images(&self, _options: &PruneOptions) -> Self::PruneImagesFuture {
+        run(&self.state, Operation::PruneImages, |state| {
+            let in_use: HashSet<String> = state
+                .modules
+                .values()
+                .map(|module| module.config.image().to_string())
+                .collect();
+            let mut deleted: Vec<String> = state.images.difference(&in_use).cloned().collect();
+            deleted.sort();
+            for image in &deleted {
+                state.images.remove(image);
+            }
+            Ok(PruneResult::new(deleted, 0))
+        })
+    }
+
+    fn load_images(&self, archive: ArchiveStream) -> Self::LoadImagesFuture {
+        let state = self.state.clone();
+        let load = archive
+            .map_err(|_| Error::InvalidArchive)
+            .concat2()
+            .and_then(move |archive| {
+                run(&state, Operation::LoadImages, move |state| {
+                    let names = String::from_utf8(archive).map_err(|_| Error::InvalidArchive)?;
+                    state.images.extend(
+                        names
+                            .lines()
+                            .filter(|name| !name.is_empty())
+                            .map(ToString::to_string),
+                    );
+                    Ok(())
+                })
+            });
+        Box::new(load)
+    }
+
+    fn save_image(&self, name: &str) -> Self::SaveImageFuture {
+        let image = name.to_string();
+        run(&self.state, Operation::SaveImage, move |state| {
+            if state.images.contains(&image) {
+                Ok(MemoryLogs(Some(image.into_bytes())))
+            } else {
+                Err(Error::ImageNotFound(image))
+            }
+        })
+    }
+}
+
+/// An in-memory `ModuleRuntime`. Clones share the same modules.
+#[derive(Clone)]
+pub struct MemoryRuntime {
+    state: Arc<Mutex<State>>,
+    registry: MemoryRegistry,
+}
+
+impl MemoryRuntime {
+    pub fn new() -> Self {
+        let state = Arc::new(Mutex::new(State::default()));
+        MemoryRuntime {
+            registry: MemoryRegistry {
+                state: state.clone(),
+            },
+            state,
+        }
+    }
+
+    /// Makes every call of the operation fail.
+    pub fn with_failure(self, op: Operation) -> Self {
+        self.fail(op, None);
+        self
+    }
+
+    /// Delays the result of every operation.
+    pub fn with_latency(self, latency: Duration) -> Self {
+        self.set_latency(latency);
+        self
+    }
+
+    /// Makes the next `times` calls of the operation fail, or every call if
+    /// `times` is `None`.
+    pub fn fail(&self, op: Operation, times: Option<u32>) {
+        lock(&self.state).failures.insert(op, times);
+    }
+
+    pub fn clear_failures(&self) {
+        lock(&self.state).failures.clear();
+    }
+
+    pub fn set_latency(&self, latency: Duration) {
+        lock(&self.state).latency = latency;
+    }
+
+    /// Simulates the module exiting on its own. A zero exit code leaves the
+    /// module stopped, anything else leaves it failed.
+    pub fn exit(&self, name: &str, exit_code: i64) -> Result<(), Error> {
+        lock(&self.state).exit(name, exit_code, ModuleAction::Die)
+    }
+
+    /// Appends a line to the output of the module.
+    pub fn write_log(&self, name: &str, stream: OutputStream, line: &str) -> Result<(), Error> {
+        lock(&self.state)
+            .module(name)
+            .map(|module| module.logs.push((stream, line.to_string())))
+    }
+
+    pub fn pid(&self, name: &str) -> Option<i32> {
+        lock(&self.state)
+            .modules
+            .get(name)
+            .and_then(|module| module.pid)
+    }
+
+    pub fn status(&self, name: &str) -> Option<ModuleStatus> {
+        lock(&self.state)
+            .modules
+            .get(name)
+            .map(|module| module.status.clone())
+    }
+}
+
+impl Default for MemoryRuntime {
+    fn default() -> Self {
+        MemoryRuntime::new()
+    }
+}
+
+impl ModuleRuntime for MemoryRuntime {
+    type Error = Error;
+    type Config = MemoryConfig;
+    type Module = MemoryModule;
+    type ModuleRegistry = MemoryRegistry;
+    type Chunk = Vec<u8>;
+    type Logs = MemoryLogs;
+    type Stats = IterOk<IntoIter<ModuleStats>, Self::Error>;
+    type Events = IterOk<IntoIter<ModuleEvent>, Self::Error>;
+
+    type CreateFuture = Box<Future<Item = (), Error = Self::Error> + Send>;
+    type EventsFuture = Box<Future<Item = Self::Events, Error = Self::Error> + Send>;
+    type ExecFuture = Box<Future<Item = Self::Logs, Error = Self::Error> + Send>;
+    type GetArchiveFuture = Box<Future<Item = Self::Logs, Error = Self::Error> + Send>;
+    type InitFuture = Box<Future<Item = (), Error = Self::Error> + Send>;
+    type ListFuture = Box<Future<Item = Vec<Self::Module>, Error = Self::Error> + Send>;
+    type LogsFuture = Box<Future<Item = Self::Logs, Error = Self::Error> + Send>;
+    type RemoveFuture = Box<Future<Item = (), Error = Self::Error> + Send>;
+    type PauseFuture = Box<Future<Item = (), Error = Self::Error> + Send>;
+    type PutArchiveFuture = Box<Future<Item = (), Error = Self::Error>>;
+    type RestartFuture = Box<Future<Item = (), Error = Self::Error> + Send>;
+    type StatsFuture = Box<Future<Item = Self::Stats, Error = Self::Error> + Send>;
+    type StartFuture = Box<Future<Item = (), Error = Self::Error> + Send>;
+    type StopFuture = Box<Future<Item = (), Error = Self::Error> + Send>;
+    type UnpauseFuture = Box<Future<Item = (), Error = Self::Error> + Send>;
+    type SystemInfoFuture = Box<Future<Item = SystemInfo, Error = Self::Error> + Send>;
+    type RemoveAllFuture = Box<Future<Item = (), Error = Self::Error> + Send>;
+    type ListVolumesFuture = Box<Future<Item = Vec<Volume>, Error = Self::Error> + Send>;
+    type InspectVolumeFuture = Box<Future<Item = Volume, Error = Self::Error> + Send>;
+    type CreateVolumeFuture = Box<Future<Item = Volume, Error = Self::Error> + Send>;
+    type RemoveVolumeFuture = Box<Future<Item = (), Error = Self::Error> + Send>;
+    type BackupVolumeFuture = Box<Future<Item = Self::Logs, Error = Self::Error> + Send>;
+
+    fn init(&self) -> Self::InitFuture {
+        run(&self.state, Operation::Init, |_| Ok(()))
+    }
+
+    fn create(&self, module: ModuleSpec<Self::Config>) -> Self::CreateFuture {
+        run(&self.state, Operation::Create, move |state| {
+            let name = module.name().to_string();
+            if state.modules.contains_key(&name) {
+                return Err(Error::Conflict(name));
+            }
+            let entry = ModuleEntry {
+                type_: module.type_().to_string(),
+                config: module.config().clone(),
+                spec_hash: module.recorded_spec_hash().ok().and_then(|hash| hash),
+                status: ModuleStatus::Created,
+                pid: None,
+                exit_code: None,
+                started_at: None,
+                finished_at: None,
+                restart_count: 0,
+                logs: Vec::new(),
+                files: HashMap::new(),
+            };
+            state.modules.insert(name.clone(), entry);
+            state.record(&name, ModuleAction::Create, None);
+            Ok(())
+        })
+    }
+
+    fn start(&self, id: &str) -> Self::StartFuture {
+        let name = id.to_string();
+        run(&self.state, Operation::Start, move |state| {
+            state.start(&name)
+        })
+    }
+
+    fn stop(&self, id: &str, _wait_before_kill: Option<Duration>) -> Self::StopFuture {
+        let name = id.to_string();
+        run(&self.state, Operation::Stop, move |state| {
+            state.exit(&name, 0, ModuleAction::Stop)
+        })
+    }
+
+    fn restart(&self, id: &str) -> Self::RestartFuture {
+        let name = id.to_string();
+        run(&self.state, Operation::Restart, move |state| {
+            if state.module(&name)?.is_running() {
+                state.exit(&name, 0, ModuleAction::Stop)?;
+            }
+            state.start(&name)?;
+            state.module(&name)?.restart_count += 1;
+            state.record(&name, ModuleAction::Restart, None);
+            Ok(())
+        })
+    }
+
+    fn pause(&self, id: &str) -> Self::PauseFuture {
+        let name = id.to_string();
+        run(&self.state, Operation::Pause, move |state| {
+            {
+                let module = state.module(&name)?;
+                match module.status {
+                    ModuleStatus::Running => module.status = ModuleStatus::Paused,
+                    ModuleStatus::Paused => return Err(Error::NotModified(name)),
+                    _ => return Err(Error::Conflict(name)),
+                }
+            }
+            state.record(&name, ModuleAction::Pause, None);
+            Ok(())
+        })
+    }
+
+    fn unpause(&self, id: &str) -> Self::UnpauseFuture {
+        let name = id.to_string();
+        run(&self.state, Operation::Unpause, move |state| {
+            {
+                let module = state.module(&name)?;
+                match module.status {
+                    ModuleStatus::Paused => module.status = ModuleStatus::Running,
+                    ModuleStatus::Running => return Err(Error::NotModified(name)),
+                    _ => return Err(Error::Conflict(name)),
+                }
+            }
+            state.record(&name, ModuleAction::Unpause, None);
+            Ok(())
+        })
+    }
+
+    fn remove(&self, id: &str) -> Self::RemoveFuture {
+        let name = id.to_string();
+        run(&self.state, Operation::Remove, move |state| {
+            state.remove(&name)
+        })
+    }
+
+    fn remove_with_volumes(&self, id: &str) -> Self::RemoveFuture {
+        let name = id.to_string();
+        run(&self.state, Operation::Remove, move |state| {
+            state.remove(&name)?;
+            state
+                .volumes
+                .retain(|_, module| module.as_ref() != Some(&name));
+            Ok(())
+        })
+    }
+
+    fn system_info(&self) -> Self::SystemInfoFuture {
+        run(&self.state, Operation::SystemInfo, |_| {
+            Ok(SystemInfo::new(
+                env::consts::OS.to_string(),
+                env::consts::ARCH.to_string(),
+            ))
+        })
+    }
+
+    fn list(&self) -> Self::ListFuture {
+        run(&self.state, Operation::List, |state| {
+            Ok(state
+                .modules
+                .iter()
+                .map(|(name, module)| module.module(name))
+                .collect())
+        })
+    }
+
+    fn logs(&self, id: &str, options: &LogOptions) -> Self::LogsFuture {
+        let name = id.to_string();
+        let tail = match *options.tail() {
+            LogTail::All => None,
+            LogTail::Num(n) => Some(n),
+        };
+        run(&self.state, Operation::Logs, move |state| {
+            state.logs(&name, tail).map(|logs| MemoryLogs(Some(logs)))
+        })
+    }
+
+    fn stats(&self, id: &str, _options: &StatsOptions) -> Self::StatsFuture {
+        let name = id.to_string();
+        run(&self.state, Operation::Stats, move |state| {
+            if state.module(&name)?.is_running() {
+                Ok(stream::iter_ok(vec![
+                    ModuleStats::default().with_read(Some(Utc::now()))
+                ]))
+            } else {
+                Err(Error::Conflict(name))
+            }
+        })
+    }
+
+    fn events(&self, _options: &EventOptions) -> Self::EventsFuture {
+        run(&self.state, Operation::Events, |state| {
+            Ok(stream::iter_ok(state.events.clone()))
+        })
+    }
+
+    // Commands are not run, their command line is echoed back as output.
+    fn exec(&self, id: &str, options: &ExecOptions) -> Self::ExecFuture {
+        let name = id.to_string();
+        let cmd = options.cmd().join(" ");
+        run(&self.state, Operation::Exec, move |state| {
+            if state.module(&name)?.status != ModuleStatus::Running {
+                return Err(Error::Conflict(name));
+            }
+            let mut output = Vec::new();
+            frame(
+                OutputStream::Stdout,
+                format!("{}\n", cmd).as_bytes(),
+                &mut output,
+            );
+            Ok(MemoryLogs(Some(output)))
+        })
+    }
+
+    fn get_archive(&self, id: &str, path: &str) -> Self::GetArchiveFuture {
+        let name = id.to_string();
+        let path = path.to_string();
+        run(&self.state, Operation::GetArchive, move |state| {
+            state
+                .module(&name)?
+                .files
+                .get(&path)
+                .cloned()
+                .map(|archive| MemoryLogs(Some(archive)))
+                .ok_or_else(|| Error::NotFound(format!("{}:{}", name, path)))
+        })
+    }
+
+    fn put_archive(&self, id: &str, path: &str, archive: ArchiveStream) -> Self::PutArchiveFuture {
+        let state = self.state.clone();
+        let name = id.to_string();
+        let path = path.to_string();
+        let put = archive
+            .map_err(|_| Error::InvalidArchive)
+            .concat2()
+            .and_then(move |archive| {
+                run(&state, Operation::PutArchive, move |state| {
+                    state.module(&name)?.files.insert(path, archive);
+                    Ok(())
+                })
+            });
+        Box::new(put)
+    }
+
+    fn registry(&self) -> &Self::ModuleRegistry {
+        &self.registry
+    }
+
+    fn remove_all(&self) -> Self::RemoveAllFuture {
+        run(&self.state, Operation::RemoveAll, |state| {
+            let names: Vec<String> = state.modules.drain().map(|(name, _)| name).collect();
+            for name in names {
+                state.record(&name, ModuleAction::Destroy, None);
+            }
+            Ok(())
+        })
+    }
+
+    fn list_volumes(&self) -> Self::ListVolumesFuture {
+        run(&self.state, Operation::ListVolumes, |state| {
+            let mut names: Vec<&String> = state.volumes.keys().collect();
+            names.sort();
+            names.into_iter().map(|name| state.volume(name)).collect()
+        })
+    }
+
+    fn inspect_volume(&self, name: &str) -> Self::InspectVolumeFuture {
+        let name = name.to_string();
+        run(&self.state, Operation::InspectVolume, move |state| {
+            state.volume(&name)
+        })
+    }
+
+    fn create_volume(&self, name: &str, module: Option<&str>) -> Self::CreateVolumeFuture {
+        let name = name.to_string();
+        let module = module.map(ToString::to_string);
+        run(&self.state, Operation::CreateVolume, move |state| {
+            state.volumes.entry(name.clone()).or_insert(module);
+            state.volume(&name)
+        })
+    }
+
+    // A volume cannot be removed while the module it belongs to exists.
+    fn remove_volume(&self, name: &str) -> Self::RemoveVolumeFuture {
+        let name = name.to_string();
+        run(&self.state, Operation::RemoveVolume, move |state| {
+            let module = state.volume(&name)?.module().map(ToString::to_string);
+            match module {
+                Some(ref module) if state.modules.contains_key(module) => {
+                    Err(Error::VolumeInUse(name, module.clone()))
+                }
+                _ => {
+                    state.volumes.remove(&name);
+                    Ok(())
+                }
+            }
+        })
+    }
+
+    // A volume archive here holds the name of the volume.
+    fn backup_volume(&self, name: &str) -> Self::BackupVolumeFuture {
+        let name = name.to_string();
+        run(&self.state, Operation::BackupVolume, move |state| {
+            state.volume(&name)?;
+            Ok(MemoryLogs(Some(name.into_bytes())))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec(name: &str) -> ModuleSpec<MemoryConfig> {
+        ModuleSpec::new(
+            name,
+            "docker",
+            MemoryConfig::new("microsoft/test-image".to_string()),
+            HashMap::new(),
+        ).unwrap()
+    }
+
+    fn runtime_with_module(name: &str) -> MemoryRuntime {
+        let runtime = MemoryRuntime::new();
+        runtime.create(spec(name)).wait().unwrap();
+        runtime
+    }
+
+    fn logs(runtime: &MemoryRuntime, name: &str, tail: LogTail) -> Vec<u8> {
+        runtime
+            .logs(name, &LogOptions::new().with_tail(tail))
+            .and_then(|logs| logs.concat2())
+            .wait()
+            .unwrap()
+    }
+
+    #[test]
+    fn lifecycle() {
+        let runtime = runtime_with_module("mod1");
+        assert_eq!(Some(ModuleStatus::Created), runtime.status("mod1"));
+        assert_eq!(None, runtime.pid("mod1"));
+
+        runtime.start("mod1").wait().unwrap();
+        assert_eq!(Some(ModuleStatus::Running), runtime.status("mod1"));
+        assert_eq!(Some(FIRST_PID), runtime.pid("mod1"));
+
+        runtime.pause("mod1").wait().unwrap();
+        assert_eq!(Some(ModuleStatus::Paused), runtime.status("mod1"));
+        runtime.unpause("mod1").wait().unwrap();
+
+        runtime.stop("mod1", None).wait().unwrap();
+        assert_eq!(Some(ModuleStatus::Stopped), runtime.status("mod1"));
+        assert_eq!(None, runtime.pid("mod1"));
+
+        runtime.remove("mod1").wait().unwrap();
+        assert_eq!(None, runtime.status("mod1"));
+        assert!(runtime.list().wait().unwrap().is_empty());
+    }
+
+    #[test]
+    fn invalid_transitions_fail() {
+        let runtime = runtime_with_module("mod1");
+
+        assert_eq!(
+            Error::Conflict("mod1".to_string()),
+            runtime.create(spec("mod1")).wait().unwrap_err()
+        );
+        assert_eq!(
+            Error::NotModified("mod1".to_string()),
+            runtime.stop("mod1", None).wait().unwrap_err()
+        );
+        assert_eq!(
+            Error::Conflict("mod1".to_string()),
+            runtime.pause("mod1").wait().unwrap_err()
+        );
+        assert_eq!(
+            Error::NotFound("mod2".to_string()),
+            runtime.start("mod2").wait().unwrap_err()
+        );
+
+        runtime.start("mod1").wait().unwrap();
+        assert_eq!(
+            Error::NotModified("mod1".to_string()),
+            runtime.start("mod1").wait().unwrap_err()
+        );
+    }
+
+    #[test]
+    fn restart_counts_restarts() {
+        let runtime = runtime_with_module("mod1");
+        runtime.start("mod1").wait().unwrap();
+        runtime.restart("mod1").wait().unwrap();
+
+        let module = runtime.list().wait().unwrap().pop().unwrap();
+        let state = module.runtime_state().wait().unwrap();
+        assert_eq!(ModuleStatus::Running, *state.status());
+        assert_eq!(1, state.restart_count());
+        assert_eq!(&Pid::Value(FIRST_PID + 1), state.pid());
+    }
+
+    #[test]
+    fn exit_reports_failure() {
+        let runtime = runtime_with_module("mod1");
+        runtime.start("mod1").wait().unwrap();
+        runtime.exit("mod1", 137).unwrap();
+
+        let module = runtime.list().wait().unwrap().pop().unwrap();
+        let state = module.runtime_state().wait().unwrap();
+        assert_eq!(ModuleStatus::Failed, *state.status());
+        assert_eq!(Some(&137), state.exit_code());
+    }
+
+    #[test]
+    fn list_reports_spec_hash() {
+        let runtime = MemoryRuntime::new();
+        let spec = spec("mod1").with_record_spec_hash(true);
+        runtime.create(spec.clone()).wait().unwrap();
+        let module = runtime.list().wait().unwrap().pop().unwrap();
+        assert_eq!(
+            spec.spec_hash().ok().as_ref().map(AsRef::as_ref),
+            module.spec_hash()
+        );
+    }
+
+    #[test]
+    fn list_reports_no_spec_hash_unless_asked() {
+        let runtime = runtime_with_module("mod1");
+        let module = runtime.list().wait().unwrap().pop().unwrap();
+        assert_eq!(None, module.spec_hash());
+    }
+
+    #[test]
+    fn logs_are_framed_and_tailed() {
+        let runtime = runtime_with_module("mod1");
+        runtime.start("mod1").wait().unwrap();
+        runtime
+            .write_log("mod1", OutputStream::Stderr, "oops")
+            .unwrap();
+
+        let all = logs(&runtime, "mod1", LogTail::All);
+        assert_eq!(1, all[0]);
+
+        let last = logs(&runtime, "mod1", LogTail::Num(1));
+        assert_eq!(vec![2, 0, 0, 0, 0, 0, 0, 5], &last[..8]);
+        assert_eq!(b"oops\n", &last[8..]);
+    }
+
+    #[test]
+    fn events_are_recorded() {
+        let runtime = runtime_with_module("mod1");
+        runtime.start("mod1").wait().unwrap();
+
+        let events = runtime
+            .events(&EventOptions::new())
+            .and_then(|events| events.collect())
+            .wait()
+            .unwrap();
+        let actions: Vec<&ModuleAction> = events.iter().map(ModuleEvent::action).collect();
+        assert_eq!(vec![&ModuleAction::Create, &ModuleAction::Start], actions);
+        assert!(events.iter().all(|event| event.time().is_some()));
+    }
+
+    #[test]
+    fn archives_round_trip() {
+        let runtime = runtime_with_module("mod1");
+        runtime
+            .put_archive(
+                "mod1",
+                "/data",
+                Box::new(stream::iter_ok(vec![vec![1, 2], vec![3]])),
+            ).wait()
+            .unwrap();
+
+        let archive = runtime
+            .get_archive("mod1", "/data")
+            .and_then(|archive| archive.concat2())
+            .wait()
+            .unwrap();
+        assert_eq!(vec![1, 2, 3], archive);
+        assert!(runtime.get_archive("mod1", "/other").wait().is_err());
+    }
+
+    #[test]
+    fn registry_tracks_images() {
+        let runtime = MemoryRuntime::new();
+        let config = MemoryConfig::new("microsoft/test-image".to_string());
+        runtime.registry().pull(&config).wait().unwrap();
+        assert_eq!(
+            vec!["microsoft/test-image".to_string()],
+            runtime.registry().images()
+        );
+
+        ModuleRegistry::remove(runtime.registry(), "microsoft/test-image")
+            .wait()
+            .unwrap();
+        assert_eq!(
+            Error::ImageNotFound("microsoft/test-image".to_string()),
+            ModuleRegistry::remove(runtime.registry(), "microsoft/test-image")
+                .wait()
+                .unwrap_err()
+        );
+    }
+
+    #[test]
+    fn saved_images_load() {
+        let runtime = MemoryRuntime::new();
+        let registry = runtime.registry();
+        registry
+            .load_images(Box::new(stream::iter_ok(vec![
+                b"microsoft/test-image\n".to_vec(),
+                b"microsoft/old-image\n".to_vec(),
+            ])))
+            .wait()
+            .unwrap();
+        let mut images = registry.images();
+        images.sort();
+        assert_eq!(
+            vec![
+                "microsoft/old-image".to_string(),
+                "microsoft/test-image".to_string(),
+            ],
+            images
+        );
+
+        let archive = registry
+            .save_image("microsoft/test-image")
+            .and_then(|archive| archive.concat2())
+            .wait()
+            .unwrap();
+        assert_eq!(b"microsoft/test-image".to_vec(), archive);
+        assert_eq!(
+            Error::ImageNotFound("microsoft/other-image".to_string()),
+            registry
+                .save_image("microsoft/other-image")
+                .wait()
+                .err()
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn prune_keeps_images_in_use() {
+        let runtime = runtime_with_module("mod1");
+        let registry = runtime.registry();
+        registry
+            .pull(&MemoryConfig::new("microsoft/test-image".to_string()))
+            .wait()
+            .unwrap();
+        registry
+            .pull(&MemoryConfig::new("microsoft/old-image".to_string()))
+            .wait()
+            .unwrap();
+        assert_eq!(2, registry.list_images().wait().unwrap().len());
+
+        let result = registry.prune_images(&PruneOptions::new()).wait().unwrap();
+        assert_eq!(
+            &["microsoft/old-image".to_string()],
+            result.images_deleted()
+        );
+        assert_eq!(vec!["microsoft/test-image".to_string()], registry.images());
+    }
+
+    #[test]
+    fn volumes_are_removed_with_their_module() {
+        let runtime = runtime_with_module("mod1");
+        runtime
+            .create_volume("mod1-data", Some("mod1"))
+            .wait()
+            .unwrap();
+        runtime.create_volume("shared", None).wait().unwrap();
+
+        assert_eq!(
+            Error::VolumeInUse("mod1-data".to_string(), "mod1".to_string()),
+            runtime.remove_volume("mod1-data").wait().unwrap_err()
+        );
+
+        runtime.remove_with_volumes("mod1").wait().unwrap();
+        let volumes = runtime.list_volumes().wait().unwrap();
+        assert_eq!(vec![Volume::new("shared".to_string())], volumes);
+        assert_eq!(
+            Error::VolumeNotFound("mod1-data".to_string()),
+            runtime.inspect_volume("mod1-data").wait().unwrap_err()
+        );
+    }
+
+    #[test]
+    fn injected_failures_are_counted() {
+        let runtime = runtime_with_module("mod1");
+        runtime.fail(Operation::Start, Some(2));
+
+        assert_eq!(
+            Error::Injected(Operation::Start),
+            runtime.start("mod1").wait().unwrap_err()
+        );
+        assert_eq!(
+            Error::Injected(Operation::Start),
+            runtime.start("mod1").wait().unwrap_err()
+        );
+        runtime.start("mod1").wait().unwrap();
+    }
+
+    #[test]
+    fn injected_failures_until_cleared() {
+        let runtime = MemoryRuntime::new().with_failure(Operation::List);
+        assert!(runtime.list().wait().is_err());
+        assert!(runtime.list().wait().is_err());
+
+        runtime.clear_failures();
+        assert!(runtime.list().wait().is_ok());
+    }
+
+    #[test]
+    fn latency_delays_operations() {
+        let runtime = MemoryRuntime::new().with_latency(Duration::from_millis(50));
+        let create = runtime.create(spec("mod1"));
+
+        // nothing happens until the latency has passed
+        assert_eq!(None, runtime.status("mod1"));
+        create.wait().unwrap();
+        assert_eq!(Some(ModuleStatus::Created), runtime.status("mod1"));
+    }
+}
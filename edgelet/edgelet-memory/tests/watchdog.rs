@@ -0,0 +1,85 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+#![deny(warnings)]
+
+extern crate futures;
+extern crate tokio;
+
+extern crate edgelet_core;
+extern crate edgelet_memory;
+extern crate edgelet_test_utils;
+
+use std::collections::HashMap;
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use futures::prelude::*;
+use futures::sync::oneshot;
+use tokio::runtime::Runtime;
+
+use edgelet_core::watchdog::{RestartPolicy, Watchdog, WatchdogSettings, WatchdogStatus};
+use edgelet_core::{AuthType, ModuleSpec, ModuleStatus};
+use edgelet_memory::{MemoryConfig, MemoryRuntime, Operation};
+use edgelet_test_utils::identity::{TestIdentity, TestIdentityManager};
+
+fn wait_for_status(runtime: &MemoryRuntime, name: &str, status: &ModuleStatus) {
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while runtime.status(name).as_ref() != Some(status) {
+        assert!(Instant::now() < deadline, "module never reached {}", status);
+        thread::sleep(Duration::from_millis(50));
+    }
+}
+
+#[test]
+fn watchdog_keeps_edge_runtime_running() {
+    let runtime = MemoryRuntime::new().with_latency(Duration::from_millis(10));
+    // the first attempt to start the agent fails, the watchdog has to retry
+    runtime.fail(Operation::Start, Some(1));
+
+    let id_mgr = TestIdentityManager::new(vec![TestIdentity::new(
+        "$edgeAgent",
+        "iotedge",
+        "1",
+        AuthType::Sas,
+    )]).with_fail_get(false);
+    let settings = WatchdogSettings::new(1, RestartPolicy::new(0, 0, 10, 0));
+    let watchdog = Watchdog::new(runtime.clone(), id_mgr, settings, WatchdogStatus::new());
+
+    let spec = ModuleSpec::new(
+        "edgeAgent",
+        "docker",
+        MemoryConfig::new("microsoft/azureiotedge-agent:1.0".to_string()),
+        HashMap::new(),
+    ).unwrap();
+
+    let (shutdown_tx, shutdown_rx) = oneshot::channel();
+    let (done_tx, done_rx) = mpsc::channel();
+    let mut rt = Runtime::new().unwrap();
+    rt.spawn(
+        watchdog
+            .run_until(spec, "$edgeAgent", shutdown_rx.map_err(|_| ()))
+            .then(move |result| {
+                done_tx.send(result.is_ok()).unwrap();
+                Ok(())
+            }),
+    );
+
+    wait_for_status(&runtime, "edgeAgent", &ModuleStatus::Running);
+    let pid = runtime.pid("edgeAgent");
+    assert_eq!(
+        vec!["microsoft/azureiotedge-agent:1.0".to_string()],
+        runtime.registry().images()
+    );
+
+    // the watchdog brings a crashed agent back up
+    runtime.exit("edgeAgent", 1).unwrap();
+    wait_for_status(&runtime, "edgeAgent", &ModuleStatus::Running);
+    assert_ne!(pid, runtime.pid("edgeAgent"));
+
+    shutdown_tx.send(()).unwrap();
+    assert!(done_rx.recv().unwrap());
+    assert_eq!(Some(ModuleStatus::Stopped), runtime.status("edgeAgent"));
+
+    rt.shutdown_now().wait().unwrap();
+}
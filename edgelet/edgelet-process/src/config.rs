@@ -0,0 +1,110 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+use error::Result;
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ProcessConfig {
+    executable: String,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    args: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    working_dir: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    user: Option<String>,
+}
+
+impl ProcessConfig {
+    pub fn new(executable: &str) -> Result<ProcessConfig> {
+        let config = ProcessConfig {
+            executable: ensure_not_empty!(executable.to_string()),
+            args: Vec::new(),
+            working_dir: None,
+            user: None,
+        };
+        Ok(config)
+    }
+
+    pub fn executable(&self) -> &str {
+        &self.executable
+    }
+
+    pub fn args(&self) -> &[String] {
+        &self.args
+    }
+
+    pub fn with_args(mut self, args: Vec<String>) -> Self {
+        self.args = args;
+        self
+    }
+
+    pub fn working_dir(&self) -> Option<&str> {
+        self.working_dir.as_ref().map(AsRef::as_ref)
+    }
+
+    pub fn with_working_dir(mut self, working_dir: String) -> Self {
+        self.working_dir = Some(working_dir);
+        self
+    }
+
+    /// User to run the process as, either a user name or `uid[:gid]`.
+    pub fn user(&self) -> Option<&str> {
+        self.user.as_ref().map(AsRef::as_ref)
+    }
+
+    pub fn with_user(mut self, user: String) -> Self {
+        self.user = Some(user);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json;
+
+    #[test]
+    #[should_panic]
+    fn empty_executable_fails() {
+        ProcessConfig::new("").unwrap();
+    }
+
+    #[test]
+    #[should_panic]
+    fn white_space_executable_fails() {
+        ProcessConfig::new("    ").unwrap();
+    }
+
+    #[test]
+    fn process_config_ser() {
+        let config = ProcessConfig::new("/usr/bin/agent")
+            .unwrap()
+            .with_args(vec!["--verbose".to_string()])
+            .with_working_dir("/var/lib/agent".to_string())
+            .with_user("edgeagentuser".to_string());
+        let actual_json = serde_json::to_string(&config).unwrap();
+        let expected_json = json!({
+            "executable": "/usr/bin/agent",
+            "args": ["--verbose"],
+            "workingDir": "/var/lib/agent",
+            "user": "edgeagentuser"
+        });
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&actual_json).unwrap(),
+            expected_json
+        );
+    }
+
+    #[test]
+    fn process_config_deser_executable_only() {
+        let input_json = json!({
+            "executable": "/usr/bin/agent"
+        });
+        let config = serde_json::from_str::<ProcessConfig>(&input_json.to_string()).unwrap();
+        assert_eq!("/usr/bin/agent", config.executable());
+        assert!(config.args().is_empty());
+        assert_eq!(None, config.working_dir());
+        assert_eq!(None, config.user());
+    }
+}
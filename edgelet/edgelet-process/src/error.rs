@@ -0,0 +1,117 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+use std::fmt;
+use std::fmt::Display;
+use std::io;
+
+use failure::{Backtrace, Context, Fail};
+use hyper::Error as HyperError;
+use serde_json;
+
+use edgelet_core::{Error as CoreError, ErrorKind as CoreErrorKind};
+use edgelet_utils::Error as UtilsError;
+
+pub type Result<T> = ::std::result::Result<T, Error>;
+
+#[derive(Debug)]
+pub struct Error {
+    inner: Context<ErrorKind>,
+}
+
+#[derive(Debug, Fail)]
+pub enum ErrorKind {
+    #[fail(display = "Utils error")]
+    Utils,
+    #[fail(display = "IO error")]
+    Io,
+    #[fail(display = "Serde error")]
+    Serde,
+    #[fail(display = "Transport error")]
+    Transport,
+    #[fail(display = "Not found")]
+    NotFound,
+    #[fail(display = "Conflict with current operation")]
+    Conflict,
+    #[fail(display = "Process already in this state")]
+    NotModified,
+    #[fail(display = "Invalid user {}", _0)]
+    InvalidUser(String),
+    #[fail(display = "The process runtime does not support {}", _0)]
+    NotSupported(&'static str),
+    #[fail(display = "Process supervisor error")]
+    Supervisor,
+}
+
+impl Fail for Error {
+    fn cause(&self) -> Option<&Fail> {
+        self.inner.cause()
+    }
+
+    fn backtrace(&self) -> Option<&Backtrace> {
+        self.inner.backtrace()
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        Display::fmt(&self.inner, f)
+    }
+}
+
+impl Error {
+    pub fn kind(&self) -> &ErrorKind {
+        self.inner.get_context()
+    }
+}
+
+impl From<ErrorKind> for Error {
+    fn from(kind: ErrorKind) -> Error {
+        Error {
+            inner: Context::new(kind),
+        }
+    }
+}
+
+impl From<Context<ErrorKind>> for Error {
+    fn from(inner: Context<ErrorKind>) -> Error {
+        Error { inner }
+    }
+}
+
+impl From<UtilsError> for Error {
+    fn from(error: UtilsError) -> Error {
+        Error {
+            inner: error.context(ErrorKind::Utils),
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(error: io::Error) -> Error {
+        Error {
+            inner: error.context(ErrorKind::Io),
+        }
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(error: serde_json::Error) -> Error {
+        Error {
+            inner: error.context(ErrorKind::Serde),
+        }
+    }
+}
+
+impl From<HyperError> for Error {
+    fn from(error: HyperError) -> Error {
+        Error {
+            inner: error.context(ErrorKind::Transport),
+        }
+    }
+}
+
+impl From<Error> for CoreError {
+    fn from(err: Error) -> CoreError {
+        CoreError::from(err.context(CoreErrorKind::ModuleRuntime))
+    }
+}
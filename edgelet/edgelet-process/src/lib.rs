@@ -0,0 +1,43 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+#![deny(warnings)]
+
+extern crate chrono;
+#[macro_use]
+extern crate failure;
+#[macro_use]
+extern crate futures;
+extern crate hyper;
+#[cfg(unix)]
+extern crate libc;
+#[macro_use]
+extern crate log;
+extern crate serde;
+#[macro_use]
+extern crate serde_derive;
+// Need stuff other than macros from serde_json for non-test code.
+#[cfg(not(test))]
+extern crate serde_json;
+
+// Need macros from serde_json for unit tests.
+#[cfg(test)]
+#[macro_use]
+extern crate serde_json;
+#[cfg(test)]
+extern crate tempfile;
+
+extern crate edgelet_core;
+#[macro_use]
+extern crate edgelet_utils;
+
+mod config;
+mod error;
+mod logs;
+mod module;
+mod runtime;
+
+pub use config::ProcessConfig;
+pub use error::{Error, ErrorKind};
+pub use logs::{Chunk, Logs};
+pub use module::{ProcessModule, MODULE_TYPE};
+pub use runtime::ProcessModuleRuntime;
@@ -0,0 +1,305 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+use futures::prelude::*;
+use hyper::{Body, Chunk as HyperChunk};
+
+use edgelet_core::LogTail;
+use error::{Error, Result};
+
+const STDOUT_STREAM: u8 = 1;
+const STDERR_STREAM: u8 = 2;
+const STDOUT_SUFFIX: &str = ".stdout.log";
+const STDERR_SUFFIX: &str = ".stderr.log";
+
+/// Size a capture file may grow to before it is rotated.
+pub const MAX_LOG_BYTES: u64 = 10 * 1024 * 1024;
+
+/// Path of the file the stdout of a module is captured to.
+pub fn stdout_path(log_dir: &Path, name: &str) -> PathBuf {
+    log_dir.join(format!("{}{}", name, STDOUT_SUFFIX))
+}
+
+/// Path of the file the stderr of a module is captured to.
+pub fn stderr_path(log_dir: &Path, name: &str) -> PathBuf {
+    log_dir.join(format!("{}{}", name, STDERR_SUFFIX))
+}
+
+/// Path a capture file is moved to when it is rotated.
+pub fn rotated_path(path: &Path) -> PathBuf {
+    let mut rotated = path.as_os_str().to_owned();
+    rotated.push(".1");
+    PathBuf::from(rotated)
+}
+
+pub fn open_log(path: &Path) -> Result<File> {
+    let file = OpenOptions::new().create(true).append(true).open(path)?;
+    Ok(file)
+}
+
+/// Moves the content of a capture file that grew past `max_bytes` to its
+/// rotated file, replacing the previous one. The file is copied and then
+/// truncated rather than renamed, since a running module keeps appending to
+/// the file it was started with.
+pub fn rotate_log(path: &Path, max_bytes: u64) -> Result<()> {
+    match fs::metadata(path) {
+        Ok(ref metadata) if metadata.len() > max_bytes => {
+            debug!("Rotating log file {}", path.display());
+            fs::copy(path, rotated_path(path))?;
+            OpenOptions::new().write(true).open(path)?.set_len(0)?;
+            Ok(())
+        }
+        Ok(_) => Ok(()),
+        Err(ref err) if err.kind() == io::ErrorKind::NotFound => Ok(()),
+        Err(err) => Err(Error::from(err)),
+    }
+}
+
+/// Rotates every capture file in the log directory that grew past
+/// `max_bytes`.
+pub fn rotate_logs(log_dir: &Path, max_bytes: u64) -> Result<()> {
+    for entry in fs::read_dir(log_dir)? {
+        let path = entry?.path();
+        let captured = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .map_or(false, |name| {
+                name.ends_with(STDOUT_SUFFIX) || name.ends_with(STDERR_SUFFIX)
+            });
+        if captured {
+            rotate_log(&path, max_bytes)?;
+        }
+    }
+    Ok(())
+}
+
+/// Reads the captured output of a module, rotated files included. The result
+/// has the same stdout/stderr framing as the Docker logs API so clients can
+/// decode logs the same way for every runtime. Lines of stdout come before
+/// lines of stderr since the files do not record how they were interleaved,
+/// and the tail is taken from the two together.
+pub fn read_logs(log_dir: &Path, name: &str, tail: &LogTail) -> Result<Vec<u8>> {
+    let stdout = read_capture(&stdout_path(log_dir, name))?;
+    let stderr = read_capture(&stderr_path(log_dir, name))?;
+    let mut lines: Vec<(u8, &[u8])> = split_lines(&stdout)
+        .map(|line| (STDOUT_STREAM, line))
+        .chain(split_lines(&stderr).map(|line| (STDERR_STREAM, line)))
+        .collect();
+    if let LogTail::Num(num) = *tail {
+        let skip = lines.len().saturating_sub(num as usize);
+        lines.drain(..skip);
+    }
+
+    let mut output = Vec::new();
+    for (stream, line) in lines {
+        let mut line = line.to_vec();
+        line.push(b'\n');
+        frame(stream, &line, &mut output);
+    }
+    Ok(output)
+}
+
+/// Frames the stdout and stderr of a finished command.
+pub fn frame_output(stdout: &[u8], stderr: &[u8]) -> Vec<u8> {
+    let mut output = Vec::new();
+    if !stdout.is_empty() {
+        frame(STDOUT_STREAM, stdout, &mut output);
+    }
+    if !stderr.is_empty() {
+        frame(STDERR_STREAM, stderr, &mut output);
+    }
+    output
+}
+
+fn read_file(path: &Path) -> Result<Vec<u8>> {
+    let mut data = Vec::new();
+    match File::open(path) {
+        Ok(mut file) => {
+            file.read_to_end(&mut data)?;
+        }
+        Err(ref err) if err.kind() == io::ErrorKind::NotFound => (),
+        Err(err) => return Err(Error::from(err)),
+    }
+    Ok(data)
+}
+
+// The rotated file holds the output that came before the current file.
+fn read_capture(path: &Path) -> Result<Vec<u8>> {
+    let mut data = read_file(&rotated_path(path))?;
+    data.extend(read_file(path)?);
+    Ok(data)
+}
+
+fn split_lines<'a>(data: &'a [u8]) -> impl Iterator<Item = &'a [u8]> {
+    data.split(|b| *b == b'\n').filter(|line| !line.is_empty())
+}
+
+// Each frame is an 8 byte header holding the stream type and the big endian
+// length of the payload.
+fn frame(stream: u8, data: &[u8], output: &mut Vec<u8>) {
+    let len = data.len() as u32;
+    output.extend_from_slice(&[
+        stream,
+        0,
+        0,
+        0,
+        (len >> 24) as u8,
+        (len >> 16) as u8,
+        (len >> 8) as u8,
+        len as u8,
+    ]);
+    output.extend_from_slice(data);
+}
+
+pub struct Logs(Body);
+
+impl From<Vec<u8>> for Logs {
+    fn from(data: Vec<u8>) -> Logs {
+        Logs(Body::from(data))
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Chunk(HyperChunk);
+
+impl IntoIterator for Chunk {
+    type Item = u8;
+    type IntoIter = <HyperChunk as IntoIterator>::IntoIter;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl Extend<u8> for Chunk {
+    fn extend<T>(&mut self, iter: T)
+    where
+        T: IntoIterator<Item = u8>,
+    {
+        self.0.extend(iter)
+    }
+}
+
+impl Stream for Logs {
+    type Item = Chunk;
+    type Error = Error;
+
+    fn poll(&mut self) -> Poll<Option<Self::Item>, Self::Error> {
+        if let Some(c) = try_ready!(self.0.poll()) {
+            Ok(Async::Ready(Some(Chunk(c))))
+        } else {
+            Ok(Async::Ready(None))
+        }
+    }
+}
+
+impl Into<Body> for Logs {
+    fn into(self) -> Body {
+        self.0
+    }
+}
+
+impl AsRef<[u8]> for Chunk {
+    fn as_ref(&self) -> &[u8] {
+        self.0.as_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::fs;
+
+    use tempfile::tempdir;
+
+    #[test]
+    fn frame_output_has_docker_headers() {
+        let output = frame_output(b"out", b"err");
+        assert_eq!(
+            vec![
+                1, 0, 0, 0, 0, 0, 0, 3, b'o', b'u', b't', 2, 0, 0, 0, 0, 0, 0, 3, b'e', b'r',
+                b'r',
+            ],
+            output
+        );
+    }
+
+    #[test]
+    fn read_logs_applies_tail() {
+        let dir = tempdir().unwrap();
+        fs::write(stdout_path(dir.path(), "mod1"), "one\ntwo\nthree\n").unwrap();
+
+        let output = read_logs(dir.path(), "mod1", &LogTail::Num(1)).unwrap();
+        assert_eq!(
+            vec![1, 0, 0, 0, 0, 0, 0, 6, b't', b'h', b'r', b'e', b'e', b'\n'],
+            output
+        );
+    }
+
+    #[test]
+    fn read_logs_applies_tail_to_both_streams() {
+        let dir = tempdir().unwrap();
+        fs::write(stdout_path(dir.path(), "mod1"), "one\ntwo\n").unwrap();
+        fs::write(stderr_path(dir.path(), "mod1"), "three\n").unwrap();
+
+        let output = read_logs(dir.path(), "mod1", &LogTail::Num(2)).unwrap();
+        assert_eq!(
+            vec![
+                1, 0, 0, 0, 0, 0, 0, 4, b't', b'w', b'o', b'\n', 2, 0, 0, 0, 0, 0, 0, 6, b't',
+                b'h', b'r', b'e', b'e', b'\n',
+            ],
+            output
+        );
+    }
+
+    #[test]
+    fn read_logs_includes_rotated_file() {
+        let dir = tempdir().unwrap();
+        let path = stdout_path(dir.path(), "mod1");
+        fs::write(&path, "one\ntw").unwrap();
+        rotate_log(&path, 4).unwrap();
+        fs::write(&path, "o\n").unwrap();
+
+        let output = read_logs(dir.path(), "mod1", &LogTail::All).unwrap();
+        assert_eq!(
+            vec![
+                1, 0, 0, 0, 0, 0, 0, 4, b'o', b'n', b'e', b'\n', 1, 0, 0, 0, 0, 0, 0, 4, b't',
+                b'w', b'o', b'\n',
+            ],
+            output
+        );
+    }
+
+    #[test]
+    fn rotate_logs_only_rotates_large_capture_files() {
+        let dir = tempdir().unwrap();
+        let large = stdout_path(dir.path(), "mod1");
+        let small = stderr_path(dir.path(), "mod1");
+        let other = dir.path().join("processes.json");
+        fs::write(&large, "0123456789").unwrap();
+        fs::write(&small, "0123").unwrap();
+        fs::write(&other, "0123456789").unwrap();
+
+        rotate_logs(dir.path(), 5).unwrap();
+
+        assert_eq!(0, fs::metadata(&large).unwrap().len());
+        assert_eq!(
+            "0123456789",
+            fs::read_to_string(rotated_path(&large)).unwrap()
+        );
+        assert_eq!(4, fs::metadata(&small).unwrap().len());
+        assert!(!rotated_path(&small).exists());
+        assert_eq!(10, fs::metadata(&other).unwrap().len());
+    }
+
+    #[test]
+    fn read_logs_without_files_is_empty() {
+        let dir = tempdir().unwrap();
+        let output = read_logs(dir.path(), "mod1", &LogTail::All).unwrap();
+        assert!(output.is_empty());
+    }
+}
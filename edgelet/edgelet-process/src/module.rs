@@ -0,0 +1,367 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::process::{Child, ExitStatus};
+
+use chrono::prelude::*;
+use futures::future::{self, FutureResult};
+
+use config::ProcessConfig;
+use edgelet_core::{pid::Pid, Module, ModuleRuntimeState, ModuleStatus};
+use error::{Error, Result};
+
+pub const MODULE_TYPE: &str = "process";
+
+/// A module as it was when the runtime was last asked about it.
+#[derive(Clone, Debug)]
+pub struct ProcessModule {
+    name: String,
+    config: ProcessConfig,
+    spec_hash: Option<String>,
+    state: ModuleRuntimeState,
+}
+
+impl ProcessModule {
+    pub fn new(name: &str, config: ProcessConfig, state: ModuleRuntimeState) -> Result<Self> {
+        Ok(ProcessModule {
+            name: ensure_not_empty!(name.to_string()),
+            config,
+            spec_hash: None,
+            state,
+        })
+    }
+
+    pub fn with_spec_hash(mut self, spec_hash: Option<String>) -> Self {
+        self.spec_hash = spec_hash;
+        self
+    }
+}
+
+impl Module for ProcessModule {
+    type Config = ProcessConfig;
+    type Error = Error;
+    type RuntimeStateFuture = FutureResult<ModuleRuntimeState, Self::Error>;
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn type_(&self) -> &str {
+        MODULE_TYPE
+    }
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn spec_hash(&self) -> Option<&str> {
+        self.spec_hash.as_ref().map(AsRef::as_ref)
+    }
+
+    fn runtime_state(&self) -> Self::RuntimeStateFuture {
+        future::ok(self.state.clone())
+    }
+}
+
+/// Book keeping for one module of the process runtime.
+pub(crate) struct ProcessState {
+    pub(crate) config: ProcessConfig,
+    pub(crate) env: HashMap<String, String>,
+    pub(crate) spec_hash: Option<String>,
+    pub(crate) child: Option<Child>,
+    pub(crate) adopted: Option<u32>,
+    pub(crate) start_ticks: Option<u64>,
+    pub(crate) paused: bool,
+    pub(crate) started_at: Option<DateTime<Utc>>,
+    pub(crate) finished_at: Option<DateTime<Utc>>,
+    pub(crate) exit_code: Option<i64>,
+}
+
+/// What is kept of a module in the state file so that its process can be
+/// found again after the daemon restarts.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SavedState {
+    config: ProcessConfig,
+    #[serde(default)]
+    env: HashMap<String, String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    spec_hash: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pid: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    start_ticks: Option<u64>,
+    #[serde(default)]
+    paused: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    started_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    finished_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    exit_code: Option<i64>,
+}
+
+impl ProcessState {
+    pub(crate) fn new(
+        config: ProcessConfig,
+        env: HashMap<String, String>,
+        spec_hash: Option<String>,
+    ) -> Self {
+        ProcessState {
+            config,
+            env,
+            spec_hash,
+            child: None,
+            adopted: None,
+            start_ticks: None,
+            paused: false,
+            started_at: None,
+            finished_at: None,
+            exit_code: None,
+        }
+    }
+
+    /// Restores a module from the state file. A process that was running is
+    /// adopted if it is still alive, otherwise it is reported as failed
+    /// since its exit status is lost.
+    pub(crate) fn restore(saved: SavedState) -> Self {
+        let mut state = ProcessState::new(saved.config, saved.env, saved.spec_hash);
+        state.paused = saved.paused;
+        state.started_at = saved.started_at;
+        state.finished_at = saved.finished_at;
+        state.exit_code = saved.exit_code;
+        if let Some(pid) = saved.pid {
+            if saved.start_ticks.is_some() && start_ticks(pid) == saved.start_ticks {
+                debug!("Adopting running process {}", pid);
+                state.adopted = Some(pid);
+                state.start_ticks = saved.start_ticks;
+            } else {
+                state.finished(-1);
+            }
+        }
+        state
+    }
+
+    pub(crate) fn save(&self) -> SavedState {
+        SavedState {
+            config: self.config.clone(),
+            env: self.env.clone(),
+            spec_hash: self.spec_hash.clone(),
+            pid: self.pid(),
+            start_ticks: self.pid().and(self.start_ticks),
+            paused: self.paused,
+            started_at: self.started_at,
+            finished_at: self.finished_at,
+            exit_code: self.exit_code,
+        }
+    }
+
+    pub(crate) fn started(&mut self, child: Child) {
+        self.start_ticks = start_ticks(child.id());
+        self.child = Some(child);
+        self.adopted = None;
+        self.paused = false;
+        self.started_at = Some(Utc::now());
+        self.finished_at = None;
+        self.exit_code = None;
+    }
+
+    /// Collects the exit status of the process if it has exited. Returns
+    /// whether the process is still running.
+    pub(crate) fn reap(&mut self) -> io::Result<bool> {
+        if let Some(pid) = self.adopted {
+            // the process is not our child, so all we can tell is whether
+            // it is still there
+            return if start_ticks(pid) == self.start_ticks {
+                Ok(true)
+            } else {
+                self.finished(-1);
+                Ok(false)
+            };
+        }
+        let status = match self.child {
+            Some(ref mut child) => child.try_wait()?,
+            None => return Ok(false),
+        };
+        match status {
+            Some(status) => {
+                self.finished(exit_code(status));
+                Ok(false)
+            }
+            None => Ok(true),
+        }
+    }
+
+    /// Kills the process without waiting for it to exit.
+    pub(crate) fn kill(&mut self) -> io::Result<()> {
+        match (self.child.as_mut(), self.adopted) {
+            (Some(child), _) => child.kill(),
+            (None, Some(pid)) => kill_pid(pid),
+            (None, None) => Ok(()),
+        }
+    }
+
+    fn finished(&mut self, exit_code: i64) {
+        self.child = None;
+        self.adopted = None;
+        self.start_ticks = None;
+        self.paused = false;
+        self.finished_at = Some(Utc::now());
+        self.exit_code = Some(exit_code);
+    }
+
+    pub(crate) fn pid(&self) -> Option<u32> {
+        self.child.as_ref().map(Child::id).or(self.adopted)
+    }
+
+    pub(crate) fn runtime_state(&self) -> ModuleRuntimeState {
+        let status = match (self.pid(), self.exit_code) {
+            (Some(_), _) if self.paused => ModuleStatus::Paused,
+            (Some(_), _) => ModuleStatus::Running,
+            (None, Some(0)) => ModuleStatus::Stopped,
+            (None, Some(_)) => ModuleStatus::Failed,
+            (None, None) => ModuleStatus::Created,
+        };
+        let pid = self
+            .pid()
+            .map(|pid| Pid::Value(pid as i32))
+            .unwrap_or(Pid::None);
+        ModuleRuntimeState::default()
+            .with_status(status)
+            .with_exit_code(self.exit_code)
+            .with_started_at(self.started_at)
+            .with_finished_at(self.finished_at)
+            .with_pid(&pid)
+    }
+
+    pub(crate) fn module(&self, name: &str) -> Result<ProcessModule> {
+        ProcessModule::new(name, self.config.clone(), self.runtime_state())
+            .map(|module| module.with_spec_hash(self.spec_hash.clone()))
+    }
+}
+
+// Processes killed by a signal report 128 + the signal number, the same as a
+// shell would.
+#[cfg(unix)]
+fn exit_code(status: ExitStatus) -> i64 {
+    use std::os::unix::process::ExitStatusExt;
+
+    status
+        .code()
+        .map(i64::from)
+        .or_else(|| status.signal().map(|signal| 128 + i64::from(signal)))
+        .unwrap_or(-1)
+}
+
+#[cfg(windows)]
+fn exit_code(status: ExitStatus) -> i64 {
+    status.code().map(i64::from).unwrap_or(-1)
+}
+
+// The start time of a process in clock ticks since boot, from the 22nd field
+// of /proc/<pid>/stat. Together with the pid it tells a process apart from a
+// later one that reused the pid. Zombies count as gone. Without procfs no
+// process is ever adopted.
+fn start_ticks(pid: u32) -> Option<u64> {
+    let stat = fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+    // the command name is in parentheses and may contain spaces
+    let fields = stat[stat.rfind(')')? + 1..]
+        .split_whitespace()
+        .collect::<Vec<_>>();
+    match fields.first() {
+        Some(&"Z") | Some(&"X") | None => None,
+        Some(_) => fields.get(19).and_then(|ticks| ticks.parse().ok()),
+    }
+}
+
+#[cfg(unix)]
+fn kill_pid(pid: u32) -> io::Result<()> {
+    if unsafe { ::libc::kill(pid as ::libc::pid_t, ::libc::SIGKILL) } == 0 {
+        Ok(())
+    } else {
+        Err(io::Error::last_os_error())
+    }
+}
+
+#[cfg(windows)]
+fn kill_pid(_pid: u32) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Other,
+        "processes are not adopted on Windows",
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state() -> ProcessState {
+        let config = ProcessConfig::new("/bin/true").unwrap();
+        ProcessState::new(config, HashMap::new(), Some("hash".to_string()))
+    }
+
+    #[test]
+    fn new_process_is_created() {
+        let state = state();
+        let runtime_state = state.runtime_state();
+        assert_eq!(ModuleStatus::Created, *runtime_state.status());
+        match *runtime_state.pid() {
+            Pid::None => (),
+            ref pid => panic!("Expected no pid, got {}", pid),
+        }
+    }
+
+    #[test]
+    fn clean_exit_is_stopped() {
+        let mut state = state();
+        state.exit_code = Some(0);
+        assert_eq!(ModuleStatus::Stopped, *state.runtime_state().status());
+    }
+
+    #[test]
+    fn failed_exit_is_failed() {
+        let mut state = state();
+        state.exit_code = Some(137);
+        let runtime_state = state.runtime_state();
+        assert_eq!(ModuleStatus::Failed, *runtime_state.status());
+        assert_eq!(Some(&137), runtime_state.exit_code());
+    }
+
+    #[test]
+    fn restore_without_live_process_is_failed() {
+        let mut state = state();
+        state.started_at = Some(Utc::now());
+        let mut saved = state.save();
+        saved.pid = Some(u32::max_value());
+        saved.start_ticks = Some(1);
+
+        let restored = ProcessState::restore(saved);
+        assert_eq!(None, restored.pid());
+        assert_eq!(ModuleStatus::Failed, *restored.runtime_state().status());
+        assert_eq!(Some("hash"), restored.spec_hash.as_ref().map(AsRef::as_ref));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn restore_adopts_live_process() {
+        let pid = ::std::process::id();
+        let mut state = state();
+        state.adopted = Some(pid);
+        state.start_ticks = start_ticks(pid);
+        assert!(state.start_ticks.is_some());
+
+        let mut restored = ProcessState::restore(state.save());
+        assert_eq!(Some(pid), restored.pid());
+        assert!(restored.reap().unwrap());
+        assert_eq!(ModuleStatus::Running, *restored.runtime_state().status());
+    }
+
+    #[test]
+    fn module_keeps_spec_hash() {
+        let module = state().module("mod1").unwrap();
+        assert_eq!("mod1", module.name());
+        assert_eq!(Some("hash"), module.spec_hash());
+    }
+}
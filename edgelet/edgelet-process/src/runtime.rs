@@ -0,0 +1,1004 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use futures::future;
+use futures::prelude::*;
+use futures::stream::{self, Empty};
+use futures::sync::oneshot;
+use log::Level;
+
+use config::ProcessConfig;
+use edgelet_core::{
+    ArchiveStream, EventOptions, ExecOptions, Image, LogOptions, ModuleEvent, ModuleRegistry,
+    ModuleRuntime, ModuleSpec, ModuleStats, PruneOptions, PruneResult, StatsOptions,
+    SystemInfo as CoreSystemInfo, Volume,
+};
+use edgelet_utils::log_failure;
+use error::{Error, ErrorKind, Result};
+use logs::{self, Chunk, Logs};
+use module::{ProcessModule, ProcessState, SavedState, MODULE_TYPE as PROCESS_MODULE_TYPE};
+use serde_json;
+
+const WAIT_BEFORE_KILL_SECONDS: u64 = 10;
+const POLL_INTERVAL_MILLIS: u64 = 100;
+const LOG_ROTATION_INTERVAL_SECS: u64 = 60;
+const STATE_FILE: &str = "processes.json";
+
+type Processes = Arc<Mutex<HashMap<String, ProcessState>>>;
+
+/// Runs modules as child processes of the daemon. The stdout and stderr of
+/// each module are appended to files in the log directory, which are rotated
+/// once they grow past `logs::MAX_LOG_BYTES`. The module table
+/// is saved next to the logs, so after a restart the daemon knows its modules
+/// again and adopts the processes that are still running instead of starting
+/// them a second time.
+#[derive(Clone)]
+pub struct ProcessModuleRuntime {
+    log_dir: PathBuf,
+    state_file: PathBuf,
+    processes: Processes,
+}
+
+impl ProcessModuleRuntime {
+    pub fn new(log_dir: &Path) -> Result<ProcessModuleRuntime> {
+        let state_file = log_dir.join(STATE_FILE);
+        let processes = load_state(&state_file)?;
+        Ok(ProcessModuleRuntime {
+            log_dir: log_dir.to_path_buf(),
+            state_file,
+            processes: Arc::new(Mutex::new(processes)),
+        })
+    }
+
+    pub fn log_dir(&self) -> &Path {
+        &self.log_dir
+    }
+
+    fn processes(&self) -> MutexGuard<HashMap<String, ProcessState>> {
+        lock(&self.processes)
+    }
+
+    fn spawn(&self, name: &str, state: &mut ProcessState) -> Result<()> {
+        let stdout = logs::stdout_path(&self.log_dir, name);
+        let stderr = logs::stderr_path(&self.log_dir, name);
+        logs::rotate_log(&stdout, logs::MAX_LOG_BYTES)?;
+        logs::rotate_log(&stderr, logs::MAX_LOG_BYTES)?;
+
+        let mut command = Command::new(state.config.executable());
+        command
+            .args(state.config.args())
+            .envs(&state.env)
+            .stdin(Stdio::null())
+            .stdout(logs::open_log(&stdout)?)
+            .stderr(logs::open_log(&stderr)?);
+        if let Some(dir) = state.config.working_dir() {
+            command.current_dir(dir);
+        }
+        if let Some(user) = state.config.user() {
+            set_user(&mut command, user)?;
+        }
+
+        let child = command.spawn()?;
+        debug!("Started process {} for module {}", child.id(), name);
+        state.started(child);
+        Ok(())
+    }
+
+    fn save(&self) {
+        save_state(&self.state_file, &self.processes());
+    }
+
+    // Drops a module that has been stopped from the table along with its
+    // logs.
+    fn forget(&self, name: &str) -> Result<()> {
+        {
+            let mut processes = self.processes();
+            let running = match processes.get_mut(name) {
+                Some(state) => state.reap()?,
+                None => return Ok(()),
+            };
+            // the module was started again while it was being stopped
+            if running {
+                return Err(Error::from(ErrorKind::Conflict));
+            }
+            processes.remove(name);
+            save_state(&self.state_file, &processes);
+        }
+
+        let stdout = logs::stdout_path(&self.log_dir, name);
+        let stderr = logs::stderr_path(&self.log_dir, name);
+        for path in &[
+            logs::rotated_path(&stdout),
+            logs::rotated_path(&stderr),
+            stdout,
+            stderr,
+        ] {
+            match fs::remove_file(path) {
+                Err(ref err) if err.kind() != io::ErrorKind::NotFound => {
+                    warn!("Could not remove log file {}: {}", path.display(), err);
+                }
+                _ => (),
+            }
+        }
+        Ok(())
+    }
+
+    fn with_process<F, T>(&self, id: &str, f: F) -> Result<T>
+    where
+        F: FnOnce(&mut ProcessState) -> Result<T>,
+    {
+        let mut processes = self.processes();
+        let state = processes
+            .get_mut(id)
+            .ok_or_else(|| Error::from(ErrorKind::NotFound))?;
+        f(state)
+    }
+}
+
+fn lock(processes: &Processes) -> MutexGuard<HashMap<String, ProcessState>> {
+    processes.lock().expect("process table lock poisoned")
+}
+
+fn load_state(path: &Path) -> Result<HashMap<String, ProcessState>> {
+    let data = match fs::read(path) {
+        Ok(data) => data,
+        Err(ref err) if err.kind() == io::ErrorKind::NotFound => return Ok(HashMap::new()),
+        Err(err) => return Err(Error::from(err)),
+    };
+    let saved: HashMap<String, SavedState> = serde_json::from_slice(&data)?;
+    Ok(saved
+        .into_iter()
+        .map(|(name, saved)| (name, ProcessState::restore(saved)))
+        .collect())
+}
+
+// Writes the module table to the state file. Failing to do so only loses
+// track of the modules if the daemon restarts, so the error is logged rather
+// than failing the operation that changed the table.
+fn save_state(path: &Path, processes: &HashMap<String, ProcessState>) {
+    let saved: HashMap<&String, SavedState> = processes
+        .iter()
+        .map(|(name, state)| (name, state.save()))
+        .collect();
+    let tmp = path.with_extension("json.tmp");
+    let result = serde_json::to_vec(&saved)
+        .map_err(Error::from)
+        .and_then(|data| {
+            fs::write(&tmp, data)?;
+            fs::rename(&tmp, path)?;
+            Ok(())
+        });
+    if let Err(err) = result {
+        warn!("Could not save the process table to {}", path.display());
+        log_failure(Level::Warn, &err);
+    }
+}
+
+fn log_result<T>(result: Result<T>, message: &str) -> Result<T> {
+    result.map_err(|e| {
+        warn!("{}", message);
+        log_failure(Level::Warn, &e);
+        e
+    })
+}
+
+// Runs blocking work on its own thread so that the reactor is not held up
+// while a process shuts down or a log file is read.
+fn blocking<F, T>(f: F) -> Box<Future<Item = T, Error = Error>>
+where
+    F: 'static + Send + FnOnce() -> Result<T>,
+    T: 'static + Send,
+{
+    let (tx, rx) = oneshot::channel();
+    thread::spawn(move || {
+        tx.send(f()).unwrap_or(());
+    });
+    Box::new(rx.then(|result| result.unwrap_or_else(|_| Err(Error::from(ErrorKind::Supervisor)))))
+}
+
+// Asks the process to exit and kills it if it is still running once the
+// grace period is over, then waits for it to go away. The table is only
+// locked to look at the process, never while waiting.
+fn terminate(processes: &Processes, state_file: &Path, name: &str, grace: Duration) -> Result<()> {
+    let deadline = Instant::now() + grace;
+    let mut requested = false;
+    let mut killed = false;
+    loop {
+        {
+            let mut processes = lock(processes);
+            let running = match processes.get_mut(name) {
+                Some(state) => {
+                    let running = state.reap()?;
+                    if running && !requested {
+                        request_stop(state)?;
+                        requested = true;
+                    } else if running && !killed && Instant::now() >= deadline {
+                        state.kill()?;
+                        killed = true;
+                    }
+                    running
+                }
+                None => return Ok(()),
+            };
+            if !running {
+                save_state(state_file, &processes);
+                return Ok(());
+            }
+        }
+        thread::sleep(Duration::from_millis(POLL_INTERVAL_MILLIS));
+    }
+}
+
+#[cfg(unix)]
+fn request_stop(state: &mut ProcessState) -> Result<()> {
+    if let Some(pid) = state.pid() {
+        // a stopped process only handles SIGTERM once it is resumed
+        if state.paused {
+            signal(pid, ::libc::SIGCONT)?;
+            state.paused = false;
+        }
+        signal(pid, ::libc::SIGTERM)?;
+    }
+    Ok(())
+}
+
+#[cfg(windows)]
+fn request_stop(state: &mut ProcessState) -> Result<()> {
+    state.kill()?;
+    Ok(())
+}
+
+#[cfg(unix)]
+fn signal(pid: u32, signal: ::libc::c_int) -> Result<()> {
+    if unsafe { ::libc::kill(pid as ::libc::pid_t, signal) } == 0 {
+        Ok(())
+    } else {
+        Err(Error::from(io::Error::last_os_error()))
+    }
+}
+
+// Command::uid would switch the user without dropping the supplementary
+// groups of the daemon, so the child does it itself before exec: groups
+// first, then the group and the user, since nothing can be changed once the
+// user is no longer root.
+#[cfg(unix)]
+fn set_user(command: &mut Command, user: &str) -> Result<()> {
+    use std::os::unix::process::CommandExt;
+    use std::ptr;
+
+    let passwd = fs::read_to_string("/etc/passwd").unwrap_or_default();
+    let (uid, gid) = resolve_user(user, &passwd)?;
+    command.before_exec(move || {
+        let failed = unsafe {
+            ::libc::setgroups(0, ptr::null()) != 0
+                || ::libc::setgid(gid as ::libc::gid_t) != 0
+                || ::libc::setuid(uid as ::libc::uid_t) != 0
+        };
+        if failed {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(())
+        }
+    });
+    Ok(())
+}
+
+#[cfg(windows)]
+fn set_user(_command: &mut Command, _user: &str) -> Result<()> {
+    Err(Error::from(ErrorKind::NotSupported(
+        "running processes as another user",
+    )))
+}
+
+// Resolves `name`, `uid`, `name:gid` or `uid:gid` against the contents of
+// /etc/passwd. The group defaults to the primary group of the user.
+#[cfg_attr(windows, allow(dead_code))]
+fn resolve_user(user: &str, passwd: &str) -> Result<(u32, u32)> {
+    let invalid = || Error::from(ErrorKind::InvalidUser(user.to_string()));
+
+    let mut parts = user.splitn(2, ':');
+    let name = parts.next().unwrap_or("");
+    let gid = match parts.next() {
+        Some(gid) => Some(gid.parse::<u32>().map_err(|_| invalid())?),
+        None => None,
+    };
+
+    let entry = passwd
+        .lines()
+        .map(|line| line.split(':').collect::<Vec<_>>())
+        .find(|fields| fields.len() > 3 && (fields[0] == name || fields[2] == name));
+    let uid = match entry {
+        Some(ref fields) => fields[2].parse::<u32>().ok(),
+        None => name.parse::<u32>().ok(),
+    }.ok_or_else(|| invalid())?;
+    let gid = gid
+        .or_else(|| entry.and_then(|fields| fields[3].parse::<u32>().ok()))
+        .unwrap_or(uid);
+    Ok((uid, gid))
+}
+
+impl ModuleRegistry for ProcessModuleRuntime {
+    type Error = Error;
+    type PullFuture = Box<Future<Item = (), Error = Self::Error>>;
+    type RemoveFuture = Box<Future<Item = (), Error = Self::Error>>;
+    type ListImagesFuture = Box<Future<Item = Vec<Image>, Error = Self::Error>>;
+    type PruneImagesFuture = Box<Future<Item = PruneResult, Error = Self::Error>>;
+    type LoadImagesFuture = Box<Future<Item = (), Error = Self::Error>>;
+    type SaveImageFuture = Box<Future<Item = Self::ImageArchive, Error = Self::Error>>;
+    type ImageChunk = Chunk;
+    type ImageArchive = Logs;
+    type Config = ProcessConfig;
+
+    // There is nothing to download for a process. Executables given as a
+    // path have to exist, bare names are looked up on PATH when started.
+    fn pull(&self, config: &Self::Config) -> Self::PullFuture {
+        let executable = Path::new(config.executable());
+        if executable.components().count() > 1 && !executable.exists() {
+            warn!("Executable {} does not exist.", executable.display());
+            Box::new(future::err(Error::from(ErrorKind::NotFound)))
+        } else {
+            Box::new(future::ok(()))
+        }
+    }
+
+    fn remove(&self, _name: &str) -> Self::RemoveFuture {
+        Box::new(future::ok(()))
+    }
+
+    // Executables are not managed by the runtime, so there are no images.
+    fn list_images(&self) -> Self::ListImagesFuture {
+        Box::new(future::ok(Vec::new()))
+    }
+
+    fn prune_images(&self, _options: &PruneOptions) -> Self::PruneImagesFuture {
+        Box::new(future::ok(PruneResult::default()))
+    }
+
+    fn load_images(&self, _archive: ArchiveStream) -> Self::LoadImagesFuture {
+        Box::new(future::err(Error::from(ErrorKind::NotSupported(
+            "loading images",
+        ))))
+    }
+
+    fn save_image(&self, _name: &str) -> Self::SaveImageFuture {
+        Box::new(future::err(Error::from(ErrorKind::NotSupported(
+            "saving images",
+        ))))
+    }
+}
+
+impl ModuleRuntime for ProcessModuleRuntime {
+    type Error = Error;
+    type Config = ProcessConfig;
+    type Module = ProcessModule;
+    type ModuleRegistry = Self;
+    type Chunk = Chunk;
+    type Logs = Logs;
+    type Stats = Empty<ModuleStats, Self::Error>;
+    type Events = Empty<ModuleEvent, Self::Error>;
+
+    type CreateFuture = Box<Future<Item = (), Error = Self::Error>>;
+    type EventsFuture = Box<Future<Item = Self::Events, Error = Self::Error>>;
+    type ExecFuture = Box<Future<Item = Self::Logs, Error = Self::Error>>;
+    type GetArchiveFuture = Box<Future<Item = Self::Logs, Error = Self::Error>>;
+    type InitFuture = Box<Future<Item = (), Error = Self::Error>>;
+    type ListFuture = Box<Future<Item = Vec<Self::Module>, Error = Self::Error>>;
+    type LogsFuture = Box<Future<Item = Self::Logs, Error = Self::Error>>;
+    type RemoveFuture = Box<Future<Item = (), Error = Self::Error>>;
+    type PauseFuture = Box<Future<Item = (), Error = Self::Error>>;
+    type PutArchiveFuture = Box<Future<Item = (), Error = Self::Error>>;
+    type RestartFuture = Box<Future<Item = (), Error = Self::Error>>;
+    type StatsFuture = Box<Future<Item = Self::Stats, Error = Self::Error>>;
+    type StartFuture = Box<Future<Item = (), Error = Self::Error>>;
+    type StopFuture = Box<Future<Item = (), Error = Self::Error>>;
+    type UnpauseFuture = Box<Future<Item = (), Error = Self::Error>>;
+    type SystemInfoFuture = Box<Future<Item = CoreSystemInfo, Error = Self::Error>>;
+    type RemoveAllFuture = Box<Future<Item = (), Error = Self::Error>>;
+    type ListVolumesFuture = Box<Future<Item = Vec<Volume>, Error = Self::Error>>;
+    type InspectVolumeFuture = Box<Future<Item = Volume, Error = Self::Error>>;
+    type CreateVolumeFuture = Box<Future<Item = Volume, Error = Self::Error>>;
+    type RemoveVolumeFuture = Box<Future<Item = (), Error = Self::Error>>;
+    type BackupVolumeFuture = Box<Future<Item = Self::Logs, Error = Self::Error>>;
+
+    // The capture files of running modules are checked for rotation in the
+    // background, since modules append to them directly.
+    fn init(&self) -> Self::InitFuture {
+        let result = fs::create_dir_all(&self.log_dir).map_err(Error::from);
+        if result.is_ok() {
+            let log_dir = self.log_dir.clone();
+            thread::spawn(move || loop {
+                if let Err(err) = logs::rotate_logs(&log_dir, logs::MAX_LOG_BYTES) {
+                    warn!("Could not rotate the process module logs.");
+                    log_failure(Level::Warn, &err);
+                }
+                thread::sleep(Duration::from_secs(LOG_ROTATION_INTERVAL_SECS));
+            });
+        }
+        Box::new(future::result(log_result(
+            result,
+            "Module runtime init failed.",
+        )))
+    }
+
+    fn create(&self, module: ModuleSpec<Self::Config>) -> Self::CreateFuture {
+        // we only want "process" modules
+        fensure!(module.type_(), module.type_() == PROCESS_MODULE_TYPE);
+
+        debug!(
+            "Creating process module {} running {}",
+            module.name(),
+            module.config().executable()
+        );
+        let mut processes = self.processes();
+        let result = if processes.contains_key(module.name()) {
+            Err(Error::from(ErrorKind::Conflict))
+        } else {
+            let state = ProcessState::new(
+                module.config().clone(),
+                module.env().clone(),
+                module.recorded_spec_hash().ok().and_then(|hash| hash),
+            );
+            processes.insert(module.name().to_string(), state);
+            save_state(&self.state_file, &processes);
+            Ok(())
+        };
+        Box::new(future::result(log_result(
+            result,
+            "Attempt to create a process module failed.",
+        )))
+    }
+
+    fn start(&self, id: &str) -> Self::StartFuture {
+        debug!("Starting process module {}", id);
+        let result = self.with_process(fensure_not_empty!(id), |state| {
+            if state.reap()? {
+                Err(Error::from(ErrorKind::NotModified))
+            } else {
+                self.spawn(id, state)
+            }
+        });
+        if result.is_ok() {
+            self.save();
+        }
+        Box::new(future::result(log_result(
+            result,
+            "Attempt to start a process module failed.",
+        )))
+    }
+
+    fn stop(&self, id: &str, wait_before_kill: Option<Duration>) -> Self::StopFuture {
+        debug!("Stopping process module {}", id);
+        let running = self.with_process(fensure_not_empty!(id), |state| Ok(state.reap()?));
+        match running {
+            Ok(true) => {
+                let processes = self.processes.clone();
+                let state_file = self.state_file.clone();
+                let name = id.to_string();
+                let grace = wait_before_kill
+                    .unwrap_or_else(|| Duration::from_secs(WAIT_BEFORE_KILL_SECONDS));
+                let stop = blocking(move || terminate(&processes, &state_file, &name, grace));
+                Box::new(stop.map_err(|e| {
+                    warn!("Attempt to stop a process module failed.");
+                    log_failure(Level::Warn, &e);
+                    e
+                }))
+            }
+            Ok(false) => Box::new(future::err(Error::from(ErrorKind::NotModified))),
+            Err(err) => Box::new(future::result(log_result(
+                Err(err),
+                "Attempt to stop a process module failed.",
+            ))),
+        }
+    }
+
+    fn restart(&self, id: &str) -> Self::RestartFuture {
+        let runtime = self.clone();
+        let name = id.to_string();
+        Box::new(
+            self.stop(fensure_not_empty!(id), None)
+                .or_else(|err| match *err.kind() {
+                    ErrorKind::NotModified => Ok(()),
+                    _ => Err(err),
+                }).and_then(move |_| runtime.start(&name)),
+        )
+    }
+
+    #[cfg(unix)]
+    fn pause(&self, id: &str) -> Self::PauseFuture {
+        debug!("Pausing process module {}", id);
+        let result = self.with_process(fensure_not_empty!(id), |state| {
+            match (state.reap()?, state.paused, state.pid()) {
+                (true, false, Some(pid)) => {
+                    signal(pid, ::libc::SIGSTOP)?;
+                    state.paused = true;
+                    Ok(())
+                }
+                (true, _, _) => Err(Error::from(ErrorKind::NotModified)),
+                (false, _, _) => Err(Error::from(ErrorKind::Conflict)),
+            }
+        });
+        if result.is_ok() {
+            self.save();
+        }
+        Box::new(future::result(log_result(
+            result,
+            "Attempt to pause a process module failed.",
+        )))
+    }
+
+    #[cfg(windows)]
+    fn pause(&self, _id: &str) -> Self::PauseFuture {
+        Box::new(future::err(Error::from(ErrorKind::NotSupported("pause"))))
+    }
+
+    #[cfg(unix)]
+    fn unpause(&self, id: &str) -> Self::UnpauseFuture {
+        debug!("Unpausing process module {}", id);
+        let result = self.with_process(fensure_not_empty!(id), |state| {
+            match (state.reap()?, state.paused, state.pid()) {
+                (true, true, Some(pid)) => {
+                    signal(pid, ::libc::SIGCONT)?;
+                    state.paused = false;
+                    Ok(())
+                }
+                (true, _, _) => Err(Error::from(ErrorKind::NotModified)),
+                (false, _, _) => Err(Error::from(ErrorKind::Conflict)),
+            }
+        });
+        if result.is_ok() {
+            self.save();
+        }
+        Box::new(future::result(log_result(
+            result,
+            "Attempt to unpause a process module failed.",
+        )))
+    }
+
+    #[cfg(windows)]
+    fn unpause(&self, _id: &str) -> Self::UnpauseFuture {
+        Box::new(future::err(Error::from(ErrorKind::NotSupported("unpause"))))
+    }
+
+    fn remove(&self, id: &str) -> Self::RemoveFuture {
+        debug!("Removing process module {}", id);
+        let id = fensure_not_empty!(id);
+        if !self.processes().contains_key(id) {
+            return Box::new(future::result(log_result(
+                Err(Error::from(ErrorKind::NotFound)),
+                "Attempt to remove a process module failed.",
+            )));
+        }
+
+        let runtime = self.clone();
+        let name = id.to_string();
+        let grace = Duration::from_secs(WAIT_BEFORE_KILL_SECONDS);
+        Box::new(
+            blocking(move || {
+                terminate(&runtime.processes, &runtime.state_file, &name, grace)?;
+                runtime.forget(&name)
+            }).map_err(|e| {
+                warn!("Attempt to remove a process module failed.");
+                log_failure(Level::Warn, &e);
+                e
+            }),
+        )
+    }
+
+    // Processes keep their data on the host, so there are no volumes to
+    // remove.
+    fn remove_with_volumes(&self, id: &str) -> Self::RemoveFuture {
+        <Self as ModuleRuntime>::remove(self, id)
+    }
+
+    fn system_info(&self) -> Self::SystemInfoFuture {
+        Box::new(future::ok(CoreSystemInfo::new(
+            env::consts::OS.to_string(),
+            env::consts::ARCH.to_string(),
+        )))
+    }
+
+    fn list(&self) -> Self::ListFuture {
+        let result = self
+            .processes()
+            .iter_mut()
+            .map(|(name, state)| {
+                state.reap()?;
+                state.module(name)
+            }).collect::<Result<Vec<_>>>();
+        Box::new(future::result(log_result(
+            result,
+            "Attempt to list process modules failed.",
+        )))
+    }
+
+    // Logs are read from the capture files as they are at the time of the
+    // call. The files record neither when a line was written nor anything
+    // after the call, so following, time ranges and timestamps are not
+    // supported.
+    fn logs(&self, id: &str, options: &LogOptions) -> Self::LogsFuture {
+        if options.follow() {
+            return Box::new(future::err(Error::from(ErrorKind::NotSupported(
+                "following logs",
+            ))));
+        }
+        if options.since() != 0 || options.until() != 0 {
+            return Box::new(future::err(Error::from(ErrorKind::NotSupported(
+                "filtering logs by time",
+            ))));
+        }
+        if options.timestamps() {
+            return Box::new(future::err(Error::from(ErrorKind::NotSupported(
+                "log timestamps",
+            ))));
+        }
+
+        let log_dir = self.log_dir.clone();
+        let name = id.to_string();
+        let tail = options.tail().clone();
+        let result = match self.with_process(id, |_| Ok(())) {
+            Ok(()) => blocking(move || logs::read_logs(&log_dir, &name, &tail)),
+            Err(err) => Box::new(future::err(err)),
+        };
+        Box::new(result.map(Logs::from).map_err(|e| {
+            warn!("Attempt to get process module logs failed.");
+            log_failure(Level::Warn, &e);
+            e
+        }))
+    }
+
+    fn stats(&self, _id: &str, _options: &StatsOptions) -> Self::StatsFuture {
+        Box::new(future::err(Error::from(ErrorKind::NotSupported("stats"))))
+    }
+
+    fn events(&self, _options: &EventOptions) -> Self::EventsFuture {
+        Box::new(future::ok(stream::empty()))
+    }
+
+    // The command runs as a separate process with the environment, working
+    // directory and user of the module.
+    fn exec(&self, id: &str, options: &ExecOptions) -> Self::ExecFuture {
+        debug!("Running {:?} for process module {}", options.cmd(), id);
+        let cmd = options.cmd().to_vec();
+        let user = options.user().map(ToString::to_string);
+        let command = self.with_process(fensure_not_empty!(id), |state| {
+            if !state.reap()? {
+                return Err(Error::from(ErrorKind::Conflict));
+            }
+            let (program, args) = cmd
+                .split_first()
+                .ok_or_else(|| Error::from(ErrorKind::NotSupported("empty commands")))?;
+            let mut command = Command::new(program);
+            command.args(args).envs(&state.env).stdin(Stdio::null());
+            if let Some(dir) = state.config.working_dir() {
+                command.current_dir(dir);
+            }
+            if let Some(user) = user
+                .as_ref()
+                .map(String::as_str)
+                .or_else(|| state.config.user())
+            {
+                set_user(&mut command, user)?;
+            }
+            Ok(command)
+        });
+
+        match command {
+            Ok(mut command) => Box::new(
+                blocking(move || {
+                    let output = command.output()?;
+                    Ok(logs::frame_output(&output.stdout, &output.stderr))
+                }).map(Logs::from)
+                .map_err(|e| {
+                    warn!("Attempt to run a command for a process module failed.");
+                    log_failure(Level::Warn, &e);
+                    e
+                }),
+            ),
+            Err(err) => Box::new(future::result(log_result(
+                Err(err),
+                "Attempt to run a command for a process module failed.",
+            ))),
+        }
+    }
+
+    fn get_archive(&self, _id: &str, _path: &str) -> Self::GetArchiveFuture {
+        Box::new(future::err(Error::from(ErrorKind::NotSupported(
+            "copying files",
+        ))))
+    }
+
+    fn put_archive(
+        &self,
+        _id: &str,
+        _path: &str,
+        _archive: ArchiveStream,
+    ) -> Self::PutArchiveFuture {
+        Box::new(future::err(Error::from(ErrorKind::NotSupported(
+            "copying files",
+        ))))
+    }
+
+    fn registry(&self) -> &Self::ModuleRegistry {
+        self
+    }
+
+    fn remove_all(&self) -> Self::RemoveAllFuture {
+        let names: Vec<String> = self.processes().keys().cloned().collect();
+        let removals = names
+            .iter()
+            .map(|name| <Self as ModuleRuntime>::remove(self, name))
+            .collect::<Vec<_>>();
+        Box::new(future::join_all(removals).map(|_| ()))
+    }
+
+    fn list_volumes(&self) -> Self::ListVolumesFuture {
+        Box::new(future::ok(Vec::new()))
+    }
+
+    fn inspect_volume(&self, _name: &str) -> Self::InspectVolumeFuture {
+        Box::new(future::err(Error::from(ErrorKind::NotFound)))
+    }
+
+    fn create_volume(&self, _name: &str, _module: Option<&str>) -> Self::CreateVolumeFuture {
+        Box::new(future::err(Error::from(ErrorKind::NotSupported("volumes"))))
+    }
+
+    fn remove_volume(&self, _name: &str) -> Self::RemoveVolumeFuture {
+        Box::new(future::err(Error::from(ErrorKind::NotFound)))
+    }
+
+    fn backup_volume(&self, _name: &str) -> Self::BackupVolumeFuture {
+        Box::new(future::err(Error::from(ErrorKind::NotFound)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use tempfile::tempdir;
+
+    use edgelet_core::{Module, ModuleStatus};
+
+    fn spec(name: &str, executable: &str, args: &[&str]) -> ModuleSpec<ProcessConfig> {
+        let config = ProcessConfig::new(executable)
+            .unwrap()
+            .with_args(args.iter().map(ToString::to_string).collect());
+        ModuleSpec::new(name, PROCESS_MODULE_TYPE, config, HashMap::new()).unwrap()
+    }
+
+    #[test]
+    fn create_fails_for_other_module_types() {
+        let dir = tempdir().unwrap();
+        let runtime = ProcessModuleRuntime::new(dir.path()).unwrap();
+        let spec = spec("mod1", "/bin/true", &[]).with_type_("docker".to_string());
+
+        let err = runtime.create(spec).wait().unwrap_err();
+        assert!(match *err.kind() {
+            ErrorKind::Utils => true,
+            _ => false,
+        });
+    }
+
+    #[test]
+    fn create_fails_for_existing_module() {
+        let dir = tempdir().unwrap();
+        let runtime = ProcessModuleRuntime::new(dir.path()).unwrap();
+        runtime
+            .create(spec("mod1", "/bin/true", &[]))
+            .wait()
+            .unwrap();
+
+        let err = runtime
+            .create(spec("mod1", "/bin/true", &[]))
+            .wait()
+            .unwrap_err();
+        assert!(match *err.kind() {
+            ErrorKind::Conflict => true,
+            _ => false,
+        });
+    }
+
+    #[test]
+    fn start_fails_for_unknown_module() {
+        let dir = tempdir().unwrap();
+        let runtime = ProcessModuleRuntime::new(dir.path()).unwrap();
+
+        let err = runtime.start("mod1").wait().unwrap_err();
+        assert!(match *err.kind() {
+            ErrorKind::NotFound => true,
+            _ => false,
+        });
+    }
+
+    #[test]
+    fn logs_fails_when_following() {
+        let dir = tempdir().unwrap();
+        let runtime = ProcessModuleRuntime::new(dir.path()).unwrap();
+        runtime
+            .create(spec("mod1", "/bin/true", &[]))
+            .wait()
+            .unwrap();
+
+        let err = runtime
+            .logs("mod1", &LogOptions::new().with_follow(true))
+            .wait()
+            .unwrap_err();
+        assert!(match *err.kind() {
+            ErrorKind::NotSupported(_) => true,
+            _ => false,
+        });
+    }
+
+    #[test]
+    fn logs_reads_captured_output() {
+        let dir = tempdir().unwrap();
+        let runtime = ProcessModuleRuntime::new(dir.path()).unwrap();
+        runtime
+            .create(spec("mod1", "/bin/true", &[]))
+            .wait()
+            .unwrap();
+        fs::write(logs::stdout_path(dir.path(), "mod1"), "out\n").unwrap();
+
+        let output = runtime
+            .logs("mod1", &LogOptions::new())
+            .wait()
+            .unwrap()
+            .concat2()
+            .wait()
+            .unwrap();
+        assert_eq!(
+            vec![1, 0, 0, 0, 0, 0, 0, 4, b'o', b'u', b't', b'\n'],
+            output.into_iter().collect::<Vec<u8>>()
+        );
+    }
+
+    #[test]
+    fn start_fails_for_empty_id() {
+        let dir = tempdir().unwrap();
+        let runtime = ProcessModuleRuntime::new(dir.path()).unwrap();
+
+        let err = runtime.start("").wait().unwrap_err();
+        assert!(match *err.kind() {
+            ErrorKind::Utils => true,
+            _ => false,
+        });
+    }
+
+    #[test]
+    fn list_reports_created_modules() {
+        let dir = tempdir().unwrap();
+        let runtime = ProcessModuleRuntime::new(dir.path()).unwrap();
+        runtime
+            .create(spec("mod1", "/bin/true", &[]))
+            .wait()
+            .unwrap();
+
+        let modules = runtime.list().wait().unwrap();
+        assert_eq!(1, modules.len());
+        assert_eq!("mod1", modules[0].name());
+        assert_eq!(PROCESS_MODULE_TYPE, modules[0].type_());
+        assert!(modules[0].spec_hash().is_none());
+        let state = modules[0].runtime_state().wait().unwrap();
+        assert_eq!(ModuleStatus::Created, *state.status());
+    }
+
+    #[test]
+    fn modules_are_known_after_restart() {
+        let dir = tempdir().unwrap();
+        let runtime = ProcessModuleRuntime::new(dir.path()).unwrap();
+        runtime
+            .create(spec("mod1", "/bin/true", &[]).with_record_spec_hash(true))
+            .wait()
+            .unwrap();
+
+        let restarted = ProcessModuleRuntime::new(dir.path()).unwrap();
+        let modules = restarted.list().wait().unwrap();
+        assert_eq!(1, modules.len());
+        assert_eq!("mod1", modules[0].name());
+        assert!(modules[0].spec_hash().is_some());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn running_process_is_adopted_after_restart() {
+        let dir = tempdir().unwrap();
+        let runtime = ProcessModuleRuntime::new(dir.path()).unwrap();
+        runtime
+            .create(spec("mod1", "/bin/sleep", &["30"]))
+            .wait()
+            .unwrap();
+        runtime.start("mod1").wait().unwrap();
+        let pid = runtime.processes()["mod1"].pid().unwrap();
+
+        let restarted = ProcessModuleRuntime::new(dir.path()).unwrap();
+        let state = restarted.list().wait().unwrap()[0]
+            .runtime_state()
+            .wait()
+            .unwrap();
+        assert_eq!(ModuleStatus::Running, *state.status());
+        assert_eq!(Some(pid), restarted.processes()["mod1"].pid());
+        let err = restarted.start("mod1").wait().unwrap_err();
+        assert!(match *err.kind() {
+            ErrorKind::NotModified => true,
+            _ => false,
+        });
+
+        <ProcessModuleRuntime as ModuleRuntime>::remove(&restarted, "mod1")
+            .wait()
+            .unwrap();
+        assert!(!runtime.processes().get_mut("mod1").unwrap().reap().unwrap());
+        assert!(restarted.list().wait().unwrap().is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn remove_asks_the_process_to_exit_first() {
+        let dir = tempdir().unwrap();
+        let marker = dir.path().join("terminated");
+        let script = format!(
+            "trap 'touch {}; exit 0' TERM; while true; do sleep 0.1; done",
+            marker.display()
+        );
+        let runtime = ProcessModuleRuntime::new(dir.path()).unwrap();
+        runtime
+            .create(spec("mod1", "/bin/sh", &["-c", &script]))
+            .wait()
+            .unwrap();
+        runtime.start("mod1").wait().unwrap();
+        thread::sleep(Duration::from_millis(500));
+
+        <ProcessModuleRuntime as ModuleRuntime>::remove(&runtime, "mod1")
+            .wait()
+            .unwrap();
+        assert!(marker.exists());
+        assert!(runtime.list().wait().unwrap().is_empty());
+    }
+
+    #[test]
+    fn pull_fails_for_missing_executable() {
+        let dir = tempdir().unwrap();
+        let runtime = ProcessModuleRuntime::new(dir.path()).unwrap();
+        let config = ProcessConfig::new("/this/file/does/not/exist").unwrap();
+
+        assert!(runtime.registry().pull(&config).wait().is_err());
+    }
+
+    #[test]
+    fn pull_succeeds_for_bare_executable_name() {
+        let dir = tempdir().unwrap();
+        let runtime = ProcessModuleRuntime::new(dir.path()).unwrap();
+        let config = ProcessConfig::new("sh").unwrap();
+
+        runtime.registry().pull(&config).wait().unwrap();
+    }
+
+    #[test]
+    fn resolve_user_by_name_and_id() {
+        let passwd = "root:x:0:0:root:/root:/bin/bash\n\
+                      edgeuser:x:1000:1001::/home/edgeuser:/bin/sh\n";
+
+        assert_eq!((1000, 1001), resolve_user("edgeuser", passwd).unwrap());
+        assert_eq!((1000, 1001), resolve_user("1000", passwd).unwrap());
+        assert_eq!((1000, 5), resolve_user("edgeuser:5", passwd).unwrap());
+        assert_eq!((2000, 2000), resolve_user("2000", passwd).unwrap());
+    }
+
+    #[test]
+    fn resolve_user_fails_for_unknown_name() {
+        let err = resolve_user("nobody-here", "").unwrap_err();
+        assert!(match *err.kind() {
+            ErrorKind::InvalidUser(ref user) => user == "nobody-here",
+            _ => false,
+        });
+    }
+}
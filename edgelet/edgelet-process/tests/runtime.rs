@@ -0,0 +1,189 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+#![deny(warnings)]
+#![cfg(unix)]
+
+extern crate futures;
+extern crate tempfile;
+
+extern crate edgelet_core;
+extern crate edgelet_process;
+
+use std::collections::HashMap;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use futures::prelude::*;
+use tempfile::tempdir;
+
+use edgelet_core::pid::Pid;
+use edgelet_core::{
+    ExecOptions, LogOptions, LogTail, Module, ModuleRuntime, ModuleRuntimeState, ModuleSpec,
+    ModuleStatus,
+};
+use edgelet_process::{ErrorKind, ProcessConfig, ProcessModuleRuntime, MODULE_TYPE};
+
+fn spec(name: &str, script: &str) -> ModuleSpec<ProcessConfig> {
+    let config = ProcessConfig::new("/bin/sh")
+        .unwrap()
+        .with_args(vec!["-c".to_string(), script.to_string()]);
+    let mut env = HashMap::new();
+    env.insert("GREETING".to_string(), "hello".to_string());
+    ModuleSpec::new(name, MODULE_TYPE, config, env).unwrap()
+}
+
+fn state(runtime: &ProcessModuleRuntime, name: &str) -> ModuleRuntimeState {
+    runtime
+        .list()
+        .wait()
+        .unwrap()
+        .into_iter()
+        .find(|m| m.name() == name)
+        .unwrap()
+        .runtime_state()
+        .wait()
+        .unwrap()
+}
+
+fn wait_for_status(runtime: &ProcessModuleRuntime, name: &str, status: &ModuleStatus) {
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while state(runtime, name).status() != status {
+        assert!(Instant::now() < deadline, "module never reached {}", status);
+        thread::sleep(Duration::from_millis(50));
+    }
+}
+
+fn logs(runtime: &ProcessModuleRuntime, name: &str) -> Vec<u8> {
+    runtime
+        .logs(name, &LogOptions::new().with_tail(LogTail::All))
+        .and_then(|logs| logs.concat2())
+        .wait()
+        .unwrap()
+        .as_ref()
+        .to_vec()
+}
+
+#[test]
+fn process_lifecycle_succeeds() {
+    let dir = tempdir().unwrap();
+    let runtime = ProcessModuleRuntime::new(dir.path()).unwrap();
+    runtime.init().wait().unwrap();
+
+    runtime
+        .create(spec("mod1", "echo $GREETING; exec sleep 30"))
+        .wait()
+        .unwrap();
+    runtime.start("mod1").wait().unwrap();
+
+    let running = state(&runtime, "mod1");
+    assert_eq!(ModuleStatus::Running, *running.status());
+    match *running.pid() {
+        Pid::Value(pid) => assert!(pid > 0),
+        _ => panic!("expected a pid for a running module"),
+    }
+
+    runtime.pause("mod1").wait().unwrap();
+    assert_eq!(ModuleStatus::Paused, *state(&runtime, "mod1").status());
+    runtime.unpause("mod1").wait().unwrap();
+    assert_eq!(ModuleStatus::Running, *state(&runtime, "mod1").status());
+
+    runtime
+        .stop("mod1", Some(Duration::from_secs(5)))
+        .wait()
+        .unwrap();
+    let stopped = state(&runtime, "mod1");
+    assert_eq!(ModuleStatus::Failed, *stopped.status());
+    assert_eq!(Some(&(128 + 15)), stopped.exit_code());
+
+    // header, then "hello\n"
+    let output = logs(&runtime, "mod1");
+    assert_eq!(1, output[0]);
+    assert_eq!(b"hello\n", &output[8..]);
+
+    runtime.remove("mod1").wait().unwrap();
+    assert!(runtime.list().wait().unwrap().is_empty());
+    assert!(!dir.path().join("mod1.stdout.log").exists());
+}
+
+#[test]
+fn stop_kills_process_after_grace_period() {
+    let dir = tempdir().unwrap();
+    let runtime = ProcessModuleRuntime::new(dir.path()).unwrap();
+
+    runtime
+        .create(spec("mod1", "trap '' TERM; while true; do sleep 1; done"))
+        .wait()
+        .unwrap();
+    runtime.start("mod1").wait().unwrap();
+    // give the shell time to install the trap
+    thread::sleep(Duration::from_millis(200));
+
+    runtime
+        .stop("mod1", Some(Duration::from_millis(500)))
+        .wait()
+        .unwrap();
+    let stopped = state(&runtime, "mod1");
+    assert_eq!(Some(&(128 + 9)), stopped.exit_code());
+}
+
+#[test]
+fn exited_process_is_reported_stopped() {
+    let dir = tempdir().unwrap();
+    let runtime = ProcessModuleRuntime::new(dir.path()).unwrap();
+
+    runtime.create(spec("mod1", "exit 0")).wait().unwrap();
+    runtime.start("mod1").wait().unwrap();
+    wait_for_status(&runtime, "mod1", &ModuleStatus::Stopped);
+
+    let err = runtime.stop("mod1", None).wait().unwrap_err();
+    assert!(match *err.kind() {
+        ErrorKind::NotModified => true,
+        _ => false,
+    });
+
+    // a stopped module can be started again
+    runtime.start("mod1").wait().unwrap();
+    wait_for_status(&runtime, "mod1", &ModuleStatus::Stopped);
+}
+
+#[test]
+fn exec_runs_with_module_environment() {
+    let dir = tempdir().unwrap();
+    let runtime = ProcessModuleRuntime::new(dir.path()).unwrap();
+
+    runtime
+        .create(spec("mod1", "exec sleep 30"))
+        .wait()
+        .unwrap();
+    runtime.start("mod1").wait().unwrap();
+
+    let options = ExecOptions::new(vec![
+        "/bin/sh".to_string(),
+        "-c".to_string(),
+        "echo $GREETING".to_string(),
+    ]);
+    let output = runtime
+        .exec("mod1", &options)
+        .and_then(|logs| logs.concat2())
+        .wait()
+        .unwrap();
+    assert_eq!(b"hello\n", &output.as_ref()[8..]);
+
+    runtime.remove_all().wait().unwrap();
+}
+
+#[test]
+fn exec_fails_for_stopped_module() {
+    let dir = tempdir().unwrap();
+    let runtime = ProcessModuleRuntime::new(dir.path()).unwrap();
+
+    runtime.create(spec("mod1", "exit 0")).wait().unwrap();
+    let err = runtime
+        .exec("mod1", &ExecOptions::new(vec!["ls".to_string()]))
+        .wait()
+        .unwrap_err();
+    assert!(match *err.kind() {
+        ErrorKind::Conflict => true,
+        _ => false,
+    });
+}
@@ -2,11 +2,13 @@
 
 use std::marker::PhantomData;
 use std::time::Duration;
+use std::vec::IntoIter;
 
 use edgelet_core::*;
 use failure::Fail;
 use futures::future::{self, FutureResult};
 use futures::prelude::*;
+use futures::stream::{self, IterOk};
 use futures::IntoFuture;
 use hyper::Body;
 
@@ -33,6 +35,12 @@ impl<E: Fail> ModuleRegistry for NullRegistry<E> {
     type Error = E;
     type PullFuture = FutureResult<(), Self::Error>;
     type RemoveFuture = FutureResult<(), Self::Error>;
+    type ListImagesFuture = FutureResult<Vec<Image>, Self::Error>;
+    type PruneImagesFuture = FutureResult<PruneResult, Self::Error>;
+    type LoadImagesFuture = FutureResult<(), Self::Error>;
+    type SaveImageFuture = FutureResult<Self::ImageArchive, Self::Error>;
+    type ImageChunk = String;
+    type ImageArchive = EmptyBody<Self::Error>;
     type Config = TestConfig;
 
     fn pull(&self, _config: &Self::Config) -> Self::PullFuture {
@@ -42,6 +50,22 @@ impl<E: Fail> ModuleRegistry for NullRegistry<E> {
     fn remove(&self, _name: &str) -> Self::RemoveFuture {
         future::ok(())
     }
+
+    fn list_images(&self) -> Self::ListImagesFuture {
+        future::ok(Vec::new())
+    }
+
+    fn prune_images(&self, _options: &PruneOptions) -> Self::PruneImagesFuture {
+        future::ok(PruneResult::default())
+    }
+
+    fn load_images(&self, _archive: ArchiveStream) -> Self::LoadImagesFuture {
+        future::ok(())
+    }
+
+    fn save_image(&self, _name: &str) -> Self::SaveImageFuture {
+        future::ok(EmptyBody::new())
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -102,6 +126,7 @@ impl<E: Clone + Fail> Module for TestModule<E> {
 pub struct TestRuntime<E: Fail> {
     module: Result<TestModule<E>, E>,
     registry: NullRegistry<E>,
+    stats: Vec<ModuleStats>,
 }
 
 impl<E: Fail> TestRuntime<E> {
@@ -109,8 +134,16 @@ impl<E: Fail> TestRuntime<E> {
         TestRuntime {
             module,
             registry: NullRegistry::new(),
+            stats: vec![ModuleStats::default()],
         }
     }
+
+    /// The samples the stats of the module stream, one default sample if not
+    /// given.
+    pub fn with_stats(mut self, stats: Vec<ModuleStats>) -> Self {
+        self.stats = stats;
+        self
+    }
 }
 
 pub struct EmptyBody<E> {
@@ -153,17 +186,31 @@ impl<E: Clone + Fail> ModuleRuntime for TestRuntime<E> {
     type ModuleRegistry = NullRegistry<E>;
     type Chunk = String;
     type Logs = EmptyBody<Self::Error>;
+    type Stats = IterOk<IntoIter<ModuleStats>, Self::Error>;
+    type Events = IterOk<IntoIter<ModuleEvent>, Self::Error>;
 
     type CreateFuture = FutureResult<(), Self::Error>;
+    type EventsFuture = FutureResult<Self::Events, Self::Error>;
+    type ExecFuture = FutureResult<Self::Logs, Self::Error>;
+    type GetArchiveFuture = FutureResult<Self::Logs, Self::Error>;
     type InitFuture = FutureResult<(), Self::Error>;
     type ListFuture = FutureResult<Vec<Self::Module>, Self::Error>;
     type LogsFuture = FutureResult<Self::Logs, Self::Error>;
     type RemoveFuture = FutureResult<(), Self::Error>;
+    type PauseFuture = FutureResult<(), Self::Error>;
+    type PutArchiveFuture = FutureResult<(), Self::Error>;
     type RestartFuture = FutureResult<(), Self::Error>;
+    type StatsFuture = FutureResult<Self::Stats, Self::Error>;
     type StartFuture = FutureResult<(), Self::Error>;
     type StopFuture = FutureResult<(), Self::Error>;
+    type UnpauseFuture = FutureResult<(), Self::Error>;
     type SystemInfoFuture = FutureResult<SystemInfo, Self::Error>;
     type RemoveAllFuture = FutureResult<(), Self::Error>;
+    type ListVolumesFuture = FutureResult<Vec<Volume>, Self::Error>;
+    type InspectVolumeFuture = FutureResult<Volume, Self::Error>;
+    type CreateVolumeFuture = FutureResult<Volume, Self::Error>;
+    type RemoveVolumeFuture = FutureResult<(), Self::Error>;
+    type BackupVolumeFuture = FutureResult<Self::Logs, Self::Error>;
 
     fn system_info(&self) -> Self::SystemInfoFuture {
         match self.module {
@@ -210,6 +257,20 @@ impl<E: Clone + Fail> ModuleRuntime for TestRuntime<E> {
         }
     }
 
+    fn pause(&self, _id: &str) -> Self::PauseFuture {
+        match self.module {
+            Ok(_) => future::ok(()),
+            Err(ref e) => future::err(e.clone()),
+        }
+    }
+
+    fn unpause(&self, _id: &str) -> Self::UnpauseFuture {
+        match self.module {
+            Ok(_) => future::ok(()),
+            Err(ref e) => future::err(e.clone()),
+        }
+    }
+
     fn remove(&self, _id: &str) -> Self::RemoveFuture {
         match self.module {
             Ok(_) => future::ok(()),
@@ -217,6 +278,13 @@ impl<E: Clone + Fail> ModuleRuntime for TestRuntime<E> {
         }
     }
 
+    fn remove_with_volumes(&self, _id: &str) -> Self::RemoveFuture {
+        match self.module {
+            Ok(_) => future::ok(()),
+            Err(ref e) => future::err(e.clone()),
+        }
+    }
+
     fn list(&self) -> Self::ListFuture {
         match self.module {
             Ok(ref m) => future::ok(vec![m.clone()]),
@@ -231,6 +299,49 @@ impl<E: Clone + Fail> ModuleRuntime for TestRuntime<E> {
         }
     }
 
+    fn stats(&self, _id: &str, _options: &StatsOptions) -> Self::StatsFuture {
+        match self.module {
+            Ok(ref _m) => future::ok(stream::iter_ok(self.stats.clone())),
+            Err(ref e) => future::err(e.clone()),
+        }
+    }
+
+    fn events(&self, _options: &EventOptions) -> Self::EventsFuture {
+        match self.module {
+            Ok(ref m) => future::ok(stream::iter_ok(vec![ModuleEvent::new(
+                m.name().to_string(),
+                ModuleAction::Start,
+            )])),
+            Err(ref e) => future::err(e.clone()),
+        }
+    }
+
+    fn exec(&self, _id: &str, _options: &ExecOptions) -> Self::ExecFuture {
+        match self.module {
+            Ok(ref _m) => future::ok(EmptyBody::new()),
+            Err(ref e) => future::err(e.clone()),
+        }
+    }
+
+    fn get_archive(&self, _id: &str, _path: &str) -> Self::GetArchiveFuture {
+        match self.module {
+            Ok(ref _m) => future::ok(EmptyBody::new()),
+            Err(ref e) => future::err(e.clone()),
+        }
+    }
+
+    fn put_archive(
+        &self,
+        _id: &str,
+        _path: &str,
+        _archive: ArchiveStream,
+    ) -> Self::PutArchiveFuture {
+        match self.module {
+            Ok(ref _m) => future::ok(()),
+            Err(ref e) => future::err(e.clone()),
+        }
+    }
+
     fn registry(&self) -> &Self::ModuleRegistry {
         &self.registry
     }
@@ -238,4 +349,44 @@ impl<E: Clone + Fail> ModuleRuntime for TestRuntime<E> {
     fn remove_all(&self) -> Self::RemoveAllFuture {
         future::ok(())
     }
+
+    fn list_volumes(&self) -> Self::ListVolumesFuture {
+        match self.module {
+            Ok(ref m) => {
+                future::ok(vec![Volume::new(format!("{}-data", m.name()))
+                    .with_module(Some(m.name().to_string()))])
+            }
+            Err(ref e) => future::err(e.clone()),
+        }
+    }
+
+    fn inspect_volume(&self, name: &str) -> Self::InspectVolumeFuture {
+        match self.module {
+            Ok(_) => future::ok(Volume::new(name.to_string())),
+            Err(ref e) => future::err(e.clone()),
+        }
+    }
+
+    fn create_volume(&self, name: &str, module: Option<&str>) -> Self::CreateVolumeFuture {
+        match self.module {
+            Ok(_) => future::ok(
+                Volume::new(name.to_string()).with_module(module.map(ToString::to_string)),
+            ),
+            Err(ref e) => future::err(e.clone()),
+        }
+    }
+
+    fn remove_volume(&self, _name: &str) -> Self::RemoveVolumeFuture {
+        match self.module {
+            Ok(_) => future::ok(()),
+            Err(ref e) => future::err(e.clone()),
+        }
+    }
+
+    fn backup_volume(&self, _name: &str) -> Self::BackupVolumeFuture {
+        match self.module {
+            Ok(_) => future::ok(EmptyBody::new()),
+            Err(ref e) => future::err(e.clone()),
+        }
+    }
 }
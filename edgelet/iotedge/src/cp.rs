@@ -0,0 +1,354 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+use std::ffi::OsStr;
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+use std::path::{Component, Path, PathBuf};
+
+use edgelet_core::{ArchiveStream, Error as CoreError, ErrorKind as CoreErrorKind, ModuleRuntime};
+use failure::Fail;
+use futures::{future, stream, Async, Future, Stream};
+use tar::{Archive, Builder};
+
+use error::{Error, ErrorKind};
+use Command;
+
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// One side of a copy. Paths of the form `MODULE:PATH` refer to a path
+/// inside a module; anything else is a local path, with `-` meaning stdin or
+/// stdout.
+#[derive(Debug, PartialEq)]
+pub enum CopyPath {
+    Module(String, String),
+    Local(PathBuf),
+    Stdio,
+}
+
+impl CopyPath {
+    pub fn parse(value: &str) -> CopyPath {
+        if value == "-" {
+            return CopyPath::Stdio;
+        }
+
+        match value.find(':') {
+            // A single letter before the colon is a Windows drive, not a module.
+            Some(index) if index > 1 && !value[..index].contains(|c| c == '/' || c == '\\') => {
+                CopyPath::Module(value[..index].to_string(), value[index + 1..].to_string())
+            }
+            _ => CopyPath::Local(PathBuf::from(value)),
+        }
+    }
+
+    fn is_module(&self) -> bool {
+        match *self {
+            CopyPath::Module(_, _) => true,
+            _ => false,
+        }
+    }
+}
+
+/// Copies files between a module and the local machine, like `docker cp`.
+/// Copying out of a module extracts the module path into an existing local
+/// directory, or to the local path when it does not exist. Copying into a
+/// module copies the local file or directory into the module path. With
+/// `-` the tar archive itself is read from stdin or written to stdout.
+pub struct Cp<M> {
+    src: CopyPath,
+    dest: CopyPath,
+    runtime: M,
+}
+
+impl<M> Cp<M> {
+    pub fn new(src: &str, dest: &str, runtime: M) -> Result<Self, Error> {
+        let src = CopyPath::parse(src);
+        let dest = CopyPath::parse(dest);
+        if src.is_module() == dest.is_module() {
+            Err(Error::from(ErrorKind::BadCopyPaths))
+        } else {
+            Ok(Cp { src, dest, runtime })
+        }
+    }
+}
+
+impl<M> Command for Cp<M>
+where
+    M: 'static + ModuleRuntime + Clone,
+{
+    type Future = Box<Future<Item = (), Error = Error>>;
+
+    fn execute(&mut self) -> Self::Future {
+        match (&self.src, &self.dest) {
+            (&CopyPath::Module(ref id, ref path), &CopyPath::Stdio) => {
+                let result = self
+                    .runtime
+                    .get_archive(id, path)
+                    .map_err(|_| Error::from(ErrorKind::ModuleRuntime))
+                    .and_then(|archive| {
+                        future::result(open_output(&CopyPath::Stdio))
+                            .and_then(|output| write_archive(archive, output))
+                    });
+                Box::new(result)
+            }
+            (&CopyPath::Module(ref id, ref path), &CopyPath::Local(ref dest)) => {
+                let dest = dest.clone();
+                let result = self
+                    .runtime
+                    .get_archive(id, path)
+                    .map_err(|_| Error::from(ErrorKind::ModuleRuntime))
+                    .and_then(|archive| {
+                        archive
+                            .map_err(|_| Error::from(ErrorKind::ModuleRuntime))
+                            .fold(Vec::new(), |mut buf, chunk| {
+                                buf.extend_from_slice(chunk.as_ref());
+                                Ok(buf) as Result<Vec<u8>, Error>
+                            })
+                    }).and_then(move |archive| extract(&archive, &dest));
+                Box::new(result)
+            }
+            (local, &CopyPath::Module(ref id, ref path)) => {
+                let archive = match *local {
+                    CopyPath::Local(ref src) => pack(src)
+                        .map(|archive| Box::new(stream::once(Ok(archive))) as ArchiveStream),
+                    _ => read_archive(local),
+                };
+                let archive = match archive {
+                    Ok(archive) => archive,
+                    Err(e) => return Box::new(future::err(e)),
+                };
+                let result = self
+                    .runtime
+                    .put_archive(id, path, archive)
+                    .map_err(|_| Error::from(ErrorKind::ModuleRuntime));
+                Box::new(result)
+            }
+            _ => Box::new(future::err(Error::from(ErrorKind::BadCopyPaths))),
+        }
+    }
+}
+
+/// Image and volume archives are always local, even when their name has a
+/// colon.
+pub fn local_path(value: &str) -> CopyPath {
+    if value == "-" {
+        CopyPath::Stdio
+    } else {
+        CopyPath::Local(PathBuf::from(value))
+    }
+}
+
+pub fn open_output(path: &CopyPath) -> Result<Box<Write>, Error> {
+    match *path {
+        CopyPath::Local(ref path) => Ok(Box::new(File::create(path)?)),
+        _ => Ok(Box::new(io::stdout())),
+    }
+}
+
+/// Reads an archive in chunks, so that it is sent on as it is read.
+pub fn read_archive(path: &CopyPath) -> Result<ArchiveStream, Error> {
+    let mut input: Box<Read> = match *path {
+        CopyPath::Local(ref path) => Box::new(File::open(path)?),
+        _ => Box::new(io::stdin()),
+    };
+    let chunks = stream::poll_fn(move || {
+        let mut chunk = vec![0; CHUNK_SIZE];
+        let read = input
+            .read(&mut chunk)
+            .map_err(|e| CoreError::from(e.context(CoreErrorKind::Io)))?;
+        if read == 0 {
+            Ok(Async::Ready(None))
+        } else {
+            chunk.truncate(read);
+            Ok(Async::Ready(Some(chunk)))
+        }
+    });
+    Ok(Box::new(chunks))
+}
+
+// The archive has the file or directory under its own name, as the module
+// runtime expects when extracting it into a directory.
+fn pack(path: &Path) -> Result<Vec<u8>, Error> {
+    let path = fs::canonicalize(path)?;
+    let name = path
+        .file_name()
+        .ok_or_else(|| Error::from(ErrorKind::BadCopyPaths))?;
+
+    let mut builder = Builder::new(Vec::new());
+    builder.follow_symlinks(false);
+    if path.is_dir() {
+        builder.append_dir_all(name, &path)?;
+    } else {
+        builder.append_path_with_name(&path, name)?;
+    }
+    Ok(builder.into_inner()?)
+}
+
+// An archive of a module path has a single top level entry. It is extracted
+// as is into an existing directory, and under the name of `dest` otherwise.
+fn extract(archive: &[u8], dest: &Path) -> Result<(), Error> {
+    let mut archive = Archive::new(archive);
+    if dest.is_dir() {
+        archive.unpack(dest)?;
+        return Ok(());
+    }
+
+    let name = dest
+        .file_name()
+        .ok_or_else(|| Error::from(ErrorKind::BadCopyPaths))?;
+    let parent = match dest.parent() {
+        Some(parent) if parent != Path::new("") => parent,
+        _ => Path::new("."),
+    };
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let target = parent.join(renamed(&entry.path()?, name)?);
+        if let Some(dir) = target.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        entry.unpack(&target)?;
+    }
+    Ok(())
+}
+
+// Replaces the top level component of an archive entry path with `name`.
+// Entries that would end up outside of it are rejected.
+fn renamed(path: &Path, name: &OsStr) -> Result<PathBuf, Error> {
+    let mut components = path
+        .components()
+        .filter(|component| *component != Component::CurDir);
+    let mut renamed = PathBuf::from(name);
+    match components.next() {
+        Some(Component::Normal(_)) => (),
+        _ => return Err(bad_entry(path)),
+    }
+    for component in components {
+        match component {
+            Component::Normal(part) => renamed.push(part),
+            _ => return Err(bad_entry(path)),
+        }
+    }
+    Ok(renamed)
+}
+
+fn bad_entry(path: &Path) -> Error {
+    Error::from(ErrorKind::BadArchiveEntry(path.display().to_string()))
+}
+
+pub fn write_archive<S, C>(
+    archive: S,
+    mut output: Box<Write>,
+) -> impl Future<Item = (), Error = Error>
+where
+    C: AsRef<[u8]>,
+    S: Stream<Item = C>,
+{
+    archive
+        .map_err(|_| Error::from(ErrorKind::ModuleRuntime))
+        .for_each(move |chunk| output.write_all(chunk.as_ref()).map_err(Error::from))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use tempdir::TempDir;
+
+    #[test]
+    fn parse_module_path() {
+        assert_eq!(
+            CopyPath::Module("tempSensor".to_string(), "/var/log/dump".to_string()),
+            CopyPath::parse("tempSensor:/var/log/dump")
+        );
+    }
+
+    #[test]
+    fn parse_local_path() {
+        assert_eq!(
+            CopyPath::Local(PathBuf::from("./calibration.tar")),
+            CopyPath::parse("./calibration.tar")
+        );
+        assert_eq!(
+            CopyPath::Local(PathBuf::from("./dir:with/colon")),
+            CopyPath::parse("./dir:with/colon")
+        );
+        assert_eq!(
+            CopyPath::Local(PathBuf::from("C:\\dumps\\dump.tar")),
+            CopyPath::parse("C:\\dumps\\dump.tar")
+        );
+    }
+
+    #[test]
+    fn parse_stdio() {
+        assert_eq!(CopyPath::Stdio, CopyPath::parse("-"));
+    }
+
+    #[test]
+    fn archives_are_local() {
+        assert_eq!(
+            CopyPath::Local(PathBuf::from("nginx:latest.tar")),
+            local_path("nginx:latest.tar")
+        );
+        assert_eq!(CopyPath::Stdio, local_path("-"));
+    }
+
+    #[test]
+    fn exactly_one_side_is_a_module() {
+        assert!(Cp::new("mod1:/var/log", "./logs.tar", ()).is_ok());
+        assert!(Cp::new("-", "mod1:/etc", ()).is_ok());
+        assert!(Cp::new("./a.tar", "./b.tar", ()).is_err());
+        assert!(Cp::new("mod1:/etc", "mod2:/etc", ()).is_err());
+    }
+
+    #[test]
+    fn renamed_replaces_top_level_entry() {
+        let name = OsStr::new("logs");
+        assert_eq!(
+            PathBuf::from("logs"),
+            renamed(Path::new("dump"), name).unwrap()
+        );
+        assert_eq!(
+            PathBuf::from("logs/a/b.txt"),
+            renamed(Path::new("./dump/a/b.txt"), name).unwrap()
+        );
+        assert!(renamed(Path::new("dump/../../etc/passwd"), name).is_err());
+        assert!(renamed(Path::new("/etc/passwd"), name).is_err());
+    }
+
+    #[test]
+    fn pack_and_extract_directory() {
+        let tmp = TempDir::new("cp").unwrap();
+        let src = tmp.path().join("dump");
+        fs::create_dir_all(src.join("nested")).unwrap();
+        fs::write(src.join("nested").join("a.txt"), b"hello").unwrap();
+        let archive = pack(&src).unwrap();
+
+        // into an existing directory the archive keeps its name
+        let existing = tmp.path().join("existing");
+        fs::create_dir(&existing).unwrap();
+        extract(&archive, &existing).unwrap();
+        assert_eq!(
+            b"hello".to_vec(),
+            fs::read(existing.join("dump").join("nested").join("a.txt")).unwrap()
+        );
+
+        // otherwise it is extracted as the destination
+        let dest = tmp.path().join("copy");
+        extract(&archive, &dest).unwrap();
+        assert_eq!(
+            b"hello".to_vec(),
+            fs::read(dest.join("nested").join("a.txt")).unwrap()
+        );
+    }
+
+    #[test]
+    fn pack_and_extract_file() {
+        let tmp = TempDir::new("cp").unwrap();
+        let src = tmp.path().join("a.txt");
+        fs::write(&src, b"hello").unwrap();
+        let archive = pack(&src).unwrap();
+
+        let dest = tmp.path().join("b.txt");
+        extract(&archive, &dest).unwrap();
+        assert_eq!(b"hello".to_vec(), fs::read(&dest).unwrap());
+    }
+}
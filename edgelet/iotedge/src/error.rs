@@ -25,6 +25,12 @@ pub enum ErrorKind {
     HttpMgmt,
     #[fail(display = "Missing host")]
     NoHost,
+    #[fail(display = "Invalid time value {}", _0)]
+    BadTimeValue(String),
+    #[fail(display = "Exactly one of the source and destination must be a MODULE:PATH")]
+    BadCopyPaths,
+    #[fail(display = "Archive entry {} is outside of the copied path", _0)]
+    BadArchiveEntry(String),
 }
 
 impl Fail for Error {
@@ -0,0 +1,97 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+use std::cell::RefCell;
+use std::io::Write;
+use std::sync::Arc;
+
+use chrono::SecondsFormat;
+use edgelet_core::{EventOptions, ModuleEvent, ModuleRuntime};
+use futures::{Future, Stream};
+
+use error::Error;
+use Command;
+
+pub struct Events<M, W> {
+    options: EventOptions,
+    runtime: M,
+    output: Arc<RefCell<W>>,
+}
+
+impl<M, W> Events<M, W> {
+    pub fn new(options: EventOptions, runtime: M, output: W) -> Self {
+        Events {
+            options,
+            runtime,
+            output: Arc::new(RefCell::new(output)),
+        }
+    }
+}
+
+impl<M, W> Command for Events<M, W>
+where
+    M: 'static + ModuleRuntime + Clone,
+    M::Error: Into<Error>,
+    W: 'static + Write,
+{
+    type Future = Box<Future<Item = (), Error = Error>>;
+
+    fn execute(&mut self) -> Self::Future {
+        let write = self.output.clone();
+        let result = self
+            .runtime
+            .events(&self.options)
+            .map_err(|e| e.into())
+            .and_then(move |events| {
+                events.map_err(|e| e.into()).for_each(move |event| {
+                    let mut w = write.borrow_mut();
+                    writeln!(w, "{}", format_event(&event))?;
+                    w.flush()?;
+                    Ok(())
+                })
+            });
+        Box::new(result)
+    }
+}
+
+fn format_event(event: &ModuleEvent) -> String {
+    let time = event
+        .time()
+        .map(|t| t.to_rfc3339_opts(SecondsFormat::Secs, true))
+        .unwrap_or_else(|| "-".to_string());
+    let mut line = format!("{} {} {}", time, event.name(), event.action());
+    if let Some(exit_code) = event.exit_code() {
+        line.push_str(&format!(" (exitCode={})", exit_code));
+    }
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use chrono::{TimeZone, Utc};
+    use edgelet_core::ModuleAction;
+
+    #[test]
+    fn format_die_event() {
+        let event = ModuleEvent::new("edgeHub".to_string(), ModuleAction::Die)
+            .with_time(Some(Utc.ymd(2018, 8, 2).and_hms(0, 0, 0)))
+            .with_exit_code(Some(137));
+        assert_eq!(
+            "2018-08-02T00:00:00Z edgeHub die (exitCode=137)",
+            format_event(&event)
+        );
+    }
+
+    #[test]
+    fn format_health_event_without_time() {
+        let event = ModuleEvent::new(
+            "tempSensor".to_string(),
+            ModuleAction::Health("unhealthy".to_string()),
+        );
+        assert_eq!(
+            "- tempSensor health_status: unhealthy",
+            format_event(&event)
+        );
+    }
+}
@@ -0,0 +1,40 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+use edgelet_core::{ExecOptions, ModuleRuntime};
+use futures::Future;
+
+use error::{Error, ErrorKind};
+use logs::write_output;
+use Command;
+
+pub struct Exec<M> {
+    id: String,
+    options: ExecOptions,
+    runtime: M,
+}
+
+impl<M> Exec<M> {
+    pub fn new(id: String, options: ExecOptions, runtime: M) -> Self {
+        Exec {
+            id,
+            options,
+            runtime,
+        }
+    }
+}
+
+impl<M> Command for Exec<M>
+where
+    M: 'static + ModuleRuntime + Clone,
+{
+    type Future = Box<Future<Item = (), Error = Error>>;
+
+    fn execute(&mut self) -> Self::Future {
+        let result = self
+            .runtime
+            .exec(&self.id, &self.options)
+            .map_err(|_| Error::from(ErrorKind::ModuleRuntime))
+            .and_then(write_output);
+        Box::new(result)
+    }
+}
@@ -0,0 +1,196 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+use std::cell::RefCell;
+use std::fmt::Display;
+use std::io::Write;
+use std::sync::Arc;
+
+use edgelet_core::{Module, ModuleRegistry, ModuleRuntime, PruneOptions};
+use futures::{future, Future};
+
+use cp::{local_path, open_output, read_archive, write_archive, CopyPath};
+use error::Error;
+use Command;
+
+const UNITS: [&str; 5] = ["B", "kB", "MB", "GB", "TB"];
+
+pub struct PruneImages<M, W> {
+    runtime: M,
+    output: Arc<RefCell<W>>,
+}
+
+impl<M, W> PruneImages<M, W> {
+    pub fn new(runtime: M, output: W) -> Self {
+        PruneImages {
+            runtime,
+            output: Arc::new(RefCell::new(output)),
+        }
+    }
+}
+
+impl<M, W> Command for PruneImages<M, W>
+where
+    M: 'static + ModuleRuntime + Clone,
+    M::Error: Into<Error>,
+    W: 'static + Write,
+{
+    type Future = Box<Future<Item = (), Error = Error>>;
+
+    fn execute(&mut self) -> Self::Future {
+        let write = self.output.clone();
+        let result = self
+            .runtime
+            .registry()
+            .prune_images(&PruneOptions::new())
+            .map_err(|e| e.into())
+            .and_then(move |result| {
+                let mut w = write.borrow_mut();
+                for id in result.images_deleted() {
+                    writeln!(w, "Deleted: {}", id)?;
+                }
+                writeln!(
+                    w,
+                    "Total reclaimed space: {}",
+                    human_size(result.space_reclaimed())
+                )?;
+                Ok(())
+            });
+        Box::new(result)
+    }
+}
+
+/// Loads the images in a tar archive written by `iotedge image save` or
+/// `docker save`, so modules can use them without pulling.
+pub struct LoadImages<M> {
+    input: CopyPath,
+    runtime: M,
+}
+
+impl<M> LoadImages<M> {
+    pub fn new(input: &str, runtime: M) -> Self {
+        LoadImages {
+            input: local_path(input),
+            runtime,
+        }
+    }
+}
+
+impl<M> Command for LoadImages<M>
+where
+    M: 'static + ModuleRuntime + Clone,
+    M::Error: Into<Error>,
+{
+    type Future = Box<Future<Item = (), Error = Error>>;
+
+    fn execute(&mut self) -> Self::Future {
+        let archive = match read_archive(&self.input) {
+            Ok(archive) => archive,
+            Err(e) => return Box::new(future::err(e)),
+        };
+        let result = self
+            .runtime
+            .registry()
+            .load_images(archive)
+            .map_err(|e| e.into());
+        Box::new(result)
+    }
+}
+
+/// Writes a tar archive of the image of a module. A name that is not a
+/// module is taken to be an image name.
+pub struct SaveImage<M> {
+    name: String,
+    output: CopyPath,
+    runtime: M,
+}
+
+impl<M> SaveImage<M> {
+    pub fn new(name: String, output: &str, runtime: M) -> Self {
+        SaveImage {
+            name,
+            output: local_path(output),
+            runtime,
+        }
+    }
+}
+
+impl<M> Command for SaveImage<M>
+where
+    M: 'static + ModuleRuntime + Clone,
+    M::Config: Display,
+    M::Error: Into<Error>,
+{
+    type Future = Box<Future<Item = (), Error = Error>>;
+
+    fn execute(&mut self) -> Self::Future {
+        let output = match open_output(&self.output) {
+            Ok(output) => output,
+            Err(e) => return Box::new(future::err(e)),
+        };
+        let name = self.name.clone();
+        let runtime = self.runtime.clone();
+        let result = self
+            .runtime
+            .list()
+            .map(move |modules| {
+                let config = modules
+                    .iter()
+                    .find(|m| m.name() == name)
+                    .map(|m| m.config().to_string());
+                image_name(&name, config)
+            }).and_then(move |image| runtime.registry().save_image(&image))
+            .map_err(|e| e.into())
+            .and_then(|archive| write_archive(archive, output));
+        Box::new(result)
+    }
+}
+
+// The image of a module is the one shown by `iotedge list`.
+fn image_name(name: &str, module_image: Option<String>) -> String {
+    match module_image {
+        Some(ref image) if !image.is_empty() => image.to_string(),
+        _ => name.to_string(),
+    }
+}
+
+// Decimal units, the way docker reports image sizes.
+fn human_size(bytes: u64) -> String {
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1000.0 && unit < UNITS.len() - 1 {
+        size /= 1000.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{}{}", bytes, UNITS[0])
+    } else {
+        format!("{:.1}{}", size, UNITS[unit])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn save_uses_module_image() {
+        assert_eq!(
+            "microsoft/azureiotedge-simulated-temperature-sensor:1.0",
+            image_name(
+                "tempSensor",
+                Some("microsoft/azureiotedge-simulated-temperature-sensor:1.0".to_string())
+            )
+        );
+        assert_eq!("tempSensor", image_name("tempSensor", Some(String::new())));
+        assert_eq!("nginx:latest", image_name("nginx:latest", None));
+    }
+
+    #[test]
+    fn sizes_use_decimal_units() {
+        assert_eq!("0B", human_size(0));
+        assert_eq!("999B", human_size(999));
+        assert_eq!("1.5kB", human_size(1500));
+        assert_eq!("104.9MB", human_size(104_857_600));
+        assert_eq!("2000.0TB", human_size(2_000_000_000_000_000));
+    }
+}
@@ -13,24 +13,41 @@ extern crate failure_derive;
 #[macro_use]
 extern crate futures;
 extern crate tabwriter;
+extern crate tar;
+#[cfg(test)]
+extern crate tempdir;
 extern crate tokio_io;
 extern crate url;
 
 use futures::Future;
 
+mod cp;
 mod error;
+mod events;
+mod exec;
+mod image;
 mod list;
 mod logs;
+mod pause;
 mod restart;
 mod unknown;
+mod unpause;
 mod version;
+mod volume;
 
+pub use cp::{CopyPath, Cp};
 pub use error::{Error, ErrorKind};
+pub use events::Events;
+pub use exec::Exec;
+pub use image::{LoadImages, PruneImages, SaveImage};
 pub use list::List;
-pub use logs::Logs;
+pub use logs::{parse_time, Logs};
+pub use pause::Pause;
 pub use restart::Restart;
 pub use unknown::Unknown;
+pub use unpause::Unpause;
 pub use version::Version;
+pub use volume::{BackupVolume, ListVolumes, RemoveVolume};
 
 pub trait Command {
     type Future: Future<Item = (), Error = Error>;
@@ -63,7 +63,7 @@ where
                                 "{}\t{}\t{}\t{}",
                                 module.name(),
                                 state.status(),
-                                humanize_state(&state),
+                                format!("{}{}", humanize_state(&state), state_details(&state)),
                                 module.config(),
                             )?;
                         }
@@ -105,6 +105,35 @@ fn humanize_state(state: &ModuleRuntimeState) -> String {
                     time_string(&HumanTime::from(Utc::now() - *time), Tense::Present)
                 )
             }).unwrap_or_else(|| "Up".to_string()),
+        ModuleStatus::Created => "Created".to_string(),
+        ModuleStatus::Paused => "Paused".to_string(),
+        ModuleStatus::Restarting => "Restarting".to_string(),
+        ModuleStatus::Backoff => state
+            .exit_code()
+            .map(|code| format!("Backing off after exit ({})", code))
+            .unwrap_or_else(|| "Backing off".to_string()),
+        ModuleStatus::Dead => "Dead".to_string(),
+    }
+}
+
+fn state_details(state: &ModuleRuntimeState) -> String {
+    let mut details = vec![];
+    if let Some(health) = state.health() {
+        details.push(health.to_string());
+    }
+    match state.restart_count() {
+        0 => (),
+        1 => details.push("1 restart".to_string()),
+        count => details.push(format!("{} restarts", count)),
+    }
+    if state.oom_killed() {
+        details.push("OOM killed".to_string());
+    }
+
+    if details.is_empty() {
+        String::new()
+    } else {
+        format!(" ({})", details.join(", "))
     }
 }
 
@@ -115,3 +144,32 @@ fn time_string(ht: &HumanTime, tense: Tense) -> String {
         ht.to_text_en(Accuracy::Rough, tense)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use edgelet_core::ModuleHealth;
+
+    #[test]
+    fn state_details_empty_by_default() {
+        let state = ModuleRuntimeState::default().with_status(ModuleStatus::Running);
+        assert_eq!("", state_details(&state));
+    }
+
+    #[test]
+    fn state_details_lists_health_restarts_and_oom() {
+        let state = ModuleRuntimeState::default()
+            .with_status(ModuleStatus::Backoff)
+            .with_health(Some(ModuleHealth::Unhealthy))
+            .with_restart_count(3)
+            .with_oom_killed(true);
+        assert_eq!(" (unhealthy, 3 restarts, OOM killed)", state_details(&state));
+    }
+
+    #[test]
+    fn humanize_paused() {
+        let state = ModuleRuntimeState::default().with_status(ModuleStatus::Paused);
+        assert_eq!("Paused", humanize_state(&state));
+    }
+}
@@ -4,6 +4,7 @@ use std::cmp;
 use std::io::{self, Write};
 
 use bytes::{Buf, BufMut, Bytes, BytesMut, IntoBuf};
+use chrono::{DateTime, Duration, Utc};
 use edgelet_core::{LogOptions, ModuleRuntime};
 use futures::prelude::*;
 use tokio_io::codec::length_delimited::{self, FramedRead};
@@ -40,24 +41,76 @@ where
             .runtime
             .logs(&id, &self.options)
             .map_err(|_| Error::from(ErrorKind::ModuleRuntime))
-            .and_then(move |logs| {
-                let chunked =
-                    Chunked::new(logs.map_err(|_| io::Error::new(io::ErrorKind::Other, "unknown")));
-                LogDecode::new(chunked)
-                    .for_each(|chunk| {
-                        match chunk {
-                            LogChunk::Stdin(b) => io::stdout().write(&b)?,
-                            LogChunk::Stdout(b) => io::stdout().write(&b)?,
-                            LogChunk::Stderr(b) => io::stderr().write(&b)?,
-                            LogChunk::Unknown(b) => io::stdout().write(&b)?,
-                        };
-                        Ok(())
-                    }).map_err(|_| Error::from(ErrorKind::ModuleRuntime))
-            });
+            .and_then(write_output);
         Box::new(result)
     }
 }
 
+/// Writes a stream framed like the module logs to stdout and stderr.
+pub(crate) fn write_output<S, C>(output: S) -> impl Future<Item = (), Error = Error>
+where
+    C: AsRef<[u8]>,
+    S: Stream<Item = C>,
+{
+    let chunked = Chunked::new(output.map_err(|_| io::Error::new(io::ErrorKind::Other, "unknown")));
+    LogDecode::new(chunked)
+        .for_each(|chunk| {
+            match chunk {
+                LogChunk::Stdin(b) => io::stdout().write(&b)?,
+                LogChunk::Stdout(b) => io::stdout().write(&b)?,
+                LogChunk::Stderr(b) => io::stderr().write(&b)?,
+                LogChunk::Unknown(b) => io::stdout().write(&b)?,
+            };
+            Ok(())
+        }).map_err(|_| Error::from(ErrorKind::ModuleRuntime))
+}
+
+/// Converts the value of a `--since` or `--until` argument into a UNIX
+/// timestamp. The value may be a UNIX timestamp, an RFC 3339 date or a
+/// duration relative to now such as `10m` or `1h30m` (units `s`, `m`, `h`
+/// and `d`).
+pub fn parse_time(value: &str) -> Result<i32, Error> {
+    parse_time_at(value, Utc::now())
+}
+
+fn parse_time_at(value: &str, now: DateTime<Utc>) -> Result<i32, Error> {
+    if let Ok(timestamp) = value.parse::<i32>() {
+        Ok(timestamp)
+    } else if let Ok(date) = DateTime::parse_from_rfc3339(value) {
+        Ok(date.timestamp() as i32)
+    } else {
+        parse_duration(value)
+            .map(|duration| (now - duration).timestamp() as i32)
+            .ok_or_else(|| Error::from(ErrorKind::BadTimeValue(value.to_string())))
+    }
+}
+
+fn parse_duration(value: &str) -> Option<Duration> {
+    let mut total = Duration::zero();
+    let mut digits = String::new();
+    for c in value.chars() {
+        if c.is_ascii_digit() {
+            digits.push(c);
+        } else {
+            let num = digits.parse::<i64>().ok()?;
+            digits.clear();
+            total = total + match c {
+                's' => Duration::seconds(num),
+                'm' => Duration::minutes(num),
+                'h' => Duration::hours(num),
+                'd' => Duration::days(num),
+                _ => return None,
+            };
+        }
+    }
+
+    if digits.is_empty() && !value.is_empty() {
+        Some(total)
+    } else {
+        None
+    }
+}
+
 /// Logs parser
 /// Logs are emitted with a simple header to specify stdout or stderr
 ///
@@ -266,4 +319,40 @@ mod tests {
         }
         assert_eq!("Roses are red violets are blue".as_bytes(), read_buffer);
     }
+
+    #[test]
+    fn parse_time_accepts_timestamps() {
+        let now = Utc::now();
+        assert_eq!(1_533_168_000, parse_time_at("1533168000", now).unwrap());
+        assert_eq!(
+            1_533_168_000,
+            parse_time_at("2018-08-02T00:00:00Z", now).unwrap()
+        );
+        assert_eq!(
+            1_533_168_000,
+            parse_time_at("2018-08-01T17:00:00-07:00", now).unwrap()
+        );
+    }
+
+    #[test]
+    fn parse_time_accepts_durations() {
+        let now = DateTime::parse_from_rfc3339("2018-08-02T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        assert_eq!(1_533_167_400, parse_time_at("10m", now).unwrap());
+        assert_eq!(1_533_162_600, parse_time_at("1h30m", now).unwrap());
+        assert_eq!(1_533_081_600, parse_time_at("1d", now).unwrap());
+        assert_eq!(1_533_167_955, parse_time_at("45s", now).unwrap());
+    }
+
+    #[test]
+    fn parse_time_rejects_garbage() {
+        let now = Utc::now();
+        assert!(parse_time_at("", now).is_err());
+        assert!(parse_time_at("m", now).is_err());
+        assert!(parse_time_at("10", now).is_ok());
+        assert!(parse_time_at("10w", now).is_err());
+        assert!(parse_time_at("10m5", now).is_err());
+        assert!(parse_time_at("yesterday", now).is_err());
+    }
 }
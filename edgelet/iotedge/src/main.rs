@@ -1,5 +1,6 @@
 // Copyright (c) Microsoft. All rights reserved.
 
+extern crate chrono;
 #[macro_use]
 extern crate clap;
 extern crate edgelet_core;
@@ -16,8 +17,9 @@ use std::io;
 use std::io::Write;
 use std::process;
 
+use chrono::Utc;
 use clap::{App, AppSettings, Arg, SubCommand};
-use edgelet_core::{LogOptions, LogTail};
+use edgelet_core::{EventOptions, ExecOptions, LogOptions, LogTail};
 use edgelet_http_mgmt::ModuleClient;
 use failure::Fail;
 use iotedge::*;
@@ -73,6 +75,24 @@ fn run() -> Result<(), Error> {
                         .required(true)
                         .index(1),
                 ),
+        ).subcommand(
+            SubCommand::with_name("pause")
+                .about("Pause all processes of a module")
+                .arg(
+                    Arg::with_name("MODULE")
+                        .help("Sets the module identity to pause")
+                        .required(true)
+                        .index(1),
+                ),
+        ).subcommand(
+            SubCommand::with_name("unpause")
+                .about("Resume all processes of a paused module")
+                .arg(
+                    Arg::with_name("MODULE")
+                        .help("Sets the module identity to unpause")
+                        .required(true)
+                        .index(1),
+                ),
         ).subcommand(
             SubCommand::with_name("logs")
                 .about("Fetch the logs of a module")
@@ -93,6 +113,146 @@ fn run() -> Result<(), Error> {
                         .help("Follow output log")
                         .short("f")
                         .long("follow"),
+                ).arg(
+                    Arg::with_name("since")
+                        .help("Only show logs since this time (UNIX timestamp, RFC 3339 date or relative duration like 10m)")
+                        .long("since")
+                        .takes_value(true)
+                        .value_name("TIME"),
+                ).arg(
+                    Arg::with_name("until")
+                        .help("Only show logs before this time (UNIX timestamp, RFC 3339 date or relative duration like 10m)")
+                        .long("until")
+                        .takes_value(true)
+                        .value_name("TIME"),
+                ).arg(
+                    Arg::with_name("timestamps")
+                        .help("Show timestamps")
+                        .short("t")
+                        .long("timestamps"),
+                ),
+        ).subcommand(
+            SubCommand::with_name("exec")
+                .about("Run a command inside a running module")
+                .arg(
+                    Arg::with_name("MODULE")
+                        .help("Sets the module identity to run the command in")
+                        .required(true)
+                        .index(1),
+                ).arg(
+                    Arg::with_name("user")
+                        .help("Username or UID to run the command as")
+                        .short("u")
+                        .long("user")
+                        .takes_value(true)
+                        .value_name("USER"),
+                ).arg(
+                    Arg::with_name("COMMAND")
+                        .help("The command and its arguments")
+                        .required(true)
+                        .multiple(true)
+                        .last(true),
+                ),
+        ).subcommand(
+            SubCommand::with_name("cp")
+                .about("Copy files between a module and the local filesystem")
+                .arg(
+                    Arg::with_name("SRC")
+                        .help("MODULE:PATH to copy from, or a local path (- for a tar archive on stdin)")
+                        .required(true)
+                        .index(1),
+                ).arg(
+                    Arg::with_name("DEST")
+                        .help("MODULE:PATH to copy into, or a local path (- for a tar archive on stdout)")
+                        .required(true)
+                        .index(2),
+                ),
+        ).subcommand(
+            SubCommand::with_name("events")
+                .about("Show module lifecycle events")
+                .arg(
+                    Arg::with_name("since")
+                        .help("Show events since this time (UNIX timestamp, RFC 3339 date or relative duration like 10m)")
+                        .long("since")
+                        .takes_value(true)
+                        .value_name("TIME"),
+                ).arg(
+                    Arg::with_name("until")
+                        .help("Show events until this time (UNIX timestamp, RFC 3339 date or relative duration like 10m)")
+                        .long("until")
+                        .takes_value(true)
+                        .value_name("TIME"),
+                ).arg(
+                    Arg::with_name("follow")
+                        .help("Keep streaming new events")
+                        .short("f")
+                        .long("follow"),
+                ),
+        ).subcommand(
+            SubCommand::with_name("image")
+                .about("Manage the images pulled for modules")
+                .setting(AppSettings::SubcommandRequiredElseHelp)
+                .subcommand(
+                    SubCommand::with_name("prune")
+                        .about("Remove images that no module uses, as configured for image garbage collection"),
+                ).subcommand(
+                    SubCommand::with_name("load")
+                        .about("Load images from a tar archive, for devices that cannot reach a registry")
+                        .arg(
+                            Arg::with_name("FILE")
+                                .help("Tar archive written by iotedge image save or docker save (- for stdin)")
+                                .required(true)
+                                .index(1),
+                        ),
+                ).subcommand(
+                    SubCommand::with_name("save")
+                        .about("Save the image of a module to a tar archive")
+                        .arg(
+                            Arg::with_name("MODULE")
+                                .help("Module whose image to save, or an image name")
+                                .required(true)
+                                .index(1),
+                        ).arg(
+                            Arg::with_name("output")
+                                .help("File to write the archive to (- for stdout)")
+                                .short("o")
+                                .long("output")
+                                .takes_value(true)
+                                .value_name("FILE")
+                                .default_value("-"),
+                        ),
+                ),
+        ).subcommand(
+            SubCommand::with_name("volume")
+                .about("Manage the volumes modules keep their data in")
+                .setting(AppSettings::SubcommandRequiredElseHelp)
+                .subcommand(SubCommand::with_name("ls").about("List volumes"))
+                .subcommand(
+                    SubCommand::with_name("rm")
+                        .about("Remove a volume that no module uses")
+                        .arg(
+                            Arg::with_name("VOLUME")
+                                .help("Volume to remove")
+                                .required(true)
+                                .index(1),
+                        ),
+                ).subcommand(
+                    SubCommand::with_name("backup")
+                        .about("Save the data in a volume to a tar archive")
+                        .arg(
+                            Arg::with_name("VOLUME")
+                                .help("Volume to back up")
+                                .required(true)
+                                .index(1),
+                        ).arg(
+                            Arg::with_name("output")
+                                .help("File to write the archive to (- for stdout)")
+                                .short("o")
+                                .long("output")
+                                .takes_value(true)
+                                .value_name("FILE")
+                                .default_value("-"),
+                        ),
                 ),
         ).subcommand(SubCommand::with_name("version").about("Show the version information"))
         .get_matches();
@@ -101,7 +261,7 @@ fn run() -> Result<(), Error> {
         .value_of("host")
         .map(|h| Url::parse(h).map_err(Error::from))
         .unwrap_or_else(|| Err(Error::from(ErrorKind::NoHost)))?;
-    let runtime = ModuleClient::new(&url, &core.handle())?;
+    let runtime = core.run(ModuleClient::new(&url, &core.handle())?.negotiate())?;
 
     match matches.subcommand() {
         ("list", Some(_args)) => core.run(List::new(runtime, io::stdout()).execute()),
@@ -112,6 +272,20 @@ fn run() -> Result<(), Error> {
                 io::stdout(),
             ).execute(),
         ),
+        ("pause", Some(args)) => core.run(
+            Pause::new(
+                args.value_of("MODULE").unwrap().to_string(),
+                runtime,
+                io::stdout(),
+            ).execute(),
+        ),
+        ("unpause", Some(args)) => core.run(
+            Unpause::new(
+                args.value_of("MODULE").unwrap().to_string(),
+                runtime,
+                io::stdout(),
+            ).execute(),
+        ),
         ("logs", Some(args)) => {
             let id = args.value_of("MODULE").unwrap().to_string();
             let follow = args.is_present("follow");
@@ -119,9 +293,83 @@ fn run() -> Result<(), Error> {
                 .value_of("tail")
                 .and_then(|a| a.parse::<LogTail>().ok())
                 .unwrap_or_default();
-            let options = LogOptions::new().with_follow(follow).with_tail(tail);
+            let since = args
+                .value_of("since")
+                .map(parse_time)
+                .unwrap_or_else(|| Ok(0))?;
+            let until = args
+                .value_of("until")
+                .map(parse_time)
+                .unwrap_or_else(|| Ok(0))?;
+            let timestamps = args.is_present("timestamps");
+            let options = LogOptions::new()
+                .with_follow(follow)
+                .with_tail(tail)
+                .with_since(since)
+                .with_until(until)
+                .with_timestamps(timestamps);
             core.run(Logs::new(id, options, runtime).execute())
         }
+        ("exec", Some(args)) => {
+            let id = args.value_of("MODULE").unwrap().to_string();
+            let cmd = args
+                .values_of("COMMAND")
+                .unwrap()
+                .map(ToString::to_string)
+                .collect();
+            let options =
+                ExecOptions::new(cmd).with_user(args.value_of("user").map(ToString::to_string));
+            core.run(Exec::new(id, options, runtime).execute())
+        }
+        ("cp", Some(args)) => core.run(
+            Cp::new(
+                args.value_of("SRC").unwrap(),
+                args.value_of("DEST").unwrap(),
+                runtime,
+            )?.execute(),
+        ),
+        ("events", Some(args)) => {
+            let since = args
+                .value_of("since")
+                .map(parse_time)
+                .unwrap_or_else(|| Ok(0))?;
+            // Without --follow only the events that already happened are shown.
+            let until = match args.value_of("until") {
+                Some(until) => parse_time(until)?,
+                None if args.is_present("follow") => 0,
+                None => Utc::now().timestamp() as i32,
+            };
+            let options = EventOptions::new().with_since(since).with_until(until);
+            core.run(Events::new(options, runtime, io::stdout()).execute())
+        }
+        ("image", Some(args)) => match args.subcommand() {
+            ("prune", Some(_args)) => core.run(PruneImages::new(runtime, io::stdout()).execute()),
+            ("load", Some(args)) => {
+                core.run(LoadImages::new(args.value_of("FILE").unwrap(), runtime).execute())
+            }
+            ("save", Some(args)) => core.run(
+                SaveImage::new(
+                    args.value_of("MODULE").unwrap().to_string(),
+                    args.value_of("output").unwrap(),
+                    runtime,
+                ).execute(),
+            ),
+            (command, _) => core.run(Unknown::new(format!("image {}", command)).execute()),
+        },
+        ("volume", Some(args)) => match args.subcommand() {
+            ("ls", Some(_args)) => core.run(ListVolumes::new(runtime, io::stdout()).execute()),
+            ("rm", Some(args)) => core.run(
+                RemoveVolume::new(args.value_of("VOLUME").unwrap().to_string(), runtime).execute(),
+            ),
+            ("backup", Some(args)) => core.run(
+                BackupVolume::new(
+                    args.value_of("VOLUME").unwrap().to_string(),
+                    args.value_of("output").unwrap(),
+                    runtime,
+                ).execute(),
+            ),
+            (command, _) => core.run(Unknown::new(format!("volume {}", command)).execute()),
+        },
         ("version", Some(_args)) => core.run(Version::new().execute()),
         (command, _) => core.run(Unknown::new(command.to_string()).execute()),
     }
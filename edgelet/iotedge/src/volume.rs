@@ -0,0 +1,165 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+use std::cell::RefCell;
+use std::io::Write;
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use chrono_humanize::{Accuracy, HumanTime, Tense};
+use edgelet_core::{ModuleRuntime, Volume};
+use futures::{future, Future};
+use tabwriter::TabWriter;
+
+use cp::{local_path, open_output, write_archive, CopyPath};
+use error::Error;
+use Command;
+
+pub struct ListVolumes<M, W> {
+    runtime: M,
+    output: Arc<RefCell<TabWriter<W>>>,
+}
+
+impl<M, W> ListVolumes<M, W>
+where
+    W: Write,
+{
+    pub fn new(runtime: M, output: W) -> Self {
+        let tab = TabWriter::new(output).minwidth(15);
+        ListVolumes {
+            runtime,
+            output: Arc::new(RefCell::new(tab)),
+        }
+    }
+}
+
+impl<M, W> Command for ListVolumes<M, W>
+where
+    M: 'static + ModuleRuntime + Clone,
+    M::Error: Into<Error>,
+    W: 'static + Write,
+{
+    type Future = Box<Future<Item = (), Error = Error>>;
+
+    fn execute(&mut self) -> Self::Future {
+        let write = self.output.clone();
+        let result = self
+            .runtime
+            .list_volumes()
+            .map_err(|e| e.into())
+            .and_then(move |volumes| {
+                let mut w = write.borrow_mut();
+                writeln!(w, "NAME\tMODULE\tDRIVER\tCREATED")?;
+                let now = Utc::now();
+                for volume in &volumes {
+                    writeln!(w, "{}", volume_row(volume, now))?;
+                }
+                w.flush()?;
+                Ok(())
+            });
+        Box::new(result)
+    }
+}
+
+/// Removes a volume. Volumes that belong to a module can only be removed
+/// once the module is gone.
+pub struct RemoveVolume<M> {
+    name: String,
+    runtime: M,
+}
+
+impl<M> RemoveVolume<M> {
+    pub fn new(name: String, runtime: M) -> Self {
+        RemoveVolume { name, runtime }
+    }
+}
+
+impl<M> Command for RemoveVolume<M>
+where
+    M: 'static + ModuleRuntime + Clone,
+    M::Error: Into<Error>,
+{
+    type Future = Box<Future<Item = (), Error = Error>>;
+
+    fn execute(&mut self) -> Self::Future {
+        let result = self.runtime.remove_volume(&self.name).map_err(|e| e.into());
+        Box::new(result)
+    }
+}
+
+/// Writes a tar archive of the data in a volume.
+pub struct BackupVolume<M> {
+    name: String,
+    output: CopyPath,
+    runtime: M,
+}
+
+impl<M> BackupVolume<M> {
+    pub fn new(name: String, output: &str, runtime: M) -> Self {
+        BackupVolume {
+            name,
+            output: local_path(output),
+            runtime,
+        }
+    }
+}
+
+impl<M> Command for BackupVolume<M>
+where
+    M: 'static + ModuleRuntime + Clone,
+    M::Error: Into<Error>,
+{
+    type Future = Box<Future<Item = (), Error = Error>>;
+
+    fn execute(&mut self) -> Self::Future {
+        let output = match open_output(&self.output) {
+            Ok(output) => output,
+            Err(e) => return Box::new(future::err(e)),
+        };
+        let result = self
+            .runtime
+            .backup_volume(&self.name)
+            .map_err(|e| e.into())
+            .and_then(|archive| write_archive(archive, output));
+        Box::new(result)
+    }
+}
+
+fn volume_row(volume: &Volume, now: DateTime<Utc>) -> String {
+    let created = volume
+        .created()
+        .map(|created| HumanTime::from(now - *created).to_text_en(Accuracy::Rough, Tense::Past))
+        .unwrap_or_default();
+    format!(
+        "{}\t{}\t{}\t{}",
+        volume.name(),
+        volume.module().unwrap_or("-"),
+        volume.driver(),
+        created,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use chrono::Duration;
+
+    #[test]
+    fn volume_row_shows_owner_and_age() {
+        let now = Utc::now();
+        let volume = Volume::new("mod1-data".to_string())
+            .with_module(Some("mod1".to_string()))
+            .with_driver("local".to_string())
+            .with_created(Some(now - Duration::hours(2)));
+        assert_eq!(
+            "mod1-data\tmod1\tlocal\t2 hours ago",
+            volume_row(&volume, now)
+        );
+    }
+
+    #[test]
+    fn volume_row_without_owner() {
+        let volume = Volume::new("shared".to_string()).with_driver("local".to_string());
+        assert_eq!("shared\t-\tlocal\t", volume_row(&volume, Utc::now()));
+    }
+}
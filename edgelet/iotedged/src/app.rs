@@ -2,7 +2,7 @@
 
 use clap::{App, Arg, ArgMatches};
 use edgelet_core;
-use edgelet_docker::DockerConfig;
+use serde_json::Value as JsonValue;
 
 use error::Error;
 use logging;
@@ -46,7 +46,7 @@ pub fn log_banner() {
     info!("Version - {}", edgelet_core::version());
 }
 
-pub fn init_common<'a>() -> Result<(Settings<DockerConfig>, ArgMatches<'a>), Error> {
+pub fn init_common<'a>() -> Result<(Settings<JsonValue>, ArgMatches<'a>), Error> {
     let matches = create_app().get_matches();
     let settings = {
         let config_file = matches
@@ -59,14 +59,14 @@ pub fn init_common<'a>() -> Result<(Settings<DockerConfig>, ArgMatches<'a>), Err
                 None
             });
 
-        Settings::<DockerConfig>::new(config_file)?
+        Settings::<JsonValue>::new(config_file)?
     };
 
     Ok((settings, matches))
 }
 
 #[cfg(target_os = "windows")]
-pub fn init() -> Result<Settings<DockerConfig>, Error> {
+pub fn init() -> Result<Settings<JsonValue>, Error> {
     let (settings, matches) = init_common()?;
 
     if matches.is_present("use-event-logger") {
@@ -81,14 +81,14 @@ pub fn init() -> Result<Settings<DockerConfig>, Error> {
 }
 
 #[cfg(not(target_os = "windows"))]
-pub fn init() -> Result<Settings<DockerConfig>, Error> {
+pub fn init() -> Result<Settings<JsonValue>, Error> {
     logging::init();
     log_banner();
     init_common().map(|(settings, _)| settings)
 }
 
 #[cfg(target_os = "windows")]
-pub fn init_win_svc() -> Result<Settings<DockerConfig>, Error> {
+pub fn init_win_svc() -> Result<Settings<JsonValue>, Error> {
     logging::init_win_log();
     log_banner();
     init_common().map(|(settings, _)| settings)
@@ -11,9 +11,13 @@ use std::sync::Mutex;
 use base64::DecodeError;
 use config::ConfigError as SettingsError;
 use edgelet_core::Error as CoreError;
+#[cfg(feature = "cri")]
+use edgelet_cri::Error as CriError;
 use edgelet_docker::Error as DockerError;
 use edgelet_hsm::Error as SoftHsmError;
 use edgelet_http::Error as HttpError;
+use edgelet_memory::Error as MemoryError;
+use edgelet_process::Error as ProcessError;
 use failure::{Backtrace, Context, Fail};
 use hsm::Error as HardHsmError;
 use hyper::error::UriError;
@@ -47,8 +51,23 @@ pub enum ErrorKind {
     Hyper,
     #[fail(display = "A TLS error occurred.")]
     HyperTls,
+    #[fail(display = "The server certificate of the https listeners could not be created.")]
+    ServerCertificate,
     #[fail(display = "A Docker error occurred.")]
     Docker,
+    #[fail(display = "A process runtime error occurred.")]
+    Process,
+    #[cfg(feature = "cri")]
+    #[fail(display = "A CRI runtime error occurred.")]
+    Cri,
+    #[cfg(not(feature = "cri"))]
+    #[fail(
+        display = "The CRI module runtime is not included in this build, \
+                   build iotedged with the \"cri\" feature to use it."
+    )]
+    CriNotIncluded,
+    #[fail(display = "A simulated module runtime error occurred.")]
+    Simulated,
     #[fail(display = "An IoT Hub error occurred.")]
     IotHub,
     #[fail(display = "A parse error occurred.")]
@@ -198,6 +217,31 @@ impl From<DockerError> for Error {
     }
 }
 
+impl From<ProcessError> for Error {
+    fn from(error: ProcessError) -> Error {
+        Error {
+            inner: error.context(ErrorKind::Process),
+        }
+    }
+}
+
+#[cfg(feature = "cri")]
+impl From<CriError> for Error {
+    fn from(error: CriError) -> Error {
+        Error {
+            inner: error.context(ErrorKind::Cri),
+        }
+    }
+}
+
+impl From<MemoryError> for Error {
+    fn from(error: MemoryError) -> Error {
+        Error {
+            inner: error.context(ErrorKind::Simulated),
+        }
+    }
+}
+
 impl From<HyperError> for Error {
     fn from(error: HyperError) -> Error {
         Error {
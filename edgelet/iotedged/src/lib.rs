@@ -8,12 +8,16 @@ extern crate clap;
 extern crate config;
 extern crate docker;
 extern crate edgelet_core;
+#[cfg(feature = "cri")]
+extern crate edgelet_cri;
 extern crate edgelet_docker;
 extern crate edgelet_hsm;
 extern crate edgelet_http;
 extern crate edgelet_http_mgmt;
 extern crate edgelet_http_workload;
 extern crate edgelet_iothub;
+extern crate edgelet_memory;
+extern crate edgelet_process;
 #[cfg(test)]
 extern crate edgelet_test_utils;
 extern crate edgelet_utils;
@@ -63,24 +67,31 @@ use std::fs;
 use std::fs::{DirBuilder, File};
 use std::io::Write;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 use docker::models::HostConfig;
 use edgelet_core::crypto::{
-    CreateCertificate, Decrypt, DerivedKeyStore, Encrypt, GetTrustBundle, KeyIdentity, KeyStore,
-    MasterEncryptionKey, MemoryKey, MemoryKeyStore, Sign, IOTEDGED_CA_ALIAS,
+    Certificate, CreateCertificate, Decrypt, DerivedKeyStore, Encrypt, GetTrustBundle, KeyBytes,
+    KeyIdentity, KeyStore, MasterEncryptionKey, MemoryKey, MemoryKeyStore, PrivateKey, Sign,
+    IOTEDGED_CA_ALIAS,
 };
-use edgelet_core::watchdog::Watchdog;
+use edgelet_core::image_gc::ImageGarbageCollector;
+use edgelet_core::watchdog::{Watchdog, WatchdogStatus};
 use edgelet_core::{CertificateIssuer, CertificateProperties, CertificateType};
-use edgelet_core::{ModuleRuntime, ModuleSpec};
-use edgelet_docker::{DockerConfig, DockerModuleRuntime};
+use edgelet_core::{Error as CoreError, Module, ModuleIndex, ModuleRuntime, ModuleSpec};
+#[cfg(feature = "cri")]
+use edgelet_cri::{CriConfig, CriModuleRuntime, MountConfig};
+use edgelet_docker::{AdmissionPolicy, DockerConfig, DockerModuleRuntime, SignaturePolicy};
 use edgelet_hsm::tpm::{TpmKey, TpmKeyStore};
 use edgelet_hsm::Crypto;
 use edgelet_http::client::Client as HttpClient;
 use edgelet_http::logging::LoggingService;
-use edgelet_http::{ApiVersionService, HyperExt, MaybeProxyClient, API_VERSION};
-use edgelet_http_mgmt::ManagementService;
+use edgelet_http::{ApiVersionService, HyperExt, MaybeProxyClient, TlsSettings, API_VERSION};
+use edgelet_http_mgmt::{IntoResponse, ManagementService};
 use edgelet_http_workload::WorkloadService;
 use edgelet_iothub::{HubIdentityManager, SasTokenSource};
+use edgelet_memory::{MemoryConfig, MemoryRuntime};
+use edgelet_process::{ProcessConfig, ProcessModuleRuntime};
 use futures::future;
 use futures::future::Either;
 use futures::sync::oneshot::{self, Receiver};
@@ -89,16 +100,19 @@ use hsm::tpm::Tpm;
 use hsm::ManageTpmKeys;
 use hyper::client::Service;
 use hyper::server::Http;
-use hyper::{Error as HyperError, Request, Response, Uri};
+use hyper::{Body, Error as HyperError, Request, Response, Uri};
 use iothubservice::DeviceClient;
 use provisioning::provisioning::{
     BackupProvisioning, DpsProvisioning, ManualProvisioning, Provision, ProvisioningResult,
 };
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value as JsonValue;
 use sha2::{Digest, Sha256};
 use tokio_core::reactor::{Core, Handle};
 use url::Url;
 
-use settings::{Dps, Manual, Provisioning, Settings, DEFAULT_CONNECTION_STRING};
+use settings::{Dps, Manual, ModuleRuntimeType, Provisioning, Settings, DEFAULT_CONNECTION_STRING};
 
 pub use self::error::{Error, ErrorKind};
 
@@ -162,6 +176,7 @@ const API_VERSION_KEY: &str = "IOTEDGE_APIVERSION";
 
 const IOTHUB_API_VERSION: &str = "2017-11-08-preview";
 const UNIX_SCHEME: &str = "unix";
+const HTTPS_SCHEME: &str = "https";
 
 /// This is the name of the provisioning backup file
 const EDGE_PROVISIONING_BACKUP_FILENAME: &str = "provisioning_backup.json";
@@ -176,13 +191,17 @@ const EDGE_SETTINGS_SUBDIR: &str = "cache";
 const IOTEDGED_VALIDITY: u64 = 7_776_000; // 90 days
 const IOTEDGED_COMMONNAME: &str = "iotedged workload ca";
 
+/// This is the alias of the server certificate of https listeners, which is
+/// issued by the workload CA
+const IOTEDGED_SERVER_ALIAS: &str = "iotedged-server";
+
 pub struct Main {
-    settings: Settings<DockerConfig>,
+    settings: Settings<JsonValue>,
     reactor: Core,
 }
 
 impl Main {
-    pub fn new(settings: Settings<DockerConfig>) -> Result<Self, Error> {
+    pub fn new(settings: Settings<JsonValue>) -> Result<Self, Error> {
         let reactor = Core::new()?;
         let main = Main { settings, reactor };
         Ok(main)
@@ -198,7 +217,7 @@ impl Main {
     {
         let Main {
             settings,
-            reactor: mut core,
+            reactor: core,
         } = self;
 
         if let Provisioning::Manual(ref manual) = settings.provisioning() {
@@ -207,103 +226,276 @@ impl Main {
             }
         }
 
-        let handle: Handle = core.handle().clone();
-        let hyper_client = MaybeProxyClient::new(&handle, get_proxy_uri()?)?;
+        info!("Using the {:?} module runtime.", settings.module_runtime());
+        match settings.module_runtime() {
+            ModuleRuntimeType::Docker => {
+                run::<DockerConfig, _>(settings.into_runtime()?, core, shutdown_signal)
+            }
+            ModuleRuntimeType::Process => {
+                run::<ProcessConfig, _>(settings.into_runtime()?, core, shutdown_signal)
+            }
+            #[cfg(feature = "cri")]
+            ModuleRuntimeType::Cri => {
+                run::<CriConfig, _>(settings.into_runtime()?, core, shutdown_signal)
+            }
+            #[cfg(not(feature = "cri"))]
+            ModuleRuntimeType::Cri => Err(Error::from(ErrorKind::CriNotIncluded)),
+            ModuleRuntimeType::Simulated => {
+                warn!("Modules are simulated and will not actually run.");
+                run::<MemoryConfig, _>(settings.into_runtime()?, core, shutdown_signal)
+            }
+        }
+    }
+}
 
+/// Ties the module configuration type used in the settings to the runtime
+/// that runs modules with that configuration.
+pub trait RuntimeConfig: 'static + Clone + DeserializeOwned + Serialize {
+    type ModuleRuntime: 'static + ModuleRuntime<Config = Self> + Clone;
+
+    fn init_runtime(
+        settings: &Settings<Self>,
+        core: &mut Core,
+    ) -> Result<Self::ModuleRuntime, Error>;
+
+    /// Adds whatever the runtime needs to the edge agent's configuration and
+    /// environment.
+    fn configure_agent(
+        settings: &Settings<Self>,
+        config: &mut Self,
+        env: &mut HashMap<String, String>,
+    ) -> Result<(), Error>;
+}
+
+impl RuntimeConfig for DockerConfig {
+    type ModuleRuntime = DockerModuleRuntime;
+
+    fn init_runtime(
+        settings: &Settings<Self>,
+        core: &mut Core,
+    ) -> Result<Self::ModuleRuntime, Error> {
         info!(
             "Using runtime network id {}",
             settings.moby_runtime().network()
         );
-        let runtime = DockerModuleRuntime::new(settings.moby_runtime().uri(), &handle)?
-            .with_network_id(settings.moby_runtime().network().to_string());
+        let mut runtime = DockerModuleRuntime::new(settings.moby_runtime().uri(), &core.handle())?
+            .with_network(settings.moby_runtime().network_settings())
+            .with_additional_networks(settings.moby_runtime().additional_networks().to_vec())
+            .with_signature_policy(SignaturePolicy::from_settings(settings.image_signatures())?)
+            .with_admission_policy(
+                AdmissionPolicy::new(settings.admission().clone())
+                    .with_daemon_sockets(daemon_sockets(settings)),
+            );
+        if let Some(timeout) = settings.moby_runtime().dependency_timeout() {
+            runtime = runtime.with_dependency_timeout(timeout);
+        }
 
-        init_docker_runtime(&runtime, &mut core)?;
+        init_module_runtime(&runtime, core)?;
+        Ok(runtime)
+    }
 
-        info!(
-            "Configuring {} as the home directory.",
-            settings.homedir().display()
+    fn configure_agent(
+        settings: &Settings<Self>,
+        config: &mut Self,
+        env: &mut HashMap<String, String>,
+    ) -> Result<(), Error> {
+        env.insert(
+            EDGE_NETWORKID_KEY.to_string(),
+            settings.moby_runtime().network().to_string(),
         );
-        env::set_var(HOMEDIR_KEY, &settings.homedir());
-
-        info!("Configuring certificates...");
-        let certificates = &settings.certificates();
-        match certificates.as_ref() {
-            None => info!(
-                "Transparent gateway certificates not found, operating in quick start mode..."
-            ),
-            Some(&c) => {
-                let path = c.device_ca_cert().as_os_str();
-                info!("Configuring the Device CA certificate using {:?}.", path);
-                env::set_var(DEVICE_CA_CERT_KEY, path);
-
-                let path = c.device_ca_pk().as_os_str();
-                info!("Configuring the Device private key using {:?}.", path);
-                env::set_var(DEVICE_CA_PK_KEY, path);
-
-                let path = c.trusted_ca_certs().as_os_str();
-                info!("Configuring the trusted CA certificates using {:?}.", path);
-                env::set_var(TRUSTED_CA_CERTS_KEY, path);
-            }
-        };
-        info!("Finished configuring certificates.");
-
-        info!("Initializing hsm...");
-        let crypto = Crypto::new()?;
-        info!("Finished initializing hsm.");
-
-        // Detect if the settings were changed and if the device needs to be reconfigured
-        let cache_subdir_path = Path::new(&settings.homedir()).join(EDGE_SETTINGS_SUBDIR);
-        check_settings_state(
-            cache_subdir_path.clone(),
-            EDGE_SETTINGS_STATE_FILENAME,
-            &settings,
-            &runtime,
-            &mut core,
-            &crypto,
-        )?;
-
-        info!("Provisioning edge device...");
-        match settings.provisioning() {
-            Provisioning::Manual(manual) => {
-                let (key_store, provisioning_result, root_key) =
-                    manual_provision(&manual, &mut core)?;
-                info!("Finished provisioning edge device.");
-                start_api(
-                    &settings,
-                    core,
-                    hyper_client,
-                    &runtime,
-                    &key_store,
-                    &provisioning_result,
-                    root_key,
-                    shutdown_signal,
-                    &crypto,
-                )?;
-            }
-            Provisioning::Dps(dps) => {
-                let dps_path = cache_subdir_path.join(EDGE_PROVISIONING_BACKUP_FILENAME);
-                let (key_store, provisioning_result, root_key) =
-                    dps_provision(&dps, hyper_client.clone(), &mut core, dps_path, &runtime)?;
-                info!("Finished provisioning edge device.");
-                start_api(
-                    &settings,
-                    core,
-                    hyper_client,
-                    &runtime,
-                    &key_store,
-                    &provisioning_result,
-                    root_key,
-                    shutdown_signal,
-                    &crypto,
-                )?;
+
+        // volume mount management and workload URIs
+        vol_mount_uri(
+            config,
+            &[
+                settings.connect().management_uri(),
+                settings.connect().workload_uri(),
+            ],
+        )
+    }
+}
+
+impl RuntimeConfig for ProcessConfig {
+    type ModuleRuntime = ProcessModuleRuntime;
+
+    fn init_runtime(
+        settings: &Settings<Self>,
+        core: &mut Core,
+    ) -> Result<Self::ModuleRuntime, Error> {
+        let log_dir = settings.process_runtime().log_dir();
+        info!("Writing module output to {}", log_dir.display());
+        let runtime = ProcessModuleRuntime::new(log_dir)?;
+
+        init_module_runtime(&runtime, core)?;
+        Ok(runtime)
+    }
+
+    // The agent runs on the host, so the management and workload URIs can be
+    // used as they are.
+    fn configure_agent(
+        _settings: &Settings<Self>,
+        _config: &mut Self,
+        _env: &mut HashMap<String, String>,
+    ) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+#[cfg(feature = "cri")]
+impl RuntimeConfig for CriConfig {
+    type ModuleRuntime = CriModuleRuntime;
+
+    fn init_runtime(
+        settings: &Settings<Self>,
+        core: &mut Core,
+    ) -> Result<Self::ModuleRuntime, Error> {
+        let cri_runtime = settings.cri_runtime();
+        info!("Using CRI endpoint {}", cri_runtime.uri());
+        let runtime = CriModuleRuntime::new(cri_runtime.uri(), cri_runtime.log_dir())?;
+
+        init_module_runtime(&runtime, core)?;
+        Ok(runtime)
+    }
+
+    // mount management and workload URIs
+    fn configure_agent(
+        settings: &Settings<Self>,
+        config: &mut Self,
+        _env: &mut HashMap<String, String>,
+    ) -> Result<(), Error> {
+        for uri in &[
+            settings.connect().management_uri(),
+            settings.connect().workload_uri(),
+        ] {
+            if uri.scheme() == UNIX_SCHEME {
+                config.add_mount(MountConfig::new(
+                    uri.path().to_string(),
+                    uri.path().to_string(),
+                ));
             }
-        };
+        }
+        Ok(())
+    }
+}
+
+impl RuntimeConfig for MemoryConfig {
+    type ModuleRuntime = MemoryRuntime;
+
+    fn init_runtime(
+        _settings: &Settings<Self>,
+        core: &mut Core,
+    ) -> Result<Self::ModuleRuntime, Error> {
+        let runtime = MemoryRuntime::new();
 
-        info!("Shutdown complete.");
+        init_module_runtime(&runtime, core)?;
+        Ok(runtime)
+    }
+
+    fn configure_agent(
+        _settings: &Settings<Self>,
+        _config: &mut Self,
+        _env: &mut HashMap<String, String>,
+    ) -> Result<(), Error> {
         Ok(())
     }
 }
 
+fn run<T, F>(settings: Settings<T>, mut core: Core, shutdown_signal: F) -> Result<(), Error>
+where
+    T: RuntimeConfig,
+    F: Future<Item = (), Error = ()> + 'static,
+    <T::ModuleRuntime as ModuleRuntime>::Error: IntoResponse + Into<CoreError> + Into<Error>,
+    <<T::ModuleRuntime as ModuleRuntime>::Module as Module>::Error: Into<CoreError>,
+    <T::ModuleRuntime as ModuleRuntime>::Logs: Into<Body>,
+{
+    let handle: Handle = core.handle().clone();
+    let hyper_client = MaybeProxyClient::new(&handle, get_proxy_uri()?)?;
+
+    let runtime = T::init_runtime(&settings, &mut core)?;
+
+    info!(
+        "Configuring {} as the home directory.",
+        settings.homedir().display()
+    );
+    env::set_var(HOMEDIR_KEY, &settings.homedir());
+
+    info!("Configuring certificates...");
+    let certificates = &settings.certificates();
+    match certificates.as_ref() {
+        None => {
+            info!("Transparent gateway certificates not found, operating in quick start mode...")
+        }
+        Some(&c) => {
+            let path = c.device_ca_cert().as_os_str();
+            info!("Configuring the Device CA certificate using {:?}.", path);
+            env::set_var(DEVICE_CA_CERT_KEY, path);
+
+            let path = c.device_ca_pk().as_os_str();
+            info!("Configuring the Device private key using {:?}.", path);
+            env::set_var(DEVICE_CA_PK_KEY, path);
+
+            let path = c.trusted_ca_certs().as_os_str();
+            info!("Configuring the trusted CA certificates using {:?}.", path);
+            env::set_var(TRUSTED_CA_CERTS_KEY, path);
+        }
+    };
+    info!("Finished configuring certificates.");
+
+    info!("Initializing hsm...");
+    let crypto = Crypto::new()?;
+    info!("Finished initializing hsm.");
+
+    // Detect if the settings were changed and if the device needs to be reconfigured
+    let cache_subdir_path = Path::new(&settings.homedir()).join(EDGE_SETTINGS_SUBDIR);
+    check_settings_state(
+        cache_subdir_path.clone(),
+        EDGE_SETTINGS_STATE_FILENAME,
+        &settings,
+        &runtime,
+        &mut core,
+        &crypto,
+    )?;
+
+    info!("Provisioning edge device...");
+    match settings.provisioning() {
+        Provisioning::Manual(manual) => {
+            let (key_store, provisioning_result, root_key) = manual_provision(&manual, &mut core)?;
+            info!("Finished provisioning edge device.");
+            start_api(
+                &settings,
+                core,
+                hyper_client,
+                &runtime,
+                &key_store,
+                &provisioning_result,
+                root_key,
+                shutdown_signal,
+                &crypto,
+            )?;
+        }
+        Provisioning::Dps(dps) => {
+            let dps_path = cache_subdir_path.join(EDGE_PROVISIONING_BACKUP_FILENAME);
+            let (key_store, provisioning_result, root_key) =
+                dps_provision(&dps, hyper_client.clone(), &mut core, dps_path, &runtime)?;
+            info!("Finished provisioning edge device.");
+            start_api(
+                &settings,
+                core,
+                hyper_client,
+                &runtime,
+                &key_store,
+                &provisioning_result,
+                root_key,
+                shutdown_signal,
+                &crypto,
+            )?;
+        }
+    };
+
+    info!("Shutdown complete.");
+    Ok(())
+}
+
 pub fn get_proxy_uri() -> Result<Option<Uri>, Error> {
     let proxy_uri = env::var("HTTPS_PROXY")
         .or_else(|_| env::var("https_proxy"))
@@ -346,15 +538,46 @@ where
     Ok(())
 }
 
-fn check_settings_state<M, C>(
+fn prepare_tls<T, C>(settings: &Settings<T>, crypto: &C) -> Result<TlsSettings, Error>
+where
+    T: DeserializeOwned + Serialize,
+    C: CreateCertificate,
+{
+    let server_props = CertificateProperties::new(
+        IOTEDGED_VALIDITY,
+        settings.hostname().to_lowercase(),
+        CertificateType::Server,
+        IOTEDGED_SERVER_ALIAS.to_string(),
+    );
+    crypto.destroy_certificate(IOTEDGED_SERVER_ALIAS.to_string())?;
+    let cert = crypto.create_certificate(&server_props)?;
+    let key = match cert.get_private_key()? {
+        Some(PrivateKey::Key(KeyBytes::Pem(key))) => key.as_ref().to_vec(),
+        // keys kept in a hardware module cannot be handed to the TLS stack
+        _ => return Err(Error::from(ErrorKind::ServerCertificate)),
+    };
+
+    let tls = settings.listen().tls();
+    let client_ca = match tls.client_ca() {
+        Some(path) => Some(fs::read(path)?),
+        None => None,
+    };
+
+    Ok(TlsSettings::new(cert.pem()?.as_ref().to_vec(), key)
+        .with_client_ca(client_ca)
+        .with_client_modules(tls.client_modules().clone()))
+}
+
+fn check_settings_state<T, M, C>(
     subdir_path: PathBuf,
     filename: &str,
-    settings: &Settings<DockerConfig>,
+    settings: &Settings<T>,
     runtime: &M,
     core: &mut Core,
     crypto: &C,
 ) -> Result<(), Error>
 where
+    T: DeserializeOwned + Serialize,
     M: ModuleRuntime,
     M::Error: Into<Error>,
     C: MasterEncryptionKey + CreateCertificate,
@@ -382,15 +605,16 @@ where
     Ok(())
 }
 
-fn reconfigure<M, C>(
+fn reconfigure<T, M, C>(
     subdir: PathBuf,
     filename: &str,
-    settings: &Settings<DockerConfig>,
+    settings: &Settings<T>,
     runtime: &M,
     crypto: &C,
     core: &mut Core,
 ) -> Result<(), Error>
 where
+    T: Serialize,
     M: ModuleRuntime,
     M::Error: Into<Error>,
     C: MasterEncryptionKey + CreateCertificate,
@@ -422,11 +646,11 @@ where
 }
 
 #[cfg_attr(feature = "cargo-clippy", allow(too_many_arguments))]
-fn start_api<S, K, F, C>(
-    settings: &Settings<DockerConfig>,
+fn start_api<T, M, S, K, F, C>(
+    settings: &Settings<T>,
     mut core: Core,
     hyper_client: S,
-    runtime: &DockerModuleRuntime,
+    runtime: &M,
     key_store: &DerivedKeyStore<K>,
     provisioning_result: &ProvisioningResult,
     root_key: K,
@@ -434,6 +658,11 @@ fn start_api<S, K, F, C>(
     crypto: &C,
 ) -> Result<(), Error>
 where
+    T: RuntimeConfig,
+    M: 'static + ModuleRuntime<Config = T> + Clone,
+    M::Error: IntoResponse + Into<CoreError>,
+    <M::Module as Module>::Error: Into<CoreError>,
+    M::Logs: Into<Body>,
     F: Future<Item = (), Error = ()> + 'static,
     S: 'static + Service<Error = HyperError, Request = Request, Response = Response>,
     K: 'static + Sign + Clone,
@@ -461,19 +690,58 @@ where
     let (mgmt_tx, mgmt_rx) = oneshot::channel();
     let (work_tx, work_rx) = oneshot::channel();
 
-    let mgmt = start_management(&settings, &core.handle(), &runtime, &id_man, mgmt_rx)?;
+    let watchdog_status = WatchdogStatus::new();
+
+    let listen = settings.listen();
+    let tls = if listen.management_uri().scheme() == HTTPS_SCHEME
+        || listen.workload_uri().scheme() == HTTPS_SCHEME
+    {
+        Some(prepare_tls(settings, crypto)?)
+    } else {
+        None
+    };
+
+    // one index of the modules authorizes the requests of both APIs, and is
+    // dropped whenever the runtime reports a module event
+    let index = Arc::new(ModuleIndex::new(runtime.clone()));
+    core.handle().spawn(index.watch_forever());
+
+    let mgmt = start_management(
+        &settings,
+        &core.handle(),
+        &runtime,
+        &index,
+        &id_man,
+        &watchdog_status,
+        tls.as_ref(),
+        mgmt_rx,
+    )?;
 
     let workload = start_workload(
         &settings,
         key_store,
         &core.handle(),
-        &runtime,
+        &index,
+        tls.as_ref(),
         work_rx,
         crypto,
     )?;
 
+    let (gc_tx, gc_rx) = oneshot::channel();
+    let image_gc = ImageGarbageCollector::new(runtime.clone(), settings.image_gc().clone())
+        .run_until(gc_rx.map_err(|_| ()))
+        .map_err(Error::from);
+
     let (runt_tx, runt_rx) = oneshot::channel();
-    let edge_rt = start_runtime(&runtime, &id_man, &hub_name, &device_id, &settings, runt_rx)?;
+    let edge_rt = start_runtime(
+        &runtime,
+        &id_man,
+        &hub_name,
+        &device_id,
+        &settings,
+        watchdog_status,
+        runt_rx,
+    )?;
 
     // Wait for the watchdog to finish, and then send signal to the workload and management services.
     // This way the edgeAgent can finish shutting down all modules.
@@ -485,20 +753,25 @@ where
 
     let shutdown = shutdown_signal.map(move |_| {
         debug!("shutdown signaled");
-        // Signal the watchdog to shutdown
+        // Signal the watchdog and the image garbage collector to shutdown
         runt_tx.send(()).unwrap_or(());
+        gc_tx.send(()).unwrap_or(());
     });
 
     core.handle().spawn(shutdown);
 
-    core.run(mgmt.join3(workload, edge_rt_with_cleanup))?;
+    core.run(mgmt.join4(workload, edge_rt_with_cleanup, image_gc))?;
 
     Ok(())
 }
 
-fn init_docker_runtime(runtime: &DockerModuleRuntime, core: &mut Core) -> Result<(), Error> {
+fn init_module_runtime<M>(runtime: &M, core: &mut Core) -> Result<(), Error>
+where
+    M: ModuleRuntime,
+    M::Error: Into<Error>,
+{
     info!("Initializing the module runtime...");
-    core.run(runtime.init())?;
+    core.run(runtime.init().map_err(|err| err.into()))?;
     info!("Finished initializing the module runtime.");
     Ok(())
 }
@@ -581,37 +854,35 @@ where
     core.run(provision)
 }
 
-fn start_runtime<K, S>(
-    runtime: &DockerModuleRuntime,
+fn start_runtime<T, M, K, S>(
+    runtime: &M,
     id_man: &HubIdentityManager<DerivedKeyStore<K>, S, K>,
     hostname: &str,
     device_id: &str,
-    settings: &Settings<DockerConfig>,
+    settings: &Settings<T>,
+    watchdog_status: WatchdogStatus,
     shutdown: Receiver<()>,
 ) -> Result<impl Future<Item = (), Error = Error>, Error>
 where
+    T: RuntimeConfig,
+    M: 'static + ModuleRuntime<Config = T> + Clone,
+    M::Error: Into<CoreError>,
+    <M::Module as Module>::Error: Into<CoreError>,
     K: 'static + Sign + Clone,
     S: 'static + Service<Error = HyperError, Request = Request, Response = Response>,
 {
-    let spec = settings.agent().clone();
-    let env = build_env(spec.env(), hostname, device_id, settings);
-    let mut spec = ModuleSpec::<DockerConfig>::new(
-        EDGE_RUNTIME_MODULE_NAME,
-        spec.type_(),
-        spec.config().clone(),
-        env,
-    )?;
-
-    // volume mount management and workload URIs
-    vol_mount_uri(
-        spec.config_mut(),
-        &[
-            settings.connect().management_uri(),
-            settings.connect().workload_uri(),
-        ],
-    )?;
-
-    let watchdog = Watchdog::new(runtime.clone(), id_man.clone());
+    let spec = settings.agent();
+    let mut env = build_env(spec.env(), hostname, device_id, settings);
+    let mut config = spec.config().clone();
+    T::configure_agent(settings, &mut config, &mut env)?;
+    let spec = ModuleSpec::<T>::new(EDGE_RUNTIME_MODULE_NAME, spec.type_(), config, env)?;
+
+    let watchdog = Watchdog::new(
+        runtime.clone(),
+        id_man.clone(),
+        settings.watchdog().clone(),
+        watchdog_status,
+    );
     let runtime_future = watchdog
         .run_until(spec, EDGE_RUNTIME_MODULEID, shutdown.map_err(|_| ()))
         .map_err(Error::from);
@@ -619,6 +890,17 @@ where
     Ok(runtime_future)
 }
 
+// Host paths of the sockets the edge agent bind mounts into modules.
+fn daemon_sockets(settings: &Settings<DockerConfig>) -> Vec<String> {
+    [
+        settings.connect().management_uri(),
+        settings.connect().workload_uri(),
+    ].iter()
+    .filter(|uri| uri.scheme() == UNIX_SCHEME)
+    .map(|uri| uri.path().to_string())
+    .collect()
+}
+
 fn vol_mount_uri(config: &mut DockerConfig, uris: &[&Url]) -> Result<(), Error> {
     let create_options = config.clone_create_options()?;
     let host_config = create_options
@@ -645,11 +927,11 @@ fn vol_mount_uri(config: &mut DockerConfig, uris: &[&Url]) -> Result<(), Error>
 }
 
 // Add the environment variables needed by the EdgeAgent.
-fn build_env(
+fn build_env<T>(
     spec_env: &HashMap<String, String>,
     hostname: &str,
     device_id: &str,
-    settings: &Settings<DockerConfig>,
+    settings: &Settings<T>,
 ) -> HashMap<String, String> {
     let mut env = HashMap::new();
     env.insert(HOSTNAME_KEY.to_string(), hostname.to_string());
@@ -672,10 +954,6 @@ fn build_env(
         EDGE_RUNTIME_MODE_KEY.to_string(),
         EDGE_RUNTIME_MODE.to_string(),
     );
-    env.insert(
-        EDGE_NETWORKID_KEY.to_string(),
-        settings.moby_runtime().network().to_string(),
-    );
     for (key, val) in spec_env.iter() {
         env.insert(key.clone(), val.clone());
     }
@@ -683,14 +961,22 @@ fn build_env(
     env
 }
 
-fn start_management<K, S>(
-    settings: &Settings<DockerConfig>,
+fn start_management<T, M, K, S>(
+    settings: &Settings<T>,
     handle: &Handle,
-    mgmt: &DockerModuleRuntime,
+    mgmt: &M,
+    index: &Arc<ModuleIndex<M>>,
     id_man: &HubIdentityManager<DerivedKeyStore<K>, S, K>,
+    watchdog_status: &WatchdogStatus,
+    tls: Option<&TlsSettings>,
     shutdown: Receiver<()>,
 ) -> Result<impl Future<Item = (), Error = Error>, Error>
 where
+    T: RuntimeConfig,
+    M: 'static + ModuleRuntime<Config = T> + Clone,
+    M::Error: IntoResponse + Into<CoreError>,
+    <M::Module as Module>::Error: Into<CoreError>,
+    M::Logs: Into<Body>,
     K: 'static + Sign + Clone,
     S: 'static + Service<Error = HyperError, Request = Request, Response = Response>,
 {
@@ -701,26 +987,44 @@ where
     let server_handle = handle.clone();
     let service = LoggingService::new(
         label,
-        ApiVersionService::new(ManagementService::new(mgmt, id_man)?),
+        ApiVersionService::new(ManagementService::new(
+            mgmt,
+            index,
+            id_man,
+            watchdog_status,
+            &settings.image_gc().prune_options().with_water_marks(None),
+            settings.authorization().management(),
+            handle,
+        )?),
     );
 
-    let run = Http::new()
-        .bind_handle(url.clone(), server_handle, service)?
+    let server = match tls {
+        Some(tls) if url.scheme() == HTTPS_SCHEME => {
+            Http::new().bind_handle_tls(url.clone(), server_handle, service, tls)?
+        }
+        _ => Http::new().bind_handle(url.clone(), server_handle, service)?,
+    };
+    let run = server
         .run_until(shutdown.map_err(|_| ()))
         .map_err(Error::from);
     info!("Listening on {} with 1 thread for management API.", url);
     Ok(run)
 }
 
-fn start_workload<K, C>(
-    settings: &Settings<DockerConfig>,
+fn start_workload<T, M, K, C>(
+    settings: &Settings<T>,
     key_store: &K,
     handle: &Handle,
-    runtime: &DockerModuleRuntime,
+    index: &Arc<ModuleIndex<M>>,
+    tls: Option<&TlsSettings>,
     shutdown: Receiver<()>,
     crypto: &C,
 ) -> Result<impl Future<Item = (), Error = Error>, Error>
 where
+    M: 'static + ModuleRuntime + Clone,
+    M::Error: Into<CoreError>,
+    <M::Module as Module>::Error: Into<CoreError>,
+    M::Logs: Into<Body>,
     K: 'static + KeyStore + Clone,
     C: 'static
         + CreateCertificate
@@ -737,11 +1041,21 @@ where
     let server_handle = handle.clone();
     let service = LoggingService::new(
         label,
-        ApiVersionService::new(WorkloadService::new(key_store, crypto.clone(), runtime)?),
+        ApiVersionService::new(WorkloadService::new(
+            key_store,
+            crypto.clone(),
+            index,
+            settings.authorization().workload(),
+        )?),
     );
 
-    let run = Http::new()
-        .bind_handle(url.clone(), server_handle, service)?
+    let server = match tls {
+        Some(tls) if url.scheme() == HTTPS_SCHEME => {
+            Http::new().bind_handle_tls(url.clone(), server_handle, service, tls)?
+        }
+        _ => Http::new().bind_handle(url.clone(), server_handle, service)?,
+    };
+    let run = server
         .run_until(shutdown.map_err(|_| ()))
         .map_err(Error::from);
     info!("Listening on {} with 1 thread for workload API.", url);
@@ -813,7 +1127,7 @@ mod tests {
 
     #[test]
     fn default_settings_raise_unconfigured_error() {
-        let settings = Settings::<DockerConfig>::new(None).unwrap();
+        let settings = Settings::<JsonValue>::new(None).unwrap();
         let main = Main::new(settings).unwrap();
         let shutdown_signal = signal::shutdown(&main.handle());
         let result = main.run_until(shutdown_signal);
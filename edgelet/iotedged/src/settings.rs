@@ -1,8 +1,10 @@
 // Copyright (c) Microsoft. All rights reserved.
 
+use std::collections::HashMap;
 use std::fs::{File as FsFile, OpenOptions};
 use std::io::Read;
 use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 use base64;
 use config::{Config, Environment, File, FileFormat};
@@ -10,12 +12,19 @@ use edgelet_utils::log_failure;
 use log::Level;
 use serde::de::DeserializeOwned;
 use serde::Serialize;
-use serde_json;
+use serde_json::{self, Value as JsonValue};
 use sha2::{Digest, Sha256};
 use url::Url;
 use url_serde;
 
-use edgelet_core::ModuleSpec;
+use edgelet_core::admission::AdmissionSettings;
+use edgelet_core::image_gc::ImageGcSettings;
+use edgelet_core::network::{MobyNetwork, Network};
+use edgelet_core::signature::ImageSignatureSettings;
+use edgelet_core::watchdog::WatchdogSettings;
+use edgelet_core::{AuthorizationSettings, ModuleSpec};
+use edgelet_http_mgmt::ROUTES as MANAGEMENT_ROUTES;
+use edgelet_http_workload::ROUTES as WORKLOAD_ROUTES;
 use error::Error;
 
 /// This is the name of the network created by the iotedged
@@ -97,6 +106,8 @@ pub struct Listen {
     workload_uri: Url,
     #[serde(with = "url_serde")]
     management_uri: Url,
+    #[serde(default)]
+    tls: ListenTls,
 }
 
 impl Listen {
@@ -107,13 +118,42 @@ impl Listen {
     pub fn management_uri(&self) -> &Url {
         &self.management_uri
     }
+
+    pub fn tls(&self) -> &ListenTls {
+        &self.tls
+    }
+}
+
+/// Client certificate settings of the listen uris with the `https` scheme.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct ListenTls {
+    client_ca: Option<PathBuf>,
+    #[serde(default)]
+    client_modules: HashMap<String, String>,
+}
+
+impl ListenTls {
+    /// The CA client certificates must be issued by. It is required by the
+    /// `https` scheme.
+    pub fn client_ca(&self) -> Option<&Path> {
+        self.client_ca.as_ref().map(AsRef::as_ref)
+    }
+
+    /// Maps the common names of client certificates to module names.
+    pub fn client_modules(&self) -> &HashMap<String, String> {
+        &self.client_modules
+    }
 }
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct MobyRuntime {
     #[serde(with = "url_serde")]
     uri: Url,
-    network: String,
+    network: MobyNetwork,
+    #[serde(default)]
+    additional_networks: Vec<Network>,
+    #[serde(default)]
+    dependency_timeout_secs: Option<u64>,
 }
 
 impl MobyRuntime {
@@ -122,12 +162,85 @@ impl MobyRuntime {
     }
 
     pub fn network(&self) -> &str {
-        if self.network.is_empty() {
+        let name = match self.network {
+            MobyNetwork::Network(ref network) => network.name(),
+            MobyNetwork::Name(ref name) => name.as_str(),
+        };
+        if name.is_empty() {
             &DEFAULT_NETWORKID
         } else {
-            &self.network
+            name
         }
     }
+
+    /// The description of the network modules are attached to, named by
+    /// `network()`.
+    pub fn network_settings(&self) -> Network {
+        let network = match self.network {
+            MobyNetwork::Network(ref network) => network.clone(),
+            MobyNetwork::Name(_) => Network::default(),
+        };
+        network.with_name(self.network().to_string())
+    }
+
+    pub fn additional_networks(&self) -> &[Network] {
+        &self.additional_networks
+    }
+
+    /// How long to wait for the dependencies of a module to become ready
+    /// when starting it, the runtime default if not given.
+    pub fn dependency_timeout(&self) -> Option<Duration> {
+        self.dependency_timeout_secs.map(Duration::from_secs)
+    }
+}
+
+/// Selects the runtime that runs the edge agent and the modules it deploys.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ModuleRuntimeType {
+    Docker,
+    Process,
+    /// Runs modules through a Container Runtime Interface endpoint, such as
+    /// containerd, without needing dockerd. Only available when built with
+    /// the "cri" feature.
+    Cri,
+    /// Keeps modules in memory without running anything, for trying out the
+    /// daemon on a machine without a container engine.
+    Simulated,
+}
+
+impl Default for ModuleRuntimeType {
+    fn default() -> Self {
+        ModuleRuntimeType::Docker
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ProcessRuntime {
+    log_dir: PathBuf,
+}
+
+impl ProcessRuntime {
+    pub fn log_dir(&self) -> &Path {
+        &self.log_dir
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct CriRuntime {
+    #[serde(with = "url_serde")]
+    uri: Url,
+    log_dir: PathBuf,
+}
+
+impl CriRuntime {
+    pub fn uri(&self) -> &Url {
+        &self.uri
+    }
+
+    pub fn log_dir(&self) -> &Path {
+        &self.log_dir
+    }
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -159,8 +272,22 @@ pub struct Settings<T> {
     connect: Connect,
     listen: Listen,
     homedir: PathBuf,
+    #[serde(default)]
+    module_runtime: ModuleRuntimeType,
     moby_runtime: MobyRuntime,
+    process_runtime: ProcessRuntime,
+    cri_runtime: CriRuntime,
     certificates: Option<Certificates>,
+    #[serde(default)]
+    watchdog: WatchdogSettings,
+    #[serde(default)]
+    image_gc: ImageGcSettings,
+    #[serde(default)]
+    image_signatures: ImageSignatureSettings,
+    #[serde(default)]
+    admission: AdmissionSettings,
+    #[serde(default)]
+    authorization: AuthorizationSettings,
 }
 
 impl<T> Settings<T>
@@ -177,6 +304,14 @@ where
         config.merge(Environment::with_prefix("iotedge"))?;
 
         let settings: Self = config.try_into()?;
+        settings
+            .authorization
+            .management()
+            .check_routes(MANAGEMENT_ROUTES)?;
+        settings
+            .authorization
+            .workload()
+            .check_routes(WORKLOAD_ROUTES)?;
 
         Ok(settings)
     }
@@ -209,14 +344,46 @@ where
         &self.homedir
     }
 
+    pub fn module_runtime(&self) -> ModuleRuntimeType {
+        self.module_runtime
+    }
+
     pub fn moby_runtime(&self) -> &MobyRuntime {
         &self.moby_runtime
     }
 
+    pub fn process_runtime(&self) -> &ProcessRuntime {
+        &self.process_runtime
+    }
+
+    pub fn cri_runtime(&self) -> &CriRuntime {
+        &self.cri_runtime
+    }
+
     pub fn certificates(&self) -> Option<&Certificates> {
         self.certificates.as_ref()
     }
 
+    pub fn watchdog(&self) -> &WatchdogSettings {
+        &self.watchdog
+    }
+
+    pub fn image_gc(&self) -> &ImageGcSettings {
+        &self.image_gc
+    }
+
+    pub fn image_signatures(&self) -> &ImageSignatureSettings {
+        &self.image_signatures
+    }
+
+    pub fn admission(&self) -> &AdmissionSettings {
+        &self.admission
+    }
+
+    pub fn authorization(&self) -> &AuthorizationSettings {
+        &self.authorization
+    }
+
     pub fn diff_with_cached(&self, path: PathBuf) -> Result<bool, Error> {
         OpenOptions::new()
             .read(true)
@@ -243,11 +410,67 @@ where
     }
 }
 
+impl Settings<JsonValue> {
+    /// Reads the agent configuration as the configuration type of the
+    /// selected module runtime.
+    pub fn into_runtime<T>(self) -> Result<Settings<T>, Error>
+    where
+        T: DeserializeOwned,
+    {
+        let Settings {
+            provisioning,
+            agent,
+            hostname,
+            connect,
+            listen,
+            homedir,
+            module_runtime,
+            moby_runtime,
+            process_runtime,
+            cri_runtime,
+            certificates,
+            watchdog,
+            image_gc,
+            image_signatures,
+            admission,
+            authorization,
+        } = self;
+
+        let config = serde_json::from_value(agent.config().clone())?;
+        let agent = ModuleSpec::new(agent.name(), agent.type_(), config, agent.env().clone())?;
+
+        Ok(Settings {
+            provisioning,
+            agent,
+            hostname,
+            connect,
+            listen,
+            homedir,
+            module_runtime,
+            moby_runtime,
+            process_runtime,
+            cri_runtime,
+            certificates,
+            watchdog,
+            image_gc,
+            image_signatures,
+            admission,
+            authorization,
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use config::{Config, File, FileFormat};
+    use edgelet_core::signature::VerificationMode;
+    #[cfg(feature = "cri")]
+    use edgelet_cri::CriConfig;
     use edgelet_docker::DockerConfig;
+    use edgelet_memory::MemoryConfig;
+    use edgelet_process::ProcessConfig;
+    use serde_json;
     use std::io::Write;
     use tempdir::TempDir;
 
@@ -259,6 +482,10 @@ mod tests {
     static BAD_SETTINGS: &str = "test/linux/bad_sample_settings.yaml";
     #[cfg(unix)]
     static GOOD_SETTINGS_TG: &str = "test/linux/sample_settings.tg.yaml";
+    #[cfg(unix)]
+    static GOOD_SETTINGS_PROCESS: &str = "test/linux/sample_settings.process.yaml";
+    #[cfg(all(unix, feature = "cri"))]
+    static GOOD_SETTINGS_CRI: &str = "test/linux/sample_settings.cri.yaml";
 
     #[cfg(windows)]
     static GOOD_SETTINGS: &str = "test/windows/sample_settings.yaml";
@@ -268,6 +495,10 @@ mod tests {
     static BAD_SETTINGS: &str = "test/windows/bad_sample_settings.yaml";
     #[cfg(windows)]
     static GOOD_SETTINGS_TG: &str = "test/windows/sample_settings.tg.yaml";
+    #[cfg(windows)]
+    static GOOD_SETTINGS_PROCESS: &str = "test/windows/sample_settings.process.yaml";
+    #[cfg(all(windows, feature = "cri"))]
+    static GOOD_SETTINGS_CRI: &str = "test/windows/sample_settings.cri.yaml";
 
     fn unwrap_manual_provisioning(p: &Provisioning) -> String {
         match p {
@@ -378,18 +609,144 @@ mod tests {
         );
     }
 
+    #[test]
+    fn module_runtime_defaults_to_docker() {
+        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS)).unwrap();
+        assert_eq!(ModuleRuntimeType::Docker, settings.module_runtime());
+    }
+
+    #[test]
+    fn process_file_selects_process_runtime() {
+        let settings = Settings::<JsonValue>::new(Some(GOOD_SETTINGS_PROCESS)).unwrap();
+        assert_eq!(ModuleRuntimeType::Process, settings.module_runtime());
+
+        let settings = settings.into_runtime::<ProcessConfig>().unwrap();
+        assert_eq!("process", settings.agent().type_());
+        assert_eq!(&["--verbose".to_string()], settings.agent().config().args());
+        #[cfg(unix)]
+        assert_eq!(
+            Path::new("/tmp/modules"),
+            settings.process_runtime().log_dir()
+        );
+    }
+
+    #[cfg(feature = "cri")]
+    #[test]
+    fn cri_file_selects_cri_runtime() {
+        let settings = Settings::<JsonValue>::new(Some(GOOD_SETTINGS_CRI)).unwrap();
+        assert_eq!(ModuleRuntimeType::Cri, settings.module_runtime());
+
+        let settings = settings.into_runtime::<CriConfig>().unwrap();
+        assert_eq!("cri", settings.agent().type_());
+        assert_eq!(
+            "microsoft/azureiotedge-agent:1.0",
+            settings.agent().config().image()
+        );
+        #[cfg(unix)]
+        assert_eq!(
+            "unix:///run/containerd/containerd.sock",
+            settings.cri_runtime().uri().as_str()
+        );
+    }
+
+    #[test]
+    fn image_gc_is_disabled_by_default() {
+        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS)).unwrap();
+        assert_eq!(&ImageGcSettings::default(), settings.image_gc());
+        assert!(!settings.image_gc().enabled());
+    }
+
+    #[test]
+    fn image_signatures_are_off_by_default() {
+        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS)).unwrap();
+        assert_eq!(
+            &ImageSignatureSettings::default(),
+            settings.image_signatures()
+        );
+        assert_eq!(VerificationMode::Off, settings.image_signatures().mode());
+    }
+
+    #[test]
+    fn admission_allows_everything_by_default() {
+        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS)).unwrap();
+        assert_eq!(&AdmissionSettings::default(), settings.admission());
+        assert!(settings.admission().allow_privileged());
+    }
+
+    #[test]
+    fn into_runtime_keeps_docker_agent() {
+        let settings = Settings::<JsonValue>::new(Some(GOOD_SETTINGS))
+            .unwrap()
+            .into_runtime::<DockerConfig>()
+            .unwrap();
+        assert_eq!("edgeAgent", settings.agent().name());
+        assert_eq!(
+            "microsoft/azureiotedge-agent:1.0",
+            settings.agent().config().image()
+        );
+    }
+
+    #[test]
+    fn simulated_runtime_uses_docker_agent() {
+        assert_eq!(
+            ModuleRuntimeType::Simulated,
+            serde_json::from_str::<ModuleRuntimeType>("\"simulated\"").unwrap()
+        );
+
+        let settings = Settings::<JsonValue>::new(Some(GOOD_SETTINGS))
+            .unwrap()
+            .into_runtime::<MemoryConfig>()
+            .unwrap();
+        assert_eq!(
+            "microsoft/azureiotedge-agent:1.0",
+            settings.agent().config().image()
+        );
+    }
+
+    #[test]
+    fn into_runtime_fails_for_mismatched_agent_config() {
+        let settings = Settings::<JsonValue>::new(Some(GOOD_SETTINGS)).unwrap();
+        assert!(settings.into_runtime::<ProcessConfig>().is_err());
+    }
+
     #[test]
     fn network_default() {
         let moby1 = MobyRuntime {
             uri: Url::parse("http://test").unwrap(),
-            network: "".to_string(),
+            network: MobyNetwork::Name("".to_string()),
+            additional_networks: Vec::new(),
+            dependency_timeout_secs: None,
         };
         assert_eq!(DEFAULT_NETWORKID, moby1.network());
 
         let moby2 = MobyRuntime {
             uri: Url::parse("http://test").unwrap(),
-            network: "some-network".to_string(),
+            network: MobyNetwork::Name("some-network".to_string()),
+            additional_networks: Vec::new(),
+            dependency_timeout_secs: None,
         };
         assert_eq!("some-network", moby2.network());
+
+        let moby3 = MobyRuntime {
+            uri: Url::parse("http://test").unwrap(),
+            network: MobyNetwork::Network(Network::new("".to_string()).with_mtu(Some(1400))),
+            additional_networks: Vec::new(),
+            dependency_timeout_secs: None,
+        };
+        assert_eq!(DEFAULT_NETWORKID, moby3.network());
+        assert_eq!(DEFAULT_NETWORKID, moby3.network_settings().name());
+        assert_eq!(Some(1400), moby3.network_settings().mtu());
+    }
+
+    #[test]
+    fn dependency_timeout_is_optional() {
+        let moby: MobyRuntime =
+            serde_json::from_str(r#"{"uri": "http://test", "network": "azure-iot-edge"}"#).unwrap();
+        assert_eq!(None, moby.dependency_timeout());
+
+        let moby: MobyRuntime = serde_json::from_str(
+            r#"{"uri": "http://test", "network": "azure-iot-edge", "dependency_timeout_secs": 0}"#,
+        ).unwrap();
+        assert_eq!(Some(Duration::from_secs(0)), moby.dependency_timeout());
     }
 }
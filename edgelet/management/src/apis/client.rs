@@ -6,8 +6,10 @@ use hyper;
 pub struct APIClient<C: hyper::client::Connect> {
     configuration: Rc<Configuration<C>>,
     identity_api: Box<::apis::IdentityApi>,
+    image_api: Box<::apis::ImageApi>,
     module_api: Box<::apis::ModuleApi>,
     system_information_api: Box<::apis::SystemInformationApi>,
+    volume_api: Box<::apis::VolumeApi>,
 }
 
 impl<C: hyper::client::Connect> APIClient<C> {
@@ -17,8 +19,10 @@ impl<C: hyper::client::Connect> APIClient<C> {
         APIClient {
             configuration: rc.clone(),
             identity_api: Box::new(::apis::IdentityApiClient::new(rc.clone())),
+            image_api: Box::new(::apis::ImageApiClient::new(rc.clone())),
             module_api: Box::new(::apis::ModuleApiClient::new(rc.clone())),
             system_information_api: Box::new(::apis::SystemInformationApiClient::new(rc.clone())),
+            volume_api: Box::new(::apis::VolumeApiClient::new(rc.clone())),
         }
     }
 
@@ -26,6 +30,10 @@ impl<C: hyper::client::Connect> APIClient<C> {
         self.identity_api.as_ref()
     }
 
+    pub fn image_api(&self) -> &::apis::ImageApi {
+        self.image_api.as_ref()
+    }
+
     pub fn module_api(&self) -> &::apis::ModuleApi {
         self.module_api.as_ref()
     }
@@ -33,4 +41,8 @@ impl<C: hyper::client::Connect> APIClient<C> {
     pub fn system_information_api(&self) -> &::apis::SystemInformationApi {
         self.system_information_api.as_ref()
     }
+
+    pub fn volume_api(&self) -> &::apis::VolumeApi {
+        self.volume_api.as_ref()
+    }
 }
@@ -0,0 +1,198 @@
+/*
+ * IoT Edge Management API
+ *
+ * No description provided (generated by Swagger Codegen https://github.com/swagger-api/swagger-codegen)
+ *
+ * OpenAPI spec version: 2018-06-28
+ *
+ * Generated by: https://github.com/swagger-api/swagger-codegen.git
+ */
+
+use std::borrow::Borrow;
+use std::borrow::Cow;
+use std::rc::Rc;
+
+use futures;
+use futures::{Future, Stream};
+use hyper;
+use serde_json;
+
+use hyper::header::{Authorization, UserAgent};
+
+use super::{configuration, Error};
+
+pub struct ImageApiClient<C: hyper::client::Connect> {
+    configuration: Rc<configuration::Configuration<C>>,
+}
+
+impl<C: hyper::client::Connect> ImageApiClient<C> {
+    pub fn new(configuration: Rc<configuration::Configuration<C>>) -> ImageApiClient<C> {
+        ImageApiClient { configuration }
+    }
+}
+
+pub trait ImageApi {
+    fn load_images(
+        &self,
+        api_version: &str,
+        archive: hyper::Body,
+    ) -> Box<Future<Item = (), Error = Error<serde_json::Value>>>;
+    fn prune_images(
+        &self,
+        api_version: &str,
+    ) -> Box<Future<Item = ::models::PruneResult, Error = Error<serde_json::Value>>>;
+    fn save_image(
+        &self,
+        api_version: &str,
+        name: &str,
+    ) -> Box<Future<Item = hyper::Body, Error = Error<serde_json::Value>>>;
+}
+
+impl<C: hyper::client::Connect> ImageApi for ImageApiClient<C> {
+    fn load_images(
+        &self,
+        api_version: &str,
+        archive: hyper::Body,
+    ) -> Box<Future<Item = (), Error = Error<serde_json::Value>>> {
+        let configuration: &configuration::Configuration<C> = self.configuration.borrow();
+
+        let method = hyper::Method::Post;
+
+        let query = ::url::form_urlencoded::Serializer::new(String::new())
+            .append_pair("api-version", &api_version.to_string())
+            .finish();
+        let uri_str = format!("/images/load?{}", query);
+
+        let uri = (configuration.uri_composer)(&configuration.base_path, &uri_str);
+        // TODO(farcaller): handle error
+        // if let Err(e) = uri {
+        //     return Box::new(futures::future::err(e));
+        // }
+        let mut req = hyper::Request::new(method, uri.unwrap());
+
+        if let Some(ref user_agent) = configuration.user_agent {
+            req.headers_mut()
+                .set(UserAgent::new(Cow::Owned(user_agent.clone())));
+        }
+
+        req.headers_mut()
+            .set(hyper::header::ContentType("application/x-tar".parse().unwrap()));
+        req.set_body(archive);
+
+        // send request
+        Box::new(
+            configuration
+                .client
+                .request(req)
+                .map_err(Error::from)
+                .and_then(|resp| {
+                    let status = resp.status();
+                    resp.body()
+                        .concat2()
+                        .and_then(move |body| Ok((status, body)))
+                        .map_err(Error::from)
+                }).and_then(|(status, body)| {
+                    if status.is_success() {
+                        Ok(body)
+                    } else {
+                        Err(Error::from((status, &*body)))
+                    }
+                }).and_then(|_| futures::future::ok(())),
+        )
+    }
+
+    fn prune_images(
+        &self,
+        api_version: &str,
+    ) -> Box<Future<Item = ::models::PruneResult, Error = Error<serde_json::Value>>> {
+        let configuration: &configuration::Configuration<C> = self.configuration.borrow();
+
+        let method = hyper::Method::Post;
+
+        let query = ::url::form_urlencoded::Serializer::new(String::new())
+            .append_pair("api-version", &api_version.to_string())
+            .finish();
+        let uri_str = format!("/images/prune?{}", query);
+
+        let uri = (configuration.uri_composer)(&configuration.base_path, &uri_str);
+        // TODO(farcaller): handle error
+        // if let Err(e) = uri {
+        //     return Box::new(futures::future::err(e));
+        // }
+        let mut req = hyper::Request::new(method, uri.unwrap());
+
+        if let Some(ref user_agent) = configuration.user_agent {
+            req.headers_mut()
+                .set(UserAgent::new(Cow::Owned(user_agent.clone())));
+        }
+
+        // send request
+        Box::new(
+            configuration
+                .client
+                .request(req)
+                .map_err(Error::from)
+                .and_then(|resp| {
+                    let status = resp.status();
+                    resp.body()
+                        .concat2()
+                        .and_then(move |body| Ok((status, body)))
+                        .map_err(Error::from)
+                }).and_then(|(status, body)| {
+                    if status.is_success() {
+                        Ok(body)
+                    } else {
+                        Err(Error::from((status, &*body)))
+                    }
+                }).and_then(|body| {
+                    let parsed: Result<::models::PruneResult, _> = serde_json::from_slice(&body);
+                    parsed.map_err(Error::from)
+                }),
+        )
+    }
+
+    fn save_image(
+        &self,
+        api_version: &str,
+        name: &str,
+    ) -> Box<Future<Item = hyper::Body, Error = Error<serde_json::Value>>> {
+        let configuration: &configuration::Configuration<C> = self.configuration.borrow();
+
+        let method = hyper::Method::Get;
+
+        let query = ::url::form_urlencoded::Serializer::new(String::new())
+            .append_pair("api-version", &api_version.to_string())
+            .append_pair("name", &name.to_string())
+            .finish();
+        let uri_str = format!("/images/save?{}", query);
+
+        let uri = (configuration.uri_composer)(&configuration.base_path, &uri_str);
+        // TODO(farcaller): handle error
+        // if let Err(e) = uri {
+        //     return Box::new(futures::future::err(e));
+        // }
+        let mut req = hyper::Request::new(method, uri.unwrap());
+
+        if let Some(ref user_agent) = configuration.user_agent {
+            req.headers_mut()
+                .set(UserAgent::new(Cow::Owned(user_agent.clone())));
+        }
+
+        // send request
+        Box::new(
+            configuration
+                .client
+                .request(req)
+                .map_err(Error::from)
+                .and_then(|resp| {
+                    let status = resp.status();
+                    if status.is_success() {
+                        Ok(resp.body())
+                    } else {
+                        let b: &[u8] = &[];
+                        Err(Error::from((status, b)))
+                    }
+                }),
+        )
+    }
+}
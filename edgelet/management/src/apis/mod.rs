@@ -52,10 +52,14 @@ use super::models::*;
 
 mod identity_api;
 pub use self::identity_api::{IdentityApi, IdentityApiClient};
+mod image_api;
+pub use self::image_api::{ImageApi, ImageApiClient};
 mod module_api;
 pub use self::module_api::{ModuleApi, ModuleApiClient};
 mod system_information_api;
 pub use self::system_information_api::{SystemInformationApi, SystemInformationApiClient};
+mod volume_api;
+pub use self::volume_api::{VolumeApi, VolumeApiClient};
 
 pub mod client;
 pub mod configuration;
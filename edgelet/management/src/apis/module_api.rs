@@ -41,23 +41,63 @@ pub trait ModuleApi {
         &self,
         api_version: &str,
         name: &str,
+        remove_volumes: bool,
     ) -> Box<Future<Item = (), Error = Error<serde_json::Value>>>;
+    fn exec_module(
+        &self,
+        api_version: &str,
+        name: &str,
+        exec: ::models::ExecSpec,
+    ) -> Box<Future<Item = hyper::Body, Error = Error<serde_json::Value>>>;
     fn get_module(
         &self,
         api_version: &str,
         name: &str,
     ) -> Box<Future<Item = ::models::ModuleDetails, Error = Error<serde_json::Value>>>;
+    fn get_module_archive(
+        &self,
+        api_version: &str,
+        name: &str,
+        path: &str,
+    ) -> Box<Future<Item = hyper::Body, Error = Error<serde_json::Value>>>;
     fn list_modules(
         &self,
         api_version: &str,
     ) -> Box<Future<Item = ::models::ModuleList, Error = Error<serde_json::Value>>>;
+    fn module_events(
+        &self,
+        api_version: &str,
+        since: i32,
+        until: i32,
+    ) -> Box<Future<Item = hyper::Body, Error = Error<serde_json::Value>>>;
     fn module_logs(
         &self,
         api_version: &str,
         name: &str,
         follow: bool,
         tail: &str,
+        since: i32,
+        until: i32,
+        timestamps: bool,
     ) -> Box<Future<Item = hyper::Body, Error = Error<serde_json::Value>>>;
+    fn module_stats(
+        &self,
+        api_version: &str,
+        name: &str,
+        stream: bool,
+    ) -> Box<Future<Item = hyper::Body, Error = Error<serde_json::Value>>>;
+    fn pause_module(
+        &self,
+        api_version: &str,
+        name: &str,
+    ) -> Box<Future<Item = (), Error = Error<serde_json::Value>>>;
+    fn put_module_archive(
+        &self,
+        api_version: &str,
+        name: &str,
+        path: &str,
+        archive: hyper::Body,
+    ) -> Box<Future<Item = (), Error = Error<serde_json::Value>>>;
     fn restart_module(
         &self,
         api_version: &str,
@@ -73,6 +113,11 @@ pub trait ModuleApi {
         api_version: &str,
         name: &str,
     ) -> Box<Future<Item = (), Error = Error<serde_json::Value>>>;
+    fn unpause_module(
+        &self,
+        api_version: &str,
+        name: &str,
+    ) -> Box<Future<Item = (), Error = Error<serde_json::Value>>>;
     fn update_module(
         &self,
         api_version: &str,
@@ -143,6 +188,7 @@ impl<C: hyper::client::Connect> ModuleApi for ModuleApiClient<C> {
         &self,
         api_version: &str,
         name: &str,
+        remove_volumes: bool,
     ) -> Box<Future<Item = (), Error = Error<serde_json::Value>>> {
         let configuration: &configuration::Configuration<C> = self.configuration.borrow();
 
@@ -150,6 +196,7 @@ impl<C: hyper::client::Connect> ModuleApi for ModuleApiClient<C> {
 
         let query = ::url::form_urlencoded::Serializer::new(String::new())
             .append_pair("api-version", &api_version.to_string())
+            .append_pair("removeVolumes", &remove_volumes.to_string())
             .finish();
         let uri_str = format!("/modules/{name}?{}", query, name = name);
 
@@ -187,6 +234,57 @@ impl<C: hyper::client::Connect> ModuleApi for ModuleApiClient<C> {
         )
     }
 
+    fn exec_module(
+        &self,
+        api_version: &str,
+        name: &str,
+        exec: ::models::ExecSpec,
+    ) -> Box<Future<Item = hyper::Body, Error = Error<serde_json::Value>>> {
+        let configuration: &configuration::Configuration<C> = self.configuration.borrow();
+
+        let method = hyper::Method::Post;
+
+        let query = ::url::form_urlencoded::Serializer::new(String::new())
+            .append_pair("api-version", &api_version.to_string())
+            .finish();
+        let uri_str = format!("/modules/{name}/exec?{}", query, name = name);
+
+        let uri = (configuration.uri_composer)(&configuration.base_path, &uri_str);
+        // TODO(farcaller): handle error
+        // if let Err(e) = uri {
+        //     return Box::new(futures::future::err(e));
+        // }
+        let mut req = hyper::Request::new(method, uri.unwrap());
+
+        if let Some(ref user_agent) = configuration.user_agent {
+            req.headers_mut()
+                .set(UserAgent::new(Cow::Owned(user_agent.clone())));
+        }
+
+        let serialized = serde_json::to_string(&exec).unwrap();
+        req.headers_mut().set(hyper::header::ContentType::json());
+        req.headers_mut()
+            .set(hyper::header::ContentLength(serialized.len() as u64));
+        req.set_body(serialized);
+
+        // send request
+        Box::new(
+            configuration
+                .client
+                .request(req)
+                .map_err(Error::from)
+                .and_then(|resp| {
+                    let status = resp.status();
+                    if status.is_success() {
+                        Ok(resp.body())
+                    } else {
+                        let b: &[u8] = &[];
+                        Err(Error::from((status, b)))
+                    }
+                }),
+        )
+    }
+
     fn get_module(
         &self,
         api_version: &str,
@@ -238,6 +336,52 @@ impl<C: hyper::client::Connect> ModuleApi for ModuleApiClient<C> {
         )
     }
 
+    fn get_module_archive(
+        &self,
+        api_version: &str,
+        name: &str,
+        path: &str,
+    ) -> Box<Future<Item = hyper::Body, Error = Error<serde_json::Value>>> {
+        let configuration: &configuration::Configuration<C> = self.configuration.borrow();
+
+        let method = hyper::Method::Get;
+
+        let query = ::url::form_urlencoded::Serializer::new(String::new())
+            .append_pair("api-version", &api_version.to_string())
+            .append_pair("path", &path.to_string())
+            .finish();
+        let uri_str = format!("/modules/{name}/archive?{}", query, name = name);
+
+        let uri = (configuration.uri_composer)(&configuration.base_path, &uri_str);
+        // TODO(farcaller): handle error
+        // if let Err(e) = uri {
+        //     return Box::new(futures::future::err(e));
+        // }
+        let mut req = hyper::Request::new(method, uri.unwrap());
+
+        if let Some(ref user_agent) = configuration.user_agent {
+            req.headers_mut()
+                .set(UserAgent::new(Cow::Owned(user_agent.clone())));
+        }
+
+        // send request
+        Box::new(
+            configuration
+                .client
+                .request(req)
+                .map_err(Error::from)
+                .and_then(|resp| {
+                    let status = resp.status();
+                    if status.is_success() {
+                        Ok(resp.body())
+                    } else {
+                        let b: &[u8] = &[];
+                        Err(Error::from((status, b)))
+                    }
+                }),
+        )
+    }
+
     fn list_modules(
         &self,
         api_version: &str,
@@ -288,12 +432,62 @@ impl<C: hyper::client::Connect> ModuleApi for ModuleApiClient<C> {
         )
     }
 
+    fn module_events(
+        &self,
+        api_version: &str,
+        since: i32,
+        until: i32,
+    ) -> Box<Future<Item = hyper::Body, Error = Error<serde_json::Value>>> {
+        let configuration: &configuration::Configuration<C> = self.configuration.borrow();
+
+        let method = hyper::Method::Get;
+
+        let query = ::url::form_urlencoded::Serializer::new(String::new())
+            .append_pair("api-version", &api_version.to_string())
+            .append_pair("since", &since.to_string())
+            .append_pair("until", &until.to_string())
+            .finish();
+        let uri_str = format!("/events?{}", query);
+
+        let uri = (configuration.uri_composer)(&configuration.base_path, &uri_str);
+        // TODO(farcaller): handle error
+        // if let Err(e) = uri {
+        //     return Box::new(futures::future::err(e));
+        // }
+        let mut req = hyper::Request::new(method, uri.unwrap());
+
+        if let Some(ref user_agent) = configuration.user_agent {
+            req.headers_mut()
+                .set(UserAgent::new(Cow::Owned(user_agent.clone())));
+        }
+
+        // send request
+        Box::new(
+            configuration
+                .client
+                .request(req)
+                .map_err(Error::from)
+                .and_then(|resp| {
+                    let status = resp.status();
+                    if status.is_success() {
+                        Ok(resp.body())
+                    } else {
+                        let b: &[u8] = &[];
+                        Err(Error::from((status, b)))
+                    }
+                }),
+        )
+    }
+
     fn module_logs(
         &self,
         api_version: &str,
         name: &str,
         follow: bool,
         tail: &str,
+        since: i32,
+        until: i32,
+        timestamps: bool,
     ) -> Box<Future<Item = hyper::Body, Error = Error<serde_json::Value>>> {
         let configuration: &configuration::Configuration<C> = self.configuration.borrow();
 
@@ -303,6 +497,9 @@ impl<C: hyper::client::Connect> ModuleApi for ModuleApiClient<C> {
             .append_pair("api-version", &api_version.to_string())
             .append_pair("follow", &follow.to_string())
             .append_pair("tail", &tail.to_string())
+            .append_pair("since", &since.to_string())
+            .append_pair("until", &until.to_string())
+            .append_pair("timestamps", &timestamps.to_string())
             .finish();
         let uri_str = format!("/modules/{name}/logs?{}", query, name = name);
 
@@ -336,6 +533,155 @@ impl<C: hyper::client::Connect> ModuleApi for ModuleApiClient<C> {
         )
     }
 
+    fn module_stats(
+        &self,
+        api_version: &str,
+        name: &str,
+        stream: bool,
+    ) -> Box<Future<Item = hyper::Body, Error = Error<serde_json::Value>>> {
+        let configuration: &configuration::Configuration<C> = self.configuration.borrow();
+
+        let method = hyper::Method::Get;
+
+        let query = ::url::form_urlencoded::Serializer::new(String::new())
+            .append_pair("api-version", &api_version.to_string())
+            .append_pair("stream", &stream.to_string())
+            .finish();
+        let uri_str = format!("/modules/{name}/stats?{}", query, name = name);
+
+        let uri = (configuration.uri_composer)(&configuration.base_path, &uri_str);
+        // TODO(farcaller): handle error
+        // if let Err(e) = uri {
+        //     return Box::new(futures::future::err(e));
+        // }
+        let mut req = hyper::Request::new(method, uri.unwrap());
+
+        if let Some(ref user_agent) = configuration.user_agent {
+            req.headers_mut()
+                .set(UserAgent::new(Cow::Owned(user_agent.clone())));
+        }
+
+        // send request
+        Box::new(
+            configuration
+                .client
+                .request(req)
+                .map_err(Error::from)
+                .and_then(|resp| {
+                    let status = resp.status();
+                    if status.is_success() {
+                        Ok(resp.body())
+                    } else {
+                        let b: &[u8] = &[];
+                        Err(Error::from((status, b)))
+                    }
+                }),
+        )
+    }
+
+    fn pause_module(
+        &self,
+        api_version: &str,
+        name: &str,
+    ) -> Box<Future<Item = (), Error = Error<serde_json::Value>>> {
+        let configuration: &configuration::Configuration<C> = self.configuration.borrow();
+
+        let method = hyper::Method::Post;
+
+        let query = ::url::form_urlencoded::Serializer::new(String::new())
+            .append_pair("api-version", &api_version.to_string())
+            .finish();
+        let uri_str = format!("/modules/{name}/pause?{}", query, name = name);
+
+        let uri = (configuration.uri_composer)(&configuration.base_path, &uri_str);
+        // TODO(farcaller): handle error
+        // if let Err(e) = uri {
+        //     return Box::new(futures::future::err(e));
+        // }
+        let mut req = hyper::Request::new(method, uri.unwrap());
+
+        if let Some(ref user_agent) = configuration.user_agent {
+            req.headers_mut()
+                .set(UserAgent::new(Cow::Owned(user_agent.clone())));
+        }
+
+        // send request
+        Box::new(
+            configuration
+                .client
+                .request(req)
+                .map_err(Error::from)
+                .and_then(|resp| {
+                    let status = resp.status();
+                    resp.body()
+                        .concat2()
+                        .and_then(move |body| Ok((status, body)))
+                        .map_err(Error::from)
+                }).and_then(|(status, body)| {
+                    if status.is_success() {
+                        Ok(body)
+                    } else {
+                        Err(Error::from((status, &*body)))
+                    }
+                }).and_then(|_| futures::future::ok(())),
+        )
+    }
+
+    fn put_module_archive(
+        &self,
+        api_version: &str,
+        name: &str,
+        path: &str,
+        archive: hyper::Body,
+    ) -> Box<Future<Item = (), Error = Error<serde_json::Value>>> {
+        let configuration: &configuration::Configuration<C> = self.configuration.borrow();
+
+        let method = hyper::Method::Put;
+
+        let query = ::url::form_urlencoded::Serializer::new(String::new())
+            .append_pair("api-version", &api_version.to_string())
+            .append_pair("path", &path.to_string())
+            .finish();
+        let uri_str = format!("/modules/{name}/archive?{}", query, name = name);
+
+        let uri = (configuration.uri_composer)(&configuration.base_path, &uri_str);
+        // TODO(farcaller): handle error
+        // if let Err(e) = uri {
+        //     return Box::new(futures::future::err(e));
+        // }
+        let mut req = hyper::Request::new(method, uri.unwrap());
+
+        if let Some(ref user_agent) = configuration.user_agent {
+            req.headers_mut()
+                .set(UserAgent::new(Cow::Owned(user_agent.clone())));
+        }
+
+        req.headers_mut()
+            .set(hyper::header::ContentType("application/x-tar".parse().unwrap()));
+        req.set_body(archive);
+
+        // send request
+        Box::new(
+            configuration
+                .client
+                .request(req)
+                .map_err(Error::from)
+                .and_then(|resp| {
+                    let status = resp.status();
+                    resp.body()
+                        .concat2()
+                        .and_then(move |body| Ok((status, body)))
+                        .map_err(Error::from)
+                }).and_then(|(status, body)| {
+                    if status.is_success() {
+                        Ok(body)
+                    } else {
+                        Err(Error::from((status, &*body)))
+                    }
+                }).and_then(|_| futures::future::ok(())),
+        )
+    }
+
     fn restart_module(
         &self,
         api_version: &str,
@@ -480,6 +826,54 @@ impl<C: hyper::client::Connect> ModuleApi for ModuleApiClient<C> {
         )
     }
 
+    fn unpause_module(
+        &self,
+        api_version: &str,
+        name: &str,
+    ) -> Box<Future<Item = (), Error = Error<serde_json::Value>>> {
+        let configuration: &configuration::Configuration<C> = self.configuration.borrow();
+
+        let method = hyper::Method::Post;
+
+        let query = ::url::form_urlencoded::Serializer::new(String::new())
+            .append_pair("api-version", &api_version.to_string())
+            .finish();
+        let uri_str = format!("/modules/{name}/unpause?{}", query, name = name);
+
+        let uri = (configuration.uri_composer)(&configuration.base_path, &uri_str);
+        // TODO(farcaller): handle error
+        // if let Err(e) = uri {
+        //     return Box::new(futures::future::err(e));
+        // }
+        let mut req = hyper::Request::new(method, uri.unwrap());
+
+        if let Some(ref user_agent) = configuration.user_agent {
+            req.headers_mut()
+                .set(UserAgent::new(Cow::Owned(user_agent.clone())));
+        }
+
+        // send request
+        Box::new(
+            configuration
+                .client
+                .request(req)
+                .map_err(Error::from)
+                .and_then(|resp| {
+                    let status = resp.status();
+                    resp.body()
+                        .concat2()
+                        .and_then(move |body| Ok((status, body)))
+                        .map_err(Error::from)
+                }).and_then(|(status, body)| {
+                    if status.is_success() {
+                        Ok(body)
+                    } else {
+                        Err(Error::from((status, &*body)))
+                    }
+                }).and_then(|_| futures::future::ok(())),
+        )
+    }
+
     fn update_module(
         &self,
         api_version: &str,
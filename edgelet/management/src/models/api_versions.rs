@@ -0,0 +1,37 @@
+/*
+ * IoT Edge Management API
+ *
+ * No description provided (generated by Swagger Codegen https://github.com/swagger-api/swagger-codegen)
+ *
+ * OpenAPI spec version: 2018-06-28
+ *
+ * Generated by: https://github.com/swagger-api/swagger-codegen.git
+ */
+
+#[allow(unused_imports)]
+use serde_json::Value;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ApiVersions {
+    #[serde(rename = "versions")]
+    versions: Vec<String>,
+}
+
+impl ApiVersions {
+    pub fn new(versions: Vec<String>) -> ApiVersions {
+        ApiVersions { versions }
+    }
+
+    pub fn set_versions(&mut self, versions: Vec<String>) {
+        self.versions = versions;
+    }
+
+    pub fn with_versions(mut self, versions: Vec<String>) -> ApiVersions {
+        self.versions = versions;
+        self
+    }
+
+    pub fn versions(&self) -> &Vec<String> {
+        &self.versions
+    }
+}
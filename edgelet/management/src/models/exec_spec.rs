@@ -0,0 +1,59 @@
+/*
+ * IoT Edge Management API
+ *
+ * No description provided (generated by Swagger Codegen https://github.com/swagger-api/swagger-codegen)
+ *
+ * OpenAPI spec version: 2018-06-28
+ *
+ * Generated by: https://github.com/swagger-api/swagger-codegen.git
+ */
+
+#[allow(unused_imports)]
+use serde_json::Value;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ExecSpec {
+    #[serde(rename = "cmd")]
+    cmd: Vec<String>,
+    #[serde(rename = "user", skip_serializing_if = "Option::is_none")]
+    user: Option<String>,
+}
+
+impl ExecSpec {
+    pub fn new(cmd: Vec<String>) -> ExecSpec {
+        ExecSpec {
+            cmd,
+            user: None,
+        }
+    }
+
+    pub fn set_cmd(&mut self, cmd: Vec<String>) {
+        self.cmd = cmd;
+    }
+
+    pub fn with_cmd(mut self, cmd: Vec<String>) -> ExecSpec {
+        self.cmd = cmd;
+        self
+    }
+
+    pub fn cmd(&self) -> &Vec<String> {
+        &self.cmd
+    }
+
+    pub fn set_user(&mut self, user: String) {
+        self.user = Some(user);
+    }
+
+    pub fn with_user(mut self, user: String) -> ExecSpec {
+        self.user = Some(user);
+        self
+    }
+
+    pub fn user(&self) -> Option<&String> {
+        self.user.as_ref()
+    }
+
+    pub fn reset_user(&mut self) {
+        self.user = None;
+    }
+}
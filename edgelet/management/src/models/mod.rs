@@ -1,9 +1,13 @@
+mod api_versions;
+pub use self::api_versions::ApiVersions;
 mod config;
 pub use self::config::Config;
 mod env_var;
 pub use self::env_var::EnvVar;
 mod error_response;
 pub use self::error_response::ErrorResponse;
+mod exec_spec;
+pub use self::exec_spec::ExecSpec;
 mod exit_status;
 pub use self::exit_status::ExitStatus;
 mod identity;
@@ -18,14 +22,28 @@ mod module_details;
 pub use self::module_details::ModuleDetails;
 mod module_list;
 pub use self::module_list::ModuleList;
+mod module_event;
+pub use self::module_event::ModuleEvent;
 mod module_spec;
 pub use self::module_spec::ModuleSpec;
+mod module_stats;
+pub use self::module_stats::ModuleStats;
+mod prune_result;
+pub use self::prune_result::PruneResult;
 mod runtime_status;
 pub use self::runtime_status::RuntimeStatus;
 mod status;
 pub use self::status::Status;
 mod system_info;
 pub use self::system_info::SystemInfo;
+mod volume;
+pub use self::volume::Volume;
+mod volume_list;
+pub use self::volume_list::VolumeList;
+mod volume_spec;
+pub use self::volume_spec::VolumeSpec;
+mod watchdog_status;
+pub use self::watchdog_status::WatchdogStatus;
 
 // TODO(farcaller): sort out files
 pub struct File;
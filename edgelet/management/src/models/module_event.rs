@@ -0,0 +1,100 @@
+/*
+ * IoT Edge Management API
+ *
+ * No description provided (generated by Swagger Codegen https://github.com/swagger-api/swagger-codegen)
+ *
+ * OpenAPI spec version: 2018-06-28
+ *
+ * Generated by: https://github.com/swagger-api/swagger-codegen.git
+ */
+
+#[allow(unused_imports)]
+use serde_json::Value;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ModuleEvent {
+    /// The name of the module.
+    #[serde(rename = "name")]
+    name: String,
+    /// The lifecycle action that occurred (create, start, die, oom, health_status: ...).
+    #[serde(rename = "action")]
+    action: String,
+    #[serde(rename = "time", skip_serializing_if = "Option::is_none")]
+    time: Option<String>,
+    #[serde(
+        rename = "exitCode",
+        skip_serializing_if = "Option::is_none"
+    )]
+    exit_code: Option<i64>,
+}
+
+impl ModuleEvent {
+    pub fn new(name: String, action: String) -> ModuleEvent {
+        ModuleEvent {
+            name,
+            action,
+            time: None,
+            exit_code: None,
+        }
+    }
+
+    pub fn set_name(&mut self, name: String) {
+        self.name = name;
+    }
+
+    pub fn with_name(mut self, name: String) -> ModuleEvent {
+        self.name = name;
+        self
+    }
+
+    pub fn name(&self) -> &String {
+        &self.name
+    }
+
+    pub fn set_action(&mut self, action: String) {
+        self.action = action;
+    }
+
+    pub fn with_action(mut self, action: String) -> ModuleEvent {
+        self.action = action;
+        self
+    }
+
+    pub fn action(&self) -> &String {
+        &self.action
+    }
+
+    pub fn set_time(&mut self, time: String) {
+        self.time = Some(time);
+    }
+
+    pub fn with_time(mut self, time: String) -> ModuleEvent {
+        self.time = Some(time);
+        self
+    }
+
+    pub fn time(&self) -> Option<&String> {
+        self.time.as_ref()
+    }
+
+    pub fn reset_time(&mut self) {
+        self.time = None;
+    }
+
+    pub fn set_exit_code(&mut self, exit_code: i64) {
+        self.exit_code = Some(exit_code);
+    }
+
+    pub fn with_exit_code(mut self, exit_code: i64) -> ModuleEvent {
+        self.exit_code = Some(exit_code);
+        self
+    }
+
+    pub fn exit_code(&self) -> Option<&i64> {
+        self.exit_code.as_ref()
+    }
+
+    pub fn reset_exit_code(&mut self) {
+        self.exit_code = None;
+    }
+}
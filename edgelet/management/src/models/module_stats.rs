@@ -0,0 +1,183 @@
+/*
+ * IoT Edge Management API
+ *
+ * No description provided (generated by Swagger Codegen https://github.com/swagger-api/swagger-codegen)
+ *
+ * OpenAPI spec version: 2018-06-28
+ *
+ * Generated by: https://github.com/swagger-api/swagger-codegen.git
+ */
+
+#[allow(unused_imports)]
+use serde_json::Value;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ModuleStats {
+    #[serde(
+        rename = "readTime",
+        skip_serializing_if = "Option::is_none"
+    )]
+    read_time: Option<String>,
+    #[serde(rename = "cpuPercent")]
+    cpu_percent: f64,
+    #[serde(rename = "cpuTotalUsage")]
+    cpu_total_usage: i64,
+    #[serde(rename = "memoryUsage")]
+    memory_usage: i64,
+    #[serde(rename = "memoryLimit")]
+    memory_limit: i64,
+    #[serde(rename = "networkRxBytes")]
+    network_rx_bytes: i64,
+    #[serde(rename = "networkTxBytes")]
+    network_tx_bytes: i64,
+    #[serde(rename = "blockReadBytes")]
+    block_read_bytes: i64,
+    #[serde(rename = "blockWriteBytes")]
+    block_write_bytes: i64,
+}
+
+impl ModuleStats {
+    pub fn new(
+        cpu_percent: f64,
+        cpu_total_usage: i64,
+        memory_usage: i64,
+        memory_limit: i64,
+        network_rx_bytes: i64,
+        network_tx_bytes: i64,
+        block_read_bytes: i64,
+        block_write_bytes: i64,
+    ) -> ModuleStats {
+        ModuleStats {
+            read_time: None,
+            cpu_percent,
+            cpu_total_usage,
+            memory_usage,
+            memory_limit,
+            network_rx_bytes,
+            network_tx_bytes,
+            block_read_bytes,
+            block_write_bytes,
+        }
+    }
+
+    pub fn set_read_time(&mut self, read_time: String) {
+        self.read_time = Some(read_time);
+    }
+
+    pub fn with_read_time(mut self, read_time: String) -> ModuleStats {
+        self.read_time = Some(read_time);
+        self
+    }
+
+    pub fn read_time(&self) -> Option<&String> {
+        self.read_time.as_ref()
+    }
+
+    pub fn reset_read_time(&mut self) {
+        self.read_time = None;
+    }
+
+    pub fn set_cpu_percent(&mut self, cpu_percent: f64) {
+        self.cpu_percent = cpu_percent;
+    }
+
+    pub fn with_cpu_percent(mut self, cpu_percent: f64) -> ModuleStats {
+        self.cpu_percent = cpu_percent;
+        self
+    }
+
+    pub fn cpu_percent(&self) -> &f64 {
+        &self.cpu_percent
+    }
+
+    pub fn set_cpu_total_usage(&mut self, cpu_total_usage: i64) {
+        self.cpu_total_usage = cpu_total_usage;
+    }
+
+    pub fn with_cpu_total_usage(mut self, cpu_total_usage: i64) -> ModuleStats {
+        self.cpu_total_usage = cpu_total_usage;
+        self
+    }
+
+    pub fn cpu_total_usage(&self) -> &i64 {
+        &self.cpu_total_usage
+    }
+
+    pub fn set_memory_usage(&mut self, memory_usage: i64) {
+        self.memory_usage = memory_usage;
+    }
+
+    pub fn with_memory_usage(mut self, memory_usage: i64) -> ModuleStats {
+        self.memory_usage = memory_usage;
+        self
+    }
+
+    pub fn memory_usage(&self) -> &i64 {
+        &self.memory_usage
+    }
+
+    pub fn set_memory_limit(&mut self, memory_limit: i64) {
+        self.memory_limit = memory_limit;
+    }
+
+    pub fn with_memory_limit(mut self, memory_limit: i64) -> ModuleStats {
+        self.memory_limit = memory_limit;
+        self
+    }
+
+    pub fn memory_limit(&self) -> &i64 {
+        &self.memory_limit
+    }
+
+    pub fn set_network_rx_bytes(&mut self, network_rx_bytes: i64) {
+        self.network_rx_bytes = network_rx_bytes;
+    }
+
+    pub fn with_network_rx_bytes(mut self, network_rx_bytes: i64) -> ModuleStats {
+        self.network_rx_bytes = network_rx_bytes;
+        self
+    }
+
+    pub fn network_rx_bytes(&self) -> &i64 {
+        &self.network_rx_bytes
+    }
+
+    pub fn set_network_tx_bytes(&mut self, network_tx_bytes: i64) {
+        self.network_tx_bytes = network_tx_bytes;
+    }
+
+    pub fn with_network_tx_bytes(mut self, network_tx_bytes: i64) -> ModuleStats {
+        self.network_tx_bytes = network_tx_bytes;
+        self
+    }
+
+    pub fn network_tx_bytes(&self) -> &i64 {
+        &self.network_tx_bytes
+    }
+
+    pub fn set_block_read_bytes(&mut self, block_read_bytes: i64) {
+        self.block_read_bytes = block_read_bytes;
+    }
+
+    pub fn with_block_read_bytes(mut self, block_read_bytes: i64) -> ModuleStats {
+        self.block_read_bytes = block_read_bytes;
+        self
+    }
+
+    pub fn block_read_bytes(&self) -> &i64 {
+        &self.block_read_bytes
+    }
+
+    pub fn set_block_write_bytes(&mut self, block_write_bytes: i64) {
+        self.block_write_bytes = block_write_bytes;
+    }
+
+    pub fn with_block_write_bytes(mut self, block_write_bytes: i64) -> ModuleStats {
+        self.block_write_bytes = block_write_bytes;
+        self
+    }
+
+    pub fn block_write_bytes(&self) -> &i64 {
+        &self.block_write_bytes
+    }
+}
@@ -0,0 +1,55 @@
+/*
+ * IoT Edge Management API
+ *
+ * No description provided (generated by Swagger Codegen https://github.com/swagger-api/swagger-codegen)
+ *
+ * OpenAPI spec version: 2018-06-28
+ *
+ * Generated by: https://github.com/swagger-api/swagger-codegen.git
+ */
+
+#[allow(unused_imports)]
+use serde_json::Value;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PruneResult {
+    #[serde(rename = "imagesDeleted")]
+    images_deleted: Vec<String>,
+    #[serde(rename = "spaceReclaimed")]
+    space_reclaimed: i64,
+}
+
+impl PruneResult {
+    pub fn new(images_deleted: Vec<String>, space_reclaimed: i64) -> PruneResult {
+        PruneResult {
+            images_deleted,
+            space_reclaimed,
+        }
+    }
+
+    pub fn set_images_deleted(&mut self, images_deleted: Vec<String>) {
+        self.images_deleted = images_deleted;
+    }
+
+    pub fn with_images_deleted(mut self, images_deleted: Vec<String>) -> PruneResult {
+        self.images_deleted = images_deleted;
+        self
+    }
+
+    pub fn images_deleted(&self) -> &Vec<String> {
+        &self.images_deleted
+    }
+
+    pub fn set_space_reclaimed(&mut self, space_reclaimed: i64) {
+        self.space_reclaimed = space_reclaimed;
+    }
+
+    pub fn with_space_reclaimed(mut self, space_reclaimed: i64) -> PruneResult {
+        self.space_reclaimed = space_reclaimed;
+        self
+    }
+
+    pub fn space_reclaimed(&self) -> &i64 {
+        &self.space_reclaimed
+    }
+}
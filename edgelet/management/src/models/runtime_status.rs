@@ -20,6 +20,18 @@ pub struct RuntimeStatus {
         skip_serializing_if = "Option::is_none"
     )]
     description: Option<String>,
+    #[serde(rename = "health", skip_serializing_if = "Option::is_none")]
+    health: Option<String>,
+    #[serde(
+        rename = "restartCount",
+        skip_serializing_if = "Option::is_none"
+    )]
+    restart_count: Option<i32>,
+    #[serde(
+        rename = "oomKilled",
+        skip_serializing_if = "Option::is_none"
+    )]
+    oom_killed: Option<bool>,
 }
 
 impl RuntimeStatus {
@@ -27,6 +39,9 @@ impl RuntimeStatus {
         RuntimeStatus {
             status,
             description: None,
+            health: None,
+            restart_count: None,
+            oom_killed: None,
         }
     }
 
@@ -59,4 +74,55 @@ impl RuntimeStatus {
     pub fn reset_description(&mut self) {
         self.description = None;
     }
+
+    pub fn set_health(&mut self, health: String) {
+        self.health = Some(health);
+    }
+
+    pub fn with_health(mut self, health: String) -> RuntimeStatus {
+        self.health = Some(health);
+        self
+    }
+
+    pub fn health(&self) -> Option<&String> {
+        self.health.as_ref()
+    }
+
+    pub fn reset_health(&mut self) {
+        self.health = None;
+    }
+
+    pub fn set_restart_count(&mut self, restart_count: i32) {
+        self.restart_count = Some(restart_count);
+    }
+
+    pub fn with_restart_count(mut self, restart_count: i32) -> RuntimeStatus {
+        self.restart_count = Some(restart_count);
+        self
+    }
+
+    pub fn restart_count(&self) -> Option<&i32> {
+        self.restart_count.as_ref()
+    }
+
+    pub fn reset_restart_count(&mut self) {
+        self.restart_count = None;
+    }
+
+    pub fn set_oom_killed(&mut self, oom_killed: bool) {
+        self.oom_killed = Some(oom_killed);
+    }
+
+    pub fn with_oom_killed(mut self, oom_killed: bool) -> RuntimeStatus {
+        self.oom_killed = Some(oom_killed);
+        self
+    }
+
+    pub fn oom_killed(&self) -> Option<&bool> {
+        self.oom_killed.as_ref()
+    }
+
+    pub fn reset_oom_killed(&mut self) {
+        self.oom_killed = None;
+    }
 }
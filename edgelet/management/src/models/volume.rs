@@ -0,0 +1,113 @@
+/*
+ * IoT Edge Management API
+ *
+ * No description provided (generated by Swagger Codegen https://github.com/swagger-api/swagger-codegen)
+ *
+ * OpenAPI spec version: 2018-06-28
+ *
+ * Generated by: https://github.com/swagger-api/swagger-codegen.git
+ */
+
+#[allow(unused_imports)]
+use serde_json::Value;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Volume {
+    #[serde(rename = "name")]
+    name: String,
+    /// The module the volume belongs to.
+    #[serde(rename = "module", skip_serializing_if = "Option::is_none")]
+    module: Option<String>,
+    #[serde(rename = "driver")]
+    driver: String,
+    /// Where the data of the volume is on the host.
+    #[serde(rename = "mountpoint")]
+    mountpoint: String,
+    #[serde(rename = "created", skip_serializing_if = "Option::is_none")]
+    created: Option<String>,
+}
+
+impl Volume {
+    pub fn new(name: String, driver: String, mountpoint: String) -> Volume {
+        Volume {
+            name,
+            module: None,
+            driver,
+            mountpoint,
+            created: None,
+        }
+    }
+
+    pub fn set_name(&mut self, name: String) {
+        self.name = name;
+    }
+
+    pub fn with_name(mut self, name: String) -> Volume {
+        self.name = name;
+        self
+    }
+
+    pub fn name(&self) -> &String {
+        &self.name
+    }
+
+    pub fn set_module(&mut self, module: String) {
+        self.module = Some(module);
+    }
+
+    pub fn with_module(mut self, module: String) -> Volume {
+        self.module = Some(module);
+        self
+    }
+
+    pub fn module(&self) -> Option<&String> {
+        self.module.as_ref()
+    }
+
+    pub fn reset_module(&mut self) {
+        self.module = None;
+    }
+
+    pub fn set_driver(&mut self, driver: String) {
+        self.driver = driver;
+    }
+
+    pub fn with_driver(mut self, driver: String) -> Volume {
+        self.driver = driver;
+        self
+    }
+
+    pub fn driver(&self) -> &String {
+        &self.driver
+    }
+
+    pub fn set_mountpoint(&mut self, mountpoint: String) {
+        self.mountpoint = mountpoint;
+    }
+
+    pub fn with_mountpoint(mut self, mountpoint: String) -> Volume {
+        self.mountpoint = mountpoint;
+        self
+    }
+
+    pub fn mountpoint(&self) -> &String {
+        &self.mountpoint
+    }
+
+    pub fn set_created(&mut self, created: String) {
+        self.created = Some(created);
+    }
+
+    pub fn with_created(mut self, created: String) -> Volume {
+        self.created = Some(created);
+        self
+    }
+
+    pub fn created(&self) -> Option<&String> {
+        self.created.as_ref()
+    }
+
+    pub fn reset_created(&mut self) {
+        self.created = None;
+    }
+}
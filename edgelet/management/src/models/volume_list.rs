@@ -0,0 +1,37 @@
+/*
+ * IoT Edge Management API
+ *
+ * No description provided (generated by Swagger Codegen https://github.com/swagger-api/swagger-codegen)
+ *
+ * OpenAPI spec version: 2018-06-28
+ *
+ * Generated by: https://github.com/swagger-api/swagger-codegen.git
+ */
+
+#[allow(unused_imports)]
+use serde_json::Value;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct VolumeList {
+    #[serde(rename = "volumes")]
+    volumes: Vec<::models::Volume>,
+}
+
+impl VolumeList {
+    pub fn new(volumes: Vec<::models::Volume>) -> VolumeList {
+        VolumeList { volumes }
+    }
+
+    pub fn set_volumes(&mut self, volumes: Vec<::models::Volume>) {
+        self.volumes = volumes;
+    }
+
+    pub fn with_volumes(mut self, volumes: Vec<::models::Volume>) -> VolumeList {
+        self.volumes = volumes;
+        self
+    }
+
+    pub fn volumes(&self) -> &Vec<::models::Volume> {
+        &self.volumes
+    }
+}
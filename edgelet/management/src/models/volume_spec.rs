@@ -0,0 +1,56 @@
+/*
+ * IoT Edge Management API
+ *
+ * No description provided (generated by Swagger Codegen https://github.com/swagger-api/swagger-codegen)
+ *
+ * OpenAPI spec version: 2018-06-28
+ *
+ * Generated by: https://github.com/swagger-api/swagger-codegen.git
+ */
+
+#[allow(unused_imports)]
+use serde_json::Value;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct VolumeSpec {
+    #[serde(rename = "name")]
+    name: String,
+    #[serde(rename = "module", skip_serializing_if = "Option::is_none")]
+    module: Option<String>,
+}
+
+impl VolumeSpec {
+    pub fn new(name: String) -> VolumeSpec {
+        VolumeSpec { name, module: None }
+    }
+
+    pub fn set_name(&mut self, name: String) {
+        self.name = name;
+    }
+
+    pub fn with_name(mut self, name: String) -> VolumeSpec {
+        self.name = name;
+        self
+    }
+
+    pub fn name(&self) -> &String {
+        &self.name
+    }
+
+    pub fn set_module(&mut self, module: String) {
+        self.module = Some(module);
+    }
+
+    pub fn with_module(mut self, module: String) -> VolumeSpec {
+        self.module = Some(module);
+        self
+    }
+
+    pub fn module(&self) -> Option<&String> {
+        self.module.as_ref()
+    }
+
+    pub fn reset_module(&mut self) {
+        self.module = None;
+    }
+}
@@ -0,0 +1,117 @@
+/*
+ * IoT Edge Management API
+ *
+ * No description provided (generated by Swagger Codegen https://github.com/swagger-api/swagger-codegen)
+ *
+ * OpenAPI spec version: 2018-06-28
+ *
+ * Generated by: https://github.com/swagger-api/swagger-codegen.git
+ */
+
+#[allow(unused_imports)]
+use serde_json::Value;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct WatchdogStatus {
+    #[serde(rename = "restartCount")]
+    restart_count: i64,
+    #[serde(rename = "consecutiveFailures")]
+    consecutive_failures: i32,
+    #[serde(rename = "crashLoop")]
+    crash_loop: bool,
+    #[serde(
+        rename = "lastRestart",
+        skip_serializing_if = "Option::is_none"
+    )]
+    last_restart: Option<String>,
+    #[serde(
+        rename = "nextAttempt",
+        skip_serializing_if = "Option::is_none"
+    )]
+    next_attempt: Option<String>,
+}
+
+impl WatchdogStatus {
+    pub fn new(restart_count: i64, consecutive_failures: i32, crash_loop: bool) -> WatchdogStatus {
+        WatchdogStatus {
+            restart_count,
+            consecutive_failures,
+            crash_loop,
+            last_restart: None,
+            next_attempt: None,
+        }
+    }
+
+    pub fn set_restart_count(&mut self, restart_count: i64) {
+        self.restart_count = restart_count;
+    }
+
+    pub fn with_restart_count(mut self, restart_count: i64) -> WatchdogStatus {
+        self.restart_count = restart_count;
+        self
+    }
+
+    pub fn restart_count(&self) -> &i64 {
+        &self.restart_count
+    }
+
+    pub fn set_consecutive_failures(&mut self, consecutive_failures: i32) {
+        self.consecutive_failures = consecutive_failures;
+    }
+
+    pub fn with_consecutive_failures(mut self, consecutive_failures: i32) -> WatchdogStatus {
+        self.consecutive_failures = consecutive_failures;
+        self
+    }
+
+    pub fn consecutive_failures(&self) -> &i32 {
+        &self.consecutive_failures
+    }
+
+    pub fn set_crash_loop(&mut self, crash_loop: bool) {
+        self.crash_loop = crash_loop;
+    }
+
+    pub fn with_crash_loop(mut self, crash_loop: bool) -> WatchdogStatus {
+        self.crash_loop = crash_loop;
+        self
+    }
+
+    pub fn crash_loop(&self) -> &bool {
+        &self.crash_loop
+    }
+
+    pub fn set_last_restart(&mut self, last_restart: String) {
+        self.last_restart = Some(last_restart);
+    }
+
+    pub fn with_last_restart(mut self, last_restart: String) -> WatchdogStatus {
+        self.last_restart = Some(last_restart);
+        self
+    }
+
+    pub fn last_restart(&self) -> Option<&String> {
+        self.last_restart.as_ref()
+    }
+
+    pub fn reset_last_restart(&mut self) {
+        self.last_restart = None;
+    }
+
+    pub fn set_next_attempt(&mut self, next_attempt: String) {
+        self.next_attempt = Some(next_attempt);
+    }
+
+    pub fn with_next_attempt(mut self, next_attempt: String) -> WatchdogStatus {
+        self.next_attempt = Some(next_attempt);
+        self
+    }
+
+    pub fn next_attempt(&self) -> Option<&String> {
+        self.next_attempt.as_ref()
+    }
+
+    pub fn reset_next_attempt(&mut self) {
+        self.next_attempt = None;
+    }
+}